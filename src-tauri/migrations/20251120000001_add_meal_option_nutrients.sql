@@ -0,0 +1,22 @@
+-- Structured nutrient profile per meal option
+-- Values are per serving; daily/weekly totals multiply them by meal_entries.servings
+-- One row per option (1:1), removed together with the option
+
+CREATE TABLE IF NOT EXISTS meal_option_nutrients (
+    meal_option_id INTEGER PRIMARY KEY,
+    kcal REAL NOT NULL DEFAULT 0 CHECK(kcal >= 0),
+    protein_g REAL NOT NULL DEFAULT 0 CHECK(protein_g >= 0),
+    carbs_g REAL NOT NULL DEFAULT 0 CHECK(carbs_g >= 0),
+    fat_g REAL NOT NULL DEFAULT 0 CHECK(fat_g >= 0),
+    fiber_g REAL NOT NULL DEFAULT 0 CHECK(fiber_g >= 0),
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (meal_option_id) REFERENCES meal_options(id) ON DELETE CASCADE
+);
+
+CREATE TRIGGER IF NOT EXISTS update_meal_option_nutrients_timestamp
+AFTER UPDATE ON meal_option_nutrients
+FOR EACH ROW
+BEGIN
+    UPDATE meal_option_nutrients SET updated_at = CURRENT_TIMESTAMP WHERE meal_option_id = OLD.meal_option_id;
+END;
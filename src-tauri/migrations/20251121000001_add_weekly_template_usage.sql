@@ -0,0 +1,18 @@
+-- Template-wide weekly usage
+-- weekly_limit is declared on meal_templates, so usage must be counted across
+-- all options of a template, not per option
+
+-- Weekly usage per template (sum over all of its options)
+-- Same Monday-based week key as weekly_meal_usage and weekly_tag_usage
+CREATE VIEW IF NOT EXISTS weekly_template_usage AS
+SELECT
+    mt.id as template_id,
+    mt.name as template_name,
+    mt.weekly_limit as weekly_limit,
+    strftime('%Y-', date(me.date, '-1 day')) || printf('%02d', CAST(strftime('%W', date(me.date, '-1 day')) AS INTEGER)) as week,
+    COUNT(*) as usage_count
+FROM meal_entries me
+JOIN meal_options mo ON me.meal_option_id = mo.id
+JOIN meal_templates mt ON mo.template_id = mt.id
+WHERE me.completed = 1
+GROUP BY mt.id, mt.name, mt.weekly_limit, week;
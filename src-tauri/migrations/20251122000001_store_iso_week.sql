@@ -0,0 +1,95 @@
+-- Store the ISO 8601 week of every meal entry
+-- Week keys used to be built in SQL with strftime('%Y-%W'), which disagrees with
+-- ISO weeks around New Year: 2024-12-30 is ISO week 2025-01 but '%Y-%W' says 2024-53,
+-- so weekly limits stopped being enforced in the first and last week of a year.
+-- iso_week ("YYYY-WW") is now written by the application with the same function
+-- ValidationService uses for its lookups, and every weekly view groups by it.
+
+-- Step 0: Drop views that depend on meal_entries
+DROP VIEW IF EXISTS weekly_meal_usage;
+DROP VIEW IF EXISTS weekly_tag_usage;
+DROP VIEW IF EXISTS weekly_template_usage;
+
+-- Step 1: Create new meal_entries table with the week column
+CREATE TABLE meal_entries_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    meal_option_id INTEGER NOT NULL,
+    date DATE NOT NULL,
+    iso_week TEXT NOT NULL CHECK(length(iso_week) = 7), -- "YYYY-WW", ISO week-year and week
+    slot_type TEXT NOT NULL CHECK(slot_type IN ('breakfast', 'morning_snack', 'lunch', 'afternoon_snack', 'dinner')),
+    location TEXT NOT NULL CHECK(location IN ('home', 'office', 'restaurant', 'any')),
+    servings REAL NOT NULL DEFAULT 1.0 CHECK(servings > 0),
+    notes TEXT,
+    completed BOOLEAN NOT NULL DEFAULT 0,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (meal_option_id) REFERENCES meal_options(id) ON DELETE RESTRICT
+);
+
+-- Step 2: Copy data, backfilling the week
+-- An ISO week belongs to the year of its Thursday, and its number is the
+-- Thursday's day of year divided by 7 (rounded up)
+INSERT INTO meal_entries_new (id, meal_option_id, date, iso_week, slot_type, location, servings, notes, completed, created_at, updated_at)
+SELECT
+    id, meal_option_id, date,
+    strftime('%Y', thursday) || '-' || printf('%02d', (CAST(strftime('%j', thursday) AS INTEGER) - 1) / 7 + 1),
+    slot_type, location, servings, notes, completed, created_at, updated_at
+FROM (
+    SELECT *,
+        date(date, '-' || ((CAST(strftime('%w', date) AS INTEGER) + 6) % 7) || ' days', '+3 days') AS thursday
+    FROM meal_entries
+);
+
+-- Step 3: Drop old table
+DROP TABLE meal_entries;
+
+-- Step 4: Rename new table
+ALTER TABLE meal_entries_new RENAME TO meal_entries;
+
+-- Step 5: Recreate indexes and the timestamp trigger
+CREATE INDEX IF NOT EXISTS idx_meal_entries_date ON meal_entries(date);
+CREATE INDEX IF NOT EXISTS idx_meal_entries_option ON meal_entries(meal_option_id);
+CREATE INDEX IF NOT EXISTS idx_meal_entries_date_slot ON meal_entries(date, slot_type);
+CREATE INDEX IF NOT EXISTS idx_meal_entries_iso_week ON meal_entries(iso_week);
+
+CREATE TRIGGER IF NOT EXISTS update_meal_entries_timestamp
+AFTER UPDATE ON meal_entries
+FOR EACH ROW
+BEGIN
+    UPDATE meal_entries SET updated_at = CURRENT_TIMESTAMP WHERE id = OLD.id;
+END;
+
+-- Step 6: Recreate views on the stored week
+CREATE VIEW IF NOT EXISTS weekly_meal_usage AS
+SELECT
+    meal_option_id,
+    iso_week as week,
+    COUNT(*) as usage_count
+FROM meal_entries
+WHERE completed = 1
+GROUP BY meal_option_id, iso_week;
+
+CREATE VIEW IF NOT EXISTS weekly_tag_usage AS
+SELECT
+    t.id as tag_id,
+    t.name as tag_name,
+    me.iso_week as week,
+    COUNT(*) as usage_count
+FROM meal_entries me
+JOIN meal_option_tags mot ON me.meal_option_id = mot.meal_option_id
+JOIN tags t ON mot.tag_id = t.id
+WHERE me.completed = 1
+GROUP BY t.id, t.name, me.iso_week;
+
+CREATE VIEW IF NOT EXISTS weekly_template_usage AS
+SELECT
+    mt.id as template_id,
+    mt.name as template_name,
+    mt.weekly_limit as weekly_limit,
+    me.iso_week as week,
+    COUNT(*) as usage_count
+FROM meal_entries me
+JOIN meal_options mo ON me.meal_option_id = mo.id
+JOIN meal_templates mt ON mo.template_id = mt.id
+WHERE me.completed = 1
+GROUP BY mt.id, mt.name, mt.weekly_limit, me.iso_week;
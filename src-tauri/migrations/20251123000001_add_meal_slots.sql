@@ -0,0 +1,141 @@
+-- User-configurable meal slots
+-- Slots used to be five hard-coded values checked on meal_entries.slot_type.
+-- They now live in meal_slots; the five former values are seeded as defaults,
+-- entries reference a slot by name and templates' compatible_slots are checked
+-- against the table by triggers.
+
+-- Step 0: Drop views that depend on meal_entries
+DROP VIEW IF EXISTS weekly_meal_usage;
+DROP VIEW IF EXISTS weekly_tag_usage;
+DROP VIEW IF EXISTS weekly_template_usage;
+
+-- Step 1: Slots table with the former fixed slots as defaults
+CREATE TABLE IF NOT EXISTS meal_slots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,          -- Internal key stored on entries/templates: "breakfast", "pre_workout"
+    display_name TEXT NOT NULL,         -- User-facing: "Breakfast", "Pre-workout"
+    sort_order INTEGER NOT NULL DEFAULT 0, -- Position within the day, ascending
+    active BOOLEAN NOT NULL DEFAULT 1,  -- Inactive slots keep their history but take no new entries
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO meal_slots (name, display_name, sort_order) VALUES
+    ('breakfast', 'Breakfast', 10),
+    ('morning_snack', 'Morning Snack', 20),
+    ('lunch', 'Lunch', 30),
+    ('afternoon_snack', 'Afternoon Snack', 40),
+    ('dinner', 'Dinner', 50);
+
+CREATE TRIGGER IF NOT EXISTS update_meal_slots_timestamp
+AFTER UPDATE ON meal_slots
+FOR EACH ROW
+BEGIN
+    UPDATE meal_slots SET updated_at = CURRENT_TIMESTAMP WHERE id = OLD.id;
+END;
+
+-- Step 2: Recreate meal_entries with slot_type referencing meal_slots
+CREATE TABLE meal_entries_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    meal_option_id INTEGER NOT NULL,
+    date DATE NOT NULL,
+    iso_week TEXT NOT NULL CHECK(length(iso_week) = 7), -- "YYYY-WW", ISO week-year and week
+    slot_type TEXT NOT NULL,
+    location TEXT NOT NULL CHECK(location IN ('home', 'office', 'restaurant', 'any')),
+    servings REAL NOT NULL DEFAULT 1.0 CHECK(servings > 0),
+    notes TEXT,
+    completed BOOLEAN NOT NULL DEFAULT 0,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (meal_option_id) REFERENCES meal_options(id) ON DELETE RESTRICT,
+    FOREIGN KEY (slot_type) REFERENCES meal_slots(name) ON DELETE RESTRICT
+);
+
+INSERT INTO meal_entries_new (id, meal_option_id, date, iso_week, slot_type, location, servings, notes, completed, created_at, updated_at)
+SELECT id, meal_option_id, date, iso_week, slot_type, location, servings, notes, completed, created_at, updated_at
+FROM meal_entries;
+
+DROP TABLE meal_entries;
+
+ALTER TABLE meal_entries_new RENAME TO meal_entries;
+
+CREATE INDEX IF NOT EXISTS idx_meal_entries_date ON meal_entries(date);
+CREATE INDEX IF NOT EXISTS idx_meal_entries_option ON meal_entries(meal_option_id);
+CREATE INDEX IF NOT EXISTS idx_meal_entries_date_slot ON meal_entries(date, slot_type);
+CREATE INDEX IF NOT EXISTS idx_meal_entries_iso_week ON meal_entries(iso_week);
+
+CREATE TRIGGER IF NOT EXISTS update_meal_entries_timestamp
+AFTER UPDATE ON meal_entries
+FOR EACH ROW
+BEGIN
+    UPDATE meal_entries SET updated_at = CURRENT_TIMESTAMP WHERE id = OLD.id;
+END;
+
+-- Step 3: compatible_slots is a JSON array, so references are checked by triggers
+CREATE TRIGGER IF NOT EXISTS check_meal_templates_slots_insert
+BEFORE INSERT ON meal_templates
+FOR EACH ROW
+WHEN EXISTS (
+    SELECT 1 FROM json_each(NEW.compatible_slots)
+    WHERE value NOT IN (SELECT name FROM meal_slots)
+)
+BEGIN
+    SELECT RAISE(ABORT, 'Unknown meal slot in compatible_slots');
+END;
+
+CREATE TRIGGER IF NOT EXISTS check_meal_templates_slots_update
+BEFORE UPDATE OF compatible_slots ON meal_templates
+FOR EACH ROW
+WHEN EXISTS (
+    SELECT 1 FROM json_each(NEW.compatible_slots)
+    WHERE value NOT IN (SELECT name FROM meal_slots)
+)
+BEGIN
+    SELECT RAISE(ABORT, 'Unknown meal slot in compatible_slots');
+END;
+
+CREATE TRIGGER IF NOT EXISTS check_meal_slots_template_references
+BEFORE DELETE ON meal_slots
+FOR EACH ROW
+WHEN EXISTS (
+    SELECT 1 FROM meal_templates mt, json_each(mt.compatible_slots) s
+    WHERE s.value = OLD.name
+)
+BEGIN
+    SELECT RAISE(ABORT, 'Meal slot is used by templates');
+END;
+
+-- Step 4: Recreate views (unchanged)
+CREATE VIEW IF NOT EXISTS weekly_meal_usage AS
+SELECT
+    meal_option_id,
+    iso_week as week,
+    COUNT(*) as usage_count
+FROM meal_entries
+WHERE completed = 1
+GROUP BY meal_option_id, iso_week;
+
+CREATE VIEW IF NOT EXISTS weekly_tag_usage AS
+SELECT
+    t.id as tag_id,
+    t.name as tag_name,
+    me.iso_week as week,
+    COUNT(*) as usage_count
+FROM meal_entries me
+JOIN meal_option_tags mot ON me.meal_option_id = mot.meal_option_id
+JOIN tags t ON mot.tag_id = t.id
+WHERE me.completed = 1
+GROUP BY t.id, t.name, me.iso_week;
+
+CREATE VIEW IF NOT EXISTS weekly_template_usage AS
+SELECT
+    mt.id as template_id,
+    mt.name as template_name,
+    mt.weekly_limit as weekly_limit,
+    me.iso_week as week,
+    COUNT(*) as usage_count
+FROM meal_entries me
+JOIN meal_options mo ON me.meal_option_id = mo.id
+JOIN meal_templates mt ON mo.template_id = mt.id
+WHERE me.completed = 1
+GROUP BY mt.id, mt.name, mt.weekly_limit, me.iso_week;
@@ -0,0 +1,29 @@
+-- Move template slot compatibility out of the compatible_slots JSON column
+-- One row per (template, slot), so slot filtering happens in SQL and a bad JSON
+-- value can no longer break template listings.
+
+-- Step 1: Join table
+CREATE TABLE IF NOT EXISTS meal_template_slots (
+    template_id INTEGER NOT NULL,
+    slot_type TEXT NOT NULL,
+    PRIMARY KEY (template_id, slot_type),
+    FOREIGN KEY (template_id) REFERENCES meal_templates(id) ON DELETE CASCADE,
+    FOREIGN KEY (slot_type) REFERENCES meal_slots(name) ON DELETE RESTRICT
+);
+
+CREATE INDEX IF NOT EXISTS idx_meal_template_slots_slot ON meal_template_slots(slot_type);
+
+-- Step 2: Copy compatibility from the JSON column
+-- Malformed values and unknown slots are dropped instead of failing the migration
+INSERT OR IGNORE INTO meal_template_slots (template_id, slot_type)
+SELECT mt.id, s.value
+FROM meal_templates mt, json_each(CASE WHEN json_valid(mt.compatible_slots) THEN mt.compatible_slots ELSE '[]' END) s
+WHERE s.value IN (SELECT name FROM meal_slots);
+
+-- Step 3: The triggers guarding the JSON column are replaced by the foreign keys
+DROP TRIGGER IF EXISTS check_meal_templates_slots_insert;
+DROP TRIGGER IF EXISTS check_meal_templates_slots_update;
+DROP TRIGGER IF EXISTS check_meal_slots_template_references;
+
+-- Step 4: Drop the JSON column
+ALTER TABLE meal_templates DROP COLUMN compatible_slots;
@@ -0,0 +1,75 @@
+-- Snapshot option/template data onto meal entries
+-- Entries used to resolve names, tags and location rules through the live
+-- option and template, so renaming an option or retagging it rewrote history.
+-- Each entry now keeps a copy taken when it is created and refreshed when it is
+-- marked as eaten; history, reports and usage read the copy.
+
+-- Step 0: Drop views that read option/template data through meal_entries
+DROP VIEW IF EXISTS weekly_tag_usage;
+DROP VIEW IF EXISTS weekly_template_usage;
+
+-- Step 1: Snapshot columns
+ALTER TABLE meal_entries ADD COLUMN option_name TEXT NOT NULL DEFAULT '';
+ALTER TABLE meal_entries ADD COLUMN template_id INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE meal_entries ADD COLUMN template_name TEXT NOT NULL DEFAULT '';
+ALTER TABLE meal_entries ADD COLUMN template_location_type TEXT NOT NULL DEFAULT 'any'
+    CHECK(template_location_type IN ('home', 'office', 'restaurant', 'any'));
+
+-- Tag set of the entry; tag_id is kept without a foreign key so deleting a tag
+-- does not erase it from past entries
+CREATE TABLE IF NOT EXISTS meal_entry_tags (
+    meal_entry_id INTEGER NOT NULL,
+    tag_id INTEGER,                      -- NULL when the tag did not exist in this database (imports)
+    tag_name TEXT NOT NULL,
+    PRIMARY KEY (meal_entry_id, tag_name),
+    FOREIGN KEY (meal_entry_id) REFERENCES meal_entries(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_meal_entry_tags_tag ON meal_entry_tags(tag_id);
+
+-- Step 2: Backfill existing entries from the current library (best available)
+UPDATE meal_entries SET
+    option_name = (SELECT mo.name FROM meal_options mo WHERE mo.id = meal_entries.meal_option_id),
+    template_id = (SELECT mo.template_id FROM meal_options mo WHERE mo.id = meal_entries.meal_option_id),
+    template_name = (
+        SELECT mt.name FROM meal_options mo
+        JOIN meal_templates mt ON mt.id = mo.template_id
+        WHERE mo.id = meal_entries.meal_option_id
+    ),
+    template_location_type = (
+        SELECT mt.location_type FROM meal_options mo
+        JOIN meal_templates mt ON mt.id = mo.template_id
+        WHERE mo.id = meal_entries.meal_option_id
+    );
+
+INSERT INTO meal_entry_tags (meal_entry_id, tag_id, tag_name)
+SELECT me.id, t.id, t.name
+FROM meal_entries me
+JOIN meal_option_tags mot ON mot.meal_option_id = me.meal_option_id
+JOIN tags t ON t.id = mot.tag_id;
+
+-- Step 3: Recreate views on the snapshot
+-- Template limits are current rules, so the limit still comes from the template
+CREATE VIEW IF NOT EXISTS weekly_tag_usage AS
+SELECT
+    t.id as tag_id,
+    t.name as tag_name,
+    me.iso_week as week,
+    COUNT(*) as usage_count
+FROM meal_entries me
+JOIN meal_entry_tags met ON met.meal_entry_id = me.id
+JOIN tags t ON met.tag_id = t.id
+WHERE me.completed = 1
+GROUP BY t.id, t.name, me.iso_week;
+
+CREATE VIEW IF NOT EXISTS weekly_template_usage AS
+SELECT
+    me.template_id as template_id,
+    COALESCE(mt.name, MAX(me.template_name)) as template_name,
+    mt.weekly_limit as weekly_limit,
+    me.iso_week as week,
+    COUNT(*) as usage_count
+FROM meal_entries me
+LEFT JOIN meal_templates mt ON mt.id = me.template_id
+WHERE me.completed = 1
+GROUP BY me.template_id, me.iso_week;
@@ -0,0 +1,8 @@
+-- Archive (soft-delete) for templates, options and tags
+-- Options that were ever eaten cannot be deleted (meal_entries RESTRICT), and
+-- deleting a template cascades into them. Archived rows are hidden from
+-- listings, search and suggestions but still resolve for past entries.
+
+ALTER TABLE meal_templates ADD COLUMN archived BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE meal_options ADD COLUMN archived BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE tags ADD COLUMN archived BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,53 @@
+-- Multi-option entries for composite meals
+-- Templates like "pane + marmellata e/o formaggio" combine several options,
+-- but an entry could only reference one. Templates now declare option groups
+-- (how many options to pick from each), options belong to a group, and an
+-- entry keeps every selected option with its own servings.
+
+-- Step 1: Combination rules of a template
+-- e.g. "pane" 1..1 (exactly one), "spalmabile" 0..1 (optional), "e/o" 1..2
+CREATE TABLE IF NOT EXISTS meal_template_option_groups (
+    template_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    min_choices INTEGER NOT NULL DEFAULT 1 CHECK(min_choices >= 0),
+    max_choices INTEGER CHECK(max_choices IS NULL OR (max_choices >= 1 AND max_choices >= min_choices)), -- NULL = no upper bound
+    sort_order INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (template_id, name),
+    FOREIGN KEY (template_id) REFERENCES meal_templates(id) ON DELETE CASCADE
+);
+
+-- Group of the option within its template (NULL = ungrouped)
+ALTER TABLE meal_options ADD COLUMN option_group TEXT;
+
+-- Step 2: Options selected in an entry
+-- Position 0 is the main option, mirrored in meal_entries.meal_option_id/servings
+CREATE TABLE IF NOT EXISTS meal_entry_options (
+    meal_entry_id INTEGER NOT NULL,
+    meal_option_id INTEGER NOT NULL,
+    position INTEGER NOT NULL DEFAULT 0,
+    servings REAL NOT NULL DEFAULT 1.0 CHECK(servings > 0),
+    option_name TEXT NOT NULL DEFAULT '', -- Snapshot, like meal_entries.option_name
+    PRIMARY KEY (meal_entry_id, meal_option_id),
+    FOREIGN KEY (meal_entry_id) REFERENCES meal_entries(id) ON DELETE CASCADE,
+    FOREIGN KEY (meal_option_id) REFERENCES meal_options(id) ON DELETE RESTRICT
+);
+
+CREATE INDEX IF NOT EXISTS idx_meal_entry_options_option ON meal_entry_options(meal_option_id);
+
+-- Step 3: Every existing entry has its single option as the main one
+INSERT INTO meal_entry_options (meal_entry_id, meal_option_id, position, servings, option_name)
+SELECT id, meal_option_id, 0, servings, option_name
+FROM meal_entries;
+
+-- Step 4: Option usage counts every selected option, not only the main one
+DROP VIEW IF EXISTS weekly_meal_usage;
+
+CREATE VIEW IF NOT EXISTS weekly_meal_usage AS
+SELECT
+    eo.meal_option_id as meal_option_id,
+    me.iso_week as week,
+    COUNT(*) as usage_count
+FROM meal_entry_options eo
+JOIN meal_entries me ON me.id = eo.meal_entry_id
+WHERE me.completed = 1
+GROUP BY eo.meal_option_id, me.iso_week;
@@ -0,0 +1,74 @@
+-- Planned vs. actual meals
+-- `completed` was a flag on the entry, so eating something other than what was
+-- planned overwrote the plan. Entries now have a status (planned, eaten or
+-- skipped) and keep the planned selection next to the options actually eaten.
+
+-- Step 1: The usage views read `completed`; drop them before the column goes
+DROP VIEW IF EXISTS weekly_meal_usage;
+DROP VIEW IF EXISTS weekly_tag_usage;
+DROP VIEW IF EXISTS weekly_template_usage;
+
+-- Step 2: Status replaces the completed flag
+ALTER TABLE meal_entries ADD COLUMN status TEXT NOT NULL DEFAULT 'planned'
+    CHECK(status IN ('planned', 'eaten', 'skipped'));
+
+UPDATE meal_entries SET status = 'eaten' WHERE completed = 1;
+
+ALTER TABLE meal_entries DROP COLUMN completed;
+
+CREATE INDEX IF NOT EXISTS idx_meal_entries_status_date ON meal_entries(status, date);
+
+-- Step 3: The selection an entry was planned with
+-- Recorded when the entry is created; meal_entry_options holds what was eaten
+CREATE TABLE IF NOT EXISTS meal_entry_planned_options (
+    meal_entry_id INTEGER NOT NULL,
+    meal_option_id INTEGER NOT NULL,
+    position INTEGER NOT NULL DEFAULT 0,
+    servings REAL NOT NULL DEFAULT 1.0 CHECK(servings > 0),
+    option_name TEXT NOT NULL DEFAULT '', -- Snapshot, like meal_entry_options.option_name
+    PRIMARY KEY (meal_entry_id, meal_option_id),
+    FOREIGN KEY (meal_entry_id) REFERENCES meal_entries(id) ON DELETE CASCADE,
+    FOREIGN KEY (meal_option_id) REFERENCES meal_options(id) ON DELETE RESTRICT
+);
+
+CREATE INDEX IF NOT EXISTS idx_meal_entry_planned_options_option ON meal_entry_planned_options(meal_option_id);
+
+-- Existing entries were planned with what they hold now
+INSERT INTO meal_entry_planned_options (meal_entry_id, meal_option_id, position, servings, option_name)
+SELECT meal_entry_id, meal_option_id, position, servings, option_name
+FROM meal_entry_options;
+
+-- Step 4: Recreate views on the status
+CREATE VIEW IF NOT EXISTS weekly_meal_usage AS
+SELECT
+    eo.meal_option_id as meal_option_id,
+    me.iso_week as week,
+    COUNT(*) as usage_count
+FROM meal_entry_options eo
+JOIN meal_entries me ON me.id = eo.meal_entry_id
+WHERE me.status = 'eaten'
+GROUP BY eo.meal_option_id, me.iso_week;
+
+CREATE VIEW IF NOT EXISTS weekly_tag_usage AS
+SELECT
+    t.id as tag_id,
+    t.name as tag_name,
+    me.iso_week as week,
+    COUNT(*) as usage_count
+FROM meal_entries me
+JOIN meal_entry_tags met ON met.meal_entry_id = me.id
+JOIN tags t ON met.tag_id = t.id
+WHERE me.status = 'eaten'
+GROUP BY t.id, t.name, me.iso_week;
+
+CREATE VIEW IF NOT EXISTS weekly_template_usage AS
+SELECT
+    me.template_id as template_id,
+    COALESCE(mt.name, MAX(me.template_name)) as template_name,
+    mt.weekly_limit as weekly_limit,
+    me.iso_week as week,
+    COUNT(*) as usage_count
+FROM meal_entries me
+LEFT JOIN meal_templates mt ON mt.id = me.template_id
+WHERE me.status = 'eaten'
+GROUP BY me.template_id, me.iso_week;
@@ -0,0 +1,129 @@
+-- Off-plan entries
+-- Every entry had to reference a meal option, so a restaurant dinner or an
+-- ad-hoc meal was either forced onto an unrelated option or not logged at all.
+-- An off-plan entry has no option and no template: option_name holds its
+-- free-text description, meal_entry_tags its estimated tags and
+-- meal_entry_nutrients its estimated nutrient profile.
+
+-- Step 0: Drop views that depend on meal_entries
+DROP VIEW IF EXISTS weekly_meal_usage;
+DROP VIEW IF EXISTS weekly_tag_usage;
+DROP VIEW IF EXISTS weekly_template_usage;
+
+-- Step 1: Keep the rows that reference entries
+-- Dropping meal_entries cascades to them; they are restored after the rename
+CREATE TEMP TABLE saved_entry_options AS SELECT * FROM meal_entry_options;
+CREATE TEMP TABLE saved_entry_planned_options AS SELECT * FROM meal_entry_planned_options;
+CREATE TEMP TABLE saved_entry_tags AS SELECT * FROM meal_entry_tags;
+
+-- Step 2: Create new meal_entries table with optional option and template
+CREATE TABLE meal_entries_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    meal_option_id INTEGER,              -- NULL for off-plan entries
+    date DATE NOT NULL,
+    iso_week TEXT NOT NULL CHECK(length(iso_week) = 7), -- "YYYY-WW", ISO week-year and week
+    slot_type TEXT NOT NULL,
+    location TEXT NOT NULL CHECK(location IN ('home', 'office', 'restaurant', 'any')),
+    servings REAL NOT NULL DEFAULT 1.0 CHECK(servings > 0),
+    notes TEXT,
+    status TEXT NOT NULL DEFAULT 'planned' CHECK(status IN ('planned', 'eaten', 'skipped')),
+    option_name TEXT NOT NULL DEFAULT '', -- Description of off-plan entries
+    template_id INTEGER,                 -- NULL for off-plan entries
+    template_name TEXT NOT NULL DEFAULT '',
+    template_location_type TEXT NOT NULL DEFAULT 'any'
+        CHECK(template_location_type IN ('home', 'office', 'restaurant', 'any')),
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK(meal_option_id IS NOT NULL OR length(trim(option_name)) > 0),
+    FOREIGN KEY (meal_option_id) REFERENCES meal_options(id) ON DELETE RESTRICT,
+    FOREIGN KEY (slot_type) REFERENCES meal_slots(name) ON DELETE RESTRICT
+);
+
+-- Step 3: Copy data
+INSERT INTO meal_entries_new (id, meal_option_id, date, iso_week, slot_type, location, servings, notes, status,
+                              option_name, template_id, template_name, template_location_type, created_at, updated_at)
+SELECT id, meal_option_id, date, iso_week, slot_type, location, servings, notes, status,
+       option_name, template_id, template_name, template_location_type, created_at, updated_at
+FROM meal_entries;
+
+-- Step 4: Drop old table
+DROP TABLE meal_entries;
+
+-- Step 5: Rename new table
+ALTER TABLE meal_entries_new RENAME TO meal_entries;
+
+-- Step 6: Restore the rows that reference entries
+INSERT INTO meal_entry_options (meal_entry_id, meal_option_id, position, servings, option_name)
+SELECT meal_entry_id, meal_option_id, position, servings, option_name FROM saved_entry_options;
+
+INSERT INTO meal_entry_planned_options (meal_entry_id, meal_option_id, position, servings, option_name)
+SELECT meal_entry_id, meal_option_id, position, servings, option_name FROM saved_entry_planned_options;
+
+INSERT INTO meal_entry_tags (meal_entry_id, tag_id, tag_name)
+SELECT meal_entry_id, tag_id, tag_name FROM saved_entry_tags;
+
+DROP TABLE saved_entry_options;
+DROP TABLE saved_entry_planned_options;
+DROP TABLE saved_entry_tags;
+
+-- Step 7: Recreate indexes and the timestamp trigger
+CREATE INDEX IF NOT EXISTS idx_meal_entries_date ON meal_entries(date);
+CREATE INDEX IF NOT EXISTS idx_meal_entries_option ON meal_entries(meal_option_id);
+CREATE INDEX IF NOT EXISTS idx_meal_entries_date_slot ON meal_entries(date, slot_type);
+CREATE INDEX IF NOT EXISTS idx_meal_entries_iso_week ON meal_entries(iso_week);
+CREATE INDEX IF NOT EXISTS idx_meal_entries_status_date ON meal_entries(status, date);
+
+CREATE TRIGGER IF NOT EXISTS update_meal_entries_timestamp
+AFTER UPDATE ON meal_entries
+FOR EACH ROW
+BEGIN
+    UPDATE meal_entries SET updated_at = CURRENT_TIMESTAMP WHERE id = OLD.id;
+END;
+
+-- Step 8: Estimated nutrient profile of an off-plan entry, per serving like
+-- meal_option_nutrients
+CREATE TABLE IF NOT EXISTS meal_entry_nutrients (
+    meal_entry_id INTEGER PRIMARY KEY,
+    kcal REAL NOT NULL DEFAULT 0 CHECK(kcal >= 0),
+    protein_g REAL NOT NULL DEFAULT 0 CHECK(protein_g >= 0),
+    carbs_g REAL NOT NULL DEFAULT 0 CHECK(carbs_g >= 0),
+    fat_g REAL NOT NULL DEFAULT 0 CHECK(fat_g >= 0),
+    fiber_g REAL NOT NULL DEFAULT 0 CHECK(fiber_g >= 0),
+    FOREIGN KEY (meal_entry_id) REFERENCES meal_entries(id) ON DELETE CASCADE
+);
+
+-- Step 9: Recreate views
+-- Off-plan entries have no options or template, but their estimated tags count
+CREATE VIEW IF NOT EXISTS weekly_meal_usage AS
+SELECT
+    eo.meal_option_id as meal_option_id,
+    me.iso_week as week,
+    COUNT(*) as usage_count
+FROM meal_entry_options eo
+JOIN meal_entries me ON me.id = eo.meal_entry_id
+WHERE me.status = 'eaten'
+GROUP BY eo.meal_option_id, me.iso_week;
+
+CREATE VIEW IF NOT EXISTS weekly_tag_usage AS
+SELECT
+    t.id as tag_id,
+    t.name as tag_name,
+    me.iso_week as week,
+    COUNT(*) as usage_count
+FROM meal_entries me
+JOIN meal_entry_tags met ON met.meal_entry_id = me.id
+JOIN tags t ON met.tag_id = t.id
+WHERE me.status = 'eaten'
+GROUP BY t.id, t.name, me.iso_week;
+
+CREATE VIEW IF NOT EXISTS weekly_template_usage AS
+SELECT
+    me.template_id as template_id,
+    COALESCE(mt.name, MAX(me.template_name)) as template_name,
+    mt.weekly_limit as weekly_limit,
+    me.iso_week as week,
+    COUNT(*) as usage_count
+FROM meal_entries me
+LEFT JOIN meal_templates mt ON mt.id = me.template_id
+WHERE me.status = 'eaten' AND me.template_id IS NOT NULL
+GROUP BY me.template_id, me.iso_week;
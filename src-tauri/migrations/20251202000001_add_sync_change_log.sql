@@ -0,0 +1,475 @@
+-- Change tracking for multi-device sync
+-- Every device keeps its own database. Tags, templates, options and entries get
+-- a UUID that is the same on every device; meal slots are identified by their
+-- name, since every database is seeded with the same slots.
+-- Triggers append one row to sync_changes for each change to a synced row,
+-- including changes to the rows it owns (a template's slots, an option's tags,
+-- an entry's options). Changes are ordered by a Lamport clock in sync_state;
+-- the sync protocol sends the current state of each changed entity, and the
+-- version with the highest (clock, device_id) wins.
+
+-- Step 1: UUID generation
+-- A version 4 UUID; re-evaluated on every use inside a trigger, but an
+-- uncorrelated subquery is evaluated only once per statement elsewhere
+CREATE VIEW sync_new_uuid AS
+SELECT lower(hex(randomblob(4))) || '-' || lower(hex(randomblob(2))) || '-4' ||
+       substr(lower(hex(randomblob(2))), 2) || '-' ||
+       substr('89ab', 1 + (abs(random()) % 4), 1) || substr(lower(hex(randomblob(2))), 2) || '-' ||
+       lower(hex(randomblob(6))) AS uuid;
+
+-- Step 2: Sync state of this device (a single row)
+CREATE TABLE sync_state (
+    id INTEGER PRIMARY KEY CHECK(id = 1),
+    device_id TEXT NOT NULL,
+    clock INTEGER NOT NULL DEFAULT 0,         -- Lamport clock of the last change seen
+    applying BOOLEAN NOT NULL DEFAULT 0,      -- Set while remote changes are applied, so they are not logged as local
+    pushed_seq INTEGER NOT NULL DEFAULT 0,    -- Last sync_changes.seq sent to the server
+    server_cursor INTEGER NOT NULL DEFAULT 0, -- Last server change received
+    server_url TEXT,
+    server_token TEXT,
+    last_synced_at DATETIME
+);
+
+INSERT INTO sync_state (id, device_id, clock) SELECT 1, uuid, 1 FROM sync_new_uuid;
+
+-- Step 3: Append-only change log
+-- Local changes carry this device's id; applied remote changes keep their origin's
+CREATE TABLE sync_changes (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    entity TEXT NOT NULL CHECK(entity IN ('slot', 'tag', 'template', 'option', 'entry')),
+    entity_key TEXT NOT NULL,           -- UUID, or the slot name
+    clock INTEGER NOT NULL,
+    device_id TEXT NOT NULL,
+    deleted BOOLEAN NOT NULL DEFAULT 0,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_sync_changes_entity ON sync_changes(entity, entity_key, clock);
+CREATE INDEX idx_sync_changes_device ON sync_changes(device_id, seq);
+
+-- Step 4: UUIDs for existing rows
+-- The subquery is correlated so each row gets its own UUID
+
+ALTER TABLE tags ADD COLUMN uuid TEXT;
+UPDATE tags SET uuid = (SELECT uuid FROM sync_new_uuid WHERE tags.id IS NOT NULL);
+CREATE UNIQUE INDEX idx_tags_uuid ON tags(uuid);
+
+ALTER TABLE meal_templates ADD COLUMN uuid TEXT;
+UPDATE meal_templates SET uuid = (SELECT uuid FROM sync_new_uuid WHERE meal_templates.id IS NOT NULL);
+CREATE UNIQUE INDEX idx_meal_templates_uuid ON meal_templates(uuid);
+
+ALTER TABLE meal_options ADD COLUMN uuid TEXT;
+UPDATE meal_options SET uuid = (SELECT uuid FROM sync_new_uuid WHERE meal_options.id IS NOT NULL);
+CREATE UNIQUE INDEX idx_meal_options_uuid ON meal_options(uuid);
+
+ALTER TABLE meal_entries ADD COLUMN uuid TEXT;
+UPDATE meal_entries SET uuid = (SELECT uuid FROM sync_new_uuid WHERE meal_entries.id IS NOT NULL);
+CREATE UNIQUE INDEX idx_meal_entries_uuid ON meal_entries(uuid);
+
+-- Step 5: Log existing rows, so the first sync sends them
+
+INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+SELECT 'slot', t.name, s.clock, s.device_id FROM meal_slots t, sync_state s ORDER BY t.id;
+
+INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+SELECT 'tag', t.uuid, s.clock, s.device_id FROM tags t, sync_state s ORDER BY t.id;
+
+INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+SELECT 'template', t.uuid, s.clock, s.device_id FROM meal_templates t, sync_state s ORDER BY t.id;
+
+INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+SELECT 'option', t.uuid, s.clock, s.device_id FROM meal_options t, sync_state s ORDER BY t.id;
+
+INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+SELECT 'entry', t.uuid, s.clock, s.device_id FROM meal_entries t, sync_state s ORDER BY t.id;
+
+-- Step 6: Log changes to synced rows
+-- New rows without a UUID (every local insert) get one first
+
+CREATE TRIGGER sync_meal_slots_insert
+AFTER INSERT ON meal_slots
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'slot', t.name, s.clock, s.device_id FROM meal_slots t, sync_state s
+    WHERE t.id = NEW.id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_slots_update
+AFTER UPDATE OF display_name, sort_order, active ON meal_slots
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'slot', NEW.name, clock, device_id FROM sync_state WHERE NOT applying;
+END;
+
+CREATE TRIGGER sync_meal_slots_delete
+AFTER DELETE ON meal_slots
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id, deleted)
+    SELECT 'slot', OLD.name, clock, device_id, 1 FROM sync_state WHERE NOT applying;
+END;
+
+CREATE TRIGGER sync_tags_insert
+AFTER INSERT ON tags
+FOR EACH ROW
+BEGIN
+    UPDATE tags SET uuid = (SELECT uuid FROM sync_new_uuid) WHERE id = NEW.id AND uuid IS NULL;
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'tag', t.uuid, s.clock, s.device_id FROM tags t, sync_state s
+    WHERE t.id = NEW.id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_tags_update
+AFTER UPDATE OF name, display_name, category, weekly_suggestion, parent_tag_id, archived ON tags
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'tag', NEW.uuid, clock, device_id FROM sync_state WHERE NOT applying;
+END;
+
+CREATE TRIGGER sync_tags_delete
+AFTER DELETE ON tags
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id, deleted)
+    SELECT 'tag', OLD.uuid, clock, device_id, 1 FROM sync_state WHERE NOT applying;
+END;
+
+CREATE TRIGGER sync_meal_templates_insert
+AFTER INSERT ON meal_templates
+FOR EACH ROW
+BEGIN
+    UPDATE meal_templates SET uuid = (SELECT uuid FROM sync_new_uuid) WHERE id = NEW.id AND uuid IS NULL;
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'template', t.uuid, s.clock, s.device_id FROM meal_templates t, sync_state s
+    WHERE t.id = NEW.id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_templates_update
+AFTER UPDATE OF name, description, location_type, weekly_limit, archived ON meal_templates
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'template', NEW.uuid, clock, device_id FROM sync_state WHERE NOT applying;
+END;
+
+CREATE TRIGGER sync_meal_templates_delete
+AFTER DELETE ON meal_templates
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id, deleted)
+    SELECT 'template', OLD.uuid, clock, device_id, 1 FROM sync_state WHERE NOT applying;
+END;
+
+CREATE TRIGGER sync_meal_options_insert
+AFTER INSERT ON meal_options
+FOR EACH ROW
+BEGIN
+    UPDATE meal_options SET uuid = (SELECT uuid FROM sync_new_uuid) WHERE id = NEW.id AND uuid IS NULL;
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'option', t.uuid, s.clock, s.device_id FROM meal_options t, sync_state s
+    WHERE t.id = NEW.id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_options_update
+AFTER UPDATE OF template_id, name, description, nutritional_notes, option_group, archived ON meal_options
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'option', NEW.uuid, clock, device_id FROM sync_state WHERE NOT applying;
+END;
+
+CREATE TRIGGER sync_meal_options_delete
+AFTER DELETE ON meal_options
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id, deleted)
+    SELECT 'option', OLD.uuid, clock, device_id, 1 FROM sync_state WHERE NOT applying;
+END;
+
+CREATE TRIGGER sync_meal_entries_insert
+AFTER INSERT ON meal_entries
+FOR EACH ROW
+BEGIN
+    UPDATE meal_entries SET uuid = (SELECT uuid FROM sync_new_uuid) WHERE id = NEW.id AND uuid IS NULL;
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'entry', t.uuid, s.clock, s.device_id FROM meal_entries t, sync_state s
+    WHERE t.id = NEW.id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_entries_update
+AFTER UPDATE OF meal_option_id, date, iso_week, slot_type, location, servings, notes, status,
+    option_name, template_id, template_name, template_location_type ON meal_entries
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'entry', NEW.uuid, clock, device_id FROM sync_state WHERE NOT applying;
+END;
+
+CREATE TRIGGER sync_meal_entries_delete
+AFTER DELETE ON meal_entries
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id, deleted)
+    SELECT 'entry', OLD.uuid, clock, device_id, 1 FROM sync_state WHERE NOT applying;
+END;
+
+-- Step 7: Log changes to the rows an entity owns as a change to the entity
+-- Nothing is logged when the owner itself is being deleted
+
+CREATE TRIGGER sync_meal_template_slots_insert
+AFTER INSERT ON meal_template_slots
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'template', p.uuid, s.clock, s.device_id FROM meal_templates p, sync_state s
+    WHERE p.id = NEW.template_id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_template_slots_update
+AFTER UPDATE ON meal_template_slots
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'template', p.uuid, s.clock, s.device_id FROM meal_templates p, sync_state s
+    WHERE p.id = NEW.template_id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_template_slots_delete
+AFTER DELETE ON meal_template_slots
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'template', p.uuid, s.clock, s.device_id FROM meal_templates p, sync_state s
+    WHERE p.id = OLD.template_id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_template_option_groups_insert
+AFTER INSERT ON meal_template_option_groups
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'template', p.uuid, s.clock, s.device_id FROM meal_templates p, sync_state s
+    WHERE p.id = NEW.template_id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_template_option_groups_update
+AFTER UPDATE ON meal_template_option_groups
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'template', p.uuid, s.clock, s.device_id FROM meal_templates p, sync_state s
+    WHERE p.id = NEW.template_id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_template_option_groups_delete
+AFTER DELETE ON meal_template_option_groups
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'template', p.uuid, s.clock, s.device_id FROM meal_templates p, sync_state s
+    WHERE p.id = OLD.template_id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_option_tags_insert
+AFTER INSERT ON meal_option_tags
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'option', p.uuid, s.clock, s.device_id FROM meal_options p, sync_state s
+    WHERE p.id = NEW.meal_option_id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_option_tags_update
+AFTER UPDATE ON meal_option_tags
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'option', p.uuid, s.clock, s.device_id FROM meal_options p, sync_state s
+    WHERE p.id = NEW.meal_option_id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_option_tags_delete
+AFTER DELETE ON meal_option_tags
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'option', p.uuid, s.clock, s.device_id FROM meal_options p, sync_state s
+    WHERE p.id = OLD.meal_option_id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_option_nutrients_insert
+AFTER INSERT ON meal_option_nutrients
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'option', p.uuid, s.clock, s.device_id FROM meal_options p, sync_state s
+    WHERE p.id = NEW.meal_option_id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_option_nutrients_update
+AFTER UPDATE OF kcal, protein_g, carbs_g, fat_g, fiber_g ON meal_option_nutrients
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'option', p.uuid, s.clock, s.device_id FROM meal_options p, sync_state s
+    WHERE p.id = NEW.meal_option_id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_option_nutrients_delete
+AFTER DELETE ON meal_option_nutrients
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'option', p.uuid, s.clock, s.device_id FROM meal_options p, sync_state s
+    WHERE p.id = OLD.meal_option_id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_entry_options_insert
+AFTER INSERT ON meal_entry_options
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'entry', p.uuid, s.clock, s.device_id FROM meal_entries p, sync_state s
+    WHERE p.id = NEW.meal_entry_id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_entry_options_update
+AFTER UPDATE ON meal_entry_options
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'entry', p.uuid, s.clock, s.device_id FROM meal_entries p, sync_state s
+    WHERE p.id = NEW.meal_entry_id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_entry_options_delete
+AFTER DELETE ON meal_entry_options
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'entry', p.uuid, s.clock, s.device_id FROM meal_entries p, sync_state s
+    WHERE p.id = OLD.meal_entry_id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_entry_planned_options_insert
+AFTER INSERT ON meal_entry_planned_options
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'entry', p.uuid, s.clock, s.device_id FROM meal_entries p, sync_state s
+    WHERE p.id = NEW.meal_entry_id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_entry_planned_options_update
+AFTER UPDATE ON meal_entry_planned_options
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'entry', p.uuid, s.clock, s.device_id FROM meal_entries p, sync_state s
+    WHERE p.id = NEW.meal_entry_id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_entry_planned_options_delete
+AFTER DELETE ON meal_entry_planned_options
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'entry', p.uuid, s.clock, s.device_id FROM meal_entries p, sync_state s
+    WHERE p.id = OLD.meal_entry_id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_entry_tags_insert
+AFTER INSERT ON meal_entry_tags
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'entry', p.uuid, s.clock, s.device_id FROM meal_entries p, sync_state s
+    WHERE p.id = NEW.meal_entry_id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_entry_tags_update
+AFTER UPDATE ON meal_entry_tags
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'entry', p.uuid, s.clock, s.device_id FROM meal_entries p, sync_state s
+    WHERE p.id = NEW.meal_entry_id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_entry_tags_delete
+AFTER DELETE ON meal_entry_tags
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'entry', p.uuid, s.clock, s.device_id FROM meal_entries p, sync_state s
+    WHERE p.id = OLD.meal_entry_id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_entry_nutrients_insert
+AFTER INSERT ON meal_entry_nutrients
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'entry', p.uuid, s.clock, s.device_id FROM meal_entries p, sync_state s
+    WHERE p.id = NEW.meal_entry_id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_entry_nutrients_update
+AFTER UPDATE ON meal_entry_nutrients
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'entry', p.uuid, s.clock, s.device_id FROM meal_entries p, sync_state s
+    WHERE p.id = NEW.meal_entry_id AND NOT s.applying;
+END;
+
+CREATE TRIGGER sync_meal_entry_nutrients_delete
+AFTER DELETE ON meal_entry_nutrients
+FOR EACH ROW
+BEGIN
+    UPDATE sync_state SET clock = clock + 1 WHERE NOT applying;
+    INSERT INTO sync_changes (entity, entity_key, clock, device_id)
+    SELECT 'entry', p.uuid, s.clock, s.device_id FROM meal_entries p, sync_state s
+    WHERE p.id = OLD.meal_entry_id AND NOT s.applying;
+END;
@@ -0,0 +1,22 @@
+-- Audit log of library and entry mutations
+-- Each create, update and delete made through the repositories records the
+-- entities it changed, as their sync data (see sync_changes) before and after
+-- the change. The rows of one repository call share an operation number, which
+-- is what undo and redo work on.
+
+CREATE TABLE audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    operation INTEGER NOT NULL,         -- Groups the rows of one repository call
+    command TEXT NOT NULL,              -- Command that made the change, e.g. 'delete_entry'
+    entity TEXT NOT NULL CHECK(entity IN ('slot', 'tag', 'template', 'option', 'entry')),
+    entity_id INTEGER NOT NULL,
+    entity_key TEXT NOT NULL,           -- UUID, or the slot name
+    before_data TEXT,                   -- JSON; NULL when the change created the entity
+    after_data TEXT,                    -- JSON; NULL when the change deleted the entity
+    status TEXT NOT NULL DEFAULT 'done' CHECK(status IN ('done', 'undone', 'dropped')),
+    undo_order INTEGER,                 -- Set while undone; the highest is redone first
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_audit_log_entity ON audit_log(entity, entity_id, id);
+CREATE INDEX idx_audit_log_operation ON audit_log(operation);
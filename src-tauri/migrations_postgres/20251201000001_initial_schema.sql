@@ -0,0 +1,225 @@
+-- Initial PostgreSQL schema for Nutrition Helper
+-- Same tables, constraints and views as the SQLite schema after all of its migrations
+-- (see ../migrations); later changes need a migration in both directories
+
+-- Meal slots of the day, configurable
+CREATE TABLE meal_slots (
+    id BIGSERIAL PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,             -- Internal key stored on entries/templates: "breakfast", "pre_workout"
+    display_name TEXT NOT NULL,            -- User-facing: "Breakfast", "Pre-workout"
+    sort_order INTEGER NOT NULL DEFAULT 0, -- Position within the day, ascending
+    active BOOLEAN NOT NULL DEFAULT TRUE,  -- Inactive slots keep their history but take no new entries
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO meal_slots (name, display_name, sort_order) VALUES
+    ('breakfast', 'Breakfast', 10),
+    ('morning_snack', 'Morning Snack', 20),
+    ('lunch', 'Lunch', 30),
+    ('afternoon_snack', 'Afternoon Snack', 40),
+    ('dinner', 'Dinner', 50);
+
+-- Meal templates (the "cards" that fill slots)
+CREATE TABLE meal_templates (
+    id BIGSERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    description TEXT,
+    location_type TEXT NOT NULL CHECK(location_type IN ('home', 'office', 'restaurant', 'any')),
+    weekly_limit INTEGER CHECK(weekly_limit IS NULL OR weekly_limit > 0),
+    archived BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_meal_templates_location ON meal_templates(location_type);
+CREATE INDEX idx_meal_templates_weekly_limit ON meal_templates(weekly_limit)
+    WHERE weekly_limit IS NOT NULL;
+
+CREATE TABLE meal_template_slots (
+    template_id BIGINT NOT NULL REFERENCES meal_templates(id) ON DELETE CASCADE,
+    slot_type TEXT NOT NULL REFERENCES meal_slots(name) ON DELETE RESTRICT,
+    PRIMARY KEY (template_id, slot_type)
+);
+
+CREATE INDEX idx_meal_template_slots_slot ON meal_template_slots(slot_type);
+
+CREATE TABLE meal_template_option_groups (
+    template_id BIGINT NOT NULL REFERENCES meal_templates(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    min_choices INTEGER NOT NULL DEFAULT 1 CHECK(min_choices >= 0),
+    max_choices INTEGER CHECK(max_choices IS NULL OR (max_choices >= 1 AND max_choices >= min_choices)), -- NULL = no upper bound
+    sort_order INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (template_id, name)
+);
+
+-- Meal options (ingredient/variation choices within a template)
+CREATE TABLE meal_options (
+    id BIGSERIAL PRIMARY KEY,
+    template_id BIGINT NOT NULL REFERENCES meal_templates(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    description TEXT,
+    nutritional_notes TEXT,
+    option_group TEXT,
+    archived BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_meal_options_template ON meal_options(template_id);
+
+CREATE TABLE meal_option_nutrients (
+    meal_option_id BIGINT PRIMARY KEY REFERENCES meal_options(id) ON DELETE CASCADE,
+    kcal DOUBLE PRECISION NOT NULL DEFAULT 0 CHECK(kcal >= 0),
+    protein_g DOUBLE PRECISION NOT NULL DEFAULT 0 CHECK(protein_g >= 0),
+    carbs_g DOUBLE PRECISION NOT NULL DEFAULT 0 CHECK(carbs_g >= 0),
+    fat_g DOUBLE PRECISION NOT NULL DEFAULT 0 CHECK(fat_g >= 0),
+    fiber_g DOUBLE PRECISION NOT NULL DEFAULT 0 CHECK(fiber_g >= 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Hierarchical tags (e.g., "pasta_integrale" -> "pasta")
+CREATE TABLE tags (
+    id BIGSERIAL PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    display_name TEXT NOT NULL,
+    category TEXT NOT NULL CHECK(category IN ('ingredient', 'dietary', 'prep_time', 'other')),
+    weekly_suggestion INTEGER CHECK(weekly_suggestion IS NULL OR weekly_suggestion >= 0),
+    parent_tag_id BIGINT REFERENCES tags(id) ON DELETE SET NULL,
+    archived BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_tags_category ON tags(category);
+CREATE INDEX idx_tags_parent ON tags(parent_tag_id);
+
+CREATE TABLE meal_option_tags (
+    meal_option_id BIGINT NOT NULL REFERENCES meal_options(id) ON DELETE CASCADE,
+    tag_id BIGINT NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+    PRIMARY KEY (meal_option_id, tag_id)
+);
+
+CREATE INDEX idx_meal_option_tags_option ON meal_option_tags(meal_option_id);
+CREATE INDEX idx_meal_option_tags_tag ON meal_option_tags(tag_id);
+
+-- Logged and planned meals, with a snapshot of the option, template and tags
+CREATE TABLE meal_entries (
+    id BIGSERIAL PRIMARY KEY,
+    meal_option_id BIGINT REFERENCES meal_options(id) ON DELETE RESTRICT, -- NULL for off-plan entries
+    date DATE NOT NULL,
+    iso_week TEXT NOT NULL CHECK(length(iso_week) = 7), -- "YYYY-WW", ISO week-year and week
+    slot_type TEXT NOT NULL REFERENCES meal_slots(name) ON DELETE RESTRICT,
+    location TEXT NOT NULL CHECK(location IN ('home', 'office', 'restaurant', 'any')),
+    servings DOUBLE PRECISION NOT NULL DEFAULT 1.0 CHECK(servings > 0),
+    notes TEXT,
+    status TEXT NOT NULL DEFAULT 'planned' CHECK(status IN ('planned', 'eaten', 'skipped')),
+    option_name TEXT NOT NULL DEFAULT '', -- Description of off-plan entries
+    template_id BIGINT,                   -- NULL for off-plan entries
+    template_name TEXT NOT NULL DEFAULT '',
+    template_location_type TEXT NOT NULL DEFAULT 'any'
+        CHECK(template_location_type IN ('home', 'office', 'restaurant', 'any')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK(meal_option_id IS NOT NULL OR length(trim(option_name)) > 0)
+);
+
+CREATE INDEX idx_meal_entries_date ON meal_entries(date);
+CREATE INDEX idx_meal_entries_option ON meal_entries(meal_option_id);
+CREATE INDEX idx_meal_entries_date_slot ON meal_entries(date, slot_type);
+CREATE INDEX idx_meal_entries_iso_week ON meal_entries(iso_week);
+CREATE INDEX idx_meal_entries_status_date ON meal_entries(status, date);
+
+CREATE TABLE meal_entry_options (
+    meal_entry_id BIGINT NOT NULL REFERENCES meal_entries(id) ON DELETE CASCADE,
+    meal_option_id BIGINT NOT NULL REFERENCES meal_options(id) ON DELETE RESTRICT,
+    position INTEGER NOT NULL DEFAULT 0,
+    servings DOUBLE PRECISION NOT NULL DEFAULT 1.0 CHECK(servings > 0),
+    option_name TEXT NOT NULL DEFAULT '', -- Snapshot, like meal_entries.option_name
+    PRIMARY KEY (meal_entry_id, meal_option_id)
+);
+
+CREATE INDEX idx_meal_entry_options_option ON meal_entry_options(meal_option_id);
+
+CREATE TABLE meal_entry_planned_options (
+    meal_entry_id BIGINT NOT NULL REFERENCES meal_entries(id) ON DELETE CASCADE,
+    meal_option_id BIGINT NOT NULL REFERENCES meal_options(id) ON DELETE RESTRICT,
+    position INTEGER NOT NULL DEFAULT 0,
+    servings DOUBLE PRECISION NOT NULL DEFAULT 1.0 CHECK(servings > 0),
+    option_name TEXT NOT NULL DEFAULT '', -- Snapshot, like meal_entry_options.option_name
+    PRIMARY KEY (meal_entry_id, meal_option_id)
+);
+
+CREATE INDEX idx_meal_entry_planned_options_option ON meal_entry_planned_options(meal_option_id);
+
+CREATE TABLE meal_entry_tags (
+    meal_entry_id BIGINT NOT NULL REFERENCES meal_entries(id) ON DELETE CASCADE,
+    tag_id BIGINT, -- NULL when the tag did not exist in this database (imports)
+    tag_name TEXT NOT NULL,
+    PRIMARY KEY (meal_entry_id, tag_name)
+);
+
+CREATE INDEX idx_meal_entry_tags_tag ON meal_entry_tags(tag_id);
+
+CREATE TABLE meal_entry_nutrients (
+    meal_entry_id BIGINT PRIMARY KEY REFERENCES meal_entries(id) ON DELETE CASCADE,
+    kcal DOUBLE PRECISION NOT NULL DEFAULT 0 CHECK(kcal >= 0),
+    protein_g DOUBLE PRECISION NOT NULL DEFAULT 0 CHECK(protein_g >= 0),
+    carbs_g DOUBLE PRECISION NOT NULL DEFAULT 0 CHECK(carbs_g >= 0),
+    fat_g DOUBLE PRECISION NOT NULL DEFAULT 0 CHECK(fat_g >= 0),
+    fiber_g DOUBLE PRECISION NOT NULL DEFAULT 0 CHECK(fiber_g >= 0)
+);
+
+-- updated_at follows every update, like the SQLite triggers
+CREATE FUNCTION set_updated_at() RETURNS trigger AS $$
+BEGIN
+    NEW.updated_at = CURRENT_TIMESTAMP;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER update_meal_slots_timestamp BEFORE UPDATE ON meal_slots
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+CREATE TRIGGER update_meal_templates_timestamp BEFORE UPDATE ON meal_templates
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+CREATE TRIGGER update_meal_options_timestamp BEFORE UPDATE ON meal_options
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+CREATE TRIGGER update_meal_option_nutrients_timestamp BEFORE UPDATE ON meal_option_nutrients
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+CREATE TRIGGER update_meal_entries_timestamp BEFORE UPDATE ON meal_entries
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- Weekly usage of eaten meals, by option, tag and template
+CREATE VIEW weekly_meal_usage AS
+SELECT
+    eo.meal_option_id AS meal_option_id,
+    me.iso_week AS week,
+    COUNT(*) AS usage_count
+FROM meal_entry_options eo
+JOIN meal_entries me ON me.id = eo.meal_entry_id
+WHERE me.status = 'eaten'
+GROUP BY eo.meal_option_id, me.iso_week;
+
+CREATE VIEW weekly_tag_usage AS
+SELECT
+    t.id AS tag_id,
+    t.name AS tag_name,
+    me.iso_week AS week,
+    COUNT(*) AS usage_count
+FROM meal_entries me
+JOIN meal_entry_tags met ON met.meal_entry_id = me.id
+JOIN tags t ON met.tag_id = t.id
+WHERE me.status = 'eaten'
+GROUP BY t.id, t.name, me.iso_week;
+
+CREATE VIEW weekly_template_usage AS
+SELECT
+    me.template_id AS template_id,
+    COALESCE(mt.name, MAX(me.template_name)) AS template_name,
+    mt.weekly_limit AS weekly_limit,
+    me.iso_week AS week,
+    COUNT(*) AS usage_count
+FROM meal_entries me
+LEFT JOIN meal_templates mt ON mt.id = me.template_id
+WHERE me.status = 'eaten' AND me.template_id IS NOT NULL
+GROUP BY me.template_id, mt.name, mt.weekly_limit, me.iso_week;
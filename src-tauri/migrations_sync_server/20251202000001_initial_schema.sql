@@ -0,0 +1,14 @@
+-- Sync server schema
+-- The server relays changes between devices without interpreting them: it keeps
+-- the latest version of each entity, numbered in the order it was received
+
+CREATE TABLE IF NOT EXISTS sync_entities (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,  -- Pull cursor; a newer version gets a new seq
+    entity TEXT NOT NULL,
+    entity_key TEXT NOT NULL,
+    clock INTEGER NOT NULL,
+    device_id TEXT NOT NULL,
+    data TEXT,                              -- JSON; NULL = deleted
+    received_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (entity, entity_key)
+);
@@ -0,0 +1,1074 @@
+// Headless command-line interface
+// Lists, plans, logs, validates, undoes and exports against a database file (or the
+// shared PostgreSQL database) through the same repository and service layers as the
+// desktop app
+
+mod output;
+
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use clap::{Args, Parser, Subcommand};
+use nutrition_helper::db;
+use nutrition_helper::models::{
+    AuditOperation, AuditRecord, CopyConflictStrategy, CopyPlanResult, CreateMealEntry,
+    CreateOffPlanEntry, EntryStatus, GeneratePlanRequest, GeneratedPlan, LocationType, MealEntry,
+    SelectedOption, SlotType, SyncEntity, SyncSettings,
+};
+use nutrition_helper::repository::{
+    AuditRepository, Backend, MealEntryStore, MealOptionStore, MealTemplateStore, SyncRepository,
+    TagStore, POSTGRES_URL_ENV,
+};
+use nutrition_helper::services::{
+    ExportService, PlanningService, SyncService, ValidationService, WeekSummaryService,
+    SYNC_TOKEN_ENV,
+};
+use nutrition_helper::{ApiError, ApiResult};
+use output::{emit, io_error, or_dash, Format, Table};
+use sqlx::SqlitePool;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "nutrition-cli",
+    version,
+    about = "Plan, log and export meals without the desktop app"
+)]
+struct Cli {
+    /// Database file, created and migrated if needed
+    #[arg(
+        long,
+        required_unless_present = "postgres",
+        conflicts_with = "postgres"
+    )]
+    db: Option<PathBuf>,
+
+    /// Use the PostgreSQL database from NUTRITION_POSTGRES_URL instead of a file;
+    /// sync, undo, history, planning, limits and export need a file
+    #[arg(long)]
+    postgres: bool,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = Format::Table)]
+    format: Format,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// List templates, options, tags or entries
+    #[command(subcommand)]
+    List(ListCommand),
+
+    /// Generate or copy planned entries
+    #[command(subcommand)]
+    Plan(PlanCommand),
+
+    /// Log a meal, or an off-plan meal with --description
+    Log(LogArgs),
+
+    /// Check a selection against slot, limit and tag rules without logging it
+    Validate(SelectionArgs),
+
+    /// Template limits and tag suggestions of a week
+    Limits {
+        /// Any day of the week (YYYY-MM-DD, today, yesterday, tomorrow)
+        #[arg(long, value_parser = parse_date, default_value = "today")]
+        week: NaiveDate,
+    },
+
+    /// Export the database or meal entries
+    #[command(subcommand)]
+    Export(ExportCommand),
+
+    /// Sync with a sync server (see nutrition-sync-server)
+    #[command(subcommand)]
+    Sync(SyncCommand),
+
+    /// Undo the latest change still in effect
+    Undo,
+
+    /// Redo the change undone last
+    Redo,
+
+    /// Every recorded change to a slot, tag, template, option or entry
+    History {
+        #[arg(value_parser = parse_entity)]
+        entity: SyncEntity,
+        id: i64,
+    },
+
+    /// Serve the HTTP/JSON API; the bearer token is read from NUTRITION_API_TOKEN
+    #[cfg(feature = "http-server")]
+    Serve {
+        /// Listen address; use 0.0.0.0 only behind a trusted network
+        #[arg(long, default_value = "127.0.0.1:7878")]
+        addr: std::net::SocketAddr,
+    },
+
+    /// Copy the database into an empty PostgreSQL database; the URL is read from
+    /// NUTRITION_POSTGRES_URL
+    #[cfg(feature = "postgres")]
+    MigratePostgres,
+}
+
+#[derive(Debug, Subcommand)]
+enum ListCommand {
+    /// Meal templates
+    Templates {
+        /// List archived templates instead
+        #[arg(long)]
+        archived: bool,
+    },
+    /// Meal options
+    Options {
+        /// Only the options of this template
+        #[arg(long)]
+        template: Option<i64>,
+    },
+    /// Tags
+    Tags,
+    /// Meal entries in a date range
+    Entries {
+        #[arg(long, value_parser = parse_date, default_value = "today")]
+        from: NaiveDate,
+        /// Defaults to --from
+        #[arg(long, value_parser = parse_date)]
+        to: Option<NaiveDate>,
+        #[arg(long, value_parser = parse_status)]
+        status: Option<EntryStatus>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum PlanCommand {
+    /// Fill the empty slots of a week from the template library
+    Generate {
+        /// Any day of the week
+        #[arg(long, value_parser = parse_date, default_value = "today")]
+        week: NaiveDate,
+        /// One location for the whole week, or seven comma-separated ones, Monday first
+        #[arg(long, value_parser = parse_location, value_delimiter = ',', default_value = "home")]
+        locations: Vec<LocationType>,
+        /// Same seed and library give the same plan; defaults to the current time
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Show the plan without writing it
+        #[arg(long)]
+        preview: bool,
+    },
+    /// Copy the entries of a day onto another day
+    CopyDay(CopyArgs),
+    /// Copy the entries of a week onto another week
+    CopyWeek(CopyArgs),
+}
+
+#[derive(Debug, Args)]
+struct CopyArgs {
+    #[arg(long, value_parser = parse_date)]
+    from: NaiveDate,
+    #[arg(long, value_parser = parse_date)]
+    to: NaiveDate,
+    /// skip, overwrite or abort when a target slot holds entries
+    #[arg(long, value_parser = parse_conflict, default_value = "skip")]
+    on_conflict: CopyConflictStrategy,
+}
+
+#[derive(Debug, Args)]
+struct SelectionArgs {
+    /// Main meal option
+    #[arg(long)]
+    option: i64,
+    /// Other options of the same template
+    #[arg(long = "extra")]
+    extra_options: Vec<i64>,
+    #[arg(long, value_parser = parse_slot)]
+    slot: SlotType,
+    #[arg(long, value_parser = parse_date, default_value = "today")]
+    date: NaiveDate,
+}
+
+#[derive(Debug, Args)]
+struct LogArgs {
+    /// Main meal option
+    #[arg(long, required_unless_present = "description")]
+    option: Option<i64>,
+    /// Other options of the same template
+    #[arg(long = "extra", conflicts_with = "description")]
+    extra_options: Vec<i64>,
+    /// Free-text description of an off-plan meal
+    #[arg(long, conflicts_with = "option")]
+    description: Option<String>,
+    /// Estimated tag of an off-plan meal
+    #[arg(long = "tag", conflicts_with = "option")]
+    tag_ids: Vec<i64>,
+    #[arg(long, value_parser = parse_slot)]
+    slot: SlotType,
+    #[arg(long, value_parser = parse_date, default_value = "today")]
+    date: NaiveDate,
+    #[arg(long, value_parser = parse_location, default_value = "home")]
+    location: LocationType,
+    #[arg(long)]
+    servings: Option<f64>,
+    #[arg(long)]
+    notes: Option<String>,
+    #[arg(long, value_parser = parse_status, default_value = "eaten")]
+    status: EntryStatus,
+}
+
+#[derive(Debug, Subcommand)]
+enum SyncCommand {
+    /// Device ID, pending changes and server
+    Status,
+    /// Set the server; the token is read from NUTRITION_SYNC_TOKEN
+    Configure {
+        /// Server URL, e.g. http://nas.local:7879; omit to stop syncing
+        #[arg(long)]
+        url: Option<String>,
+    },
+    /// Pull changes from the server, then push local ones
+    Now,
+}
+
+#[derive(Debug, Subcommand)]
+enum ExportCommand {
+    /// The whole database as the app's versioned JSON document
+    Json {
+        /// Write to this file instead of stdout
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Meal entries of a date range as CSV
+    Csv {
+        #[arg(long, value_parser = parse_date, required_unless_present = "month")]
+        from: Option<NaiveDate>,
+        #[arg(long, value_parser = parse_date, requires = "from")]
+        to: Option<NaiveDate>,
+        /// A whole month instead of --from/--to (YYYY-MM or "last")
+        #[arg(long, value_parser = parse_month, conflicts_with = "from")]
+        month: Option<(NaiveDate, NaiveDate)>,
+        /// Write to this file instead of stdout
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let backend = match open_backend(&cli).await {
+        Ok(backend) => backend,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let result = run(&backend, cli, &mut std::io::stdout().lock()).await;
+    backend.close().await;
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// The PostgreSQL database with --postgres, the --db file otherwise
+async fn open_backend(cli: &Cli) -> Result<Backend, String> {
+    match &cli.db {
+        Some(path) => db::initialize_database(path.clone())
+            .await
+            .map(Backend::Sqlite)
+            .map_err(|e| format!("cannot open {}: {}", path.display(), e)),
+        None => {
+            let url = std::env::var(POSTGRES_URL_ENV)
+                .map_err(|_| format!("set {} to the PostgreSQL URL", POSTGRES_URL_ENV))?;
+            Backend::connect_postgres(&url)
+                .await
+                .map_err(|e| format!("cannot connect to PostgreSQL: {}", e))
+        }
+    }
+}
+
+/// Run one command, writing its result to `out`
+/// Validation warnings go to stderr so they never mix with JSON output
+async fn run(backend: &Backend, cli: Cli, out: &mut dyn Write) -> ApiResult<()> {
+    let format = cli.format;
+    #[cfg(feature = "http-server")]
+    let db_path = cli.db;
+
+    match cli.command {
+        Command::List(list) => run_list(backend, list, format, out).await,
+        Command::Plan(plan) => run_plan(backend.sqlite()?, plan, format, out).await,
+        Command::Log(args) => run_log(backend, args, format, out).await,
+        Command::Validate(args) => {
+            let option_ids: Vec<i64> = std::iter::once(args.option)
+                .chain(args.extra_options)
+                .collect();
+            let warnings = ValidationService::validate_meal_selection(
+                backend,
+                &option_ids,
+                &args.slot,
+                args.date,
+            )
+            .await?;
+            emit(out, format, &warnings, |warnings| {
+                let mut table =
+                    Table::new(vec!["warning", "message"]).empty_message("Selection is valid");
+                for warning in warnings {
+                    table.push(vec![
+                        format!("{:?}", warning.warning_type),
+                        warning.message.clone(),
+                    ]);
+                }
+                table
+            })
+        }
+        Command::Limits { week } => {
+            let summary = WeekSummaryService::get_week_summary(backend.sqlite()?, week).await?;
+            emit(out, format, &summary, |summary| {
+                let mut table = Table::new(vec!["kind", "name", "used", "limit", "status"])
+                    .empty_message("No limits or suggestions this week");
+                for usage in &summary.template_usage {
+                    let status = match usage.weekly_limit {
+                        Some(limit) if usage.usage_count > i64::from(limit) => "exceeded",
+                        Some(limit) if usage.usage_count == i64::from(limit) => "full",
+                        _ => "",
+                    };
+                    table.push(vec![
+                        "template".to_string(),
+                        usage.template_name.clone(),
+                        usage.usage_count.to_string(),
+                        or_dash(usage.weekly_limit),
+                        status.to_string(),
+                    ]);
+                }
+                for usage in &summary.tag_usage {
+                    let status = match (usage.exceeded, usage.avoid) {
+                        (true, _) => "exceeded",
+                        (false, true) => "avoid",
+                        _ => "",
+                    };
+                    table.push(vec![
+                        "tag".to_string(),
+                        usage.display_name.clone(),
+                        usage.usage_count.to_string(),
+                        or_dash(usage.weekly_suggestion),
+                        status.to_string(),
+                    ]);
+                }
+                table
+            })
+        }
+        Command::Export(export) => run_export(backend.sqlite()?, export, out).await,
+        Command::Sync(sync) => run_sync(backend.sqlite()?, sync, format, out).await,
+        Command::Undo => {
+            let operation = AuditRepository::undo_last(backend.sqlite()?).await?;
+            emit_operation(operation, "Nothing to undo", format, out)
+        }
+        Command::Redo => {
+            let operation = AuditRepository::redo(backend.sqlite()?).await?;
+            emit_operation(operation, "Nothing to redo", format, out)
+        }
+        Command::History { entity, id } => {
+            let history = AuditRepository::get_history(backend.sqlite()?, entity, id).await?;
+            emit(out, format, &history, |history| {
+                audit_table(history).empty_message("No recorded changes")
+            })
+        }
+        #[cfg(feature = "http-server")]
+        Command::Serve { addr } => {
+            use nutrition_helper::server::{self, ServerConfig, TOKEN_ENV};
+
+            let token = std::env::var(TOKEN_ENV).map_err(|_| {
+                ApiError::ValidationError(format!("Set {} to the API bearer token", TOKEN_ENV))
+            })?;
+            let config = ServerConfig { addr, token };
+            // Backups are SQLite-only; with --postgres the backup routes are rejected
+            let backup_config = db::BackupConfig::for_database(&db_path.unwrap_or_default());
+
+            eprintln!("Serving the API on http://{}/api", addr);
+            server::run_server(backend.clone(), backup_config, &config).await
+        }
+        #[cfg(feature = "postgres")]
+        Command::MigratePostgres => {
+            use nutrition_helper::db::postgres::{self, URL_ENV};
+
+            let url = std::env::var(URL_ENV).map_err(|_| {
+                ApiError::ValidationError(format!("Set {} to the PostgreSQL URL", URL_ENV))
+            })?;
+            let target = postgres::connect(&url).await?;
+            let copied = postgres::migrate_sqlite_to_postgres(backend.sqlite()?, &target).await;
+            target.close().await;
+
+            emit(out, format, &copied?, |copied| {
+                let mut table = Table::new(vec!["table", "rows"]);
+                for table_rows in copied {
+                    table.push(vec![
+                        table_rows.table.to_string(),
+                        table_rows.rows.to_string(),
+                    ]);
+                }
+                table
+            })
+        }
+    }
+}
+
+async fn run_list(
+    backend: &Backend,
+    list: ListCommand,
+    format: Format,
+    out: &mut dyn Write,
+) -> ApiResult<()> {
+    match list {
+        ListCommand::Templates { archived } => {
+            let templates = if archived {
+                backend.get_archived_templates().await?
+            } else {
+                backend.get_templates().await?
+            };
+            emit(out, format, &templates, |templates| {
+                let mut table = Table::new(vec!["id", "name", "slots", "location", "limit"]);
+                for template in templates {
+                    let slots: Vec<&str> = template
+                        .compatible_slots
+                        .iter()
+                        .map(|s| s.to_db_string())
+                        .collect();
+                    table.push(vec![
+                        template.id.to_string(),
+                        template.name.clone(),
+                        slots.join(","),
+                        template.location_type.to_db_string().to_string(),
+                        or_dash(template.weekly_limit),
+                    ]);
+                }
+                table
+            })
+        }
+        ListCommand::Options { template } => {
+            let options = match template {
+                Some(template_id) => backend.get_options_by_template(template_id).await?,
+                None => backend.get_options().await?,
+            };
+            emit(out, format, &options, |options| {
+                let mut table = Table::new(vec!["id", "template", "name", "group"]);
+                for option in options {
+                    table.push(vec![
+                        option.id.to_string(),
+                        option.template_id.to_string(),
+                        option.name.clone(),
+                        or_dash(option.option_group.as_ref()),
+                    ]);
+                }
+                table
+            })
+        }
+        ListCommand::Tags => {
+            let tags = backend.get_tags().await?;
+            emit(out, format, &tags, |tags| {
+                let mut table = Table::new(vec![
+                    "id",
+                    "name",
+                    "display",
+                    "category",
+                    "suggestion",
+                    "parent",
+                ]);
+                for tag in tags {
+                    table.push(vec![
+                        tag.id.to_string(),
+                        tag.name.clone(),
+                        tag.display_name.clone(),
+                        tag.category.to_db_string().to_string(),
+                        or_dash(tag.weekly_suggestion),
+                        or_dash(tag.parent_tag_id),
+                    ]);
+                }
+                table
+            })
+        }
+        ListCommand::Entries { from, to, status } => {
+            let to = to.unwrap_or(from);
+            if from > to {
+                return Err(ApiError::ValidationError(
+                    "Start date must not be after end date".to_string(),
+                ));
+            }
+            let mut entries = backend.get_entries_by_date_range(from, to).await?;
+            if let Some(status) = status {
+                entries.retain(|e| e.status == status);
+            }
+            emit(out, format, &entries, |entries| entries_table(entries))
+        }
+    }
+}
+
+async fn run_plan(
+    pool: &SqlitePool,
+    plan: PlanCommand,
+    format: Format,
+    out: &mut dyn Write,
+) -> ApiResult<()> {
+    let result = match plan {
+        PlanCommand::Generate {
+            week,
+            locations,
+            seed,
+            preview,
+        } => {
+            let location_schedule = match locations.as_slice() {
+                [location] => vec![*location; 7],
+                _ => locations,
+            };
+            let request = GeneratePlanRequest {
+                week_start: week,
+                location_schedule,
+                seed: seed.unwrap_or_else(|| Local::now().timestamp().unsigned_abs()),
+                preview,
+            };
+            let plan = PlanningService::generate_week(pool, request).await?;
+            return emit(out, format, &plan, generated_plan_table);
+        }
+        PlanCommand::CopyDay(args) => {
+            PlanningService::copy_day(pool, args.from, args.to, args.on_conflict).await?
+        }
+        PlanCommand::CopyWeek(args) => {
+            PlanningService::copy_week(pool, args.from, args.to, args.on_conflict).await?
+        }
+    };
+
+    emit(out, format, &result, copy_result_table)
+}
+
+async fn run_log(
+    backend: &Backend,
+    args: LogArgs,
+    format: Format,
+    out: &mut dyn Write,
+) -> ApiResult<()> {
+    let entry = match (args.option, args.description) {
+        (_, Some(description)) => {
+            backend
+                .create_off_plan_entry(CreateOffPlanEntry {
+                    date: args.date,
+                    slot_type: args.slot,
+                    location: args.location,
+                    description,
+                    servings: args.servings,
+                    notes: args.notes,
+                    status: Some(args.status),
+                    tag_ids: args.tag_ids,
+                    estimated_nutrients: None,
+                })
+                .await?
+        }
+        (Some(meal_option_id), None) => {
+            let entry = CreateMealEntry {
+                meal_option_id,
+                date: args.date,
+                slot_type: args.slot,
+                location: args.location,
+                servings: args.servings,
+                notes: args.notes,
+                status: Some(args.status),
+                extra_options: args
+                    .extra_options
+                    .into_iter()
+                    .map(|meal_option_id| SelectedOption {
+                        meal_option_id,
+                        servings: None,
+                    })
+                    .collect(),
+            };
+            // Same checks as the create_entry command
+            let warnings = ValidationService::validate_meal_selection(
+                backend,
+                &entry.option_ids(),
+                &entry.slot_type,
+                entry.date,
+            )
+            .await?;
+            for warning in &warnings {
+                eprintln!("warning: {}", warning.message);
+            }
+            backend.create_entry(entry).await?
+        }
+        (None, None) => {
+            return Err(ApiError::ValidationError(
+                "Either --option or --description is required".to_string(),
+            ))
+        }
+    };
+
+    emit(out, format, &entry, |entry| {
+        entries_table(std::slice::from_ref(entry))
+    })
+}
+
+async fn run_sync(
+    pool: &SqlitePool,
+    sync: SyncCommand,
+    format: Format,
+    out: &mut dyn Write,
+) -> ApiResult<()> {
+    let status = match sync {
+        SyncCommand::Status => SyncRepository::get_status(pool).await?,
+        SyncCommand::Configure { url } => {
+            let token = match url {
+                Some(_) => Some(std::env::var(SYNC_TOKEN_ENV).map_err(|_| {
+                    ApiError::ValidationError(format!(
+                        "Set {} to the sync server token",
+                        SYNC_TOKEN_ENV
+                    ))
+                })?),
+                None => None,
+            };
+            let settings = SyncSettings {
+                server_url: url,
+                token,
+            };
+            SyncRepository::set_settings(pool, settings).await?
+        }
+        SyncCommand::Now => {
+            let report = SyncService::sync_configured(pool).await?;
+            for skipped in &report.pulled.skipped {
+                eprintln!(
+                    "skipped {} {}: {}",
+                    skipped.entity.to_db_string(),
+                    skipped.key,
+                    skipped.reason
+                );
+            }
+            return emit(out, format, &report, |report| {
+                let mut table = Table::new(vec!["direction", "applied", "stale", "skipped"]);
+                table.push(vec![
+                    "pulled".to_string(),
+                    report.pulled.applied.to_string(),
+                    report.pulled.stale.to_string(),
+                    report.pulled.skipped.len().to_string(),
+                ]);
+                table.push(vec![
+                    "pushed".to_string(),
+                    report.pushed.accepted.to_string(),
+                    report.pushed.stale.to_string(),
+                    "-".to_string(),
+                ]);
+                table
+            });
+        }
+    };
+
+    emit(out, format, &status, |status| {
+        let mut table = Table::new(vec!["device", "clock", "pending", "server", "last sync"]);
+        table.push(vec![
+            status.device_id.clone(),
+            status.clock.to_string(),
+            status.pending_changes.to_string(),
+            or_dash(status.server_url.as_ref()),
+            or_dash(status.last_synced_at.map(|at| at.format("%Y-%m-%d %H:%M"))),
+        ]);
+        table
+    })
+}
+
+async fn run_export(
+    pool: &SqlitePool,
+    export: ExportCommand,
+    out: &mut dyn Write,
+) -> ApiResult<()> {
+    match export {
+        ExportCommand::Json { output } => {
+            let document = ExportService::export_database(pool).await?;
+            let mut writer = open_output(output.as_ref(), out)?;
+            serde_json::to_writer_pretty(&mut writer, &document)
+                .map_err(|e| ApiError::InternalError(format!("Cannot write JSON: {}", e)))?;
+            writeln!(writer).map_err(io_error)
+        }
+        ExportCommand::Csv {
+            from,
+            to,
+            month,
+            output,
+        } => {
+            let (start, end) = match (month, from) {
+                (Some(range), _) => range,
+                (None, Some(from)) => (from, to.unwrap_or(from)),
+                (None, None) => {
+                    return Err(ApiError::ValidationError(
+                        "Either --from or --month is required".to_string(),
+                    ))
+                }
+            };
+            let writer = open_output(output.as_ref(), out)?;
+            let written = ExportService::write_entries_csv(pool, start, end, writer).await?;
+            eprintln!("{} entries exported", written);
+            Ok(())
+        }
+    }
+}
+
+/// A file when --output is given, the command output otherwise
+fn open_output<'a>(
+    path: Option<&PathBuf>,
+    out: &'a mut dyn Write,
+) -> ApiResult<Box<dyn Write + 'a>> {
+    match path {
+        Some(path) => {
+            let file = File::create(path).map_err(|e| {
+                ApiError::InternalError(format!("Cannot create {}: {}", path.display(), e))
+            })?;
+            Ok(Box::new(BufWriter::new(file)))
+        }
+        None => Ok(Box::new(out)),
+    }
+}
+
+fn entries_table(entries: &[MealEntry]) -> Table {
+    let mut table = Table::new(vec![
+        "id", "date", "slot", "status", "meal", "servings", "location",
+    ])
+    .empty_message("No entries");
+    for entry in entries {
+        let meal = if entry.is_off_plan() {
+            format!("{} (off-plan)", entry.option_name)
+        } else {
+            let names: Vec<&str> = entry
+                .options
+                .iter()
+                .map(|o| o.option_name.as_str())
+                .collect();
+            names.join(" + ")
+        };
+        table.push(vec![
+            entry.id.to_string(),
+            entry.date.to_string(),
+            entry.slot_type.to_string(),
+            entry.status.to_db_string().to_string(),
+            meal,
+            entry.servings.to_string(),
+            entry.location.to_db_string().to_string(),
+        ]);
+    }
+    table
+}
+
+fn generated_plan_table(plan: &GeneratedPlan) -> Table {
+    let mut table = Table::new(vec!["date", "slot", "location", "meal", "note"])
+        .empty_message("Every slot of the week is already filled");
+    for slot in &plan.slots {
+        let note = match (slot.entry_id, slot.over_suggestion.is_empty()) {
+            (None, true) => "preview".to_string(),
+            (None, false) => format!("preview, over: {}", slot.over_suggestion.join(", ")),
+            (Some(id), true) => format!("entry {}", id),
+            (Some(id), false) => format!("entry {}, over: {}", id, slot.over_suggestion.join(", ")),
+        };
+        table.push(vec![
+            slot.date.to_string(),
+            slot.slot_type.to_string(),
+            slot.location.to_db_string().to_string(),
+            format!("{} / {}", slot.template_name, slot.option_name),
+            note,
+        ]);
+    }
+    for slot in &plan.unfilled {
+        table.push(vec![
+            slot.date.to_string(),
+            slot.slot_type.to_string(),
+            "-".to_string(),
+            "(unfilled)".to_string(),
+            slot.reason.clone(),
+        ]);
+    }
+    table
+}
+
+fn emit_operation(
+    operation: Option<AuditOperation>,
+    empty_message: &'static str,
+    format: Format,
+    out: &mut dyn Write,
+) -> ApiResult<()> {
+    emit(out, format, &operation, |operation| {
+        let records = operation.as_ref().map_or(&[][..], |o| &o.records[..]);
+        audit_table(records).empty_message(empty_message)
+    })
+}
+
+fn audit_table(records: &[AuditRecord]) -> Table {
+    let mut table = Table::new(vec![
+        "operation",
+        "command",
+        "entity",
+        "id",
+        "change",
+        "status",
+        "at",
+    ]);
+    for record in records {
+        let change = match (&record.before, &record.after) {
+            (None, _) => "created",
+            (_, None) => "deleted",
+            _ => "updated",
+        };
+        table.push(vec![
+            record.operation.to_string(),
+            record.command.clone(),
+            record.entity.to_db_string().to_string(),
+            record.entity_id.to_string(),
+            change.to_string(),
+            record.status.to_db_string().to_string(),
+            record.created_at.format("%Y-%m-%d %H:%M").to_string(),
+        ]);
+    }
+    table
+}
+
+fn copy_result_table(result: &CopyPlanResult) -> Table {
+    let mut table =
+        Table::new(vec!["source", "date", "slot", "result"]).empty_message("Nothing to copy");
+    for copied in &result.copied {
+        let mut outcome = format!("copied as entry {}", copied.entry.id);
+        for warning in &copied.warnings {
+            outcome.push_str(&format!("; {}", warning.message));
+        }
+        table.push(vec![
+            copied.source_entry_id.to_string(),
+            copied.entry.date.to_string(),
+            copied.entry.slot_type.to_string(),
+            outcome,
+        ]);
+    }
+    for skipped in &result.skipped {
+        table.push(vec![
+            skipped.source_entry_id.to_string(),
+            skipped.date.to_string(),
+            skipped.slot_type.to_string(),
+            "skipped, slot occupied".to_string(),
+        ]);
+    }
+    for failed in &result.failed {
+        table.push(vec![
+            failed.source_entry_id.to_string(),
+            failed.date.to_string(),
+            failed.slot_type.to_string(),
+            format!("failed: {}", failed.error),
+        ]);
+    }
+    table
+}
+
+/// Parse a date as YYYY-MM-DD or relative to today
+fn parse_date(s: &str) -> Result<NaiveDate, String> {
+    let today = Local::now().date_naive();
+    match s {
+        "today" => Ok(today),
+        "yesterday" => Ok(today - Duration::days(1)),
+        "tomorrow" => Ok(today + Duration::days(1)),
+        _ => NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .map_err(|e| format!("Invalid date {}: {}", s, e)),
+    }
+}
+
+/// Parse a month as YYYY-MM, or "last" for the month before today's
+/// Returns its first and last day
+fn parse_month(s: &str) -> Result<(NaiveDate, NaiveDate), String> {
+    let first = if s == "last" {
+        let this_month = Local::now().date_naive().with_day(1).unwrap();
+        (this_month - Duration::days(1)).with_day(1).unwrap()
+    } else {
+        NaiveDate::parse_from_str(&format!("{}-01", s), "%Y-%m-%d")
+            .map_err(|_| format!("Invalid month {}, expected YYYY-MM or last", s))?
+    };
+    let next = first
+        .checked_add_months(chrono::Months::new(1))
+        .ok_or_else(|| format!("Invalid month {}", s))?;
+    Ok((first, next - Duration::days(1)))
+}
+
+fn parse_slot(s: &str) -> Result<SlotType, String> {
+    SlotType::from_db_string(s)
+}
+
+fn parse_location(s: &str) -> Result<LocationType, String> {
+    LocationType::from_db_string(s)
+}
+
+fn parse_status(s: &str) -> Result<EntryStatus, String> {
+    EntryStatus::from_db_string(s)
+}
+
+fn parse_entity(s: &str) -> Result<SyncEntity, String> {
+    SyncEntity::from_db_string(s)
+}
+
+fn parse_conflict(s: &str) -> Result<CopyConflictStrategy, String> {
+    match s {
+        "skip" => Ok(CopyConflictStrategy::Skip),
+        "overwrite" => Ok(CopyConflictStrategy::Overwrite),
+        "abort" => Ok(CopyConflictStrategy::Abort),
+        _ => Err(format!("Invalid conflict strategy: {}", s)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nutrition_helper::models::{CreateMealOption, CreateMealTemplate};
+    use nutrition_helper::repository::{MealOptionRepository, MealTemplateRepository};
+    use tempfile::TempDir;
+
+    async fn setup() -> (SqlitePool, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let pool = db::initialize_database(temp_dir.path().join("cli.db"))
+            .await
+            .unwrap();
+        (pool, temp_dir)
+    }
+
+    async fn run_args(pool: &SqlitePool, args: &[&str]) -> ApiResult<String> {
+        let cli =
+            Cli::try_parse_from(["nutrition-cli", "--db", "unused.db"].iter().chain(args)).unwrap();
+        let mut out = Vec::new();
+        run(&Backend::Sqlite(pool.clone()), cli, &mut out).await?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_parse_dates() {
+        let today = Local::now().date_naive();
+        assert_eq!(parse_date("yesterday").unwrap(), today - Duration::days(1));
+        assert_eq!(
+            parse_date("2024-11-04").unwrap(),
+            NaiveDate::from_ymd_opt(2024, 11, 4).unwrap()
+        );
+        assert!(parse_date("04/11/2024").is_err());
+
+        assert_eq!(
+            parse_month("2024-02").unwrap(),
+            (
+                NaiveDate::from_ymd_opt(2024, 2, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()
+            )
+        );
+        let (first, last) = parse_month("last").unwrap();
+        assert_eq!(first.day(), 1);
+        assert_eq!(last + Duration::days(1), today.with_day(1).unwrap());
+        assert!(parse_month("2024-13").is_err());
+    }
+
+    #[test]
+    fn test_log_requires_option_or_description() {
+        let base = ["nutrition-cli", "--db", "x.db", "log", "--slot", "lunch"];
+        assert!(Cli::try_parse_from(base).is_err());
+        assert!(Cli::try_parse_from(base.iter().chain(&["--option", "1"])).is_ok());
+        assert!(Cli::try_parse_from(base.iter().chain(&["--description", "Pizza"])).is_ok());
+        assert!(Cli::try_parse_from(base.iter().chain(&[
+            "--option",
+            "1",
+            "--description",
+            "Pizza"
+        ]))
+        .is_err());
+        assert!(Cli::try_parse_from(base.iter().chain(&["--option", "1", "--tag", "2"])).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_log_and_list_entries() {
+        let (pool, _temp_dir) = setup().await;
+        let template = MealTemplateRepository::create(
+            &pool,
+            CreateMealTemplate {
+                name: "Pasta".to_string(),
+                description: None,
+                compatible_slots: vec![SlotType::LUNCH],
+                location_type: LocationType::Any,
+                weekly_limit: Some(1),
+            },
+        )
+        .await
+        .unwrap();
+        let option = MealOptionRepository::create(
+            &pool,
+            CreateMealOption {
+                template_id: template.id,
+                name: "Pasta al pomodoro".to_string(),
+                description: None,
+                nutritional_notes: None,
+            },
+        )
+        .await
+        .unwrap();
+        let option_id = option.id.to_string();
+
+        let logged = run_args(
+            &pool,
+            &[
+                "--format",
+                "json",
+                "log",
+                "--option",
+                &option_id,
+                "--slot",
+                "lunch",
+                "--date",
+                "2024-11-04",
+            ],
+        )
+        .await
+        .unwrap();
+        let entry: MealEntry = serde_json::from_str(&logged).unwrap();
+        assert_eq!(entry.meal_option_id, Some(option.id));
+        assert_eq!(entry.status, EntryStatus::Eaten);
+
+        // The weekly limit is enforced like in the app
+        let error = run_args(
+            &pool,
+            &[
+                "log",
+                "--option",
+                &option_id,
+                "--slot",
+                "lunch",
+                "--date",
+                "2024-11-05",
+            ],
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(error, ApiError::BusinessValidationError(_)));
+
+        let listed = run_args(
+            &pool,
+            &[
+                "list",
+                "entries",
+                "--from",
+                "2024-11-04",
+                "--to",
+                "2024-11-10",
+            ],
+        )
+        .await
+        .unwrap();
+        let lines: Vec<&str> = listed.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("ID"));
+        assert!(lines[1].contains("Pasta al pomodoro"));
+        assert!(lines[1].contains("eaten"));
+
+        let limits = run_args(&pool, &["limits", "--week", "2024-11-06"])
+            .await
+            .unwrap();
+        assert!(limits.contains("template  Pasta"));
+        assert!(limits.contains("full"));
+    }
+}
@@ -0,0 +1,123 @@
+// CLI output
+// JSON documents or plain aligned tables written to any writer
+
+use clap::ValueEnum;
+use nutrition_helper::{ApiError, ApiResult};
+use serde::Serialize;
+use std::io::Write;
+
+/// How command results are printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Table, // Aligned columns for reading in a terminal
+    Json,  // The same models the app returns, for scripts
+}
+
+/// A plain text table, columns padded to their widest cell
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    headers: Vec<&'static str>,
+    rows: Vec<Vec<String>>,
+    empty_message: &'static str, // Printed instead of a header-only table
+}
+
+impl Table {
+    pub fn new(headers: Vec<&'static str>) -> Self {
+        Self {
+            headers,
+            rows: Vec::new(),
+            empty_message: "No results",
+        }
+    }
+
+    pub fn empty_message(mut self, message: &'static str) -> Self {
+        self.empty_message = message;
+        self
+    }
+
+    pub fn push(&mut self, row: Vec<String>) {
+        debug_assert_eq!(row.len(), self.headers.len());
+        self.rows.push(row);
+    }
+
+    pub fn write(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        if self.rows.is_empty() {
+            return writeln!(out, "{}", self.empty_message);
+        }
+
+        let mut widths: Vec<usize> = self.headers.iter().map(|h| h.chars().count()).collect();
+        for row in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let header: Vec<String> = self.headers.iter().map(|h| h.to_uppercase()).collect();
+        Self::write_row(out, &header, &widths)?;
+        for row in &self.rows {
+            Self::write_row(out, row, &widths)?;
+        }
+        Ok(())
+    }
+
+    fn write_row(out: &mut dyn Write, cells: &[String], widths: &[usize]) -> std::io::Result<()> {
+        let line: Vec<String> = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        writeln!(out, "{}", line.join("  ").trim_end())
+    }
+}
+
+/// Print a result as pretty JSON or as the table built from it
+pub fn emit<T: Serialize>(
+    out: &mut dyn Write,
+    format: Format,
+    value: &T,
+    table: impl FnOnce(&T) -> Table,
+) -> ApiResult<()> {
+    match format {
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut *out, value)
+                .map_err(|e| ApiError::InternalError(format!("Cannot write JSON: {}", e)))?;
+            writeln!(out).map_err(io_error)
+        }
+        Format::Table => table(value).write(out).map_err(io_error),
+    }
+}
+
+/// Optional values print as a dash
+pub fn or_dash<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(|| "-".to_string(), |v| v.to_string())
+}
+
+pub fn io_error(e: std::io::Error) -> ApiError {
+    ApiError::InternalError(format!("Cannot write output: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_alignment() {
+        let mut table = Table::new(vec!["id", "name"]);
+        table.push(vec!["1".to_string(), "Pasta".to_string()]);
+        table.push(vec!["12".to_string(), "Riso".to_string()]);
+
+        let mut out = Vec::new();
+        table.write(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "ID  NAME\n1   Pasta\n12  Riso\n"
+        );
+
+        let mut out = Vec::new();
+        Table::new(vec!["id"])
+            .empty_message("Nothing logged")
+            .write(&mut out)
+            .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "Nothing logged\n");
+    }
+}
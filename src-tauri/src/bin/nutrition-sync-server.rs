@@ -0,0 +1,65 @@
+// Reference sync server
+// Relays change batches between devices; see src/server/sync.rs for the protocol.
+// The bearer token is read from NUTRITION_SYNC_TOKEN.
+
+use clap::Parser;
+use nutrition_helper::server::sync::{open_sync_store, run_sync_server, SYNC_DEFAULT_ADDR};
+use nutrition_helper::server::ServerConfig;
+use nutrition_helper::services::SYNC_TOKEN_ENV;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "nutrition-sync-server",
+    version,
+    about = "Relay changes between Nutrition Helper devices"
+)]
+struct Cli {
+    /// Relay database file, created and migrated if needed
+    #[arg(long, default_value = "nutrition_sync.db")]
+    db: PathBuf,
+
+    /// Listen address
+    #[arg(long, default_value_t = SYNC_DEFAULT_ADDR)]
+    addr: SocketAddr,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let Ok(token) = std::env::var(SYNC_TOKEN_ENV) else {
+        eprintln!("error: set {} to the sync bearer token", SYNC_TOKEN_ENV);
+        return ExitCode::FAILURE;
+    };
+    let config = ServerConfig {
+        addr: cli.addr,
+        token,
+    };
+    if let Err(e) = config.validate() {
+        eprintln!("error: {}", e);
+        return ExitCode::FAILURE;
+    }
+
+    let pool = match open_sync_store(&cli.db).await {
+        Ok(pool) => pool,
+        Err(e) => {
+            eprintln!("error: cannot open {}: {}", cli.db.display(), e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    eprintln!("Relaying sync changes on http://{}/sync", cli.addr);
+    let result = run_sync_server(pool.clone(), &config).await;
+    pool.close().await;
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
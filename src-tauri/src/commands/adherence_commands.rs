@@ -0,0 +1,27 @@
+// Adherence Tauri commands
+// Command handlers for planned vs. eaten statistics
+
+use crate::error::{ApiError, ApiResult};
+use crate::models::AdherenceReport;
+use crate::repository::Backend;
+use crate::services::AdherenceService;
+use chrono::NaiveDate;
+use tauri::State;
+
+/// Get plan adherence for a date range (inclusive), per day, per ISO week and overall
+/// Adherence is the percentage of logged slots (eaten or skipped) eaten as planned
+#[tauri::command]
+pub async fn get_adherence(
+    start_date: String, // Format: "YYYY-MM-DD"
+    end_date: String,   // Format: "YYYY-MM-DD"
+    backend: State<'_, Backend>,
+) -> ApiResult<AdherenceReport> {
+    let start = NaiveDate::parse_from_str(&start_date, "%Y-%m-%d")
+        .map_err(|e| ApiError::ValidationError(format!("Invalid start date: {}", e)))?;
+    let end = NaiveDate::parse_from_str(&end_date, "%Y-%m-%d")
+        .map_err(|e| ApiError::ValidationError(format!("Invalid end date: {}", e)))?;
+
+    AdherenceService::get_adherence(backend.sqlite()?, start, end)
+        .await
+        .map_err(Into::into)
+}
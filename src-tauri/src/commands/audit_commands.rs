@@ -0,0 +1,37 @@
+// Audit-related Tauri commands
+// Command handlers for undoing and redoing changes and browsing their history
+
+use crate::error::ApiResult;
+use crate::models::{AuditOperation, AuditRecord, SyncEntity};
+use crate::repository::{AuditRepository, Backend};
+use tauri::State;
+
+/// Undo the latest change still in effect
+/// Returns None when there is nothing to undo
+#[tauri::command]
+pub async fn undo_last(backend: State<'_, Backend>) -> ApiResult<Option<AuditOperation>> {
+    AuditRepository::undo_last(backend.sqlite()?)
+        .await
+        .map_err(Into::into)
+}
+
+/// Redo the change undone last
+/// Returns None when nothing was undone since the last change
+#[tauri::command]
+pub async fn redo(backend: State<'_, Backend>) -> ApiResult<Option<AuditOperation>> {
+    AuditRepository::redo(backend.sqlite()?)
+        .await
+        .map_err(Into::into)
+}
+
+/// Get every recorded change to an entity, oldest first
+#[tauri::command]
+pub async fn get_history(
+    entity: SyncEntity,
+    id: i64,
+    backend: State<'_, Backend>,
+) -> ApiResult<Vec<AuditRecord>> {
+    AuditRepository::get_history(backend.sqlite()?, entity, id)
+        .await
+        .map_err(Into::into)
+}
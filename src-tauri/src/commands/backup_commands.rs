@@ -0,0 +1,52 @@
+// Backup-related Tauri commands
+// Command handlers for listing, creating and restoring database snapshots
+
+use crate::db::backup;
+use crate::db::{BackupConfig, BackupInfo};
+use crate::error::{ApiError, ApiResult};
+use crate::repository::Backend;
+use tauri::State;
+
+/// List available backups, newest first
+#[tauri::command]
+pub async fn list_backups(
+    backend: State<'_, Backend>,
+    config: State<'_, BackupConfig>,
+) -> ApiResult<Vec<BackupInfo>> {
+    backend.sqlite()?;
+    backup::list_backups(config.inner()).map_err(Into::into)
+}
+
+/// Take a manual backup now
+#[tauri::command]
+pub async fn create_backup(
+    backend: State<'_, Backend>,
+    config: State<'_, BackupConfig>,
+) -> ApiResult<BackupInfo> {
+    backup::create_backup(backend.sqlite()?, config.inner(), "manual")
+        .await
+        .map_err(Into::into)
+}
+
+/// Restore a backup by file name (as returned by `list_backups`)
+/// The current state is snapshotted first; that snapshot is returned so it can be restored back
+#[tauri::command]
+pub async fn restore_backup(
+    file_name: String,
+    backend: State<'_, Backend>,
+    config: State<'_, BackupConfig>,
+) -> ApiResult<BackupInfo> {
+    let exists = backup::list_backups(config.inner())?
+        .iter()
+        .any(|b| b.file_name == file_name);
+    if !exists {
+        return Err(ApiError::NotFound(format!(
+            "Backup '{}' not found",
+            file_name
+        )));
+    }
+
+    backup::restore_backup(backend.sqlite()?, config.inner(), &file_name)
+        .await
+        .map_err(Into::into)
+}
@@ -0,0 +1,88 @@
+// Export/import-related Tauri commands
+// Command handlers for moving data in and out of the database
+
+use crate::error::{ApiError, ApiResult};
+use crate::models::{
+    DatabaseExport, ImportSummary, LibraryPack, LibraryPackImport, PackConflictStrategy,
+};
+use crate::repository::Backend;
+use crate::services::{ExportService, LibraryPackService};
+use chrono::NaiveDate;
+use std::fs::File;
+use std::io::BufWriter;
+use tauri::State;
+use tokio::runtime::Handle;
+
+/// Export the whole database as a versioned JSON document
+#[tauri::command]
+pub async fn export_database(backend: State<'_, Backend>) -> ApiResult<DatabaseExport> {
+    ExportService::export_database(backend.sqlite()?).await
+}
+
+/// Import a JSON document produced by `export_database`
+/// All rows get new IDs; the import runs in a single transaction
+#[tauri::command]
+pub async fn import_database(
+    document: serde_json::Value,
+    backend: State<'_, Backend>,
+) -> ApiResult<ImportSummary> {
+    ExportService::import_database(backend.sqlite()?, document).await
+}
+
+/// Export templates, options and tags as a library pack, without meal history
+#[tauri::command]
+pub async fn export_library_pack(backend: State<'_, Backend>) -> ApiResult<LibraryPack> {
+    LibraryPackService::export_pack(backend.sqlite()?).await
+}
+
+/// Merge a library pack into the library by name
+/// Items that differ from the library are reported and resolved with `on_conflict`;
+/// with `preview` set nothing is written
+#[tauri::command]
+pub async fn import_library_pack(
+    document: serde_json::Value,
+    on_conflict: PackConflictStrategy,
+    preview: bool,
+    backend: State<'_, Backend>,
+) -> ApiResult<LibraryPackImport> {
+    LibraryPackService::import_pack(backend.sqlite()?, document, on_conflict, preview).await
+}
+
+/// Export meal entries in a date range to a CSV file
+/// Columns: date, slot, location, template, option, planned_option, servings, status, off_plan,
+/// notes, tags
+/// Returns the number of entries written
+#[tauri::command]
+pub async fn export_entries_csv(
+    start_date: String, // Format: "YYYY-MM-DD"
+    end_date: String,   // Format: "YYYY-MM-DD"
+    path: String,
+    backend: State<'_, Backend>,
+) -> ApiResult<usize> {
+    let start = NaiveDate::parse_from_str(&start_date, "%Y-%m-%d")
+        .map_err(|e| ApiError::ValidationError(format!("Invalid start date: {}", e)))?;
+    let end = NaiveDate::parse_from_str(&end_date, "%Y-%m-%d")
+        .map_err(|e| ApiError::ValidationError(format!("Invalid end date: {}", e)))?;
+
+    // The CSV writer is synchronous, so the whole export runs on a blocking thread
+    let pool = backend.sqlite()?.clone();
+    let runtime = Handle::current();
+    tokio::task::spawn_blocking(move || {
+        let file = File::create(&path)
+            .map_err(|e| ApiError::InternalError(format!("Cannot create {}: {}", path, e)))?;
+
+        let result = runtime.block_on(ExportService::write_entries_csv(
+            &pool,
+            start,
+            end,
+            BufWriter::new(file),
+        ));
+        if result.is_err() {
+            // Don't leave a truncated spreadsheet behind
+            let _ = std::fs::remove_file(&path);
+        }
+        result
+    })
+    .await
+    .map_err(|e| ApiError::InternalError(format!("CSV export task failed: {}", e)))?
+}
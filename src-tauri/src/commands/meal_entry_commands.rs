@@ -1,37 +1,33 @@
 // MealEntry-related Tauri commands
 // Command handlers for meal entry CRUD operations and weekly usage tracking
 
-use crate::error::ApiResult;
+use crate::error::{ApiError, ApiResult};
 use crate::models::{
-    CreateMealEntry, MealEntry, SlotType, UpdateMealEntry, WeeklyTagUsage, WeeklyUsage,
+    CreateMealEntry, CreateOffPlanEntry, EntryStatus, LogActualMeal, MealEntry, SlotType,
+    UpdateMealEntry, WeeklyTagUsage, WeeklyTagUsageNode, WeeklyTemplateUsage, WeeklyUsage,
 };
-use crate::repository::MealEntryRepository;
+use crate::repository::{Backend, MealEntryStore};
 use crate::services::{ValidationService, ValidationWarning};
 use chrono::NaiveDate;
-use sqlx::SqlitePool;
 use tauri::State;
 
 /// Get a meal entry by ID
 #[tauri::command]
-pub async fn get_entry_by_id(id: i64, pool: State<'_, SqlitePool>) -> ApiResult<Option<MealEntry>> {
-    MealEntryRepository::get_by_id(pool.inner(), id)
-        .await
-        .map_err(Into::into)
+pub async fn get_entry_by_id(id: i64, backend: State<'_, Backend>) -> ApiResult<Option<MealEntry>> {
+    backend.get_entry(id).await.map_err(Into::into)
 }
 
 /// Get all meal entries for a specific date
 #[tauri::command]
 pub async fn get_entries_by_date(
     date: String, // Format: "YYYY-MM-DD"
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<Vec<MealEntry>> {
     let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|e| {
         crate::error::ApiError::ValidationError(format!("Invalid date format: {}", e))
     })?;
 
-    MealEntryRepository::get_by_date(pool.inner(), date)
-        .await
-        .map_err(Into::into)
+    backend.get_entries_by_date(date).await.map_err(Into::into)
 }
 
 /// Get all meal entries in a date range
@@ -39,7 +35,7 @@ pub async fn get_entries_by_date(
 pub async fn get_entries_by_date_range(
     start_date: String, // Format: "YYYY-MM-DD"
     end_date: String,   // Format: "YYYY-MM-DD"
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<Vec<MealEntry>> {
     let start = NaiveDate::parse_from_str(&start_date, "%Y-%m-%d").map_err(|e| {
         crate::error::ApiError::ValidationError(format!("Invalid start date: {}", e))
@@ -47,7 +43,8 @@ pub async fn get_entries_by_date_range(
     let end = NaiveDate::parse_from_str(&end_date, "%Y-%m-%d")
         .map_err(|e| crate::error::ApiError::ValidationError(format!("Invalid end date: {}", e)))?;
 
-    MealEntryRepository::get_by_date_range(pool.inner(), start, end)
+    backend
+        .get_entries_by_date_range(start, end)
         .await
         .map_err(Into::into)
 }
@@ -57,24 +54,26 @@ pub async fn get_entries_by_date_range(
 pub async fn get_entry_by_date_and_slot(
     date: String, // Format: "YYYY-MM-DD"
     slot: SlotType,
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<Vec<MealEntry>> {
     let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|e| {
         crate::error::ApiError::ValidationError(format!("Invalid date format: {}", e))
     })?;
 
-    MealEntryRepository::get_by_date_and_slot(pool.inner(), date, slot)
+    backend
+        .get_entries_by_date_and_slot(date, &slot)
         .await
         .map_err(Into::into)
 }
 
-/// Get all entries by completion status
+/// Get all entries with a status (planned, eaten or skipped)
 #[tauri::command]
-pub async fn get_entries_by_completed(
-    completed: bool,
-    pool: State<'_, SqlitePool>,
+pub async fn get_entries_by_status(
+    status: EntryStatus,
+    backend: State<'_, Backend>,
 ) -> ApiResult<Vec<MealEntry>> {
-    MealEntryRepository::get_by_completed(pool.inner(), completed)
+    backend
+        .get_entries_by_status(status)
         .await
         .map_err(Into::into)
 }
@@ -83,9 +82,10 @@ pub async fn get_entries_by_completed(
 #[tauri::command]
 pub async fn get_entries_by_meal_option(
     meal_option_id: i64,
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<Vec<MealEntry>> {
-    MealEntryRepository::get_by_meal_option(pool.inner(), meal_option_id)
+    backend
+        .get_entries_by_option(meal_option_id)
         .await
         .map_err(Into::into)
 }
@@ -95,11 +95,9 @@ pub async fn get_entries_by_meal_option(
 #[tauri::command]
 pub async fn get_recent_entries(
     limit: i32,
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<Vec<MealEntry>> {
-    MealEntryRepository::get_recent_entries(pool.inner(), limit)
-        .await
-        .map_err(Into::into)
+    backend.get_recent_entries(limit).await.map_err(Into::into)
 }
 
 /// Get weekly usage count for a specific meal option
@@ -107,21 +105,51 @@ pub async fn get_recent_entries(
 pub async fn get_weekly_usage(
     meal_option_id: i64,
     week: String, // Format: "YYYY-WW" - ISO week format
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<Option<WeeklyUsage>> {
-    MealEntryRepository::get_weekly_usage(pool.inner(), meal_option_id, &week)
+    backend
+        .get_weekly_usage(meal_option_id, &week)
+        .await
+        .map_err(Into::into)
+}
+
+/// Get weekly usage per template (all options of a template combined)
+/// Includes every template with a weekly limit, plus any other template used that week
+#[tauri::command]
+pub async fn get_weekly_template_usage(
+    week: String, // Format: "YYYY-WW" - ISO week format
+    backend: State<'_, Backend>,
+) -> ApiResult<Vec<WeeklyTemplateUsage>> {
+    backend
+        .get_all_weekly_template_usage(&week)
         .await
         .map_err(Into::into)
 }
 
 /// Get weekly usage count for a specific tag
+/// Includes entries tagged with any of its descendants (e.g. "pasta_integrale" counts as "pasta")
 #[tauri::command]
 pub async fn get_weekly_tag_usage(
     tag_id: i64,
     week: String, // Format: "YYYY-WW" - ISO week format
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<Option<WeeklyTagUsage>> {
-    MealEntryRepository::get_weekly_tag_usage(pool.inner(), tag_id, &week)
+    backend
+        .get_weekly_tag_usage(tag_id, &week)
+        .await
+        .map_err(Into::into)
+}
+
+/// Get weekly usage for a tag's whole lineage: ancestors, the tag itself and descendants
+/// Each node carries its direct count and the count rolled up over its descendants
+#[tauri::command]
+pub async fn get_weekly_tag_usage_breakdown(
+    tag_id: i64,
+    week: String, // Format: "YYYY-WW" - ISO week format
+    backend: State<'_, Backend>,
+) -> ApiResult<Vec<WeeklyTagUsageNode>> {
+    backend
+        .get_weekly_tag_usage_breakdown(tag_id, &week)
         .await
         .map_err(Into::into)
 }
@@ -134,41 +162,93 @@ pub async fn get_weekly_tag_usage(
 #[tauri::command]
 pub async fn create_entry(
     entry: CreateMealEntry,
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<(MealEntry, Vec<ValidationWarning>)> {
     // Validate the entry before creation
-    let warnings = ValidationService::validate_meal_entry(
-        pool.inner(),
-        entry.meal_option_id,
-        entry.slot_type,
+    let warnings = ValidationService::validate_meal_selection(
+        backend.inner(),
+        &entry.option_ids(),
+        &entry.slot_type,
         entry.date,
     )
     .await?;
 
     // If validation passed, create the entry
-    let created_entry = MealEntryRepository::create(pool.inner(), entry).await?;
+    let created_entry = backend.create_entry(entry).await?;
 
     Ok((created_entry, warnings))
 }
 
+/// Log an off-plan meal (restaurant, ad-hoc) with a free-text description
+/// No template rules apply; the estimated tags count toward weekly tag usage and
+/// the estimated nutrients toward nutrient totals
+#[tauri::command]
+pub async fn create_off_plan_entry(
+    entry: CreateOffPlanEntry,
+    backend: State<'_, Backend>,
+) -> ApiResult<MealEntry> {
+    backend
+        .create_off_plan_entry(entry)
+        .await
+        .map_err(Into::into)
+}
+
 /// Update an existing meal entry
 #[tauri::command]
 pub async fn update_entry(
     id: i64,
     updates: UpdateMealEntry,
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<MealEntry> {
-    MealEntryRepository::update(pool.inner(), id, updates)
-        .await
-        .map_err(Into::into)
+    backend.update_entry(id, updates).await.map_err(Into::into)
+}
+
+/// Log what was actually eaten for an entry
+/// The eaten options are validated like in `create_entry`; the planned options are
+/// kept, so a swap shows up in adherence. An entry already logged as eaten has to
+/// be set back to planned first, otherwise it would count against its own limits.
+#[tauri::command]
+pub async fn log_actual(
+    entry_id: i64,
+    actual: LogActualMeal,
+    backend: State<'_, Backend>,
+) -> ApiResult<(MealEntry, Vec<ValidationWarning>)> {
+    let entry = backend
+        .get_entry(entry_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Meal entry {} not found", entry_id)))?;
+
+    if entry.status == EntryStatus::Eaten {
+        return Err(ApiError::ValidationError(format!(
+            "Meal entry {} is already logged as eaten",
+            entry_id
+        )));
+    }
+
+    if entry.is_off_plan() {
+        return Err(ApiError::ValidationError(format!(
+            "Meal entry {} is off-plan and has no plan to log against",
+            entry_id
+        )));
+    }
+
+    let warnings = ValidationService::validate_meal_selection(
+        backend.inner(),
+        &actual.option_ids(),
+        &entry.slot_type,
+        entry.date,
+    )
+    .await?;
+
+    let logged = backend.log_actual(entry_id, actual).await?;
+
+    Ok((logged, warnings))
 }
 
 /// Delete a meal entry
 #[tauri::command]
-pub async fn delete_entry(id: i64, pool: State<'_, SqlitePool>) -> ApiResult<()> {
-    MealEntryRepository::delete(pool.inner(), id)
-        .await
-        .map_err(Into::into)
+pub async fn delete_entry(id: i64, backend: State<'_, Backend>) -> ApiResult<()> {
+    backend.delete_entry(id).await.map_err(Into::into)
 }
 
 /// Validate a potential meal entry without creating it
@@ -177,15 +257,20 @@ pub async fn delete_entry(id: i64, pool: State<'_, SqlitePool>) -> ApiResult<()>
 #[tauri::command]
 pub async fn validate_entry(
     meal_option_id: i64,
+    extra_option_ids: Option<Vec<i64>>, // Other options of a composite meal
     slot: SlotType,
     date: String, // Format: "YYYY-MM-DD"
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<Vec<ValidationWarning>> {
     let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|e| {
         crate::error::ApiError::ValidationError(format!("Invalid date format: {}", e))
     })?;
 
-    ValidationService::validate_meal_entry(pool.inner(), meal_option_id, slot, date)
+    let option_ids: Vec<i64> = std::iter::once(meal_option_id)
+        .chain(extra_option_ids.unwrap_or_default())
+        .collect();
+
+    ValidationService::validate_meal_selection(backend.inner(), &option_ids, &slot, date)
         .await
         .map_err(Into::into)
 }
@@ -196,8 +281,10 @@ mod tests {
     use crate::models::{
         CreateMealOption, CreateMealTemplate, CreateTag, LocationType, TagCategory,
     };
+    use crate::repository::MealEntryRepository;
     use crate::repository::{MealOptionRepository, MealTemplateRepository, TagRepository};
     use sqlx::sqlite::SqlitePoolOptions;
+    use sqlx::SqlitePool;
 
     async fn setup_test_pool() -> SqlitePool {
         let pool = SqlitePoolOptions::new()
@@ -220,7 +307,7 @@ mod tests {
             name: "Test Template".to_string(),
             description: None,
             location_type: LocationType::Home,
-            compatible_slots: vec![SlotType::Breakfast],
+            compatible_slots: vec![SlotType::BREAKFAST],
             weekly_limit: None,
         };
         let template_id = MealTemplateRepository::create(pool, template)
@@ -264,21 +351,22 @@ mod tests {
         let entry = CreateMealEntry {
             meal_option_id: option_id,
             date,
-            slot_type: SlotType::Breakfast,
+            slot_type: SlotType::BREAKFAST,
             location: LocationType::Home,
             servings: Some(1.0),
             notes: Some("Test entry".to_string()),
-            completed: Some(false),
+            status: Some(EntryStatus::Planned),
+            extra_options: vec![],
         };
 
         let created = MealEntryRepository::create(&pool, entry)
             .await
             .expect("Failed to create entry");
 
-        assert_eq!(created.meal_option_id, option_id);
+        assert_eq!(created.meal_option_id, Some(option_id));
         assert_eq!(created.date, date);
-        assert_eq!(created.slot_type, SlotType::Breakfast);
-        assert!(!created.completed);
+        assert_eq!(created.slot_type, SlotType::BREAKFAST);
+        assert_eq!(created.status, EntryStatus::Planned);
 
         let fetched = MealEntryRepository::get_by_id(&pool, created.id)
             .await
@@ -286,7 +374,7 @@ mod tests {
             .expect("Entry not found");
 
         assert_eq!(fetched.id, created.id);
-        assert_eq!(fetched.meal_option_id, option_id);
+        assert_eq!(fetched.meal_option_id, Some(option_id));
     }
 
     #[tokio::test]
@@ -301,31 +389,34 @@ mod tests {
         let entry1 = CreateMealEntry {
             meal_option_id: option_id,
             date: date1,
-            slot_type: SlotType::Breakfast,
+            slot_type: SlotType::BREAKFAST,
             location: LocationType::Home,
             servings: None,
             notes: None,
-            completed: None,
+            status: None,
+            extra_options: vec![],
         };
 
         let entry2 = CreateMealEntry {
             meal_option_id: option_id,
             date: date1,
-            slot_type: SlotType::Lunch,
+            slot_type: SlotType::LUNCH,
             location: LocationType::Home,
             servings: None,
             notes: None,
-            completed: None,
+            status: None,
+            extra_options: vec![],
         };
 
         let entry3 = CreateMealEntry {
             meal_option_id: option_id,
             date: date2,
-            slot_type: SlotType::Breakfast,
+            slot_type: SlotType::BREAKFAST,
             location: LocationType::Home,
             servings: None,
             notes: None,
-            completed: None,
+            status: None,
+            extra_options: vec![],
         };
 
         MealEntryRepository::create(&pool, entry1)
@@ -358,11 +449,12 @@ mod tests {
             let entry = CreateMealEntry {
                 meal_option_id: option_id,
                 date: *date,
-                slot_type: SlotType::Breakfast,
+                slot_type: SlotType::BREAKFAST,
                 location: LocationType::Home,
                 servings: None,
                 notes: None,
-                completed: None,
+                status: None,
+                extra_options: vec![],
             };
             MealEntryRepository::create(&pool, entry)
                 .await
@@ -386,18 +478,19 @@ mod tests {
         let entry = CreateMealEntry {
             meal_option_id: option_id,
             date,
-            slot_type: SlotType::Breakfast,
+            slot_type: SlotType::BREAKFAST,
             location: LocationType::Home,
             servings: None,
             notes: None,
-            completed: None,
+            status: None,
+            extra_options: vec![],
         };
 
         let created = MealEntryRepository::create(&pool, entry)
             .await
             .expect("Failed to create entry");
 
-        let fetched = MealEntryRepository::get_by_date_and_slot(&pool, date, SlotType::Breakfast)
+        let fetched = MealEntryRepository::get_by_date_and_slot(&pool, date, &SlotType::BREAKFAST)
             .await
             .expect("Failed to get entry");
 
@@ -405,7 +498,7 @@ mod tests {
         assert_eq!(fetched[0].id, created.id);
 
         // Try getting a different slot (should be empty)
-        let not_found = MealEntryRepository::get_by_date_and_slot(&pool, date, SlotType::Lunch)
+        let not_found = MealEntryRepository::get_by_date_and_slot(&pool, date, &SlotType::LUNCH)
             .await
             .expect("Failed to query");
 
@@ -413,7 +506,7 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_get_entries_by_completed() {
+    async fn test_get_entries_by_status() {
         let pool = setup_test_pool().await;
         let option_id = create_test_option(&pool).await;
 
@@ -423,41 +516,43 @@ mod tests {
         let planned = CreateMealEntry {
             meal_option_id: option_id,
             date,
-            slot_type: SlotType::Breakfast,
+            slot_type: SlotType::BREAKFAST,
             location: LocationType::Home,
             servings: None,
             notes: None,
-            completed: Some(false),
+            status: Some(EntryStatus::Planned),
+            extra_options: vec![],
         };
 
-        // Create completed entry
-        let completed = CreateMealEntry {
+        // Create eaten entry
+        let eaten = CreateMealEntry {
             meal_option_id: option_id,
             date,
-            slot_type: SlotType::Lunch,
+            slot_type: SlotType::LUNCH,
             location: LocationType::Home,
             servings: None,
             notes: None,
-            completed: Some(true),
+            status: Some(EntryStatus::Eaten),
+            extra_options: vec![],
         };
 
         MealEntryRepository::create(&pool, planned)
             .await
             .expect("Failed to create planned entry");
-        MealEntryRepository::create(&pool, completed)
+        MealEntryRepository::create(&pool, eaten)
             .await
-            .expect("Failed to create completed entry");
+            .expect("Failed to create eaten entry");
 
-        let planned_entries = MealEntryRepository::get_by_completed(&pool, false)
+        let planned_entries = MealEntryRepository::get_by_status(&pool, EntryStatus::Planned)
             .await
             .expect("Failed to get planned entries");
 
-        let completed_entries = MealEntryRepository::get_by_completed(&pool, true)
+        let eaten_entries = MealEntryRepository::get_by_status(&pool, EntryStatus::Eaten)
             .await
-            .expect("Failed to get completed entries");
+            .expect("Failed to get eaten entries");
 
         assert_eq!(planned_entries.len(), 1);
-        assert_eq!(completed_entries.len(), 1);
+        assert_eq!(eaten_entries.len(), 1);
     }
 
     #[tokio::test]
@@ -469,11 +564,12 @@ mod tests {
         let entry = CreateMealEntry {
             meal_option_id: option_id,
             date,
-            slot_type: SlotType::Breakfast,
+            slot_type: SlotType::BREAKFAST,
             location: LocationType::Home,
             servings: Some(1.0),
             notes: None,
-            completed: Some(false),
+            status: Some(EntryStatus::Planned),
+            extra_options: vec![],
         };
 
         let created = MealEntryRepository::create(&pool, entry)
@@ -484,7 +580,7 @@ mod tests {
             location: Some(LocationType::Office),
             servings: Some(1.5),
             notes: Some(Some("Updated notes".to_string())),
-            completed: Some(true),
+            status: Some(EntryStatus::Eaten),
         };
 
         let updated = MealEntryRepository::update(&pool, created.id, updates)
@@ -494,7 +590,7 @@ mod tests {
         assert_eq!(updated.location, LocationType::Office);
         assert_eq!(updated.servings, 1.5);
         assert_eq!(updated.notes, Some("Updated notes".to_string()));
-        assert!(updated.completed);
+        assert_eq!(updated.status, EntryStatus::Eaten);
     }
 
     #[tokio::test]
@@ -506,11 +602,12 @@ mod tests {
         let entry = CreateMealEntry {
             meal_option_id: option_id,
             date,
-            slot_type: SlotType::Breakfast,
+            slot_type: SlotType::BREAKFAST,
             location: LocationType::Home,
             servings: None,
             notes: None,
-            completed: None,
+            status: None,
+            extra_options: vec![],
         };
 
         let created = MealEntryRepository::create(&pool, entry)
@@ -539,21 +636,23 @@ mod tests {
         let entry1 = CreateMealEntry {
             meal_option_id: option_id,
             date: date1,
-            slot_type: SlotType::Breakfast,
+            slot_type: SlotType::BREAKFAST,
             location: LocationType::Home,
             servings: None,
             notes: None,
-            completed: None,
+            status: None,
+            extra_options: vec![],
         };
 
         let entry2 = CreateMealEntry {
             meal_option_id: option_id,
             date: date2,
-            slot_type: SlotType::Breakfast,
+            slot_type: SlotType::BREAKFAST,
             location: LocationType::Home,
             servings: None,
             notes: None,
-            completed: None,
+            status: None,
+            extra_options: vec![],
         };
 
         MealEntryRepository::create(&pool, entry1)
@@ -584,11 +683,12 @@ mod tests {
             let entry = CreateMealEntry {
                 meal_option_id: option_id,
                 date: *date,
-                slot_type: SlotType::Breakfast,
+                slot_type: SlotType::BREAKFAST,
                 location: LocationType::Home,
                 servings: None,
                 notes: None,
-                completed: Some(true), // Only completed entries count
+                status: Some(EntryStatus::Eaten), // Only eaten entries count
+                extra_options: vec![],
             };
             MealEntryRepository::create(&pool, entry)
                 .await
@@ -624,11 +724,12 @@ mod tests {
             let entry = CreateMealEntry {
                 meal_option_id: option_id,
                 date: *date,
-                slot_type: SlotType::Breakfast,
+                slot_type: SlotType::BREAKFAST,
                 location: LocationType::Home,
                 servings: None,
                 notes: None,
-                completed: Some(true),
+                status: Some(EntryStatus::Eaten),
+                extra_options: vec![],
             };
             MealEntryRepository::create(&pool, entry)
                 .await
@@ -656,7 +757,7 @@ mod tests {
         let template = CreateMealTemplate {
             name: "Limited Template".to_string(),
             description: None,
-            compatible_slots: vec![SlotType::Breakfast],
+            compatible_slots: vec![SlotType::BREAKFAST],
             location_type: LocationType::Home,
             weekly_limit: Some(2),
         };
@@ -680,7 +781,7 @@ mod tests {
 
         // First entry should pass validation
         let warnings1 =
-            ValidationService::validate_meal_entry(&pool, option_id, SlotType::Breakfast, date)
+            ValidationService::validate_meal_entry(&pool, option_id, &SlotType::BREAKFAST, date)
                 .await
                 .expect("First entry validation should pass");
         assert!(warnings1.is_empty());
@@ -688,11 +789,12 @@ mod tests {
         let entry1 = CreateMealEntry {
             meal_option_id: option_id,
             date,
-            slot_type: SlotType::Breakfast,
+            slot_type: SlotType::BREAKFAST,
             location: LocationType::Home,
             servings: None,
             notes: None,
-            completed: Some(true),
+            status: Some(EntryStatus::Eaten),
+            extra_options: vec![],
         };
         MealEntryRepository::create(&pool, entry1)
             .await
@@ -702,7 +804,7 @@ mod tests {
         let warnings2 = ValidationService::validate_meal_entry(
             &pool,
             option_id,
-            SlotType::Breakfast,
+            &SlotType::BREAKFAST,
             date + chrono::Duration::days(1),
         )
         .await
@@ -712,11 +814,12 @@ mod tests {
         let entry2 = CreateMealEntry {
             meal_option_id: option_id,
             date: date + chrono::Duration::days(1),
-            slot_type: SlotType::Breakfast,
+            slot_type: SlotType::BREAKFAST,
             location: LocationType::Home,
             servings: None,
             notes: None,
-            completed: Some(true),
+            status: Some(EntryStatus::Eaten),
+            extra_options: vec![],
         };
         MealEntryRepository::create(&pool, entry2)
             .await
@@ -726,7 +829,7 @@ mod tests {
         let result = ValidationService::validate_meal_entry(
             &pool,
             option_id,
-            SlotType::Breakfast,
+            &SlotType::BREAKFAST,
             date + chrono::Duration::days(2),
         )
         .await;
@@ -749,7 +852,7 @@ mod tests {
         let template = CreateMealTemplate {
             name: "Breakfast Only".to_string(),
             description: None,
-            compatible_slots: vec![SlotType::Breakfast],
+            compatible_slots: vec![SlotType::BREAKFAST],
             location_type: LocationType::Home,
             weekly_limit: None,
         };
@@ -773,7 +876,7 @@ mod tests {
 
         // Try to validate entry for incompatible slot (Dinner)
         let result =
-            ValidationService::validate_meal_entry(&pool, option_id, SlotType::Dinner, date).await;
+            ValidationService::validate_meal_entry(&pool, option_id, &SlotType::DINNER, date).await;
         assert!(result.is_err());
 
         // Verify it's an incompatible slot error
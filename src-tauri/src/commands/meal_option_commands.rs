@@ -1,49 +1,46 @@
 // MealOption-related Tauri commands
 // Command handlers for meal option CRUD operations and tag management
 
-use crate::error::ApiResult;
-use crate::models::{CreateMealOption, MealOption, MealOptionWithTags, UpdateMealOption};
-use crate::repository::MealOptionRepository;
-use sqlx::SqlitePool;
+use crate::error::{ApiError, ApiResult};
+use crate::models::{
+    CreateMealOption, MealOption, MealOptionWithTags, NutrientProfile, SetNutrientProfile,
+    UpdateMealOption,
+};
+use crate::repository::{Backend, MealOptionStore};
 use tauri::State;
 
 /// Get all meal options
 #[tauri::command]
-pub async fn get_all_options(pool: State<'_, SqlitePool>) -> ApiResult<Vec<MealOption>> {
-    MealOptionRepository::get_all(pool.inner())
-        .await
-        .map_err(Into::into)
+pub async fn get_all_options(backend: State<'_, Backend>) -> ApiResult<Vec<MealOption>> {
+    backend.get_options().await.map_err(Into::into)
 }
 
 /// Get a meal option by ID
 #[tauri::command]
 pub async fn get_option_by_id(
     id: i64,
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<Option<MealOption>> {
-    MealOptionRepository::get_by_id(pool.inner(), id)
-        .await
-        .map_err(Into::into)
+    backend.get_option(id).await.map_err(Into::into)
 }
 
 /// Get a meal option with its tags by ID
 #[tauri::command]
 pub async fn get_option_with_tags(
     id: i64,
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<Option<MealOptionWithTags>> {
-    MealOptionRepository::get_with_tags(pool.inner(), id)
-        .await
-        .map_err(Into::into)
+    backend.get_option_with_tags(id).await.map_err(Into::into)
 }
 
 /// Get all meal options for a template
 #[tauri::command]
 pub async fn get_options_by_template(
     template_id: i64,
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<Vec<MealOption>> {
-    MealOptionRepository::get_by_template_id(pool.inner(), template_id)
+    backend
+        .get_options_by_template(template_id)
         .await
         .map_err(Into::into)
 }
@@ -52,9 +49,10 @@ pub async fn get_options_by_template(
 #[tauri::command]
 pub async fn get_options_by_template_with_tags(
     template_id: i64,
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<Vec<MealOptionWithTags>> {
-    MealOptionRepository::get_by_template_with_tags(pool.inner(), template_id)
+    backend
+        .get_options_by_template_with_tags(template_id)
         .await
         .map_err(Into::into)
 }
@@ -63,22 +61,18 @@ pub async fn get_options_by_template_with_tags(
 #[tauri::command]
 pub async fn search_options(
     query: String,
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<Vec<MealOption>> {
-    MealOptionRepository::search(pool.inner(), &query)
-        .await
-        .map_err(Into::into)
+    backend.search_options(&query).await.map_err(Into::into)
 }
 
 /// Create a new meal option
 #[tauri::command]
 pub async fn create_option(
     option: CreateMealOption,
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<MealOption> {
-    MealOptionRepository::create(pool.inner(), option)
-        .await
-        .map_err(Into::into)
+    backend.create_option(option).await.map_err(Into::into)
 }
 
 /// Update an existing meal option
@@ -86,29 +80,68 @@ pub async fn create_option(
 pub async fn update_option(
     id: i64,
     updates: UpdateMealOption,
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<MealOption> {
-    MealOptionRepository::update(pool.inner(), id, updates)
+    backend.update_option(id, updates).await.map_err(Into::into)
+}
+
+/// Get the archived meal options
+#[tauri::command]
+pub async fn get_archived_options(backend: State<'_, Backend>) -> ApiResult<Vec<MealOption>> {
+    backend.get_archived_options().await.map_err(Into::into)
+}
+
+/// Put a meal option in one of its template's option groups (None = ungrouped)
+#[tauri::command]
+pub async fn set_option_group(
+    id: i64,
+    option_group: Option<String>,
+    backend: State<'_, Backend>,
+) -> ApiResult<MealOption> {
+    backend
+        .set_option_group(id, option_group)
+        .await
+        .map_err(Into::into)
+}
+
+/// Archive a meal option: hide it from listings, search and suggestions
+#[tauri::command]
+pub async fn archive_option(id: i64, backend: State<'_, Backend>) -> ApiResult<MealOption> {
+    backend
+        .set_option_archived(id, true)
         .await
         .map_err(Into::into)
 }
 
-/// Delete a meal option
+/// Restore an archived meal option
 #[tauri::command]
-pub async fn delete_option(id: i64, pool: State<'_, SqlitePool>) -> ApiResult<()> {
-    MealOptionRepository::delete(pool.inner(), id)
+pub async fn unarchive_option(id: i64, backend: State<'_, Backend>) -> ApiResult<MealOption> {
+    backend
+        .set_option_archived(id, false)
         .await
         .map_err(Into::into)
 }
 
+/// Delete a meal option that was never logged
+/// Returns DeleteBlocked with the number of entries otherwise
+#[tauri::command]
+pub async fn delete_option(id: i64, backend: State<'_, Backend>) -> ApiResult<()> {
+    if let Some(blocked) = backend.get_option_delete_blockers(id).await? {
+        return Err(ApiError::DeleteBlocked(blocked));
+    }
+
+    backend.delete_option(id).await.map_err(Into::into)
+}
+
 /// Add tags to a meal option
 #[tauri::command]
 pub async fn add_tags_to_option(
     option_id: i64,
     tag_ids: Vec<i64>,
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<()> {
-    MealOptionRepository::add_tags(pool.inner(), option_id, tag_ids)
+    backend
+        .add_option_tags(option_id, tag_ids)
         .await
         .map_err(Into::into)
 }
@@ -118,9 +151,10 @@ pub async fn add_tags_to_option(
 pub async fn remove_tags_from_option(
     option_id: i64,
     tag_ids: Vec<i64>,
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<()> {
-    MealOptionRepository::remove_tags(pool.inner(), option_id, tag_ids)
+    backend
+        .remove_option_tags(option_id, tag_ids)
         .await
         .map_err(Into::into)
 }
@@ -130,9 +164,47 @@ pub async fn remove_tags_from_option(
 pub async fn set_option_tags(
     option_id: i64,
     tag_ids: Vec<i64>,
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<()> {
-    MealOptionRepository::set_tags(pool.inner(), option_id, tag_ids)
+    backend
+        .set_option_tags(option_id, tag_ids)
+        .await
+        .map_err(Into::into)
+}
+
+/// Get the per-serving nutrient profile of a meal option
+#[tauri::command]
+pub async fn get_option_nutrients(
+    option_id: i64,
+    backend: State<'_, Backend>,
+) -> ApiResult<Option<NutrientProfile>> {
+    backend
+        .get_option_nutrients(option_id)
+        .await
+        .map_err(Into::into)
+}
+
+/// Set the per-serving nutrient profile of a meal option (replaces any existing profile)
+#[tauri::command]
+pub async fn set_option_nutrients(
+    option_id: i64,
+    nutrients: SetNutrientProfile,
+    backend: State<'_, Backend>,
+) -> ApiResult<NutrientProfile> {
+    backend
+        .set_option_nutrients(option_id, nutrients)
+        .await
+        .map_err(Into::into)
+}
+
+/// Remove the nutrient profile of a meal option
+#[tauri::command]
+pub async fn delete_option_nutrients(
+    option_id: i64,
+    backend: State<'_, Backend>,
+) -> ApiResult<bool> {
+    backend
+        .delete_option_nutrients(option_id)
         .await
         .map_err(Into::into)
 }
@@ -141,8 +213,10 @@ pub async fn set_option_tags(
 mod tests {
     use super::*;
     use crate::models::{CreateMealTemplate, CreateTag, LocationType, SlotType, TagCategory};
+    use crate::repository::MealOptionRepository;
     use crate::repository::{MealTemplateRepository, TagRepository};
     use sqlx::sqlite::SqlitePoolOptions;
+    use sqlx::SqlitePool;
 
     async fn setup_test_pool() -> SqlitePool {
         let pool = SqlitePoolOptions::new()
@@ -165,7 +239,7 @@ mod tests {
             name: "Test Template".to_string(),
             description: Some("Test".to_string()),
             location_type: LocationType::Home,
-            compatible_slots: vec![SlotType::Breakfast],
+            compatible_slots: vec![SlotType::BREAKFAST],
             weekly_limit: None,
         };
 
@@ -261,7 +335,7 @@ mod tests {
             name: "Template 2".to_string(),
             description: None,
             location_type: LocationType::Home,
-            compatible_slots: vec![SlotType::Lunch],
+            compatible_slots: vec![SlotType::LUNCH],
             weekly_limit: None,
         };
         let template_id2 = MealTemplateRepository::create(&pool, template2)
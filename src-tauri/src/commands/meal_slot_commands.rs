@@ -0,0 +1,53 @@
+// MealSlot-related Tauri commands
+// Command handlers for configuring the meal slots of the day
+
+use crate::error::ApiResult;
+use crate::models::{CreateMealSlot, MealSlot, UpdateMealSlot};
+use crate::repository::{Backend, MealSlotStore};
+use tauri::State;
+
+/// Get all meal slots in day order, inactive ones included
+#[tauri::command]
+pub async fn get_all_meal_slots(backend: State<'_, Backend>) -> ApiResult<Vec<MealSlot>> {
+    backend.get_slots().await.map_err(Into::into)
+}
+
+/// Get the active meal slots in day order
+#[tauri::command]
+pub async fn get_active_meal_slots(backend: State<'_, Backend>) -> ApiResult<Vec<MealSlot>> {
+    backend.get_active_slots().await.map_err(Into::into)
+}
+
+/// Get a meal slot by ID
+#[tauri::command]
+pub async fn get_meal_slot_by_id(
+    id: i64,
+    backend: State<'_, Backend>,
+) -> ApiResult<Option<MealSlot>> {
+    backend.get_slot(id).await.map_err(Into::into)
+}
+
+/// Create a new meal slot
+#[tauri::command]
+pub async fn create_meal_slot(
+    slot: CreateMealSlot,
+    backend: State<'_, Backend>,
+) -> ApiResult<MealSlot> {
+    backend.create_slot(slot).await.map_err(Into::into)
+}
+
+/// Update an existing meal slot (display name, order, active flag)
+#[tauri::command]
+pub async fn update_meal_slot(
+    id: i64,
+    updates: UpdateMealSlot,
+    backend: State<'_, Backend>,
+) -> ApiResult<MealSlot> {
+    backend.update_slot(id, updates).await.map_err(Into::into)
+}
+
+/// Delete a meal slot that no entry or template uses
+#[tauri::command]
+pub async fn delete_meal_slot(id: i64, backend: State<'_, Backend>) -> ApiResult<bool> {
+    backend.delete_slot(id).await.map_err(Into::into)
+}
@@ -1,38 +1,36 @@
 // MealTemplate-related Tauri commands
 // Command handlers for meal template CRUD operations
 
-use crate::error::ApiResult;
-use crate::models::{CreateMealTemplate, LocationType, MealTemplate, SlotType, UpdateMealTemplate};
-use crate::repository::MealTemplateRepository;
-use sqlx::SqlitePool;
+use crate::error::{ApiError, ApiResult};
+use crate::models::{
+    CreateMealTemplate, LocationType, MealTemplate, OptionGroup, SlotType, UpdateMealTemplate,
+};
+use crate::repository::{Backend, MealTemplateStore};
 use tauri::State;
 
 /// Get all meal templates
 #[tauri::command]
-pub async fn get_all_templates(pool: State<'_, SqlitePool>) -> ApiResult<Vec<MealTemplate>> {
-    MealTemplateRepository::get_all(pool.inner())
-        .await
-        .map_err(Into::into)
+pub async fn get_all_templates(backend: State<'_, Backend>) -> ApiResult<Vec<MealTemplate>> {
+    backend.get_templates().await.map_err(Into::into)
 }
 
 /// Get a meal template by ID
 #[tauri::command]
 pub async fn get_template_by_id(
     id: i64,
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<Option<MealTemplate>> {
-    MealTemplateRepository::get_by_id(pool.inner(), id)
-        .await
-        .map_err(Into::into)
+    backend.get_template(id).await.map_err(Into::into)
 }
 
 /// Get meal templates by location type
 #[tauri::command]
 pub async fn get_templates_by_location(
     location: LocationType,
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<Vec<MealTemplate>> {
-    MealTemplateRepository::get_by_location(pool.inner(), location)
+    backend
+        .get_templates_by_location(location)
         .await
         .map_err(Into::into)
 }
@@ -41,20 +39,23 @@ pub async fn get_templates_by_location(
 #[tauri::command]
 pub async fn get_templates_by_slot(
     slot: SlotType,
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<Vec<MealTemplate>> {
-    MealTemplateRepository::get_by_slot(pool.inner(), slot)
+    backend
+        .get_templates_by_slot(&slot)
         .await
         .map_err(Into::into)
 }
 
-/// Search meal templates by name
+/// Search meal templates by name, optionally only those compatible with a slot
 #[tauri::command]
 pub async fn search_templates(
     query: String,
-    pool: State<'_, SqlitePool>,
+    slot: Option<SlotType>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<Vec<MealTemplate>> {
-    MealTemplateRepository::search(pool.inner(), &query)
+    backend
+        .search_templates(&query, slot.as_ref())
         .await
         .map_err(Into::into)
 }
@@ -63,11 +64,9 @@ pub async fn search_templates(
 #[tauri::command]
 pub async fn create_template(
     template: CreateMealTemplate,
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<MealTemplate> {
-    MealTemplateRepository::create(pool.inner(), template)
-        .await
-        .map_err(Into::into)
+    backend.create_template(template).await.map_err(Into::into)
 }
 
 /// Update an existing meal template
@@ -75,26 +74,81 @@ pub async fn create_template(
 pub async fn update_template(
     id: i64,
     updates: UpdateMealTemplate,
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<MealTemplate> {
-    MealTemplateRepository::update(pool.inner(), id, updates)
+    backend
+        .update_template(id, updates)
         .await
         .map_err(Into::into)
 }
 
-/// Delete a meal template
+/// Get the archived meal templates
 #[tauri::command]
-pub async fn delete_template(id: i64, pool: State<'_, SqlitePool>) -> ApiResult<bool> {
-    MealTemplateRepository::delete(pool.inner(), id)
+pub async fn get_archived_templates(backend: State<'_, Backend>) -> ApiResult<Vec<MealTemplate>> {
+    backend.get_archived_templates().await.map_err(Into::into)
+}
+
+/// Get the option groups (combination rules) of a meal template
+#[tauri::command]
+pub async fn get_template_option_groups(
+    template_id: i64,
+    backend: State<'_, Backend>,
+) -> ApiResult<Vec<OptionGroup>> {
+    backend
+        .get_option_groups(template_id)
         .await
         .map_err(Into::into)
 }
 
+/// Replace the option groups of a meal template
+/// Options in a group that is no longer declared become ungrouped
+#[tauri::command]
+pub async fn set_template_option_groups(
+    template_id: i64,
+    groups: Vec<OptionGroup>,
+    backend: State<'_, Backend>,
+) -> ApiResult<Vec<OptionGroup>> {
+    backend
+        .set_option_groups(template_id, groups)
+        .await
+        .map_err(Into::into)
+}
+
+/// Archive a meal template: hide it and its options from listings, search and suggestions
+#[tauri::command]
+pub async fn archive_template(id: i64, backend: State<'_, Backend>) -> ApiResult<MealTemplate> {
+    backend
+        .set_template_archived(id, true)
+        .await
+        .map_err(Into::into)
+}
+
+/// Restore an archived meal template
+#[tauri::command]
+pub async fn unarchive_template(id: i64, backend: State<'_, Backend>) -> ApiResult<MealTemplate> {
+    backend
+        .set_template_archived(id, false)
+        .await
+        .map_err(Into::into)
+}
+
+/// Delete a meal template (and its options) when none of its options was logged
+/// Returns DeleteBlocked with the number of entries otherwise
+#[tauri::command]
+pub async fn delete_template(id: i64, backend: State<'_, Backend>) -> ApiResult<bool> {
+    if let Some(blocked) = backend.get_template_delete_blockers(id).await? {
+        return Err(ApiError::DeleteBlocked(blocked));
+    }
+
+    backend.delete_template(id).await.map_err(Into::into)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::repository::MealTemplateRepository;
     use sqlx::sqlite::SqlitePoolOptions;
+    use sqlx::SqlitePool;
 
     async fn setup_test_pool() -> SqlitePool {
         let pool = SqlitePoolOptions::new()
@@ -119,7 +173,7 @@ mod tests {
         let create_template = CreateMealTemplate {
             name: "Pane con marmellata".to_string(),
             description: Some("Bread with jam".to_string()),
-            compatible_slots: vec![SlotType::Breakfast, SlotType::MorningSnack],
+            compatible_slots: vec![SlotType::BREAKFAST, SlotType::MORNING_SNACK],
             location_type: LocationType::Home,
             weekly_limit: None,
         };
@@ -150,7 +204,7 @@ mod tests {
             CreateMealTemplate {
                 name: "Yogurt".to_string(),
                 description: None,
-                compatible_slots: vec![SlotType::Breakfast],
+                compatible_slots: vec![SlotType::BREAKFAST],
                 location_type: LocationType::Any,
                 weekly_limit: None,
             },
@@ -163,7 +217,7 @@ mod tests {
             CreateMealTemplate {
                 name: "Pasta".to_string(),
                 description: Some("Pasta dish".to_string()),
-                compatible_slots: vec![SlotType::Lunch, SlotType::Dinner],
+                compatible_slots: vec![SlotType::LUNCH, SlotType::DINNER],
                 location_type: LocationType::Home,
                 weekly_limit: None,
             },
@@ -184,7 +238,7 @@ mod tests {
             CreateMealTemplate {
                 name: "Home Meal".to_string(),
                 description: None,
-                compatible_slots: vec![SlotType::Lunch],
+                compatible_slots: vec![SlotType::LUNCH],
                 location_type: LocationType::Home,
                 weekly_limit: None,
             },
@@ -197,7 +251,7 @@ mod tests {
             CreateMealTemplate {
                 name: "Office Meal".to_string(),
                 description: None,
-                compatible_slots: vec![SlotType::Lunch],
+                compatible_slots: vec![SlotType::LUNCH],
                 location_type: LocationType::Office,
                 weekly_limit: None,
             },
@@ -221,7 +275,7 @@ mod tests {
             CreateMealTemplate {
                 name: "Breakfast Only".to_string(),
                 description: None,
-                compatible_slots: vec![SlotType::Breakfast],
+                compatible_slots: vec![SlotType::BREAKFAST],
                 location_type: LocationType::Home,
                 weekly_limit: None,
             },
@@ -234,7 +288,7 @@ mod tests {
             CreateMealTemplate {
                 name: "Lunch and Dinner".to_string(),
                 description: None,
-                compatible_slots: vec![SlotType::Lunch, SlotType::Dinner],
+                compatible_slots: vec![SlotType::LUNCH, SlotType::DINNER],
                 location_type: LocationType::Home,
                 weekly_limit: None,
             },
@@ -242,13 +296,13 @@ mod tests {
         .await
         .unwrap();
 
-        let breakfast_templates = MealTemplateRepository::get_by_slot(&pool, SlotType::Breakfast)
+        let breakfast_templates = MealTemplateRepository::get_by_slot(&pool, &SlotType::BREAKFAST)
             .await
             .unwrap();
         assert_eq!(breakfast_templates.len(), 1);
         assert_eq!(breakfast_templates[0].name, "Breakfast Only");
 
-        let lunch_templates = MealTemplateRepository::get_by_slot(&pool, SlotType::Lunch)
+        let lunch_templates = MealTemplateRepository::get_by_slot(&pool, &SlotType::LUNCH)
             .await
             .unwrap();
         assert_eq!(lunch_templates.len(), 1);
@@ -264,7 +318,7 @@ mod tests {
             CreateMealTemplate {
                 name: "Pasta carbonara".to_string(),
                 description: Some("Classic pasta dish".to_string()),
-                compatible_slots: vec![SlotType::Lunch],
+                compatible_slots: vec![SlotType::LUNCH],
                 location_type: LocationType::Home,
                 weekly_limit: None,
             },
@@ -277,7 +331,7 @@ mod tests {
             CreateMealTemplate {
                 name: "Chicken salad".to_string(),
                 description: Some("Fresh salad".to_string()),
-                compatible_slots: vec![SlotType::Lunch],
+                compatible_slots: vec![SlotType::LUNCH],
                 location_type: LocationType::Office,
                 weekly_limit: None,
             },
@@ -285,13 +339,13 @@ mod tests {
         .await
         .unwrap();
 
-        let pasta_results = MealTemplateRepository::search(&pool, "pasta")
+        let pasta_results = MealTemplateRepository::search(&pool, "pasta", None)
             .await
             .unwrap();
         assert_eq!(pasta_results.len(), 1);
         assert_eq!(pasta_results[0].name, "Pasta carbonara");
 
-        let carbonara_results = MealTemplateRepository::search(&pool, "carbo")
+        let carbonara_results = MealTemplateRepository::search(&pool, "carbo", None)
             .await
             .unwrap();
         assert_eq!(carbonara_results.len(), 1);
@@ -306,7 +360,7 @@ mod tests {
             CreateMealTemplate {
                 name: "Original Name".to_string(),
                 description: None,
-                compatible_slots: vec![SlotType::Lunch],
+                compatible_slots: vec![SlotType::LUNCH],
                 location_type: LocationType::Home,
                 weekly_limit: None,
             },
@@ -320,7 +374,7 @@ mod tests {
             UpdateMealTemplate {
                 name: Some("Updated Name".to_string()),
                 description: Some(Some("New description".to_string())),
-                compatible_slots: Some(vec![SlotType::Lunch, SlotType::Dinner]),
+                compatible_slots: Some(vec![SlotType::LUNCH, SlotType::DINNER]),
                 location_type: Some(LocationType::Office),
                 weekly_limit: None,
             },
@@ -343,7 +397,7 @@ mod tests {
             CreateMealTemplate {
                 name: "To Delete".to_string(),
                 description: None,
-                compatible_slots: vec![SlotType::Lunch],
+                compatible_slots: vec![SlotType::LUNCH],
                 location_type: LocationType::Home,
                 weekly_limit: None,
             },
@@ -372,7 +426,7 @@ mod tests {
             CreateMealTemplate {
                 name: "".to_string(),
                 description: None,
-                compatible_slots: vec![SlotType::Lunch],
+                compatible_slots: vec![SlotType::LUNCH],
                 location_type: LocationType::Home,
                 weekly_limit: None,
             },
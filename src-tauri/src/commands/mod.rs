@@ -1,13 +1,35 @@
 // Command handlers module
 // Tauri commands for IPC communication between frontend and backend
 
+pub mod adherence_commands;
+pub mod audit_commands;
+pub mod backup_commands;
+pub mod export_commands;
 pub mod meal_entry_commands;
 pub mod meal_option_commands;
+pub mod meal_slot_commands;
 pub mod meal_template_commands;
+pub mod nutrition_commands;
+pub mod plan_import_commands;
+pub mod planning_commands;
+pub mod storage_commands;
+pub mod sync_commands;
 pub mod tag_commands;
+pub mod week_summary_commands;
 
 // Re-export all commands for easy registration
+pub use adherence_commands::*;
+pub use audit_commands::*;
+pub use backup_commands::*;
+pub use export_commands::*;
 pub use meal_entry_commands::*;
 pub use meal_option_commands::*;
+pub use meal_slot_commands::*;
 pub use meal_template_commands::*;
+pub use nutrition_commands::*;
+pub use plan_import_commands::*;
+pub use planning_commands::*;
+pub use storage_commands::*;
+pub use sync_commands::*;
 pub use tag_commands::*;
+pub use week_summary_commands::*;
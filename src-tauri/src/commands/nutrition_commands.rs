@@ -0,0 +1,162 @@
+// Nutrition-related Tauri commands
+// Command handlers for daily and date-range nutrient totals
+
+use crate::error::{ApiError, ApiResult};
+use crate::models::{DailyNutrientTotals, NutrientSummary};
+use crate::repository::Backend;
+use crate::services::NutritionService;
+use chrono::NaiveDate;
+use tauri::State;
+
+/// Get nutrient totals for a single day
+/// When `completed_only` is true, planned (not yet eaten) entries are ignored;
+/// skipped entries never count
+#[tauri::command]
+pub async fn get_daily_nutrient_totals(
+    date: String, // Format: "YYYY-MM-DD"
+    completed_only: bool,
+    backend: State<'_, Backend>,
+) -> ApiResult<DailyNutrientTotals> {
+    let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+        .map_err(|e| ApiError::ValidationError(format!("Invalid date format: {}", e)))?;
+
+    NutritionService::get_daily_totals(backend.sqlite()?, date, completed_only)
+        .await
+        .map_err(Into::into)
+}
+
+/// Get nutrient totals for a date range (inclusive), with a per-day breakdown
+/// When `completed_only` is true, planned (not yet eaten) entries are ignored;
+/// skipped entries never count
+#[tauri::command]
+pub async fn get_nutrient_totals(
+    start_date: String, // Format: "YYYY-MM-DD"
+    end_date: String,   // Format: "YYYY-MM-DD"
+    completed_only: bool,
+    backend: State<'_, Backend>,
+) -> ApiResult<NutrientSummary> {
+    let start = NaiveDate::parse_from_str(&start_date, "%Y-%m-%d")
+        .map_err(|e| ApiError::ValidationError(format!("Invalid start date: {}", e)))?;
+    let end = NaiveDate::parse_from_str(&end_date, "%Y-%m-%d")
+        .map_err(|e| ApiError::ValidationError(format!("Invalid end date: {}", e)))?;
+
+    if start > end {
+        return Err(ApiError::ValidationError(
+            "Start date must not be after end date".to_string(),
+        ));
+    }
+
+    NutritionService::get_totals_for_range(backend.sqlite()?, start, end, completed_only)
+        .await
+        .map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        CreateMealEntry, CreateMealOption, CreateMealTemplate, EntryStatus, LocationType,
+        SetNutrientProfile, SlotType,
+    };
+    use crate::repository::{MealEntryRepository, MealOptionRepository, MealTemplateRepository};
+    use sqlx::sqlite::SqlitePoolOptions;
+    use sqlx::SqlitePool;
+
+    async fn setup_test_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect(":memory:")
+            .await
+            .expect("Failed to create test pool");
+
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
+
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_weekly_totals_flow() {
+        let pool = setup_test_pool().await;
+
+        let template_id = MealTemplateRepository::create(
+            &pool,
+            CreateMealTemplate {
+                name: "Yogurt".to_string(),
+                description: None,
+                compatible_slots: vec![SlotType::BREAKFAST],
+                location_type: LocationType::Any,
+                weekly_limit: None,
+            },
+        )
+        .await
+        .expect("Failed to create template")
+        .id;
+
+        let option_id = MealOptionRepository::create(
+            &pool,
+            CreateMealOption {
+                template_id,
+                name: "Yogurt greco".to_string(),
+                description: None,
+                nutritional_notes: None,
+            },
+        )
+        .await
+        .expect("Failed to create option")
+        .id;
+
+        MealOptionRepository::set_nutrients(
+            &pool,
+            option_id,
+            SetNutrientProfile {
+                kcal: 97.0,
+                protein_g: 9.0,
+                carbs_g: 4.0,
+                fat_g: 5.0,
+                fiber_g: 0.0,
+            },
+        )
+        .await
+        .expect("Failed to set nutrients");
+
+        // One breakfast every day of the week
+        let monday = NaiveDate::from_ymd_opt(2024, 11, 4).unwrap();
+        for day in 0..7 {
+            let entry = CreateMealEntry {
+                meal_option_id: option_id,
+                date: monday + chrono::Duration::days(day),
+                slot_type: SlotType::BREAKFAST,
+                location: LocationType::Home,
+                servings: None,
+                notes: None,
+                status: Some(EntryStatus::Eaten),
+                extra_options: vec![],
+            };
+            MealEntryRepository::create(&pool, entry)
+                .await
+                .expect("Failed to create entry");
+        }
+
+        let summary = NutritionService::get_totals_for_range(
+            &pool,
+            monday,
+            monday + chrono::Duration::days(6),
+            true,
+        )
+        .await
+        .expect("Failed to get totals");
+
+        assert_eq!(summary.days.len(), 7);
+        assert_eq!(summary.entry_count, 7);
+        assert_eq!(summary.totals.kcal, 679.0);
+        assert_eq!(summary.totals.protein_g, 63.0);
+
+        // Totals cross the IPC boundary as JSON
+        let json = serde_json::to_string(&summary).unwrap();
+        assert!(json.contains("protein_g"));
+        assert!(json.contains("2024-11-04"));
+    }
+}
@@ -0,0 +1,22 @@
+// Plan import Tauri commands
+// Command handlers for turning a dietitian's plain-text plan into library templates
+
+use crate::error::ApiResult;
+use crate::models::PlanImport;
+use crate::repository::Backend;
+use crate::services::PlanImportService;
+use tauri::State;
+
+/// Import a plain-text meal plan into templates and options
+/// Slot headings open a block, "Oppure" separates templates and "o", "e/o" and "+"
+/// join options. Templates are matched with the library by name and only gain
+/// slots, options and option groups. With `preview` set, the comparison is
+/// returned without writing anything; otherwise everything is written in one transaction.
+#[tauri::command]
+pub async fn import_plan_text(
+    text: String,
+    preview: bool,
+    backend: State<'_, Backend>,
+) -> ApiResult<PlanImport> {
+    PlanImportService::import_text(backend.sqlite()?, &text, preview).await
+}
@@ -0,0 +1,76 @@
+// Planning-related Tauri commands
+// Command handlers for copying planned days and weeks, generating week plans
+// and suggesting options for a slot
+
+use crate::error::{ApiError, ApiResult};
+use crate::models::{
+    CopyConflictStrategy, CopyPlanResult, GeneratePlanRequest, GeneratedPlan, LocationType,
+    OptionSuggestion, SlotType,
+};
+use crate::repository::Backend;
+use crate::services::{PlanningService, SuggestionService, ValidationService};
+use chrono::NaiveDate;
+use tauri::State;
+
+/// Copy all entries of a day onto another day as planned entries
+/// Each copy is validated on the target date; failures and warnings are reported per entry.
+/// `on_conflict` decides what happens to target slots that already hold entries.
+#[tauri::command]
+pub async fn copy_day(
+    source_date: String, // Format: "YYYY-MM-DD"
+    target_date: String, // Format: "YYYY-MM-DD"
+    on_conflict: CopyConflictStrategy,
+    backend: State<'_, Backend>,
+) -> ApiResult<CopyPlanResult> {
+    let source = NaiveDate::parse_from_str(&source_date, "%Y-%m-%d")
+        .map_err(|e| ApiError::ValidationError(format!("Invalid source date: {}", e)))?;
+    let target = NaiveDate::parse_from_str(&target_date, "%Y-%m-%d")
+        .map_err(|e| ApiError::ValidationError(format!("Invalid target date: {}", e)))?;
+
+    PlanningService::copy_day(backend.sqlite()?, source, target, on_conflict).await
+}
+
+/// Copy all entries of an ISO week onto another week as planned entries
+/// Entries keep their weekday and slot; see `copy_day` for validation and conflicts
+#[tauri::command]
+pub async fn copy_week(
+    source_week: String, // Format: "YYYY-WW" - ISO week format
+    target_week: String, // Format: "YYYY-WW" - ISO week format
+    on_conflict: CopyConflictStrategy,
+    backend: State<'_, Backend>,
+) -> ApiResult<CopyPlanResult> {
+    let source = ValidationService::parse_week_string(&source_week)
+        .ok_or_else(|| ApiError::ValidationError(format!("Invalid week: {}", source_week)))?;
+    let target = ValidationService::parse_week_string(&target_week)
+        .ok_or_else(|| ApiError::ValidationError(format!("Invalid week: {}", target_week)))?;
+
+    PlanningService::copy_week(backend.sqlite()?, source, target, on_conflict).await
+}
+
+/// Generate planned entries for every empty slot of a week from the template library
+/// Respects slot compatibility, the per-day location schedule, template weekly limits and
+/// tag suggestions (0 = never). The same seed always yields the same plan.
+/// With `preview` set, the plan is returned without writing anything.
+#[tauri::command]
+pub async fn generate_week_plan(
+    request: GeneratePlanRequest,
+    backend: State<'_, Backend>,
+) -> ApiResult<GeneratedPlan> {
+    PlanningService::generate_week(backend.sqlite()?, request).await
+}
+
+/// Suggest options for a slot, best first
+/// Only options that pass `validate_entry` for this date and slot are returned, ranked by
+/// remaining weekly uses of their template, tag suggestion headroom and time since last eaten
+#[tauri::command]
+pub async fn suggest_options(
+    date: String, // Format: "YYYY-MM-DD"
+    slot: SlotType,
+    location: LocationType,
+    backend: State<'_, Backend>,
+) -> ApiResult<Vec<OptionSuggestion>> {
+    let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+        .map_err(|e| ApiError::ValidationError(format!("Invalid date format: {}", e)))?;
+
+    SuggestionService::suggest_options(backend.sqlite()?, date, slot, location).await
+}
@@ -0,0 +1,57 @@
+// Storage backend Tauri commands
+// Tells the frontend which database it runs on and which commands that database refuses
+
+use crate::error::ApiResult;
+use crate::models::StorageBackend;
+use crate::repository::Backend;
+use tauri::State;
+
+/// Get the storage backend and the commands it does not support
+/// On PostgreSQL these are the sync, undo, backup, export/import, planning and
+/// summary commands; on SQLite the list is empty
+#[tauri::command]
+pub async fn get_storage_backend(backend: State<'_, Backend>) -> ApiResult<StorageBackend> {
+    Ok(StorageBackend {
+        kind: backend.kind().to_string(),
+        unsupported_commands: backend
+            .unsupported_commands()
+            .iter()
+            .map(|c| c.to_string())
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::repository::SQLITE_ONLY_COMMANDS;
+    use std::collections::BTreeSet;
+    use std::path::Path;
+
+    #[test]
+    fn test_sqlite_only_commands_match_handlers() {
+        // Every command that reaches for the SQLite pool must be listed, and nothing else
+        let this_file = Path::new(file!());
+        let mut needs_sqlite = BTreeSet::new();
+        for file in std::fs::read_dir(this_file.parent().unwrap()).unwrap() {
+            let path = file.unwrap().path();
+            if path == this_file {
+                continue;
+            }
+            let source = std::fs::read_to_string(path).unwrap();
+            for handler in source.split("#[tauri::command]").skip(1) {
+                let name = handler
+                    .split("fn ")
+                    .nth(1)
+                    .and_then(|rest| rest.split(['(', '<']).next())
+                    .unwrap();
+                let body = handler.split("\n}\n").next().unwrap();
+                if body.contains(".sqlite()") {
+                    needs_sqlite.insert(name.to_string());
+                }
+            }
+        }
+
+        let listed: BTreeSet<String> = SQLITE_ONLY_COMMANDS.iter().map(|c| c.to_string()).collect();
+        assert_eq!(needs_sqlite, listed);
+    }
+}
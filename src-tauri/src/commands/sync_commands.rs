@@ -0,0 +1,34 @@
+// Sync-related Tauri commands
+// Command handlers for configuring the sync server and syncing with it
+
+use crate::error::ApiResult;
+use crate::models::{SyncReport, SyncSettings, SyncStatus};
+use crate::repository::{Backend, SyncRepository};
+use crate::services::SyncService;
+use tauri::State;
+
+/// Get this device's sync ID, pending changes and server
+#[tauri::command]
+pub async fn get_sync_status(backend: State<'_, Backend>) -> ApiResult<SyncStatus> {
+    SyncRepository::get_status(backend.sqlite()?)
+        .await
+        .map_err(Into::into)
+}
+
+/// Set the sync server and token, or clear them to stop syncing
+/// Switching servers pushes and pulls everything again on the next sync
+#[tauri::command]
+pub async fn configure_sync(
+    settings: SyncSettings,
+    backend: State<'_, Backend>,
+) -> ApiResult<SyncStatus> {
+    SyncRepository::set_settings(backend.sqlite()?, settings)
+        .await
+        .map_err(Into::into)
+}
+
+/// Pull changes from the configured server, then push local ones
+#[tauri::command]
+pub async fn sync_now(backend: State<'_, Backend>) -> ApiResult<SyncReport> {
+    SyncService::sync_configured(backend.sqlite()?).await
+}
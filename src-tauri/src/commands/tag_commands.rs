@@ -1,61 +1,51 @@
 // Tag-related Tauri commands
 // Command handlers for tag CRUD operations
 
-use crate::error::ApiResult;
+use crate::error::{ApiError, ApiResult};
 use crate::models::{CreateTag, Tag, TagCategory, UpdateTag};
-use crate::repository::TagRepository;
-use sqlx::SqlitePool;
+use crate::repository::{Backend, TagStore};
 use tauri::State;
 
 /// Get all tags
 #[tauri::command]
-pub async fn get_all_tags(pool: State<'_, SqlitePool>) -> ApiResult<Vec<Tag>> {
-    TagRepository::get_all(pool.inner())
-        .await
-        .map_err(Into::into)
+pub async fn get_all_tags(backend: State<'_, Backend>) -> ApiResult<Vec<Tag>> {
+    backend.get_tags().await.map_err(Into::into)
 }
 
 /// Get a tag by ID
 #[tauri::command]
-pub async fn get_tag_by_id(id: i64, pool: State<'_, SqlitePool>) -> ApiResult<Option<Tag>> {
-    TagRepository::get_by_id(pool.inner(), id)
-        .await
-        .map_err(Into::into)
+pub async fn get_tag_by_id(id: i64, backend: State<'_, Backend>) -> ApiResult<Option<Tag>> {
+    backend.get_tag(id).await.map_err(Into::into)
 }
 
 /// Get a tag by name
 #[tauri::command]
-pub async fn get_tag_by_name(name: String, pool: State<'_, SqlitePool>) -> ApiResult<Option<Tag>> {
-    TagRepository::get_by_name(pool.inner(), &name)
-        .await
-        .map_err(Into::into)
+pub async fn get_tag_by_name(name: String, backend: State<'_, Backend>) -> ApiResult<Option<Tag>> {
+    backend.get_tag_by_name(&name).await.map_err(Into::into)
 }
 
 /// Get all tags by category
 #[tauri::command]
 pub async fn get_tags_by_category(
     category: TagCategory,
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<Vec<Tag>> {
-    TagRepository::get_by_category(pool.inner(), category)
+    backend
+        .get_tags_by_category(category)
         .await
         .map_err(Into::into)
 }
 
 /// Get child tags of a parent tag
 #[tauri::command]
-pub async fn get_tag_children(parent_id: i64, pool: State<'_, SqlitePool>) -> ApiResult<Vec<Tag>> {
-    TagRepository::get_children(pool.inner(), parent_id)
-        .await
-        .map_err(Into::into)
+pub async fn get_tag_children(parent_id: i64, backend: State<'_, Backend>) -> ApiResult<Vec<Tag>> {
+    backend.get_child_tags(parent_id).await.map_err(Into::into)
 }
 
 /// Create a new tag
 #[tauri::command]
-pub async fn create_tag(tag: CreateTag, pool: State<'_, SqlitePool>) -> ApiResult<Tag> {
-    TagRepository::create(pool.inner(), tag)
-        .await
-        .map_err(Into::into)
+pub async fn create_tag(tag: CreateTag, backend: State<'_, Backend>) -> ApiResult<Tag> {
+    backend.create_tag(tag).await.map_err(Into::into)
 }
 
 /// Update an existing tag
@@ -63,26 +53,49 @@ pub async fn create_tag(tag: CreateTag, pool: State<'_, SqlitePool>) -> ApiResul
 pub async fn update_tag(
     id: i64,
     updates: UpdateTag,
-    pool: State<'_, SqlitePool>,
+    backend: State<'_, Backend>,
 ) -> ApiResult<Tag> {
-    TagRepository::update(pool.inner(), id, updates)
-        .await
-        .map_err(Into::into)
+    backend.update_tag(id, updates).await.map_err(Into::into)
 }
 
-/// Delete a tag
+/// Get the archived tags
 #[tauri::command]
-pub async fn delete_tag(id: i64, pool: State<'_, SqlitePool>) -> ApiResult<bool> {
-    TagRepository::delete(pool.inner(), id)
+pub async fn get_archived_tags(backend: State<'_, Backend>) -> ApiResult<Vec<Tag>> {
+    backend.get_archived_tags().await.map_err(Into::into)
+}
+
+/// Archive a tag: hide it from listings while options and history keep it
+#[tauri::command]
+pub async fn archive_tag(id: i64, backend: State<'_, Backend>) -> ApiResult<Tag> {
+    backend.set_tag_archived(id, true).await.map_err(Into::into)
+}
+
+/// Restore an archived tag
+#[tauri::command]
+pub async fn unarchive_tag(id: i64, backend: State<'_, Backend>) -> ApiResult<Tag> {
+    backend
+        .set_tag_archived(id, false)
         .await
         .map_err(Into::into)
 }
 
+/// Delete a tag that no option or child tag uses
+/// Returns DeleteBlocked listing what still depends on it otherwise
+#[tauri::command]
+pub async fn delete_tag(id: i64, backend: State<'_, Backend>) -> ApiResult<bool> {
+    if let Some(blocked) = backend.get_tag_delete_blockers(id).await? {
+        return Err(ApiError::DeleteBlocked(blocked));
+    }
+
+    backend.delete_tag(id).await.map_err(Into::into)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::repository::TagRepository;
     use sqlx::sqlite::SqlitePoolOptions;
+    use sqlx::SqlitePool;
 
     async fn setup_test_pool() -> SqlitePool {
         let pool = SqlitePoolOptions::new()
@@ -0,0 +1,23 @@
+// Week summary Tauri commands
+// Command handler for the weekly overview
+
+use crate::error::{ApiError, ApiResult};
+use crate::models::WeekSummary;
+use crate::repository::Backend;
+use crate::services::{ValidationService, WeekSummaryService};
+use tauri::State;
+
+/// Get the overview of an ISO week: the 7-day slot grid with option and template
+/// names, template usage vs. weekly limit, tag usage vs. suggestion and completion ratio
+#[tauri::command]
+pub async fn get_week_summary(
+    week: String, // Format: "YYYY-WW" - ISO week format
+    backend: State<'_, Backend>,
+) -> ApiResult<WeekSummary> {
+    let week_start = ValidationService::parse_week_string(&week)
+        .ok_or_else(|| ApiError::ValidationError(format!("Invalid week: {}", week)))?;
+
+    WeekSummaryService::get_week_summary(backend.sqlite()?, week_start)
+        .await
+        .map_err(Into::into)
+}
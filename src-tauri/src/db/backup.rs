@@ -0,0 +1,558 @@
+// Database backups
+// Timestamped VACUUM INTO snapshots with rotation, and in-place restore
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::{Connection, SqlitePool};
+use std::path::{Path, PathBuf};
+
+/// Number of snapshots kept by default
+pub const DEFAULT_BACKUP_ROTATIONS: usize = 10;
+
+const BACKUP_PREFIX: &str = "backup_";
+const BACKUP_EXTENSION: &str = "db";
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%3fZ";
+
+/// Where backups are written and how many are kept
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackupConfig {
+    pub dir: PathBuf,
+    pub keep: usize,
+}
+
+impl BackupConfig {
+    pub fn new(dir: PathBuf, keep: usize) -> Self {
+        Self { dir, keep }
+    }
+
+    /// Default configuration: a `backups` directory next to the database file
+    pub fn for_database(db_path: &Path) -> Self {
+        let dir = db_path
+            .parent()
+            .map(|p| p.join("backups"))
+            .unwrap_or_else(|| PathBuf::from("backups"));
+        Self::new(dir, DEFAULT_BACKUP_ROTATIONS)
+    }
+}
+
+/// A snapshot file in the backup directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub file_name: String,
+    pub created_at: DateTime<Utc>,
+    pub reason: String, // "startup", "manual", "pre-restore", "pre-migration-<version>"
+    pub size_bytes: u64,
+}
+
+/// Write a snapshot of the database with `VACUUM INTO`, then rotate old snapshots
+pub async fn create_backup(
+    pool: &SqlitePool,
+    config: &BackupConfig,
+    reason: &str,
+) -> Result<BackupInfo, sqlx::Error> {
+    let backup = write_snapshot(pool, config, reason).await?;
+    rotate_backups(config)?;
+
+    Ok(backup)
+}
+
+/// Write a snapshot without rotating
+async fn write_snapshot(
+    pool: &SqlitePool,
+    config: &BackupConfig,
+    reason: &str,
+) -> Result<BackupInfo, sqlx::Error> {
+    std::fs::create_dir_all(&config.dir).map_err(sqlx::Error::Io)?;
+
+    let created_at = Utc::now();
+    let timestamp = created_at.format(TIMESTAMP_FORMAT).to_string();
+
+    // Snapshots taken within the same millisecond get a counter after the timestamp
+    let mut file_name = format!(
+        "{}{}_{}.{}",
+        BACKUP_PREFIX, timestamp, reason, BACKUP_EXTENSION
+    );
+    let mut counter = 1;
+    while config.dir.join(&file_name).exists() {
+        file_name = format!(
+            "{}{}-{}_{}.{}",
+            BACKUP_PREFIX, timestamp, counter, reason, BACKUP_EXTENSION
+        );
+        counter += 1;
+    }
+    let path = config.dir.join(&file_name);
+
+    sqlx::query("VACUUM INTO ?")
+        .bind(path.to_string_lossy().to_string())
+        .execute(pool)
+        .await?;
+    if let Err(e) = scrub_snapshot(&path).await {
+        let _ = std::fs::remove_file(&path);
+        return Err(e);
+    }
+
+    let size_bytes = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    Ok(BackupInfo {
+        file_name,
+        created_at,
+        reason: reason.to_string(),
+        size_bytes,
+    })
+}
+
+/// List snapshots in the backup directory, newest first
+pub fn list_backups(config: &BackupConfig) -> Result<Vec<BackupInfo>, sqlx::Error> {
+    let entries = match std::fs::read_dir(&config.dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(sqlx::Error::Io(e)),
+    };
+
+    let mut backups = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(sqlx::Error::Io)?;
+        let file_name = entry.file_name().to_string_lossy().to_string();
+
+        if let Some((created_at, counter, reason)) = parse_backup_file_name(&file_name) {
+            let size_bytes = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            let info = BackupInfo {
+                file_name,
+                created_at,
+                reason,
+                size_bytes,
+            };
+            backups.push((counter, info));
+        }
+    }
+
+    backups.sort_by(|(a_counter, a), (b_counter, b)| {
+        (b.created_at, b_counter).cmp(&(a.created_at, a_counter))
+    });
+    Ok(backups.into_iter().map(|(_, info)| info).collect())
+}
+
+/// Delete the oldest snapshots beyond `config.keep`
+pub fn rotate_backups(config: &BackupConfig) -> Result<(), sqlx::Error> {
+    rotate_backups_keeping(config, &[])
+}
+
+/// Rotate, but never delete the snapshots in `protected`; they count toward `keep`
+fn rotate_backups_keeping(config: &BackupConfig, protected: &[&str]) -> Result<(), sqlx::Error> {
+    let backups = list_backups(config)?;
+    let kept = backups
+        .iter()
+        .filter(|b| protected.contains(&b.file_name.as_str()))
+        .count();
+
+    let unprotected = backups
+        .into_iter()
+        .filter(|b| !protected.contains(&b.file_name.as_str()));
+    for old in unprotected.skip(config.keep.saturating_sub(kept)) {
+        std::fs::remove_file(config.dir.join(&old.file_name)).map_err(sqlx::Error::Io)?;
+    }
+
+    Ok(())
+}
+
+/// Restore a snapshot into the live database
+///
+/// The current state is snapshotted first ("pre-restore"). The backup is
+/// copied to a scratch file and migrated to the current schema, then every
+/// table is replaced with the scratch copy's rows in a single transaction.
+/// Snapshots are rotated only once the restore succeeded, and never the
+/// restored one or the pre-restore one.
+///
+/// Rows are copied rather than swapping the pool onto the restored file: the
+/// pool is shared by the Tauri commands and the HTTP server as immutable state,
+/// so a swap would need a lock around every database access. Copying in one
+/// transaction keeps every holder of the pool valid, never leaves a half
+/// restored file behind, and keeps the device's sync identity and change log.
+pub async fn restore_backup(
+    pool: &SqlitePool,
+    config: &BackupConfig,
+    file_name: &str,
+) -> Result<BackupInfo, sqlx::Error> {
+    let backup = list_backups(config)?
+        .into_iter()
+        .find(|b| b.file_name == file_name)
+        .ok_or_else(|| sqlx::Error::Protocol(format!("Backup '{}' not found", file_name)))?;
+
+    let safety_backup = write_snapshot(pool, config, "pre-restore").await?;
+
+    // Bring the snapshot up to the current schema on a scratch copy
+    let scratch_path = config.dir.join(".restore.db");
+    std::fs::copy(config.dir.join(&backup.file_name), &scratch_path).map_err(sqlx::Error::Io)?;
+    let migrated = migrate_scratch_copy(&scratch_path).await;
+    if let Err(e) = migrated {
+        let _ = std::fs::remove_file(&scratch_path);
+        return Err(e);
+    }
+
+    let copied = copy_tables_from(pool, &scratch_path).await;
+    let _ = std::fs::remove_file(&scratch_path);
+    copied?;
+
+    rotate_backups_keeping(config, &[&backup.file_name, &safety_backup.file_name])?;
+
+    Ok(safety_backup)
+}
+
+/// Remove the sync server token from a snapshot; backups get copied around more
+/// freely than the live database. Restores keep the live sync state anyway.
+async fn scrub_snapshot(path: &Path) -> Result<(), sqlx::Error> {
+    let snapshot = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect(&format!("sqlite://{}", path.display()))
+        .await?;
+    let result = async {
+        // Overwrite the freed bytes too, not just unlink them from the row
+        sqlx::query("PRAGMA secure_delete = ON")
+            .execute(&snapshot)
+            .await?;
+        let has_sync_state: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'sync_state')",
+        )
+        .fetch_one(&snapshot)
+        .await?;
+        if has_sync_state {
+            sqlx::query("UPDATE sync_state SET server_token = NULL")
+                .execute(&snapshot)
+                .await?;
+        }
+        Ok(())
+    }
+    .await;
+    snapshot.close().await;
+    result
+}
+
+async fn migrate_scratch_copy(path: &Path) -> Result<(), sqlx::Error> {
+    let scratch = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect(&format!("sqlite://{}", path.display()))
+        .await?;
+    let result = sqlx::migrate!("./migrations").run(&scratch).await;
+    scratch.close().await;
+    result.map_err(Into::into)
+}
+
+/// Replace the rows of every application table with those of another database file
+async fn copy_tables_from(pool: &SqlitePool, source: &Path) -> Result<(), sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+
+    sqlx::query("ATTACH DATABASE ? AS restore_source")
+        .bind(source.to_string_lossy().to_string())
+        .execute(&mut *conn)
+        .await?;
+
+    // Foreign keys cannot be toggled inside a transaction. They are switched back
+    // on below whether the copy succeeds or not, since the connection goes back
+    // to the pool.
+    let result = async {
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(&mut *conn)
+            .await?;
+
+        // The sync identity and change log stay: the triggers log the restore itself
+        // as changes, so other devices receive the restored state on the next sync
+        let tables: Vec<String> = sqlx::query_scalar(
+            "SELECT name FROM main.sqlite_master
+             WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND name != '_sqlx_migrations'
+               AND name NOT IN ('sync_state', 'sync_changes')",
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut tx = conn.begin().await?;
+        for table in tables.iter().map(String::as_str).chain(["sqlite_sequence"]) {
+            sqlx::query(&format!("DELETE FROM main.\"{}\"", table))
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query(&format!(
+                "INSERT INTO main.\"{0}\" SELECT * FROM restore_source.\"{0}\"",
+                table
+            ))
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+    .await;
+
+    let enabled = sqlx::query("PRAGMA foreign_keys = ON")
+        .execute(&mut *conn)
+        .await;
+    let detached = sqlx::query("DETACH DATABASE restore_source")
+        .execute(&mut *conn)
+        .await;
+    if enabled.is_err() {
+        // Never hand out a connection that skips foreign key checks
+        drop(conn.detach());
+    }
+
+    result?;
+    enabled?;
+    detached?;
+    Ok(())
+}
+
+/// Parse "backup_<timestamp>[-<counter>]_<reason>.db" into its timestamp,
+/// counter (0 when absent) and reason
+fn parse_backup_file_name(file_name: &str) -> Option<(DateTime<Utc>, u32, String)> {
+    let stem = file_name
+        .strip_prefix(BACKUP_PREFIX)?
+        .strip_suffix(&format!(".{}", BACKUP_EXTENSION))?;
+    let (timestamp, reason) = stem.split_once('_')?;
+    let (timestamp, counter) = match timestamp.split_once('-') {
+        Some((timestamp, counter)) => (timestamp, counter.parse().ok()?),
+        None => (timestamp, 0),
+    };
+    let created_at = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()?;
+
+    Some((created_at.and_utc(), counter, reason.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::initialize_database_with_backups;
+    use crate::models::SyncSettings;
+    use crate::repository::SyncRepository;
+    use tempfile::TempDir;
+
+    async fn setup(keep: usize) -> (SqlitePool, BackupConfig, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let config = BackupConfig::new(temp_dir.path().join("backups"), keep);
+        let pool = initialize_database_with_backups(db_path, &config)
+            .await
+            .unwrap();
+        (pool, config, temp_dir)
+    }
+
+    async fn tag_names(pool: &SqlitePool) -> Vec<String> {
+        sqlx::query_scalar("SELECT name FROM tags ORDER BY name")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn insert_tag(pool: &SqlitePool, name: &str) {
+        sqlx::query("INSERT INTO tags (name, display_name, category) VALUES (?, ?, 'ingredient')")
+            .bind(name)
+            .bind(name)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn test_parse_backup_file_name() {
+        let (created_at, counter, reason) =
+            parse_backup_file_name("backup_20241104T083000123Z_pre-migration-20251120000001.db")
+                .unwrap();
+        assert_eq!(
+            created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            "2024-11-04 08:30:00"
+        );
+        assert_eq!(counter, 0);
+        assert_eq!(reason, "pre-migration-20251120000001");
+
+        let (_, counter, reason) =
+            parse_backup_file_name("backup_20241104T083000123Z-2_manual.db").unwrap();
+        assert_eq!(counter, 2);
+        assert_eq!(reason, "manual");
+
+        assert!(parse_backup_file_name("nutrition_helper.db").is_none());
+        assert!(parse_backup_file_name("backup_garbage_manual.db").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_create_and_list_backups() {
+        let (pool, config, _temp_dir) = setup(10).await;
+
+        let backup = create_backup(&pool, &config, "manual").await.unwrap();
+        assert_eq!(backup.reason, "manual");
+        assert!(config.dir.join(&backup.file_name).exists());
+
+        let backups = list_backups(&config).unwrap();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(backups[0].file_name, backup.file_name);
+        assert!(backups[0].size_bytes > 0);
+    }
+
+    #[tokio::test]
+    async fn test_rotation_keeps_newest() {
+        let (pool, config, _temp_dir) = setup(3).await;
+
+        let mut created = Vec::new();
+        for _ in 0..5 {
+            created.push(create_backup(&pool, &config, "manual").await.unwrap());
+        }
+
+        let backups = list_backups(&config).unwrap();
+        assert_eq!(backups.len(), 3);
+        // Newest first, oldest two removed
+        assert_eq!(backups[0].file_name, created[4].file_name);
+        assert_eq!(backups[2].file_name, created[2].file_name);
+    }
+
+    #[test]
+    fn test_list_orders_same_millisecond_snapshots() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = BackupConfig::new(temp_dir.path().to_path_buf(), 10);
+        for file_name in [
+            "backup_20241104T083000123Z-1_manual.db",
+            "backup_20241104T083000123Z_startup.db",
+            "backup_20241104T083000122Z_pre-restore.db",
+            "backup_20241104T083000123Z-2_manual.db",
+        ] {
+            std::fs::write(temp_dir.path().join(file_name), b"").unwrap();
+        }
+
+        let names: Vec<String> = list_backups(&config)
+            .unwrap()
+            .into_iter()
+            .map(|b| b.file_name)
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "backup_20241104T083000123Z-2_manual.db",
+                "backup_20241104T083000123Z-1_manual.db",
+                "backup_20241104T083000123Z_startup.db",
+                "backup_20241104T083000122Z_pre-restore.db",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_restore_keeps_foreign_keys_on_failure() {
+        let (_, _, temp_dir) = setup(10).await;
+        // One connection, so the check below runs on the connection the copy used
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect(&format!(
+                "sqlite://{}",
+                temp_dir.path().join("test.db").display()
+            ))
+            .await
+            .unwrap();
+
+        let missing = temp_dir.path().join("missing").join("source.db");
+        assert!(copy_tables_from(&pool, &missing).await.is_err());
+        let corrupt = temp_dir.path().join("corrupt.db");
+        std::fs::write(&corrupt, b"not a database").unwrap();
+        assert!(copy_tables_from(&pool, &corrupt).await.is_err());
+
+        let foreign_keys: i64 = sqlx::query_scalar("PRAGMA foreign_keys")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(foreign_keys, 1);
+        let attached: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pragma_database_list")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(attached, 1);
+    }
+
+    #[tokio::test]
+    async fn test_restore_backup() {
+        let (pool, config, _temp_dir) = setup(10).await;
+
+        insert_tag(&pool, "pasta").await;
+        let snapshot = create_backup(&pool, &config, "manual").await.unwrap();
+
+        // Changes after the snapshot
+        insert_tag(&pool, "riso").await;
+        sqlx::query("DELETE FROM tags WHERE name = 'pasta'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(tag_names(&pool).await, vec!["riso"]);
+
+        let safety = restore_backup(&pool, &config, &snapshot.file_name)
+            .await
+            .unwrap();
+        assert_eq!(safety.reason, "pre-restore");
+
+        // Same pool now sees the restored rows, and new rows still get fresh IDs
+        assert_eq!(tag_names(&pool).await, vec!["pasta"]);
+        insert_tag(&pool, "farro").await;
+        assert_eq!(tag_names(&pool).await, vec!["farro", "pasta"]);
+
+        // The pre-restore snapshot still has the discarded state
+        restore_backup(&pool, &config, &safety.file_name)
+            .await
+            .unwrap();
+        assert_eq!(tag_names(&pool).await, vec!["riso"]);
+    }
+
+    #[tokio::test]
+    async fn test_restore_oldest_backup_with_rotation_full() {
+        let (pool, config, _temp_dir) = setup(3).await;
+
+        insert_tag(&pool, "pasta").await;
+        let oldest = create_backup(&pool, &config, "manual").await.unwrap();
+        insert_tag(&pool, "riso").await;
+        create_backup(&pool, &config, "manual").await.unwrap();
+        create_backup(&pool, &config, "manual").await.unwrap();
+        assert_eq!(list_backups(&config).unwrap().len(), 3);
+
+        let safety = restore_backup(&pool, &config, &oldest.file_name)
+            .await
+            .unwrap();
+        assert_eq!(tag_names(&pool).await, vec!["pasta"]);
+
+        // Both the restored backup and the safety snapshot survive rotation
+        let names: Vec<String> = list_backups(&config)
+            .unwrap()
+            .into_iter()
+            .map(|b| b.file_name)
+            .collect();
+        assert_eq!(names.len(), 3);
+        assert!(names.contains(&oldest.file_name));
+        assert!(names.contains(&safety.file_name));
+    }
+
+    #[tokio::test]
+    async fn test_backup_leaves_out_sync_token() {
+        let (pool, config, _temp_dir) = setup(10).await;
+        let token = "backup-must-not-keep-this-token";
+        SyncRepository::set_settings(
+            &pool,
+            SyncSettings {
+                server_url: Some("https://nas.local".to_string()),
+                token: Some(token.to_string()),
+            },
+        )
+        .await
+        .unwrap();
+
+        let snapshot = create_backup(&pool, &config, "manual").await.unwrap();
+
+        let bytes = std::fs::read(config.dir.join(&snapshot.file_name)).unwrap();
+        assert!(!bytes.windows(token.len()).any(|w| w == token.as_bytes()));
+
+        // A restore keeps the live server settings
+        restore_backup(&pool, &config, &snapshot.file_name)
+            .await
+            .unwrap();
+        assert_eq!(
+            SyncRepository::get_server(&pool).await.unwrap(),
+            Some(("https://nas.local".to_string(), token.to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_restore_unknown_backup() {
+        let (pool, config, _temp_dir) = setup(10).await;
+
+        let result = restore_backup(&pool, &config, "../test.db").await;
+        assert!(result.is_err());
+        // No safety snapshot is taken for a missing backup
+        assert!(list_backups(&config).unwrap().is_empty());
+    }
+}
@@ -1,13 +1,32 @@
 // Database module
 // Database connection and initialization
 
+pub mod backup;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+
+pub use backup::{BackupConfig, BackupInfo};
+
+use sqlx::migrate::{Migrate, Migrator};
 use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
+use std::collections::HashSet;
 use std::path::PathBuf;
 use tauri::Manager;
 
 /// Initialize the database connection pool
 /// Creates the database file if it doesn't exist and runs migrations
+/// Backups go to a `backups` directory next to the database file
 pub async fn initialize_database(db_path: PathBuf) -> Result<SqlitePool, sqlx::Error> {
+    let backup_config = BackupConfig::for_database(&db_path);
+    initialize_database_with_backups(db_path, &backup_config).await
+}
+
+/// Initialize the database connection pool with an explicit backup configuration
+/// An existing database is snapshotted before each pending migration and once on startup
+pub async fn initialize_database_with_backups(
+    db_path: PathBuf,
+    backup_config: &BackupConfig,
+) -> Result<SqlitePool, sqlx::Error> {
     // Ensure the parent directory exists
     if let Some(parent) = db_path.parent() {
         std::fs::create_dir_all(parent).map_err(sqlx::Error::Io)?;
@@ -23,11 +42,56 @@ pub async fn initialize_database(db_path: PathBuf) -> Result<SqlitePool, sqlx::E
         .await?;
 
     // Run migrations
-    sqlx::migrate!("./migrations").run(&pool).await?;
+    let migrator = sqlx::migrate!("./migrations");
+    let existing_database = run_migrations_with_backups(&pool, &migrator, backup_config).await?;
+
+    if existing_database {
+        backup::create_backup(&pool, backup_config, "startup").await?;
+    }
 
     Ok(pool)
 }
 
+/// Apply pending migrations one at a time, taking a snapshot before each
+/// Returns false for a fresh database (nothing to back up)
+async fn run_migrations_with_backups(
+    pool: &SqlitePool,
+    migrator: &Migrator,
+    backup_config: &BackupConfig,
+) -> Result<bool, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    conn.ensure_migrations_table().await?;
+    let applied: HashSet<i64> = conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|m| m.version)
+        .collect();
+    drop(conn);
+
+    if applied.is_empty() {
+        migrator.run(pool).await?;
+        return Ok(false);
+    }
+
+    let pending = migrator
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration() && !applied.contains(&m.version));
+
+    for migration in pending {
+        let reason = format!("pre-migration-{}", migration.version);
+        backup::create_backup(pool, backup_config, &reason).await?;
+
+        let mut conn = pool.acquire().await?;
+        conn.apply(migration).await?;
+    }
+
+    // Verifies checksums of everything already applied
+    migrator.run(pool).await?;
+
+    Ok(true)
+}
+
 /// Get the default database path for the application
 /// Uses the app data directory provided by Tauri
 pub fn get_database_path(app_handle: &tauri::AppHandle) -> PathBuf {
@@ -108,11 +172,57 @@ mod tests {
             "meal_option_tags junction table not found"
         );
 
-        // Should have exactly 5 tables
+        assert!(
+            table_names.contains(&"meal_option_nutrients".to_string()),
+            "meal_option_nutrients table not found"
+        );
+        assert!(
+            table_names.contains(&"meal_slots".to_string()),
+            "meal_slots table not found"
+        );
+        assert!(
+            table_names.contains(&"meal_template_slots".to_string()),
+            "meal_template_slots junction table not found"
+        );
+        assert!(
+            table_names.contains(&"meal_entry_tags".to_string()),
+            "meal_entry_tags snapshot table not found"
+        );
+        assert!(
+            table_names.contains(&"meal_template_option_groups".to_string()),
+            "meal_template_option_groups table not found"
+        );
+        assert!(
+            table_names.contains(&"meal_entry_options".to_string()),
+            "meal_entry_options table not found"
+        );
+        assert!(
+            table_names.contains(&"meal_entry_planned_options".to_string()),
+            "meal_entry_planned_options table not found"
+        );
+        assert!(
+            table_names.contains(&"meal_entry_nutrients".to_string()),
+            "meal_entry_nutrients table not found"
+        );
+
+        assert!(
+            table_names.contains(&"sync_state".to_string()),
+            "sync_state table not found"
+        );
+        assert!(
+            table_names.contains(&"sync_changes".to_string()),
+            "sync_changes table not found"
+        );
+        assert!(
+            table_names.contains(&"audit_log".to_string()),
+            "audit_log table not found"
+        );
+
+        // Should have exactly 16 tables
         assert_eq!(
             table_names.len(),
-            5,
-            "Expected 5 tables, found: {:?}",
+            16,
+            "Expected 16 tables, found: {:?}",
             table_names
         );
     }
@@ -134,8 +244,9 @@ mod tests {
 
         let index_names: Vec<String> = indexes.into_iter().map(|(name,)| name).collect();
 
-        // Verify indexes exist (10 indexes total after adding weekly_limit index)
+        // Verify indexes exist
         assert!(index_names.contains(&"idx_meal_entries_date".to_string()));
+        assert!(index_names.contains(&"idx_meal_entries_iso_week".to_string()));
         assert!(index_names.contains(&"idx_meal_entries_option".to_string()));
         assert!(index_names.contains(&"idx_meal_entries_date_slot".to_string()));
         assert!(index_names.contains(&"idx_meal_options_template".to_string()));
@@ -145,12 +256,25 @@ mod tests {
         assert!(index_names.contains(&"idx_tags_parent".to_string()));
         assert!(index_names.contains(&"idx_meal_option_tags_option".to_string()));
         assert!(index_names.contains(&"idx_meal_option_tags_tag".to_string()));
-
-        // Should have exactly 10 indexes (9 original + 1 for weekly_limit)
+        assert!(index_names.contains(&"idx_meal_template_slots_slot".to_string()));
+        assert!(index_names.contains(&"idx_meal_entry_tags_tag".to_string()));
+        assert!(index_names.contains(&"idx_meal_entry_options_option".to_string()));
+        assert!(index_names.contains(&"idx_meal_entries_status_date".to_string()));
+        assert!(index_names.contains(&"idx_meal_entry_planned_options_option".to_string()));
+        assert!(index_names.contains(&"idx_sync_changes_entity".to_string()));
+        assert!(index_names.contains(&"idx_sync_changes_device".to_string()));
+        assert!(index_names.contains(&"idx_tags_uuid".to_string()));
+        assert!(index_names.contains(&"idx_meal_templates_uuid".to_string()));
+        assert!(index_names.contains(&"idx_meal_options_uuid".to_string()));
+        assert!(index_names.contains(&"idx_meal_entries_uuid".to_string()));
+        assert!(index_names.contains(&"idx_audit_log_entity".to_string()));
+        assert!(index_names.contains(&"idx_audit_log_operation".to_string()));
+
+        // Should have exactly 24 indexes (9 original + weekly_limit + iso_week + template slots + entry tags + entry options + status + planned options + 2 change log + 4 sync UUIDs + 2 audit log)
         assert_eq!(
             index_names.len(),
-            10,
-            "Expected 10 indexes, found: {:?}",
+            24,
+            "Expected 24 indexes, found: {:?}",
             index_names
         );
     }
@@ -171,16 +295,317 @@ mod tests {
 
         let view_names: Vec<String> = views.into_iter().map(|(name,)| name).collect();
 
-        // Verify both views exist (meal usage + tag usage)
+        // Verify all views exist (meal usage + template usage + tag usage + sync UUIDs)
         assert!(view_names.contains(&"weekly_meal_usage".to_string()));
+        assert!(view_names.contains(&"weekly_template_usage".to_string()));
         assert!(view_names.contains(&"weekly_tag_usage".to_string()));
+        assert!(view_names.contains(&"sync_new_uuid".to_string()));
 
-        // Should have exactly 2 views
+        // Should have exactly 4 views
         assert_eq!(
             view_names.len(),
-            2,
-            "Expected 2 views, found: {:?}",
+            4,
+            "Expected 4 views, found: {:?}",
             view_names
         );
     }
+
+    #[tokio::test]
+    async fn test_iso_week_backfill() {
+        use crate::services::ValidationService;
+        use chrono::NaiveDate;
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect(":memory:")
+            .await
+            .unwrap();
+
+        // Apply everything before the iso_week migration
+        let migrator = sqlx::migrate!("./migrations");
+        let mut conn = pool.acquire().await.unwrap();
+        conn.ensure_migrations_table().await.unwrap();
+        for migration in migrator.iter().filter(|m| m.version < 20251122000001) {
+            conn.apply(migration).await.unwrap();
+        }
+
+        sqlx::query(
+            "INSERT INTO meal_templates (name, compatible_slots, location_type) VALUES ('T', '[\"lunch\"]', 'any')",
+        )
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO meal_options (template_id, name) VALUES (1, 'O')")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+
+        // Year boundaries, 53-week years and every weekday around them
+        let mut dates = Vec::new();
+        for year in [2020, 2024, 2026, 2027] {
+            let start = NaiveDate::from_ymd_opt(year, 12, 25).unwrap();
+            dates.extend((0..14).map(|d| start + chrono::Duration::days(d)));
+        }
+        dates.push(NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
+        for date in &dates {
+            sqlx::query(
+                "INSERT INTO meal_entries (meal_option_id, date, slot_type, location) VALUES (1, ?, 'lunch', 'home')",
+            )
+            .bind(date)
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        }
+        drop(conn);
+
+        migrator.run(&pool).await.unwrap();
+
+        let rows: Vec<(NaiveDate, String)> =
+            sqlx::query_as("SELECT date, iso_week FROM meal_entries ORDER BY id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(rows.len(), dates.len());
+        for (date, iso_week) in rows {
+            assert_eq!(
+                iso_week,
+                ValidationService::get_week_string(date),
+                "{}",
+                date
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_template_slots_migration() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect(":memory:")
+            .await
+            .unwrap();
+
+        // Apply everything before the join table migration
+        let migrator = sqlx::migrate!("./migrations");
+        let mut conn = pool.acquire().await.unwrap();
+        conn.ensure_migrations_table().await.unwrap();
+        for migration in migrator.iter().filter(|m| m.version < 20251124000001) {
+            conn.apply(migration).await.unwrap();
+        }
+
+        // Valid JSON, then a row corrupted behind the triggers' back
+        sqlx::query(
+            r#"INSERT INTO meal_templates (name, compatible_slots, location_type)
+               VALUES ('Pasta', '["lunch","dinner"]', 'home'), ('Broken', '[]', 'any')"#,
+        )
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+        sqlx::query("DROP TRIGGER check_meal_templates_slots_update")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        sqlx::query(
+            "UPDATE meal_templates SET compatible_slots = 'not json' WHERE name = 'Broken'",
+        )
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+        drop(conn);
+
+        migrator.run(&pool).await.unwrap();
+
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            "SELECT mt.name, mts.slot_type
+             FROM meal_template_slots mts JOIN meal_templates mt ON mt.id = mts.template_id
+             ORDER BY mt.name, mts.slot_type",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                ("Pasta".to_string(), "dinner".to_string()),
+                ("Pasta".to_string(), "lunch".to_string())
+            ]
+        );
+
+        // The broken template survives, without slots
+        let templates: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM meal_templates")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(templates, 2);
+    }
+
+    #[tokio::test]
+    async fn test_entry_snapshot_backfill() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect(":memory:")
+            .await
+            .unwrap();
+
+        // Apply everything before the snapshot migration
+        let migrator = sqlx::migrate!("./migrations");
+        let mut conn = pool.acquire().await.unwrap();
+        conn.ensure_migrations_table().await.unwrap();
+        for migration in migrator.iter().filter(|m| m.version < 20251125000001) {
+            conn.apply(migration).await.unwrap();
+        }
+
+        sqlx::query(
+            "INSERT INTO meal_templates (id, name, location_type) VALUES (1, 'Pasta', 'home');
+             INSERT INTO meal_options (id, template_id, name) VALUES (1, 1, 'Pasta al pomodoro');
+             INSERT INTO tags (id, name, display_name, category) VALUES (1, 'pasta', 'Pasta', 'ingredient');
+             INSERT INTO meal_option_tags (meal_option_id, tag_id) VALUES (1, 1);
+             INSERT INTO meal_entries (meal_option_id, date, iso_week, slot_type, location, completed)
+             VALUES (1, '2024-11-04', '2024-45', 'lunch', 'home', 1);",
+        )
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+        drop(conn);
+
+        migrator.run(&pool).await.unwrap();
+
+        let row: (String, i64, String, String) = sqlx::query_as(
+            "SELECT option_name, template_id, template_name, template_location_type FROM meal_entries",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            row,
+            (
+                "Pasta al pomodoro".to_string(),
+                1,
+                "Pasta".to_string(),
+                "home".to_string()
+            )
+        );
+
+        let tags: Vec<(i64, String)> =
+            sqlx::query_as("SELECT tag_id, tag_name FROM meal_entry_tags")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(tags, vec![(1, "pasta".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_off_plan_migration_keeps_entry_rows() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect(":memory:")
+            .await
+            .unwrap();
+
+        // Apply everything before meal_entries is rebuilt for off-plan entries
+        let migrator = sqlx::migrate!("./migrations");
+        let mut conn = pool.acquire().await.unwrap();
+        conn.ensure_migrations_table().await.unwrap();
+        for migration in migrator.iter().filter(|m| m.version < 20251129000001) {
+            conn.apply(migration).await.unwrap();
+        }
+
+        sqlx::query(
+            "INSERT INTO meal_templates (id, name, location_type) VALUES (1, 'Pasta', 'home');
+             INSERT INTO meal_options (id, template_id, name) VALUES (1, 1, 'Pasta al pomodoro');
+             INSERT INTO meal_entries (id, meal_option_id, date, iso_week, slot_type, location, status,
+                                       option_name, template_id, template_name)
+             VALUES (1, 1, '2024-11-04', '2024-45', 'lunch', 'home', 'eaten', 'Pasta al pomodoro', 1, 'Pasta');
+             INSERT INTO meal_entry_options (meal_entry_id, meal_option_id, position, servings, option_name)
+             VALUES (1, 1, 0, 1.0, 'Pasta al pomodoro');
+             INSERT INTO meal_entry_planned_options (meal_entry_id, meal_option_id, position, servings, option_name)
+             VALUES (1, 1, 0, 1.0, 'Pasta al pomodoro');
+             INSERT INTO meal_entry_tags (meal_entry_id, tag_id, tag_name) VALUES (1, NULL, 'pasta');",
+        )
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+        drop(conn);
+
+        migrator.run(&pool).await.unwrap();
+
+        // Dropping the old table cascades; the rows have to be back afterwards
+        for table in [
+            "meal_entry_options",
+            "meal_entry_planned_options",
+            "meal_entry_tags",
+        ] {
+            let count: i64 = sqlx::query_scalar(&format!(
+                "SELECT COUNT(*) FROM {} WHERE meal_entry_id = 1",
+                table
+            ))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            assert_eq!(count, 1, "{} lost its rows", table);
+        }
+
+        let usage: i64 = sqlx::query_scalar(
+            "SELECT usage_count FROM weekly_template_usage WHERE template_id = 1",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(usage, 1);
+    }
+
+    #[tokio::test]
+    async fn test_startup_backup_only_for_existing_database() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let config = BackupConfig::for_database(&db_path);
+
+        // Fresh database: nothing to back up
+        let pool = initialize_database(db_path.clone()).await.unwrap();
+        pool.close().await;
+        assert!(backup::list_backups(&config).unwrap().is_empty());
+
+        // Reopening takes a startup snapshot
+        let pool = initialize_database(db_path).await.unwrap();
+        pool.close().await;
+        let backups = backup::list_backups(&config).unwrap();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(backups[0].reason, "startup");
+    }
+
+    #[tokio::test]
+    async fn test_backup_before_pending_migration() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let config = BackupConfig::for_database(&db_path);
+
+        // A database from before the latest migration
+        let migrator = sqlx::migrate!("./migrations");
+        let latest = migrator.iter().map(|m| m.version).max().unwrap();
+        let pool = SqlitePoolOptions::new()
+            .connect(&format!("sqlite://{}?mode=rwc", db_path.display()))
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        conn.ensure_migrations_table().await.unwrap();
+        for migration in migrator.iter().filter(|m| m.version < latest) {
+            conn.apply(migration).await.unwrap();
+        }
+        drop(conn);
+        pool.close().await;
+
+        let pool = initialize_database(db_path).await.unwrap();
+
+        let applied: Vec<i64> = sqlx::query_scalar("SELECT version FROM _sqlx_migrations")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert!(applied.contains(&latest));
+
+        let backups = backup::list_backups(&config).unwrap();
+        let reasons: Vec<&str> = backups.iter().map(|b| b.reason.as_str()).collect();
+        assert_eq!(
+            reasons,
+            ["startup".to_string(), format!("pre-migration-{}", latest)]
+        );
+    }
 }
@@ -29,8 +29,14 @@ pub enum ApiError {
     /// Foreign key constraint violation
     ForeignKeyViolation(String),
 
+    /// Library item still in use; the payload lists what blocks the delete
+    DeleteBlocked(crate::models::DeleteBlocked),
+
     /// Internal server error (500)
     InternalError(String),
+
+    /// Missing or wrong API token (401, HTTP server only)
+    Unauthorized(String),
 }
 
 impl std::fmt::Display for ApiError {
@@ -44,7 +50,9 @@ impl std::fmt::Display for ApiError {
             }
             ApiError::Conflict(msg) => write!(f, "Conflict: {}", msg),
             ApiError::ForeignKeyViolation(msg) => write!(f, "Foreign key violation: {}", msg),
+            ApiError::DeleteBlocked(blocked) => write!(f, "Delete blocked: {}", blocked),
             ApiError::InternalError(msg) => write!(f, "Internal error: {}", msg),
+            ApiError::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
         }
     }
 }
@@ -61,18 +69,18 @@ impl From<sqlx::Error> for ApiError {
                 let error_code = db_err.code().map(|c| c.to_string());
                 let error_message = db_err.message().to_string();
 
-                // SQLite constraint errors
+                // SQLite and PostgreSQL constraint errors
                 if let Some(code) = error_code {
                     match code.as_str() {
                         // UNIQUE constraint failed
-                        "1555" | "2067" => {
+                        "1555" | "2067" | "23505" => {
                             return ApiError::Conflict(format!(
                                 "Resource already exists: {}",
                                 error_message
                             ));
                         }
                         // FOREIGN KEY constraint failed
-                        "787" | "1811" => {
+                        "787" | "1811" | "23503" => {
                             return ApiError::ForeignKeyViolation(format!(
                                 "Referenced resource not found: {}",
                                 error_message
@@ -102,7 +110,12 @@ impl From<sqlx::Error> for ApiError {
 /// Convert ValidationError to ApiError
 impl From<crate::services::ValidationError> for ApiError {
     fn from(err: crate::services::ValidationError) -> Self {
-        ApiError::BusinessValidationError(err)
+        match err {
+            crate::services::ValidationError::Database { message } => {
+                ApiError::DatabaseError(message)
+            }
+            err => ApiError::BusinessValidationError(err),
+        }
     }
 }
 
@@ -137,6 +150,10 @@ mod tests {
                 ApiError::InternalError("Unexpected error".to_string()),
                 "Internal error: Unexpected error",
             ),
+            (
+                ApiError::Unauthorized("Missing bearer token".to_string()),
+                "Unauthorized: Missing bearer token",
+            ),
         ];
 
         for (error, expected) in errors {
@@ -191,8 +208,8 @@ mod tests {
 
         let val_err = ValidationError::IncompatibleSlot {
             option_name: "Pizza".to_string(),
-            slot: SlotType::Breakfast,
-            compatible_slots: vec![SlotType::Lunch],
+            slot: SlotType::BREAKFAST,
+            compatible_slots: vec![SlotType::LUNCH],
         };
 
         let api_err: ApiError = val_err.into();
@@ -4,10 +4,12 @@ pub mod commands;
 pub mod db;
 mod error;
 pub mod models;
-mod repository;
-mod services;
+pub mod repository;
+#[cfg(feature = "http-server")]
+pub mod server;
+pub mod services;
 
-use sqlx::SqlitePool;
+use repository::Backend;
 use tauri::Manager;
 
 // Re-export error types for use in commands
@@ -20,14 +22,14 @@ fn greet(name: &str) -> String {
 
 /// Test command to verify database connection
 #[tauri::command]
-async fn test_database(state: tauri::State<'_, SqlitePool>) -> Result<String, String> {
-    let pool = state.inner();
-
+async fn test_database(state: tauri::State<'_, Backend>) -> Result<String, String> {
     // Simple query to verify connection
-    let result: (i32,) = sqlx::query_as("SELECT 1")
-        .fetch_one(pool)
-        .await
-        .map_err(|e| format!("Database error: {}", e))?;
+    let result: (i32,) = match state.inner() {
+        Backend::Sqlite(pool) => sqlx::query_as("SELECT 1").fetch_one(pool).await,
+        #[cfg(feature = "postgres")]
+        Backend::Postgres(pool) => sqlx::query_as("SELECT 1").fetch_one(pool).await,
+    }
+    .map_err(|e| format!("Database error: {}", e))?;
 
     Ok(format!(
         "Database connection successful! Test query returned: {}",
@@ -42,14 +44,36 @@ pub fn run() {
         .setup(|app| {
             // Initialize database
             let db_path = db::get_database_path(app.handle());
+            let backup_config = db::BackupConfig::for_database(&db_path);
 
             tauri::async_runtime::block_on(async move {
-                let pool = db::initialize_database(db_path)
-                    .await
-                    .expect("Failed to initialize database");
+                // PostgreSQL when NUTRITION_POSTGRES_URL is set, the local SQLite file otherwise
+                let backend = Backend::from_env(|| {
+                    db::initialize_database_with_backups(db_path, &backup_config)
+                })
+                .await
+                .expect("Failed to initialize database");
+
+                // Serve the HTTP API next to IPC when a token is configured
+                #[cfg(feature = "http-server")]
+                match server::ServerConfig::from_env() {
+                    Ok(Some(config)) => {
+                        let (backend, backup_config) = (backend.clone(), backup_config.clone());
+                        tauri::async_runtime::spawn(async move {
+                            if let Err(e) =
+                                server::run_server(backend, backup_config, &config).await
+                            {
+                                eprintln!("HTTP API stopped: {}", e);
+                            }
+                        });
+                    }
+                    Ok(None) => {}
+                    Err(e) => eprintln!("HTTP API not started: {}", e),
+                }
 
-                // Make the pool available to commands
-                app.manage(pool);
+                // Make the backend and backup location available to commands
+                app.manage(backend);
+                app.manage(backup_config);
             });
 
             Ok(())
@@ -57,6 +81,13 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             greet,
             test_database,
+            // MealSlot commands
+            commands::get_all_meal_slots,
+            commands::get_active_meal_slots,
+            commands::get_meal_slot_by_id,
+            commands::create_meal_slot,
+            commands::update_meal_slot,
+            commands::delete_meal_slot,
             // Tag commands
             commands::get_all_tags,
             commands::get_tag_by_id,
@@ -66,6 +97,9 @@ pub fn run() {
             commands::create_tag,
             commands::update_tag,
             commands::delete_tag,
+            commands::get_archived_tags,
+            commands::archive_tag,
+            commands::unarchive_tag,
             // MealTemplate commands
             commands::get_all_templates,
             commands::get_template_by_id,
@@ -75,6 +109,11 @@ pub fn run() {
             commands::create_template,
             commands::update_template,
             commands::delete_template,
+            commands::get_archived_templates,
+            commands::get_template_option_groups,
+            commands::set_template_option_groups,
+            commands::archive_template,
+            commands::unarchive_template,
             // MealOption commands
             commands::get_all_options,
             commands::get_option_by_id,
@@ -85,23 +124,68 @@ pub fn run() {
             commands::create_option,
             commands::update_option,
             commands::delete_option,
+            commands::get_archived_options,
+            commands::set_option_group,
+            commands::archive_option,
+            commands::unarchive_option,
             commands::add_tags_to_option,
             commands::remove_tags_from_option,
             commands::set_option_tags,
+            commands::get_option_nutrients,
+            commands::set_option_nutrients,
+            commands::delete_option_nutrients,
             // MealEntry commands
             commands::get_entry_by_id,
             commands::get_entries_by_date,
             commands::get_entries_by_date_range,
             commands::get_entry_by_date_and_slot,
-            commands::get_entries_by_completed,
+            commands::get_entries_by_status,
             commands::get_entries_by_meal_option,
             commands::get_recent_entries,
             commands::get_weekly_usage,
+            commands::get_weekly_template_usage,
             commands::get_weekly_tag_usage,
+            commands::get_weekly_tag_usage_breakdown,
             commands::create_entry,
+            commands::create_off_plan_entry,
             commands::update_entry,
             commands::delete_entry,
             commands::validate_entry,
+            commands::log_actual,
+            // Adherence commands
+            commands::get_adherence,
+            // Nutrition commands
+            commands::get_daily_nutrient_totals,
+            commands::get_nutrient_totals,
+            // Planning commands
+            commands::copy_day,
+            commands::copy_week,
+            commands::generate_week_plan,
+            commands::suggest_options,
+            // Week summary commands
+            commands::get_week_summary,
+            // Export/import commands
+            commands::export_database,
+            commands::import_database,
+            commands::export_entries_csv,
+            commands::export_library_pack,
+            commands::import_library_pack,
+            // Plan import commands
+            commands::import_plan_text,
+            // Backup commands
+            commands::list_backups,
+            commands::create_backup,
+            commands::restore_backup,
+            // Storage backend commands
+            commands::get_storage_backend,
+            // Sync commands
+            commands::get_sync_status,
+            commands::configure_sync,
+            commands::sync_now,
+            // Audit commands
+            commands::undo_last,
+            commands::redo,
+            commands::get_history,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,63 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// Plan adherence over a set of entries, each entry being one planned slot
+/// Adherence only looks at logged entries (eaten or skipped), so slots still
+/// ahead in the plan do not lower it; off-plan entries were never planned and are
+/// counted on their own
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AdherenceCounts {
+    pub planned_slots: i64, // Every entry with a meal option, whatever its status
+    pub eaten_as_planned: i64, // Eaten with the planned options
+    pub swapped: i64,       // Eaten with other options than planned
+    pub skipped: i64,
+    pub pending: i64,           // Still planned, not logged yet
+    pub off_plan: i64,          // Entries logged without a meal option
+    pub adherence_percent: f64, // eaten_as_planned / logged * 100, 0.0 when nothing is logged
+}
+
+impl AdherenceCounts {
+    /// Add another set of counts; call `update_percent` afterwards
+    pub fn add(&mut self, other: &AdherenceCounts) {
+        self.planned_slots += other.planned_slots;
+        self.eaten_as_planned += other.eaten_as_planned;
+        self.swapped += other.swapped;
+        self.skipped += other.skipped;
+        self.pending += other.pending;
+        self.off_plan += other.off_plan;
+    }
+
+    /// Recompute the percentage from the counts
+    pub fn update_percent(&mut self) {
+        let logged = self.eaten_as_planned + self.swapped + self.skipped;
+        self.adherence_percent = if logged > 0 {
+            self.eaten_as_planned as f64 * 100.0 / logged as f64
+        } else {
+            0.0
+        };
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DayAdherence {
+    pub date: NaiveDate,
+    pub counts: AdherenceCounts,
+}
+
+/// Adherence of an ISO week, counting only the days inside the requested range
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeekAdherence {
+    pub week: String,          // Format: "YYYY-WW" (ISO week)
+    pub start_date: NaiveDate, // Monday
+    pub counts: AdherenceCounts,
+}
+
+/// Planned vs. eaten over a date range, per day, per ISO week and overall
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdherenceReport {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub days: Vec<DayAdherence>, // Every day of the range, including empty ones
+    pub weeks: Vec<WeekAdherence>,
+    pub total: AdherenceCounts,
+}
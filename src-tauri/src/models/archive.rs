@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+
+/// Library items that can be archived instead of deleted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LibraryItemKind {
+    Template,
+    Option,
+    Tag,
+}
+
+impl std::fmt::Display for LibraryItemKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LibraryItemKind::Template => write!(f, "Template"),
+            LibraryItemKind::Option => write!(f, "Option"),
+            LibraryItemKind::Tag => write!(f, "Tag"),
+        }
+    }
+}
+
+/// Something that keeps a library item from being deleted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum DeleteBlocker {
+    /// Meal entries logged with the option (or with one of the template's options)
+    MealEntries { count: i64 },
+    /// Options that would silently lose the tag
+    TaggedOptions { option_names: Vec<String> },
+    /// Child tags that would lose their parent
+    ChildTags { tag_names: Vec<String> },
+}
+
+impl std::fmt::Display for DeleteBlocker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeleteBlocker::MealEntries { count } => write!(f, "used by {} meal entries", count),
+            DeleteBlocker::TaggedOptions { option_names } => {
+                write!(f, "assigned to options {}", option_names.join(", "))
+            }
+            DeleteBlocker::ChildTags { tag_names } => {
+                write!(f, "parent of tags {}", tag_names.join(", "))
+            }
+        }
+    }
+}
+
+/// Why a library item cannot be deleted; archive it instead
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeleteBlocked {
+    pub kind: LibraryItemKind,
+    pub id: i64,
+    pub name: String,
+    pub blockers: Vec<DeleteBlocker>,
+}
+
+impl std::fmt::Display for DeleteBlocked {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reasons: Vec<String> = self.blockers.iter().map(|b| b.to_string()).collect();
+        write!(
+            f,
+            "{} '{}' cannot be deleted: {}; archive it instead",
+            self.kind,
+            self.name,
+            reasons.join("; ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delete_blocked_display() {
+        let blocked = DeleteBlocked {
+            kind: LibraryItemKind::Tag,
+            id: 3,
+            name: "pasta".to_string(),
+            blockers: vec![
+                DeleteBlocker::TaggedOptions {
+                    option_names: vec!["Carbonara".to_string(), "Pesto".to_string()],
+                },
+                DeleteBlocker::ChildTags {
+                    tag_names: vec!["pasta_integrale".to_string()],
+                },
+            ],
+        };
+
+        assert_eq!(
+            blocked.to_string(),
+            "Tag 'pasta' cannot be deleted: assigned to options Carbonara, Pesto; \
+             parent of tags pasta_integrale; archive it instead"
+        );
+    }
+
+    #[test]
+    fn test_delete_blocker_serialization() {
+        let blocker = DeleteBlocker::MealEntries { count: 4 };
+        let json = serde_json::to_value(&blocker).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"reason": "meal_entries", "count": 4})
+        );
+
+        let kind = serde_json::to_value(LibraryItemKind::Option).unwrap();
+        assert_eq!(kind, serde_json::json!("option"));
+    }
+}
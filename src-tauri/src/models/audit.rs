@@ -0,0 +1,56 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::SyncEntity;
+
+/// Whether an audited change is in effect
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditStatus {
+    Done,
+    Undone,
+    Dropped, // Undone, then superseded by a new change: it can no longer be redone
+}
+
+impl AuditStatus {
+    pub fn to_db_string(self) -> &'static str {
+        match self {
+            AuditStatus::Done => "done",
+            AuditStatus::Undone => "undone",
+            AuditStatus::Dropped => "dropped",
+        }
+    }
+
+    pub fn from_db_string(s: &str) -> Result<Self, String> {
+        match s {
+            "done" => Ok(AuditStatus::Done),
+            "undone" => Ok(AuditStatus::Undone),
+            "dropped" => Ok(AuditStatus::Dropped),
+            _ => Err(format!("Invalid audit status: {}", s)),
+        }
+    }
+}
+
+/// One entity changed by an audited command
+/// `before` and `after` hold the entity's sync data (see `SyncChange`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub id: i64,
+    pub operation: i64,
+    pub command: String,
+    pub entity: SyncEntity,
+    pub entity_id: i64,
+    pub entity_key: String,
+    pub before: Option<serde_json::Value>, // None = created by the command
+    pub after: Option<serde_json::Value>,  // None = deleted by the command
+    pub status: AuditStatus,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Everything one command changed, as undone or redone
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditOperation {
+    pub operation: i64,
+    pub command: String,
+    pub records: Vec<AuditRecord>,
+}
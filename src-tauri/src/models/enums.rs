@@ -1,51 +1,58 @@
 use serde::{Deserialize, Serialize};
 use sqlx::Type;
+use std::borrow::Cow;
 
-/// The five fixed meal slots per day
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
-#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
-#[serde(rename_all = "snake_case")]
-pub enum SlotType {
-    Breakfast,
-    MorningSnack,
-    Lunch,
-    AfternoonSnack,
-    Dinner,
-}
+/// Key of a meal slot, referencing `meal_slots.name` (e.g. "breakfast")
+/// Slots are user-configurable; the constants are the five seeded defaults
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String")]
+pub struct SlotType(Cow<'static, str>);
 
 impl SlotType {
-    /// Get all slot types in order
-    pub fn all() -> [SlotType; 5] {
+    pub const BREAKFAST: SlotType = SlotType(Cow::Borrowed("breakfast"));
+    pub const MORNING_SNACK: SlotType = SlotType(Cow::Borrowed("morning_snack"));
+    pub const LUNCH: SlotType = SlotType(Cow::Borrowed("lunch"));
+    pub const AFTERNOON_SNACK: SlotType = SlotType(Cow::Borrowed("afternoon_snack"));
+    pub const DINNER: SlotType = SlotType(Cow::Borrowed("dinner"));
+
+    /// Get the seeded default slots in order
+    pub fn defaults() -> [SlotType; 5] {
         [
-            SlotType::Breakfast,
-            SlotType::MorningSnack,
-            SlotType::Lunch,
-            SlotType::AfternoonSnack,
-            SlotType::Dinner,
+            SlotType::BREAKFAST,
+            SlotType::MORNING_SNACK,
+            SlotType::LUNCH,
+            SlotType::AFTERNOON_SNACK,
+            SlotType::DINNER,
         ]
     }
 
     /// Convert to database string representation
-    pub fn to_db_string(self) -> &'static str {
-        match self {
-            SlotType::Breakfast => "breakfast",
-            SlotType::MorningSnack => "morning_snack",
-            SlotType::Lunch => "lunch",
-            SlotType::AfternoonSnack => "afternoon_snack",
-            SlotType::Dinner => "dinner",
-        }
+    pub fn to_db_string(&self) -> &str {
+        &self.0
     }
 
     /// Parse from database string
+    /// Only checks the key format; whether the slot exists is up to `meal_slots`
     pub fn from_db_string(s: &str) -> Result<Self, String> {
-        match s {
-            "breakfast" => Ok(SlotType::Breakfast),
-            "morning_snack" => Ok(SlotType::MorningSnack),
-            "lunch" => Ok(SlotType::Lunch),
-            "afternoon_snack" => Ok(SlotType::AfternoonSnack),
-            "dinner" => Ok(SlotType::Dinner),
-            _ => Err(format!("Invalid slot type: {}", s)),
+        if s.is_empty() || !s.chars().all(|c| c.is_ascii_lowercase() || c == '_') {
+            return Err(format!("Invalid slot type: {}", s));
         }
+
+        Ok(SlotType(Cow::Owned(s.to_string())))
+    }
+}
+
+impl TryFrom<String> for SlotType {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        SlotType::from_db_string(&s)
+    }
+}
+
+impl std::fmt::Display for SlotType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
     }
 }
 
@@ -118,25 +125,61 @@ impl TagCategory {
     }
 }
 
+/// What became of a meal entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum EntryStatus {
+    Planned, // Not eaten yet
+    Eaten,
+    Skipped, // The slot was planned but nothing was eaten
+}
+
+impl EntryStatus {
+    pub fn to_db_string(self) -> &'static str {
+        match self {
+            EntryStatus::Planned => "planned",
+            EntryStatus::Eaten => "eaten",
+            EntryStatus::Skipped => "skipped",
+        }
+    }
+
+    pub fn from_db_string(s: &str) -> Result<Self, String> {
+        match s {
+            "planned" => Ok(EntryStatus::Planned),
+            "eaten" => Ok(EntryStatus::Eaten),
+            "skipped" => Ok(EntryStatus::Skipped),
+            _ => Err(format!("Invalid entry status: {}", s)),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn test_slot_type_all() {
-        let slots = SlotType::all();
+    fn test_slot_type_defaults() {
+        let slots = SlotType::defaults();
         assert_eq!(slots.len(), 5);
-        assert_eq!(slots[0], SlotType::Breakfast);
-        assert_eq!(slots[4], SlotType::Dinner);
+        assert_eq!(slots[0], SlotType::BREAKFAST);
+        assert_eq!(slots[4], SlotType::DINNER);
     }
 
     #[test]
     fn test_slot_type_db_conversion() {
-        assert_eq!(SlotType::Breakfast.to_db_string(), "breakfast");
-        assert_eq!(SlotType::MorningSnack.to_db_string(), "morning_snack");
+        assert_eq!(SlotType::BREAKFAST.to_db_string(), "breakfast");
+        assert_eq!(SlotType::MORNING_SNACK.to_db_string(), "morning_snack");
 
-        assert_eq!(SlotType::from_db_string("lunch").unwrap(), SlotType::Lunch);
-        assert!(SlotType::from_db_string("invalid").is_err());
+        assert_eq!(SlotType::from_db_string("lunch").unwrap(), SlotType::LUNCH);
+        assert_eq!(
+            SlotType::from_db_string("pre_workout")
+                .unwrap()
+                .to_db_string(),
+            "pre_workout"
+        );
+        assert!(SlotType::from_db_string("Pre Workout").is_err());
+        assert!(SlotType::from_db_string("").is_err());
     }
 
     #[test]
@@ -167,12 +210,25 @@ mod tests {
         assert!(TagCategory::from_db_string("invalid").is_err());
     }
 
+    #[test]
+    fn test_entry_status_db_conversion() {
+        assert_eq!(EntryStatus::Skipped.to_db_string(), "skipped");
+        assert_eq!(
+            EntryStatus::from_db_string("eaten").unwrap(),
+            EntryStatus::Eaten
+        );
+        assert!(EntryStatus::from_db_string("completed").is_err());
+    }
+
     #[test]
     fn test_enum_serialization() {
         // Test serde serialization (for IPC)
-        let slot = SlotType::Breakfast;
+        let slot = SlotType::BREAKFAST;
         let json = serde_json::to_string(&slot).unwrap();
         assert_eq!(json, r#""breakfast""#);
+        let parsed: SlotType = serde_json::from_str(r#""pre_workout""#).unwrap();
+        assert_eq!(parsed.to_db_string(), "pre_workout");
+        assert!(serde_json::from_str::<SlotType>(r#""Pre Workout""#).is_err());
 
         let location = LocationType::Home;
         let json = serde_json::to_string(&location).unwrap();
@@ -0,0 +1,136 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{EntryStatus, LocationType, OptionGroup, SetNutrientProfile, SlotType, TagCategory};
+
+/// Current version of the JSON export format
+/// Bump when the document shape changes and teach the importer to upgrade older versions
+pub const EXPORT_FORMAT_VERSION: u32 = 7;
+
+/// Full-database export document
+/// IDs are the ones from the exporting database; references between sections
+/// use those IDs and are remapped on import
+/// Version 1 documents have no `slots` and only use the five default slots
+/// Version 2 documents have no entry snapshots; imported entries take one from the imported library
+/// Version 3 documents have no archived flags; everything is imported as active
+/// Version 4 documents have no option groups and only single-option entries
+/// Version 5 documents flag eaten entries with `completed` and have no planned options
+/// Version 6 documents have no off-plan entries
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatabaseExport {
+    pub format_version: u32,
+    pub exported_at: DateTime<Utc>,
+    #[serde(default)]
+    pub slots: Vec<ExportedSlot>,
+    pub tags: Vec<ExportedTag>,
+    pub templates: Vec<ExportedTemplate>,
+    pub options: Vec<ExportedOption>,
+    pub option_tags: Vec<ExportedOptionTag>,
+    pub entries: Vec<ExportedEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedSlot {
+    pub name: SlotType,
+    pub display_name: String,
+    pub sort_order: i32,
+    pub active: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedTag {
+    pub id: i64,
+    pub name: String,
+    pub display_name: String,
+    pub category: TagCategory,
+    pub weekly_suggestion: Option<i32>,
+    pub parent_tag_id: Option<i64>,
+    #[serde(default)]
+    pub archived: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedTemplate {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub compatible_slots: Vec<SlotType>,
+    pub location_type: LocationType,
+    pub weekly_limit: Option<i32>,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub option_groups: Vec<OptionGroup>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedOption {
+    pub id: i64,
+    pub template_id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub nutritional_notes: Option<String>,
+    #[serde(default)]
+    pub nutrients: Option<SetNutrientProfile>,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub option_group: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedOptionTag {
+    pub meal_option_id: i64,
+    pub tag_id: i64,
+}
+
+/// Off-plan entries have no option; their description and estimated tags are the snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedEntry {
+    pub meal_option_id: Option<i64>, // None for off-plan entries
+    pub date: NaiveDate,
+    pub slot_type: SlotType,
+    pub location: LocationType,
+    pub servings: f64,
+    pub notes: Option<String>,
+    pub status: EntryStatus,
+    #[serde(default)]
+    pub snapshot: Option<ExportedEntrySnapshot>,
+    #[serde(default)]
+    pub extra_options: Vec<ExportedEntryOption>,
+    #[serde(default)]
+    pub planned_options: Vec<ExportedEntryOption>, // Main first; empty = planned as selected
+    #[serde(default)]
+    pub estimated_nutrients: Option<SetNutrientProfile>, // Off-plan entries only
+}
+
+/// An option selected alongside the main one in a composite meal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedEntryOption {
+    pub meal_option_id: i64,
+    pub servings: f64,
+    pub option_name: String, // Snapshot
+}
+
+/// Option and template data an entry was logged with
+/// Tags are referenced by name since they may no longer exist
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedEntrySnapshot {
+    pub option_name: String,
+    pub template_name: String,
+    pub template_location_type: LocationType,
+    pub tag_names: Vec<String>,
+}
+
+/// Result of importing a database export
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImportSummary {
+    pub slots_created: usize,
+    pub slots_merged: usize, // Existing slots reused by name
+    pub tags_created: usize,
+    pub tags_merged: usize, // Existing tags reused by name
+    pub templates_created: usize,
+    pub options_created: usize,
+    pub option_tags_created: usize,
+    pub entries_created: usize,
+}
@@ -0,0 +1,92 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    ExportedSlot, LibraryItemKind, LocationType, OptionGroup, SetNutrientProfile, SlotType,
+    TagCategory,
+};
+
+/// Current version of the library pack format
+/// Bump when the document shape changes and teach the importer to upgrade older versions
+pub const LIBRARY_PACK_FORMAT_VERSION: u32 = 1;
+
+/// A shareable template library: tags, templates and options, without meal history
+/// Everything is referenced by name so a pack can be merged into another database
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LibraryPack {
+    pub format_version: u32,
+    pub exported_at: DateTime<Utc>,
+    #[serde(default)]
+    pub slots: Vec<ExportedSlot>, // Slots the templates are compatible with
+    pub tags: Vec<PackTag>,
+    pub templates: Vec<PackTemplate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackTag {
+    pub name: String,
+    pub display_name: String,
+    pub category: TagCategory,
+    pub weekly_suggestion: Option<i32>,
+    pub parent: Option<String>, // Name of the parent tag
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackTemplate {
+    pub name: String,
+    pub description: Option<String>,
+    pub compatible_slots: Vec<SlotType>,
+    pub location_type: LocationType,
+    pub weekly_limit: Option<i32>,
+    #[serde(default)]
+    pub option_groups: Vec<OptionGroup>,
+    pub options: Vec<PackOption>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackOption {
+    pub name: String,
+    pub description: Option<String>,
+    pub nutritional_notes: Option<String>,
+    #[serde(default)]
+    pub nutrients: Option<SetNutrientProfile>,
+    #[serde(default)]
+    pub option_group: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>, // Tag names
+}
+
+/// What to do with a pack item whose name exists in the library with different data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PackConflictStrategy {
+    Skip,      // Keep the library item as it is
+    Overwrite, // Replace the library item's data with the pack's
+    Rename,    // Keep the library item and add the pack's under a new name
+}
+
+/// A pack item that matched a library item by name but differs from it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackConflict {
+    pub kind: LibraryItemKind,
+    pub name: String,
+    pub template_name: Option<String>, // Template of a conflicting option
+    pub fields: Vec<String>,           // Fields that differ, e.g. "category", "tags"
+    pub resolution: PackConflictStrategy,
+    pub renamed_to: Option<String>,
+}
+
+/// Result of importing a library pack
+/// Merged items matched a library item by name; renamed items count as created
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LibraryPackImport {
+    pub preview: bool, // true = nothing was written
+    pub slots_created: usize,
+    pub tags_created: usize,
+    pub tags_merged: usize,
+    pub templates_created: usize,
+    pub templates_merged: usize,
+    pub options_created: usize,
+    pub options_merged: usize,
+    pub conflicts: Vec<PackConflict>,
+}
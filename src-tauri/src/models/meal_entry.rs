@@ -1,25 +1,58 @@
-use chrono::{DateTime, NaiveDate, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
-use super::{LocationType, SlotType};
+use super::{EntryStatus, LocationType, SetNutrientProfile, SlotType};
 
 /// Level 4: Meal Entry - Actual meal logging and planning
-/// Tracks both planned meals (future) and logged meals (past/completed)
+/// Tracks both planned meals (future) and logged meals (eaten or skipped)
+/// Option name, template and tags are a snapshot taken when the entry was created
+/// and refreshed when it was marked as eaten, so history survives library edits
+/// Composite meals select several options of the template; `meal_option_id` and
+/// `servings` describe the main one, `options` lists all of them (main first)
+/// `options` is what was eaten (or will be); `planned_options` keeps what was
+/// planned when the entry was created, so a swap does not erase the plan
+/// Off-plan entries (restaurant, ad-hoc meals) have no option and no template:
+/// `option_name` is their free-text description, `tag_names` their estimated tags
+/// and `estimated_nutrients` their estimated profile per serving
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
 pub struct MealEntry {
     pub id: i64,
-    pub meal_option_id: i64,
+    pub meal_option_id: Option<i64>, // None for off-plan entries
     pub date: NaiveDate,
     pub slot_type: SlotType,
     pub location: LocationType,
     pub servings: f64, // Default 1.0, nutrition plan uses strict serving sizes
     pub notes: Option<String>,
-    pub completed: bool, // FALSE = planned, TRUE = consumed
+    pub status: EntryStatus,
+    pub option_name: String,
+    pub template_id: Option<i64>, // None for off-plan entries
+    pub template_name: String,
+    pub template_location_type: LocationType, // Location rule of the template at the time
+    pub tag_names: Vec<String>,               // Tags of all selected options at the time, by name
+    pub options: Vec<MealEntryOption>,
+    pub planned_options: Vec<MealEntryOption>,
+    pub estimated_nutrients: Option<SetNutrientProfile>, // Off-plan entries only
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// One option selected in a meal entry
+/// Stored in `meal_entry_options`; the name is a snapshot like the entry's
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MealEntryOption {
+    pub meal_option_id: i64,
+    pub option_name: String,
+    pub servings: f64,
+}
+
+/// An option selected alongside the main one when creating an entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SelectedOption {
+    pub meal_option_id: i64,
+    pub servings: Option<f64>, // Defaults to 1.0 if not provided
+}
+
 /// Input for creating a new meal entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateMealEntry {
@@ -29,7 +62,25 @@ pub struct CreateMealEntry {
     pub location: LocationType,
     pub servings: Option<f64>, // Defaults to 1.0 if not provided
     pub notes: Option<String>,
-    pub completed: Option<bool>, // Defaults to false (planned)
+    pub status: Option<EntryStatus>, // Defaults to planned
+    #[serde(default)]
+    pub extra_options: Vec<SelectedOption>, // Other options of the same template
+}
+
+/// Input for logging an off-plan meal (restaurant, ad-hoc) without a meal option
+/// Estimated tags count toward weekly tag usage like the tags of an option
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateOffPlanEntry {
+    pub date: NaiveDate,
+    pub slot_type: SlotType,
+    pub location: LocationType,
+    pub description: String, // e.g. "Business dinner, grilled fish and salad"
+    pub servings: Option<f64>, // Defaults to 1.0 if not provided
+    pub notes: Option<String>,
+    pub status: Option<EntryStatus>, // Defaults to eaten
+    #[serde(default)]
+    pub tag_ids: Vec<i64>, // Estimated tags
+    pub estimated_nutrients: Option<SetNutrientProfile>, // Per serving
 }
 
 /// Input for updating an existing meal entry
@@ -38,20 +89,157 @@ pub struct UpdateMealEntry {
     pub location: Option<LocationType>,
     pub servings: Option<f64>,
     pub notes: Option<Option<String>>,
-    pub completed: Option<bool>,
+    pub status: Option<EntryStatus>, // Eaten here means eaten as planned
+}
+
+/// What was actually eaten in a slot, logged against its entry
+/// Replaces the entry's options and marks it as eaten; the planned options stay
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogActualMeal {
+    pub meal_option_id: i64,
+    pub servings: Option<f64>, // Defaults to 1.0 if not provided
+    #[serde(default)]
+    pub extra_options: Vec<SelectedOption>, // Other options of the same template
+    pub location: Option<LocationType>, // Keeps the entry's location if not provided
+    pub notes: Option<Option<String>>,
+}
+
+/// Check a main option and the options selected alongside it
+fn validate_selection(
+    meal_option_id: i64,
+    servings: Option<f64>,
+    extra_options: &[SelectedOption],
+) -> Result<(), String> {
+    if meal_option_id <= 0 {
+        return Err("Invalid meal option ID".to_string());
+    }
+
+    if let Some(servings) = servings {
+        if servings <= 0.0 {
+            return Err("Servings must be positive".to_string());
+        }
+    }
+
+    for (i, extra) in extra_options.iter().enumerate() {
+        if extra.meal_option_id <= 0 {
+            return Err("Invalid meal option ID".to_string());
+        }
+
+        if extra.servings.is_some_and(|s| s <= 0.0) {
+            return Err("Servings must be positive".to_string());
+        }
+
+        if extra.meal_option_id == meal_option_id
+            || extra_options[..i]
+                .iter()
+                .any(|o| o.meal_option_id == extra.meal_option_id)
+        {
+            return Err(format!(
+                "Meal option {} is selected more than once",
+                extra.meal_option_id
+            ));
+        }
+    }
+
+    Ok(())
 }
 
 impl CreateMealEntry {
     /// Validate entry creation data
     pub fn validate(&self) -> Result<(), String> {
-        if self.meal_option_id <= 0 {
-            return Err("Invalid meal option ID".to_string());
+        validate_selection(self.meal_option_id, self.servings, &self.extra_options)
+    }
+
+    /// IDs of every selected option, main option first
+    pub fn option_ids(&self) -> Vec<i64> {
+        std::iter::once(self.meal_option_id)
+            .chain(self.extra_options.iter().map(|o| o.meal_option_id))
+            .collect()
+    }
+
+    /// Get servings value, defaulting to 1.0 if not provided
+    pub fn servings_or_default(&self) -> f64 {
+        self.servings.unwrap_or(1.0)
+    }
+
+    /// Get status value, defaulting to planned if not provided
+    pub fn status_or_default(&self) -> EntryStatus {
+        self.status.unwrap_or(EntryStatus::Planned)
+    }
+}
+
+impl MealEntry {
+    /// ISO week key of a date ("YYYY-WW")
+    /// Weeks start on Monday as per ISO 8601, and belong to the year of their Thursday.
+    /// This is the only place week keys are built: the same value is stored in
+    /// `meal_entries.iso_week`, which every weekly usage view groups by.
+    pub fn week_key(date: NaiveDate) -> String {
+        let iso_week = date.iso_week();
+        format!("{}-{:02}", iso_week.year(), iso_week.week())
+    }
+
+    /// Whether the entry was eaten with the options it was planned with
+    /// Servings may differ; any other option makes it a swap
+    pub fn eaten_as_planned(&self) -> bool {
+        self.status == EntryStatus::Eaten && !self.is_off_plan() && !self.is_swap()
+    }
+
+    /// Whether the entry was logged without a meal option
+    pub fn is_off_plan(&self) -> bool {
+        self.meal_option_id.is_none()
+    }
+
+    /// Whether what was eaten differs from the plan
+    pub fn is_swap(&self) -> bool {
+        let mut eaten: Vec<i64> = self.options.iter().map(|o| o.meal_option_id).collect();
+        let mut planned: Vec<i64> = self
+            .planned_options
+            .iter()
+            .map(|o| o.meal_option_id)
+            .collect();
+        eaten.sort_unstable();
+        planned.sort_unstable();
+
+        self.status == EntryStatus::Eaten && eaten != planned
+    }
+}
+
+impl LogActualMeal {
+    /// Validate the logged selection
+    pub fn validate(&self) -> Result<(), String> {
+        validate_selection(self.meal_option_id, self.servings, &self.extra_options)
+    }
+
+    /// IDs of every eaten option, main option first
+    pub fn option_ids(&self) -> Vec<i64> {
+        std::iter::once(self.meal_option_id)
+            .chain(self.extra_options.iter().map(|o| o.meal_option_id))
+            .collect()
+    }
+
+    /// Get servings value, defaulting to 1.0 if not provided
+    pub fn servings_or_default(&self) -> f64 {
+        self.servings.unwrap_or(1.0)
+    }
+}
+
+impl CreateOffPlanEntry {
+    /// Validate off-plan entry data
+    pub fn validate(&self) -> Result<(), String> {
+        if self.description.trim().is_empty() {
+            return Err("Description cannot be empty".to_string());
         }
 
-        if let Some(servings) = self.servings {
-            if servings <= 0.0 {
-                return Err("Servings must be positive".to_string());
-            }
+        if self.servings.is_some_and(|s| s <= 0.0) {
+            return Err("Servings must be positive".to_string());
+        }
+
+        if self.tag_ids.iter().any(|&id| id <= 0) {
+            return Err("Invalid tag ID".to_string());
+        }
+
+        if let Some(nutrients) = &self.estimated_nutrients {
+            nutrients.validate()?;
         }
 
         Ok(())
@@ -62,9 +250,10 @@ impl CreateMealEntry {
         self.servings.unwrap_or(1.0)
     }
 
-    /// Get completed value, defaulting to false (planned) if not provided
-    pub fn completed_or_default(&self) -> bool {
-        self.completed.unwrap_or(false)
+    /// Get status value, defaulting to eaten if not provided
+    /// Off-plan meals are usually logged after the fact
+    pub fn status_or_default(&self) -> EntryStatus {
+        self.status.unwrap_or(EntryStatus::Eaten)
     }
 }
 
@@ -82,6 +271,7 @@ impl UpdateMealEntry {
 }
 
 /// Helper struct for weekly usage tracking
+/// Counts entries that selected the option, as main or extra option
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct WeeklyUsage {
     pub meal_option_id: i64,
@@ -89,7 +279,19 @@ pub struct WeeklyUsage {
     pub usage_count: i64,
 }
 
+/// Helper struct for weekly template usage tracking
+/// Counts entries of all options belonging to the template
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct WeeklyTemplateUsage {
+    pub template_id: i64,
+    pub template_name: String,
+    pub week: String, // Format: "YYYY-WW" (ISO week)
+    pub usage_count: i64,
+    pub weekly_limit: Option<i32>,
+}
+
 /// Helper struct for weekly tag usage tracking
+/// Usage includes entries tagged with any descendant tag
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct WeeklyTagUsage {
     pub tag_id: i64,
@@ -98,6 +300,18 @@ pub struct WeeklyTagUsage {
     pub usage_count: i64,
 }
 
+/// One tag in a weekly tag usage breakdown (ancestors, the tag itself, descendants)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WeeklyTagUsageNode {
+    pub tag_id: i64,
+    pub tag_name: String,
+    pub display_name: String,
+    pub parent_tag_id: Option<i64>,
+    pub weekly_suggestion: Option<i32>,
+    pub direct_count: i64, // Entries tagged with exactly this tag
+    pub total_count: i64,  // Entries tagged with this tag or any descendant
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -108,11 +322,12 @@ mod tests {
         let valid = CreateMealEntry {
             meal_option_id: 1,
             date: NaiveDate::from_ymd_opt(2024, 11, 4).unwrap(),
-            slot_type: SlotType::Breakfast,
+            slot_type: SlotType::BREAKFAST,
             location: LocationType::Home,
             servings: Some(1.0),
             notes: None,
-            completed: Some(false),
+            status: Some(EntryStatus::Planned),
+            extra_options: vec![],
         };
         assert!(valid.validate().is_ok());
 
@@ -120,11 +335,12 @@ mod tests {
         let invalid = CreateMealEntry {
             meal_option_id: 0,
             date: NaiveDate::from_ymd_opt(2024, 11, 4).unwrap(),
-            slot_type: SlotType::Breakfast,
+            slot_type: SlotType::BREAKFAST,
             location: LocationType::Home,
             servings: Some(1.0),
             notes: None,
-            completed: None,
+            status: None,
+            extra_options: vec![],
         };
         assert!(invalid.validate().is_err());
 
@@ -132,29 +348,116 @@ mod tests {
         let invalid = CreateMealEntry {
             meal_option_id: 1,
             date: NaiveDate::from_ymd_opt(2024, 11, 4).unwrap(),
-            slot_type: SlotType::Breakfast,
+            slot_type: SlotType::BREAKFAST,
             location: LocationType::Home,
             servings: Some(0.0),
             notes: None,
-            completed: None,
+            status: None,
+            extra_options: vec![],
         };
         assert!(invalid.validate().is_err());
     }
 
+    #[test]
+    fn test_create_entry_extra_options_validation() {
+        let mut entry = CreateMealEntry {
+            meal_option_id: 1,
+            date: NaiveDate::from_ymd_opt(2024, 11, 4).unwrap(),
+            slot_type: SlotType::BREAKFAST,
+            location: LocationType::Home,
+            servings: None,
+            notes: None,
+            status: None,
+            extra_options: vec![
+                SelectedOption {
+                    meal_option_id: 2,
+                    servings: Some(0.5),
+                },
+                SelectedOption {
+                    meal_option_id: 3,
+                    servings: None,
+                },
+            ],
+        };
+        assert!(entry.validate().is_ok());
+        assert_eq!(entry.option_ids(), vec![1, 2, 3]);
+
+        // The main option selected again
+        entry.extra_options[1].meal_option_id = 1;
+        assert!(entry.validate().is_err());
+
+        // Invalid servings on an extra option
+        entry.extra_options[1].meal_option_id = 3;
+        entry.extra_options[0].servings = Some(0.0);
+        assert!(entry.validate().is_err());
+    }
+
     #[test]
     fn test_create_entry_defaults() {
         let entry = CreateMealEntry {
             meal_option_id: 1,
             date: NaiveDate::from_ymd_opt(2024, 11, 4).unwrap(),
-            slot_type: SlotType::Lunch,
+            slot_type: SlotType::LUNCH,
             location: LocationType::Office,
             servings: None,
             notes: None,
-            completed: None,
+            status: None,
+            extra_options: vec![],
         };
 
         assert_eq!(entry.servings_or_default(), 1.0);
-        assert!(!entry.completed_or_default());
+        assert_eq!(entry.status_or_default(), EntryStatus::Planned);
+    }
+
+    #[test]
+    fn test_off_plan_entry_validation() {
+        let valid = CreateOffPlanEntry {
+            date: NaiveDate::from_ymd_opt(2024, 11, 4).unwrap(),
+            slot_type: SlotType::DINNER,
+            location: LocationType::Restaurant,
+            description: "Business dinner".to_string(),
+            servings: None,
+            notes: None,
+            status: None,
+            tag_ids: vec![1, 2],
+            estimated_nutrients: Some(SetNutrientProfile {
+                kcal: 900.0,
+                protein_g: 45.0,
+                carbs_g: 80.0,
+                fat_g: 40.0,
+                fiber_g: 6.0,
+            }),
+        };
+        assert!(valid.validate().is_ok());
+        assert_eq!(valid.servings_or_default(), 1.0);
+        assert_eq!(valid.status_or_default(), EntryStatus::Eaten);
+
+        // Blank description
+        let invalid = CreateOffPlanEntry {
+            description: "   ".to_string(),
+            ..valid.clone()
+        };
+        assert!(invalid.validate().is_err());
+
+        // Invalid tag ID
+        let invalid = CreateOffPlanEntry {
+            tag_ids: vec![0],
+            ..valid.clone()
+        };
+        assert!(invalid.validate().is_err());
+
+        // Negative estimate
+        let invalid = CreateOffPlanEntry {
+            estimated_nutrients: Some(SetNutrientProfile {
+                kcal: -1.0,
+                protein_g: 0.0,
+                carbs_g: 0.0,
+                fat_g: 0.0,
+                fiber_g: 0.0,
+            }),
+            ..valid
+        };
+        assert!(invalid.validate().is_err());
     }
 
     #[test]
@@ -163,7 +466,7 @@ mod tests {
             location: Some(LocationType::Restaurant),
             servings: Some(1.5),
             notes: Some(Some("Had extra avocado".to_string())),
-            completed: Some(true),
+            status: Some(EntryStatus::Eaten),
         };
         assert!(valid.validate().is_ok());
 
@@ -172,7 +475,7 @@ mod tests {
             location: None,
             servings: Some(-1.0),
             notes: None,
-            completed: None,
+            status: None,
         };
         assert!(invalid.validate().is_err());
     }
@@ -182,11 +485,12 @@ mod tests {
         let entry = CreateMealEntry {
             meal_option_id: 5,
             date: NaiveDate::from_ymd_opt(2024, 11, 5).unwrap(),
-            slot_type: SlotType::Dinner,
+            slot_type: SlotType::DINNER,
             location: LocationType::Home,
             servings: Some(1.2),
             notes: Some("Extra vegetables".to_string()),
-            completed: Some(true),
+            status: Some(EntryStatus::Eaten),
+            extra_options: vec![],
         };
 
         let json = serde_json::to_string(&entry).unwrap();
@@ -194,7 +498,7 @@ mod tests {
 
         assert_eq!(deserialized.meal_option_id, 5);
         assert_eq!(deserialized.servings, Some(1.2));
-        assert_eq!(deserialized.completed, Some(true));
+        assert_eq!(deserialized.status, Some(EntryStatus::Eaten));
     }
 
     #[test]
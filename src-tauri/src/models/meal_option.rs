@@ -11,6 +11,8 @@ pub struct MealOption {
     pub name: String,
     pub description: Option<String>,
     pub nutritional_notes: Option<String>,
+    pub option_group: Option<String>, // Option group of the template this option belongs to
+    pub archived: bool,               // Hidden from listings and suggestions, kept for history
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -115,6 +117,8 @@ mod tests {
                 name: "pasta_integrale".to_string(),
                 description: Some("Whole wheat pasta".to_string()),
                 nutritional_notes: None,
+                option_group: None,
+                archived: false,
                 created_at: Utc::now(),
                 updated_at: Utc::now(),
             },
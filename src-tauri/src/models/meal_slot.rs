@@ -0,0 +1,114 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::SlotType;
+
+/// A configurable meal slot of the day (e.g., "Breakfast", "Pre-workout")
+/// Entries and templates reference slots by `name`, which never changes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MealSlot {
+    pub id: i64,
+    pub name: SlotType,       // Internal key: "breakfast", "pre_workout"
+    pub display_name: String, // User-facing: "Breakfast", "Pre-workout"
+    pub sort_order: i32,      // Position within the day, ascending
+    pub active: bool,         // Inactive slots keep their history but take no new entries
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Input for creating a new meal slot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateMealSlot {
+    pub name: String,
+    pub display_name: String,
+    pub sort_order: i32,
+    pub active: Option<bool>, // Defaults to true
+}
+
+/// Input for updating an existing meal slot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateMealSlot {
+    pub display_name: Option<String>,
+    pub sort_order: Option<i32>,
+    pub active: Option<bool>,
+}
+
+impl CreateMealSlot {
+    /// Validate slot creation data
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Slot name cannot be empty".to_string());
+        }
+
+        // Name is the key stored on entries and templates
+        if SlotType::from_db_string(&self.name).is_err() {
+            return Err("Slot name must be lowercase with underscores only".to_string());
+        }
+
+        if self.display_name.trim().is_empty() {
+            return Err("Slot display name cannot be empty".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+impl UpdateMealSlot {
+    /// Validate slot update data
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(ref display_name) = self.display_name {
+            if display_name.trim().is_empty() {
+                return Err("Slot display name cannot be empty".to_string());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_meal_slot_validation() {
+        let valid = CreateMealSlot {
+            name: "pre_workout".to_string(),
+            display_name: "Pre-workout".to_string(),
+            sort_order: 35,
+            active: None,
+        };
+        assert!(valid.validate().is_ok());
+
+        // Invalid name format
+        let invalid = CreateMealSlot {
+            name: "Pre Workout".to_string(),
+            ..valid.clone()
+        };
+        assert!(invalid.validate().is_err());
+
+        // Empty display name
+        let invalid = CreateMealSlot {
+            display_name: "  ".to_string(),
+            ..valid
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_update_meal_slot_validation() {
+        let valid = UpdateMealSlot {
+            display_name: None,
+            sort_order: Some(1),
+            active: Some(false),
+        };
+        assert!(valid.validate().is_ok());
+
+        let invalid = UpdateMealSlot {
+            display_name: Some(String::new()),
+            sort_order: None,
+            active: None,
+        };
+        assert!(invalid.validate().is_err());
+    }
+}
@@ -1,12 +1,11 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
 
 use super::{LocationType, SlotType};
 
 /// Level 2: Meal Template - The "cards" that fill slots (the "Oppure" choices)
 /// Example: "Pane con marmellata e formaggio spalmabile"
-/// Note: compatible_slots is stored as JSON in the database
+/// Note: compatible_slots is stored in the meal_template_slots join table
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MealTemplate {
     pub id: i64,
@@ -15,45 +14,11 @@ pub struct MealTemplate {
     pub compatible_slots: Vec<SlotType>, // Which slots can this template fill
     pub location_type: LocationType,     // Where this meal can be prepared
     pub weekly_limit: Option<i32>,       // Hard limit: max times per week (NULL = unlimited)
+    pub archived: bool,                  // Hidden (with its options) from listings and suggestions
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-/// Row structure for fetching from database (compatible_slots as String)
-#[derive(Debug, FromRow)]
-pub struct MealTemplateRow {
-    pub id: i64,
-    pub name: String,
-    pub description: Option<String>,
-    pub compatible_slots: String, // JSON string from DB
-    pub location_type: String,    // TEXT from DB
-    pub weekly_limit: Option<i32>,
-    pub created_at: DateTime<Utc>,
-    pub updated_at: DateTime<Utc>,
-}
-
-impl TryFrom<MealTemplateRow> for MealTemplate {
-    type Error = String;
-
-    fn try_from(row: MealTemplateRow) -> Result<Self, Self::Error> {
-        let compatible_slots = MealTemplate::parse_compatible_slots(&row.compatible_slots)
-            .map_err(|e| format!("Failed to parse compatible_slots: {}", e))?;
-
-        let location_type = LocationType::from_db_string(&row.location_type)?;
-
-        Ok(MealTemplate {
-            id: row.id,
-            name: row.name,
-            description: row.description,
-            compatible_slots,
-            location_type,
-            weekly_limit: row.weekly_limit,
-            created_at: row.created_at,
-            updated_at: row.updated_at,
-        })
-    }
-}
-
 /// Input for creating a new meal template
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateMealTemplate {
@@ -74,6 +39,43 @@ pub struct UpdateMealTemplate {
     pub weekly_limit: Option<Option<i32>>, // None = no change, Some(None) = clear, Some(Some(n)) = set to n
 }
 
+/// Combination rule of a template: how many options to pick from one group
+/// Stored in `meal_template_option_groups`; options join a group by name
+/// Example: "pane" 1..1 (exactly one), "spalmabile" 0..1 (optional), "e/o" 1..2
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OptionGroup {
+    pub name: String,
+    pub min_choices: i32,
+    pub max_choices: Option<i32>, // None = no upper bound
+}
+
+impl OptionGroup {
+    /// Validate a group definition
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Option group name cannot be empty".to_string());
+        }
+
+        if self.min_choices < 0 {
+            return Err(format!(
+                "Option group '{}' cannot require a negative number of choices",
+                self.name
+            ));
+        }
+
+        if let Some(max) = self.max_choices {
+            if max < 1 || max < self.min_choices {
+                return Err(format!(
+                    "Option group '{}' allows at most {} but requires at least {}",
+                    self.name, max, self.min_choices
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl CreateMealTemplate {
     /// Validate template creation data
     pub fn validate(&self) -> Result<(), String> {
@@ -95,19 +97,6 @@ impl CreateMealTemplate {
     }
 }
 
-// Helper functions for converting compatible_slots to/from JSON
-impl MealTemplate {
-    /// Parse compatible slots from JSON string (from database)
-    pub fn parse_compatible_slots(json: &str) -> Result<Vec<SlotType>, serde_json::Error> {
-        serde_json::from_str(json)
-    }
-
-    /// Convert compatible slots to JSON string (for database)
-    pub fn serialize_compatible_slots(slots: &[SlotType]) -> String {
-        serde_json::to_string(slots).unwrap()
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -117,7 +106,7 @@ mod tests {
         let valid = CreateMealTemplate {
             name: "Pane con marmellata".to_string(),
             description: Some("Breakfast bread with jam".to_string()),
-            compatible_slots: vec![SlotType::Breakfast, SlotType::MorningSnack],
+            compatible_slots: vec![SlotType::BREAKFAST, SlotType::MORNING_SNACK],
             location_type: LocationType::Home,
             weekly_limit: Some(3),
         };
@@ -127,7 +116,7 @@ mod tests {
         let invalid = CreateMealTemplate {
             name: "".to_string(),
             description: None,
-            compatible_slots: vec![SlotType::Breakfast],
+            compatible_slots: vec![SlotType::BREAKFAST],
             location_type: LocationType::Home,
             weekly_limit: None,
         };
@@ -147,7 +136,7 @@ mod tests {
         let invalid = CreateMealTemplate {
             name: "Test".to_string(),
             description: None,
-            compatible_slots: vec![SlotType::Breakfast],
+            compatible_slots: vec![SlotType::BREAKFAST],
             location_type: LocationType::Home,
             weekly_limit: Some(0),
         };
@@ -157,7 +146,7 @@ mod tests {
         let invalid = CreateMealTemplate {
             name: "Test".to_string(),
             description: None,
-            compatible_slots: vec![SlotType::Breakfast],
+            compatible_slots: vec![SlotType::BREAKFAST],
             location_type: LocationType::Home,
             weekly_limit: Some(-1),
         };
@@ -169,13 +158,13 @@ mod tests {
         let template = CreateMealTemplate {
             name: "Yogurt".to_string(),
             description: None,
-            compatible_slots: vec![SlotType::Breakfast, SlotType::MorningSnack],
+            compatible_slots: vec![SlotType::BREAKFAST, SlotType::MORNING_SNACK],
             location_type: LocationType::Any,
             weekly_limit: None,
         };
 
-        assert!(template.compatible_slots.contains(&SlotType::Breakfast));
-        assert!(!template.compatible_slots.contains(&SlotType::Dinner));
+        assert!(template.compatible_slots.contains(&SlotType::BREAKFAST));
+        assert!(!template.compatible_slots.contains(&SlotType::DINNER));
     }
 
     #[test]
@@ -183,7 +172,7 @@ mod tests {
         let template = CreateMealTemplate {
             name: "Pasta con verdure".to_string(),
             description: Some("Whole wheat pasta with vegetables".to_string()),
-            compatible_slots: vec![SlotType::Lunch, SlotType::Dinner],
+            compatible_slots: vec![SlotType::LUNCH, SlotType::DINNER],
             location_type: LocationType::Home,
             weekly_limit: Some(4),
         };
@@ -197,78 +186,42 @@ mod tests {
     }
 
     #[test]
-    fn test_compatible_slots_json_conversion() {
-        let slots = vec![SlotType::Breakfast, SlotType::Lunch];
-        let json_str = MealTemplate::serialize_compatible_slots(&slots);
-
-        assert!(json_str.contains("breakfast"));
-        assert!(json_str.contains("lunch"));
-
-        let parsed = MealTemplate::parse_compatible_slots(&json_str).unwrap();
-        assert_eq!(parsed, slots);
-    }
-
-    #[test]
-    fn test_meal_template_row_conversion() {
-        use chrono::Utc;
-
-        // Test successful conversion
-        let row = MealTemplateRow {
-            id: 1,
-            name: "Test Template".to_string(),
-            description: Some("Test description".to_string()),
-            compatible_slots: r#"["breakfast","lunch"]"#.to_string(),
-            location_type: "home".to_string(),
-            weekly_limit: Some(3),
-            created_at: Utc::now(),
-            updated_at: Utc::now(),
+    fn test_option_group_validation() {
+        let exactly_one = OptionGroup {
+            name: "pane".to_string(),
+            min_choices: 1,
+            max_choices: Some(1),
         };
+        assert!(exactly_one.validate().is_ok());
 
-        let template: MealTemplate = row.try_into().unwrap();
-        assert_eq!(template.id, 1);
-        assert_eq!(template.name, "Test Template");
-        assert_eq!(template.compatible_slots.len(), 2);
-        assert_eq!(template.location_type, LocationType::Home);
-        assert_eq!(template.weekly_limit, Some(3));
-
-        // Test invalid compatible_slots JSON
-        let invalid_row = MealTemplateRow {
-            id: 1,
-            name: "Test".to_string(),
-            description: None,
-            compatible_slots: "invalid json".to_string(),
-            location_type: "home".to_string(),
-            weekly_limit: None,
-            created_at: Utc::now(),
-            updated_at: Utc::now(),
+        let and_or = OptionGroup {
+            name: "spalmabile".to_string(),
+            min_choices: 1,
+            max_choices: None,
         };
+        assert!(and_or.validate().is_ok());
 
-        let result: Result<MealTemplate, String> = invalid_row.try_into();
-        assert!(result.is_err());
-        assert!(result
-            .unwrap_err()
-            .contains("Failed to parse compatible_slots"));
-
-        // Test invalid location_type
-        let invalid_row = MealTemplateRow {
-            id: 1,
-            name: "Test".to_string(),
-            description: None,
-            compatible_slots: r#"["breakfast"]"#.to_string(),
-            location_type: "invalid_location".to_string(),
-            weekly_limit: None,
-            created_at: Utc::now(),
-            updated_at: Utc::now(),
+        // Max below min
+        let invalid = OptionGroup {
+            name: "pane".to_string(),
+            min_choices: 2,
+            max_choices: Some(1),
         };
+        assert!(invalid.validate().is_err());
 
-        let result: Result<MealTemplate, String> = invalid_row.try_into();
-        assert!(result.is_err());
-    }
+        // A group nothing can be picked from
+        let invalid = OptionGroup {
+            name: "pane".to_string(),
+            min_choices: 0,
+            max_choices: Some(0),
+        };
+        assert!(invalid.validate().is_err());
 
-    #[test]
-    fn test_parse_compatible_slots_error() {
-        // Test error case for invalid JSON
-        let result = MealTemplate::parse_compatible_slots("not valid json");
-        assert!(result.is_err());
+        let invalid = OptionGroup {
+            name: " ".to_string(),
+            min_choices: 1,
+            max_choices: Some(1),
+        };
+        assert!(invalid.validate().is_err());
     }
 }
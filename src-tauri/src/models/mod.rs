@@ -5,14 +5,38 @@
 // Note: These will be used in Phase 2 (Tauri commands)
 #![allow(dead_code)]
 
+mod adherence;
+mod archive;
+mod audit;
 mod enums;
+mod export;
+mod library_pack;
 mod meal_entry;
 mod meal_option;
+mod meal_slot;
 mod meal_template;
+mod nutrient_profile;
+mod plan_import;
+mod planning;
+mod storage;
+mod sync;
 mod tag;
+mod week_summary;
 
+pub use adherence::*;
+pub use archive::*;
+pub use audit::*;
 pub use enums::*;
+pub use export::*;
+pub use library_pack::*;
 pub use meal_entry::*;
 pub use meal_option::*;
+pub use meal_slot::*;
 pub use meal_template::*;
+pub use nutrient_profile::*;
+pub use plan_import::*;
+pub use planning::*;
+pub use storage::*;
+pub use sync::*;
 pub use tag::*;
+pub use week_summary::*;
@@ -0,0 +1,148 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Nutrient profile of a meal option, expressed per serving
+/// Stored in `meal_option_nutrients` (one row per option)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct NutrientProfile {
+    pub meal_option_id: i64,
+    pub kcal: f64,
+    pub protein_g: f64,
+    pub carbs_g: f64,
+    pub fat_g: f64,
+    pub fiber_g: f64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Input for creating or replacing the nutrient profile of a meal option
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetNutrientProfile {
+    pub kcal: f64,
+    pub protein_g: f64,
+    pub carbs_g: f64,
+    pub fat_g: f64,
+    pub fiber_g: f64,
+}
+
+/// Summed nutrients (servings already applied)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct NutrientTotals {
+    pub kcal: f64,
+    pub protein_g: f64,
+    pub carbs_g: f64,
+    pub fat_g: f64,
+    pub fiber_g: f64,
+}
+
+/// Nutrient totals for a single day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyNutrientTotals {
+    pub date: NaiveDate,
+    pub totals: NutrientTotals,
+    pub entry_count: i64,
+    /// Entries with an option that has no nutrient profile (counted as zero)
+    pub entries_without_profile: i64,
+    /// Off-plan entries; their estimated profile is included in the totals
+    pub off_plan_entries: i64,
+}
+
+/// Nutrient totals for a date range, with a per-day breakdown
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NutrientSummary {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub days: Vec<DailyNutrientTotals>, // Only days that have entries
+    pub totals: NutrientTotals,
+    pub entry_count: i64,
+    pub entries_without_profile: i64,
+    pub off_plan_entries: i64,
+}
+
+impl SetNutrientProfile {
+    /// Validate nutrient values (finite and non-negative)
+    pub fn validate(&self) -> Result<(), String> {
+        let values = [
+            ("kcal", self.kcal),
+            ("protein_g", self.protein_g),
+            ("carbs_g", self.carbs_g),
+            ("fat_g", self.fat_g),
+            ("fiber_g", self.fiber_g),
+        ];
+
+        for (name, value) in values {
+            if !value.is_finite() || value < 0.0 {
+                return Err(format!("{} must be a non-negative number", name));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl NutrientTotals {
+    /// Add another set of totals to this one
+    pub fn add(&mut self, other: &NutrientTotals) {
+        self.kcal += other.kcal;
+        self.protein_g += other.protein_g;
+        self.carbs_g += other.carbs_g;
+        self.fat_g += other.fat_g;
+        self.fiber_g += other.fiber_g;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_nutrient_profile_validation() {
+        let valid = SetNutrientProfile {
+            kcal: 250.0,
+            protein_g: 12.5,
+            carbs_g: 30.0,
+            fat_g: 8.0,
+            fiber_g: 0.0,
+        };
+        assert!(valid.validate().is_ok());
+
+        // Negative value
+        let invalid = SetNutrientProfile {
+            protein_g: -1.0,
+            ..valid.clone()
+        };
+        assert!(invalid.validate().is_err());
+
+        // Not a number
+        let invalid = SetNutrientProfile {
+            kcal: f64::NAN,
+            ..valid
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_nutrient_totals_add() {
+        let mut totals = NutrientTotals::default();
+        totals.add(&NutrientTotals {
+            kcal: 100.0,
+            protein_g: 5.0,
+            carbs_g: 10.0,
+            fat_g: 2.0,
+            fiber_g: 1.0,
+        });
+        totals.add(&NutrientTotals {
+            kcal: 50.0,
+            protein_g: 1.0,
+            carbs_g: 0.0,
+            fat_g: 3.0,
+            fiber_g: 0.5,
+        });
+
+        assert_eq!(totals.kcal, 150.0);
+        assert_eq!(totals.protein_g, 6.0);
+        assert_eq!(totals.fat_g, 5.0);
+        assert_eq!(totals.fiber_g, 1.5);
+    }
+}
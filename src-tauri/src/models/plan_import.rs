@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+
+use super::{CreateMealOption, CreateMealTemplate, OptionGroup, SlotType};
+
+/// An option read from a plan's text, before its template exists
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParsedOption {
+    pub name: String,
+    pub nutritional_notes: Option<String>, // Text in parentheses, e.g. "60 g"
+    pub option_group: Option<String>,
+}
+
+impl ParsedOption {
+    /// Creation input for this option once its template is known
+    pub fn to_create(&self, template_id: i64) -> CreateMealOption {
+        CreateMealOption {
+            template_id,
+            name: self.name.clone(),
+            description: None,
+            nutritional_notes: self.nutritional_notes.clone(),
+        }
+    }
+}
+
+/// A template read from a plan's text
+/// A template listed under several slots appears once, compatible with all of them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParsedTemplate {
+    pub template: CreateMealTemplate,
+    pub option_groups: Vec<OptionGroup>, // Empty when a single option is picked
+    pub options: Vec<ParsedOption>,
+}
+
+/// Result of parsing a plan's text, without touching the database
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ParsedPlan {
+    pub templates: Vec<ParsedTemplate>,
+    pub warnings: Vec<String>, // Lines that were skipped, with the reason
+}
+
+/// How a parsed template relates to the library
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanTemplateStatus {
+    New,       // No template with this name yet
+    Changed,   // Matched by name; slots, options or groups are added
+    Unchanged, // Matched by name; the library already has everything
+}
+
+/// A parsed template compared with the library template of the same name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanTemplatePreview {
+    pub status: PlanTemplateStatus,
+    pub template_id: Option<i64>, // Matched template, or the created one once imported
+    pub parsed: ParsedTemplate,
+    pub added_slots: Vec<SlotType>,
+    pub added_options: Vec<String>,
+    pub unlisted_options: Vec<String>, // Library options missing from the plan; left as they are
+    pub option_groups_changed: bool,
+}
+
+/// Outcome of importing a plan's text
+/// With `preview` set nothing was written
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanImport {
+    pub preview: bool,
+    pub templates: Vec<PlanTemplatePreview>,
+    pub warnings: Vec<String>,
+}
@@ -0,0 +1,146 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use super::{LocationType, MealEntry, SelectedOption, SlotType};
+use crate::services::{ValidationError, ValidationWarning};
+
+/// What to do when a target slot of a copy already holds entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CopyConflictStrategy {
+    Skip,      // Leave the occupied slot alone and don't copy into it
+    Overwrite, // Delete the existing entries of the slot, then copy
+    Abort,     // Copy nothing if any target slot is occupied
+}
+
+/// Outcome of copying a day or a week of entries
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CopyPlanResult {
+    pub copied: Vec<CopiedEntry>,
+    pub skipped: Vec<SkippedCopy>,
+    pub failed: Vec<FailedCopy>,
+    pub replaced_entries: usize, // Existing entries deleted by Overwrite
+}
+
+/// An entry created by a copy, with the validation warnings it raised
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CopiedEntry {
+    pub source_entry_id: i64,
+    pub entry: MealEntry,
+    pub warnings: Vec<ValidationWarning>,
+}
+
+/// A source entry not copied because its target slot was occupied
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkippedCopy {
+    pub source_entry_id: i64,
+    pub date: NaiveDate, // Target date
+    pub slot_type: SlotType,
+}
+
+/// A source entry not copied because it failed validation on the target date
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailedCopy {
+    pub source_entry_id: i64,
+    pub date: NaiveDate, // Target date
+    pub slot_type: SlotType,
+    pub error: ValidationError,
+}
+
+/// Input for generating a week of planned entries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneratePlanRequest {
+    pub week_start: NaiveDate,                // Any day of the target ISO week
+    pub location_schedule: Vec<LocationType>, // One location per day, Monday first
+    pub seed: u64,                            // Same seed + same library = same plan
+    pub preview: bool,                        // true = compute only, write nothing
+}
+
+impl GeneratePlanRequest {
+    /// Validate generator input
+    pub fn validate(&self) -> Result<(), String> {
+        if self.location_schedule.len() != 7 {
+            return Err(format!(
+                "Location schedule must have 7 days, got {}",
+                self.location_schedule.len()
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// A generated week plan
+/// Slots that already held entries are left untouched and not listed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeneratedPlan {
+    pub week: String, // Format: "YYYY-WW" (ISO week)
+    pub seed: u64,
+    pub preview: bool,
+    pub slots: Vec<GeneratedSlot>,
+    pub unfilled: Vec<UnfilledSlot>,
+}
+
+/// One slot filled by the generator
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeneratedSlot {
+    pub date: NaiveDate,
+    pub slot_type: SlotType,
+    pub location: LocationType,
+    pub meal_option_id: i64,
+    pub option_name: String,
+    pub template_id: i64,
+    pub template_name: String,
+    pub entry_id: Option<i64>,              // None in preview mode
+    pub over_suggestion: Vec<String>,       // Tags pushed past their weekly suggestion
+    pub extra_options: Vec<SelectedOption>, // Picked to meet the template's option groups
+}
+
+/// A slot the generator could not fill
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnfilledSlot {
+    pub date: NaiveDate,
+    pub slot_type: SlotType,
+    pub reason: String,
+}
+
+/// A ranked option suggestion for one slot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OptionSuggestion {
+    pub meal_option_id: i64,
+    pub option_name: String,
+    pub template_id: i64,
+    pub template_name: String,
+    pub remaining_weekly_uses: Option<i64>, // Template limit minus this week's uses, None = unlimited
+    pub tag_headroom: Option<i64>, // Smallest suggestion minus usage over the option's tags, None = no suggestions
+    pub last_eaten: Option<NaiveDate>, // Most recent completed entry on or before the date
+    pub warnings: Vec<ValidationWarning>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conflict_strategy_serialization() {
+        let json = serde_json::to_string(&CopyConflictStrategy::Overwrite).unwrap();
+        assert_eq!(json, r#""overwrite""#);
+
+        let parsed: CopyConflictStrategy = serde_json::from_str(r#""skip""#).unwrap();
+        assert_eq!(parsed, CopyConflictStrategy::Skip);
+    }
+
+    #[test]
+    fn test_generate_plan_request_validation() {
+        let mut request = GeneratePlanRequest {
+            week_start: NaiveDate::from_ymd_opt(2024, 11, 4).unwrap(),
+            location_schedule: vec![LocationType::Home; 7],
+            seed: 42,
+            preview: true,
+        };
+        assert!(request.validate().is_ok());
+
+        request.location_schedule.pop();
+        assert!(request.validate().is_err());
+    }
+}
@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+/// The database the app runs on, and the commands it cannot serve
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageBackend {
+    pub kind: String, // "sqlite" or "postgres"
+    pub unsupported_commands: Vec<String>,
+}
@@ -0,0 +1,223 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{EntryStatus, LocationType, OptionGroup, SetNutrientProfile, SlotType, TagCategory};
+
+/// Kind of a synced entity
+/// Ordered so that referenced entities come first: a batch applies in this order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncEntity {
+    Slot,
+    Tag,
+    Template,
+    Option,
+    Entry,
+}
+
+impl SyncEntity {
+    /// Every entity, in apply order
+    pub const ALL: [SyncEntity; 5] = [
+        SyncEntity::Slot,
+        SyncEntity::Tag,
+        SyncEntity::Template,
+        SyncEntity::Option,
+        SyncEntity::Entry,
+    ];
+
+    pub fn to_db_string(self) -> &'static str {
+        match self {
+            SyncEntity::Slot => "slot",
+            SyncEntity::Tag => "tag",
+            SyncEntity::Template => "template",
+            SyncEntity::Option => "option",
+            SyncEntity::Entry => "entry",
+        }
+    }
+
+    pub fn from_db_string(s: &str) -> Result<Self, String> {
+        match s {
+            "slot" => Ok(SyncEntity::Slot),
+            "tag" => Ok(SyncEntity::Tag),
+            "template" => Ok(SyncEntity::Template),
+            "option" => Ok(SyncEntity::Option),
+            "entry" => Ok(SyncEntity::Entry),
+            _ => Err(format!("Invalid sync entity: {}", s)),
+        }
+    }
+}
+
+/// The state of one entity at a version, as exchanged with the sync server
+/// Versions are ordered by (clock, device_id); the highest one wins
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncChange {
+    pub entity: SyncEntity,
+    pub key: String, // UUID of the row; the name for slots
+    pub clock: i64,  // Lamport clock of the device that made the change
+    pub device_id: String,
+    pub data: Option<serde_json::Value>, // One of the Synced* types; None = deleted
+}
+
+impl SyncChange {
+    /// Whether this version wins over another version of the same entity
+    pub fn supersedes(&self, clock: i64, device_id: &str) -> bool {
+        (self.clock, self.device_id.as_str()) > (clock, device_id)
+    }
+}
+
+/// Slot data of a SyncChange
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncedSlot {
+    pub display_name: String,
+    pub sort_order: i32,
+    pub active: bool,
+}
+
+/// Tag data of a SyncChange; the parent is referenced by UUID
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncedTag {
+    pub name: String,
+    pub display_name: String,
+    pub category: TagCategory,
+    pub weekly_suggestion: Option<i32>,
+    pub parent: Option<String>,
+    pub archived: bool,
+}
+
+/// Template data of a SyncChange, with its slots and option groups
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncedTemplate {
+    pub name: String,
+    pub description: Option<String>,
+    pub compatible_slots: Vec<SlotType>,
+    pub location_type: LocationType,
+    pub weekly_limit: Option<i32>,
+    pub archived: bool,
+    pub option_groups: Vec<OptionGroup>,
+}
+
+/// Option data of a SyncChange; template and tags are referenced by UUID
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncedOption {
+    pub template: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub nutritional_notes: Option<String>,
+    pub option_group: Option<String>,
+    pub archived: bool,
+    pub tags: Vec<String>,
+    pub nutrients: Option<SetNutrientProfile>,
+}
+
+/// Entry data of a SyncChange, snapshot included; options and the template are
+/// referenced by UUID
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncedEntry {
+    pub option: Option<String>, // None for off-plan entries
+    pub date: NaiveDate,
+    pub slot_type: SlotType,
+    pub location: LocationType,
+    pub servings: f64,
+    pub notes: Option<String>,
+    pub status: EntryStatus,
+    pub option_name: String,
+    pub template: Option<String>, // None when off-plan or the template is gone
+    pub template_name: String,
+    pub template_location_type: LocationType,
+    pub options: Vec<SyncedEntryOption>,         // Main first
+    pub planned_options: Vec<SyncedEntryOption>, // Main first
+    pub tags: Vec<SyncedEntryTag>,
+    pub estimated_nutrients: Option<SetNutrientProfile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncedEntryOption {
+    pub option: String,
+    pub servings: f64,
+    pub option_name: String,
+}
+
+/// A snapshot tag; the UUID is None when the tag no longer exists
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncedEntryTag {
+    pub tag: Option<String>,
+    pub tag_name: String,
+}
+
+/// Body of a push to the sync server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncPush {
+    pub device_id: String,
+    pub changes: Vec<SyncChange>,
+}
+
+/// Server answer to a push
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncPushResult {
+    pub accepted: usize,
+    pub stale: usize, // The server already had a newer version
+}
+
+/// Server answer to a pull: the latest version of everything changed after the
+/// cursor by other devices, and the cursor to pull from next time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncPull {
+    pub changes: Vec<SyncChange>,
+    pub cursor: i64,
+}
+
+/// A remote change that could not be applied, e.g. an entry whose option was
+/// deleted here, or the deletion of an option that entries here still use
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncSkipped {
+    pub entity: SyncEntity,
+    pub key: String,
+    pub reason: String,
+}
+
+/// Result of applying pulled changes
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncApplyResult {
+    pub applied: usize,
+    pub stale: usize, // A newer version was already here
+    pub skipped: Vec<SyncSkipped>,
+}
+
+/// Result of a full sync with the server
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncReport {
+    pub pulled: SyncApplyResult,
+    pub pushed: SyncPushResult,
+}
+
+/// Sync identity and progress of this database
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncStatus {
+    pub device_id: String,
+    pub clock: i64,
+    pub pending_changes: i64, // Entities changed here since the last push
+    pub server_url: Option<String>,
+    pub last_synced_at: Option<DateTime<Utc>>,
+}
+
+/// Input for configuring the sync server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncSettings {
+    pub server_url: Option<String>, // None = sync disabled
+    pub token: Option<String>,
+}
+
+impl SyncSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(url) = &self.server_url {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err("Sync server URL must start with http:// or https://".to_string());
+            }
+            if self.token.as_deref().unwrap_or_default().trim().is_empty() {
+                return Err("A sync server needs a token".to_string());
+            }
+        }
+
+        Ok(())
+    }
+}
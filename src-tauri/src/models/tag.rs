@@ -13,6 +13,7 @@ pub struct Tag {
     pub category: TagCategory,
     pub weekly_suggestion: Option<i32>, // Soft limit (e.g., 3 for "max 3x/week")
     pub parent_tag_id: Option<i64>,     // For hierarchies: pasta_integrale -> pasta
+    pub archived: bool,                 // Hidden from listings; still counts in usage roll-ups
     pub created_at: DateTime<Utc>,
 }
 
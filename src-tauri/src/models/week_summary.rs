@@ -0,0 +1,62 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use super::{EntryStatus, LocationType, SlotType, WeeklyTemplateUsage};
+
+/// Overview of one ISO week: slot grid, limit/suggestion usage and completion
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeekSummary {
+    pub week: String,                             // Format: "YYYY-WW" (ISO week)
+    pub start_date: NaiveDate,                    // Monday
+    pub end_date: NaiveDate,                      // Sunday
+    pub days: Vec<WeekSummaryDay>,                // Always 7 days, Monday first
+    pub template_usage: Vec<WeeklyTemplateUsage>, // Limited or used templates
+    pub tag_usage: Vec<WeeklyTagSuggestionUsage>, // Tags with a suggestion or usage
+    pub total_entries: i64,
+    pub completed_entries: i64, // Eaten entries
+    pub off_plan_entries: i64,  // Entries logged without a meal option
+    pub completion_ratio: f64,  // completed / total, 0.0 for an empty week
+}
+
+/// One day of the week grid
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeekSummaryDay {
+    pub date: NaiveDate,
+    pub slots: Vec<WeekSummarySlot>, // Active slots plus inactive ones used this week, in day order
+}
+
+/// One slot of a day; usually holds zero or one entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeekSummarySlot {
+    pub slot_type: SlotType,
+    pub display_name: String,
+    pub entries: Vec<WeekSummaryEntry>,
+}
+
+/// A meal entry with its option and template names resolved
+/// Off-plan entries have no option or template; `option_name` is their description
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeekSummaryEntry {
+    pub entry_id: i64,
+    pub meal_option_id: Option<i64>,
+    pub option_name: String,
+    pub template_id: Option<i64>,
+    pub template_name: String,
+    pub location: LocationType,
+    pub servings: f64,
+    pub notes: Option<String>,
+    pub status: EntryStatus,
+    pub off_plan: bool,
+}
+
+/// Weekly usage of a tag (rolled up over child tags) against its suggestion
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeeklyTagSuggestionUsage {
+    pub tag_id: i64,
+    pub tag_name: String,
+    pub display_name: String,
+    pub weekly_suggestion: Option<i32>, // 0 = avoid
+    pub usage_count: i64,
+    pub avoid: bool,    // Zero suggestion: the tag should not be eaten at all
+    pub exceeded: bool, // Usage above the suggestion (any use of an "avoid" tag)
+}
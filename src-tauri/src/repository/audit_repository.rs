@@ -0,0 +1,628 @@
+use super::sync_repository::EntitySnapshot;
+use crate::models::{AuditOperation, AuditRecord, AuditStatus, SyncEntity};
+use crate::repository::SyncRepository;
+use sqlx::{Acquire, Result, Row, SqliteConnection, SqlitePool};
+use std::collections::HashSet;
+
+/// An entity a repository call may change, with its state before the call
+struct Tracked {
+    entity: SyncEntity,
+    id: i64,
+    key: Option<String>, // None until the row exists
+    before: Option<serde_json::Value>,
+}
+
+/// Records what one command changes in the audit log
+/// Snapshot every entity the command may change with `track` (or mark new rows with
+/// `created`), make the change, then `record` it. Entities that did not change are
+/// left out; a command that changed nothing records nothing. Commands made of several
+/// writes, e.g. an import, pass one scope down so they are undone as one operation.
+pub(crate) struct AuditScope {
+    command: &'static str,
+    tracked: Vec<Tracked>,
+    watched: Vec<(SyncEntity, HashSet<String>)>, // Keys present before `track_new`
+}
+
+impl AuditScope {
+    /// `command` names the command the call belongs to, e.g. "delete_entry"
+    pub(crate) fn new(command: &'static str) -> Self {
+        Self {
+            command,
+            tracked: Vec::new(),
+            watched: Vec::new(),
+        }
+    }
+
+    /// Snapshot entities before they change; IDs that do not exist are ignored
+    pub(crate) async fn track(
+        &mut self,
+        conn: &mut SqliteConnection,
+        entity: SyncEntity,
+        ids: impl IntoIterator<Item = i64>,
+    ) -> Result<()> {
+        for id in ids {
+            if self
+                .tracked
+                .iter()
+                .any(|t| t.entity == entity && t.id == id)
+            {
+                continue;
+            }
+
+            let key = SyncRepository::key_of(conn, entity, id).await?;
+            let before = match &key {
+                Some(key) => SyncRepository::snapshot(conn, entity, key).await?,
+                None => None,
+            };
+            self.tracked.push(Tracked {
+                entity,
+                id,
+                key,
+                before,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Snapshot the entities whose IDs `sql` selects, with `id` bound to ?1
+    /// e.g. the options a template delete cascades to
+    pub(crate) async fn track_query(
+        &mut self,
+        conn: &mut SqliteConnection,
+        entity: SyncEntity,
+        sql: &str,
+        id: i64,
+    ) -> Result<()> {
+        let ids: Vec<i64> = sqlx::query_scalar(sql)
+            .bind(id)
+            .fetch_all(&mut *conn)
+            .await?;
+
+        self.track(conn, entity, ids).await
+    }
+
+    /// Record every row of `entity` the command creates, without naming them
+    /// For bulk writers such as imports that insert rows with raw SQL
+    pub(crate) async fn track_new(
+        &mut self,
+        conn: &mut SqliteConnection,
+        entity: SyncEntity,
+    ) -> Result<()> {
+        let keys = SyncRepository::keys_of_all(conn, entity)
+            .await?
+            .into_iter()
+            .map(|(_, key)| key)
+            .collect();
+        self.watched.push((entity, keys));
+        Ok(())
+    }
+
+    /// Snapshot every row of `entity`, and record the rows the command creates too
+    /// For bulk writers that may change any row of a table, e.g. a library pack import
+    pub(crate) async fn track_all(
+        &mut self,
+        conn: &mut SqliteConnection,
+        entity: SyncEntity,
+    ) -> Result<()> {
+        let ids: Vec<i64> = SyncRepository::keys_of_all(conn, entity)
+            .await?
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        self.track(conn, entity, ids).await?;
+        self.track_new(conn, entity).await
+    }
+
+    /// An entity the call created
+    pub(crate) fn created(&mut self, entity: SyncEntity, id: i64) {
+        self.tracked.push(Tracked {
+            entity,
+            id,
+            key: None,
+            before: None,
+        });
+    }
+
+    /// Write the changes to the audit log as one operation
+    /// A new change cannot be redone over, so undone operations are dropped
+    pub(crate) async fn record(self, conn: &mut SqliteConnection) -> Result<()> {
+        let mut tx = conn.begin().await?;
+
+        let mut tracked = self.tracked;
+        for (entity, existing) in self.watched {
+            for (id, key) in SyncRepository::keys_of_all(&mut tx, entity).await? {
+                let known = tracked.iter().any(|t| {
+                    t.entity == entity
+                        && match &t.key {
+                            Some(tracked_key) => tracked_key == &key,
+                            None => t.id == id,
+                        }
+                });
+                if !existing.contains(&key) && !known {
+                    tracked.push(Tracked {
+                        entity,
+                        id,
+                        key: Some(key),
+                        before: None,
+                    });
+                }
+            }
+        }
+
+        let mut changes = Vec::new();
+        for tracked in tracked {
+            let key = match tracked.key {
+                Some(key) => key,
+                None => match SyncRepository::key_of(&mut tx, tracked.entity, tracked.id).await? {
+                    Some(key) => key,
+                    None => continue,
+                },
+            };
+            let after = SyncRepository::snapshot(&mut tx, tracked.entity, &key).await?;
+            if after != tracked.before {
+                changes.push((tracked.entity, tracked.id, key, tracked.before, after));
+            }
+        }
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let operation: i64 =
+            sqlx::query_scalar("SELECT COALESCE(MAX(operation), 0) + 1 FROM audit_log")
+                .fetch_one(&mut *tx)
+                .await?;
+        for (entity, id, key, before, after) in changes {
+            sqlx::query(
+                "INSERT INTO audit_log (operation, command, entity, entity_id, entity_key,
+                                        before_data, after_data)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )
+            .bind(operation)
+            .bind(self.command)
+            .bind(entity.to_db_string())
+            .bind(id)
+            .bind(&key)
+            .bind(before.map(|data| data.to_string()))
+            .bind(after.map(|data| data.to_string()))
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query(
+            "UPDATE audit_log SET status = 'dropped', undo_order = NULL WHERE status = 'undone'",
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await
+    }
+}
+
+pub struct AuditRepository;
+
+impl AuditRepository {
+    fn row_to_record(row: &sqlx::sqlite::SqliteRow) -> Result<AuditRecord> {
+        let entity: String = row.try_get("entity")?;
+        let status: String = row.try_get("status")?;
+        let before: Option<String> = row.try_get("before_data")?;
+        let after: Option<String> = row.try_get("after_data")?;
+        let parse = |data: Option<String>| {
+            data.map(|data| serde_json::from_str(&data))
+                .transpose()
+                .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+        };
+
+        Ok(AuditRecord {
+            id: row.try_get("id")?,
+            operation: row.try_get("operation")?,
+            command: row.try_get("command")?,
+            entity: SyncEntity::from_db_string(&entity).map_err(sqlx::Error::Protocol)?,
+            entity_id: row.try_get("entity_id")?,
+            entity_key: row.try_get("entity_key")?,
+            before: parse(before)?,
+            after: parse(after)?,
+            status: AuditStatus::from_db_string(&status).map_err(sqlx::Error::Protocol)?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+
+    /// Revert the latest operation still in effect
+    /// Every entity it changed goes back to its state before, whatever changed it
+    /// since (e.g. a sync). Returns None when there is nothing to undo.
+    pub async fn undo_last(pool: &SqlitePool) -> Result<Option<AuditOperation>> {
+        let mut tx = pool.begin().await?;
+
+        let operation: Option<i64> =
+            sqlx::query_scalar("SELECT MAX(operation) FROM audit_log WHERE status = 'done'")
+                .fetch_one(&mut *tx)
+                .await?;
+        let Some(operation) = operation else {
+            return Ok(None);
+        };
+
+        let records = Self::get_operation_records(&mut tx, operation).await?;
+        SyncRepository::restore(
+            &mut tx,
+            records
+                .iter()
+                .map(|r| snapshot_of(r, r.before.clone()))
+                .collect(),
+        )
+        .await?;
+
+        sqlx::query(
+            "UPDATE audit_log
+             SET status = 'undone',
+                 undo_order = (SELECT COALESCE(MAX(undo_order), 0) + 1 FROM audit_log)
+             WHERE operation = ?1",
+        )
+        .bind(operation)
+        .execute(&mut *tx)
+        .await?;
+
+        let operation = Self::get_operation(&mut tx, operation).await?;
+        tx.commit().await?;
+
+        Ok(operation)
+    }
+
+    /// Apply again the operation undone last
+    /// Returns None when nothing was undone since the last change
+    pub async fn redo(pool: &SqlitePool) -> Result<Option<AuditOperation>> {
+        let mut tx = pool.begin().await?;
+
+        let operation: Option<i64> = sqlx::query_scalar(
+            "SELECT operation FROM audit_log WHERE status = 'undone'
+             ORDER BY undo_order DESC LIMIT 1",
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(operation) = operation else {
+            return Ok(None);
+        };
+
+        let records = Self::get_operation_records(&mut tx, operation).await?;
+        SyncRepository::restore(
+            &mut tx,
+            records
+                .iter()
+                .map(|r| snapshot_of(r, r.after.clone()))
+                .collect(),
+        )
+        .await?;
+
+        sqlx::query("UPDATE audit_log SET status = 'done', undo_order = NULL WHERE operation = ?1")
+            .bind(operation)
+            .execute(&mut *tx)
+            .await?;
+
+        let operation = Self::get_operation(&mut tx, operation).await?;
+        tx.commit().await?;
+
+        Ok(operation)
+    }
+
+    /// Every recorded change to an entity, oldest first
+    /// An entity recreated under another ID by an undo keeps its whole history
+    pub async fn get_history(
+        pool: &SqlitePool,
+        entity: SyncEntity,
+        id: i64,
+    ) -> Result<Vec<AuditRecord>> {
+        let rows = sqlx::query(
+            "SELECT id, operation, command, entity, entity_id, entity_key, before_data,
+                    after_data, status, created_at
+             FROM audit_log
+             WHERE entity = ?1
+               AND entity_key IN (SELECT entity_key FROM audit_log WHERE entity = ?1 AND entity_id = ?2)
+             ORDER BY id",
+        )
+        .bind(entity.to_db_string())
+        .bind(id)
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter().map(Self::row_to_record).collect()
+    }
+
+    async fn get_operation(
+        conn: &mut SqliteConnection,
+        operation: i64,
+    ) -> Result<Option<AuditOperation>> {
+        let records = Self::get_operation_records(conn, operation).await?;
+
+        Ok(records
+            .first()
+            .map(|first| first.command.clone())
+            .map(|command| AuditOperation {
+                operation,
+                command,
+                records,
+            }))
+    }
+
+    async fn get_operation_records(
+        conn: &mut SqliteConnection,
+        operation: i64,
+    ) -> Result<Vec<AuditRecord>> {
+        let rows = sqlx::query(
+            "SELECT id, operation, command, entity, entity_id, entity_key, before_data,
+                    after_data, status, created_at
+             FROM audit_log
+             WHERE operation = ?1
+             ORDER BY id",
+        )
+        .bind(operation)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        rows.iter().map(Self::row_to_record).collect()
+    }
+}
+
+/// Snapshot restoring the entity of `record` to `data`
+fn snapshot_of(record: &AuditRecord, data: Option<serde_json::Value>) -> EntitySnapshot {
+    EntitySnapshot {
+        entity: record.entity,
+        id: record.entity_id,
+        key: record.entity_key.clone(),
+        data,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use crate::models::{
+        CreateMealEntry, CreateMealOption, CreateMealTemplate, CreateTag, EntryStatus,
+        LocationType, SlotType, TagCategory, UpdateMealTemplate,
+    };
+    use crate::repository::{
+        MealEntryRepository, MealOptionRepository, MealTemplateRepository, TagRepository,
+    };
+    use chrono::NaiveDate;
+    use tempfile::TempDir;
+
+    async fn setup_test_db() -> (SqlitePool, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let pool = db::initialize_database(db_path).await.unwrap();
+        (pool, temp_dir)
+    }
+
+    async fn create_template(pool: &SqlitePool) -> i64 {
+        MealTemplateRepository::create(
+            pool,
+            CreateMealTemplate {
+                name: "Pasta".to_string(),
+                description: None,
+                compatible_slots: vec![SlotType::LUNCH],
+                location_type: LocationType::Any,
+                weekly_limit: None,
+            },
+        )
+        .await
+        .unwrap()
+        .id
+    }
+
+    async fn create_option(pool: &SqlitePool, template_id: i64) -> i64 {
+        MealOptionRepository::create(
+            pool,
+            CreateMealOption {
+                template_id,
+                name: "Pasta al pomodoro".to_string(),
+                description: None,
+                nutritional_notes: None,
+            },
+        )
+        .await
+        .unwrap()
+        .id
+    }
+
+    async fn create_tag(pool: &SqlitePool, name: &str, parent_tag_id: Option<i64>) -> i64 {
+        TagRepository::create(
+            pool,
+            CreateTag {
+                name: name.to_string(),
+                display_name: name.to_string(),
+                category: TagCategory::Ingredient,
+                weekly_suggestion: None,
+                parent_tag_id,
+            },
+        )
+        .await
+        .unwrap()
+        .id
+    }
+
+    async fn option_tags(pool: &SqlitePool, option_id: i64) -> Vec<i64> {
+        let mut tags = MealOptionRepository::get_with_tags(pool, option_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .tags;
+        tags.sort();
+        tags
+    }
+
+    #[tokio::test]
+    async fn test_undo_delete_entry_restores_it() {
+        let (pool, _temp_dir) = setup_test_db().await;
+        let option_id = create_option(&pool, create_template(&pool).await).await;
+        let entry = MealEntryRepository::create(
+            &pool,
+            CreateMealEntry {
+                meal_option_id: option_id,
+                date: NaiveDate::from_ymd_opt(2024, 11, 4).unwrap(),
+                slot_type: SlotType::LUNCH,
+                location: LocationType::Home,
+                servings: Some(1.5),
+                notes: Some("With basil".to_string()),
+                status: Some(EntryStatus::Eaten),
+                extra_options: vec![],
+            },
+        )
+        .await
+        .unwrap();
+
+        MealEntryRepository::delete(&pool, entry.id).await.unwrap();
+        assert!(MealEntryRepository::get_by_id(&pool, entry.id)
+            .await
+            .unwrap()
+            .is_none());
+
+        let undone = AuditRepository::undo_last(&pool).await.unwrap().unwrap();
+        assert_eq!(undone.command, "delete_entry");
+        assert_eq!(undone.records[0].status, AuditStatus::Undone);
+
+        // Same ID and content as before the delete
+        let restored = MealEntryRepository::get_by_id(&pool, entry.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(restored.meal_option_id, Some(option_id));
+        assert_eq!(restored.servings, 1.5);
+        assert_eq!(restored.notes.as_deref(), Some("With basil"));
+        assert_eq!(restored.status, EntryStatus::Eaten);
+    }
+
+    #[tokio::test]
+    async fn test_undo_and_redo_set_option_tags() {
+        let (pool, _temp_dir) = setup_test_db().await;
+        let option_id = create_option(&pool, create_template(&pool).await).await;
+        let pasta = create_tag(&pool, "pasta", None).await;
+        let tomato = create_tag(&pool, "pomodoro", None).await;
+        let cheese = create_tag(&pool, "formaggio", None).await;
+
+        MealOptionRepository::set_tags(&pool, option_id, vec![pasta, tomato])
+            .await
+            .unwrap();
+        MealOptionRepository::set_tags(&pool, option_id, vec![cheese])
+            .await
+            .unwrap();
+
+        let undone = AuditRepository::undo_last(&pool).await.unwrap().unwrap();
+        assert_eq!(undone.command, "set_option_tags");
+        assert_eq!(option_tags(&pool, option_id).await, vec![pasta, tomato]);
+
+        let redone = AuditRepository::redo(&pool).await.unwrap().unwrap();
+        assert_eq!(redone.operation, undone.operation);
+        assert_eq!(redone.records[0].status, AuditStatus::Done);
+        assert_eq!(option_tags(&pool, option_id).await, vec![cheese]);
+
+        assert!(AuditRepository::redo(&pool).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_new_change_drops_redo() {
+        let (pool, _temp_dir) = setup_test_db().await;
+        let template_id = create_template(&pool).await;
+        let rename = |name: &str| UpdateMealTemplate {
+            name: Some(name.to_string()),
+            description: None,
+            compatible_slots: None,
+            location_type: None,
+            weekly_limit: None,
+        };
+
+        MealTemplateRepository::update(&pool, template_id, rename("Pasta fredda"))
+            .await
+            .unwrap();
+        AuditRepository::undo_last(&pool).await.unwrap().unwrap();
+        MealTemplateRepository::update(&pool, template_id, rename("Pasta al forno"))
+            .await
+            .unwrap();
+
+        assert!(AuditRepository::redo(&pool).await.unwrap().is_none());
+        let history = AuditRepository::get_history(&pool, SyncEntity::Template, template_id)
+            .await
+            .unwrap();
+        assert_eq!(history[1].status, AuditStatus::Dropped);
+
+        // A change that changes nothing is not recorded
+        MealTemplateRepository::update(&pool, template_id, rename("Pasta al forno"))
+            .await
+            .unwrap();
+        let undone = AuditRepository::undo_last(&pool).await.unwrap().unwrap();
+        assert_eq!(
+            undone.records[0].after.as_ref().unwrap()["name"],
+            "Pasta al forno"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_history_of_template() {
+        let (pool, _temp_dir) = setup_test_db().await;
+        let template_id = create_template(&pool).await;
+        MealTemplateRepository::update(
+            &pool,
+            template_id,
+            UpdateMealTemplate {
+                name: None,
+                description: None,
+                compatible_slots: None,
+                location_type: None,
+                weekly_limit: Some(Some(2)),
+            },
+        )
+        .await
+        .unwrap();
+        MealTemplateRepository::set_archived(&pool, template_id, true)
+            .await
+            .unwrap();
+
+        let history = AuditRepository::get_history(&pool, SyncEntity::Template, template_id)
+            .await
+            .unwrap();
+        let commands: Vec<&str> = history.iter().map(|r| r.command.as_str()).collect();
+        assert_eq!(
+            commands,
+            ["create_template", "update_template", "archive_template"]
+        );
+        assert!(history[0].before.is_none());
+        assert_eq!(
+            history[1].before.as_ref().unwrap()["weekly_limit"],
+            serde_json::Value::Null
+        );
+        assert_eq!(history[1].after.as_ref().unwrap()["weekly_limit"], 2);
+        assert_eq!(history[2].after.as_ref().unwrap()["archived"], true);
+
+        assert!(
+            AuditRepository::get_history(&pool, SyncEntity::Entry, template_id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_undo_delete_tag_restores_links() {
+        let (pool, _temp_dir) = setup_test_db().await;
+        let option_id = create_option(&pool, create_template(&pool).await).await;
+        let pasta = create_tag(&pool, "pasta", None).await;
+        let wholemeal = create_tag(&pool, "pasta_integrale", Some(pasta)).await;
+        MealOptionRepository::set_tags(&pool, option_id, vec![pasta])
+            .await
+            .unwrap();
+
+        TagRepository::delete(&pool, pasta).await.unwrap();
+        assert!(option_tags(&pool, option_id).await.is_empty());
+
+        let undone = AuditRepository::undo_last(&pool).await.unwrap().unwrap();
+        assert_eq!(undone.command, "delete_tag");
+
+        assert!(TagRepository::get_by_id(&pool, pasta)
+            .await
+            .unwrap()
+            .is_some());
+        assert_eq!(option_tags(&pool, option_id).await, vec![pasta]);
+        let child = TagRepository::get_by_id(&pool, wholemeal)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(child.parent_tag_id, Some(pasta));
+    }
+}
@@ -0,0 +1,474 @@
+// Storage backend
+// The store selected at startup: the SQLite database by default, or a shared
+// PostgreSQL database (`postgres` feature). Library and entry CRUD work on both;
+// sync, undo/history, backups, export/import, planning and summaries need SQLite
+
+use super::store::{MealEntryStore, MealOptionStore, MealSlotStore, MealTemplateStore, TagStore};
+use crate::error::ApiResult;
+use crate::models::{
+    CreateMealEntry, CreateMealOption, CreateMealSlot, CreateMealTemplate, CreateOffPlanEntry,
+    CreateTag, DeleteBlocked, EntryStatus, LocationType, LogActualMeal, MealEntry, MealOption,
+    MealOptionWithTags, MealSlot, MealTemplate, NutrientProfile, OptionGroup, SetNutrientProfile,
+    SlotType, Tag, TagCategory, UpdateMealEntry, UpdateMealOption, UpdateMealSlot,
+    UpdateMealTemplate, UpdateTag, WeeklyTagUsage, WeeklyTagUsageNode, WeeklyTemplateUsage,
+    WeeklyUsage,
+};
+use chrono::NaiveDate;
+#[cfg(feature = "postgres")]
+use sqlx::PgPool;
+use sqlx::{Result, SqlitePool};
+use std::future::Future;
+
+/// Environment variable holding the PostgreSQL URL; when set, the app uses PostgreSQL
+pub const POSTGRES_URL_ENV: &str = "NUTRITION_POSTGRES_URL";
+
+/// Tauri commands that need the SQLite database and fail on PostgreSQL
+/// Keep in sync with the commands calling `Backend::sqlite` and with the README.
+pub const SQLITE_ONLY_COMMANDS: &[&str] = &[
+    // Sync, undo and history
+    "get_sync_status",
+    "configure_sync",
+    "sync_now",
+    "undo_last",
+    "redo",
+    "get_history",
+    // Backups
+    "list_backups",
+    "create_backup",
+    "restore_backup",
+    // Export and import
+    "export_database",
+    "import_database",
+    "export_entries_csv",
+    "export_library_pack",
+    "import_library_pack",
+    "import_plan_text",
+    // Planning
+    "copy_day",
+    "copy_week",
+    "generate_week_plan",
+    "suggest_options",
+    // Summaries
+    "get_week_summary",
+    "get_adherence",
+    "get_daily_nutrient_totals",
+    "get_nutrient_totals",
+];
+
+/// The database behind commands, the CLI and the HTTP API
+#[derive(Debug, Clone)]
+pub enum Backend {
+    Sqlite(SqlitePool),
+    #[cfg(feature = "postgres")]
+    Postgres(PgPool),
+}
+
+/// Run the same store call on whichever pool the backend holds
+macro_rules! dispatch {
+    ($backend:expr, $store:ident => $call:expr) => {
+        match $backend {
+            Backend::Sqlite($store) => $call.await,
+            #[cfg(feature = "postgres")]
+            Backend::Postgres($store) => $call.await,
+        }
+    };
+}
+
+impl Backend {
+    /// PostgreSQL when `NUTRITION_POSTGRES_URL` is set, otherwise the SQLite
+    /// database opened by `open_sqlite`
+    pub async fn from_env<F, Fut>(open_sqlite: F) -> Result<Self>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<SqlitePool>>,
+    {
+        match std::env::var(POSTGRES_URL_ENV) {
+            Ok(url) if !url.is_empty() => Self::connect_postgres(&url).await,
+            _ => Ok(Backend::Sqlite(open_sqlite().await?)),
+        }
+    }
+
+    /// Connect to PostgreSQL and apply pending migrations
+    #[cfg(feature = "postgres")]
+    pub async fn connect_postgres(url: &str) -> Result<Self> {
+        Ok(Backend::Postgres(crate::db::postgres::connect(url).await?))
+    }
+
+    #[cfg(not(feature = "postgres"))]
+    pub async fn connect_postgres(_url: &str) -> Result<Self> {
+        Err(sqlx::Error::Configuration(
+            format!(
+                "{} is set, but PostgreSQL requires a build with the `postgres` feature",
+                POSTGRES_URL_ENV
+            )
+            .into(),
+        ))
+    }
+
+    /// Short name of the backend: "sqlite" or "postgres"
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Backend::Sqlite(_) => "sqlite",
+            #[cfg(feature = "postgres")]
+            Backend::Postgres(_) => "postgres",
+        }
+    }
+
+    /// Tauri commands this backend refuses; none for SQLite
+    pub fn unsupported_commands(&self) -> &'static [&'static str] {
+        match self {
+            Backend::Sqlite(_) => &[],
+            #[cfg(feature = "postgres")]
+            Backend::Postgres(_) => SQLITE_ONLY_COMMANDS,
+        }
+    }
+
+    /// The SQLite pool, for the features the PostgreSQL backend does not support
+    pub fn sqlite(&self) -> ApiResult<&SqlitePool> {
+        match self {
+            Backend::Sqlite(pool) => Ok(pool),
+            #[cfg(feature = "postgres")]
+            Backend::Postgres(_) => Err(crate::ApiError::ValidationError(
+                "Not available with the PostgreSQL backend; this feature needs the SQLite database"
+                    .to_string(),
+            )),
+        }
+    }
+
+    pub async fn close(&self) {
+        match self {
+            Backend::Sqlite(pool) => pool.close().await,
+            #[cfg(feature = "postgres")]
+            Backend::Postgres(pool) => pool.close().await,
+        }
+    }
+}
+
+impl MealSlotStore for Backend {
+    async fn create_slot(&self, slot: CreateMealSlot) -> Result<MealSlot> {
+        dispatch!(self, store => store.create_slot(slot))
+    }
+
+    async fn get_slot(&self, id: i64) -> Result<Option<MealSlot>> {
+        dispatch!(self, store => store.get_slot(id))
+    }
+
+    async fn get_slot_by_name(&self, name: &str) -> Result<Option<MealSlot>> {
+        dispatch!(self, store => store.get_slot_by_name(name))
+    }
+
+    async fn get_slots(&self) -> Result<Vec<MealSlot>> {
+        dispatch!(self, store => store.get_slots())
+    }
+
+    async fn get_active_slots(&self) -> Result<Vec<MealSlot>> {
+        dispatch!(self, store => store.get_active_slots())
+    }
+
+    async fn update_slot(&self, id: i64, update: UpdateMealSlot) -> Result<MealSlot> {
+        dispatch!(self, store => store.update_slot(id, update))
+    }
+
+    async fn delete_slot(&self, id: i64) -> Result<bool> {
+        dispatch!(self, store => store.delete_slot(id))
+    }
+}
+
+impl TagStore for Backend {
+    async fn create_tag(&self, tag: CreateTag) -> Result<Tag> {
+        dispatch!(self, store => store.create_tag(tag))
+    }
+
+    async fn get_tag(&self, id: i64) -> Result<Option<Tag>> {
+        dispatch!(self, store => store.get_tag(id))
+    }
+
+    async fn get_tag_by_name(&self, name: &str) -> Result<Option<Tag>> {
+        dispatch!(self, store => store.get_tag_by_name(name))
+    }
+
+    async fn get_tags(&self) -> Result<Vec<Tag>> {
+        dispatch!(self, store => store.get_tags())
+    }
+
+    async fn get_archived_tags(&self) -> Result<Vec<Tag>> {
+        dispatch!(self, store => store.get_archived_tags())
+    }
+
+    async fn get_tags_by_category(&self, category: TagCategory) -> Result<Vec<Tag>> {
+        dispatch!(self, store => store.get_tags_by_category(category))
+    }
+
+    async fn get_child_tags(&self, parent_id: i64) -> Result<Vec<Tag>> {
+        dispatch!(self, store => store.get_child_tags(parent_id))
+    }
+
+    async fn get_option_tags_with_ancestors(&self, meal_option_id: i64) -> Result<Vec<Tag>> {
+        dispatch!(self, store => store.get_option_tags_with_ancestors(meal_option_id))
+    }
+
+    async fn update_tag(&self, id: i64, update: UpdateTag) -> Result<Tag> {
+        dispatch!(self, store => store.update_tag(id, update))
+    }
+
+    async fn set_tag_archived(&self, id: i64, archived: bool) -> Result<Tag> {
+        dispatch!(self, store => store.set_tag_archived(id, archived))
+    }
+
+    async fn get_tag_delete_blockers(&self, id: i64) -> Result<Option<DeleteBlocked>> {
+        dispatch!(self, store => store.get_tag_delete_blockers(id))
+    }
+
+    async fn delete_tag(&self, id: i64) -> Result<bool> {
+        dispatch!(self, store => store.delete_tag(id))
+    }
+}
+
+impl MealTemplateStore for Backend {
+    async fn create_template(&self, template: CreateMealTemplate) -> Result<MealTemplate> {
+        dispatch!(self, store => store.create_template(template))
+    }
+
+    async fn get_template(&self, id: i64) -> Result<Option<MealTemplate>> {
+        dispatch!(self, store => store.get_template(id))
+    }
+
+    async fn get_templates(&self) -> Result<Vec<MealTemplate>> {
+        dispatch!(self, store => store.get_templates())
+    }
+
+    async fn get_archived_templates(&self) -> Result<Vec<MealTemplate>> {
+        dispatch!(self, store => store.get_archived_templates())
+    }
+
+    async fn get_templates_by_location(&self, location: LocationType) -> Result<Vec<MealTemplate>> {
+        dispatch!(self, store => store.get_templates_by_location(location))
+    }
+
+    async fn get_templates_by_slot(&self, slot: &SlotType) -> Result<Vec<MealTemplate>> {
+        dispatch!(self, store => store.get_templates_by_slot(slot))
+    }
+
+    async fn get_templates_by_slot_and_location(
+        &self,
+        slot: &SlotType,
+        location: LocationType,
+    ) -> Result<Vec<MealTemplate>> {
+        dispatch!(self, store => store.get_templates_by_slot_and_location(slot, location))
+    }
+
+    async fn search_templates(
+        &self,
+        query: &str,
+        slot: Option<&SlotType>,
+    ) -> Result<Vec<MealTemplate>> {
+        dispatch!(self, store => store.search_templates(query, slot))
+    }
+
+    async fn update_template(&self, id: i64, update: UpdateMealTemplate) -> Result<MealTemplate> {
+        dispatch!(self, store => store.update_template(id, update))
+    }
+
+    async fn set_template_archived(&self, id: i64, archived: bool) -> Result<MealTemplate> {
+        dispatch!(self, store => store.set_template_archived(id, archived))
+    }
+
+    async fn get_option_groups(&self, template_id: i64) -> Result<Vec<OptionGroup>> {
+        dispatch!(self, store => store.get_option_groups(template_id))
+    }
+
+    async fn set_option_groups(
+        &self,
+        template_id: i64,
+        groups: Vec<OptionGroup>,
+    ) -> Result<Vec<OptionGroup>> {
+        dispatch!(self, store => store.set_option_groups(template_id, groups))
+    }
+
+    async fn get_template_delete_blockers(&self, id: i64) -> Result<Option<DeleteBlocked>> {
+        dispatch!(self, store => store.get_template_delete_blockers(id))
+    }
+
+    async fn delete_template(&self, id: i64) -> Result<bool> {
+        dispatch!(self, store => store.delete_template(id))
+    }
+}
+
+impl MealOptionStore for Backend {
+    async fn create_option(&self, option: CreateMealOption) -> Result<MealOption> {
+        dispatch!(self, store => store.create_option(option))
+    }
+
+    async fn get_option(&self, id: i64) -> Result<Option<MealOption>> {
+        dispatch!(self, store => store.get_option(id))
+    }
+
+    async fn get_options(&self) -> Result<Vec<MealOption>> {
+        dispatch!(self, store => store.get_options())
+    }
+
+    async fn get_archived_options(&self) -> Result<Vec<MealOption>> {
+        dispatch!(self, store => store.get_archived_options())
+    }
+
+    async fn get_options_by_template(&self, template_id: i64) -> Result<Vec<MealOption>> {
+        dispatch!(self, store => store.get_options_by_template(template_id))
+    }
+
+    async fn get_option_with_tags(&self, id: i64) -> Result<Option<MealOptionWithTags>> {
+        dispatch!(self, store => store.get_option_with_tags(id))
+    }
+
+    async fn get_options_by_template_with_tags(
+        &self,
+        template_id: i64,
+    ) -> Result<Vec<MealOptionWithTags>> {
+        dispatch!(self, store => store.get_options_by_template_with_tags(template_id))
+    }
+
+    async fn search_options(&self, query: &str) -> Result<Vec<MealOption>> {
+        dispatch!(self, store => store.search_options(query))
+    }
+
+    async fn update_option(&self, id: i64, update: UpdateMealOption) -> Result<MealOption> {
+        dispatch!(self, store => store.update_option(id, update))
+    }
+
+    async fn set_option_archived(&self, id: i64, archived: bool) -> Result<MealOption> {
+        dispatch!(self, store => store.set_option_archived(id, archived))
+    }
+
+    async fn set_option_group(&self, id: i64, option_group: Option<String>) -> Result<MealOption> {
+        dispatch!(self, store => store.set_option_group(id, option_group))
+    }
+
+    async fn add_option_tags(&self, option_id: i64, tag_ids: Vec<i64>) -> Result<()> {
+        dispatch!(self, store => store.add_option_tags(option_id, tag_ids))
+    }
+
+    async fn remove_option_tags(&self, option_id: i64, tag_ids: Vec<i64>) -> Result<()> {
+        dispatch!(self, store => store.remove_option_tags(option_id, tag_ids))
+    }
+
+    async fn set_option_tags(&self, option_id: i64, tag_ids: Vec<i64>) -> Result<()> {
+        dispatch!(self, store => store.set_option_tags(option_id, tag_ids))
+    }
+
+    async fn get_option_nutrients(&self, option_id: i64) -> Result<Option<NutrientProfile>> {
+        dispatch!(self, store => store.get_option_nutrients(option_id))
+    }
+
+    async fn set_option_nutrients(
+        &self,
+        option_id: i64,
+        nutrients: SetNutrientProfile,
+    ) -> Result<NutrientProfile> {
+        dispatch!(self, store => store.set_option_nutrients(option_id, nutrients))
+    }
+
+    async fn delete_option_nutrients(&self, option_id: i64) -> Result<bool> {
+        dispatch!(self, store => store.delete_option_nutrients(option_id))
+    }
+
+    async fn get_option_delete_blockers(&self, id: i64) -> Result<Option<DeleteBlocked>> {
+        dispatch!(self, store => store.get_option_delete_blockers(id))
+    }
+
+    async fn delete_option(&self, id: i64) -> Result<()> {
+        dispatch!(self, store => store.delete_option(id))
+    }
+}
+
+impl MealEntryStore for Backend {
+    async fn create_entry(&self, entry: CreateMealEntry) -> Result<MealEntry> {
+        dispatch!(self, store => store.create_entry(entry))
+    }
+
+    async fn create_off_plan_entry(&self, entry: CreateOffPlanEntry) -> Result<MealEntry> {
+        dispatch!(self, store => store.create_off_plan_entry(entry))
+    }
+
+    async fn get_entry(&self, id: i64) -> Result<Option<MealEntry>> {
+        dispatch!(self, store => store.get_entry(id))
+    }
+
+    async fn get_entries_by_date(&self, date: NaiveDate) -> Result<Vec<MealEntry>> {
+        dispatch!(self, store => store.get_entries_by_date(date))
+    }
+
+    async fn get_entries_by_date_range(
+        &self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<MealEntry>> {
+        dispatch!(self, store => store.get_entries_by_date_range(start_date, end_date))
+    }
+
+    async fn get_entries_by_date_and_slot(
+        &self,
+        date: NaiveDate,
+        slot: &SlotType,
+    ) -> Result<Vec<MealEntry>> {
+        dispatch!(self, store => store.get_entries_by_date_and_slot(date, slot))
+    }
+
+    async fn get_entries_by_status(&self, status: EntryStatus) -> Result<Vec<MealEntry>> {
+        dispatch!(self, store => store.get_entries_by_status(status))
+    }
+
+    async fn get_entries_by_option(&self, meal_option_id: i64) -> Result<Vec<MealEntry>> {
+        dispatch!(self, store => store.get_entries_by_option(meal_option_id))
+    }
+
+    async fn get_recent_entries(&self, limit: i32) -> Result<Vec<MealEntry>> {
+        dispatch!(self, store => store.get_recent_entries(limit))
+    }
+
+    async fn update_entry(&self, id: i64, update: UpdateMealEntry) -> Result<MealEntry> {
+        dispatch!(self, store => store.update_entry(id, update))
+    }
+
+    async fn log_actual(&self, id: i64, actual: LogActualMeal) -> Result<MealEntry> {
+        dispatch!(self, store => store.log_actual(id, actual))
+    }
+
+    async fn delete_entry(&self, id: i64) -> Result<()> {
+        dispatch!(self, store => store.delete_entry(id))
+    }
+
+    async fn get_weekly_usage(
+        &self,
+        meal_option_id: i64,
+        week: &str,
+    ) -> Result<Option<WeeklyUsage>> {
+        dispatch!(self, store => store.get_weekly_usage(meal_option_id, week))
+    }
+
+    async fn get_weekly_template_usage(
+        &self,
+        template_id: i64,
+        week: &str,
+    ) -> Result<Option<WeeklyTemplateUsage>> {
+        dispatch!(self, store => store.get_weekly_template_usage(template_id, week))
+    }
+
+    async fn get_all_weekly_template_usage(&self, week: &str) -> Result<Vec<WeeklyTemplateUsage>> {
+        dispatch!(self, store => store.get_all_weekly_template_usage(week))
+    }
+
+    async fn get_weekly_tag_usage(
+        &self,
+        tag_id: i64,
+        week: &str,
+    ) -> Result<Option<WeeklyTagUsage>> {
+        dispatch!(self, store => store.get_weekly_tag_usage(tag_id, week))
+    }
+
+    async fn get_weekly_tag_usage_breakdown(
+        &self,
+        tag_id: i64,
+        week: &str,
+    ) -> Result<Vec<WeeklyTagUsageNode>> {
+        dispatch!(self, store => store.get_weekly_tag_usage_breakdown(tag_id, week))
+    }
+}
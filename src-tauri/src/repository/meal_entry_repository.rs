@@ -1,13 +1,45 @@
 use crate::models::{
-    CreateMealEntry, LocationType, MealEntry, SlotType, UpdateMealEntry, WeeklyTagUsage,
-    WeeklyUsage,
+    CreateMealEntry, CreateOffPlanEntry, EntryStatus, LocationType, LogActualMeal, MealEntry,
+    MealEntryOption, SelectedOption, SetNutrientProfile, SlotType, SyncEntity, UpdateMealEntry,
+    WeeklyTagUsage, WeeklyTagUsageNode, WeeklyTemplateUsage, WeeklyUsage,
 };
+use crate::repository::AuditScope;
 use chrono::NaiveDate;
-use sqlx::{Result, Row, SqlitePool};
+use sqlx::{Result, Row, SqliteConnection, SqlitePool};
 
 pub struct MealEntryRepository;
 
 impl MealEntryRepository {
+    /// Columns selected for a MealEntry from `meal_entries me`
+    /// Snapshot tag names are aggregated from meal_entry_tags, selected and planned
+    /// options from meal_entry_options / meal_entry_planned_options as JSON arrays
+    /// (main option first); the estimated nutrients of off-plan entries as a JSON object
+    const COLUMNS: &'static str = r#"
+        me.id, me.meal_option_id, me.date, me.slot_type, me.location, me.servings, me.notes,
+        me.status, me.option_name, me.template_id, me.template_name,
+        me.template_location_type, me.created_at, me.updated_at,
+        (SELECT GROUP_CONCAT(met.tag_name, ',' ORDER BY met.tag_name)
+         FROM meal_entry_tags met
+         WHERE met.meal_entry_id = me.id) AS tag_names,
+        (SELECT json_group_array(json_object(
+                    'meal_option_id', eo.meal_option_id,
+                    'option_name', eo.option_name,
+                    'servings', eo.servings) ORDER BY eo.position)
+         FROM meal_entry_options eo
+         WHERE eo.meal_entry_id = me.id) AS options,
+        (SELECT json_group_array(json_object(
+                    'meal_option_id', po.meal_option_id,
+                    'option_name', po.option_name,
+                    'servings', po.servings) ORDER BY po.position)
+         FROM meal_entry_planned_options po
+         WHERE po.meal_entry_id = me.id) AS planned_options,
+        (SELECT json_object(
+                    'kcal', n.kcal, 'protein_g', n.protein_g, 'carbs_g', n.carbs_g,
+                    'fat_g', n.fat_g, 'fiber_g', n.fiber_g)
+         FROM meal_entry_nutrients n
+         WHERE n.meal_entry_id = me.id) AS estimated_nutrients
+    "#;
+
     /// Helper to convert a database row to MealEntry
     fn row_to_entry(row: &sqlx::sqlite::SqliteRow) -> Result<MealEntry> {
         let slot_type_str: String = row.try_get("slot_type")?;
@@ -17,6 +49,36 @@ impl MealEntryRepository {
         let location =
             LocationType::from_db_string(&location_str).map_err(sqlx::Error::Protocol)?;
 
+        let status_str: String = row.try_get("status")?;
+        let status = EntryStatus::from_db_string(&status_str).map_err(sqlx::Error::Protocol)?;
+
+        let template_location_str: String = row.try_get("template_location_type")?;
+        let template_location_type =
+            LocationType::from_db_string(&template_location_str).map_err(sqlx::Error::Protocol)?;
+
+        let tag_names: Option<String> = row.try_get("tag_names")?;
+        let tag_names = tag_names
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect();
+
+        let options: String = row.try_get("options")?;
+        let options: Vec<MealEntryOption> =
+            serde_json::from_str(&options).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+
+        let planned_options: String = row.try_get("planned_options")?;
+        let planned_options: Vec<MealEntryOption> =
+            serde_json::from_str(&planned_options).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+
+        let estimated_nutrients: Option<String> = row.try_get("estimated_nutrients")?;
+        let estimated_nutrients: Option<SetNutrientProfile> = estimated_nutrients
+            .map(|json| serde_json::from_str(&json))
+            .transpose()
+            .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+
         Ok(MealEntry {
             id: row.try_get("id")?,
             meal_option_id: row.try_get("meal_option_id")?,
@@ -25,49 +87,318 @@ impl MealEntryRepository {
             location,
             servings: row.try_get("servings")?,
             notes: row.try_get("notes")?,
-            completed: row.try_get("completed")?,
+            status,
+            option_name: row.try_get("option_name")?,
+            template_id: row.try_get("template_id")?,
+            template_name: row.try_get("template_name")?,
+            template_location_type,
+            tag_names,
+            options,
+            planned_options,
+            estimated_nutrients,
             created_at: row.try_get("created_at")?,
             updated_at: row.try_get("updated_at")?,
         })
     }
 
-    /// Create a new meal entry
-    pub async fn create(pool: &SqlitePool, entry: CreateMealEntry) -> Result<MealEntry> {
-        // Validate using the model's validation method
-        entry.validate().map_err(sqlx::Error::Protocol)?;
+    /// Record the options selected in a new entry
+    /// The main option and its servings are read from the entry row itself;
+    /// call before `snapshot`, which fills in the option names
+    pub(crate) async fn insert_options(
+        conn: &mut SqliteConnection,
+        entry_id: i64,
+        extra_options: &[SelectedOption],
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO meal_entry_options (meal_entry_id, meal_option_id, position, servings)
+             SELECT id, meal_option_id, 0, servings FROM meal_entries WHERE id = ?1",
+        )
+        .bind(entry_id)
+        .execute(&mut *conn)
+        .await?;
+
+        for (i, extra) in extra_options.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO meal_entry_options (meal_entry_id, meal_option_id, position, servings)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )
+            .bind(entry_id)
+            .bind(extra.meal_option_id)
+            .bind(i as i64 + 1)
+            .bind(extra.servings.unwrap_or(1.0))
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Copy the current option names, template and tag set onto an entry
+    /// Called when an entry is created and when it is marked as eaten; after
+    /// that the snapshot no longer follows edits to the options or template
+    /// Off-plan entries keep their description and estimated tags
+    pub(crate) async fn snapshot(conn: &mut SqliteConnection, entry_id: i64) -> Result<()> {
+        let off_plan: Option<bool> =
+            sqlx::query_scalar("SELECT meal_option_id IS NULL FROM meal_entries WHERE id = ?1")
+                .bind(entry_id)
+                .fetch_optional(&mut *conn)
+                .await?;
+        if off_plan == Some(true) {
+            return Ok(());
+        }
+
+        sqlx::query(
+            "UPDATE meal_entries SET
+                 option_name = mo.name,
+                 template_id = mt.id,
+                 template_name = mt.name,
+                 template_location_type = mt.location_type
+             FROM meal_options mo
+             JOIN meal_templates mt ON mt.id = mo.template_id
+             WHERE mo.id = meal_entries.meal_option_id AND meal_entries.id = ?1",
+        )
+        .bind(entry_id)
+        .execute(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            "UPDATE meal_entry_options SET option_name = mo.name
+             FROM meal_options mo
+             WHERE mo.id = meal_entry_options.meal_option_id
+               AND meal_entry_options.meal_entry_id = ?1",
+        )
+        .bind(entry_id)
+        .execute(&mut *conn)
+        .await?;
+
+        sqlx::query("DELETE FROM meal_entry_tags WHERE meal_entry_id = ?1")
+            .bind(entry_id)
+            .execute(&mut *conn)
+            .await?;
+
+        // Tags of every selected option, each tag once
+        sqlx::query(
+            "INSERT OR IGNORE INTO meal_entry_tags (meal_entry_id, tag_id, tag_name)
+             SELECT eo.meal_entry_id, t.id, t.name
+             FROM meal_entry_options eo
+             JOIN meal_option_tags mot ON mot.meal_option_id = eo.meal_option_id
+             JOIN tags t ON t.id = mot.tag_id
+             WHERE eo.meal_entry_id = ?1",
+        )
+        .bind(entry_id)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record an entry's current options as the plan it was created with
+    /// Call after `snapshot`, so the planned options keep their names
+    pub(crate) async fn record_plan(conn: &mut SqliteConnection, entry_id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM meal_entry_planned_options WHERE meal_entry_id = ?1")
+            .bind(entry_id)
+            .execute(&mut *conn)
+            .await?;
+
+        sqlx::query(
+            "INSERT INTO meal_entry_planned_options (meal_entry_id, meal_option_id, position, servings, option_name)
+             SELECT meal_entry_id, meal_option_id, position, servings, option_name
+             FROM meal_entry_options WHERE meal_entry_id = ?1",
+        )
+        .bind(entry_id)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Store the estimated nutrient profile (per serving) of an off-plan entry
+    pub(crate) async fn set_estimated_nutrients(
+        conn: &mut SqliteConnection,
+        entry_id: i64,
+        nutrients: &SetNutrientProfile,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO meal_entry_nutrients (meal_entry_id, kcal, protein_g, carbs_g, fat_g, fiber_g)
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(entry_id)
+        .bind(nutrients.kcal)
+        .bind(nutrients.protein_g)
+        .bind(nutrients.carbs_g)
+        .bind(nutrients.fat_g)
+        .bind(nutrients.fiber_g)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
 
-        // Check that meal_option_id exists
+    /// Check that the main option exists and the extra options belong to its template
+    async fn check_selection(
+        conn: &mut SqliteConnection,
+        meal_option_id: i64,
+        extra_options: &[SelectedOption],
+    ) -> Result<()> {
         let option_exists: bool =
             sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM meal_options WHERE id = ?)")
-                .bind(entry.meal_option_id)
-                .fetch_one(pool)
+                .bind(meal_option_id)
+                .fetch_one(&mut *conn)
                 .await?;
 
         if !option_exists {
             return Err(sqlx::Error::Protocol(format!(
                 "Meal option with id {} does not exist",
-                entry.meal_option_id
+                meal_option_id
             )));
         }
 
+        for extra in extra_options {
+            let same_template: Option<bool> = sqlx::query_scalar(
+                "SELECT mo.template_id = main.template_id
+                 FROM meal_options mo, meal_options main
+                 WHERE mo.id = ? AND main.id = ?",
+            )
+            .bind(extra.meal_option_id)
+            .bind(meal_option_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+
+            match same_template {
+                None => {
+                    return Err(sqlx::Error::Protocol(format!(
+                        "Meal option with id {} does not exist",
+                        extra.meal_option_id
+                    )))
+                }
+                Some(false) => {
+                    return Err(sqlx::Error::Protocol(format!(
+                        "Meal option with id {} belongs to a different template",
+                        extra.meal_option_id
+                    )))
+                }
+                Some(true) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Create a new meal entry
+    pub async fn create(pool: &SqlitePool, entry: CreateMealEntry) -> Result<MealEntry> {
+        let mut tx = pool.begin().await?;
+        let id = Self::insert(&mut tx, &entry).await?;
+
+        let mut audit = AuditScope::new("create_entry");
+        audit.created(SyncEntity::Entry, id);
+        audit.record(&mut tx).await?;
+        tx.commit().await?;
+
+        Self::get_by_id(pool, id)
+            .await?
+            .ok_or_else(|| sqlx::Error::RowNotFound)
+    }
+
+    /// Insert a new meal entry on `conn`, e.g. within a caller's transaction
+    /// The selection is snapshotted and recorded as the plan, as for `create`.
+    /// Returns the new entry's ID.
+    pub(crate) async fn insert(
+        conn: &mut SqliteConnection,
+        entry: &CreateMealEntry,
+    ) -> Result<i64> {
+        // Validate using the model's validation method
+        entry.validate().map_err(sqlx::Error::Protocol)?;
+
+        Self::check_selection(conn, entry.meal_option_id, &entry.extra_options).await?;
+
         let servings = entry.servings_or_default();
-        let completed = entry.completed_or_default();
+        let status = entry.status_or_default();
 
         let result = sqlx::query(
-            "INSERT INTO meal_entries (meal_option_id, date, slot_type, location, servings, notes, completed) 
-             VALUES (?, ?, ?, ?, ?, ?, ?)"
+            "INSERT INTO meal_entries (meal_option_id, date, iso_week, slot_type, location, servings, notes, status) 
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
         )
         .bind(entry.meal_option_id)
         .bind(entry.date)
+        .bind(MealEntry::week_key(entry.date))
         .bind(entry.slot_type.to_db_string())
         .bind(entry.location.to_db_string())
         .bind(servings)
         .bind(&entry.notes)
-        .bind(completed)
-        .execute(pool)
+        .bind(status.to_db_string())
+        .execute(&mut *conn)
+        .await?;
+
+        let id = result.last_insert_rowid();
+        Self::insert_options(conn, id, &entry.extra_options).await?;
+        Self::snapshot(conn, id).await?;
+        Self::record_plan(conn, id).await?;
+
+        Ok(id)
+    }
+
+    /// Create an off-plan entry: a free-text meal with no option or template
+    /// The estimated tags are stored like an option's tag snapshot, so they count
+    /// toward weekly tag usage; nothing is recorded as planned
+    pub async fn create_off_plan(
+        pool: &SqlitePool,
+        entry: CreateOffPlanEntry,
+    ) -> Result<MealEntry> {
+        entry.validate().map_err(sqlx::Error::Protocol)?;
+
+        for tag_id in &entry.tag_ids {
+            let tag_exists: bool =
+                sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM tags WHERE id = ?)")
+                    .bind(tag_id)
+                    .fetch_one(pool)
+                    .await?;
+
+            if !tag_exists {
+                return Err(sqlx::Error::Protocol(format!(
+                    "Tag with id {} does not exist",
+                    tag_id
+                )));
+            }
+        }
+
+        let mut tx = pool.begin().await?;
+        let result = sqlx::query(
+            "INSERT INTO meal_entries (meal_option_id, date, iso_week, slot_type, location, servings, notes, status,
+                                       option_name, template_id, template_name)
+             VALUES (NULL, ?, ?, ?, ?, ?, ?, ?, ?, NULL, '')",
+        )
+        .bind(entry.date)
+        .bind(MealEntry::week_key(entry.date))
+        .bind(entry.slot_type.to_db_string())
+        .bind(entry.location.to_db_string())
+        .bind(entry.servings_or_default())
+        .bind(&entry.notes)
+        .bind(entry.status_or_default().to_db_string())
+        .bind(entry.description.trim())
+        .execute(&mut *tx)
         .await?;
 
         let id = result.last_insert_rowid();
+        for tag_id in &entry.tag_ids {
+            sqlx::query(
+                "INSERT OR IGNORE INTO meal_entry_tags (meal_entry_id, tag_id, tag_name)
+                 SELECT ?, id, name FROM tags WHERE id = ?",
+            )
+            .bind(id)
+            .bind(tag_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        if let Some(nutrients) = &entry.estimated_nutrients {
+            Self::set_estimated_nutrients(&mut tx, id, nutrients).await?;
+        }
+
+        let mut audit = AuditScope::new("create_off_plan_entry");
+        audit.created(SyncEntity::Entry, id);
+        audit.record(&mut tx).await?;
+        tx.commit().await?;
+
         Self::get_by_id(pool, id)
             .await?
             .ok_or_else(|| sqlx::Error::RowNotFound)
@@ -75,15 +406,12 @@ impl MealEntryRepository {
 
     /// Get a meal entry by ID
     pub async fn get_by_id(pool: &SqlitePool, id: i64) -> Result<Option<MealEntry>> {
-        let row = sqlx::query(
-            "SELECT id, meal_option_id, date, slot_type, location, servings, notes, completed,
-                    created_at, updated_at
-             FROM meal_entries 
-             WHERE id = ?",
-        )
-        .bind(id)
-        .fetch_optional(pool)
-        .await?;
+        let sql = format!(
+            "SELECT {} FROM meal_entries me
+             WHERE me.id = ?",
+            Self::COLUMNS
+        );
+        let row = sqlx::query(&sql).bind(id).fetch_optional(pool).await?;
 
         match row {
             Some(row) => Ok(Some(Self::row_to_entry(&row)?)),
@@ -91,24 +419,27 @@ impl MealEntryRepository {
         }
     }
 
+    /// Get all meal entries, oldest first
+    pub async fn get_all(pool: &SqlitePool) -> Result<Vec<MealEntry>> {
+        let sql = format!(
+            "SELECT {} FROM meal_entries me
+             ORDER BY me.date, me.id",
+            Self::COLUMNS
+        );
+        let rows = sqlx::query(&sql).fetch_all(pool).await?;
+
+        rows.iter().map(Self::row_to_entry).collect()
+    }
+
     /// Get all entries for a specific date
     pub async fn get_by_date(pool: &SqlitePool, date: NaiveDate) -> Result<Vec<MealEntry>> {
-        let rows = sqlx::query(
-            "SELECT id, meal_option_id, date, slot_type, location, servings, notes, completed,
-                    created_at, updated_at
-             FROM meal_entries 
-             WHERE date = ?
-             ORDER BY CASE slot_type
-                 WHEN 'breakfast' THEN 1
-                 WHEN 'morning_snack' THEN 2
-                 WHEN 'lunch' THEN 3
-                 WHEN 'afternoon_snack' THEN 4
-                 WHEN 'dinner' THEN 5
-             END",
-        )
-        .bind(date)
-        .fetch_all(pool)
-        .await?;
+        let sql = format!(
+            "SELECT {} FROM meal_entries me
+             WHERE me.date = ?
+             ORDER BY (SELECT sort_order FROM meal_slots WHERE name = me.slot_type)",
+            Self::COLUMNS
+        );
+        let rows = sqlx::query(&sql).bind(date).fetch_all(pool).await?;
 
         rows.iter().map(Self::row_to_entry).collect()
     }
@@ -119,23 +450,17 @@ impl MealEntryRepository {
         start_date: NaiveDate,
         end_date: NaiveDate,
     ) -> Result<Vec<MealEntry>> {
-        let rows = sqlx::query(
-            "SELECT id, meal_option_id, date, slot_type, location, servings, notes, completed,
-                    created_at, updated_at
-             FROM meal_entries 
-             WHERE date BETWEEN ? AND ?
-             ORDER BY date, CASE slot_type
-                 WHEN 'breakfast' THEN 1
-                 WHEN 'morning_snack' THEN 2
-                 WHEN 'lunch' THEN 3
-                 WHEN 'afternoon_snack' THEN 4
-                 WHEN 'dinner' THEN 5
-             END",
-        )
-        .bind(start_date)
-        .bind(end_date)
-        .fetch_all(pool)
-        .await?;
+        let sql = format!(
+            "SELECT {} FROM meal_entries me
+             WHERE me.date BETWEEN ? AND ?
+             ORDER BY me.date, (SELECT sort_order FROM meal_slots WHERE name = me.slot_type)",
+            Self::COLUMNS
+        );
+        let rows = sqlx::query(&sql)
+            .bind(start_date)
+            .bind(end_date)
+            .fetch_all(pool)
+            .await?;
 
         rows.iter().map(Self::row_to_entry).collect()
     }
@@ -144,59 +469,56 @@ impl MealEntryRepository {
     pub async fn get_by_date_and_slot(
         pool: &SqlitePool,
         date: NaiveDate,
-        slot: SlotType,
+        slot: &SlotType,
     ) -> Result<Vec<MealEntry>> {
-        let rows = sqlx::query(
-            "SELECT id, meal_option_id, date, slot_type, location, servings, notes, completed,
-                    created_at, updated_at
-             FROM meal_entries 
-             WHERE date = ? AND slot_type = ?",
-        )
-        .bind(date)
-        .bind(slot.to_db_string())
-        .fetch_all(pool)
-        .await?;
+        let sql = format!(
+            "SELECT {} FROM meal_entries me
+             WHERE me.date = ? AND me.slot_type = ?",
+            Self::COLUMNS
+        );
+        let rows = sqlx::query(&sql)
+            .bind(date)
+            .bind(slot.to_db_string())
+            .fetch_all(pool)
+            .await?;
 
         rows.iter().map(Self::row_to_entry).collect()
     }
 
-    /// Get entries by completion status
-    pub async fn get_by_completed(pool: &SqlitePool, completed: bool) -> Result<Vec<MealEntry>> {
-        let rows = sqlx::query(
-            "SELECT id, meal_option_id, date, slot_type, location, servings, notes, completed,
-                    created_at, updated_at
-             FROM meal_entries 
-             WHERE completed = ?
-             ORDER BY date DESC, CASE slot_type
-                 WHEN 'breakfast' THEN 1
-                 WHEN 'morning_snack' THEN 2
-                 WHEN 'lunch' THEN 3
-                 WHEN 'afternoon_snack' THEN 4
-                 WHEN 'dinner' THEN 5
-             END",
-        )
-        .bind(completed)
-        .fetch_all(pool)
-        .await?;
+    /// Get entries by status (planned, eaten or skipped)
+    pub async fn get_by_status(pool: &SqlitePool, status: EntryStatus) -> Result<Vec<MealEntry>> {
+        let sql = format!(
+            "SELECT {} FROM meal_entries me
+             WHERE me.status = ?
+             ORDER BY me.date DESC, (SELECT sort_order FROM meal_slots WHERE name = me.slot_type)",
+            Self::COLUMNS
+        );
+        let rows = sqlx::query(&sql)
+            .bind(status.to_db_string())
+            .fetch_all(pool)
+            .await?;
 
         rows.iter().map(Self::row_to_entry).collect()
     }
 
-    /// Get all entries for a specific meal option
+    /// Get all entries that selected a specific meal option (as main or extra option)
     pub async fn get_by_meal_option(
         pool: &SqlitePool,
         meal_option_id: i64,
     ) -> Result<Vec<MealEntry>> {
-        let rows = sqlx::query(
-            "SELECT id, meal_option_id, date, slot_type, location, servings, notes, completed,
-                    created_at, updated_at
-             FROM meal_entries 
-             WHERE meal_option_id = ?
-             ORDER BY date DESC",
-        )
-        .bind(meal_option_id)
-        .fetch_all(pool)
-        .await?;
+        let sql = format!(
+            "SELECT {} FROM meal_entries me
+             WHERE EXISTS (
+                 SELECT 1 FROM meal_entry_options eo
+                 WHERE eo.meal_entry_id = me.id AND eo.meal_option_id = ?
+             )
+             ORDER BY me.date DESC",
+            Self::COLUMNS
+        );
+        let rows = sqlx::query(&sql)
+            .bind(meal_option_id)
+            .fetch_all(pool)
+            .await?;
 
         rows.iter().map(Self::row_to_entry).collect()
     }
@@ -220,16 +542,71 @@ impl MealEntryRepository {
         Ok(row)
     }
 
-    /// Get weekly usage statistics for a tag
+    /// Get weekly usage statistics for a template (all of its options combined)
+    pub async fn get_weekly_template_usage(
+        pool: &SqlitePool,
+        template_id: i64,
+        week: &str,
+    ) -> Result<Option<WeeklyTemplateUsage>> {
+        let row = sqlx::query_as::<_, WeeklyTemplateUsage>(
+            "SELECT template_id, template_name, week, usage_count, weekly_limit
+             FROM weekly_template_usage
+             WHERE template_id = ? AND week = ?",
+        )
+        .bind(template_id)
+        .bind(week)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    /// Get weekly usage for every template that has a weekly limit or was used that week
+    /// Limited templates with no usage are reported with a count of 0
+    pub async fn get_all_weekly_template_usage(
+        pool: &SqlitePool,
+        week: &str,
+    ) -> Result<Vec<WeeklyTemplateUsage>> {
+        let rows = sqlx::query_as::<_, WeeklyTemplateUsage>(
+            "SELECT mt.id as template_id, mt.name as template_name, ? as week,
+                    COALESCE(wtu.usage_count, 0) as usage_count, mt.weekly_limit
+             FROM meal_templates mt
+             LEFT JOIN weekly_template_usage wtu ON wtu.template_id = mt.id AND wtu.week = ?
+             WHERE mt.weekly_limit IS NOT NULL OR wtu.usage_count IS NOT NULL
+             ORDER BY mt.name",
+        )
+        .bind(week)
+        .bind(week)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Get weekly usage statistics for a tag, rolled up over its descendants
+    /// An entry counts once even if it carries several tags from the same subtree
     pub async fn get_weekly_tag_usage(
         pool: &SqlitePool,
         tag_id: i64,
         week: &str,
     ) -> Result<Option<WeeklyTagUsage>> {
         let row = sqlx::query_as::<_, WeeklyTagUsage>(
-            "SELECT tag_id, tag_name, week, usage_count 
-             FROM weekly_tag_usage 
-             WHERE tag_id = ? AND week = ?",
+            "WITH RECURSIVE subtree(id) AS (
+                 SELECT id FROM tags WHERE id = ?1
+                 UNION
+                 SELECT t.id FROM tags t JOIN subtree s ON t.parent_tag_id = s.id
+             )
+             SELECT t.id as tag_id, t.name as tag_name, ?2 as week,
+                    COUNT(DISTINCT me.id) as usage_count
+             FROM tags t
+             JOIN meal_entries me ON me.status = 'eaten' AND me.iso_week = ?2
+             WHERE t.id = ?1
+               AND EXISTS (
+                   SELECT 1 FROM meal_entry_tags met
+                   JOIN subtree s ON s.id = met.tag_id
+                   WHERE met.meal_entry_id = me.id
+               )
+             GROUP BY t.id, t.name",
         )
         .bind(tag_id)
         .bind(week)
@@ -239,37 +616,114 @@ impl MealEntryRepository {
         Ok(row)
     }
 
+    /// Get the weekly usage breakdown of a tag's whole lineage
+    /// Returns its ancestors, the tag itself and all descendants, each with
+    /// direct and rolled-up counts, ordered from the root down
+    pub async fn get_weekly_tag_usage_breakdown(
+        pool: &SqlitePool,
+        tag_id: i64,
+        week: &str,
+    ) -> Result<Vec<WeeklyTagUsageNode>> {
+        let rows = sqlx::query_as::<_, WeeklyTagUsageNode>(
+            "WITH RECURSIVE
+             ancestors(id, depth) AS (
+                 SELECT id, 0 FROM tags WHERE id = ?1
+                 UNION
+                 SELECT t.parent_tag_id, a.depth - 1
+                 FROM tags t JOIN ancestors a ON t.id = a.id
+                 WHERE t.parent_tag_id IS NOT NULL
+             ),
+             descendants(id, depth) AS (
+                 SELECT id, 0 FROM tags WHERE id = ?1
+                 UNION
+                 SELECT t.id, d.depth + 1
+                 FROM tags t JOIN descendants d ON t.parent_tag_id = d.id
+             ),
+             lineage(id, depth) AS (
+                 SELECT id, depth FROM ancestors
+                 UNION
+                 SELECT id, depth FROM descendants
+             ),
+             closure(ancestor_id, tag_id) AS (
+                 SELECT id, id FROM lineage
+                 UNION
+                 SELECT c.ancestor_id, t.id
+                 FROM tags t JOIN closure c ON t.parent_tag_id = c.tag_id
+             ),
+             week_entries(entry_id, tag_id) AS (
+                 SELECT me.id, met.tag_id
+                 FROM meal_entries me
+                 JOIN meal_entry_tags met ON met.meal_entry_id = me.id
+                 WHERE me.status = 'eaten' AND me.iso_week = ?2
+             )
+             SELECT t.id as tag_id, t.name as tag_name, t.display_name, t.parent_tag_id,
+                    t.weekly_suggestion,
+                    (SELECT COUNT(DISTINCT we.entry_id) FROM week_entries we
+                     WHERE we.tag_id = t.id) as direct_count,
+                    (SELECT COUNT(DISTINCT we.entry_id) FROM week_entries we
+                     JOIN closure c ON c.tag_id = we.tag_id
+                     WHERE c.ancestor_id = t.id) as total_count
+             FROM lineage l
+             JOIN tags t ON t.id = l.id
+             GROUP BY t.id
+             ORDER BY MIN(l.depth), t.name",
+        )
+        .bind(tag_id)
+        .bind(week)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows)
+    }
+
     /// Get recently used meal entries (for quick reselection)
     /// Returns the most recent unique meal_option_id entries, ordered by most recent first
+    /// Off-plan entries have no option to reselect and are left out
     pub async fn get_recent_entries(pool: &SqlitePool, limit: i32) -> Result<Vec<MealEntry>> {
-        let rows = sqlx::query(
-            "SELECT id, meal_option_id, date, slot_type, location, servings, notes, completed,
-                    created_at, updated_at
-             FROM meal_entries 
-             WHERE id IN (
+        let sql = format!(
+            "SELECT {} FROM meal_entries me
+             WHERE me.id IN (
                  SELECT MAX(id) 
                  FROM meal_entries 
+                 WHERE meal_option_id IS NOT NULL
                  GROUP BY meal_option_id
              )
-             ORDER BY date DESC, created_at DESC
+             ORDER BY me.date DESC, me.created_at DESC
              LIMIT ?",
-        )
-        .bind(limit)
-        .fetch_all(pool)
-        .await?;
+            Self::COLUMNS
+        );
+        let rows = sqlx::query(&sql).bind(limit).fetch_all(pool).await?;
 
         rows.iter().map(Self::row_to_entry).collect()
     }
 
+    /// Get the date each meal option was last eaten on or before `date`
+    /// Extra options of composite meals count as eaten too; options never eaten are not listed
+    pub async fn get_last_eaten_dates(
+        pool: &SqlitePool,
+        date: NaiveDate,
+    ) -> Result<Vec<(i64, NaiveDate)>> {
+        sqlx::query_as::<_, (i64, NaiveDate)>(
+            "SELECT eo.meal_option_id, MAX(me.date)
+             FROM meal_entry_options eo
+             JOIN meal_entries me ON me.id = eo.meal_entry_id
+             WHERE me.status = 'eaten' AND me.date <= ?
+             GROUP BY eo.meal_option_id",
+        )
+        .bind(date)
+        .fetch_all(pool)
+        .await
+    }
+
     /// Update a meal entry
     pub async fn update(pool: &SqlitePool, id: i64, update: UpdateMealEntry) -> Result<MealEntry> {
         // Validate using the model's validation method
         update.validate().map_err(sqlx::Error::Protocol)?;
 
         // Check that entry exists
-        if Self::get_by_id(pool, id).await?.is_none() {
+        let Some(existing) = Self::get_by_id(pool, id).await? else {
             return Err(sqlx::Error::RowNotFound);
-        }
+        };
 
         // Build dynamic update query based on which fields are Some
         let mut updates = Vec::new();
@@ -284,8 +738,8 @@ impl MealEntryRepository {
         if update.notes.is_some() {
             updates.push("notes = ?");
         }
-        if update.completed.is_some() {
-            updates.push("completed = ?");
+        if update.status.is_some() {
+            updates.push("status = ?");
         }
 
         if updates.is_empty() {
@@ -310,12 +764,87 @@ impl MealEntryRepository {
         if let Some(notes) = &update.notes {
             query = query.bind(notes.as_ref());
         }
-        if let Some(completed) = update.completed {
-            query = query.bind(completed);
+        if let Some(status) = update.status {
+            query = query.bind(status.to_db_string());
         }
 
         query = query.bind(id);
-        query.execute(pool).await?;
+
+        let mut tx = pool.begin().await?;
+        let mut audit = AuditScope::new("update_entry");
+        audit.track(&mut tx, SyncEntity::Entry, [id]).await?;
+        query.execute(&mut *tx).await?;
+
+        // Servings of the entry are the servings of its main option
+        if let Some(servings) = update.servings {
+            sqlx::query(
+                "UPDATE meal_entry_options SET servings = ? WHERE meal_entry_id = ? AND position = 0",
+            )
+            .bind(servings)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        // A planned entry being marked as eaten records the option as it is now
+        if update.status == Some(EntryStatus::Eaten) && existing.status != EntryStatus::Eaten {
+            Self::snapshot(&mut tx, id).await?;
+        }
+        audit.record(&mut tx).await?;
+        tx.commit().await?;
+
+        Self::get_by_id(pool, id)
+            .await?
+            .ok_or_else(|| sqlx::Error::RowNotFound)
+    }
+
+    /// Log what was actually eaten for an entry
+    /// Replaces the entry's options, marks it as eaten and refreshes its snapshot;
+    /// the planned options are kept, so a swap stays visible
+    pub async fn log_actual(
+        pool: &SqlitePool,
+        id: i64,
+        actual: LogActualMeal,
+    ) -> Result<MealEntry> {
+        actual.validate().map_err(sqlx::Error::Protocol)?;
+
+        if Self::get_by_id(pool, id).await?.is_none() {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        let mut tx = pool.begin().await?;
+        Self::check_selection(&mut tx, actual.meal_option_id, &actual.extra_options).await?;
+        let mut audit = AuditScope::new("log_actual");
+        audit.track(&mut tx, SyncEntity::Entry, [id]).await?;
+        sqlx::query(
+            "UPDATE meal_entries
+             SET meal_option_id = ?, servings = ?, location = COALESCE(?, location),
+                 status = 'eaten', updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?",
+        )
+        .bind(actual.meal_option_id)
+        .bind(actual.servings_or_default())
+        .bind(actual.location.map(LocationType::to_db_string))
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        if let Some(notes) = &actual.notes {
+            sqlx::query("UPDATE meal_entries SET notes = ? WHERE id = ?")
+                .bind(notes.as_ref())
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        sqlx::query("DELETE FROM meal_entry_options WHERE meal_entry_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        Self::insert_options(&mut tx, id, &actual.extra_options).await?;
+        Self::snapshot(&mut tx, id).await?;
+        audit.record(&mut tx).await?;
+        tx.commit().await?;
 
         Self::get_by_id(pool, id)
             .await?
@@ -324,16 +853,62 @@ impl MealEntryRepository {
 
     /// Delete a meal entry
     pub async fn delete(pool: &SqlitePool, id: i64) -> Result<()> {
+        let mut tx = pool.begin().await?;
+        let mut audit = AuditScope::new("delete_entry");
+        audit.track(&mut tx, SyncEntity::Entry, [id]).await?;
+
         let result = sqlx::query("DELETE FROM meal_entries WHERE id = ?")
             .bind(id)
-            .execute(pool)
+            .execute(&mut *tx)
             .await?;
 
         if result.rows_affected() == 0 {
             return Err(sqlx::Error::RowNotFound);
         }
 
-        Ok(())
+        audit.record(&mut tx).await?;
+        tx.commit().await
+    }
+
+    /// Delete every entry in a slot of a day
+    /// Returns the number of deleted entries
+    pub async fn delete_by_date_and_slot(
+        pool: &SqlitePool,
+        date: NaiveDate,
+        slot: &SlotType,
+    ) -> Result<u64> {
+        let mut tx = pool.begin().await?;
+        let mut audit = AuditScope::new("delete_slot_entries");
+        let deleted = Self::delete_slot(&mut tx, date, slot, &mut audit).await?;
+
+        audit.record(&mut tx).await?;
+        tx.commit().await?;
+
+        Ok(deleted)
+    }
+
+    /// Delete every entry in a slot of a day on `conn` as part of `audit`'s command
+    pub(crate) async fn delete_slot(
+        conn: &mut SqliteConnection,
+        date: NaiveDate,
+        slot: &SlotType,
+        audit: &mut AuditScope,
+    ) -> Result<u64> {
+        let ids: Vec<i64> =
+            sqlx::query_scalar("SELECT id FROM meal_entries WHERE date = ? AND slot_type = ?")
+                .bind(date)
+                .bind(slot.to_db_string())
+                .fetch_all(&mut *conn)
+                .await?;
+        audit.track(conn, SyncEntity::Entry, ids).await?;
+
+        let result = sqlx::query("DELETE FROM meal_entries WHERE date = ? AND slot_type = ?")
+            .bind(date)
+            .bind(slot.to_db_string())
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(result.rows_affected())
     }
 }
 
@@ -341,9 +916,13 @@ impl MealEntryRepository {
 mod tests {
     use super::*;
     use crate::db;
-    use crate::models::{CreateMealOption, CreateMealTemplate};
-    use crate::repository::{MealOptionRepository, MealTemplateRepository};
+    use crate::models::{
+        CreateMealOption, CreateMealTemplate, CreateTag, TagCategory, UpdateMealOption,
+        UpdateMealTemplate,
+    };
+    use crate::repository::{MealOptionRepository, MealTemplateRepository, TagRepository};
     use chrono::NaiveDate;
+    use std::collections::HashMap;
     use std::path::PathBuf;
     use tempfile::TempDir;
 
@@ -360,7 +939,7 @@ mod tests {
             name: "Test Template".to_string(),
             description: Some("Test Description".to_string()),
             location_type: LocationType::Home,
-            compatible_slots: vec![SlotType::Breakfast, SlotType::Lunch],
+            compatible_slots: vec![SlotType::BREAKFAST, SlotType::LUNCH],
             weekly_limit: None,
         };
         let template = MealTemplateRepository::create(pool, template)
@@ -386,20 +965,21 @@ mod tests {
         let entry = CreateMealEntry {
             meal_option_id: option_id,
             date: NaiveDate::from_ymd_opt(2024, 11, 5).unwrap(),
-            slot_type: SlotType::Breakfast,
+            slot_type: SlotType::BREAKFAST,
             location: LocationType::Home,
             servings: Some(1.5),
             notes: Some("Extra avocado".to_string()),
-            completed: Some(true),
+            status: Some(EntryStatus::Eaten),
+            extra_options: vec![],
         };
 
         let created = MealEntryRepository::create(&pool, entry).await.unwrap();
 
-        assert_eq!(created.meal_option_id, option_id);
+        assert_eq!(created.meal_option_id, Some(option_id));
         assert_eq!(created.date, NaiveDate::from_ymd_opt(2024, 11, 5).unwrap());
-        assert_eq!(created.slot_type, SlotType::Breakfast);
+        assert_eq!(created.slot_type, SlotType::BREAKFAST);
         assert_eq!(created.servings, 1.5);
-        assert!(created.completed);
+        assert_eq!(created.status, EntryStatus::Eaten);
     }
 
     #[tokio::test]
@@ -410,17 +990,18 @@ mod tests {
         let entry = CreateMealEntry {
             meal_option_id: option_id,
             date: NaiveDate::from_ymd_opt(2024, 11, 5).unwrap(),
-            slot_type: SlotType::Lunch,
+            slot_type: SlotType::LUNCH,
             location: LocationType::Office,
             servings: None, // Should default to 1.0
             notes: None,
-            completed: None, // Should default to false
+            status: None, // Should default to planned
+            extra_options: vec![],
         };
 
         let created = MealEntryRepository::create(&pool, entry).await.unwrap();
 
         assert_eq!(created.servings, 1.0);
-        assert!(!created.completed);
+        assert_eq!(created.status, EntryStatus::Planned);
     }
 
     #[tokio::test]
@@ -430,7 +1011,7 @@ mod tests {
         let date = NaiveDate::from_ymd_opt(2024, 11, 5).unwrap();
 
         // Create multiple entries for the same date
-        for slot in [SlotType::Breakfast, SlotType::Lunch, SlotType::Dinner] {
+        for slot in [SlotType::BREAKFAST, SlotType::LUNCH, SlotType::DINNER] {
             let entry = CreateMealEntry {
                 meal_option_id: option_id,
                 date,
@@ -438,7 +1019,8 @@ mod tests {
                 location: LocationType::Home,
                 servings: None,
                 notes: None,
-                completed: None,
+                status: None,
+                extra_options: vec![],
             };
             MealEntryRepository::create(&pool, entry).await.unwrap();
         }
@@ -447,9 +1029,9 @@ mod tests {
 
         assert_eq!(entries.len(), 3);
         // Verify they're sorted by slot order
-        assert_eq!(entries[0].slot_type, SlotType::Breakfast);
-        assert_eq!(entries[1].slot_type, SlotType::Lunch);
-        assert_eq!(entries[2].slot_type, SlotType::Dinner);
+        assert_eq!(entries[0].slot_type, SlotType::BREAKFAST);
+        assert_eq!(entries[1].slot_type, SlotType::LUNCH);
+        assert_eq!(entries[2].slot_type, SlotType::DINNER);
     }
 
     #[tokio::test]
@@ -462,11 +1044,12 @@ mod tests {
             let entry = CreateMealEntry {
                 meal_option_id: option_id,
                 date: NaiveDate::from_ymd_opt(2024, 11, day).unwrap(),
-                slot_type: SlotType::Breakfast,
+                slot_type: SlotType::BREAKFAST,
                 location: LocationType::Home,
                 servings: None,
                 notes: None,
-                completed: None,
+                status: None,
+                extra_options: vec![],
             };
             MealEntryRepository::create(&pool, entry).await.unwrap();
         }
@@ -501,7 +1084,7 @@ mod tests {
         let date = NaiveDate::from_ymd_opt(2024, 11, 5).unwrap();
 
         // Create entries for different slots
-        for slot in [SlotType::Breakfast, SlotType::Lunch] {
+        for slot in [SlotType::BREAKFAST, SlotType::LUNCH] {
             let entry = CreateMealEntry {
                 meal_option_id: option_id,
                 date,
@@ -509,47 +1092,60 @@ mod tests {
                 location: LocationType::Home,
                 servings: None,
                 notes: None,
-                completed: None,
+                status: None,
+                extra_options: vec![],
             };
             MealEntryRepository::create(&pool, entry).await.unwrap();
         }
 
-        let entries = MealEntryRepository::get_by_date_and_slot(&pool, date, SlotType::Breakfast)
+        let entries = MealEntryRepository::get_by_date_and_slot(&pool, date, &SlotType::BREAKFAST)
             .await
             .unwrap();
 
         assert_eq!(entries.len(), 1);
-        assert_eq!(entries[0].slot_type, SlotType::Breakfast);
+        assert_eq!(entries[0].slot_type, SlotType::BREAKFAST);
     }
 
     #[tokio::test]
-    async fn test_get_by_completed() {
+    async fn test_get_by_status() {
         let (pool, _temp_dir) = setup_test_db().await;
         let option_id = create_test_option(&pool).await;
 
-        // Create planned and completed entries
-        for (day, completed) in [(1, false), (2, false), (3, true), (4, true)] {
+        // Create planned, eaten and skipped entries
+        for (day, status) in [
+            (1, EntryStatus::Planned),
+            (2, EntryStatus::Planned),
+            (3, EntryStatus::Eaten),
+            (4, EntryStatus::Eaten),
+            (5, EntryStatus::Skipped),
+        ] {
             let entry = CreateMealEntry {
                 meal_option_id: option_id,
                 date: NaiveDate::from_ymd_opt(2024, 11, day).unwrap(),
-                slot_type: SlotType::Breakfast,
+                slot_type: SlotType::BREAKFAST,
                 location: LocationType::Home,
                 servings: None,
                 notes: None,
-                completed: Some(completed),
+                status: Some(status),
+                extra_options: vec![],
             };
             MealEntryRepository::create(&pool, entry).await.unwrap();
         }
 
-        let planned = MealEntryRepository::get_by_completed(&pool, false)
+        let planned = MealEntryRepository::get_by_status(&pool, EntryStatus::Planned)
             .await
             .unwrap();
         assert_eq!(planned.len(), 2);
 
-        let completed = MealEntryRepository::get_by_completed(&pool, true)
+        let eaten = MealEntryRepository::get_by_status(&pool, EntryStatus::Eaten)
+            .await
+            .unwrap();
+        assert_eq!(eaten.len(), 2);
+
+        let skipped = MealEntryRepository::get_by_status(&pool, EntryStatus::Skipped)
             .await
             .unwrap();
-        assert_eq!(completed.len(), 2);
+        assert_eq!(skipped.len(), 1);
     }
 
     #[tokio::test]
@@ -562,11 +1158,12 @@ mod tests {
             let entry = CreateMealEntry {
                 meal_option_id: option_id,
                 date: NaiveDate::from_ymd_opt(2024, 11, day).unwrap(),
-                slot_type: SlotType::Breakfast,
+                slot_type: SlotType::BREAKFAST,
                 location: LocationType::Home,
                 servings: None,
                 notes: None,
-                completed: None,
+                status: None,
+                extra_options: vec![],
             };
             MealEntryRepository::create(&pool, entry).await.unwrap();
         }
@@ -582,17 +1179,18 @@ mod tests {
         let (pool, _temp_dir) = setup_test_db().await;
         let option_id = create_test_option(&pool).await;
 
-        // Create completed entries in the same week (Nov 4-6, 2024 are all in week 45)
+        // Create eaten entries in the same week (Nov 4-6, 2024 are all in week 45)
         // Nov 4 = Monday, Nov 5 = Tuesday, Nov 6 = Wednesday
         for day in [4, 5, 6] {
             let entry = CreateMealEntry {
                 meal_option_id: option_id,
                 date: NaiveDate::from_ymd_opt(2024, 11, day).unwrap(),
-                slot_type: SlotType::Breakfast,
+                slot_type: SlotType::BREAKFAST,
                 location: LocationType::Home,
                 servings: None,
                 notes: None,
-                completed: Some(true), // Only completed entries count
+                status: Some(EntryStatus::Eaten), // Only eaten entries count,
+                extra_options: vec![],
             };
             MealEntryRepository::create(&pool, entry).await.unwrap();
         }
@@ -607,6 +1205,471 @@ mod tests {
         assert_eq!(usage.usage_count, 3);
     }
 
+    #[tokio::test]
+    async fn test_weekly_tag_usage_rolls_up_children() {
+        let (pool, _temp_dir) = setup_test_db().await;
+        let option_id = create_test_option(&pool).await;
+        let template_id = MealOptionRepository::get_by_id(&pool, option_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .template_id;
+
+        // carboidrati > pasta > pasta_integrale, and riso next to pasta
+        let mut tag_ids = HashMap::new();
+        for (name, parent, suggestion) in [
+            ("carboidrati", None, None),
+            ("pasta", Some("carboidrati"), Some(3)),
+            ("pasta_integrale", Some("pasta"), None),
+            ("riso", Some("carboidrati"), None),
+        ] {
+            let tag = TagRepository::create(
+                &pool,
+                CreateTag {
+                    name: name.to_string(),
+                    display_name: name.to_string(),
+                    category: TagCategory::Ingredient,
+                    weekly_suggestion: suggestion,
+                    parent_tag_id: parent.map(|p| tag_ids[p]),
+                },
+            )
+            .await
+            .unwrap();
+            tag_ids.insert(name, tag.id);
+        }
+
+        // Entry 1: option tagged with both pasta and pasta_integrale (counts once)
+        MealOptionRepository::add_tags(
+            &pool,
+            option_id,
+            vec![tag_ids["pasta"], tag_ids["pasta_integrale"]],
+        )
+        .await
+        .unwrap();
+        // Entry 2: a rice option
+        let rice_option_id = MealOptionRepository::create(
+            &pool,
+            CreateMealOption {
+                template_id,
+                name: "Risotto".to_string(),
+                description: None,
+                nutritional_notes: None,
+            },
+        )
+        .await
+        .unwrap()
+        .id;
+        MealOptionRepository::add_tags(&pool, rice_option_id, vec![tag_ids["riso"]])
+            .await
+            .unwrap();
+
+        for (option, day) in [(option_id, 4), (rice_option_id, 5)] {
+            let entry = CreateMealEntry {
+                meal_option_id: option,
+                date: NaiveDate::from_ymd_opt(2024, 11, day).unwrap(),
+                slot_type: SlotType::LUNCH,
+                location: LocationType::Home,
+                servings: None,
+                notes: None,
+                status: Some(EntryStatus::Eaten),
+                extra_options: vec![],
+            };
+            MealEntryRepository::create(&pool, entry).await.unwrap();
+        }
+
+        for (name, expected) in [
+            ("carboidrati", 2),
+            ("pasta", 1),
+            ("pasta_integrale", 1),
+            ("riso", 1),
+        ] {
+            let usage = MealEntryRepository::get_weekly_tag_usage(&pool, tag_ids[name], "2024-45")
+                .await
+                .unwrap()
+                .map(|u| u.usage_count)
+                .unwrap_or(0);
+            assert_eq!(usage, expected, "usage of {}", name);
+        }
+
+        // Breakdown of pasta: its ancestor, itself and its child, root first
+        let breakdown =
+            MealEntryRepository::get_weekly_tag_usage_breakdown(&pool, tag_ids["pasta"], "2024-45")
+                .await
+                .unwrap();
+        let names: Vec<&str> = breakdown.iter().map(|n| n.tag_name.as_str()).collect();
+        assert_eq!(names, vec!["carboidrati", "pasta", "pasta_integrale"]);
+
+        let counts: Vec<(i64, i64)> = breakdown
+            .iter()
+            .map(|n| (n.direct_count, n.total_count))
+            .collect();
+        // carboidrati also includes the sibling riso subtree
+        assert_eq!(counts, vec![(0, 2), (1, 1), (1, 1)]);
+        assert_eq!(breakdown[1].weekly_suggestion, Some(3));
+    }
+
+    #[tokio::test]
+    async fn test_weekly_template_usage_view() {
+        let (pool, _temp_dir) = setup_test_db().await;
+        let option_id = create_test_option(&pool).await;
+        let template_id = MealOptionRepository::get_by_id(&pool, option_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .template_id;
+
+        // Second option of the same template
+        let other_option_id = MealOptionRepository::create(
+            &pool,
+            CreateMealOption {
+                template_id,
+                name: "Other Option".to_string(),
+                description: None,
+                nutritional_notes: None,
+            },
+        )
+        .await
+        .unwrap()
+        .id;
+
+        for (option, day, status) in [
+            (option_id, 4, EntryStatus::Eaten),
+            (other_option_id, 5, EntryStatus::Eaten),
+            (other_option_id, 6, EntryStatus::Planned), // Planned entries don't count
+        ] {
+            let entry = CreateMealEntry {
+                meal_option_id: option,
+                date: NaiveDate::from_ymd_opt(2024, 11, day).unwrap(),
+                slot_type: SlotType::BREAKFAST,
+                location: LocationType::Home,
+                servings: None,
+                notes: None,
+                status: Some(status),
+                extra_options: vec![],
+            };
+            MealEntryRepository::create(&pool, entry).await.unwrap();
+        }
+
+        let usage = MealEntryRepository::get_weekly_template_usage(&pool, template_id, "2024-45")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(usage.usage_count, 2);
+        assert_eq!(usage.template_name, "Test Template");
+
+        let report = MealEntryRepository::get_all_weekly_template_usage(&pool, "2024-45")
+            .await
+            .unwrap();
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].usage_count, 2);
+
+        // Unlimited templates without usage are left out of the report
+        let report = MealEntryRepository::get_all_weekly_template_usage(&pool, "2024-46")
+            .await
+            .unwrap();
+        assert!(report.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_survives_library_edits() {
+        let (pool, _temp_dir) = setup_test_db().await;
+        let option_id = create_test_option(&pool).await;
+        let option = MealOptionRepository::get_by_id(&pool, option_id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let mut tag_ids = HashMap::new();
+        for name in ["pasta", "riso"] {
+            let tag = TagRepository::create(
+                &pool,
+                CreateTag {
+                    name: name.to_string(),
+                    display_name: name.to_string(),
+                    category: TagCategory::Ingredient,
+                    weekly_suggestion: None,
+                    parent_tag_id: None,
+                },
+            )
+            .await
+            .unwrap();
+            tag_ids.insert(name, tag.id);
+        }
+        MealOptionRepository::set_tags(&pool, option_id, vec![tag_ids["pasta"]])
+            .await
+            .unwrap();
+
+        let entry = MealEntryRepository::create(
+            &pool,
+            CreateMealEntry {
+                meal_option_id: option_id,
+                date: NaiveDate::from_ymd_opt(2024, 11, 4).unwrap(),
+                slot_type: SlotType::LUNCH,
+                location: LocationType::Home,
+                servings: None,
+                notes: None,
+                status: Some(EntryStatus::Eaten),
+                extra_options: vec![],
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(entry.option_name, "Test Option");
+        assert_eq!(entry.template_id, Some(option.template_id));
+        assert_eq!(entry.template_name, "Test Template");
+        assert_eq!(entry.template_location_type, LocationType::Home);
+        assert_eq!(entry.tag_names, vec!["pasta".to_string()]);
+
+        // Rename, move the template to another location and retag the option
+        MealOptionRepository::update(
+            &pool,
+            option_id,
+            UpdateMealOption {
+                name: Some("Renamed".to_string()),
+                description: None,
+                nutritional_notes: None,
+            },
+        )
+        .await
+        .unwrap();
+        MealTemplateRepository::update(
+            &pool,
+            option.template_id,
+            UpdateMealTemplate {
+                name: Some("Renamed Template".to_string()),
+                description: None,
+                compatible_slots: None,
+                location_type: Some(LocationType::Office),
+                weekly_limit: None,
+            },
+        )
+        .await
+        .unwrap();
+        MealOptionRepository::set_tags(&pool, option_id, vec![tag_ids["riso"]])
+            .await
+            .unwrap();
+
+        let logged = MealEntryRepository::get_by_id(&pool, entry.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(logged.option_name, "Test Option");
+        assert_eq!(logged.template_name, "Test Template");
+        assert_eq!(logged.template_location_type, LocationType::Home);
+        assert_eq!(logged.tag_names, vec!["pasta".to_string()]);
+
+        // Usage still counts the tag the entry was logged with
+        let pasta = MealEntryRepository::get_weekly_tag_usage(&pool, tag_ids["pasta"], "2024-45")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(pasta.usage_count, 1);
+        let riso = MealEntryRepository::get_weekly_tag_usage(&pool, tag_ids["riso"], "2024-45")
+            .await
+            .unwrap();
+        assert_eq!(riso.map_or(0, |u| u.usage_count), 0);
+
+        // Deleting the tag does not erase it from history
+        TagRepository::delete(&pool, tag_ids["pasta"])
+            .await
+            .unwrap();
+        let logged = MealEntryRepository::get_by_id(&pool, entry.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(logged.tag_names, vec!["pasta".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_refreshed_when_eaten() {
+        let (pool, _temp_dir) = setup_test_db().await;
+        let option_id = create_test_option(&pool).await;
+
+        let planned = MealEntryRepository::create(
+            &pool,
+            CreateMealEntry {
+                meal_option_id: option_id,
+                date: NaiveDate::from_ymd_opt(2024, 11, 4).unwrap(),
+                slot_type: SlotType::LUNCH,
+                location: LocationType::Home,
+                servings: None,
+                notes: None,
+                status: None,
+                extra_options: vec![],
+            },
+        )
+        .await
+        .unwrap();
+
+        let rename = |name: &str| UpdateMealOption {
+            name: Some(name.to_string()),
+            description: None,
+            nutritional_notes: None,
+        };
+        MealOptionRepository::update(&pool, option_id, rename("Before eating"))
+            .await
+            .unwrap();
+
+        let eaten = MealEntryRepository::update(
+            &pool,
+            planned.id,
+            UpdateMealEntry {
+                location: None,
+                servings: None,
+                notes: None,
+                status: Some(EntryStatus::Eaten),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(planned.option_name, "Test Option");
+        assert_eq!(eaten.option_name, "Before eating");
+
+        // Once eaten, later edits to the entry keep the snapshot
+        MealOptionRepository::update(&pool, option_id, rename("After eating"))
+            .await
+            .unwrap();
+        let updated = MealEntryRepository::update(
+            &pool,
+            planned.id,
+            UpdateMealEntry {
+                location: None,
+                servings: Some(2.0),
+                notes: None,
+                status: Some(EntryStatus::Eaten),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(updated.option_name, "Before eating");
+    }
+
+    #[tokio::test]
+    async fn test_multi_option_entry() {
+        let (pool, _temp_dir) = setup_test_db().await;
+        let bread_id = create_test_option(&pool).await;
+        let bread = MealOptionRepository::get_by_id(&pool, bread_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let jam = MealOptionRepository::create(
+            &pool,
+            CreateMealOption {
+                template_id: bread.template_id,
+                name: "Marmellata".to_string(),
+                description: None,
+                nutritional_notes: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let mut tag_ids = HashMap::new();
+        for name in ["pane", "frutta"] {
+            let tag = TagRepository::create(
+                &pool,
+                CreateTag {
+                    name: name.to_string(),
+                    display_name: name.to_string(),
+                    category: TagCategory::Ingredient,
+                    weekly_suggestion: None,
+                    parent_tag_id: None,
+                },
+            )
+            .await
+            .unwrap();
+            tag_ids.insert(name, tag.id);
+        }
+        MealOptionRepository::set_tags(&pool, bread_id, vec![tag_ids["pane"]])
+            .await
+            .unwrap();
+        MealOptionRepository::set_tags(&pool, jam.id, vec![tag_ids["frutta"]])
+            .await
+            .unwrap();
+
+        let entry = MealEntryRepository::create(
+            &pool,
+            CreateMealEntry {
+                meal_option_id: bread_id,
+                date: NaiveDate::from_ymd_opt(2024, 11, 4).unwrap(),
+                slot_type: SlotType::BREAKFAST,
+                location: LocationType::Home,
+                servings: None,
+                notes: None,
+                status: Some(EntryStatus::Eaten),
+                extra_options: vec![SelectedOption {
+                    meal_option_id: jam.id,
+                    servings: Some(0.5),
+                }],
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            entry.options,
+            vec![
+                MealEntryOption {
+                    meal_option_id: bread_id,
+                    option_name: "Test Option".to_string(),
+                    servings: 1.0,
+                },
+                MealEntryOption {
+                    meal_option_id: jam.id,
+                    option_name: "Marmellata".to_string(),
+                    servings: 0.5,
+                },
+            ]
+        );
+        assert_eq!(
+            entry.tag_names,
+            vec!["frutta".to_string(), "pane".to_string()]
+        );
+
+        // Both options count as used, and the entry is found through either
+        for option_id in [bread_id, jam.id] {
+            let usage = MealEntryRepository::get_weekly_usage(&pool, option_id, "2024-45")
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(usage.usage_count, 1);
+
+            let entries = MealEntryRepository::get_by_meal_option(&pool, option_id)
+                .await
+                .unwrap();
+            assert_eq!(entries.len(), 1);
+        }
+
+        // The template is used once, not once per option
+        let template_usage =
+            MealEntryRepository::get_weekly_template_usage(&pool, bread.template_id, "2024-45")
+                .await
+                .unwrap()
+                .unwrap();
+        assert_eq!(template_usage.usage_count, 1);
+
+        // Extras must come from the same template
+        let other_template_option = create_test_option(&pool).await;
+        let result = MealEntryRepository::create(
+            &pool,
+            CreateMealEntry {
+                meal_option_id: bread_id,
+                date: NaiveDate::from_ymd_opt(2024, 11, 5).unwrap(),
+                slot_type: SlotType::BREAKFAST,
+                location: LocationType::Home,
+                servings: None,
+                notes: None,
+                status: None,
+                extra_options: vec![SelectedOption {
+                    meal_option_id: other_template_option,
+                    servings: None,
+                }],
+            },
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
     #[tokio::test]
     async fn test_update_entry() {
         let (pool, _temp_dir) = setup_test_db().await;
@@ -615,20 +1678,21 @@ mod tests {
         let entry = CreateMealEntry {
             meal_option_id: option_id,
             date: NaiveDate::from_ymd_opt(2024, 11, 5).unwrap(),
-            slot_type: SlotType::Breakfast,
+            slot_type: SlotType::BREAKFAST,
             location: LocationType::Home,
             servings: Some(1.0),
             notes: Some("Original notes".to_string()),
-            completed: Some(false),
+            status: Some(EntryStatus::Planned),
+            extra_options: vec![],
         };
         let created = MealEntryRepository::create(&pool, entry).await.unwrap();
 
-        // Update servings and mark as completed
+        // Update servings and mark as eaten
         let update = UpdateMealEntry {
             location: Some(LocationType::Office),
             servings: Some(1.5),
             notes: None,
-            completed: Some(true),
+            status: Some(EntryStatus::Eaten),
         };
         let updated = MealEntryRepository::update(&pool, created.id, update)
             .await
@@ -637,14 +1701,14 @@ mod tests {
         assert_eq!(updated.location, LocationType::Office);
         assert_eq!(updated.servings, 1.5);
         assert_eq!(updated.notes, Some("Original notes".to_string()));
-        assert!(updated.completed);
+        assert_eq!(updated.status, EntryStatus::Eaten);
 
         // Clear notes
         let update = UpdateMealEntry {
             location: None,
             servings: None,
             notes: Some(None),
-            completed: None,
+            status: None,
         };
         let updated = MealEntryRepository::update(&pool, created.id, update)
             .await
@@ -652,6 +1716,151 @@ mod tests {
         assert_eq!(updated.notes, None);
     }
 
+    #[tokio::test]
+    async fn test_log_actual_keeps_plan() {
+        let (pool, _temp_dir) = setup_test_db().await;
+        let option_id = create_test_option(&pool).await;
+        let template_id = MealOptionRepository::get_by_id(&pool, option_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .template_id;
+        let swap = MealOptionRepository::create(
+            &pool,
+            CreateMealOption {
+                template_id,
+                name: "Swapped Option".to_string(),
+                description: None,
+                nutritional_notes: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let entry = CreateMealEntry {
+            meal_option_id: option_id,
+            date: NaiveDate::from_ymd_opt(2024, 11, 5).unwrap(),
+            slot_type: SlotType::LUNCH,
+            location: LocationType::Home,
+            servings: None,
+            notes: None,
+            status: None,
+            extra_options: vec![],
+        };
+        let created = MealEntryRepository::create(&pool, entry).await.unwrap();
+        assert!(!created.is_swap());
+
+        let actual = LogActualMeal {
+            meal_option_id: swap.id,
+            servings: Some(2.0),
+            extra_options: vec![],
+            location: Some(LocationType::Office),
+            notes: Some(Some("Canteen was out of pasta".to_string())),
+        };
+        let logged = MealEntryRepository::log_actual(&pool, created.id, actual)
+            .await
+            .unwrap();
+
+        assert_eq!(logged.status, EntryStatus::Eaten);
+        assert_eq!(logged.meal_option_id, Some(swap.id));
+        assert_eq!(logged.servings, 2.0);
+        assert_eq!(logged.location, LocationType::Office);
+        assert_eq!(logged.options[0].option_name, "Swapped Option");
+        assert_eq!(logged.planned_options.len(), 1);
+        assert_eq!(logged.planned_options[0].meal_option_id, option_id);
+        assert!(logged.is_swap());
+        assert!(!logged.eaten_as_planned());
+    }
+
+    #[tokio::test]
+    async fn test_off_plan_entry() {
+        let (pool, _temp_dir) = setup_test_db().await;
+        let fish = TagRepository::create(
+            &pool,
+            CreateTag {
+                name: "pesce".to_string(),
+                display_name: "Pesce".to_string(),
+                category: TagCategory::Ingredient,
+                weekly_suggestion: Some(2),
+                parent_tag_id: None,
+            },
+        )
+        .await
+        .unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 11, 5).unwrap();
+
+        let entry = CreateOffPlanEntry {
+            date,
+            slot_type: SlotType::LUNCH,
+            location: LocationType::Restaurant,
+            description: "  Business lunch, grilled fish  ".to_string(),
+            servings: None,
+            notes: None,
+            status: Some(EntryStatus::Planned),
+            tag_ids: vec![fish.id],
+            estimated_nutrients: Some(SetNutrientProfile {
+                kcal: 850.0,
+                protein_g: 50.0,
+                carbs_g: 60.0,
+                fat_g: 35.0,
+                fiber_g: 5.0,
+            }),
+        };
+        let created = MealEntryRepository::create_off_plan(&pool, entry)
+            .await
+            .unwrap();
+
+        assert!(created.is_off_plan());
+        assert_eq!(created.meal_option_id, None);
+        assert_eq!(created.template_id, None);
+        assert_eq!(created.option_name, "Business lunch, grilled fish");
+        assert_eq!(created.tag_names, vec!["pesce".to_string()]);
+        assert!(created.options.is_empty());
+        assert_eq!(created.estimated_nutrients.as_ref().unwrap().kcal, 850.0);
+
+        // Marking it eaten keeps the estimated tags, which then count as usage
+        let update = UpdateMealEntry {
+            location: None,
+            servings: None,
+            notes: None,
+            status: Some(EntryStatus::Eaten),
+        };
+        let eaten = MealEntryRepository::update(&pool, created.id, update)
+            .await
+            .unwrap();
+        assert_eq!(eaten.tag_names, vec!["pesce".to_string()]);
+        assert!(!eaten.eaten_as_planned());
+
+        let week = MealEntry::week_key(date);
+        let usage = MealEntryRepository::get_weekly_tag_usage(&pool, fish.id, &week)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(usage.usage_count, 1);
+
+        // No option to reselect
+        let recent = MealEntryRepository::get_recent_entries(&pool, 10)
+            .await
+            .unwrap();
+        assert!(recent.is_empty());
+
+        // Unknown tag
+        let entry = CreateOffPlanEntry {
+            date,
+            slot_type: SlotType::DINNER,
+            location: LocationType::Home,
+            description: "Leftovers".to_string(),
+            servings: None,
+            notes: None,
+            status: None,
+            tag_ids: vec![99999],
+            estimated_nutrients: None,
+        };
+        assert!(MealEntryRepository::create_off_plan(&pool, entry)
+            .await
+            .is_err());
+    }
+
     #[tokio::test]
     async fn test_delete_entry() {
         let (pool, _temp_dir) = setup_test_db().await;
@@ -660,11 +1869,12 @@ mod tests {
         let entry = CreateMealEntry {
             meal_option_id: option_id,
             date: NaiveDate::from_ymd_opt(2024, 11, 5).unwrap(),
-            slot_type: SlotType::Breakfast,
+            slot_type: SlotType::BREAKFAST,
             location: LocationType::Home,
             servings: None,
             notes: None,
-            completed: None,
+            status: None,
+            extra_options: vec![],
         };
         let created = MealEntryRepository::create(&pool, entry).await.unwrap();
 
@@ -680,6 +1890,82 @@ mod tests {
         assert!(retrieved.is_none());
     }
 
+    #[tokio::test]
+    async fn test_get_last_eaten_dates() {
+        let (pool, _temp_dir) = setup_test_db().await;
+        let option_id = create_test_option(&pool).await;
+
+        for (day, status) in [
+            (4, EntryStatus::Eaten),
+            (6, EntryStatus::Eaten),
+            (8, EntryStatus::Planned),
+            (12, EntryStatus::Eaten),
+        ] {
+            let entry = CreateMealEntry {
+                meal_option_id: option_id,
+                date: NaiveDate::from_ymd_opt(2024, 11, day).unwrap(),
+                slot_type: SlotType::LUNCH,
+                location: LocationType::Home,
+                servings: None,
+                notes: None,
+                status: Some(status),
+                extra_options: vec![],
+            };
+            MealEntryRepository::create(&pool, entry).await.unwrap();
+        }
+
+        // Planned entries and entries after the date don't count
+        let dates = MealEntryRepository::get_last_eaten_dates(
+            &pool,
+            NaiveDate::from_ymd_opt(2024, 11, 10).unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            dates,
+            vec![(option_id, NaiveDate::from_ymd_opt(2024, 11, 6).unwrap())]
+        );
+
+        let dates = MealEntryRepository::get_last_eaten_dates(
+            &pool,
+            NaiveDate::from_ymd_opt(2024, 11, 1).unwrap(),
+        )
+        .await
+        .unwrap();
+        assert!(dates.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_delete_by_date_and_slot() {
+        let (pool, _temp_dir) = setup_test_db().await;
+        let option_id = create_test_option(&pool).await;
+        let date = NaiveDate::from_ymd_opt(2024, 11, 5).unwrap();
+
+        for slot in [SlotType::BREAKFAST, SlotType::BREAKFAST, SlotType::LUNCH] {
+            let entry = CreateMealEntry {
+                meal_option_id: option_id,
+                date,
+                slot_type: slot,
+                location: LocationType::Home,
+                servings: None,
+                notes: None,
+                status: None,
+                extra_options: vec![],
+            };
+            MealEntryRepository::create(&pool, entry).await.unwrap();
+        }
+
+        let deleted =
+            MealEntryRepository::delete_by_date_and_slot(&pool, date, &SlotType::BREAKFAST)
+                .await
+                .unwrap();
+        assert_eq!(deleted, 2);
+
+        let remaining = MealEntryRepository::get_by_date(&pool, date).await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].slot_type, SlotType::LUNCH);
+    }
+
     #[tokio::test]
     async fn test_validation_error() {
         let (pool, _temp_dir) = setup_test_db().await;
@@ -689,11 +1975,12 @@ mod tests {
         let entry = CreateMealEntry {
             meal_option_id: option_id,
             date: NaiveDate::from_ymd_opt(2024, 11, 5).unwrap(),
-            slot_type: SlotType::Breakfast,
+            slot_type: SlotType::BREAKFAST,
             location: LocationType::Home,
             servings: Some(0.0),
             notes: None,
-            completed: None,
+            status: None,
+            extra_options: vec![],
         };
 
         let result = MealEntryRepository::create(&pool, entry).await;
@@ -707,11 +1994,12 @@ mod tests {
         let entry = CreateMealEntry {
             meal_option_id: 99999, // Non-existent option
             date: NaiveDate::from_ymd_opt(2024, 11, 5).unwrap(),
-            slot_type: SlotType::Breakfast,
+            slot_type: SlotType::BREAKFAST,
             location: LocationType::Home,
             servings: None,
             notes: None,
-            completed: None,
+            status: None,
+            extra_options: vec![],
         };
 
         let result = MealEntryRepository::create(&pool, entry).await;
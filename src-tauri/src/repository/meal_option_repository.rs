@@ -1,4 +1,8 @@
-use crate::models::{CreateMealOption, MealOption, MealOptionWithTags, UpdateMealOption};
+use crate::models::{
+    CreateMealOption, DeleteBlocked, DeleteBlocker, LibraryItemKind, MealOption,
+    MealOptionWithTags, NutrientProfile, SetNutrientProfile, SyncEntity, UpdateMealOption,
+};
+use crate::repository::AuditScope;
 use sqlx::{Result, Row, SqlitePool};
 
 pub struct MealOptionRepository;
@@ -11,6 +15,8 @@ impl MealOptionRepository {
         let name = row.try_get("name")?;
         let description: Option<String> = row.try_get("description")?;
         let nutritional_notes: Option<String> = row.try_get("nutritional_notes")?;
+        let option_group: Option<String> = row.try_get("option_group")?;
+        let archived = row.try_get("archived")?;
         let created_at = row.try_get("created_at")?;
         let updated_at = row.try_get("updated_at")?;
 
@@ -20,6 +26,8 @@ impl MealOptionRepository {
             name,
             description,
             nutritional_notes,
+            option_group,
+            archived,
             created_at,
             updated_at,
         })
@@ -44,6 +52,7 @@ impl MealOptionRepository {
             )));
         }
 
+        let mut tx = pool.begin().await?;
         let result = sqlx::query(
             "INSERT INTO meal_options (template_id, name, description, nutritional_notes) 
              VALUES (?, ?, ?, ?)",
@@ -52,19 +61,24 @@ impl MealOptionRepository {
         .bind(&option.name)
         .bind(&option.description)
         .bind(&option.nutritional_notes)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?;
 
         let id = result.last_insert_rowid();
+        let mut audit = AuditScope::new("create_option");
+        audit.created(SyncEntity::Option, id);
+        audit.record(&mut tx).await?;
+        tx.commit().await?;
+
         Self::get_by_id(pool, id)
             .await?
-            .ok_or_else(|| sqlx::Error::RowNotFound)
+            .ok_or(sqlx::Error::RowNotFound)
     }
 
     /// Get a meal option by ID
     pub async fn get_by_id(pool: &SqlitePool, id: i64) -> Result<Option<MealOption>> {
         let row = sqlx::query(
-            "SELECT id, template_id, name, description, nutritional_notes, 
+            "SELECT id, template_id, name, description, nutritional_notes, option_group, archived,
                     created_at, updated_at
              FROM meal_options 
              WHERE id = ?",
@@ -79,12 +93,43 @@ impl MealOptionRepository {
         }
     }
 
-    /// Get all meal options
+    /// Get all meal options, except archived ones and those of archived templates
     pub async fn get_all(pool: &SqlitePool) -> Result<Vec<MealOption>> {
         let rows = sqlx::query(
-            "SELECT id, template_id, name, description, nutritional_notes, 
+            "SELECT id, template_id, name, description, nutritional_notes, option_group, archived,
                     created_at, updated_at
-             FROM meal_options 
+             FROM meal_options
+             WHERE archived = 0
+               AND template_id NOT IN (SELECT id FROM meal_templates WHERE archived = 1)
+             ORDER BY name",
+        )
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter().map(Self::row_to_option).collect()
+    }
+
+    /// Get every meal option, archived ones included (export)
+    pub async fn get_all_including_archived(pool: &SqlitePool) -> Result<Vec<MealOption>> {
+        let rows = sqlx::query(
+            "SELECT id, template_id, name, description, nutritional_notes, option_group, archived,
+                    created_at, updated_at
+             FROM meal_options
+             ORDER BY name",
+        )
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter().map(Self::row_to_option).collect()
+    }
+
+    /// Get the archived meal options
+    pub async fn get_archived(pool: &SqlitePool) -> Result<Vec<MealOption>> {
+        let rows = sqlx::query(
+            "SELECT id, template_id, name, description, nutritional_notes, option_group, archived,
+                    created_at, updated_at
+             FROM meal_options
+             WHERE archived = 1
              ORDER BY name",
         )
         .fetch_all(pool)
@@ -93,16 +138,16 @@ impl MealOptionRepository {
         rows.iter().map(Self::row_to_option).collect()
     }
 
-    /// Get all meal options for a specific template
+    /// Get the meal options of a specific template, archived ones excluded
     pub async fn get_by_template_id(
         pool: &SqlitePool,
         template_id: i64,
     ) -> Result<Vec<MealOption>> {
         let rows = sqlx::query(
-            "SELECT id, template_id, name, description, nutritional_notes, 
+            "SELECT id, template_id, name, description, nutritional_notes, option_group, archived,
                     created_at, updated_at
              FROM meal_options 
-             WHERE template_id = ?
+             WHERE template_id = ? AND archived = 0
              ORDER BY name",
         )
         .bind(template_id)
@@ -158,6 +203,15 @@ impl MealOptionRepository {
         Ok(rows.iter().map(|row| row.get("tag_id")).collect())
     }
 
+    /// Get every (meal_option_id, tag_id) link
+    pub async fn get_all_tag_links(pool: &SqlitePool) -> Result<Vec<(i64, i64)>> {
+        sqlx::query_as::<_, (i64, i64)>(
+            "SELECT meal_option_id, tag_id FROM meal_option_tags ORDER BY meal_option_id, tag_id",
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     /// Add tags to a meal option
     pub async fn add_tags(pool: &SqlitePool, option_id: i64, tag_ids: Vec<i64>) -> Result<()> {
         // Verify option exists
@@ -181,6 +235,12 @@ impl MealOptionRepository {
             }
         }
 
+        let mut tx = pool.begin().await?;
+        let mut audit = AuditScope::new("add_tags_to_option");
+        audit
+            .track(&mut tx, SyncEntity::Option, [option_id])
+            .await?;
+
         // Insert tags (ignore duplicates)
         for tag_id in tag_ids {
             sqlx::query(
@@ -188,24 +248,32 @@ impl MealOptionRepository {
             )
             .bind(option_id)
             .bind(tag_id)
-            .execute(pool)
+            .execute(&mut *tx)
             .await?;
         }
 
-        Ok(())
+        audit.record(&mut tx).await?;
+        tx.commit().await
     }
 
     /// Remove tags from a meal option
     pub async fn remove_tags(pool: &SqlitePool, option_id: i64, tag_ids: Vec<i64>) -> Result<()> {
+        let mut tx = pool.begin().await?;
+        let mut audit = AuditScope::new("remove_tags_from_option");
+        audit
+            .track(&mut tx, SyncEntity::Option, [option_id])
+            .await?;
+
         for tag_id in tag_ids {
             sqlx::query("DELETE FROM meal_option_tags WHERE meal_option_id = ? AND tag_id = ?")
                 .bind(option_id)
                 .bind(tag_id)
-                .execute(pool)
+                .execute(&mut *tx)
                 .await?;
         }
 
-        Ok(())
+        audit.record(&mut tx).await?;
+        tx.commit().await
     }
 
     /// Replace all tags for a meal option
@@ -231,10 +299,16 @@ impl MealOptionRepository {
             }
         }
 
+        let mut tx = pool.begin().await?;
+        let mut audit = AuditScope::new("set_option_tags");
+        audit
+            .track(&mut tx, SyncEntity::Option, [option_id])
+            .await?;
+
         // Remove all existing tags
         sqlx::query("DELETE FROM meal_option_tags WHERE meal_option_id = ?")
             .bind(option_id)
-            .execute(pool)
+            .execute(&mut *tx)
             .await?;
 
         // Add new tags
@@ -242,22 +316,26 @@ impl MealOptionRepository {
             sqlx::query("INSERT INTO meal_option_tags (meal_option_id, tag_id) VALUES (?, ?)")
                 .bind(option_id)
                 .bind(tag_id)
-                .execute(pool)
+                .execute(&mut *tx)
                 .await?;
         }
 
-        Ok(())
+        audit.record(&mut tx).await?;
+        tx.commit().await
     }
 
     /// Search meal options by name or description
+    /// Archived options and options of archived templates are not returned
     pub async fn search(pool: &SqlitePool, query: &str) -> Result<Vec<MealOption>> {
         let search_pattern = format!("%{}%", query);
 
         let rows = sqlx::query(
-            "SELECT id, template_id, name, description, nutritional_notes, 
+            "SELECT id, template_id, name, description, nutritional_notes, option_group, archived,
                     created_at, updated_at
              FROM meal_options 
-             WHERE name LIKE ? OR description LIKE ?
+             WHERE (name LIKE ? OR description LIKE ?)
+               AND archived = 0
+               AND template_id NOT IN (SELECT id FROM meal_templates WHERE archived = 1)
              ORDER BY name",
         )
         .bind(&search_pattern)
@@ -297,7 +375,7 @@ impl MealOptionRepository {
             // No updates to make, just return the current option
             return Self::get_by_id(pool, id)
                 .await?
-                .ok_or_else(|| sqlx::Error::RowNotFound);
+                .ok_or(sqlx::Error::RowNotFound);
         }
 
         query_str.push_str(&updates.join(", "));
@@ -317,25 +395,229 @@ impl MealOptionRepository {
         }
 
         query = query.bind(id);
-        query.execute(pool).await?;
+
+        let mut tx = pool.begin().await?;
+        let mut audit = AuditScope::new("update_option");
+        audit.track(&mut tx, SyncEntity::Option, [id]).await?;
+        query.execute(&mut *tx).await?;
+        audit.record(&mut tx).await?;
+        tx.commit().await?;
+
+        Self::get_by_id(pool, id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    /// Get the per-serving nutrient profile of a meal option
+    pub async fn get_nutrients(
+        pool: &SqlitePool,
+        option_id: i64,
+    ) -> Result<Option<NutrientProfile>> {
+        sqlx::query_as::<_, NutrientProfile>(
+            "SELECT meal_option_id, kcal, protein_g, carbs_g, fat_g, fiber_g, created_at, updated_at
+             FROM meal_option_nutrients
+             WHERE meal_option_id = ?",
+        )
+        .bind(option_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Get the nutrient profiles of all meal options
+    pub async fn get_all_nutrients(pool: &SqlitePool) -> Result<Vec<NutrientProfile>> {
+        sqlx::query_as::<_, NutrientProfile>(
+            "SELECT meal_option_id, kcal, protein_g, carbs_g, fat_g, fiber_g, created_at, updated_at
+             FROM meal_option_nutrients
+             ORDER BY meal_option_id",
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Create or replace the per-serving nutrient profile of a meal option
+    pub async fn set_nutrients(
+        pool: &SqlitePool,
+        option_id: i64,
+        nutrients: SetNutrientProfile,
+    ) -> Result<NutrientProfile> {
+        nutrients.validate().map_err(sqlx::Error::Protocol)?;
+
+        // Verify option exists
+        if Self::get_by_id(pool, option_id).await?.is_none() {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        let mut tx = pool.begin().await?;
+        let mut audit = AuditScope::new("set_option_nutrients");
+        audit
+            .track(&mut tx, SyncEntity::Option, [option_id])
+            .await?;
+
+        let profile = sqlx::query_as::<_, NutrientProfile>(
+            "INSERT INTO meal_option_nutrients (meal_option_id, kcal, protein_g, carbs_g, fat_g, fiber_g)
+             VALUES (?, ?, ?, ?, ?, ?)
+             ON CONFLICT(meal_option_id) DO UPDATE SET
+                 kcal = excluded.kcal,
+                 protein_g = excluded.protein_g,
+                 carbs_g = excluded.carbs_g,
+                 fat_g = excluded.fat_g,
+                 fiber_g = excluded.fiber_g
+             RETURNING meal_option_id, kcal, protein_g, carbs_g, fat_g, fiber_g, created_at, updated_at",
+        )
+        .bind(option_id)
+        .bind(nutrients.kcal)
+        .bind(nutrients.protein_g)
+        .bind(nutrients.carbs_g)
+        .bind(nutrients.fat_g)
+        .bind(nutrients.fiber_g)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        audit.record(&mut tx).await?;
+        tx.commit().await?;
+        Ok(profile)
+    }
+
+    /// Remove the nutrient profile of a meal option
+    /// Returns false if the option had no profile
+    pub async fn delete_nutrients(pool: &SqlitePool, option_id: i64) -> Result<bool> {
+        let mut tx = pool.begin().await?;
+        let mut audit = AuditScope::new("delete_option_nutrients");
+        audit
+            .track(&mut tx, SyncEntity::Option, [option_id])
+            .await?;
+
+        let result = sqlx::query("DELETE FROM meal_option_nutrients WHERE meal_option_id = ?")
+            .bind(option_id)
+            .execute(&mut *tx)
+            .await?;
+
+        audit.record(&mut tx).await?;
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Archive or restore a meal option
+    /// Past entries keep resolving it; it just stops being offered
+    pub async fn set_archived(pool: &SqlitePool, id: i64, archived: bool) -> Result<MealOption> {
+        let mut tx = pool.begin().await?;
+        let mut audit = AuditScope::new(if archived {
+            "archive_option"
+        } else {
+            "unarchive_option"
+        });
+        audit.track(&mut tx, SyncEntity::Option, [id]).await?;
+
+        let result = sqlx::query(
+            "UPDATE meal_options SET archived = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+        )
+        .bind(archived)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        audit.record(&mut tx).await?;
+        tx.commit().await?;
+
+        Self::get_by_id(pool, id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    /// Put a meal option in one of its template's option groups (None = ungrouped)
+    /// The group must be declared on the template
+    pub async fn set_group(
+        pool: &SqlitePool,
+        id: i64,
+        option_group: Option<String>,
+    ) -> Result<MealOption> {
+        let Some(option) = Self::get_by_id(pool, id).await? else {
+            return Err(sqlx::Error::RowNotFound);
+        };
+
+        if let Some(group) = &option_group {
+            let declared: bool = sqlx::query_scalar(
+                "SELECT EXISTS(SELECT 1 FROM meal_template_option_groups
+                               WHERE template_id = ? AND name = ?)",
+            )
+            .bind(option.template_id)
+            .bind(group)
+            .fetch_one(pool)
+            .await?;
+
+            if !declared {
+                return Err(sqlx::Error::Protocol(format!(
+                    "Option group '{}' is not declared on the template",
+                    group
+                )));
+            }
+        }
+
+        let mut tx = pool.begin().await?;
+        let mut audit = AuditScope::new("set_option_group");
+        audit.track(&mut tx, SyncEntity::Option, [id]).await?;
+        sqlx::query(
+            "UPDATE meal_options SET option_group = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+        )
+        .bind(&option_group)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+        audit.record(&mut tx).await?;
+        tx.commit().await?;
 
         Self::get_by_id(pool, id)
             .await?
-            .ok_or_else(|| sqlx::Error::RowNotFound)
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    /// Explain what keeps a meal option from being deleted
+    /// Returns None when the option does not exist or no entry selected or planned it
+    pub async fn get_delete_blockers(pool: &SqlitePool, id: i64) -> Result<Option<DeleteBlocked>> {
+        let Some(option) = Self::get_by_id(pool, id).await? else {
+            return Ok(None);
+        };
+
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(DISTINCT meal_entry_id) FROM (
+                 SELECT meal_entry_id, meal_option_id FROM meal_entry_options
+                 UNION ALL
+                 SELECT meal_entry_id, meal_option_id FROM meal_entry_planned_options
+             )
+             WHERE meal_option_id = ?",
+        )
+        .bind(id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok((count > 0).then(|| DeleteBlocked {
+            kind: LibraryItemKind::Option,
+            id,
+            name: option.name,
+            blockers: vec![DeleteBlocker::MealEntries { count }],
+        }))
     }
 
     /// Delete a meal option
     pub async fn delete(pool: &SqlitePool, id: i64) -> Result<()> {
+        let mut tx = pool.begin().await?;
+        let mut audit = AuditScope::new("delete_option");
+        audit.track(&mut tx, SyncEntity::Option, [id]).await?;
+
         let result = sqlx::query("DELETE FROM meal_options WHERE id = ?")
             .bind(id)
-            .execute(pool)
+            .execute(&mut *tx)
             .await?;
 
         if result.rows_affected() == 0 {
             return Err(sqlx::Error::RowNotFound);
         }
 
-        Ok(())
+        audit.record(&mut tx).await?;
+        tx.commit().await
     }
 }
 
@@ -343,7 +625,9 @@ impl MealOptionRepository {
 mod tests {
     use super::*;
     use crate::db;
-    use crate::models::{CreateMealTemplate, CreateTag, LocationType, SlotType, TagCategory};
+    use crate::models::{
+        CreateMealTemplate, CreateTag, EntryStatus, LocationType, SlotType, TagCategory,
+    };
     use crate::repository::{MealTemplateRepository, TagRepository};
     use std::path::PathBuf;
     use tempfile::TempDir;
@@ -360,7 +644,7 @@ mod tests {
             name: "Test Template".to_string(),
             description: Some("Test Description".to_string()),
             location_type: LocationType::Home,
-            compatible_slots: vec![SlotType::Breakfast],
+            compatible_slots: vec![SlotType::BREAKFAST],
             weekly_limit: None,
         };
 
@@ -679,4 +963,236 @@ mod tests {
         let result = MealOptionRepository::create(&pool, option).await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_set_and_get_nutrients() {
+        let (pool, _temp_dir) = setup_test_db().await;
+        let template_id = create_test_template(&pool).await;
+
+        let option = CreateMealOption {
+            template_id,
+            name: "Ricotta".to_string(),
+            description: None,
+            nutritional_notes: None,
+        };
+        let created = MealOptionRepository::create(&pool, option).await.unwrap();
+
+        // No profile yet
+        let nutrients = MealOptionRepository::get_nutrients(&pool, created.id)
+            .await
+            .unwrap();
+        assert!(nutrients.is_none());
+
+        let profile = MealOptionRepository::set_nutrients(
+            &pool,
+            created.id,
+            SetNutrientProfile {
+                kcal: 146.0,
+                protein_g: 11.0,
+                carbs_g: 3.5,
+                fat_g: 10.0,
+                fiber_g: 0.0,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(profile.meal_option_id, created.id);
+        assert_eq!(profile.kcal, 146.0);
+
+        // Setting again replaces the existing profile
+        MealOptionRepository::set_nutrients(
+            &pool,
+            created.id,
+            SetNutrientProfile {
+                kcal: 120.0,
+                protein_g: 12.0,
+                carbs_g: 3.0,
+                fat_g: 7.0,
+                fiber_g: 0.0,
+            },
+        )
+        .await
+        .unwrap();
+
+        let fetched = MealOptionRepository::get_nutrients(&pool, created.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(fetched.kcal, 120.0);
+        assert_eq!(fetched.fat_g, 7.0);
+
+        // Delete the profile
+        assert!(MealOptionRepository::delete_nutrients(&pool, created.id)
+            .await
+            .unwrap());
+        assert!(!MealOptionRepository::delete_nutrients(&pool, created.id)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_set_nutrients_invalid() {
+        let (pool, _temp_dir) = setup_test_db().await;
+
+        // Non-existent option
+        let result = MealOptionRepository::set_nutrients(
+            &pool,
+            99999,
+            SetNutrientProfile {
+                kcal: 100.0,
+                protein_g: 1.0,
+                carbs_g: 1.0,
+                fat_g: 1.0,
+                fiber_g: 1.0,
+            },
+        )
+        .await;
+        assert!(result.is_err());
+
+        // Negative values
+        let template_id = create_test_template(&pool).await;
+        let option = CreateMealOption {
+            template_id,
+            name: "Test".to_string(),
+            description: None,
+            nutritional_notes: None,
+        };
+        let created = MealOptionRepository::create(&pool, option).await.unwrap();
+
+        let result = MealOptionRepository::set_nutrients(
+            &pool,
+            created.id,
+            SetNutrientProfile {
+                kcal: -5.0,
+                protein_g: 1.0,
+                carbs_g: 1.0,
+                fat_g: 1.0,
+                fiber_g: 1.0,
+            },
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_archive_option() {
+        let (pool, _temp_dir) = setup_test_db().await;
+        let template_id = create_test_template(&pool).await;
+
+        let option = CreateMealOption {
+            template_id,
+            name: "Ricotta".to_string(),
+            description: None,
+            nutritional_notes: None,
+        };
+        let created = MealOptionRepository::create(&pool, option).await.unwrap();
+        assert!(!created.archived);
+
+        let archived = MealOptionRepository::set_archived(&pool, created.id, true)
+            .await
+            .unwrap();
+        assert!(archived.archived);
+
+        // Hidden from listings and search, still resolvable by ID
+        assert!(MealOptionRepository::get_all(&pool)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(MealOptionRepository::search(&pool, "Ricotta")
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(MealOptionRepository::get_by_template_id(&pool, template_id)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(MealOptionRepository::get_by_id(&pool, created.id)
+            .await
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            MealOptionRepository::get_archived(&pool)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        MealOptionRepository::set_archived(&pool, created.id, false)
+            .await
+            .unwrap();
+        assert_eq!(MealOptionRepository::get_all(&pool).await.unwrap().len(), 1);
+
+        // Archiving the template hides its options too
+        MealTemplateRepository::set_archived(&pool, template_id, true)
+            .await
+            .unwrap();
+        assert!(MealOptionRepository::get_all(&pool)
+            .await
+            .unwrap()
+            .is_empty());
+
+        assert!(MealOptionRepository::set_archived(&pool, 99999, true)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_delete_blockers() {
+        use crate::models::CreateMealEntry;
+        use crate::repository::MealEntryRepository;
+
+        let (pool, _temp_dir) = setup_test_db().await;
+        let template_id = create_test_template(&pool).await;
+
+        let option = CreateMealOption {
+            template_id,
+            name: "Ricotta".to_string(),
+            description: None,
+            nutritional_notes: None,
+        };
+        let created = MealOptionRepository::create(&pool, option).await.unwrap();
+
+        assert!(MealOptionRepository::get_delete_blockers(&pool, created.id)
+            .await
+            .unwrap()
+            .is_none());
+
+        MealEntryRepository::create(
+            &pool,
+            CreateMealEntry {
+                meal_option_id: created.id,
+                date: chrono::NaiveDate::from_ymd_opt(2025, 11, 3).unwrap(),
+                slot_type: SlotType::BREAKFAST,
+                location: LocationType::Home,
+                servings: None,
+                notes: None,
+                status: Some(EntryStatus::Eaten),
+                extra_options: vec![],
+            },
+        )
+        .await
+        .unwrap();
+
+        let blocked = MealOptionRepository::get_delete_blockers(&pool, created.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(blocked.kind, LibraryItemKind::Option);
+        assert_eq!(blocked.name, "Ricotta");
+        assert_eq!(
+            blocked.blockers,
+            vec![DeleteBlocker::MealEntries { count: 1 }]
+        );
+
+        let template_blocked = MealTemplateRepository::get_delete_blockers(&pool, template_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(template_blocked.kind, LibraryItemKind::Template);
+        assert_eq!(
+            template_blocked.blockers,
+            vec![DeleteBlocker::MealEntries { count: 1 }]
+        );
+    }
 }
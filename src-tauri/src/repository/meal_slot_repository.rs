@@ -0,0 +1,404 @@
+use crate::models::{CreateMealSlot, MealSlot, SlotType, SyncEntity, UpdateMealSlot};
+use crate::repository::AuditScope;
+use sqlx::{Result, Row, SqlitePool};
+
+pub struct MealSlotRepository;
+
+impl MealSlotRepository {
+    /// Helper to map a row to a MealSlot
+    fn row_to_slot(row: &sqlx::sqlite::SqliteRow) -> Result<MealSlot> {
+        let name: String = row.try_get("name")?;
+        let name = SlotType::from_db_string(&name).map_err(sqlx::Error::Protocol)?;
+
+        Ok(MealSlot {
+            id: row.try_get("id")?,
+            name,
+            display_name: row.try_get("display_name")?,
+            sort_order: row.try_get("sort_order")?,
+            active: row.try_get("active")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+
+    /// Create a new meal slot
+    pub async fn create(pool: &SqlitePool, slot: CreateMealSlot) -> Result<MealSlot> {
+        slot.validate().map_err(sqlx::Error::Protocol)?;
+
+        let mut tx = pool.begin().await?;
+        let row = sqlx::query(
+            r#"
+            INSERT INTO meal_slots (name, display_name, sort_order, active)
+            VALUES (?1, ?2, ?3, ?4)
+            RETURNING id, name, display_name, sort_order, active, created_at, updated_at
+            "#,
+        )
+        .bind(&slot.name)
+        .bind(&slot.display_name)
+        .bind(slot.sort_order)
+        .bind(slot.active.unwrap_or(true))
+        .fetch_one(&mut *tx)
+        .await?;
+        let created = Self::row_to_slot(&row)?;
+
+        let mut audit = AuditScope::new("create_meal_slot");
+        audit.created(SyncEntity::Slot, created.id);
+        audit.record(&mut tx).await?;
+        tx.commit().await?;
+
+        Ok(created)
+    }
+
+    /// Get a meal slot by ID
+    pub async fn get_by_id(pool: &SqlitePool, id: i64) -> Result<Option<MealSlot>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, name, display_name, sort_order, active, created_at, updated_at
+            FROM meal_slots
+            WHERE id = ?1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+        match row {
+            Some(r) => Ok(Some(Self::row_to_slot(&r)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Get a meal slot by name
+    pub async fn get_by_name(pool: &SqlitePool, name: &str) -> Result<Option<MealSlot>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, name, display_name, sort_order, active, created_at, updated_at
+            FROM meal_slots
+            WHERE name = ?1
+            "#,
+        )
+        .bind(name)
+        .fetch_optional(pool)
+        .await?;
+
+        match row {
+            Some(r) => Ok(Some(Self::row_to_slot(&r)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Get all meal slots in day order, inactive ones included
+    pub async fn get_all(pool: &SqlitePool) -> Result<Vec<MealSlot>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, name, display_name, sort_order, active, created_at, updated_at
+            FROM meal_slots
+            ORDER BY sort_order, id
+            "#,
+        )
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter().map(Self::row_to_slot).collect()
+    }
+
+    /// Get the active meal slots in day order
+    pub async fn get_active(pool: &SqlitePool) -> Result<Vec<MealSlot>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, name, display_name, sort_order, active, created_at, updated_at
+            FROM meal_slots
+            WHERE active = 1
+            ORDER BY sort_order, id
+            "#,
+        )
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter().map(Self::row_to_slot).collect()
+    }
+
+    /// Update a meal slot
+    /// The name is the key referenced by entries and templates and cannot change
+    pub async fn update(pool: &SqlitePool, id: i64, update: UpdateMealSlot) -> Result<MealSlot> {
+        update.validate().map_err(sqlx::Error::Protocol)?;
+
+        let existing = Self::get_by_id(pool, id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        let display_name = update.display_name.unwrap_or(existing.display_name);
+        let sort_order = update.sort_order.unwrap_or(existing.sort_order);
+        let active = update.active.unwrap_or(existing.active);
+
+        let mut tx = pool.begin().await?;
+        let mut audit = AuditScope::new("update_meal_slot");
+        audit.track(&mut tx, SyncEntity::Slot, [id]).await?;
+
+        let row = sqlx::query(
+            r#"
+            UPDATE meal_slots
+            SET display_name = ?1, sort_order = ?2, active = ?3
+            WHERE id = ?4
+            RETURNING id, name, display_name, sort_order, active, created_at, updated_at
+            "#,
+        )
+        .bind(&display_name)
+        .bind(sort_order)
+        .bind(active)
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        audit.record(&mut tx).await?;
+        tx.commit().await?;
+        Self::row_to_slot(&row)
+    }
+
+    /// Delete a meal slot
+    /// Slots still used by entries or templates are kept; deactivate them instead
+    pub async fn delete(pool: &SqlitePool, id: i64) -> Result<bool> {
+        let Some(slot) = Self::get_by_id(pool, id).await? else {
+            return Ok(false);
+        };
+
+        let (entries, templates): (i64, i64) = sqlx::query_as(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM meal_entries WHERE slot_type = ?1),
+                (SELECT COUNT(*) FROM meal_template_slots WHERE slot_type = ?1)
+            "#,
+        )
+        .bind(slot.name.to_db_string())
+        .fetch_one(pool)
+        .await?;
+
+        if entries > 0 || templates > 0 {
+            return Err(sqlx::Error::Protocol(format!(
+                "Meal slot '{}' is used by {} entries and {} templates; deactivate it instead",
+                slot.name, entries, templates
+            )));
+        }
+
+        let mut tx = pool.begin().await?;
+        let mut audit = AuditScope::new("delete_meal_slot");
+        audit.track(&mut tx, SyncEntity::Slot, [id]).await?;
+
+        let result = sqlx::query("DELETE FROM meal_slots WHERE id = ?1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        audit.record(&mut tx).await?;
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        CreateMealEntry, CreateMealOption, CreateMealTemplate, EntryStatus, LocationType,
+        UpdateMealTemplate,
+    };
+    use crate::repository::{MealEntryRepository, MealOptionRepository, MealTemplateRepository};
+    use chrono::NaiveDate;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_db() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect(":memory:")
+            .await
+            .expect("Failed to create in-memory database");
+
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
+
+        pool
+    }
+
+    fn pre_workout() -> CreateMealSlot {
+        CreateMealSlot {
+            name: "pre_workout".to_string(),
+            display_name: "Pre-workout".to_string(),
+            sort_order: 35,
+            active: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_slots_are_seeded() {
+        let pool = setup_test_db().await;
+
+        let slots = MealSlotRepository::get_all(&pool).await.unwrap();
+        let names: Vec<SlotType> = slots.iter().map(|s| s.name.clone()).collect();
+
+        assert_eq!(names, SlotType::defaults().to_vec());
+        assert!(slots.iter().all(|s| s.active));
+        assert_eq!(slots[0].display_name, "Breakfast");
+    }
+
+    #[tokio::test]
+    async fn test_create_slot_in_day_order() {
+        let pool = setup_test_db().await;
+
+        let slot = MealSlotRepository::create(&pool, pre_workout())
+            .await
+            .unwrap();
+        assert_eq!(slot.name.to_db_string(), "pre_workout");
+        assert!(slot.active);
+
+        // Sorted between lunch (30) and afternoon snack (40)
+        let slots = MealSlotRepository::get_active(&pool).await.unwrap();
+        let names: Vec<&str> = slots.iter().map(|s| s.name.to_db_string()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "breakfast",
+                "morning_snack",
+                "lunch",
+                "pre_workout",
+                "afternoon_snack",
+                "dinner"
+            ]
+        );
+
+        // Names are unique
+        assert!(MealSlotRepository::create(&pool, pre_workout())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_deactivate_slot() {
+        let pool = setup_test_db().await;
+
+        let snack = MealSlotRepository::get_by_name(&pool, "morning_snack")
+            .await
+            .unwrap()
+            .unwrap();
+        let updated = MealSlotRepository::update(
+            &pool,
+            snack.id,
+            UpdateMealSlot {
+                display_name: Some("Mid-morning".to_string()),
+                sort_order: None,
+                active: Some(false),
+            },
+        )
+        .await
+        .unwrap();
+
+        assert!(!updated.active);
+        assert_eq!(updated.display_name, "Mid-morning");
+        assert_eq!(updated.sort_order, snack.sort_order);
+
+        let active = MealSlotRepository::get_active(&pool).await.unwrap();
+        assert_eq!(active.len(), 4);
+        assert!(active.iter().all(|s| s.name != SlotType::MORNING_SNACK));
+        assert_eq!(MealSlotRepository::get_all(&pool).await.unwrap().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_delete_slot_in_use() {
+        let pool = setup_test_db().await;
+        let slot = MealSlotRepository::create(&pool, pre_workout())
+            .await
+            .unwrap();
+
+        let template = MealTemplateRepository::create(
+            &pool,
+            CreateMealTemplate {
+                name: "Banana".to_string(),
+                description: None,
+                compatible_slots: vec![slot.name.clone()],
+                location_type: LocationType::Any,
+                weekly_limit: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        // Referenced by a template
+        assert!(MealSlotRepository::delete(&pool, slot.id).await.is_err());
+
+        let option = MealOptionRepository::create(
+            &pool,
+            CreateMealOption {
+                template_id: template.id,
+                name: "Banana".to_string(),
+                description: None,
+                nutritional_notes: None,
+            },
+        )
+        .await
+        .unwrap();
+        let entry = MealEntryRepository::create(
+            &pool,
+            CreateMealEntry {
+                meal_option_id: option.id,
+                date: NaiveDate::from_ymd_opt(2024, 11, 4).unwrap(),
+                slot_type: slot.name.clone(),
+                location: LocationType::Home,
+                servings: None,
+                notes: None,
+                status: Some(EntryStatus::Eaten),
+                extra_options: vec![],
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(entry.slot_type, slot.name);
+
+        MealTemplateRepository::update(
+            &pool,
+            template.id,
+            UpdateMealTemplate {
+                name: None,
+                description: None,
+                compatible_slots: Some(vec![SlotType::LUNCH]),
+                location_type: None,
+                weekly_limit: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        // Still referenced by an entry
+        assert!(MealSlotRepository::delete(&pool, slot.id).await.is_err());
+
+        MealEntryRepository::delete(&pool, entry.id).await.unwrap();
+        assert!(MealSlotRepository::delete(&pool, slot.id).await.unwrap());
+        assert!(!MealSlotRepository::delete(&pool, slot.id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_slots_are_rejected() {
+        let pool = setup_test_db().await;
+
+        let result = MealTemplateRepository::create(
+            &pool,
+            CreateMealTemplate {
+                name: "Shake".to_string(),
+                description: None,
+                compatible_slots: vec![SlotType::from_db_string("pre_workout").unwrap()],
+                location_type: LocationType::Any,
+                weekly_limit: None,
+            },
+        )
+        .await;
+        assert!(result.is_err());
+
+        let result = sqlx::query(
+            "INSERT INTO meal_entries (meal_option_id, date, iso_week, slot_type, location)
+             VALUES (1, '2024-11-04', '2024-45', 'pre_workout', 'home')",
+        )
+        .execute(&pool)
+        .await;
+        assert!(result.is_err());
+    }
+}
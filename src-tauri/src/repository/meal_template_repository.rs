@@ -1,9 +1,24 @@
-use crate::models::{CreateMealTemplate, LocationType, MealTemplate, SlotType, UpdateMealTemplate};
-use sqlx::{Result, Row, SqlitePool};
+use crate::models::{
+    CreateMealTemplate, DeleteBlocked, DeleteBlocker, LibraryItemKind, LocationType, MealTemplate,
+    OptionGroup, SlotType, SyncEntity, UpdateMealTemplate,
+};
+use crate::repository::AuditScope;
+use sqlx::{Result, Row, SqliteConnection, SqlitePool};
 
 pub struct MealTemplateRepository;
 
 impl MealTemplateRepository {
+    /// Columns selected for a MealTemplate from `meal_templates mt`
+    /// Compatible slots are aggregated from meal_template_slots in day order
+    const COLUMNS: &'static str = r#"
+        mt.id, mt.name, mt.description, mt.location_type, mt.weekly_limit, mt.archived,
+        mt.created_at, mt.updated_at,
+        (SELECT GROUP_CONCAT(mts.slot_type, ',' ORDER BY ms.sort_order, ms.id)
+         FROM meal_template_slots mts
+         JOIN meal_slots ms ON ms.name = mts.slot_type
+         WHERE mts.template_id = mt.id) AS compatible_slots
+    "#;
+
     /// Helper to map a row to MealTemplate
     fn row_to_template(row: &sqlx::sqlite::SqliteRow) -> Result<MealTemplate> {
         let location_str: String = row.try_get("location_type")?;
@@ -14,14 +29,15 @@ impl MealTemplateRepository {
             )))
         })?;
 
-        let compatible_slots_json: String = row.try_get("compatible_slots")?;
-        let compatible_slots = MealTemplate::parse_compatible_slots(&compatible_slots_json)
-            .map_err(|e| {
-                sqlx::Error::Decode(Box::new(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    e.to_string(),
-                )))
-            })?;
+        let compatible_slots: Option<String> = row.try_get("compatible_slots")?;
+        let compatible_slots = compatible_slots
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .filter(|s| !s.is_empty())
+            .map(SlotType::from_db_string)
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(sqlx::Error::Protocol)?;
 
         Ok(MealTemplate {
             id: row.try_get("id")?,
@@ -30,49 +46,179 @@ impl MealTemplateRepository {
             compatible_slots,
             location_type,
             weekly_limit: row.try_get("weekly_limit")?,
+            archived: row.try_get("archived")?,
             created_at: row.try_get("created_at")?,
             updated_at: row.try_get("updated_at")?,
         })
     }
 
+    /// Replace the compatible slots of a template
+    /// Unknown slots fail the foreign key on meal_slots
+    pub(crate) async fn set_slots(
+        conn: &mut SqliteConnection,
+        template_id: i64,
+        slots: &[SlotType],
+    ) -> Result<()> {
+        sqlx::query("DELETE FROM meal_template_slots WHERE template_id = ?1")
+            .bind(template_id)
+            .execute(&mut *conn)
+            .await?;
+
+        for slot in slots {
+            sqlx::query(
+                "INSERT OR IGNORE INTO meal_template_slots (template_id, slot_type) VALUES (?1, ?2)",
+            )
+            .bind(template_id)
+            .bind(slot.to_db_string())
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the option groups of a template in declaration order
+    /// A template without groups takes a single option per entry
+    pub async fn get_option_groups(
+        pool: &SqlitePool,
+        template_id: i64,
+    ) -> Result<Vec<OptionGroup>> {
+        let rows = sqlx::query(
+            "SELECT name, min_choices, max_choices
+             FROM meal_template_option_groups
+             WHERE template_id = ?1
+             ORDER BY sort_order, name",
+        )
+        .bind(template_id)
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(OptionGroup {
+                    name: row.try_get("name")?,
+                    min_choices: row.try_get("min_choices")?,
+                    max_choices: row.try_get("max_choices")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Replace the option groups of a template
+    /// Options left in a group that is no longer declared become ungrouped
+    pub async fn set_option_groups(
+        pool: &SqlitePool,
+        template_id: i64,
+        groups: Vec<OptionGroup>,
+    ) -> Result<Vec<OptionGroup>> {
+        for group in &groups {
+            group.validate().map_err(sqlx::Error::Protocol)?;
+        }
+
+        if Self::get_by_id(pool, template_id).await?.is_none() {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        let mut tx = pool.begin().await?;
+        let mut audit = AuditScope::new("set_template_option_groups");
+        audit
+            .track(&mut tx, SyncEntity::Template, [template_id])
+            .await?;
+        audit
+            .track_query(
+                &mut tx,
+                SyncEntity::Option,
+                "SELECT id FROM meal_options WHERE template_id = ?1",
+                template_id,
+            )
+            .await?;
+        Self::replace_option_groups(&mut tx, template_id, &groups).await?;
+        audit.record(&mut tx).await?;
+        tx.commit().await?;
+
+        Self::get_option_groups(pool, template_id).await
+    }
+
+    /// Replace the option groups of a template on an open connection
+    pub(crate) async fn replace_option_groups(
+        conn: &mut SqliteConnection,
+        template_id: i64,
+        groups: &[OptionGroup],
+    ) -> Result<()> {
+        sqlx::query("DELETE FROM meal_template_option_groups WHERE template_id = ?1")
+            .bind(template_id)
+            .execute(&mut *conn)
+            .await?;
+
+        for (sort_order, group) in groups.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO meal_template_option_groups
+                     (template_id, name, min_choices, max_choices, sort_order)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )
+            .bind(template_id)
+            .bind(&group.name)
+            .bind(group.min_choices)
+            .bind(group.max_choices)
+            .bind(sort_order as i64)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        sqlx::query(
+            "UPDATE meal_options SET option_group = NULL
+             WHERE template_id = ?1
+               AND option_group NOT IN (
+                   SELECT name FROM meal_template_option_groups WHERE template_id = ?1
+               )",
+        )
+        .bind(template_id)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
     /// Create a new meal template
     pub async fn create(pool: &SqlitePool, template: CreateMealTemplate) -> Result<MealTemplate> {
         template.validate().map_err(sqlx::Error::Protocol)?;
 
         let location_str = template.location_type.to_db_string();
-        let compatible_slots_json =
-            MealTemplate::serialize_compatible_slots(&template.compatible_slots);
 
-        let row = sqlx::query(
+        let mut tx = pool.begin().await?;
+        let id: i64 = sqlx::query_scalar(
             r#"
-            INSERT INTO meal_templates (name, description, compatible_slots, location_type, weekly_limit)
-            VALUES (?1, ?2, ?3, ?4, ?5)
-            RETURNING id, name, description, compatible_slots, location_type, weekly_limit, created_at, updated_at
+            INSERT INTO meal_templates (name, description, location_type, weekly_limit)
+            VALUES (?1, ?2, ?3, ?4)
+            RETURNING id
             "#,
         )
         .bind(&template.name)
         .bind(&template.description)
-        .bind(&compatible_slots_json)
         .bind(location_str)
         .bind(template.weekly_limit)
-        .fetch_one(pool)
+        .fetch_one(&mut *tx)
         .await?;
 
-        Self::row_to_template(&row)
+        Self::set_slots(&mut tx, id, &template.compatible_slots).await?;
+
+        let mut audit = AuditScope::new("create_template");
+        audit.created(SyncEntity::Template, id);
+        audit.record(&mut tx).await?;
+        tx.commit().await?;
+
+        Self::get_by_id(pool, id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)
     }
 
     /// Get a template by ID
     pub async fn get_by_id(pool: &SqlitePool, id: i64) -> Result<Option<MealTemplate>> {
-        let row = sqlx::query(
-            r#"
-            SELECT id, name, description, compatible_slots, location_type, weekly_limit, created_at, updated_at
-            FROM meal_templates
-            WHERE id = ?1
-            "#,
-        )
-        .bind(id)
-        .fetch_optional(pool)
-        .await?;
+        let sql = format!(
+            "SELECT {} FROM meal_templates mt WHERE mt.id = ?1",
+            Self::COLUMNS
+        );
+        let row = sqlx::query(&sql).bind(id).fetch_optional(pool).await?;
 
         match row {
             Some(r) => Ok(Some(Self::row_to_template(&r)?)),
@@ -80,17 +226,35 @@ impl MealTemplateRepository {
         }
     }
 
-    /// Get all templates
+    /// Get all templates that are not archived
     pub async fn get_all(pool: &SqlitePool) -> Result<Vec<MealTemplate>> {
-        let rows = sqlx::query(
-            r#"
-            SELECT id, name, description, compatible_slots, location_type, weekly_limit, created_at, updated_at
-            FROM meal_templates
-            ORDER BY name
-            "#,
-        )
-        .fetch_all(pool)
-        .await?;
+        let sql = format!(
+            "SELECT {} FROM meal_templates mt WHERE mt.archived = 0 ORDER BY mt.name",
+            Self::COLUMNS
+        );
+        let rows = sqlx::query(&sql).fetch_all(pool).await?;
+
+        rows.iter().map(Self::row_to_template).collect()
+    }
+
+    /// Get every template, archived ones included (export)
+    pub async fn get_all_including_archived(pool: &SqlitePool) -> Result<Vec<MealTemplate>> {
+        let sql = format!(
+            "SELECT {} FROM meal_templates mt ORDER BY mt.name",
+            Self::COLUMNS
+        );
+        let rows = sqlx::query(&sql).fetch_all(pool).await?;
+
+        rows.iter().map(Self::row_to_template).collect()
+    }
+
+    /// Get the archived templates
+    pub async fn get_archived(pool: &SqlitePool) -> Result<Vec<MealTemplate>> {
+        let sql = format!(
+            "SELECT {} FROM meal_templates mt WHERE mt.archived = 1 ORDER BY mt.name",
+            Self::COLUMNS
+        );
+        let rows = sqlx::query(&sql).fetch_all(pool).await?;
 
         rows.iter().map(Self::row_to_template).collect()
     }
@@ -102,48 +266,81 @@ impl MealTemplateRepository {
     ) -> Result<Vec<MealTemplate>> {
         let location_str = location.to_db_string();
 
-        let rows = sqlx::query(
-            r#"
-            SELECT id, name, description, compatible_slots, location_type, weekly_limit, created_at, updated_at
-            FROM meal_templates
-            WHERE location_type = ?1 OR location_type = 'any'
-            ORDER BY name
-            "#,
-        )
-        .bind(location_str)
-        .fetch_all(pool)
-        .await?;
+        let sql = format!(
+            "SELECT {} FROM meal_templates mt
+             WHERE (mt.location_type = ?1 OR mt.location_type = 'any') AND mt.archived = 0
+             ORDER BY mt.name",
+            Self::COLUMNS
+        );
+        let rows = sqlx::query(&sql).bind(location_str).fetch_all(pool).await?;
 
         rows.iter().map(Self::row_to_template).collect()
     }
 
     /// Get templates compatible with a specific slot
-    pub async fn get_by_slot(pool: &SqlitePool, slot: SlotType) -> Result<Vec<MealTemplate>> {
-        // Fetch all templates and filter in Rust
-        // This is simpler and more reliable than trying to match JSON in SQL
-        let all_templates = Self::get_all(pool).await?;
-
-        Ok(all_templates
-            .into_iter()
-            .filter(|t| t.compatible_slots.contains(&slot))
-            .collect())
+    pub async fn get_by_slot(pool: &SqlitePool, slot: &SlotType) -> Result<Vec<MealTemplate>> {
+        let sql = format!(
+            "SELECT {} FROM meal_templates mt
+             JOIN meal_template_slots ts ON ts.template_id = mt.id AND ts.slot_type = ?1
+             WHERE mt.archived = 0
+             ORDER BY mt.name",
+            Self::COLUMNS
+        );
+        let rows = sqlx::query(&sql)
+            .bind(slot.to_db_string())
+            .fetch_all(pool)
+            .await?;
+
+        rows.iter().map(Self::row_to_template).collect()
     }
 
-    /// Search templates by name
-    pub async fn search(pool: &SqlitePool, query: &str) -> Result<Vec<MealTemplate>> {
+    /// Get templates that can fill a slot at a location
+    /// A template for 'any' location fits everywhere, and an 'any' location takes every template
+    pub async fn get_by_slot_and_location(
+        pool: &SqlitePool,
+        slot: &SlotType,
+        location: LocationType,
+    ) -> Result<Vec<MealTemplate>> {
+        let sql = format!(
+            "SELECT {} FROM meal_templates mt
+             JOIN meal_template_slots ts ON ts.template_id = mt.id AND ts.slot_type = ?1
+             WHERE (mt.location_type = ?2 OR mt.location_type = 'any' OR ?2 = 'any')
+               AND mt.archived = 0
+             ORDER BY mt.name",
+            Self::COLUMNS
+        );
+        let rows = sqlx::query(&sql)
+            .bind(slot.to_db_string())
+            .bind(location.to_db_string())
+            .fetch_all(pool)
+            .await?;
+
+        rows.iter().map(Self::row_to_template).collect()
+    }
+
+    /// Search templates by name, optionally only those compatible with a slot
+    pub async fn search(
+        pool: &SqlitePool,
+        query: &str,
+        slot: Option<&SlotType>,
+    ) -> Result<Vec<MealTemplate>> {
         let search_pattern = format!("%{}%", query);
 
-        let rows = sqlx::query(
-            r#"
-            SELECT id, name, description, compatible_slots, location_type, weekly_limit, created_at, updated_at
-            FROM meal_templates
-            WHERE name LIKE ?1 OR description LIKE ?1
-            ORDER BY name
-            "#,
-        )
-        .bind(search_pattern)
-        .fetch_all(pool)
-        .await?;
+        let sql = format!(
+            "SELECT {} FROM meal_templates mt
+             WHERE (mt.name LIKE ?1 OR mt.description LIKE ?1)
+               AND mt.archived = 0
+               AND (?2 IS NULL OR EXISTS (
+                   SELECT 1 FROM meal_template_slots ts
+                   WHERE ts.template_id = mt.id AND ts.slot_type = ?2))
+             ORDER BY mt.name",
+            Self::COLUMNS
+        );
+        let rows = sqlx::query(&sql)
+            .bind(search_pattern)
+            .bind(slot.map(|s| s.to_db_string()))
+            .fetch_all(pool)
+            .await?;
 
         rows.iter().map(Self::row_to_template).collect()
     }
@@ -165,7 +362,6 @@ impl MealTemplateRepository {
             Some(val) => val,
             None => existing.description,
         };
-        let compatible_slots = update.compatible_slots.unwrap_or(existing.compatible_slots);
         let location_type = update.location_type.unwrap_or(existing.location_type);
         let weekly_limit = match update.weekly_limit {
             Some(val) => val,
@@ -173,35 +369,117 @@ impl MealTemplateRepository {
         };
 
         let location_str = location_type.to_db_string();
-        let compatible_slots_json = MealTemplate::serialize_compatible_slots(&compatible_slots);
 
-        let row = sqlx::query(
+        let mut tx = pool.begin().await?;
+        let mut audit = AuditScope::new("update_template");
+        audit.track(&mut tx, SyncEntity::Template, [id]).await?;
+        sqlx::query(
             r#"
             UPDATE meal_templates
-            SET name = ?1, description = ?2, compatible_slots = ?3, location_type = ?4, weekly_limit = ?5
-            WHERE id = ?6
-            RETURNING id, name, description, compatible_slots, location_type, weekly_limit, created_at, updated_at
+            SET name = ?1, description = ?2, location_type = ?3, weekly_limit = ?4
+            WHERE id = ?5
             "#,
         )
         .bind(&name)
         .bind(&description)
-        .bind(&compatible_slots_json)
         .bind(location_str)
         .bind(weekly_limit)
         .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        if let Some(slots) = &update.compatible_slots {
+            Self::set_slots(&mut tx, id, slots).await?;
+        }
+        audit.record(&mut tx).await?;
+        tx.commit().await?;
+
+        Self::get_by_id(pool, id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    /// Archive or restore a template
+    /// An archived template hides its options too; past entries keep resolving both
+    pub async fn set_archived(pool: &SqlitePool, id: i64, archived: bool) -> Result<MealTemplate> {
+        let mut tx = pool.begin().await?;
+        let mut audit = AuditScope::new(if archived {
+            "archive_template"
+        } else {
+            "unarchive_template"
+        });
+        audit.track(&mut tx, SyncEntity::Template, [id]).await?;
+
+        let result = sqlx::query(
+            "UPDATE meal_templates SET archived = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
+        )
+        .bind(archived)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        audit.record(&mut tx).await?;
+        tx.commit().await?;
+
+        Self::get_by_id(pool, id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    /// Explain what keeps a template from being deleted
+    /// Deleting cascades to its options, which fails once any of them was logged or planned.
+    /// Returns None when the template does not exist or nothing blocks it
+    pub async fn get_delete_blockers(pool: &SqlitePool, id: i64) -> Result<Option<DeleteBlocked>> {
+        let Some(template) = Self::get_by_id(pool, id).await? else {
+            return Ok(None);
+        };
+
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(DISTINCT eo.meal_entry_id) FROM (
+                 SELECT meal_entry_id, meal_option_id FROM meal_entry_options
+                 UNION ALL
+                 SELECT meal_entry_id, meal_option_id FROM meal_entry_planned_options
+             ) eo
+             JOIN meal_options mo ON mo.id = eo.meal_option_id
+             WHERE mo.template_id = ?1",
+        )
+        .bind(id)
         .fetch_one(pool)
         .await?;
 
-        Self::row_to_template(&row)
+        Ok((count > 0).then(|| DeleteBlocked {
+            kind: LibraryItemKind::Template,
+            id,
+            name: template.name,
+            blockers: vec![DeleteBlocker::MealEntries { count }],
+        }))
     }
 
-    /// Delete a template
+    /// Delete a template, and its options with it
     pub async fn delete(pool: &SqlitePool, id: i64) -> Result<bool> {
+        let mut tx = pool.begin().await?;
+        let mut audit = AuditScope::new("delete_template");
+        audit.track(&mut tx, SyncEntity::Template, [id]).await?;
+        audit
+            .track_query(
+                &mut tx,
+                SyncEntity::Option,
+                "SELECT id FROM meal_options WHERE template_id = ?1",
+                id,
+            )
+            .await?;
+
         let result = sqlx::query("DELETE FROM meal_templates WHERE id = ?1")
             .bind(id)
-            .execute(pool)
+            .execute(&mut *tx)
             .await?;
 
+        audit.record(&mut tx).await?;
+        tx.commit().await?;
+
         Ok(result.rows_affected() > 0)
     }
 }
@@ -235,7 +513,7 @@ mod tests {
         let create = CreateMealTemplate {
             name: "Pane con marmellata".to_string(),
             description: Some("Bread with jam".to_string()),
-            compatible_slots: vec![SlotType::Breakfast, SlotType::MorningSnack],
+            compatible_slots: vec![SlotType::BREAKFAST, SlotType::MORNING_SNACK],
             location_type: LocationType::Home,
             weekly_limit: Some(3),
         };
@@ -249,6 +527,54 @@ mod tests {
         assert!(template.id > 0);
     }
 
+    #[tokio::test]
+    async fn test_compatible_slots_in_day_order() {
+        let pool = setup_test_db().await;
+
+        // Input order and duplicates don't matter
+        let template = MealTemplateRepository::create(
+            &pool,
+            CreateMealTemplate {
+                name: "Frutta".to_string(),
+                description: None,
+                compatible_slots: vec![
+                    SlotType::AFTERNOON_SNACK,
+                    SlotType::BREAKFAST,
+                    SlotType::AFTERNOON_SNACK,
+                ],
+                location_type: LocationType::Any,
+                weekly_limit: None,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            template.compatible_slots,
+            vec![SlotType::BREAKFAST, SlotType::AFTERNOON_SNACK]
+        );
+
+        // Unknown slots fail the foreign key and leave nothing behind
+        let result = MealTemplateRepository::create(
+            &pool,
+            CreateMealTemplate {
+                name: "Shake".to_string(),
+                description: None,
+                compatible_slots: vec![
+                    SlotType::BREAKFAST,
+                    SlotType::from_db_string("pre_workout").unwrap(),
+                ],
+                location_type: LocationType::Any,
+                weekly_limit: None,
+            },
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(
+            MealTemplateRepository::get_all(&pool).await.unwrap().len(),
+            1
+        );
+    }
+
     #[tokio::test]
     async fn test_get_template_by_id() {
         let pool = setup_test_db().await;
@@ -258,7 +584,7 @@ mod tests {
             CreateMealTemplate {
                 name: "Yogurt".to_string(),
                 description: None,
-                compatible_slots: vec![SlotType::Breakfast],
+                compatible_slots: vec![SlotType::BREAKFAST],
                 location_type: LocationType::Any,
                 weekly_limit: None,
             },
@@ -284,7 +610,7 @@ mod tests {
             CreateMealTemplate {
                 name: "Home Meal".to_string(),
                 description: None,
-                compatible_slots: vec![SlotType::Lunch],
+                compatible_slots: vec![SlotType::LUNCH],
                 location_type: LocationType::Home,
                 weekly_limit: None,
             },
@@ -297,7 +623,7 @@ mod tests {
             CreateMealTemplate {
                 name: "Office Meal".to_string(),
                 description: None,
-                compatible_slots: vec![SlotType::Lunch],
+                compatible_slots: vec![SlotType::LUNCH],
                 location_type: LocationType::Office,
                 weekly_limit: None,
             },
@@ -310,7 +636,7 @@ mod tests {
             CreateMealTemplate {
                 name: "Any Location".to_string(),
                 description: None,
-                compatible_slots: vec![SlotType::Lunch],
+                compatible_slots: vec![SlotType::LUNCH],
                 location_type: LocationType::Any,
                 weekly_limit: None,
             },
@@ -337,7 +663,7 @@ mod tests {
             CreateMealTemplate {
                 name: "Breakfast Only".to_string(),
                 description: None,
-                compatible_slots: vec![SlotType::Breakfast],
+                compatible_slots: vec![SlotType::BREAKFAST],
                 location_type: LocationType::Home,
                 weekly_limit: None,
             },
@@ -350,7 +676,7 @@ mod tests {
             CreateMealTemplate {
                 name: "Lunch and Dinner".to_string(),
                 description: None,
-                compatible_slots: vec![SlotType::Lunch, SlotType::Dinner],
+                compatible_slots: vec![SlotType::LUNCH, SlotType::DINNER],
                 location_type: LocationType::Home,
                 weekly_limit: None,
             },
@@ -358,13 +684,13 @@ mod tests {
         .await
         .unwrap();
 
-        let breakfast_templates = MealTemplateRepository::get_by_slot(&pool, SlotType::Breakfast)
+        let breakfast_templates = MealTemplateRepository::get_by_slot(&pool, &SlotType::BREAKFAST)
             .await
             .unwrap();
         assert_eq!(breakfast_templates.len(), 1);
         assert_eq!(breakfast_templates[0].name, "Breakfast Only");
 
-        let lunch_templates = MealTemplateRepository::get_by_slot(&pool, SlotType::Lunch)
+        let lunch_templates = MealTemplateRepository::get_by_slot(&pool, &SlotType::LUNCH)
             .await
             .unwrap();
         assert_eq!(lunch_templates.len(), 1);
@@ -380,7 +706,7 @@ mod tests {
             CreateMealTemplate {
                 name: "Pasta carbonara".to_string(),
                 description: Some("Classic pasta dish".to_string()),
-                compatible_slots: vec![SlotType::Lunch],
+                compatible_slots: vec![SlotType::LUNCH],
                 location_type: LocationType::Home,
                 weekly_limit: None,
             },
@@ -393,7 +719,7 @@ mod tests {
             CreateMealTemplate {
                 name: "Pasta aglio e olio".to_string(),
                 description: None,
-                compatible_slots: vec![SlotType::Dinner],
+                compatible_slots: vec![SlotType::DINNER],
                 location_type: LocationType::Home,
                 weekly_limit: None,
             },
@@ -401,16 +727,73 @@ mod tests {
         .await
         .unwrap();
 
-        let results = MealTemplateRepository::search(&pool, "pasta")
+        let results = MealTemplateRepository::search(&pool, "pasta", None)
             .await
             .unwrap();
         assert_eq!(results.len(), 2);
 
-        let results = MealTemplateRepository::search(&pool, "carbonara")
+        let results = MealTemplateRepository::search(&pool, "carbonara", None)
             .await
             .unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].name, "Pasta carbonara");
+
+        // Restricted to a slot
+        let results = MealTemplateRepository::search(&pool, "pasta", Some(&SlotType::DINNER))
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "Pasta aglio e olio");
+    }
+
+    #[tokio::test]
+    async fn test_get_by_slot_and_location() {
+        let pool = setup_test_db().await;
+
+        for (name, slots, location_type) in [
+            ("Home Lunch", vec![SlotType::LUNCH], LocationType::Home),
+            ("Office Lunch", vec![SlotType::LUNCH], LocationType::Office),
+            ("Anywhere", vec![SlotType::LUNCH], LocationType::Any),
+            ("Home Dinner", vec![SlotType::DINNER], LocationType::Home),
+        ] {
+            MealTemplateRepository::create(
+                &pool,
+                CreateMealTemplate {
+                    name: name.to_string(),
+                    description: None,
+                    compatible_slots: slots,
+                    location_type,
+                    weekly_limit: None,
+                },
+            )
+            .await
+            .unwrap();
+        }
+
+        let names = |templates: Vec<MealTemplate>| -> Vec<String> {
+            templates.into_iter().map(|t| t.name).collect()
+        };
+
+        let at_home = MealTemplateRepository::get_by_slot_and_location(
+            &pool,
+            &SlotType::LUNCH,
+            LocationType::Home,
+        )
+        .await
+        .unwrap();
+        assert_eq!(names(at_home), vec!["Anywhere", "Home Lunch"]);
+
+        let anywhere = MealTemplateRepository::get_by_slot_and_location(
+            &pool,
+            &SlotType::LUNCH,
+            LocationType::Any,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            names(anywhere),
+            vec!["Anywhere", "Home Lunch", "Office Lunch"]
+        );
     }
 
     #[tokio::test]
@@ -422,7 +805,7 @@ mod tests {
             CreateMealTemplate {
                 name: "Original".to_string(),
                 description: Some("Original description".to_string()),
-                compatible_slots: vec![SlotType::Breakfast],
+                compatible_slots: vec![SlotType::BREAKFAST],
                 location_type: LocationType::Home,
                 weekly_limit: Some(5),
             },
@@ -436,7 +819,7 @@ mod tests {
             UpdateMealTemplate {
                 name: Some("Updated".to_string()),
                 description: Some(None), // Clear description
-                compatible_slots: Some(vec![SlotType::Lunch, SlotType::Dinner]),
+                compatible_slots: Some(vec![SlotType::LUNCH, SlotType::DINNER]),
                 location_type: Some(LocationType::Office),
                 weekly_limit: Some(Some(3)),
             },
@@ -446,7 +829,10 @@ mod tests {
 
         assert_eq!(updated.name, "Updated");
         assert!(updated.description.is_none());
-        assert_eq!(updated.compatible_slots.len(), 2);
+        assert_eq!(
+            updated.compatible_slots,
+            vec![SlotType::LUNCH, SlotType::DINNER]
+        );
         assert_eq!(updated.location_type, LocationType::Office);
         assert_eq!(updated.weekly_limit, Some(3));
     }
@@ -460,7 +846,7 @@ mod tests {
             CreateMealTemplate {
                 name: "To Delete".to_string(),
                 description: None,
-                compatible_slots: vec![SlotType::Breakfast],
+                compatible_slots: vec![SlotType::BREAKFAST],
                 location_type: LocationType::Home,
                 weekly_limit: None,
             },
@@ -489,7 +875,7 @@ mod tests {
             CreateMealTemplate {
                 name: "".to_string(),
                 description: None,
-                compatible_slots: vec![SlotType::Breakfast],
+                compatible_slots: vec![SlotType::BREAKFAST],
                 location_type: LocationType::Home,
                 weekly_limit: None,
             },
@@ -3,17 +3,36 @@
 // Note: Repositories will be used in Phase 2 (Tauri commands)
 #![allow(dead_code)]
 
+mod audit_repository;
+mod backend;
 mod meal_entry_repository;
 mod meal_option_repository;
+mod meal_slot_repository;
 mod meal_template_repository;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+mod sqlite_store;
+mod store;
+mod sync_repository;
 mod tag_repository;
 
 // Re-export repositories (will be used in Phase 2)
 #[allow(unused_imports)]
+pub use audit_repository::AuditRepository;
+pub(crate) use audit_repository::AuditScope;
+pub use backend::{Backend, POSTGRES_URL_ENV, SQLITE_ONLY_COMMANDS};
+#[allow(unused_imports)]
 pub use meal_entry_repository::MealEntryRepository;
 #[allow(unused_imports)]
 pub use meal_option_repository::MealOptionRepository;
 #[allow(unused_imports)]
+pub use meal_slot_repository::MealSlotRepository;
+#[allow(unused_imports)]
 pub use meal_template_repository::MealTemplateRepository;
+pub use store::{
+    MealEntryStore, MealOptionStore, MealSlotStore, MealTemplateStore, Store, TagStore,
+};
+#[allow(unused_imports)]
+pub use sync_repository::SyncRepository;
 #[allow(unused_imports)]
 pub use tag_repository::TagRepository;
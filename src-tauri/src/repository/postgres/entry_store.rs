@@ -0,0 +1,657 @@
+use super::{parse_db_string, split_list};
+use crate::models::{
+    CreateMealEntry, CreateOffPlanEntry, EntryStatus, LocationType, LogActualMeal, MealEntry,
+    MealEntryOption, SelectedOption, SetNutrientProfile, SlotType, UpdateMealEntry, WeeklyTagUsage,
+    WeeklyTagUsageNode, WeeklyTemplateUsage, WeeklyUsage,
+};
+use crate::repository::MealEntryStore;
+use chrono::NaiveDate;
+use sqlx::postgres::PgRow;
+use sqlx::{PgConnection, PgPool, Result, Row};
+
+/// Columns selected for a MealEntry from `meal_entries me`
+/// Same aggregates as the SQLite repository: snapshot tag names as a list,
+/// selected and planned options as JSON arrays (main option first) and the
+/// estimated nutrients of off-plan entries as a JSON object
+const COLUMNS: &str = r#"
+    me.id, me.meal_option_id, me.date, me.slot_type, me.location, me.servings, me.notes,
+    me.status, me.option_name, me.template_id, me.template_name,
+    me.template_location_type, me.created_at, me.updated_at,
+    (SELECT string_agg(met.tag_name, ',' ORDER BY met.tag_name)
+     FROM meal_entry_tags met
+     WHERE met.meal_entry_id = me.id) AS tag_names,
+    (SELECT COALESCE(json_agg(json_build_object(
+                'meal_option_id', eo.meal_option_id,
+                'option_name', eo.option_name,
+                'servings', eo.servings) ORDER BY eo.position), '[]')::TEXT
+     FROM meal_entry_options eo
+     WHERE eo.meal_entry_id = me.id) AS options,
+    (SELECT COALESCE(json_agg(json_build_object(
+                'meal_option_id', po.meal_option_id,
+                'option_name', po.option_name,
+                'servings', po.servings) ORDER BY po.position), '[]')::TEXT
+     FROM meal_entry_planned_options po
+     WHERE po.meal_entry_id = me.id) AS planned_options,
+    (SELECT json_build_object(
+                'kcal', n.kcal, 'protein_g', n.protein_g, 'carbs_g', n.carbs_g,
+                'fat_g', n.fat_g, 'fiber_g', n.fiber_g)::TEXT
+     FROM meal_entry_nutrients n
+     WHERE n.meal_entry_id = me.id) AS estimated_nutrients
+"#;
+
+/// Orders entries of a day by slot
+const SLOT_ORDER: &str = "(SELECT sort_order FROM meal_slots WHERE name = me.slot_type)";
+
+fn row_to_entry(row: &PgRow) -> Result<MealEntry> {
+    let slot_type: String = row.try_get("slot_type")?;
+    let location: String = row.try_get("location")?;
+    let status: String = row.try_get("status")?;
+    let template_location_type: String = row.try_get("template_location_type")?;
+
+    let options: String = row.try_get("options")?;
+    let options: Vec<MealEntryOption> =
+        serde_json::from_str(&options).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+
+    let planned_options: String = row.try_get("planned_options")?;
+    let planned_options: Vec<MealEntryOption> =
+        serde_json::from_str(&planned_options).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+
+    let estimated_nutrients: Option<String> = row.try_get("estimated_nutrients")?;
+    let estimated_nutrients: Option<SetNutrientProfile> = estimated_nutrients
+        .map(|json| serde_json::from_str(&json))
+        .transpose()
+        .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+
+    Ok(MealEntry {
+        id: row.try_get("id")?,
+        meal_option_id: row.try_get("meal_option_id")?,
+        date: row.try_get("date")?,
+        slot_type: parse_db_string(SlotType::from_db_string(&slot_type))?,
+        location: parse_db_string(LocationType::from_db_string(&location))?,
+        servings: row.try_get("servings")?,
+        notes: row.try_get("notes")?,
+        status: parse_db_string(EntryStatus::from_db_string(&status))?,
+        option_name: row.try_get("option_name")?,
+        template_id: row.try_get("template_id")?,
+        template_name: row.try_get("template_name")?,
+        template_location_type: parse_db_string(LocationType::from_db_string(
+            &template_location_type,
+        ))?,
+        tag_names: split_list(row.try_get("tag_names")?),
+        options,
+        planned_options,
+        estimated_nutrients,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+type PgQuery<'q> = sqlx::query::Query<'q, sqlx::Postgres, sqlx::postgres::PgArguments>;
+
+/// Fetch entries matching the clauses after `FROM meal_entries me`
+async fn fetch_entries(
+    pool: &PgPool,
+    clauses: &str,
+    bind: impl for<'q> FnOnce(PgQuery<'q>) -> PgQuery<'q>,
+) -> Result<Vec<MealEntry>> {
+    let sql = format!("SELECT {} FROM meal_entries me {}", COLUMNS, clauses);
+    let rows = bind(sqlx::query(&sql)).fetch_all(pool).await?;
+
+    rows.iter().map(row_to_entry).collect()
+}
+
+/// Record the options selected in a new entry, main option first
+async fn insert_options(
+    conn: &mut PgConnection,
+    entry_id: i64,
+    extra_options: &[SelectedOption],
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO meal_entry_options (meal_entry_id, meal_option_id, position, servings)
+         SELECT id, meal_option_id, 0, servings FROM meal_entries WHERE id = $1",
+    )
+    .bind(entry_id)
+    .execute(&mut *conn)
+    .await?;
+
+    for (i, extra) in extra_options.iter().enumerate() {
+        sqlx::query(
+            "INSERT INTO meal_entry_options (meal_entry_id, meal_option_id, position, servings)
+             VALUES ($1, $2, $3, $4)",
+        )
+        .bind(entry_id)
+        .bind(extra.meal_option_id)
+        .bind(i as i32 + 1)
+        .bind(extra.servings.unwrap_or(1.0))
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Copy the current option names, template and tag set onto an entry
+/// Off-plan entries keep their description and estimated tags
+async fn snapshot(conn: &mut PgConnection, entry_id: i64) -> Result<()> {
+    let off_plan: Option<bool> =
+        sqlx::query_scalar("SELECT meal_option_id IS NULL FROM meal_entries WHERE id = $1")
+            .bind(entry_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+    if off_plan == Some(true) {
+        return Ok(());
+    }
+
+    sqlx::query(
+        "UPDATE meal_entries SET
+             option_name = mo.name,
+             template_id = mt.id,
+             template_name = mt.name,
+             template_location_type = mt.location_type
+         FROM meal_options mo
+         JOIN meal_templates mt ON mt.id = mo.template_id
+         WHERE mo.id = meal_entries.meal_option_id AND meal_entries.id = $1",
+    )
+    .bind(entry_id)
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        "UPDATE meal_entry_options SET option_name = mo.name
+         FROM meal_options mo
+         WHERE mo.id = meal_entry_options.meal_option_id
+           AND meal_entry_options.meal_entry_id = $1",
+    )
+    .bind(entry_id)
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query("DELETE FROM meal_entry_tags WHERE meal_entry_id = $1")
+        .bind(entry_id)
+        .execute(&mut *conn)
+        .await?;
+
+    // Tags of every selected option, each tag once
+    sqlx::query(
+        "INSERT INTO meal_entry_tags (meal_entry_id, tag_id, tag_name)
+         SELECT DISTINCT eo.meal_entry_id, t.id, t.name
+         FROM meal_entry_options eo
+         JOIN meal_option_tags mot ON mot.meal_option_id = eo.meal_option_id
+         JOIN tags t ON t.id = mot.tag_id
+         WHERE eo.meal_entry_id = $1
+         ON CONFLICT DO NOTHING",
+    )
+    .bind(entry_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Record an entry's current options as the plan it was created with
+async fn record_plan(conn: &mut PgConnection, entry_id: i64) -> Result<()> {
+    sqlx::query("DELETE FROM meal_entry_planned_options WHERE meal_entry_id = $1")
+        .bind(entry_id)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query(
+        "INSERT INTO meal_entry_planned_options (meal_entry_id, meal_option_id, position, servings, option_name)
+         SELECT meal_entry_id, meal_option_id, position, servings, option_name
+         FROM meal_entry_options WHERE meal_entry_id = $1",
+    )
+    .bind(entry_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Check that the main option exists and the extra options belong to its template
+async fn check_selection(
+    pool: &PgPool,
+    meal_option_id: i64,
+    extra_options: &[SelectedOption],
+) -> Result<()> {
+    let option_exists: bool =
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM meal_options WHERE id = $1)")
+            .bind(meal_option_id)
+            .fetch_one(pool)
+            .await?;
+
+    if !option_exists {
+        return Err(sqlx::Error::Protocol(format!(
+            "Meal option with id {} does not exist",
+            meal_option_id
+        )));
+    }
+
+    for extra in extra_options {
+        let same_template: Option<bool> = sqlx::query_scalar(
+            "SELECT mo.template_id = main.template_id
+             FROM meal_options mo, meal_options main
+             WHERE mo.id = $1 AND main.id = $2",
+        )
+        .bind(extra.meal_option_id)
+        .bind(meal_option_id)
+        .fetch_optional(pool)
+        .await?;
+
+        match same_template {
+            None => {
+                return Err(sqlx::Error::Protocol(format!(
+                    "Meal option with id {} does not exist",
+                    extra.meal_option_id
+                )))
+            }
+            Some(false) => {
+                return Err(sqlx::Error::Protocol(format!(
+                    "Meal option with id {} belongs to a different template",
+                    extra.meal_option_id
+                )))
+            }
+            Some(true) => {}
+        }
+    }
+
+    Ok(())
+}
+
+impl MealEntryStore for PgPool {
+    async fn create_entry(&self, entry: CreateMealEntry) -> Result<MealEntry> {
+        entry.validate().map_err(sqlx::Error::Protocol)?;
+
+        check_selection(self, entry.meal_option_id, &entry.extra_options).await?;
+
+        let mut tx = self.begin().await?;
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO meal_entries (meal_option_id, date, iso_week, slot_type, location, servings, notes, status)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+             RETURNING id",
+        )
+        .bind(entry.meal_option_id)
+        .bind(entry.date)
+        .bind(MealEntry::week_key(entry.date))
+        .bind(entry.slot_type.to_db_string())
+        .bind(entry.location.to_db_string())
+        .bind(entry.servings_or_default())
+        .bind(&entry.notes)
+        .bind(entry.status_or_default().to_db_string())
+        .fetch_one(&mut *tx)
+        .await?;
+
+        insert_options(&mut tx, id, &entry.extra_options).await?;
+        snapshot(&mut tx, id).await?;
+        record_plan(&mut tx, id).await?;
+        tx.commit().await?;
+
+        self.get_entry(id).await?.ok_or(sqlx::Error::RowNotFound)
+    }
+
+    async fn create_off_plan_entry(&self, entry: CreateOffPlanEntry) -> Result<MealEntry> {
+        entry.validate().map_err(sqlx::Error::Protocol)?;
+
+        for tag_id in &entry.tag_ids {
+            let tag_exists: bool =
+                sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM tags WHERE id = $1)")
+                    .bind(tag_id)
+                    .fetch_one(self)
+                    .await?;
+
+            if !tag_exists {
+                return Err(sqlx::Error::Protocol(format!(
+                    "Tag with id {} does not exist",
+                    tag_id
+                )));
+            }
+        }
+
+        let mut tx = self.begin().await?;
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO meal_entries (meal_option_id, date, iso_week, slot_type, location, servings, notes, status,
+                                       option_name, template_id, template_name)
+             VALUES (NULL, $1, $2, $3, $4, $5, $6, $7, $8, NULL, '')
+             RETURNING id",
+        )
+        .bind(entry.date)
+        .bind(MealEntry::week_key(entry.date))
+        .bind(entry.slot_type.to_db_string())
+        .bind(entry.location.to_db_string())
+        .bind(entry.servings_or_default())
+        .bind(&entry.notes)
+        .bind(entry.status_or_default().to_db_string())
+        .bind(entry.description.trim())
+        .fetch_one(&mut *tx)
+        .await?;
+
+        for tag_id in &entry.tag_ids {
+            sqlx::query(
+                "INSERT INTO meal_entry_tags (meal_entry_id, tag_id, tag_name)
+                 SELECT $1, id, name FROM tags WHERE id = $2
+                 ON CONFLICT DO NOTHING",
+            )
+            .bind(id)
+            .bind(tag_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        if let Some(nutrients) = &entry.estimated_nutrients {
+            sqlx::query(
+                "INSERT INTO meal_entry_nutrients (meal_entry_id, kcal, protein_g, carbs_g, fat_g, fiber_g)
+                 VALUES ($1, $2, $3, $4, $5, $6)",
+            )
+            .bind(id)
+            .bind(nutrients.kcal)
+            .bind(nutrients.protein_g)
+            .bind(nutrients.carbs_g)
+            .bind(nutrients.fat_g)
+            .bind(nutrients.fiber_g)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        self.get_entry(id).await?.ok_or(sqlx::Error::RowNotFound)
+    }
+
+    async fn get_entry(&self, id: i64) -> Result<Option<MealEntry>> {
+        let sql = format!("SELECT {} FROM meal_entries me WHERE me.id = $1", COLUMNS);
+        let row = sqlx::query(&sql).bind(id).fetch_optional(self).await?;
+
+        row.as_ref().map(row_to_entry).transpose()
+    }
+
+    async fn get_entries_by_date(&self, date: NaiveDate) -> Result<Vec<MealEntry>> {
+        let clauses = format!("WHERE me.date = $1 ORDER BY {}", SLOT_ORDER);
+        fetch_entries(self, &clauses, |q| q.bind(date)).await
+    }
+
+    async fn get_entries_by_date_range(
+        &self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<MealEntry>> {
+        let clauses = format!(
+            "WHERE me.date BETWEEN $1 AND $2 ORDER BY me.date, {}",
+            SLOT_ORDER
+        );
+        fetch_entries(self, &clauses, |q| q.bind(start_date).bind(end_date)).await
+    }
+
+    async fn get_entries_by_date_and_slot(
+        &self,
+        date: NaiveDate,
+        slot: &SlotType,
+    ) -> Result<Vec<MealEntry>> {
+        let slot = slot.to_db_string().to_string();
+        fetch_entries(
+            self,
+            "WHERE me.date = $1 AND me.slot_type = $2 ORDER BY me.id",
+            |q| q.bind(date).bind(slot),
+        )
+        .await
+    }
+
+    async fn get_entries_by_status(&self, status: EntryStatus) -> Result<Vec<MealEntry>> {
+        let clauses = format!("WHERE me.status = $1 ORDER BY me.date DESC, {}", SLOT_ORDER);
+        fetch_entries(self, &clauses, |q| q.bind(status.to_db_string())).await
+    }
+
+    async fn get_entries_by_option(&self, meal_option_id: i64) -> Result<Vec<MealEntry>> {
+        fetch_entries(
+            self,
+            "WHERE EXISTS (
+                 SELECT 1 FROM meal_entry_options eo
+                 WHERE eo.meal_entry_id = me.id AND eo.meal_option_id = $1
+             )
+             ORDER BY me.date DESC",
+            |q| q.bind(meal_option_id),
+        )
+        .await
+    }
+
+    async fn get_recent_entries(&self, limit: i32) -> Result<Vec<MealEntry>> {
+        fetch_entries(
+            self,
+            "WHERE me.id IN (
+                 SELECT MAX(id)
+                 FROM meal_entries
+                 WHERE meal_option_id IS NOT NULL
+                 GROUP BY meal_option_id
+             )
+             ORDER BY me.date DESC, me.created_at DESC
+             LIMIT $1",
+            |q| q.bind(i64::from(limit)),
+        )
+        .await
+    }
+
+    async fn update_entry(&self, id: i64, update: UpdateMealEntry) -> Result<MealEntry> {
+        update.validate().map_err(sqlx::Error::Protocol)?;
+
+        let existing = self.get_entry(id).await?.ok_or(sqlx::Error::RowNotFound)?;
+
+        let location = update.location.unwrap_or(existing.location);
+        let servings = update.servings.unwrap_or(existing.servings);
+        let notes = update.notes.unwrap_or(existing.notes);
+        let status = update.status.unwrap_or(existing.status);
+
+        let mut tx = self.begin().await?;
+        sqlx::query(
+            "UPDATE meal_entries SET location = $1, servings = $2, notes = $3, status = $4
+             WHERE id = $5",
+        )
+        .bind(location.to_db_string())
+        .bind(servings)
+        .bind(&notes)
+        .bind(status.to_db_string())
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        // Servings of the entry are the servings of its main option
+        if update.servings.is_some() {
+            sqlx::query(
+                "UPDATE meal_entry_options SET servings = $1 WHERE meal_entry_id = $2 AND position = 0",
+            )
+            .bind(servings)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        // A planned entry being marked as eaten records the option as it is now
+        if status == EntryStatus::Eaten && existing.status != EntryStatus::Eaten {
+            snapshot(&mut tx, id).await?;
+        }
+        tx.commit().await?;
+
+        self.get_entry(id).await?.ok_or(sqlx::Error::RowNotFound)
+    }
+
+    async fn log_actual(&self, id: i64, actual: LogActualMeal) -> Result<MealEntry> {
+        actual.validate().map_err(sqlx::Error::Protocol)?;
+
+        if self.get_entry(id).await?.is_none() {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        check_selection(self, actual.meal_option_id, &actual.extra_options).await?;
+
+        let mut tx = self.begin().await?;
+        sqlx::query(
+            "UPDATE meal_entries
+             SET meal_option_id = $1, servings = $2, location = COALESCE($3, location),
+                 status = 'eaten'
+             WHERE id = $4",
+        )
+        .bind(actual.meal_option_id)
+        .bind(actual.servings_or_default())
+        .bind(actual.location.map(LocationType::to_db_string))
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        if let Some(notes) = &actual.notes {
+            sqlx::query("UPDATE meal_entries SET notes = $1 WHERE id = $2")
+                .bind(notes.as_ref())
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        sqlx::query("DELETE FROM meal_entry_options WHERE meal_entry_id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        insert_options(&mut tx, id, &actual.extra_options).await?;
+        snapshot(&mut tx, id).await?;
+        tx.commit().await?;
+
+        self.get_entry(id).await?.ok_or(sqlx::Error::RowNotFound)
+    }
+
+    async fn delete_entry(&self, id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM meal_entries WHERE id = $1")
+            .bind(id)
+            .execute(self)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn get_weekly_usage(
+        &self,
+        meal_option_id: i64,
+        week: &str,
+    ) -> Result<Option<WeeklyUsage>> {
+        sqlx::query_as::<_, WeeklyUsage>(
+            "SELECT meal_option_id, week, usage_count
+             FROM weekly_meal_usage
+             WHERE meal_option_id = $1 AND week = $2",
+        )
+        .bind(meal_option_id)
+        .bind(week)
+        .fetch_optional(self)
+        .await
+    }
+
+    async fn get_weekly_template_usage(
+        &self,
+        template_id: i64,
+        week: &str,
+    ) -> Result<Option<WeeklyTemplateUsage>> {
+        sqlx::query_as::<_, WeeklyTemplateUsage>(
+            "SELECT template_id, template_name, week, usage_count, weekly_limit
+             FROM weekly_template_usage
+             WHERE template_id = $1 AND week = $2",
+        )
+        .bind(template_id)
+        .bind(week)
+        .fetch_optional(self)
+        .await
+    }
+
+    async fn get_all_weekly_template_usage(&self, week: &str) -> Result<Vec<WeeklyTemplateUsage>> {
+        sqlx::query_as::<_, WeeklyTemplateUsage>(
+            "SELECT mt.id AS template_id, mt.name AS template_name, $1::TEXT AS week,
+                    COALESCE(wtu.usage_count, 0) AS usage_count, mt.weekly_limit
+             FROM meal_templates mt
+             LEFT JOIN weekly_template_usage wtu ON wtu.template_id = mt.id AND wtu.week = $1
+             WHERE mt.weekly_limit IS NOT NULL OR wtu.usage_count IS NOT NULL
+             ORDER BY mt.name",
+        )
+        .bind(week)
+        .fetch_all(self)
+        .await
+    }
+
+    async fn get_weekly_tag_usage(
+        &self,
+        tag_id: i64,
+        week: &str,
+    ) -> Result<Option<WeeklyTagUsage>> {
+        sqlx::query_as::<_, WeeklyTagUsage>(
+            "WITH RECURSIVE subtree(id) AS (
+                 SELECT id FROM tags WHERE id = $1
+                 UNION
+                 SELECT t.id FROM tags t JOIN subtree s ON t.parent_tag_id = s.id
+             )
+             SELECT t.id AS tag_id, t.name AS tag_name, $2::TEXT AS week,
+                    COUNT(DISTINCT me.id) AS usage_count
+             FROM tags t
+             JOIN meal_entries me ON me.status = 'eaten' AND me.iso_week = $2
+             WHERE t.id = $1
+               AND EXISTS (
+                   SELECT 1 FROM meal_entry_tags met
+                   JOIN subtree s ON s.id = met.tag_id
+                   WHERE met.meal_entry_id = me.id
+               )
+             GROUP BY t.id, t.name",
+        )
+        .bind(tag_id)
+        .bind(week)
+        .fetch_optional(self)
+        .await
+    }
+
+    async fn get_weekly_tag_usage_breakdown(
+        &self,
+        tag_id: i64,
+        week: &str,
+    ) -> Result<Vec<WeeklyTagUsageNode>> {
+        sqlx::query_as::<_, WeeklyTagUsageNode>(
+            "WITH RECURSIVE
+             ancestors(id, depth) AS (
+                 SELECT id, 0 FROM tags WHERE id = $1
+                 UNION
+                 SELECT t.parent_tag_id, a.depth - 1
+                 FROM tags t JOIN ancestors a ON t.id = a.id
+                 WHERE t.parent_tag_id IS NOT NULL
+             ),
+             descendants(id, depth) AS (
+                 SELECT id, 0 FROM tags WHERE id = $1
+                 UNION
+                 SELECT t.id, d.depth + 1
+                 FROM tags t JOIN descendants d ON t.parent_tag_id = d.id
+             ),
+             lineage(id, depth) AS (
+                 SELECT id, depth FROM ancestors
+                 UNION
+                 SELECT id, depth FROM descendants
+             ),
+             closure(ancestor_id, tag_id) AS (
+                 SELECT id, id FROM lineage
+                 UNION
+                 SELECT c.ancestor_id, t.id
+                 FROM tags t JOIN closure c ON t.parent_tag_id = c.tag_id
+             ),
+             week_entries(entry_id, tag_id) AS (
+                 SELECT me.id, met.tag_id
+                 FROM meal_entries me
+                 JOIN meal_entry_tags met ON met.meal_entry_id = me.id
+                 WHERE me.status = 'eaten' AND me.iso_week = $2
+             )
+             SELECT t.id AS tag_id, t.name AS tag_name, t.display_name, t.parent_tag_id,
+                    t.weekly_suggestion,
+                    (SELECT COUNT(DISTINCT we.entry_id) FROM week_entries we
+                     WHERE we.tag_id = t.id) AS direct_count,
+                    (SELECT COUNT(DISTINCT we.entry_id) FROM week_entries we
+                     JOIN closure c ON c.tag_id = we.tag_id
+                     WHERE c.ancestor_id = t.id) AS total_count
+             FROM lineage l
+             JOIN tags t ON t.id = l.id
+             GROUP BY t.id
+             ORDER BY MIN(l.depth), t.name",
+        )
+        .bind(tag_id)
+        .bind(week)
+        .fetch_all(self)
+        .await
+    }
+}
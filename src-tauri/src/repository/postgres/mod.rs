@@ -0,0 +1,25 @@
+// PostgreSQL repositories
+// The repository traits over a shared PostgreSQL database (`postgres` feature).
+// Behavior matches the SQLite repositories, except that no audit log or sync change
+// log is written: undo, history and sync stay SQLite-only (see `Backend::sqlite`)
+
+mod entry_store;
+mod option_store;
+mod slot_store;
+mod tag_store;
+mod template_store;
+
+/// Enum columns are stored as text; an unknown value is a decoding error
+fn parse_db_string<T>(parsed: Result<T, String>) -> Result<T, sqlx::Error> {
+    parsed.map_err(sqlx::Error::Protocol)
+}
+
+/// Split a comma-separated aggregate (string_agg) into its parts
+fn split_list(list: Option<String>) -> Vec<String> {
+    list.as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
+}
@@ -0,0 +1,366 @@
+use crate::models::{
+    CreateMealOption, DeleteBlocked, DeleteBlocker, LibraryItemKind, MealOption,
+    MealOptionWithTags, NutrientProfile, SetNutrientProfile, UpdateMealOption,
+};
+use crate::repository::MealOptionStore;
+use sqlx::{PgPool, Result};
+
+const COLUMNS: &str = "id, template_id, name, description, nutritional_notes, option_group, \
+                       archived, created_at, updated_at";
+
+const NUTRIENT_COLUMNS: &str =
+    "meal_option_id, kcal, protein_g, carbs_g, fat_g, fiber_g, created_at, updated_at";
+
+/// Options that are offered: not archived and in a template that is not archived
+const OFFERED: &str =
+    "NOT archived AND template_id NOT IN (SELECT id FROM meal_templates WHERE archived)";
+
+async fn tag_ids_for_option(pool: &PgPool, option_id: i64) -> Result<Vec<i64>> {
+    sqlx::query_scalar(
+        "SELECT tag_id FROM meal_option_tags WHERE meal_option_id = $1 ORDER BY tag_id",
+    )
+    .bind(option_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Fail with a protocol error naming the first tag that does not exist
+async fn check_tags_exist(pool: &PgPool, tag_ids: &[i64]) -> Result<()> {
+    for tag_id in tag_ids {
+        let tag_exists: bool =
+            sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM tags WHERE id = $1)")
+                .bind(tag_id)
+                .fetch_one(pool)
+                .await?;
+
+        if !tag_exists {
+            return Err(sqlx::Error::Protocol(format!(
+                "Tag with id {} does not exist",
+                tag_id
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+impl MealOptionStore for PgPool {
+    async fn create_option(&self, option: CreateMealOption) -> Result<MealOption> {
+        option.validate().map_err(sqlx::Error::Protocol)?;
+
+        let template_exists: bool =
+            sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM meal_templates WHERE id = $1)")
+                .bind(option.template_id)
+                .fetch_one(self)
+                .await?;
+
+        if !template_exists {
+            return Err(sqlx::Error::Protocol(format!(
+                "Template with id {} does not exist",
+                option.template_id
+            )));
+        }
+
+        let sql = format!(
+            "INSERT INTO meal_options (template_id, name, description, nutritional_notes)
+             VALUES ($1, $2, $3, $4)
+             RETURNING {}",
+            COLUMNS
+        );
+        sqlx::query_as::<_, MealOption>(&sql)
+            .bind(option.template_id)
+            .bind(&option.name)
+            .bind(&option.description)
+            .bind(&option.nutritional_notes)
+            .fetch_one(self)
+            .await
+    }
+
+    async fn get_option(&self, id: i64) -> Result<Option<MealOption>> {
+        let sql = format!("SELECT {} FROM meal_options WHERE id = $1", COLUMNS);
+        sqlx::query_as::<_, MealOption>(&sql)
+            .bind(id)
+            .fetch_optional(self)
+            .await
+    }
+
+    async fn get_options(&self) -> Result<Vec<MealOption>> {
+        let sql = format!(
+            "SELECT {} FROM meal_options WHERE {} ORDER BY name",
+            COLUMNS, OFFERED
+        );
+        sqlx::query_as::<_, MealOption>(&sql).fetch_all(self).await
+    }
+
+    async fn get_archived_options(&self) -> Result<Vec<MealOption>> {
+        let sql = format!(
+            "SELECT {} FROM meal_options WHERE archived ORDER BY name",
+            COLUMNS
+        );
+        sqlx::query_as::<_, MealOption>(&sql).fetch_all(self).await
+    }
+
+    async fn get_options_by_template(&self, template_id: i64) -> Result<Vec<MealOption>> {
+        let sql = format!(
+            "SELECT {} FROM meal_options WHERE template_id = $1 AND NOT archived ORDER BY name",
+            COLUMNS
+        );
+        sqlx::query_as::<_, MealOption>(&sql)
+            .bind(template_id)
+            .fetch_all(self)
+            .await
+    }
+
+    async fn get_option_with_tags(&self, id: i64) -> Result<Option<MealOptionWithTags>> {
+        let Some(option) = self.get_option(id).await? else {
+            return Ok(None);
+        };
+        let tags = tag_ids_for_option(self, id).await?;
+
+        Ok(Some(MealOptionWithTags { option, tags }))
+    }
+
+    async fn get_options_by_template_with_tags(
+        &self,
+        template_id: i64,
+    ) -> Result<Vec<MealOptionWithTags>> {
+        let mut options_with_tags = Vec::new();
+        for option in self.get_options_by_template(template_id).await? {
+            let tags = tag_ids_for_option(self, option.id).await?;
+            options_with_tags.push(MealOptionWithTags { option, tags });
+        }
+
+        Ok(options_with_tags)
+    }
+
+    async fn search_options(&self, query: &str) -> Result<Vec<MealOption>> {
+        let search_pattern = format!("%{}%", query);
+
+        // ILIKE keeps SQLite's case-insensitive LIKE
+        let sql = format!(
+            "SELECT {} FROM meal_options
+             WHERE (name ILIKE $1 OR description ILIKE $1) AND {}
+             ORDER BY name",
+            COLUMNS, OFFERED
+        );
+        sqlx::query_as::<_, MealOption>(&sql)
+            .bind(search_pattern)
+            .fetch_all(self)
+            .await
+    }
+
+    async fn update_option(&self, id: i64, update: UpdateMealOption) -> Result<MealOption> {
+        let existing = self.get_option(id).await?.ok_or(sqlx::Error::RowNotFound)?;
+
+        let name = update.name.unwrap_or(existing.name);
+        let description = update.description.unwrap_or(existing.description);
+        let nutritional_notes = update
+            .nutritional_notes
+            .unwrap_or(existing.nutritional_notes);
+
+        let sql = format!(
+            "UPDATE meal_options SET name = $1, description = $2, nutritional_notes = $3
+             WHERE id = $4
+             RETURNING {}",
+            COLUMNS
+        );
+        sqlx::query_as::<_, MealOption>(&sql)
+            .bind(&name)
+            .bind(&description)
+            .bind(&nutritional_notes)
+            .bind(id)
+            .fetch_one(self)
+            .await
+    }
+
+    async fn set_option_archived(&self, id: i64, archived: bool) -> Result<MealOption> {
+        let sql = format!(
+            "UPDATE meal_options SET archived = $1 WHERE id = $2 RETURNING {}",
+            COLUMNS
+        );
+        sqlx::query_as::<_, MealOption>(&sql)
+            .bind(archived)
+            .bind(id)
+            .fetch_optional(self)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    async fn set_option_group(&self, id: i64, option_group: Option<String>) -> Result<MealOption> {
+        let option = self.get_option(id).await?.ok_or(sqlx::Error::RowNotFound)?;
+
+        if let Some(group) = &option_group {
+            let declared: bool = sqlx::query_scalar(
+                "SELECT EXISTS(SELECT 1 FROM meal_template_option_groups
+                               WHERE template_id = $1 AND name = $2)",
+            )
+            .bind(option.template_id)
+            .bind(group)
+            .fetch_one(self)
+            .await?;
+
+            if !declared {
+                return Err(sqlx::Error::Protocol(format!(
+                    "Option group '{}' is not declared on the template",
+                    group
+                )));
+            }
+        }
+
+        let sql = format!(
+            "UPDATE meal_options SET option_group = $1 WHERE id = $2 RETURNING {}",
+            COLUMNS
+        );
+        sqlx::query_as::<_, MealOption>(&sql)
+            .bind(&option_group)
+            .bind(id)
+            .fetch_one(self)
+            .await
+    }
+
+    async fn add_option_tags(&self, option_id: i64, tag_ids: Vec<i64>) -> Result<()> {
+        if self.get_option(option_id).await?.is_none() {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        check_tags_exist(self, &tag_ids).await?;
+
+        for tag_id in tag_ids {
+            sqlx::query(
+                "INSERT INTO meal_option_tags (meal_option_id, tag_id) VALUES ($1, $2)
+                 ON CONFLICT DO NOTHING",
+            )
+            .bind(option_id)
+            .bind(tag_id)
+            .execute(self)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn remove_option_tags(&self, option_id: i64, tag_ids: Vec<i64>) -> Result<()> {
+        sqlx::query("DELETE FROM meal_option_tags WHERE meal_option_id = $1 AND tag_id = ANY($2)")
+            .bind(option_id)
+            .bind(&tag_ids)
+            .execute(self)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn set_option_tags(&self, option_id: i64, tag_ids: Vec<i64>) -> Result<()> {
+        if self.get_option(option_id).await?.is_none() {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        check_tags_exist(self, &tag_ids).await?;
+
+        let mut tx = self.begin().await?;
+        sqlx::query("DELETE FROM meal_option_tags WHERE meal_option_id = $1")
+            .bind(option_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for tag_id in tag_ids {
+            sqlx::query("INSERT INTO meal_option_tags (meal_option_id, tag_id) VALUES ($1, $2)")
+                .bind(option_id)
+                .bind(tag_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn get_option_nutrients(&self, option_id: i64) -> Result<Option<NutrientProfile>> {
+        let sql = format!(
+            "SELECT {} FROM meal_option_nutrients WHERE meal_option_id = $1",
+            NUTRIENT_COLUMNS
+        );
+        sqlx::query_as::<_, NutrientProfile>(&sql)
+            .bind(option_id)
+            .fetch_optional(self)
+            .await
+    }
+
+    async fn set_option_nutrients(
+        &self,
+        option_id: i64,
+        nutrients: SetNutrientProfile,
+    ) -> Result<NutrientProfile> {
+        nutrients.validate().map_err(sqlx::Error::Protocol)?;
+
+        if self.get_option(option_id).await?.is_none() {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        let sql = format!(
+            "INSERT INTO meal_option_nutrients (meal_option_id, kcal, protein_g, carbs_g, fat_g, fiber_g)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT (meal_option_id) DO UPDATE SET
+                 kcal = excluded.kcal,
+                 protein_g = excluded.protein_g,
+                 carbs_g = excluded.carbs_g,
+                 fat_g = excluded.fat_g,
+                 fiber_g = excluded.fiber_g
+             RETURNING {}",
+            NUTRIENT_COLUMNS
+        );
+        sqlx::query_as::<_, NutrientProfile>(&sql)
+            .bind(option_id)
+            .bind(nutrients.kcal)
+            .bind(nutrients.protein_g)
+            .bind(nutrients.carbs_g)
+            .bind(nutrients.fat_g)
+            .bind(nutrients.fiber_g)
+            .fetch_one(self)
+            .await
+    }
+
+    async fn delete_option_nutrients(&self, option_id: i64) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM meal_option_nutrients WHERE meal_option_id = $1")
+            .bind(option_id)
+            .execute(self)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn get_option_delete_blockers(&self, id: i64) -> Result<Option<DeleteBlocked>> {
+        let Some(option) = self.get_option(id).await? else {
+            return Ok(None);
+        };
+
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(DISTINCT meal_entry_id) FROM (
+                 SELECT meal_entry_id, meal_option_id FROM meal_entry_options
+                 UNION ALL
+                 SELECT meal_entry_id, meal_option_id FROM meal_entry_planned_options
+             ) eo
+             WHERE meal_option_id = $1",
+        )
+        .bind(id)
+        .fetch_one(self)
+        .await?;
+
+        Ok((count > 0).then(|| DeleteBlocked {
+            kind: LibraryItemKind::Option,
+            id,
+            name: option.name,
+            blockers: vec![DeleteBlocker::MealEntries { count }],
+        }))
+    }
+
+    async fn delete_option(&self, id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM meal_options WHERE id = $1")
+            .bind(id)
+            .execute(self)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        Ok(())
+    }
+}
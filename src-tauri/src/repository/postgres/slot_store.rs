@@ -0,0 +1,126 @@
+use super::parse_db_string;
+use crate::models::{CreateMealSlot, MealSlot, SlotType, UpdateMealSlot};
+use crate::repository::MealSlotStore;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Result, Row};
+
+const COLUMNS: &str = "id, name, display_name, sort_order, active, created_at, updated_at";
+
+fn row_to_slot(row: &PgRow) -> Result<MealSlot> {
+    let name: String = row.try_get("name")?;
+
+    Ok(MealSlot {
+        id: row.try_get("id")?,
+        name: parse_db_string(SlotType::from_db_string(&name))?,
+        display_name: row.try_get("display_name")?,
+        sort_order: row.try_get("sort_order")?,
+        active: row.try_get("active")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+impl MealSlotStore for PgPool {
+    async fn create_slot(&self, slot: CreateMealSlot) -> Result<MealSlot> {
+        slot.validate().map_err(sqlx::Error::Protocol)?;
+
+        let sql = format!(
+            "INSERT INTO meal_slots (name, display_name, sort_order, active)
+             VALUES ($1, $2, $3, $4)
+             RETURNING {}",
+            COLUMNS
+        );
+        let row = sqlx::query(&sql)
+            .bind(&slot.name)
+            .bind(&slot.display_name)
+            .bind(slot.sort_order)
+            .bind(slot.active.unwrap_or(true))
+            .fetch_one(self)
+            .await?;
+
+        row_to_slot(&row)
+    }
+
+    async fn get_slot(&self, id: i64) -> Result<Option<MealSlot>> {
+        let sql = format!("SELECT {} FROM meal_slots WHERE id = $1", COLUMNS);
+        let row = sqlx::query(&sql).bind(id).fetch_optional(self).await?;
+
+        row.as_ref().map(row_to_slot).transpose()
+    }
+
+    async fn get_slot_by_name(&self, name: &str) -> Result<Option<MealSlot>> {
+        let sql = format!("SELECT {} FROM meal_slots WHERE name = $1", COLUMNS);
+        let row = sqlx::query(&sql).bind(name).fetch_optional(self).await?;
+
+        row.as_ref().map(row_to_slot).transpose()
+    }
+
+    async fn get_slots(&self) -> Result<Vec<MealSlot>> {
+        let sql = format!("SELECT {} FROM meal_slots ORDER BY sort_order, id", COLUMNS);
+        let rows = sqlx::query(&sql).fetch_all(self).await?;
+
+        rows.iter().map(row_to_slot).collect()
+    }
+
+    async fn get_active_slots(&self) -> Result<Vec<MealSlot>> {
+        let sql = format!(
+            "SELECT {} FROM meal_slots WHERE active ORDER BY sort_order, id",
+            COLUMNS
+        );
+        let rows = sqlx::query(&sql).fetch_all(self).await?;
+
+        rows.iter().map(row_to_slot).collect()
+    }
+
+    async fn update_slot(&self, id: i64, update: UpdateMealSlot) -> Result<MealSlot> {
+        update.validate().map_err(sqlx::Error::Protocol)?;
+
+        let existing = self.get_slot(id).await?.ok_or(sqlx::Error::RowNotFound)?;
+
+        let sql = format!(
+            "UPDATE meal_slots
+             SET display_name = $1, sort_order = $2, active = $3
+             WHERE id = $4
+             RETURNING {}",
+            COLUMNS
+        );
+        let row = sqlx::query(&sql)
+            .bind(update.display_name.unwrap_or(existing.display_name))
+            .bind(update.sort_order.unwrap_or(existing.sort_order))
+            .bind(update.active.unwrap_or(existing.active))
+            .bind(id)
+            .fetch_one(self)
+            .await?;
+
+        row_to_slot(&row)
+    }
+
+    async fn delete_slot(&self, id: i64) -> Result<bool> {
+        let Some(slot) = self.get_slot(id).await? else {
+            return Ok(false);
+        };
+
+        let (entries, templates): (i64, i64) = sqlx::query_as(
+            "SELECT
+                (SELECT COUNT(*) FROM meal_entries WHERE slot_type = $1),
+                (SELECT COUNT(*) FROM meal_template_slots WHERE slot_type = $1)",
+        )
+        .bind(slot.name.to_db_string())
+        .fetch_one(self)
+        .await?;
+
+        if entries > 0 || templates > 0 {
+            return Err(sqlx::Error::Protocol(format!(
+                "Meal slot '{}' is used by {} entries and {} templates; deactivate it instead",
+                slot.name, entries, templates
+            )));
+        }
+
+        let result = sqlx::query("DELETE FROM meal_slots WHERE id = $1")
+            .bind(id)
+            .execute(self)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
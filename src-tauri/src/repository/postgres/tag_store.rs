@@ -0,0 +1,243 @@
+use super::parse_db_string;
+use crate::models::{
+    CreateTag, DeleteBlocked, DeleteBlocker, LibraryItemKind, Tag, TagCategory, UpdateTag,
+};
+use crate::repository::TagStore;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Result, Row};
+
+const COLUMNS: &str =
+    "id, name, display_name, category, weekly_suggestion, parent_tag_id, archived, created_at";
+
+fn row_to_tag(row: &PgRow) -> Result<Tag> {
+    let category: String = row.try_get("category")?;
+
+    Ok(Tag {
+        id: row.try_get("id")?,
+        name: row.try_get("name")?,
+        display_name: row.try_get("display_name")?,
+        category: parse_db_string(TagCategory::from_db_string(&category))?,
+        weekly_suggestion: row.try_get("weekly_suggestion")?,
+        parent_tag_id: row.try_get("parent_tag_id")?,
+        archived: row.try_get("archived")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+/// Fetch tags matching a WHERE clause, by name
+async fn fetch_tags(pool: &PgPool, filter: &str, bind: Option<&str>) -> Result<Vec<Tag>> {
+    let sql = format!(
+        "SELECT {} FROM tags WHERE {} ORDER BY name",
+        COLUMNS, filter
+    );
+    let mut query = sqlx::query(&sql);
+    if let Some(value) = bind {
+        query = query.bind(value);
+    }
+    let rows = query.fetch_all(pool).await?;
+
+    rows.iter().map(row_to_tag).collect()
+}
+
+impl TagStore for PgPool {
+    async fn create_tag(&self, tag: CreateTag) -> Result<Tag> {
+        tag.validate().map_err(sqlx::Error::Protocol)?;
+
+        let sql = format!(
+            "INSERT INTO tags (name, display_name, category, weekly_suggestion, parent_tag_id)
+             VALUES ($1, $2, $3, $4, $5)
+             RETURNING {}",
+            COLUMNS
+        );
+        let row = sqlx::query(&sql)
+            .bind(&tag.name)
+            .bind(&tag.display_name)
+            .bind(tag.category.to_db_string())
+            .bind(tag.weekly_suggestion)
+            .bind(tag.parent_tag_id)
+            .fetch_one(self)
+            .await?;
+
+        row_to_tag(&row)
+    }
+
+    async fn get_tag(&self, id: i64) -> Result<Option<Tag>> {
+        let sql = format!("SELECT {} FROM tags WHERE id = $1", COLUMNS);
+        let row = sqlx::query(&sql).bind(id).fetch_optional(self).await?;
+
+        row.as_ref().map(row_to_tag).transpose()
+    }
+
+    async fn get_tag_by_name(&self, name: &str) -> Result<Option<Tag>> {
+        let sql = format!("SELECT {} FROM tags WHERE name = $1", COLUMNS);
+        let row = sqlx::query(&sql).bind(name).fetch_optional(self).await?;
+
+        row.as_ref().map(row_to_tag).transpose()
+    }
+
+    async fn get_tags(&self) -> Result<Vec<Tag>> {
+        fetch_tags(self, "NOT archived", None).await
+    }
+
+    async fn get_archived_tags(&self) -> Result<Vec<Tag>> {
+        fetch_tags(self, "archived", None).await
+    }
+
+    async fn get_tags_by_category(&self, category: TagCategory) -> Result<Vec<Tag>> {
+        fetch_tags(
+            self,
+            "category = $1 AND NOT archived",
+            Some(category.to_db_string()),
+        )
+        .await
+    }
+
+    async fn get_child_tags(&self, parent_id: i64) -> Result<Vec<Tag>> {
+        let sql = format!(
+            "SELECT {} FROM tags WHERE parent_tag_id = $1 AND NOT archived ORDER BY name",
+            COLUMNS
+        );
+        let rows = sqlx::query(&sql).bind(parent_id).fetch_all(self).await?;
+
+        rows.iter().map(row_to_tag).collect()
+    }
+
+    async fn get_option_tags_with_ancestors(&self, meal_option_id: i64) -> Result<Vec<Tag>> {
+        let sql = format!(
+            "WITH RECURSIVE lineage(id) AS (
+                 SELECT tag_id FROM meal_option_tags WHERE meal_option_id = $1
+                 UNION
+                 SELECT t.parent_tag_id
+                 FROM tags t
+                 JOIN lineage l ON t.id = l.id
+                 WHERE t.parent_tag_id IS NOT NULL
+             )
+             SELECT {} FROM tags
+             WHERE id IN (SELECT id FROM lineage)
+             ORDER BY name",
+            COLUMNS
+        );
+        let rows = sqlx::query(&sql)
+            .bind(meal_option_id)
+            .fetch_all(self)
+            .await?;
+
+        rows.iter().map(row_to_tag).collect()
+    }
+
+    async fn update_tag(&self, id: i64, update: UpdateTag) -> Result<Tag> {
+        let existing = self.get_tag(id).await?.ok_or(sqlx::Error::RowNotFound)?;
+
+        let display_name = update.display_name.unwrap_or(existing.display_name);
+        let category = update.category.unwrap_or(existing.category);
+        let weekly_suggestion = update
+            .weekly_suggestion
+            .unwrap_or(existing.weekly_suggestion);
+        let parent_tag_id = update.parent_tag_id.unwrap_or(existing.parent_tag_id);
+
+        // Usage roll-ups walk parent_tag_id, so the hierarchy must stay acyclic
+        if let Some(parent_id) = parent_tag_id {
+            let creates_cycle: bool = sqlx::query_scalar(
+                "WITH RECURSIVE subtree(id) AS (
+                     SELECT $1::BIGINT
+                     UNION
+                     SELECT t.id FROM tags t JOIN subtree s ON t.parent_tag_id = s.id
+                 )
+                 SELECT EXISTS(SELECT 1 FROM subtree WHERE id = $2)",
+            )
+            .bind(id)
+            .bind(parent_id)
+            .fetch_one(self)
+            .await?;
+
+            if creates_cycle {
+                return Err(sqlx::Error::Protocol(
+                    "A tag cannot be nested under itself or one of its descendants".to_string(),
+                ));
+            }
+        }
+
+        let sql = format!(
+            "UPDATE tags
+             SET display_name = $1, category = $2, weekly_suggestion = $3, parent_tag_id = $4
+             WHERE id = $5
+             RETURNING {}",
+            COLUMNS
+        );
+        let row = sqlx::query(&sql)
+            .bind(&display_name)
+            .bind(category.to_db_string())
+            .bind(weekly_suggestion)
+            .bind(parent_tag_id)
+            .bind(id)
+            .fetch_one(self)
+            .await?;
+
+        row_to_tag(&row)
+    }
+
+    async fn set_tag_archived(&self, id: i64, archived: bool) -> Result<Tag> {
+        let sql = format!(
+            "UPDATE tags SET archived = $1 WHERE id = $2 RETURNING {}",
+            COLUMNS
+        );
+        let row = sqlx::query(&sql)
+            .bind(archived)
+            .bind(id)
+            .fetch_optional(self)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        row_to_tag(&row)
+    }
+
+    async fn get_tag_delete_blockers(&self, id: i64) -> Result<Option<DeleteBlocked>> {
+        let Some(tag) = self.get_tag(id).await? else {
+            return Ok(None);
+        };
+
+        let mut blockers = Vec::new();
+
+        let option_names: Vec<String> = sqlx::query_scalar(
+            "SELECT mo.name FROM meal_option_tags mot
+             JOIN meal_options mo ON mo.id = mot.meal_option_id
+             WHERE mot.tag_id = $1
+             ORDER BY mo.name",
+        )
+        .bind(id)
+        .fetch_all(self)
+        .await?;
+        if !option_names.is_empty() {
+            blockers.push(DeleteBlocker::TaggedOptions { option_names });
+        }
+
+        let tag_names: Vec<String> =
+            sqlx::query_scalar("SELECT name FROM tags WHERE parent_tag_id = $1 ORDER BY name")
+                .bind(id)
+                .fetch_all(self)
+                .await?;
+        if !tag_names.is_empty() {
+            blockers.push(DeleteBlocker::ChildTags { tag_names });
+        }
+
+        if blockers.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(DeleteBlocked {
+            kind: LibraryItemKind::Tag,
+            id,
+            name: tag.name,
+            blockers,
+        }))
+    }
+
+    async fn delete_tag(&self, id: i64) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM tags WHERE id = $1")
+            .bind(id)
+            .execute(self)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,333 @@
+use super::{parse_db_string, split_list};
+use crate::models::{
+    CreateMealTemplate, DeleteBlocked, DeleteBlocker, LibraryItemKind, LocationType, MealTemplate,
+    OptionGroup, SlotType, UpdateMealTemplate,
+};
+use crate::repository::MealTemplateStore;
+use sqlx::postgres::PgRow;
+use sqlx::{PgConnection, PgPool, Result, Row};
+
+/// Columns selected for a MealTemplate from `meal_templates mt`
+/// Compatible slots are aggregated from meal_template_slots in day order
+const COLUMNS: &str = r#"
+    mt.id, mt.name, mt.description, mt.location_type, mt.weekly_limit, mt.archived,
+    mt.created_at, mt.updated_at,
+    (SELECT string_agg(mts.slot_type, ',' ORDER BY ms.sort_order, ms.id)
+     FROM meal_template_slots mts
+     JOIN meal_slots ms ON ms.name = mts.slot_type
+     WHERE mts.template_id = mt.id) AS compatible_slots
+"#;
+
+fn row_to_template(row: &PgRow) -> Result<MealTemplate> {
+    let location_type: String = row.try_get("location_type")?;
+    let compatible_slots = split_list(row.try_get("compatible_slots")?)
+        .iter()
+        .map(|slot| parse_db_string(SlotType::from_db_string(slot)))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(MealTemplate {
+        id: row.try_get("id")?,
+        name: row.try_get("name")?,
+        description: row.try_get("description")?,
+        compatible_slots,
+        location_type: parse_db_string(LocationType::from_db_string(&location_type))?,
+        weekly_limit: row.try_get("weekly_limit")?,
+        archived: row.try_get("archived")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+/// Fetch templates matching a WHERE clause (and optional join), by name
+async fn fetch_templates(
+    pool: &PgPool,
+    join_and_filter: &str,
+    binds: &[&str],
+) -> Result<Vec<MealTemplate>> {
+    let sql = format!(
+        "SELECT {} FROM meal_templates mt {} ORDER BY mt.name",
+        COLUMNS, join_and_filter
+    );
+    let mut query = sqlx::query(&sql);
+    for value in binds {
+        query = query.bind(*value);
+    }
+    let rows = query.fetch_all(pool).await?;
+
+    rows.iter().map(row_to_template).collect()
+}
+
+/// Replace the compatible slots of a template
+/// Unknown slots fail the foreign key on meal_slots
+async fn set_slots(conn: &mut PgConnection, template_id: i64, slots: &[SlotType]) -> Result<()> {
+    sqlx::query("DELETE FROM meal_template_slots WHERE template_id = $1")
+        .bind(template_id)
+        .execute(&mut *conn)
+        .await?;
+
+    for slot in slots {
+        sqlx::query(
+            "INSERT INTO meal_template_slots (template_id, slot_type) VALUES ($1, $2)
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(template_id)
+        .bind(slot.to_db_string())
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+impl MealTemplateStore for PgPool {
+    async fn create_template(&self, template: CreateMealTemplate) -> Result<MealTemplate> {
+        template.validate().map_err(sqlx::Error::Protocol)?;
+
+        let mut tx = self.begin().await?;
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO meal_templates (name, description, location_type, weekly_limit)
+             VALUES ($1, $2, $3, $4)
+             RETURNING id",
+        )
+        .bind(&template.name)
+        .bind(&template.description)
+        .bind(template.location_type.to_db_string())
+        .bind(template.weekly_limit)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        set_slots(&mut tx, id, &template.compatible_slots).await?;
+        tx.commit().await?;
+
+        self.get_template(id).await?.ok_or(sqlx::Error::RowNotFound)
+    }
+
+    async fn get_template(&self, id: i64) -> Result<Option<MealTemplate>> {
+        let sql = format!("SELECT {} FROM meal_templates mt WHERE mt.id = $1", COLUMNS);
+        let row = sqlx::query(&sql).bind(id).fetch_optional(self).await?;
+
+        row.as_ref().map(row_to_template).transpose()
+    }
+
+    async fn get_templates(&self) -> Result<Vec<MealTemplate>> {
+        fetch_templates(self, "WHERE NOT mt.archived", &[]).await
+    }
+
+    async fn get_archived_templates(&self) -> Result<Vec<MealTemplate>> {
+        fetch_templates(self, "WHERE mt.archived", &[]).await
+    }
+
+    async fn get_templates_by_location(&self, location: LocationType) -> Result<Vec<MealTemplate>> {
+        fetch_templates(
+            self,
+            "WHERE (mt.location_type = $1 OR mt.location_type = 'any') AND NOT mt.archived",
+            &[location.to_db_string()],
+        )
+        .await
+    }
+
+    async fn get_templates_by_slot(&self, slot: &SlotType) -> Result<Vec<MealTemplate>> {
+        fetch_templates(
+            self,
+            "JOIN meal_template_slots ts ON ts.template_id = mt.id AND ts.slot_type = $1
+             WHERE NOT mt.archived",
+            &[slot.to_db_string()],
+        )
+        .await
+    }
+
+    async fn get_templates_by_slot_and_location(
+        &self,
+        slot: &SlotType,
+        location: LocationType,
+    ) -> Result<Vec<MealTemplate>> {
+        fetch_templates(
+            self,
+            "JOIN meal_template_slots ts ON ts.template_id = mt.id AND ts.slot_type = $1
+             WHERE (mt.location_type = $2 OR mt.location_type = 'any' OR $2 = 'any')
+               AND NOT mt.archived",
+            &[slot.to_db_string(), location.to_db_string()],
+        )
+        .await
+    }
+
+    async fn search_templates(
+        &self,
+        query: &str,
+        slot: Option<&SlotType>,
+    ) -> Result<Vec<MealTemplate>> {
+        let search_pattern = format!("%{}%", query);
+
+        // ILIKE keeps SQLite's case-insensitive LIKE
+        let sql = format!(
+            "SELECT {} FROM meal_templates mt
+             WHERE (mt.name ILIKE $1 OR mt.description ILIKE $1)
+               AND NOT mt.archived
+               AND ($2::TEXT IS NULL OR EXISTS (
+                   SELECT 1 FROM meal_template_slots ts
+                   WHERE ts.template_id = mt.id AND ts.slot_type = $2))
+             ORDER BY mt.name",
+            COLUMNS
+        );
+        let rows = sqlx::query(&sql)
+            .bind(search_pattern)
+            .bind(slot.map(|s| s.to_db_string()))
+            .fetch_all(self)
+            .await?;
+
+        rows.iter().map(row_to_template).collect()
+    }
+
+    async fn update_template(&self, id: i64, update: UpdateMealTemplate) -> Result<MealTemplate> {
+        let existing = self
+            .get_template(id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        let name = update.name.unwrap_or(existing.name);
+        let description = update.description.unwrap_or(existing.description);
+        let location_type = update.location_type.unwrap_or(existing.location_type);
+        let weekly_limit = update.weekly_limit.unwrap_or(existing.weekly_limit);
+
+        let mut tx = self.begin().await?;
+        sqlx::query(
+            "UPDATE meal_templates
+             SET name = $1, description = $2, location_type = $3, weekly_limit = $4
+             WHERE id = $5",
+        )
+        .bind(&name)
+        .bind(&description)
+        .bind(location_type.to_db_string())
+        .bind(weekly_limit)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        if let Some(slots) = &update.compatible_slots {
+            set_slots(&mut tx, id, slots).await?;
+        }
+        tx.commit().await?;
+
+        self.get_template(id).await?.ok_or(sqlx::Error::RowNotFound)
+    }
+
+    async fn set_template_archived(&self, id: i64, archived: bool) -> Result<MealTemplate> {
+        let result = sqlx::query("UPDATE meal_templates SET archived = $1 WHERE id = $2")
+            .bind(archived)
+            .bind(id)
+            .execute(self)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        self.get_template(id).await?.ok_or(sqlx::Error::RowNotFound)
+    }
+
+    async fn get_option_groups(&self, template_id: i64) -> Result<Vec<OptionGroup>> {
+        let rows = sqlx::query(
+            "SELECT name, min_choices, max_choices
+             FROM meal_template_option_groups
+             WHERE template_id = $1
+             ORDER BY sort_order, name",
+        )
+        .bind(template_id)
+        .fetch_all(self)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(OptionGroup {
+                    name: row.try_get("name")?,
+                    min_choices: row.try_get("min_choices")?,
+                    max_choices: row.try_get("max_choices")?,
+                })
+            })
+            .collect()
+    }
+
+    async fn set_option_groups(
+        &self,
+        template_id: i64,
+        groups: Vec<OptionGroup>,
+    ) -> Result<Vec<OptionGroup>> {
+        for group in &groups {
+            group.validate().map_err(sqlx::Error::Protocol)?;
+        }
+
+        if self.get_template(template_id).await?.is_none() {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        let mut tx = self.begin().await?;
+        sqlx::query("DELETE FROM meal_template_option_groups WHERE template_id = $1")
+            .bind(template_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for (sort_order, group) in groups.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO meal_template_option_groups
+                     (template_id, name, min_choices, max_choices, sort_order)
+                 VALUES ($1, $2, $3, $4, $5)",
+            )
+            .bind(template_id)
+            .bind(&group.name)
+            .bind(group.min_choices)
+            .bind(group.max_choices)
+            .bind(sort_order as i32)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query(
+            "UPDATE meal_options SET option_group = NULL
+             WHERE template_id = $1
+               AND option_group NOT IN (
+                   SELECT name FROM meal_template_option_groups WHERE template_id = $1
+               )",
+        )
+        .bind(template_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        self.get_option_groups(template_id).await
+    }
+
+    async fn get_template_delete_blockers(&self, id: i64) -> Result<Option<DeleteBlocked>> {
+        let Some(template) = self.get_template(id).await? else {
+            return Ok(None);
+        };
+
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(DISTINCT eo.meal_entry_id) FROM (
+                 SELECT meal_entry_id, meal_option_id FROM meal_entry_options
+                 UNION ALL
+                 SELECT meal_entry_id, meal_option_id FROM meal_entry_planned_options
+             ) eo
+             JOIN meal_options mo ON mo.id = eo.meal_option_id
+             WHERE mo.template_id = $1",
+        )
+        .bind(id)
+        .fetch_one(self)
+        .await?;
+
+        Ok((count > 0).then(|| DeleteBlocked {
+            kind: LibraryItemKind::Template,
+            id,
+            name: template.name,
+            blockers: vec![DeleteBlocker::MealEntries { count }],
+        }))
+    }
+
+    async fn delete_template(&self, id: i64) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM meal_templates WHERE id = $1")
+            .bind(id)
+            .execute(self)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
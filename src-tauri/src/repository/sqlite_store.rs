@@ -0,0 +1,347 @@
+// SQLite repository traits
+// The default backend: every method delegates to the SQLite repositories
+
+use super::store::{MealEntryStore, MealOptionStore, MealSlotStore, MealTemplateStore, TagStore};
+use super::{
+    MealEntryRepository, MealOptionRepository, MealSlotRepository, MealTemplateRepository,
+    TagRepository,
+};
+use crate::models::{
+    CreateMealEntry, CreateMealOption, CreateMealSlot, CreateMealTemplate, CreateOffPlanEntry,
+    CreateTag, DeleteBlocked, EntryStatus, LocationType, LogActualMeal, MealEntry, MealOption,
+    MealOptionWithTags, MealSlot, MealTemplate, NutrientProfile, OptionGroup, SetNutrientProfile,
+    SlotType, Tag, TagCategory, UpdateMealEntry, UpdateMealOption, UpdateMealSlot,
+    UpdateMealTemplate, UpdateTag, WeeklyTagUsage, WeeklyTagUsageNode, WeeklyTemplateUsage,
+    WeeklyUsage,
+};
+use chrono::NaiveDate;
+use sqlx::{Result, SqlitePool};
+
+impl MealSlotStore for SqlitePool {
+    async fn create_slot(&self, slot: CreateMealSlot) -> Result<MealSlot> {
+        MealSlotRepository::create(self, slot).await
+    }
+
+    async fn get_slot(&self, id: i64) -> Result<Option<MealSlot>> {
+        MealSlotRepository::get_by_id(self, id).await
+    }
+
+    async fn get_slot_by_name(&self, name: &str) -> Result<Option<MealSlot>> {
+        MealSlotRepository::get_by_name(self, name).await
+    }
+
+    async fn get_slots(&self) -> Result<Vec<MealSlot>> {
+        MealSlotRepository::get_all(self).await
+    }
+
+    async fn get_active_slots(&self) -> Result<Vec<MealSlot>> {
+        MealSlotRepository::get_active(self).await
+    }
+
+    async fn update_slot(&self, id: i64, update: UpdateMealSlot) -> Result<MealSlot> {
+        MealSlotRepository::update(self, id, update).await
+    }
+
+    async fn delete_slot(&self, id: i64) -> Result<bool> {
+        MealSlotRepository::delete(self, id).await
+    }
+}
+
+impl TagStore for SqlitePool {
+    async fn create_tag(&self, tag: CreateTag) -> Result<Tag> {
+        TagRepository::create(self, tag).await
+    }
+
+    async fn get_tag(&self, id: i64) -> Result<Option<Tag>> {
+        TagRepository::get_by_id(self, id).await
+    }
+
+    async fn get_tag_by_name(&self, name: &str) -> Result<Option<Tag>> {
+        TagRepository::get_by_name(self, name).await
+    }
+
+    async fn get_tags(&self) -> Result<Vec<Tag>> {
+        TagRepository::get_all(self).await
+    }
+
+    async fn get_archived_tags(&self) -> Result<Vec<Tag>> {
+        TagRepository::get_archived(self).await
+    }
+
+    async fn get_tags_by_category(&self, category: TagCategory) -> Result<Vec<Tag>> {
+        TagRepository::get_by_category(self, category).await
+    }
+
+    async fn get_child_tags(&self, parent_id: i64) -> Result<Vec<Tag>> {
+        TagRepository::get_children(self, parent_id).await
+    }
+
+    async fn get_option_tags_with_ancestors(&self, meal_option_id: i64) -> Result<Vec<Tag>> {
+        TagRepository::get_option_tags_with_ancestors(self, meal_option_id).await
+    }
+
+    async fn update_tag(&self, id: i64, update: UpdateTag) -> Result<Tag> {
+        TagRepository::update(self, id, update).await
+    }
+
+    async fn set_tag_archived(&self, id: i64, archived: bool) -> Result<Tag> {
+        TagRepository::set_archived(self, id, archived).await
+    }
+
+    async fn get_tag_delete_blockers(&self, id: i64) -> Result<Option<DeleteBlocked>> {
+        TagRepository::get_delete_blockers(self, id).await
+    }
+
+    async fn delete_tag(&self, id: i64) -> Result<bool> {
+        TagRepository::delete(self, id).await
+    }
+}
+
+impl MealTemplateStore for SqlitePool {
+    async fn create_template(&self, template: CreateMealTemplate) -> Result<MealTemplate> {
+        MealTemplateRepository::create(self, template).await
+    }
+
+    async fn get_template(&self, id: i64) -> Result<Option<MealTemplate>> {
+        MealTemplateRepository::get_by_id(self, id).await
+    }
+
+    async fn get_templates(&self) -> Result<Vec<MealTemplate>> {
+        MealTemplateRepository::get_all(self).await
+    }
+
+    async fn get_archived_templates(&self) -> Result<Vec<MealTemplate>> {
+        MealTemplateRepository::get_archived(self).await
+    }
+
+    async fn get_templates_by_location(&self, location: LocationType) -> Result<Vec<MealTemplate>> {
+        MealTemplateRepository::get_by_location(self, location).await
+    }
+
+    async fn get_templates_by_slot(&self, slot: &SlotType) -> Result<Vec<MealTemplate>> {
+        MealTemplateRepository::get_by_slot(self, slot).await
+    }
+
+    async fn get_templates_by_slot_and_location(
+        &self,
+        slot: &SlotType,
+        location: LocationType,
+    ) -> Result<Vec<MealTemplate>> {
+        MealTemplateRepository::get_by_slot_and_location(self, slot, location).await
+    }
+
+    async fn search_templates(
+        &self,
+        query: &str,
+        slot: Option<&SlotType>,
+    ) -> Result<Vec<MealTemplate>> {
+        MealTemplateRepository::search(self, query, slot).await
+    }
+
+    async fn update_template(&self, id: i64, update: UpdateMealTemplate) -> Result<MealTemplate> {
+        MealTemplateRepository::update(self, id, update).await
+    }
+
+    async fn set_template_archived(&self, id: i64, archived: bool) -> Result<MealTemplate> {
+        MealTemplateRepository::set_archived(self, id, archived).await
+    }
+
+    async fn get_option_groups(&self, template_id: i64) -> Result<Vec<OptionGroup>> {
+        MealTemplateRepository::get_option_groups(self, template_id).await
+    }
+
+    async fn set_option_groups(
+        &self,
+        template_id: i64,
+        groups: Vec<OptionGroup>,
+    ) -> Result<Vec<OptionGroup>> {
+        MealTemplateRepository::set_option_groups(self, template_id, groups).await
+    }
+
+    async fn get_template_delete_blockers(&self, id: i64) -> Result<Option<DeleteBlocked>> {
+        MealTemplateRepository::get_delete_blockers(self, id).await
+    }
+
+    async fn delete_template(&self, id: i64) -> Result<bool> {
+        MealTemplateRepository::delete(self, id).await
+    }
+}
+
+impl MealOptionStore for SqlitePool {
+    async fn create_option(&self, option: CreateMealOption) -> Result<MealOption> {
+        MealOptionRepository::create(self, option).await
+    }
+
+    async fn get_option(&self, id: i64) -> Result<Option<MealOption>> {
+        MealOptionRepository::get_by_id(self, id).await
+    }
+
+    async fn get_options(&self) -> Result<Vec<MealOption>> {
+        MealOptionRepository::get_all(self).await
+    }
+
+    async fn get_archived_options(&self) -> Result<Vec<MealOption>> {
+        MealOptionRepository::get_archived(self).await
+    }
+
+    async fn get_options_by_template(&self, template_id: i64) -> Result<Vec<MealOption>> {
+        MealOptionRepository::get_by_template_id(self, template_id).await
+    }
+
+    async fn get_option_with_tags(&self, id: i64) -> Result<Option<MealOptionWithTags>> {
+        MealOptionRepository::get_with_tags(self, id).await
+    }
+
+    async fn get_options_by_template_with_tags(
+        &self,
+        template_id: i64,
+    ) -> Result<Vec<MealOptionWithTags>> {
+        MealOptionRepository::get_by_template_with_tags(self, template_id).await
+    }
+
+    async fn search_options(&self, query: &str) -> Result<Vec<MealOption>> {
+        MealOptionRepository::search(self, query).await
+    }
+
+    async fn update_option(&self, id: i64, update: UpdateMealOption) -> Result<MealOption> {
+        MealOptionRepository::update(self, id, update).await
+    }
+
+    async fn set_option_archived(&self, id: i64, archived: bool) -> Result<MealOption> {
+        MealOptionRepository::set_archived(self, id, archived).await
+    }
+
+    async fn set_option_group(&self, id: i64, option_group: Option<String>) -> Result<MealOption> {
+        MealOptionRepository::set_group(self, id, option_group).await
+    }
+
+    async fn add_option_tags(&self, option_id: i64, tag_ids: Vec<i64>) -> Result<()> {
+        MealOptionRepository::add_tags(self, option_id, tag_ids).await
+    }
+
+    async fn remove_option_tags(&self, option_id: i64, tag_ids: Vec<i64>) -> Result<()> {
+        MealOptionRepository::remove_tags(self, option_id, tag_ids).await
+    }
+
+    async fn set_option_tags(&self, option_id: i64, tag_ids: Vec<i64>) -> Result<()> {
+        MealOptionRepository::set_tags(self, option_id, tag_ids).await
+    }
+
+    async fn get_option_nutrients(&self, option_id: i64) -> Result<Option<NutrientProfile>> {
+        MealOptionRepository::get_nutrients(self, option_id).await
+    }
+
+    async fn set_option_nutrients(
+        &self,
+        option_id: i64,
+        nutrients: SetNutrientProfile,
+    ) -> Result<NutrientProfile> {
+        MealOptionRepository::set_nutrients(self, option_id, nutrients).await
+    }
+
+    async fn delete_option_nutrients(&self, option_id: i64) -> Result<bool> {
+        MealOptionRepository::delete_nutrients(self, option_id).await
+    }
+
+    async fn get_option_delete_blockers(&self, id: i64) -> Result<Option<DeleteBlocked>> {
+        MealOptionRepository::get_delete_blockers(self, id).await
+    }
+
+    async fn delete_option(&self, id: i64) -> Result<()> {
+        MealOptionRepository::delete(self, id).await
+    }
+}
+
+impl MealEntryStore for SqlitePool {
+    async fn create_entry(&self, entry: CreateMealEntry) -> Result<MealEntry> {
+        MealEntryRepository::create(self, entry).await
+    }
+
+    async fn create_off_plan_entry(&self, entry: CreateOffPlanEntry) -> Result<MealEntry> {
+        MealEntryRepository::create_off_plan(self, entry).await
+    }
+
+    async fn get_entry(&self, id: i64) -> Result<Option<MealEntry>> {
+        MealEntryRepository::get_by_id(self, id).await
+    }
+
+    async fn get_entries_by_date(&self, date: NaiveDate) -> Result<Vec<MealEntry>> {
+        MealEntryRepository::get_by_date(self, date).await
+    }
+
+    async fn get_entries_by_date_range(
+        &self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<MealEntry>> {
+        MealEntryRepository::get_by_date_range(self, start_date, end_date).await
+    }
+
+    async fn get_entries_by_date_and_slot(
+        &self,
+        date: NaiveDate,
+        slot: &SlotType,
+    ) -> Result<Vec<MealEntry>> {
+        MealEntryRepository::get_by_date_and_slot(self, date, slot).await
+    }
+
+    async fn get_entries_by_status(&self, status: EntryStatus) -> Result<Vec<MealEntry>> {
+        MealEntryRepository::get_by_status(self, status).await
+    }
+
+    async fn get_entries_by_option(&self, meal_option_id: i64) -> Result<Vec<MealEntry>> {
+        MealEntryRepository::get_by_meal_option(self, meal_option_id).await
+    }
+
+    async fn get_recent_entries(&self, limit: i32) -> Result<Vec<MealEntry>> {
+        MealEntryRepository::get_recent_entries(self, limit).await
+    }
+
+    async fn update_entry(&self, id: i64, update: UpdateMealEntry) -> Result<MealEntry> {
+        MealEntryRepository::update(self, id, update).await
+    }
+
+    async fn log_actual(&self, id: i64, actual: LogActualMeal) -> Result<MealEntry> {
+        MealEntryRepository::log_actual(self, id, actual).await
+    }
+
+    async fn delete_entry(&self, id: i64) -> Result<()> {
+        MealEntryRepository::delete(self, id).await
+    }
+
+    async fn get_weekly_usage(
+        &self,
+        meal_option_id: i64,
+        week: &str,
+    ) -> Result<Option<WeeklyUsage>> {
+        MealEntryRepository::get_weekly_usage(self, meal_option_id, week).await
+    }
+
+    async fn get_weekly_template_usage(
+        &self,
+        template_id: i64,
+        week: &str,
+    ) -> Result<Option<WeeklyTemplateUsage>> {
+        MealEntryRepository::get_weekly_template_usage(self, template_id, week).await
+    }
+
+    async fn get_all_weekly_template_usage(&self, week: &str) -> Result<Vec<WeeklyTemplateUsage>> {
+        MealEntryRepository::get_all_weekly_template_usage(self, week).await
+    }
+
+    async fn get_weekly_tag_usage(
+        &self,
+        tag_id: i64,
+        week: &str,
+    ) -> Result<Option<WeeklyTagUsage>> {
+        MealEntryRepository::get_weekly_tag_usage(self, tag_id, week).await
+    }
+
+    async fn get_weekly_tag_usage_breakdown(
+        &self,
+        tag_id: i64,
+        week: &str,
+    ) -> Result<Vec<WeeklyTagUsageNode>> {
+        MealEntryRepository::get_weekly_tag_usage_breakdown(self, tag_id, week).await
+    }
+}
@@ -0,0 +1,348 @@
+// Repository traits
+// Backend-independent data access for slots, tags, templates, options and entries;
+// implemented for SqlitePool (the default) and PgPool (with the `postgres` feature)
+
+use crate::models::{
+    CreateMealEntry, CreateMealOption, CreateMealSlot, CreateMealTemplate, CreateOffPlanEntry,
+    CreateTag, DeleteBlocked, EntryStatus, LocationType, LogActualMeal, MealEntry, MealOption,
+    MealOptionWithTags, MealSlot, MealTemplate, NutrientProfile, OptionGroup, SetNutrientProfile,
+    SlotType, Tag, TagCategory, UpdateMealEntry, UpdateMealOption, UpdateMealSlot,
+    UpdateMealTemplate, UpdateTag, WeeklyTagUsage, WeeklyTagUsageNode, WeeklyTemplateUsage,
+    WeeklyUsage,
+};
+use chrono::NaiveDate;
+use sqlx::Result;
+use std::future::Future;
+
+/// Meal slot storage, see `MealSlotRepository`
+pub trait MealSlotStore: Send + Sync {
+    fn create_slot(&self, slot: CreateMealSlot) -> impl Future<Output = Result<MealSlot>> + Send;
+
+    fn get_slot(&self, id: i64) -> impl Future<Output = Result<Option<MealSlot>>> + Send;
+
+    fn get_slot_by_name(&self, name: &str)
+        -> impl Future<Output = Result<Option<MealSlot>>> + Send;
+
+    /// All slots in day order, inactive ones included
+    fn get_slots(&self) -> impl Future<Output = Result<Vec<MealSlot>>> + Send;
+
+    fn get_active_slots(&self) -> impl Future<Output = Result<Vec<MealSlot>>> + Send;
+
+    /// The name cannot change
+    fn update_slot(
+        &self,
+        id: i64,
+        update: UpdateMealSlot,
+    ) -> impl Future<Output = Result<MealSlot>> + Send;
+
+    /// Fails with a protocol error while entries or templates use the slot;
+    /// returns false if the slot did not exist
+    fn delete_slot(&self, id: i64) -> impl Future<Output = Result<bool>> + Send;
+}
+
+/// Tag storage, see `TagRepository` for the SQLite behavior every backend follows
+pub trait TagStore: Send + Sync {
+    fn create_tag(&self, tag: CreateTag) -> impl Future<Output = Result<Tag>> + Send;
+
+    fn get_tag(&self, id: i64) -> impl Future<Output = Result<Option<Tag>>> + Send;
+
+    fn get_tag_by_name(&self, name: &str) -> impl Future<Output = Result<Option<Tag>>> + Send;
+
+    /// Tags that are not archived, by name
+    fn get_tags(&self) -> impl Future<Output = Result<Vec<Tag>>> + Send;
+
+    fn get_archived_tags(&self) -> impl Future<Output = Result<Vec<Tag>>> + Send;
+
+    fn get_tags_by_category(
+        &self,
+        category: TagCategory,
+    ) -> impl Future<Output = Result<Vec<Tag>>> + Send;
+
+    fn get_child_tags(&self, parent_id: i64) -> impl Future<Output = Result<Vec<Tag>>> + Send;
+
+    /// Tags of a meal option together with all of their ancestors
+    fn get_option_tags_with_ancestors(
+        &self,
+        meal_option_id: i64,
+    ) -> impl Future<Output = Result<Vec<Tag>>> + Send;
+
+    /// Fails with a protocol error when the new parent would create a cycle
+    fn update_tag(&self, id: i64, update: UpdateTag) -> impl Future<Output = Result<Tag>> + Send;
+
+    fn set_tag_archived(&self, id: i64, archived: bool)
+        -> impl Future<Output = Result<Tag>> + Send;
+
+    fn get_tag_delete_blockers(
+        &self,
+        id: i64,
+    ) -> impl Future<Output = Result<Option<DeleteBlocked>>> + Send;
+
+    /// Returns false if the tag did not exist
+    fn delete_tag(&self, id: i64) -> impl Future<Output = Result<bool>> + Send;
+}
+
+/// Meal template storage, see `MealTemplateRepository`
+pub trait MealTemplateStore: Send + Sync {
+    fn create_template(
+        &self,
+        template: CreateMealTemplate,
+    ) -> impl Future<Output = Result<MealTemplate>> + Send;
+
+    fn get_template(&self, id: i64) -> impl Future<Output = Result<Option<MealTemplate>>> + Send;
+
+    /// Templates that are not archived, by name
+    fn get_templates(&self) -> impl Future<Output = Result<Vec<MealTemplate>>> + Send;
+
+    fn get_archived_templates(&self) -> impl Future<Output = Result<Vec<MealTemplate>>> + Send;
+
+    fn get_templates_by_location(
+        &self,
+        location: LocationType,
+    ) -> impl Future<Output = Result<Vec<MealTemplate>>> + Send;
+
+    fn get_templates_by_slot(
+        &self,
+        slot: &SlotType,
+    ) -> impl Future<Output = Result<Vec<MealTemplate>>> + Send;
+
+    fn get_templates_by_slot_and_location(
+        &self,
+        slot: &SlotType,
+        location: LocationType,
+    ) -> impl Future<Output = Result<Vec<MealTemplate>>> + Send;
+
+    fn search_templates(
+        &self,
+        query: &str,
+        slot: Option<&SlotType>,
+    ) -> impl Future<Output = Result<Vec<MealTemplate>>> + Send;
+
+    fn update_template(
+        &self,
+        id: i64,
+        update: UpdateMealTemplate,
+    ) -> impl Future<Output = Result<MealTemplate>> + Send;
+
+    fn set_template_archived(
+        &self,
+        id: i64,
+        archived: bool,
+    ) -> impl Future<Output = Result<MealTemplate>> + Send;
+
+    fn get_option_groups(
+        &self,
+        template_id: i64,
+    ) -> impl Future<Output = Result<Vec<OptionGroup>>> + Send;
+
+    /// Options left in a group that is no longer declared become ungrouped
+    fn set_option_groups(
+        &self,
+        template_id: i64,
+        groups: Vec<OptionGroup>,
+    ) -> impl Future<Output = Result<Vec<OptionGroup>>> + Send;
+
+    fn get_template_delete_blockers(
+        &self,
+        id: i64,
+    ) -> impl Future<Output = Result<Option<DeleteBlocked>>> + Send;
+
+    /// Returns false if the template did not exist
+    fn delete_template(&self, id: i64) -> impl Future<Output = Result<bool>> + Send;
+}
+
+/// Meal option storage, see `MealOptionRepository`
+pub trait MealOptionStore: Send + Sync {
+    fn create_option(
+        &self,
+        option: CreateMealOption,
+    ) -> impl Future<Output = Result<MealOption>> + Send;
+
+    fn get_option(&self, id: i64) -> impl Future<Output = Result<Option<MealOption>>> + Send;
+
+    /// Options that are not archived and whose template is not archived, by name
+    fn get_options(&self) -> impl Future<Output = Result<Vec<MealOption>>> + Send;
+
+    fn get_archived_options(&self) -> impl Future<Output = Result<Vec<MealOption>>> + Send;
+
+    fn get_options_by_template(
+        &self,
+        template_id: i64,
+    ) -> impl Future<Output = Result<Vec<MealOption>>> + Send;
+
+    fn get_option_with_tags(
+        &self,
+        id: i64,
+    ) -> impl Future<Output = Result<Option<MealOptionWithTags>>> + Send;
+
+    fn get_options_by_template_with_tags(
+        &self,
+        template_id: i64,
+    ) -> impl Future<Output = Result<Vec<MealOptionWithTags>>> + Send;
+
+    fn search_options(&self, query: &str) -> impl Future<Output = Result<Vec<MealOption>>> + Send;
+
+    fn update_option(
+        &self,
+        id: i64,
+        update: UpdateMealOption,
+    ) -> impl Future<Output = Result<MealOption>> + Send;
+
+    fn set_option_archived(
+        &self,
+        id: i64,
+        archived: bool,
+    ) -> impl Future<Output = Result<MealOption>> + Send;
+
+    /// The group must be declared on the option's template
+    fn set_option_group(
+        &self,
+        id: i64,
+        option_group: Option<String>,
+    ) -> impl Future<Output = Result<MealOption>> + Send;
+
+    /// Tags already on the option are ignored
+    fn add_option_tags(
+        &self,
+        option_id: i64,
+        tag_ids: Vec<i64>,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    fn remove_option_tags(
+        &self,
+        option_id: i64,
+        tag_ids: Vec<i64>,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    fn set_option_tags(
+        &self,
+        option_id: i64,
+        tag_ids: Vec<i64>,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    fn get_option_nutrients(
+        &self,
+        option_id: i64,
+    ) -> impl Future<Output = Result<Option<NutrientProfile>>> + Send;
+
+    fn set_option_nutrients(
+        &self,
+        option_id: i64,
+        nutrients: SetNutrientProfile,
+    ) -> impl Future<Output = Result<NutrientProfile>> + Send;
+
+    /// Returns false if the option had no profile
+    fn delete_option_nutrients(&self, option_id: i64) -> impl Future<Output = Result<bool>> + Send;
+
+    fn get_option_delete_blockers(
+        &self,
+        id: i64,
+    ) -> impl Future<Output = Result<Option<DeleteBlocked>>> + Send;
+
+    /// Fails with RowNotFound if the option did not exist
+    fn delete_option(&self, id: i64) -> impl Future<Output = Result<()>> + Send;
+}
+
+/// Meal entry storage, see `MealEntryRepository`
+pub trait MealEntryStore: Send + Sync {
+    /// Snapshots the options, template and tags, and records them as the plan
+    fn create_entry(
+        &self,
+        entry: CreateMealEntry,
+    ) -> impl Future<Output = Result<MealEntry>> + Send;
+
+    fn create_off_plan_entry(
+        &self,
+        entry: CreateOffPlanEntry,
+    ) -> impl Future<Output = Result<MealEntry>> + Send;
+
+    fn get_entry(&self, id: i64) -> impl Future<Output = Result<Option<MealEntry>>> + Send;
+
+    fn get_entries_by_date(
+        &self,
+        date: NaiveDate,
+    ) -> impl Future<Output = Result<Vec<MealEntry>>> + Send;
+
+    fn get_entries_by_date_range(
+        &self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> impl Future<Output = Result<Vec<MealEntry>>> + Send;
+
+    fn get_entries_by_date_and_slot(
+        &self,
+        date: NaiveDate,
+        slot: &SlotType,
+    ) -> impl Future<Output = Result<Vec<MealEntry>>> + Send;
+
+    fn get_entries_by_status(
+        &self,
+        status: EntryStatus,
+    ) -> impl Future<Output = Result<Vec<MealEntry>>> + Send;
+
+    fn get_entries_by_option(
+        &self,
+        meal_option_id: i64,
+    ) -> impl Future<Output = Result<Vec<MealEntry>>> + Send;
+
+    /// The latest entry of each meal option, most recent first
+    fn get_recent_entries(&self, limit: i32)
+        -> impl Future<Output = Result<Vec<MealEntry>>> + Send;
+
+    fn update_entry(
+        &self,
+        id: i64,
+        update: UpdateMealEntry,
+    ) -> impl Future<Output = Result<MealEntry>> + Send;
+
+    /// Replaces the selected options and marks the entry as eaten; the plan is kept
+    fn log_actual(
+        &self,
+        id: i64,
+        actual: LogActualMeal,
+    ) -> impl Future<Output = Result<MealEntry>> + Send;
+
+    /// Fails with RowNotFound if the entry did not exist
+    fn delete_entry(&self, id: i64) -> impl Future<Output = Result<()>> + Send;
+
+    fn get_weekly_usage(
+        &self,
+        meal_option_id: i64,
+        week: &str,
+    ) -> impl Future<Output = Result<Option<WeeklyUsage>>> + Send;
+
+    fn get_weekly_template_usage(
+        &self,
+        template_id: i64,
+        week: &str,
+    ) -> impl Future<Output = Result<Option<WeeklyTemplateUsage>>> + Send;
+
+    /// Every template that has a weekly limit or was used that week
+    fn get_all_weekly_template_usage(
+        &self,
+        week: &str,
+    ) -> impl Future<Output = Result<Vec<WeeklyTemplateUsage>>> + Send;
+
+    /// Rolled up over the tag's descendants
+    fn get_weekly_tag_usage(
+        &self,
+        tag_id: i64,
+        week: &str,
+    ) -> impl Future<Output = Result<Option<WeeklyTagUsage>>> + Send;
+
+    fn get_weekly_tag_usage_breakdown(
+        &self,
+        tag_id: i64,
+        week: &str,
+    ) -> impl Future<Output = Result<Vec<WeeklyTagUsageNode>>> + Send;
+}
+
+/// Every store the services need, implemented by both pools and `Backend`
+pub trait Store:
+    MealSlotStore + TagStore + MealTemplateStore + MealOptionStore + MealEntryStore
+{
+}
+
+impl<T> Store for T where
+    T: MealSlotStore + TagStore + MealTemplateStore + MealOptionStore + MealEntryStore
+{
+}
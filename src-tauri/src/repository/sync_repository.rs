@@ -0,0 +1,1552 @@
+use crate::models::{
+    EntryStatus, LocationType, MealEntry, OptionGroup, SetNutrientProfile, SlotType,
+    SyncApplyResult, SyncChange, SyncEntity, SyncPull, SyncSettings, SyncSkipped, SyncStatus,
+    SyncedEntry, SyncedEntryOption, SyncedEntryTag, SyncedOption, SyncedSlot, SyncedTag,
+    SyncedTemplate, TagCategory,
+};
+use crate::repository::MealTemplateRepository;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sqlx::{Acquire, Result, Row, SqliteConnection, SqlitePool};
+use std::cmp::Ordering;
+
+/// Outcome of applying one remote change
+enum Applied {
+    Done,
+    Skipped(String),
+}
+
+/// The state of one entity row at some point, as sync data (None = deleted)
+#[derive(Debug, Clone)]
+pub(crate) struct EntitySnapshot {
+    pub entity: SyncEntity,
+    pub id: i64,
+    pub key: String,
+    pub data: Option<serde_json::Value>,
+}
+
+pub struct SyncRepository;
+
+impl SyncRepository {
+    /// Get the sync identity and progress of this database
+    pub async fn get_status(pool: &SqlitePool) -> Result<SyncStatus> {
+        let row = sqlx::query(
+            "SELECT device_id, clock, server_url, last_synced_at,
+                    (SELECT COUNT(*) FROM (
+                         SELECT DISTINCT entity, entity_key FROM sync_changes c
+                         WHERE c.device_id = s.device_id AND c.seq > s.pushed_seq
+                     )) AS pending_changes
+             FROM sync_state s",
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(SyncStatus {
+            device_id: row.try_get("device_id")?,
+            clock: row.try_get("clock")?,
+            pending_changes: row.try_get("pending_changes")?,
+            server_url: row.try_get("server_url")?,
+            last_synced_at: row.try_get("last_synced_at")?,
+        })
+    }
+
+    /// Set or clear the sync server
+    /// Switching to another server starts over: everything is pushed and pulled again
+    pub async fn set_settings(pool: &SqlitePool, settings: SyncSettings) -> Result<SyncStatus> {
+        settings.validate().map_err(sqlx::Error::Protocol)?;
+
+        let token = settings.server_url.as_ref().and(settings.token.as_ref());
+        sqlx::query(
+            "UPDATE sync_state
+             SET pushed_seq = CASE WHEN server_url IS ?1 THEN pushed_seq ELSE 0 END,
+                 server_cursor = CASE WHEN server_url IS ?1 THEN server_cursor ELSE 0 END,
+                 server_url = ?1,
+                 server_token = ?2",
+        )
+        .bind(&settings.server_url)
+        .bind(token)
+        .execute(pool)
+        .await?;
+
+        Self::get_status(pool).await
+    }
+
+    /// Get the configured server URL and token, if any
+    pub async fn get_server(pool: &SqlitePool) -> Result<Option<(String, String)>> {
+        let row: (Option<String>, Option<String>) =
+            sqlx::query_as("SELECT server_url, server_token FROM sync_state")
+                .fetch_one(pool)
+                .await?;
+
+        Ok(match row {
+            (Some(url), Some(token)) => Some((url, token)),
+            _ => None,
+        })
+    }
+
+    /// Get the server position to pull from
+    pub async fn get_cursor(pool: &SqlitePool) -> Result<i64> {
+        sqlx::query_scalar("SELECT server_cursor FROM sync_state")
+            .fetch_one(pool)
+            .await
+    }
+
+    /// Get the current state of every entity changed here since the last push,
+    /// with the change log position to pass to `mark_pushed` once the server has them
+    /// Entities last changed by another device are left out: the server has that version
+    pub async fn get_pending_changes(pool: &SqlitePool) -> Result<(Vec<SyncChange>, i64)> {
+        let mut tx = pool.begin().await?;
+
+        let (device_id, pushed_seq): (String, i64) =
+            sqlx::query_as("SELECT device_id, pushed_seq FROM sync_state")
+                .fetch_one(&mut *tx)
+                .await?;
+        let up_to: i64 = sqlx::query_scalar("SELECT COALESCE(MAX(seq), 0) FROM sync_changes")
+            .fetch_one(&mut *tx)
+            .await?;
+
+        let keys: Vec<(String, String)> = sqlx::query_as(
+            "SELECT DISTINCT entity, entity_key FROM sync_changes
+             WHERE device_id = ?1 AND seq > ?2 AND seq <= ?3
+             ORDER BY entity, entity_key",
+        )
+        .bind(&device_id)
+        .bind(pushed_seq)
+        .bind(up_to)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut changes = Vec::new();
+        for (entity, key) in keys {
+            let entity = SyncEntity::from_db_string(&entity).map_err(sqlx::Error::Protocol)?;
+            let Some((clock, last_device, deleted)) =
+                Self::current_version(&mut tx, entity, &key).await?
+            else {
+                continue;
+            };
+            if last_device != device_id {
+                continue;
+            }
+
+            let data = if deleted {
+                None
+            } else {
+                Self::load(&mut tx, entity, &key).await?
+            };
+            changes.push(SyncChange {
+                entity,
+                key,
+                clock,
+                device_id: device_id.clone(),
+                data,
+            });
+        }
+        changes.sort_by_key(|change| change.entity);
+        tx.commit().await?;
+
+        Ok((changes, up_to))
+    }
+
+    /// Record that the changes up to `seq` are on the server
+    pub async fn mark_pushed(pool: &SqlitePool, seq: i64) -> Result<()> {
+        sqlx::query("UPDATE sync_state SET pushed_seq = MAX(pushed_seq, ?1), last_synced_at = CURRENT_TIMESTAMP")
+            .bind(seq)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Apply changes pulled from the server and move the cursor past them
+    /// A change applies only when its version is newer than the one here; a change
+    /// that cannot be applied (see `SyncSkipped`) is reported and the rest go on
+    pub async fn apply_pull(pool: &SqlitePool, pull: SyncPull) -> Result<SyncApplyResult> {
+        let mut changes = pull.changes;
+        changes.sort_by(|a, b| apply_order((a.entity, &a.data), (b.entity, &b.data)));
+
+        let mut tx = pool.begin().await?;
+        // Applied rows are logged below with their origin's version, not by the triggers
+        sqlx::query("UPDATE sync_state SET applying = 1")
+            .execute(&mut *tx)
+            .await?;
+
+        let mut result = SyncApplyResult::default();
+        for change in &changes {
+            sqlx::query("UPDATE sync_state SET clock = MAX(clock, ?1)")
+                .bind(change.clock)
+                .execute(&mut *tx)
+                .await?;
+
+            if let Some((clock, device_id, _)) =
+                Self::current_version(&mut tx, change.entity, &change.key).await?
+            {
+                if !change.supersedes(clock, &device_id) {
+                    result.stale += 1;
+                    continue;
+                }
+            }
+
+            let mut savepoint = tx.begin().await?;
+            let applied = match Self::apply_change(
+                &mut savepoint,
+                change.entity,
+                &change.key,
+                None,
+                change.data.clone(),
+            )
+            .await
+            {
+                Ok(applied) => applied,
+                // Constraint failures, e.g. deleting an option that entries here still use
+                Err(sqlx::Error::Database(e)) => Applied::Skipped(e.message().to_string()),
+                Err(e) => return Err(e),
+            };
+
+            match applied {
+                Applied::Done => {
+                    sqlx::query(
+                        "INSERT INTO sync_changes (entity, entity_key, clock, device_id, deleted)
+                         VALUES (?1, ?2, ?3, ?4, ?5)",
+                    )
+                    .bind(change.entity.to_db_string())
+                    .bind(&change.key)
+                    .bind(change.clock)
+                    .bind(&change.device_id)
+                    .bind(change.data.is_none())
+                    .execute(&mut *savepoint)
+                    .await?;
+                    savepoint.commit().await?;
+                    result.applied += 1;
+                }
+                Applied::Skipped(reason) => {
+                    savepoint.rollback().await?;
+                    result.skipped.push(SyncSkipped {
+                        entity: change.entity,
+                        key: change.key.clone(),
+                        reason,
+                    });
+                }
+            }
+        }
+
+        Self::link_tag_parents(
+            &mut tx,
+            changes.iter().map(|c| (c.entity, c.key.as_str(), &c.data)),
+        )
+        .await?;
+
+        sqlx::query(
+            "UPDATE sync_state
+             SET applying = 0,
+                 server_cursor = MAX(server_cursor, ?1),
+                 last_synced_at = CURRENT_TIMESTAMP",
+        )
+        .bind(pull.cursor)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    /// Key of an entity row: its UUID, or the name for slots
+    pub(crate) async fn key_of(
+        conn: &mut SqliteConnection,
+        entity: SyncEntity,
+        id: i64,
+    ) -> Result<Option<String>> {
+        let (table, key) = table_of(entity);
+        sqlx::query_scalar(&format!("SELECT {} FROM {} WHERE id = ?1", key, table))
+            .bind(id)
+            .fetch_optional(&mut *conn)
+            .await
+    }
+
+    /// ID and key of every row of an entity
+    pub(crate) async fn keys_of_all(
+        conn: &mut SqliteConnection,
+        entity: SyncEntity,
+    ) -> Result<Vec<(i64, String)>> {
+        let (table, key) = table_of(entity);
+        sqlx::query_as(&format!("SELECT id, {} FROM {} ORDER BY id", key, table))
+            .fetch_all(&mut *conn)
+            .await
+    }
+
+    /// Current state of an entity as sync data; None if it does not exist
+    pub(crate) async fn snapshot(
+        conn: &mut SqliteConnection,
+        entity: SyncEntity,
+        key: &str,
+    ) -> Result<Option<serde_json::Value>> {
+        Self::load(conn, entity, key).await
+    }
+
+    /// Put entities back to earlier snapshots, e.g. to undo a change
+    /// Recreated rows get their old ID back when it is free. The triggers log the
+    /// result as a local change, so it reaches other devices like any edit.
+    /// Fails if a snapshot no longer fits, e.g. an option whose template is gone
+    pub(crate) async fn restore(
+        conn: &mut SqliteConnection,
+        mut snapshots: Vec<EntitySnapshot>,
+    ) -> Result<()> {
+        snapshots.sort_by(|a, b| apply_order((a.entity, &a.data), (b.entity, &b.data)));
+
+        for snapshot in &snapshots {
+            let (table, key) = table_of(snapshot.entity);
+            let taken: bool = sqlx::query_scalar(&format!(
+                "SELECT EXISTS(SELECT 1 FROM {} WHERE id = ?1 AND {} != ?2)",
+                table, key
+            ))
+            .bind(snapshot.id)
+            .bind(&snapshot.key)
+            .fetch_one(&mut *conn)
+            .await?;
+
+            let id = (!taken).then_some(snapshot.id);
+            let applied = Self::apply_change(
+                conn,
+                snapshot.entity,
+                &snapshot.key,
+                id,
+                snapshot.data.clone(),
+            )
+            .await?;
+            if let Applied::Skipped(reason) = applied {
+                return Err(sqlx::Error::Protocol(reason));
+            }
+        }
+
+        Self::link_tag_parents(
+            conn,
+            snapshots
+                .iter()
+                .map(|s| (s.entity, s.key.as_str(), &s.data)),
+        )
+        .await
+    }
+
+    /// A parent tag may have been applied after its children
+    async fn link_tag_parents<'a>(
+        conn: &mut SqliteConnection,
+        changes: impl Iterator<Item = (SyncEntity, &'a str, &'a Option<serde_json::Value>)>,
+    ) -> Result<()> {
+        for (entity, key, data) in changes {
+            if entity != SyncEntity::Tag {
+                continue;
+            }
+            let Some(tag) = data.clone().map(decode::<SyncedTag>).transpose()? else {
+                continue;
+            };
+            if let Some(parent) = tag.parent {
+                sqlx::query(
+                    "UPDATE tags SET parent_tag_id = (SELECT id FROM tags WHERE uuid = ?1)
+                     WHERE uuid = ?2 AND parent_tag_id IS NULL",
+                )
+                .bind(parent)
+                .bind(key)
+                .execute(&mut *conn)
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Latest logged version of an entity: (clock, device_id, deleted)
+    async fn current_version(
+        conn: &mut SqliteConnection,
+        entity: SyncEntity,
+        key: &str,
+    ) -> Result<Option<(i64, String, bool)>> {
+        sqlx::query_as(
+            "SELECT clock, device_id, deleted FROM sync_changes
+             WHERE entity = ?1 AND entity_key = ?2
+             ORDER BY clock DESC, device_id DESC
+             LIMIT 1",
+        )
+        .bind(entity.to_db_string())
+        .bind(key)
+        .fetch_optional(&mut *conn)
+        .await
+    }
+
+    /// Current state of an entity as sync data; None if it no longer exists
+    async fn load(
+        conn: &mut SqliteConnection,
+        entity: SyncEntity,
+        key: &str,
+    ) -> Result<Option<serde_json::Value>> {
+        match entity {
+            SyncEntity::Slot => encode(Self::load_slot(conn, key).await?),
+            SyncEntity::Tag => encode(Self::load_tag(conn, key).await?),
+            SyncEntity::Template => encode(Self::load_template(conn, key).await?),
+            SyncEntity::Option => encode(Self::load_option(conn, key).await?),
+            SyncEntity::Entry => encode(Self::load_entry(conn, key).await?),
+        }
+    }
+
+    async fn load_slot(conn: &mut SqliteConnection, name: &str) -> Result<Option<SyncedSlot>> {
+        let row =
+            sqlx::query("SELECT display_name, sort_order, active FROM meal_slots WHERE name = ?1")
+                .bind(name)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+        row.map(|row| {
+            Ok(SyncedSlot {
+                display_name: row.try_get("display_name")?,
+                sort_order: row.try_get("sort_order")?,
+                active: row.try_get("active")?,
+            })
+        })
+        .transpose()
+    }
+
+    async fn load_tag(conn: &mut SqliteConnection, uuid: &str) -> Result<Option<SyncedTag>> {
+        let row = sqlx::query(
+            "SELECT t.name, t.display_name, t.category, t.weekly_suggestion, t.archived,
+                    p.uuid AS parent
+             FROM tags t
+             LEFT JOIN tags p ON p.id = t.parent_tag_id
+             WHERE t.uuid = ?1",
+        )
+        .bind(uuid)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        row.map(|row| {
+            let category: String = row.try_get("category")?;
+            Ok(SyncedTag {
+                name: row.try_get("name")?,
+                display_name: row.try_get("display_name")?,
+                category: TagCategory::from_db_string(&category).map_err(sqlx::Error::Protocol)?,
+                weekly_suggestion: row.try_get("weekly_suggestion")?,
+                parent: row.try_get("parent")?,
+                archived: row.try_get("archived")?,
+            })
+        })
+        .transpose()
+    }
+
+    async fn load_template(
+        conn: &mut SqliteConnection,
+        uuid: &str,
+    ) -> Result<Option<SyncedTemplate>> {
+        let Some(row) = sqlx::query(
+            "SELECT id, name, description, location_type, weekly_limit, archived
+             FROM meal_templates WHERE uuid = ?1",
+        )
+        .bind(uuid)
+        .fetch_optional(&mut *conn)
+        .await?
+        else {
+            return Ok(None);
+        };
+        let id: i64 = row.try_get("id")?;
+        let location_type: String = row.try_get("location_type")?;
+
+        let slots: Vec<String> = sqlx::query_scalar(
+            "SELECT ts.slot_type FROM meal_template_slots ts
+             JOIN meal_slots s ON s.name = ts.slot_type
+             WHERE ts.template_id = ?1
+             ORDER BY s.sort_order, s.name",
+        )
+        .bind(id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let groups = sqlx::query(
+            "SELECT name, min_choices, max_choices FROM meal_template_option_groups
+             WHERE template_id = ?1
+             ORDER BY sort_order, name",
+        )
+        .bind(id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(Some(SyncedTemplate {
+            name: row.try_get("name")?,
+            description: row.try_get("description")?,
+            compatible_slots: slots
+                .iter()
+                .map(|slot| SlotType::from_db_string(slot).map_err(sqlx::Error::Protocol))
+                .collect::<Result<_>>()?,
+            location_type: LocationType::from_db_string(&location_type)
+                .map_err(sqlx::Error::Protocol)?,
+            weekly_limit: row.try_get("weekly_limit")?,
+            archived: row.try_get("archived")?,
+            option_groups: groups
+                .iter()
+                .map(|group| {
+                    Ok(OptionGroup {
+                        name: group.try_get("name")?,
+                        min_choices: group.try_get("min_choices")?,
+                        max_choices: group.try_get("max_choices")?,
+                    })
+                })
+                .collect::<Result<_>>()?,
+        }))
+    }
+
+    async fn load_option(conn: &mut SqliteConnection, uuid: &str) -> Result<Option<SyncedOption>> {
+        let Some(row) = sqlx::query(
+            "SELECT o.id, t.uuid AS template, o.name, o.description, o.nutritional_notes,
+                    o.option_group, o.archived
+             FROM meal_options o
+             JOIN meal_templates t ON t.id = o.template_id
+             WHERE o.uuid = ?1",
+        )
+        .bind(uuid)
+        .fetch_optional(&mut *conn)
+        .await?
+        else {
+            return Ok(None);
+        };
+        let id: i64 = row.try_get("id")?;
+
+        let tags: Vec<String> = sqlx::query_scalar(
+            "SELECT t.uuid FROM meal_option_tags ot
+             JOIN tags t ON t.id = ot.tag_id
+             WHERE ot.meal_option_id = ?1
+             ORDER BY t.uuid",
+        )
+        .bind(id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let nutrients = sqlx::query(
+            "SELECT kcal, protein_g, carbs_g, fat_g, fiber_g
+             FROM meal_option_nutrients WHERE meal_option_id = ?1",
+        )
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        Ok(Some(SyncedOption {
+            template: row.try_get("template")?,
+            name: row.try_get("name")?,
+            description: row.try_get("description")?,
+            nutritional_notes: row.try_get("nutritional_notes")?,
+            option_group: row.try_get("option_group")?,
+            archived: row.try_get("archived")?,
+            tags,
+            nutrients: nutrients.as_ref().map(row_to_nutrients).transpose()?,
+        }))
+    }
+
+    async fn load_entry(conn: &mut SqliteConnection, uuid: &str) -> Result<Option<SyncedEntry>> {
+        let Some(row) = sqlx::query(
+            "SELECT me.id, o.uuid AS option, me.date, me.slot_type, me.location, me.servings,
+                    me.notes, me.status, me.option_name, t.uuid AS template, me.template_name,
+                    me.template_location_type
+             FROM meal_entries me
+             LEFT JOIN meal_options o ON o.id = me.meal_option_id
+             LEFT JOIN meal_templates t ON t.id = me.template_id
+             WHERE me.uuid = ?1",
+        )
+        .bind(uuid)
+        .fetch_optional(&mut *conn)
+        .await?
+        else {
+            return Ok(None);
+        };
+        let id: i64 = row.try_get("id")?;
+        let slot_type: String = row.try_get("slot_type")?;
+        let location: String = row.try_get("location")?;
+        let status: String = row.try_get("status")?;
+        let template_location_type: String = row.try_get("template_location_type")?;
+
+        let options = Self::load_entry_options(conn, "meal_entry_options", id).await?;
+        let planned_options =
+            Self::load_entry_options(conn, "meal_entry_planned_options", id).await?;
+
+        let tags = sqlx::query(
+            "SELECT t.uuid AS tag, et.tag_name FROM meal_entry_tags et
+             LEFT JOIN tags t ON t.id = et.tag_id
+             WHERE et.meal_entry_id = ?1
+             ORDER BY et.tag_name",
+        )
+        .bind(id)
+        .fetch_all(&mut *conn)
+        .await?
+        .iter()
+        .map(|tag| {
+            Ok(SyncedEntryTag {
+                tag: tag.try_get("tag")?,
+                tag_name: tag.try_get("tag_name")?,
+            })
+        })
+        .collect::<Result<_>>()?;
+
+        let nutrients = sqlx::query(
+            "SELECT kcal, protein_g, carbs_g, fat_g, fiber_g
+             FROM meal_entry_nutrients WHERE meal_entry_id = ?1",
+        )
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        Ok(Some(SyncedEntry {
+            option: row.try_get("option")?,
+            date: row.try_get("date")?,
+            slot_type: SlotType::from_db_string(&slot_type).map_err(sqlx::Error::Protocol)?,
+            location: LocationType::from_db_string(&location).map_err(sqlx::Error::Protocol)?,
+            servings: row.try_get("servings")?,
+            notes: row.try_get("notes")?,
+            status: EntryStatus::from_db_string(&status).map_err(sqlx::Error::Protocol)?,
+            option_name: row.try_get("option_name")?,
+            template: row.try_get("template")?,
+            template_name: row.try_get("template_name")?,
+            template_location_type: LocationType::from_db_string(&template_location_type)
+                .map_err(sqlx::Error::Protocol)?,
+            options,
+            planned_options,
+            tags,
+            estimated_nutrients: nutrients.as_ref().map(row_to_nutrients).transpose()?,
+        }))
+    }
+
+    /// Selected or planned options of an entry, main first
+    async fn load_entry_options(
+        conn: &mut SqliteConnection,
+        table: &str,
+        entry_id: i64,
+    ) -> Result<Vec<SyncedEntryOption>> {
+        let sql = format!(
+            "SELECT o.uuid AS option, eo.servings, eo.option_name FROM {} eo
+             JOIN meal_options o ON o.id = eo.meal_option_id
+             WHERE eo.meal_entry_id = ?1
+             ORDER BY eo.position",
+            table
+        );
+        sqlx::query(&sql)
+            .bind(entry_id)
+            .fetch_all(&mut *conn)
+            .await?
+            .iter()
+            .map(|row| {
+                Ok(SyncedEntryOption {
+                    option: row.try_get("option")?,
+                    servings: row.try_get("servings")?,
+                    option_name: row.try_get("option_name")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Bring an entity to `data` (None = deleted)
+    /// A row that has to be inserted gets `id`, or a new ID when None
+    async fn apply_change(
+        conn: &mut SqliteConnection,
+        entity: SyncEntity,
+        key: &str,
+        id: Option<i64>,
+        data: Option<serde_json::Value>,
+    ) -> Result<Applied> {
+        let Some(data) = data else {
+            return Self::apply_delete(conn, entity, key).await;
+        };
+
+        match entity {
+            SyncEntity::Slot => Self::apply_slot(conn, key, id, decode(data)?).await,
+            SyncEntity::Tag => Self::apply_tag(conn, key, id, decode(data)?).await,
+            SyncEntity::Template => Self::apply_template(conn, key, id, decode(data)?).await,
+            SyncEntity::Option => Self::apply_option(conn, key, id, decode(data)?).await,
+            SyncEntity::Entry => Self::apply_entry(conn, key, id, decode(data)?).await,
+        }
+    }
+
+    async fn apply_delete(
+        conn: &mut SqliteConnection,
+        entity: SyncEntity,
+        key: &str,
+    ) -> Result<Applied> {
+        let (table, column) = table_of(entity);
+        sqlx::query(&format!("DELETE FROM {} WHERE {} = ?1", table, column))
+            .bind(key)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(Applied::Done)
+    }
+
+    async fn apply_slot(
+        conn: &mut SqliteConnection,
+        name: &str,
+        id: Option<i64>,
+        slot: SyncedSlot,
+    ) -> Result<Applied> {
+        if let Err(e) = SlotType::from_db_string(name) {
+            return Ok(Applied::Skipped(e));
+        }
+
+        sqlx::query(
+            "INSERT INTO meal_slots (id, name, display_name, sort_order, active)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(name) DO UPDATE SET
+                 display_name = excluded.display_name,
+                 sort_order = excluded.sort_order,
+                 active = excluded.active",
+        )
+        .bind(id)
+        .bind(name)
+        .bind(&slot.display_name)
+        .bind(slot.sort_order)
+        .bind(slot.active)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(Applied::Done)
+    }
+
+    async fn apply_tag(
+        conn: &mut SqliteConnection,
+        uuid: &str,
+        id: Option<i64>,
+        tag: SyncedTag,
+    ) -> Result<Applied> {
+        let name = Self::unique_tag_name(conn, &tag.name, uuid).await?;
+        // Set after the whole batch if the parent is not here yet
+        let parent_id = match &tag.parent {
+            Some(parent) => Self::id_by_uuid(conn, "tags", parent).await?,
+            None => None,
+        };
+
+        let updated = sqlx::query(
+            "UPDATE tags
+             SET name = ?1, display_name = ?2, category = ?3, weekly_suggestion = ?4,
+                 parent_tag_id = ?5, archived = ?6
+             WHERE uuid = ?7",
+        )
+        .bind(&name)
+        .bind(&tag.display_name)
+        .bind(tag.category.to_db_string())
+        .bind(tag.weekly_suggestion)
+        .bind(parent_id)
+        .bind(tag.archived)
+        .bind(uuid)
+        .execute(&mut *conn)
+        .await?;
+
+        if updated.rows_affected() == 0 {
+            sqlx::query(
+                "INSERT INTO tags (id, uuid, name, display_name, category, weekly_suggestion,
+                                   parent_tag_id, archived)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )
+            .bind(id)
+            .bind(uuid)
+            .bind(&name)
+            .bind(&tag.display_name)
+            .bind(tag.category.to_db_string())
+            .bind(tag.weekly_suggestion)
+            .bind(parent_id)
+            .bind(tag.archived)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(Applied::Done)
+    }
+
+    /// Tag names are unique, so two devices creating the same tag end up with two
+    /// tags of that name; the one with the greater UUID gets a suffix on every device
+    async fn unique_tag_name(
+        conn: &mut SqliteConnection,
+        name: &str,
+        uuid: &str,
+    ) -> Result<String> {
+        let other: Option<(i64, String)> =
+            sqlx::query_as("SELECT id, uuid FROM tags WHERE name = ?1 AND uuid != ?2")
+                .bind(name)
+                .bind(uuid)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+        let Some((other_id, other_uuid)) = other else {
+            return Ok(name.to_string());
+        };
+        if other_uuid.as_str() < uuid {
+            return Ok(suffixed_tag_name(name, uuid));
+        }
+
+        sqlx::query("UPDATE tags SET name = ?1 WHERE id = ?2")
+            .bind(suffixed_tag_name(name, &other_uuid))
+            .bind(other_id)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(name.to_string())
+    }
+
+    async fn apply_template(
+        conn: &mut SqliteConnection,
+        uuid: &str,
+        id: Option<i64>,
+        template: SyncedTemplate,
+    ) -> Result<Applied> {
+        let updated = sqlx::query(
+            "UPDATE meal_templates
+             SET name = ?1, description = ?2, location_type = ?3, weekly_limit = ?4, archived = ?5
+             WHERE uuid = ?6",
+        )
+        .bind(&template.name)
+        .bind(&template.description)
+        .bind(template.location_type.to_db_string())
+        .bind(template.weekly_limit)
+        .bind(template.archived)
+        .bind(uuid)
+        .execute(&mut *conn)
+        .await?;
+
+        if updated.rows_affected() == 0 {
+            sqlx::query(
+                "INSERT INTO meal_templates (id, uuid, name, description, location_type, weekly_limit, archived)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )
+            .bind(id)
+            .bind(uuid)
+            .bind(&template.name)
+            .bind(&template.description)
+            .bind(template.location_type.to_db_string())
+            .bind(template.weekly_limit)
+            .bind(template.archived)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        let id = Self::id_by_uuid(conn, "meal_templates", uuid)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+        MealTemplateRepository::set_slots(conn, id, &template.compatible_slots).await?;
+        MealTemplateRepository::replace_option_groups(conn, id, &template.option_groups).await?;
+
+        Ok(Applied::Done)
+    }
+
+    async fn apply_option(
+        conn: &mut SqliteConnection,
+        uuid: &str,
+        id: Option<i64>,
+        option: SyncedOption,
+    ) -> Result<Applied> {
+        let Some(template_id) = Self::id_by_uuid(conn, "meal_templates", &option.template).await?
+        else {
+            return Ok(Applied::Skipped(format!(
+                "Template {} does not exist",
+                option.template
+            )));
+        };
+
+        let updated = sqlx::query(
+            "UPDATE meal_options
+             SET template_id = ?1, name = ?2, description = ?3, nutritional_notes = ?4,
+                 option_group = ?5, archived = ?6
+             WHERE uuid = ?7",
+        )
+        .bind(template_id)
+        .bind(&option.name)
+        .bind(&option.description)
+        .bind(&option.nutritional_notes)
+        .bind(&option.option_group)
+        .bind(option.archived)
+        .bind(uuid)
+        .execute(&mut *conn)
+        .await?;
+
+        if updated.rows_affected() == 0 {
+            sqlx::query(
+                "INSERT INTO meal_options (id, uuid, template_id, name, description, nutritional_notes,
+                                           option_group, archived)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )
+            .bind(id)
+            .bind(uuid)
+            .bind(template_id)
+            .bind(&option.name)
+            .bind(&option.description)
+            .bind(&option.nutritional_notes)
+            .bind(&option.option_group)
+            .bind(option.archived)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        let id = Self::id_by_uuid(conn, "meal_options", uuid)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        // Tags deleted here are left out
+        sqlx::query("DELETE FROM meal_option_tags WHERE meal_option_id = ?1")
+            .bind(id)
+            .execute(&mut *conn)
+            .await?;
+        for tag in &option.tags {
+            sqlx::query(
+                "INSERT OR IGNORE INTO meal_option_tags (meal_option_id, tag_id)
+                 SELECT ?1, id FROM tags WHERE uuid = ?2",
+            )
+            .bind(id)
+            .bind(tag)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        sqlx::query("DELETE FROM meal_option_nutrients WHERE meal_option_id = ?1")
+            .bind(id)
+            .execute(&mut *conn)
+            .await?;
+        if let Some(nutrients) = &option.nutrients {
+            sqlx::query(
+                "INSERT INTO meal_option_nutrients (meal_option_id, kcal, protein_g, carbs_g, fat_g, fiber_g)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )
+            .bind(id)
+            .bind(nutrients.kcal)
+            .bind(nutrients.protein_g)
+            .bind(nutrients.carbs_g)
+            .bind(nutrients.fat_g)
+            .bind(nutrients.fiber_g)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(Applied::Done)
+    }
+
+    async fn apply_entry(
+        conn: &mut SqliteConnection,
+        uuid: &str,
+        id: Option<i64>,
+        entry: SyncedEntry,
+    ) -> Result<Applied> {
+        let mut option_ids = Vec::new();
+        for option in entry.option.iter().chain(
+            entry
+                .options
+                .iter()
+                .chain(&entry.planned_options)
+                .map(|o| &o.option),
+        ) {
+            match Self::id_by_uuid(conn, "meal_options", option).await? {
+                Some(id) => option_ids.push(id),
+                None => {
+                    return Ok(Applied::Skipped(format!(
+                        "Meal option {} does not exist",
+                        option
+                    )))
+                }
+            }
+        }
+        let meal_option_id = entry.option.as_ref().map(|_| option_ids[0]);
+        let offset = usize::from(entry.option.is_some());
+        let (selected_ids, planned_ids) = option_ids[offset..].split_at(entry.options.len());
+        let template_id = match &entry.template {
+            Some(template) => Self::id_by_uuid(conn, "meal_templates", template).await?,
+            None => None,
+        };
+
+        let updated = sqlx::query(
+            "UPDATE meal_entries
+             SET meal_option_id = ?1, date = ?2, iso_week = ?3, slot_type = ?4, location = ?5,
+                 servings = ?6, notes = ?7, status = ?8, option_name = ?9, template_id = ?10,
+                 template_name = ?11, template_location_type = ?12
+             WHERE uuid = ?13",
+        )
+        .bind(meal_option_id)
+        .bind(entry.date)
+        .bind(MealEntry::week_key(entry.date))
+        .bind(entry.slot_type.to_db_string())
+        .bind(entry.location.to_db_string())
+        .bind(entry.servings)
+        .bind(&entry.notes)
+        .bind(entry.status.to_db_string())
+        .bind(&entry.option_name)
+        .bind(template_id)
+        .bind(&entry.template_name)
+        .bind(entry.template_location_type.to_db_string())
+        .bind(uuid)
+        .execute(&mut *conn)
+        .await?;
+
+        if updated.rows_affected() == 0 {
+            sqlx::query(
+                "INSERT INTO meal_entries (id, uuid, meal_option_id, date, iso_week, slot_type, location,
+                                           servings, notes, status, option_name, template_id,
+                                           template_name, template_location_type)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+            )
+            .bind(id)
+            .bind(uuid)
+            .bind(meal_option_id)
+            .bind(entry.date)
+            .bind(MealEntry::week_key(entry.date))
+            .bind(entry.slot_type.to_db_string())
+            .bind(entry.location.to_db_string())
+            .bind(entry.servings)
+            .bind(&entry.notes)
+            .bind(entry.status.to_db_string())
+            .bind(&entry.option_name)
+            .bind(template_id)
+            .bind(&entry.template_name)
+            .bind(entry.template_location_type.to_db_string())
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        let id = Self::id_by_uuid(conn, "meal_entries", uuid)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        for table in [
+            "meal_entry_options",
+            "meal_entry_planned_options",
+            "meal_entry_tags",
+            "meal_entry_nutrients",
+        ] {
+            sqlx::query(&format!("DELETE FROM {} WHERE meal_entry_id = ?1", table))
+                .bind(id)
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        for (table, options, ids) in [
+            ("meal_entry_options", &entry.options, selected_ids),
+            (
+                "meal_entry_planned_options",
+                &entry.planned_options,
+                planned_ids,
+            ),
+        ] {
+            let sql = format!(
+                "INSERT INTO {} (meal_entry_id, meal_option_id, position, servings, option_name)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                table
+            );
+            for (position, (option, option_id)) in options.iter().zip(ids).enumerate() {
+                sqlx::query(&sql)
+                    .bind(id)
+                    .bind(option_id)
+                    .bind(position as i64)
+                    .bind(option.servings)
+                    .bind(&option.option_name)
+                    .execute(&mut *conn)
+                    .await?;
+            }
+        }
+
+        for tag in &entry.tags {
+            sqlx::query(
+                "INSERT OR IGNORE INTO meal_entry_tags (meal_entry_id, tag_id, tag_name)
+                 VALUES (?1, (SELECT id FROM tags WHERE uuid = ?2), ?3)",
+            )
+            .bind(id)
+            .bind(&tag.tag)
+            .bind(&tag.tag_name)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        if let Some(nutrients) = &entry.estimated_nutrients {
+            sqlx::query(
+                "INSERT INTO meal_entry_nutrients (meal_entry_id, kcal, protein_g, carbs_g, fat_g, fiber_g)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )
+            .bind(id)
+            .bind(nutrients.kcal)
+            .bind(nutrients.protein_g)
+            .bind(nutrients.carbs_g)
+            .bind(nutrients.fat_g)
+            .bind(nutrients.fiber_g)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(Applied::Done)
+    }
+
+    async fn id_by_uuid(
+        conn: &mut SqliteConnection,
+        table: &str,
+        uuid: &str,
+    ) -> Result<Option<i64>> {
+        sqlx::query_scalar(&format!("SELECT id FROM {} WHERE uuid = ?1", table))
+            .bind(uuid)
+            .fetch_optional(&mut *conn)
+            .await
+    }
+}
+
+/// Table of an entity and its key column
+fn table_of(entity: SyncEntity) -> (&'static str, &'static str) {
+    match entity {
+        SyncEntity::Slot => ("meal_slots", "name"),
+        SyncEntity::Tag => ("tags", "uuid"),
+        SyncEntity::Template => ("meal_templates", "uuid"),
+        SyncEntity::Option => ("meal_options", "uuid"),
+        SyncEntity::Entry => ("meal_entries", "uuid"),
+    }
+}
+
+/// Order in which a batch of changes applies: referenced entities first;
+/// deletions (no data) last, referencing entities first
+fn apply_order(
+    a: (SyncEntity, &Option<serde_json::Value>),
+    b: (SyncEntity, &Option<serde_json::Value>),
+) -> Ordering {
+    match (a.1.is_some(), b.1.is_some()) {
+        (true, true) => a.0.cmp(&b.0),
+        (false, false) => b.0.cmp(&a.0),
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+    }
+}
+
+/// `name` with a suffix derived from `uuid`, in letters since tag names allow no digits
+fn suffixed_tag_name(name: &str, uuid: &str) -> String {
+    let suffix: String = uuid
+        .chars()
+        .filter_map(|c| c.to_digit(16))
+        .take(8)
+        .map(|d| char::from(b'a' + d as u8))
+        .collect();
+
+    format!("{}_{}", name, suffix)
+}
+
+fn row_to_nutrients(row: &sqlx::sqlite::SqliteRow) -> Result<SetNutrientProfile> {
+    Ok(SetNutrientProfile {
+        kcal: row.try_get("kcal")?,
+        protein_g: row.try_get("protein_g")?,
+        carbs_g: row.try_get("carbs_g")?,
+        fat_g: row.try_get("fat_g")?,
+        fiber_g: row.try_get("fiber_g")?,
+    })
+}
+
+fn encode<T: Serialize>(data: Option<T>) -> Result<Option<serde_json::Value>> {
+    data.map(serde_json::to_value)
+        .transpose()
+        .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+}
+
+fn decode<T: DeserializeOwned>(data: serde_json::Value) -> Result<T> {
+    serde_json::from_value(data)
+        .map_err(|e| sqlx::Error::Protocol(format!("Invalid sync data: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use crate::models::{
+        CreateMealEntry, CreateMealOption, CreateMealTemplate, CreateTag, UpdateMealTemplate,
+    };
+    use crate::repository::{MealEntryRepository, MealOptionRepository, TagRepository};
+    use chrono::NaiveDate;
+    use tempfile::TempDir;
+
+    async fn setup_test_db() -> (SqlitePool, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let pool = db::initialize_database(db_path).await.unwrap();
+        (pool, temp_dir)
+    }
+
+    /// Push everything pending on `from` and apply it on `to`, as a server would relay it
+    async fn transfer(from: &SqlitePool, to: &SqlitePool) -> SyncApplyResult {
+        let (changes, seq) = SyncRepository::get_pending_changes(from).await.unwrap();
+        SyncRepository::mark_pushed(from, seq).await.unwrap();
+        SyncRepository::apply_pull(to, SyncPull { changes, cursor: 0 })
+            .await
+            .unwrap()
+    }
+
+    async fn create_option(pool: &SqlitePool, template_name: &str) -> (i64, i64) {
+        let template = MealTemplateRepository::create(
+            pool,
+            CreateMealTemplate {
+                name: template_name.to_string(),
+                description: None,
+                compatible_slots: vec![SlotType::BREAKFAST],
+                location_type: LocationType::Home,
+                weekly_limit: None,
+            },
+        )
+        .await
+        .unwrap();
+        let option = MealOptionRepository::create(
+            pool,
+            CreateMealOption {
+                template_id: template.id,
+                name: "Ricotta".to_string(),
+                description: None,
+                nutritional_notes: None,
+            },
+        )
+        .await
+        .unwrap();
+        (template.id, option.id)
+    }
+
+    async fn create_tag(pool: &SqlitePool, name: &str) -> i64 {
+        TagRepository::create(
+            pool,
+            CreateTag {
+                name: name.to_string(),
+                display_name: name.to_string(),
+                category: TagCategory::Ingredient,
+                weekly_suggestion: None,
+                parent_tag_id: None,
+            },
+        )
+        .await
+        .unwrap()
+        .id
+    }
+
+    async fn uuid_of(pool: &SqlitePool, table: &str, id: i64) -> String {
+        sqlx::query_scalar(&format!("SELECT uuid FROM {} WHERE id = ?1", table))
+            .bind(id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn id_of(pool: &SqlitePool, table: &str, uuid: &str) -> i64 {
+        sqlx::query_scalar(&format!("SELECT id FROM {} WHERE uuid = ?1", table))
+            .bind(uuid)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_mutations_are_pending_until_pushed() {
+        let (pool, _temp_dir) = setup_test_db().await;
+        let (_, seq) = SyncRepository::get_pending_changes(&pool).await.unwrap();
+        SyncRepository::mark_pushed(&pool, seq).await.unwrap();
+        assert_eq!(
+            SyncRepository::get_status(&pool)
+                .await
+                .unwrap()
+                .pending_changes,
+            0
+        );
+
+        let (template_id, _) = create_option(&pool, "Toast").await;
+        let (changes, seq) = SyncRepository::get_pending_changes(&pool).await.unwrap();
+        let entities: Vec<SyncEntity> = changes.iter().map(|c| c.entity).collect();
+        assert_eq!(entities, vec![SyncEntity::Template, SyncEntity::Option]);
+        assert_eq!(
+            changes[0].key,
+            uuid_of(&pool, "meal_templates", template_id).await
+        );
+
+        let status = SyncRepository::get_status(&pool).await.unwrap();
+        assert_eq!(status.pending_changes, 2);
+        assert!(changes.iter().all(|c| c.device_id == status.device_id));
+
+        SyncRepository::mark_pushed(&pool, seq).await.unwrap();
+        assert_eq!(
+            SyncRepository::get_status(&pool)
+                .await
+                .unwrap()
+                .pending_changes,
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_pull_copies_entities() {
+        let (a, _dir_a) = setup_test_db().await;
+        let (b, _dir_b) = setup_test_db().await;
+
+        let (_, option_id) = create_option(&a, "Toast").await;
+        let tag_id = create_tag(&a, "ricotta").await;
+        MealOptionRepository::set_tags(&a, option_id, vec![tag_id])
+            .await
+            .unwrap();
+        MealOptionRepository::set_nutrients(
+            &a,
+            option_id,
+            SetNutrientProfile {
+                kcal: 250.0,
+                protein_g: 12.0,
+                carbs_g: 30.0,
+                fat_g: 8.0,
+                fiber_g: 3.0,
+            },
+        )
+        .await
+        .unwrap();
+        let entry = MealEntryRepository::create(
+            &a,
+            CreateMealEntry {
+                meal_option_id: option_id,
+                date: NaiveDate::from_ymd_opt(2025, 12, 1).unwrap(),
+                slot_type: SlotType::BREAKFAST,
+                location: LocationType::Home,
+                servings: Some(1.5),
+                notes: Some("Warm".to_string()),
+                status: Some(EntryStatus::Eaten),
+                extra_options: vec![],
+            },
+        )
+        .await
+        .unwrap();
+
+        let result = transfer(&a, &b).await;
+        assert!(result.skipped.is_empty(), "{:?}", result.skipped);
+
+        let option_uuid = uuid_of(&a, "meal_options", option_id).await;
+        let b_option_id = id_of(&b, "meal_options", &option_uuid).await;
+        let b_option = MealOptionRepository::get_with_tags(&b, b_option_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let b_tag = TagRepository::get_by_name(&b, "ricotta")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(b_option.tags, vec![b_tag.id]);
+        let b_nutrients = MealOptionRepository::get_nutrients(&b, b_option_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(b_nutrients.kcal, 250.0);
+
+        let b_entries = MealEntryRepository::get_by_meal_option(&b, b_option_id)
+            .await
+            .unwrap();
+        assert_eq!(b_entries.len(), 1);
+        assert_eq!(b_entries[0].servings, 1.5);
+        assert_eq!(b_entries[0].notes.as_deref(), Some("Warm"));
+        assert_eq!(b_entries[0].status, EntryStatus::Eaten);
+        assert_eq!(b_entries[0].tag_names, entry.tag_names);
+        assert_eq!(b_entries[0].options.len(), 1);
+        assert_eq!(b_entries[0].template_name, "Toast");
+
+        // Applied changes are not pushed back
+        let (changes, _) = SyncRepository::get_pending_changes(&b).await.unwrap();
+        assert!(changes.iter().all(|c| c.entity == SyncEntity::Slot));
+    }
+
+    #[tokio::test]
+    async fn test_latest_version_wins() {
+        let (a, _dir_a) = setup_test_db().await;
+        let (b, _dir_b) = setup_test_db().await;
+        let (template_id, _) = create_option(&a, "Toast").await;
+        transfer(&a, &b).await;
+
+        let uuid = uuid_of(&a, "meal_templates", template_id).await;
+        let b_template_id = id_of(&b, "meal_templates", &uuid).await;
+        let rename = |name: &str| UpdateMealTemplate {
+            name: Some(name.to_string()),
+            description: None,
+            compatible_slots: None,
+            location_type: None,
+            weekly_limit: None,
+        };
+
+        // Both rename offline: the higher (clock, device_id) wins on both sides
+        MealTemplateRepository::update(&a, template_id, rename("Toast A"))
+            .await
+            .unwrap();
+        MealTemplateRepository::update(&b, b_template_id, rename("Toast B"))
+            .await
+            .unwrap();
+        let (mut from_a, _) = SyncRepository::get_pending_changes(&a).await.unwrap();
+        let (mut from_b, _) = SyncRepository::get_pending_changes(&b).await.unwrap();
+        from_a.retain(|c| c.entity == SyncEntity::Template);
+        from_b.retain(|c| c.entity == SyncEntity::Template);
+        let winner = if from_a[0].supersedes(from_b[0].clock, &from_b[0].device_id) {
+            "Toast A"
+        } else {
+            "Toast B"
+        };
+
+        let on_a = SyncRepository::apply_pull(
+            &a,
+            SyncPull {
+                changes: from_b,
+                cursor: 0,
+            },
+        )
+        .await
+        .unwrap();
+        let on_b = SyncRepository::apply_pull(
+            &b,
+            SyncPull {
+                changes: from_a,
+                cursor: 0,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(on_a.applied + on_b.applied, 1);
+        assert_eq!(on_a.stale + on_b.stale, 1);
+
+        let a_name = MealTemplateRepository::get_by_id(&a, template_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .name;
+        let b_name = MealTemplateRepository::get_by_id(&b, b_template_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .name;
+        assert_eq!(a_name, winner);
+        assert_eq!(b_name, winner);
+
+        // An edit made after seeing the other device's wins everywhere
+        MealTemplateRepository::update(&a, template_id, rename("Toast C"))
+            .await
+            .unwrap();
+        let result = transfer(&a, &b).await;
+        assert_eq!(result.stale, 0);
+        let b_name = MealTemplateRepository::get_by_id(&b, b_template_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .name;
+        assert_eq!(b_name, "Toast C");
+    }
+
+    #[tokio::test]
+    async fn test_tag_name_collision_converges() {
+        let (a, _dir_a) = setup_test_db().await;
+        let (b, _dir_b) = setup_test_db().await;
+        let a_tag = create_tag(&a, "pasta").await;
+        let b_tag = create_tag(&b, "pasta").await;
+        let a_uuid = uuid_of(&a, "tags", a_tag).await;
+        let b_uuid = uuid_of(&b, "tags", b_tag).await;
+
+        let (from_a, _) = SyncRepository::get_pending_changes(&a).await.unwrap();
+        let (from_b, _) = SyncRepository::get_pending_changes(&b).await.unwrap();
+        SyncRepository::apply_pull(
+            &a,
+            SyncPull {
+                changes: from_b,
+                cursor: 0,
+            },
+        )
+        .await
+        .unwrap();
+        SyncRepository::apply_pull(
+            &b,
+            SyncPull {
+                changes: from_a,
+                cursor: 0,
+            },
+        )
+        .await
+        .unwrap();
+
+        let names = |pool: SqlitePool| async move {
+            let rows: Vec<(String, String)> =
+                sqlx::query_as("SELECT uuid, name FROM tags ORDER BY uuid")
+                    .fetch_all(&pool)
+                    .await
+                    .unwrap();
+            rows
+        };
+        let a_names = names(a.clone()).await;
+        assert_eq!(a_names, names(b.clone()).await);
+
+        let (low, high) = if a_uuid < b_uuid {
+            (a_uuid, b_uuid)
+        } else {
+            (b_uuid, a_uuid)
+        };
+        assert!(a_names.contains(&(low, "pasta".to_string())));
+        assert!(a_names.contains(&(high.clone(), suffixed_tag_name("pasta", &high))));
+        assert!(suffixed_tag_name("pasta", &high)
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c == '_'));
+    }
+
+    #[tokio::test]
+    async fn test_blocked_delete_is_skipped() {
+        let (a, _dir_a) = setup_test_db().await;
+        let (b, _dir_b) = setup_test_db().await;
+        let (_, option_id) = create_option(&a, "Toast").await;
+        transfer(&a, &b).await;
+
+        // B logs a meal with the option while A deletes it
+        let uuid = uuid_of(&a, "meal_options", option_id).await;
+        let b_option_id = id_of(&b, "meal_options", &uuid).await;
+        MealEntryRepository::create(
+            &b,
+            CreateMealEntry {
+                meal_option_id: b_option_id,
+                date: NaiveDate::from_ymd_opt(2025, 12, 1).unwrap(),
+                slot_type: SlotType::BREAKFAST,
+                location: LocationType::Home,
+                servings: None,
+                notes: None,
+                status: None,
+                extra_options: vec![],
+            },
+        )
+        .await
+        .unwrap();
+        MealOptionRepository::delete(&a, option_id).await.unwrap();
+
+        let result = transfer(&a, &b).await;
+        assert_eq!(result.skipped.len(), 1);
+        assert_eq!(result.skipped[0].entity, SyncEntity::Option);
+        assert!(MealOptionRepository::get_by_id(&b, b_option_id)
+            .await
+            .unwrap()
+            .is_some());
+
+        // A gets an entry it cannot place
+        let result = transfer(&b, &a).await;
+        assert_eq!(result.skipped.len(), 1);
+        assert_eq!(result.skipped[0].entity, SyncEntity::Entry);
+    }
+
+    #[tokio::test]
+    async fn test_set_settings() {
+        let (pool, _temp_dir) = setup_test_db().await;
+
+        let result = SyncRepository::set_settings(
+            &pool,
+            SyncSettings {
+                server_url: Some("nas.local:8787".to_string()),
+                token: Some("secret".to_string()),
+            },
+        )
+        .await;
+        assert!(result.is_err());
+
+        let status = SyncRepository::set_settings(
+            &pool,
+            SyncSettings {
+                server_url: Some("http://nas.local:8787".to_string()),
+                token: Some("secret".to_string()),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(status.server_url.as_deref(), Some("http://nas.local:8787"));
+        assert_eq!(
+            SyncRepository::get_server(&pool).await.unwrap(),
+            Some(("http://nas.local:8787".to_string(), "secret".to_string()))
+        );
+
+        SyncRepository::set_settings(
+            &pool,
+            SyncSettings {
+                server_url: None,
+                token: Some("secret".to_string()),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(SyncRepository::get_server(&pool).await.unwrap(), None);
+    }
+}
@@ -1,4 +1,8 @@
-use crate::models::{CreateTag, Tag, TagCategory, UpdateTag};
+use crate::models::{
+    CreateTag, DeleteBlocked, DeleteBlocker, LibraryItemKind, SyncEntity, Tag, TagCategory,
+    UpdateTag,
+};
+use crate::repository::AuditScope;
 use sqlx::{Result, Row, SqlitePool};
 
 pub struct TagRepository;
@@ -21,6 +25,7 @@ impl TagRepository {
             category,
             weekly_suggestion: row.try_get("weekly_suggestion")?,
             parent_tag_id: row.try_get("parent_tag_id")?,
+            archived: row.try_get("archived")?,
             created_at: row.try_get("created_at")?,
         })
     }
@@ -31,11 +36,12 @@ impl TagRepository {
 
         let category_str = tag.category.to_db_string();
 
+        let mut tx = pool.begin().await?;
         let row = sqlx::query(
             r#"
             INSERT INTO tags (name, display_name, category, weekly_suggestion, parent_tag_id)
             VALUES (?1, ?2, ?3, ?4, ?5)
-            RETURNING id, name, display_name, category, weekly_suggestion, parent_tag_id, created_at
+            RETURNING id, name, display_name, category, weekly_suggestion, parent_tag_id, archived, created_at
             "#,
         )
         .bind(&tag.name)
@@ -43,17 +49,23 @@ impl TagRepository {
         .bind(category_str)
         .bind(tag.weekly_suggestion)
         .bind(tag.parent_tag_id)
-        .fetch_one(pool)
+        .fetch_one(&mut *tx)
         .await?;
+        let created = Self::row_to_tag(&row)?;
 
-        Self::row_to_tag(&row)
+        let mut audit = AuditScope::new("create_tag");
+        audit.created(SyncEntity::Tag, created.id);
+        audit.record(&mut tx).await?;
+        tx.commit().await?;
+
+        Ok(created)
     }
 
     /// Get a tag by ID
     pub async fn get_by_id(pool: &SqlitePool, id: i64) -> Result<Option<Tag>> {
         let row = sqlx::query(
             r#"
-            SELECT id, name, display_name, category, weekly_suggestion, parent_tag_id, created_at
+            SELECT id, name, display_name, category, weekly_suggestion, parent_tag_id, archived, created_at
             FROM tags
             WHERE id = ?1
             "#,
@@ -72,7 +84,7 @@ impl TagRepository {
     pub async fn get_by_name(pool: &SqlitePool, name: &str) -> Result<Option<Tag>> {
         let row = sqlx::query(
             r#"
-            SELECT id, name, display_name, category, weekly_suggestion, parent_tag_id, created_at
+            SELECT id, name, display_name, category, weekly_suggestion, parent_tag_id, archived, created_at
             FROM tags
             WHERE name = ?1
             "#,
@@ -87,12 +99,13 @@ impl TagRepository {
         }
     }
 
-    /// Get all tags
+    /// Get all tags that are not archived
     pub async fn get_all(pool: &SqlitePool) -> Result<Vec<Tag>> {
         let rows = sqlx::query(
             r#"
-            SELECT id, name, display_name, category, weekly_suggestion, parent_tag_id, created_at
+            SELECT id, name, display_name, category, weekly_suggestion, parent_tag_id, archived, created_at
             FROM tags
+            WHERE archived = 0
             ORDER BY name
             "#,
         )
@@ -102,15 +115,46 @@ impl TagRepository {
         rows.iter().map(Self::row_to_tag).collect()
     }
 
-    /// Get all tags by category
+    /// Get every tag, archived ones included (usage roll-ups, export)
+    pub async fn get_all_including_archived(pool: &SqlitePool) -> Result<Vec<Tag>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, name, display_name, category, weekly_suggestion, parent_tag_id, archived, created_at
+            FROM tags
+            ORDER BY name
+            "#,
+        )
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter().map(Self::row_to_tag).collect()
+    }
+
+    /// Get the archived tags
+    pub async fn get_archived(pool: &SqlitePool) -> Result<Vec<Tag>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, name, display_name, category, weekly_suggestion, parent_tag_id, archived, created_at
+            FROM tags
+            WHERE archived = 1
+            ORDER BY name
+            "#,
+        )
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter().map(Self::row_to_tag).collect()
+    }
+
+    /// Get all tags by category, archived ones excluded
     pub async fn get_by_category(pool: &SqlitePool, category: TagCategory) -> Result<Vec<Tag>> {
         let category_str = category.to_db_string();
 
         let rows = sqlx::query(
             r#"
-            SELECT id, name, display_name, category, weekly_suggestion, parent_tag_id, created_at
+            SELECT id, name, display_name, category, weekly_suggestion, parent_tag_id, archived, created_at
             FROM tags
-            WHERE category = ?1
+            WHERE category = ?1 AND archived = 0
             ORDER BY name
             "#,
         )
@@ -121,13 +165,13 @@ impl TagRepository {
         rows.iter().map(Self::row_to_tag).collect()
     }
 
-    /// Get child tags of a parent tag
+    /// Get child tags of a parent tag, archived ones excluded
     pub async fn get_children(pool: &SqlitePool, parent_id: i64) -> Result<Vec<Tag>> {
         let rows = sqlx::query(
             r#"
-            SELECT id, name, display_name, category, weekly_suggestion, parent_tag_id, created_at
+            SELECT id, name, display_name, category, weekly_suggestion, parent_tag_id, archived, created_at
             FROM tags
-            WHERE parent_tag_id = ?1
+            WHERE parent_tag_id = ?1 AND archived = 0
             ORDER BY name
             "#,
         )
@@ -138,6 +182,35 @@ impl TagRepository {
         rows.iter().map(Self::row_to_tag).collect()
     }
 
+    /// Get the tags of a meal option together with all of their ancestors
+    /// Eating "pasta_integrale" is also an occurrence of its parent "pasta"
+    pub async fn get_option_tags_with_ancestors(
+        pool: &SqlitePool,
+        meal_option_id: i64,
+    ) -> Result<Vec<Tag>> {
+        let rows = sqlx::query(
+            r#"
+            WITH RECURSIVE lineage(id) AS (
+                SELECT tag_id FROM meal_option_tags WHERE meal_option_id = ?1
+                UNION
+                SELECT t.parent_tag_id
+                FROM tags t
+                JOIN lineage l ON t.id = l.id
+                WHERE t.parent_tag_id IS NOT NULL
+            )
+            SELECT id, name, display_name, category, weekly_suggestion, parent_tag_id, archived, created_at
+            FROM tags
+            WHERE id IN (SELECT id FROM lineage)
+            ORDER BY name
+            "#,
+        )
+        .bind(meal_option_id)
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter().map(Self::row_to_tag).collect()
+    }
+
     /// Update a tag
     pub async fn update(pool: &SqlitePool, id: i64, update: UpdateTag) -> Result<Tag> {
         // Get existing tag first
@@ -161,12 +234,40 @@ impl TagRepository {
             None => existing.parent_tag_id,
         };
 
+        // Usage roll-ups walk parent_tag_id, so the hierarchy must stay acyclic
+        if let Some(parent_id) = parent_tag_id {
+            let creates_cycle: bool = sqlx::query_scalar(
+                r#"
+                WITH RECURSIVE subtree(id) AS (
+                    SELECT ?1
+                    UNION
+                    SELECT t.id FROM tags t JOIN subtree s ON t.parent_tag_id = s.id
+                )
+                SELECT EXISTS(SELECT 1 FROM subtree WHERE id = ?2)
+                "#,
+            )
+            .bind(id)
+            .bind(parent_id)
+            .fetch_one(pool)
+            .await?;
+
+            if creates_cycle {
+                return Err(sqlx::Error::Protocol(
+                    "A tag cannot be nested under itself or one of its descendants".to_string(),
+                ));
+            }
+        }
+
+        let mut tx = pool.begin().await?;
+        let mut audit = AuditScope::new("update_tag");
+        audit.track(&mut tx, SyncEntity::Tag, [id]).await?;
+
         let row = sqlx::query(
             r#"
             UPDATE tags
             SET display_name = ?1, category = ?2, weekly_suggestion = ?3, parent_tag_id = ?4
             WHERE id = ?5
-            RETURNING id, name, display_name, category, weekly_suggestion, parent_tag_id, created_at
+            RETURNING id, name, display_name, category, weekly_suggestion, parent_tag_id, archived, created_at
             "#,
         )
         .bind(&display_name)
@@ -174,19 +275,120 @@ impl TagRepository {
         .bind(weekly_suggestion)
         .bind(parent_tag_id)
         .bind(id)
-        .fetch_one(pool)
+        .fetch_one(&mut *tx)
         .await?;
 
+        audit.record(&mut tx).await?;
+        tx.commit().await?;
+
+        Self::row_to_tag(&row)
+    }
+
+    /// Archive or restore a tag
+    /// Options keep an archived tag and it still counts in usage roll-ups
+    pub async fn set_archived(pool: &SqlitePool, id: i64, archived: bool) -> Result<Tag> {
+        let mut tx = pool.begin().await?;
+        let mut audit = AuditScope::new(if archived {
+            "archive_tag"
+        } else {
+            "unarchive_tag"
+        });
+        audit.track(&mut tx, SyncEntity::Tag, [id]).await?;
+
+        let row = sqlx::query(
+            r#"
+            UPDATE tags SET archived = ?1 WHERE id = ?2
+            RETURNING id, name, display_name, category, weekly_suggestion, parent_tag_id, archived, created_at
+            "#,
+        )
+        .bind(archived)
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
+
+        audit.record(&mut tx).await?;
+        tx.commit().await?;
+
         Self::row_to_tag(&row)
     }
 
+    /// Explain what keeps a tag from being deleted
+    /// Returns None when the tag does not exist or nothing depends on it
+    pub async fn get_delete_blockers(pool: &SqlitePool, id: i64) -> Result<Option<DeleteBlocked>> {
+        let Some(tag) = Self::get_by_id(pool, id).await? else {
+            return Ok(None);
+        };
+
+        let mut blockers = Vec::new();
+
+        let option_names: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT mo.name FROM meal_option_tags mot
+            JOIN meal_options mo ON mo.id = mot.meal_option_id
+            WHERE mot.tag_id = ?1
+            ORDER BY mo.name
+            "#,
+        )
+        .bind(id)
+        .fetch_all(pool)
+        .await?;
+        if !option_names.is_empty() {
+            blockers.push(DeleteBlocker::TaggedOptions { option_names });
+        }
+
+        let tag_names: Vec<String> =
+            sqlx::query_scalar("SELECT name FROM tags WHERE parent_tag_id = ?1 ORDER BY name")
+                .bind(id)
+                .fetch_all(pool)
+                .await?;
+        if !tag_names.is_empty() {
+            blockers.push(DeleteBlocker::ChildTags { tag_names });
+        }
+
+        if blockers.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(DeleteBlocked {
+            kind: LibraryItemKind::Tag,
+            id,
+            name: tag.name,
+            blockers,
+        }))
+    }
+
     /// Delete a tag
+    /// Its child tags lose their parent and its options lose the tag
     pub async fn delete(pool: &SqlitePool, id: i64) -> Result<bool> {
+        let mut tx = pool.begin().await?;
+        let mut audit = AuditScope::new("delete_tag");
+        audit.track(&mut tx, SyncEntity::Tag, [id]).await?;
+        audit
+            .track_query(
+                &mut tx,
+                SyncEntity::Tag,
+                "SELECT id FROM tags WHERE parent_tag_id = ?1",
+                id,
+            )
+            .await?;
+        audit
+            .track_query(
+                &mut tx,
+                SyncEntity::Option,
+                "SELECT meal_option_id FROM meal_option_tags WHERE tag_id = ?1",
+                id,
+            )
+            .await?;
+
         let result = sqlx::query("DELETE FROM tags WHERE id = ?1")
             .bind(id)
-            .execute(pool)
+            .execute(&mut *tx)
             .await?;
 
+        audit.record(&mut tx).await?;
+        tx.commit().await?;
+
         Ok(result.rows_affected() > 0)
     }
 }
@@ -457,4 +659,125 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_update_rejects_parent_cycle() {
+        let pool = setup_test_db().await;
+
+        let mut parent_tag_id = None;
+        let mut ids = Vec::new();
+        for name in ["pasta", "pasta_integrale", "spaghetti_integrali"] {
+            let tag = TagRepository::create(
+                &pool,
+                CreateTag {
+                    name: name.to_string(),
+                    display_name: name.to_string(),
+                    category: TagCategory::Ingredient,
+                    weekly_suggestion: None,
+                    parent_tag_id,
+                },
+            )
+            .await
+            .unwrap();
+            parent_tag_id = Some(tag.id);
+            ids.push(tag.id);
+        }
+
+        // Root under its own grandchild
+        let update = UpdateTag {
+            display_name: None,
+            category: None,
+            weekly_suggestion: None,
+            parent_tag_id: Some(Some(ids[2])),
+        };
+        assert!(TagRepository::update(&pool, ids[0], update).await.is_err());
+
+        // Tag as its own parent
+        let update = UpdateTag {
+            display_name: None,
+            category: None,
+            weekly_suggestion: None,
+            parent_tag_id: Some(Some(ids[1])),
+        };
+        assert!(TagRepository::update(&pool, ids[1], update).await.is_err());
+
+        // Moving a leaf elsewhere is fine
+        let update = UpdateTag {
+            display_name: None,
+            category: None,
+            weekly_suggestion: None,
+            parent_tag_id: Some(Some(ids[0])),
+        };
+        assert!(TagRepository::update(&pool, ids[2], update).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_archive_tag_and_delete_blockers() {
+        let pool = setup_test_db().await;
+
+        let parent = TagRepository::create(
+            &pool,
+            CreateTag {
+                name: "pasta".to_string(),
+                display_name: "Pasta".to_string(),
+                category: TagCategory::Ingredient,
+                weekly_suggestion: Some(3),
+                parent_tag_id: None,
+            },
+        )
+        .await
+        .unwrap();
+        let child = TagRepository::create(
+            &pool,
+            CreateTag {
+                name: "pasta_integrale".to_string(),
+                display_name: "Pasta Integrale".to_string(),
+                category: TagCategory::Ingredient,
+                weekly_suggestion: None,
+                parent_tag_id: Some(parent.id),
+            },
+        )
+        .await
+        .unwrap();
+
+        let blocked = TagRepository::get_delete_blockers(&pool, parent.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            blocked.blockers,
+            vec![DeleteBlocker::ChildTags {
+                tag_names: vec!["pasta_integrale".to_string()]
+            }]
+        );
+        assert!(TagRepository::get_delete_blockers(&pool, child.id)
+            .await
+            .unwrap()
+            .is_none());
+
+        let archived = TagRepository::set_archived(&pool, child.id, true)
+            .await
+            .unwrap();
+        assert!(archived.archived);
+
+        let all = TagRepository::get_all(&pool).await.unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].name, "pasta");
+        assert!(TagRepository::get_children(&pool, parent.id)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            TagRepository::get_all_including_archived(&pool)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+
+        TagRepository::set_archived(&pool, child.id, false)
+            .await
+            .unwrap();
+        assert_eq!(TagRepository::get_all(&pool).await.unwrap().len(), 2);
+    }
 }
@@ -0,0 +1,154 @@
+// Data endpoints
+// Export/import, library packs, plan text import, backups and undo/redo,
+// mirroring their Tauri commands
+
+use super::{respond, ApiResponse, AppState, Json, Path, Query};
+use crate::db::backup;
+use crate::db::BackupInfo;
+use crate::error::ApiError;
+use crate::models::{
+    AuditOperation, AuditRecord, DatabaseExport, ImportSummary, LibraryPack, LibraryPackImport,
+    PackConflictStrategy, PlanImport, SyncEntity,
+};
+use crate::repository::AuditRepository;
+use crate::services::{ExportService, LibraryPackService, PlanImportService};
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use axum::Router;
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+pub(super) fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/export", get(export_database))
+        .route("/export/entries.csv", get(export_entries_csv))
+        .route("/import", post(import_database))
+        .route("/library-pack", get(export_library_pack))
+        .route("/library-pack/import", post(import_library_pack))
+        .route("/plan-import", post(import_plan_text))
+        .route("/backups", get(list_backups).post(create_backup))
+        .route("/backups/{file_name}/restore", post(restore_backup))
+        .route("/undo", post(undo_last))
+        .route("/redo", post(redo))
+        .route("/history/{entity}/{id}", get(get_history))
+}
+
+async fn export_database(State(state): State<AppState>) -> ApiResponse<DatabaseExport> {
+    respond(ExportService::export_database(state.backend.sqlite()?).await)
+}
+
+async fn import_database(
+    State(state): State<AppState>,
+    Json(document): Json<serde_json::Value>,
+) -> ApiResponse<ImportSummary> {
+    respond(ExportService::import_database(state.backend.sqlite()?, document).await)
+}
+
+#[derive(Debug, Deserialize)]
+struct CsvFilter {
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+}
+
+/// Same columns as the export_entries_csv command, sent as the response body
+/// instead of being written to a path on the server
+async fn export_entries_csv(
+    State(state): State<AppState>,
+    Query(filter): Query<CsvFilter>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut csv = Vec::new();
+    ExportService::write_entries_csv(
+        state.backend.sqlite()?,
+        filter.start_date,
+        filter.end_date,
+        &mut csv,
+    )
+    .await?;
+
+    Ok(([(header::CONTENT_TYPE, "text/csv; charset=utf-8")], csv))
+}
+
+async fn export_library_pack(State(state): State<AppState>) -> ApiResponse<LibraryPack> {
+    respond(LibraryPackService::export_pack(state.backend.sqlite()?).await)
+}
+
+#[derive(Debug, Deserialize)]
+struct LibraryPackBody {
+    document: serde_json::Value,
+    on_conflict: PackConflictStrategy,
+    #[serde(default)]
+    preview: bool,
+}
+
+async fn import_library_pack(
+    State(state): State<AppState>,
+    Json(body): Json<LibraryPackBody>,
+) -> ApiResponse<LibraryPackImport> {
+    respond(
+        LibraryPackService::import_pack(
+            state.backend.sqlite()?,
+            body.document,
+            body.on_conflict,
+            body.preview,
+        )
+        .await,
+    )
+}
+
+#[derive(Debug, Deserialize)]
+struct PlanTextBody {
+    text: String,
+    #[serde(default)]
+    preview: bool,
+}
+
+async fn import_plan_text(
+    State(state): State<AppState>,
+    Json(body): Json<PlanTextBody>,
+) -> ApiResponse<PlanImport> {
+    respond(PlanImportService::import_text(state.backend.sqlite()?, &body.text, body.preview).await)
+}
+
+async fn list_backups(State(state): State<AppState>) -> ApiResponse<Vec<BackupInfo>> {
+    state.backend.sqlite()?;
+    respond(backup::list_backups(&state.backup_config))
+}
+
+async fn create_backup(State(state): State<AppState>) -> ApiResponse<BackupInfo> {
+    respond(backup::create_backup(state.backend.sqlite()?, &state.backup_config, "manual").await)
+}
+
+/// The current state is snapshotted first; that snapshot is returned
+async fn restore_backup(
+    State(state): State<AppState>,
+    Path(file_name): Path<String>,
+) -> ApiResponse<BackupInfo> {
+    let exists = backup::list_backups(&state.backup_config)?
+        .iter()
+        .any(|b| b.file_name == file_name);
+    if !exists {
+        return Err(ApiError::NotFound(format!(
+            "Backup '{}' not found",
+            file_name
+        )));
+    }
+
+    respond(backup::restore_backup(state.backend.sqlite()?, &state.backup_config, &file_name).await)
+}
+
+async fn undo_last(State(state): State<AppState>) -> ApiResponse<Option<AuditOperation>> {
+    respond(AuditRepository::undo_last(state.backend.sqlite()?).await)
+}
+
+async fn redo(State(state): State<AppState>) -> ApiResponse<Option<AuditOperation>> {
+    respond(AuditRepository::redo(state.backend.sqlite()?).await)
+}
+
+async fn get_history(
+    State(state): State<AppState>,
+    Path((entity, id)): Path<(SyncEntity, i64)>,
+) -> ApiResponse<Vec<AuditRecord>> {
+    respond(AuditRepository::get_history(state.backend.sqlite()?, entity, id).await)
+}
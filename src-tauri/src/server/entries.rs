@@ -0,0 +1,264 @@
+// Meal entry endpoints
+// Entry CRUD, logging, validation and weekly usage, mirroring the meal entry Tauri commands
+
+use super::{check_range, found, respond, ApiResponse, AppState, Json, Path, Query};
+use crate::error::ApiError;
+use crate::models::{
+    CreateMealEntry, CreateOffPlanEntry, EntryStatus, LogActualMeal, MealEntry, SlotType,
+    UpdateMealEntry, WeeklyTagUsage, WeeklyTagUsageNode, WeeklyTemplateUsage, WeeklyUsage,
+};
+use crate::repository::MealEntryStore;
+use crate::services::{ValidationService, ValidationWarning};
+use axum::extract::State;
+use axum::routing::{get, post};
+use axum::Router;
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+pub(super) fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/entries", get(list_entries).post(create_entry))
+        .route("/entries/recent", get(recent_entries))
+        .route("/entries/off-plan", post(create_off_plan_entry))
+        .route("/entries/validate", post(validate_entry))
+        .route(
+            "/entries/{id}",
+            get(get_entry).put(update_entry).delete(delete_entry),
+        )
+        .route("/entries/{id}/actual", post(log_actual))
+        .route("/usage/options/{id}", get(option_usage))
+        .route("/usage/templates", get(template_usage))
+        .route("/usage/tags/{id}", get(tag_usage))
+        .route("/usage/tags/{id}/breakdown", get(tag_usage_breakdown))
+}
+
+/// Exactly one way of selecting entries: a date (optionally with a slot), a date
+/// range, a status or a meal option
+#[derive(Debug, Deserialize)]
+struct EntryFilter {
+    date: Option<NaiveDate>,
+    slot: Option<SlotType>,
+    start_date: Option<NaiveDate>,
+    end_date: Option<NaiveDate>,
+    status: Option<EntryStatus>,
+    meal_option_id: Option<i64>,
+}
+
+async fn list_entries(
+    State(state): State<AppState>,
+    Query(filter): Query<EntryFilter>,
+) -> ApiResponse<Vec<MealEntry>> {
+    let backend = &state.backend;
+    let entries = match filter {
+        EntryFilter {
+            date: Some(date),
+            slot,
+            start_date: None,
+            end_date: None,
+            status: None,
+            meal_option_id: None,
+        } => match slot {
+            Some(slot) => backend.get_entries_by_date_and_slot(date, &slot).await,
+            None => backend.get_entries_by_date(date).await,
+        },
+        EntryFilter {
+            date: None,
+            slot: None,
+            start_date: Some(start),
+            end_date: Some(end),
+            status: None,
+            meal_option_id: None,
+        } => {
+            check_range(start, end)?;
+            backend.get_entries_by_date_range(start, end).await
+        }
+        EntryFilter {
+            date: None,
+            slot: None,
+            start_date: None,
+            end_date: None,
+            status: Some(status),
+            meal_option_id: None,
+        } => backend.get_entries_by_status(status).await,
+        EntryFilter {
+            date: None,
+            slot: None,
+            start_date: None,
+            end_date: None,
+            status: None,
+            meal_option_id: Some(option_id),
+        } => backend.get_entries_by_option(option_id).await,
+        _ => return Err(ApiError::ValidationError(
+            "Filter entries by date (and slot), start_date and end_date, status or meal_option_id"
+                .to_string(),
+        )),
+    };
+
+    respond(entries)
+}
+
+#[derive(Debug, Deserialize)]
+struct RecentFilter {
+    limit: i32,
+}
+
+async fn recent_entries(
+    State(state): State<AppState>,
+    Query(filter): Query<RecentFilter>,
+) -> ApiResponse<Vec<MealEntry>> {
+    respond(state.backend.get_recent_entries(filter.limit).await)
+}
+
+async fn get_entry(State(state): State<AppState>, Path(id): Path<i64>) -> ApiResponse<MealEntry> {
+    found(state.backend.get_entry(id).await?, "Meal entry", id)
+}
+
+/// Validated like the create_entry command; answers `[entry, warnings]`
+async fn create_entry(
+    State(state): State<AppState>,
+    Json(entry): Json<CreateMealEntry>,
+) -> ApiResponse<(MealEntry, Vec<ValidationWarning>)> {
+    let warnings = ValidationService::validate_meal_selection(
+        &state.backend,
+        &entry.option_ids(),
+        &entry.slot_type,
+        entry.date,
+    )
+    .await?;
+
+    let created = state.backend.create_entry(entry).await?;
+
+    Ok(Json((created, warnings)))
+}
+
+async fn create_off_plan_entry(
+    State(state): State<AppState>,
+    Json(entry): Json<CreateOffPlanEntry>,
+) -> ApiResponse<MealEntry> {
+    respond(state.backend.create_off_plan_entry(entry).await)
+}
+
+async fn update_entry(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(updates): Json<UpdateMealEntry>,
+) -> ApiResponse<MealEntry> {
+    respond(state.backend.update_entry(id, updates).await)
+}
+
+/// Same rules as the log_actual command; answers `[entry, warnings]`
+async fn log_actual(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(actual): Json<LogActualMeal>,
+) -> ApiResponse<(MealEntry, Vec<ValidationWarning>)> {
+    let entry = state
+        .backend
+        .get_entry(id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Meal entry {} not found", id)))?;
+
+    if entry.status == EntryStatus::Eaten {
+        return Err(ApiError::ValidationError(format!(
+            "Meal entry {} is already logged as eaten",
+            id
+        )));
+    }
+
+    if entry.is_off_plan() {
+        return Err(ApiError::ValidationError(format!(
+            "Meal entry {} is off-plan and has no plan to log against",
+            id
+        )));
+    }
+
+    let warnings = ValidationService::validate_meal_selection(
+        &state.backend,
+        &actual.option_ids(),
+        &entry.slot_type,
+        entry.date,
+    )
+    .await?;
+
+    let logged = state.backend.log_actual(id, actual).await?;
+
+    Ok(Json((logged, warnings)))
+}
+
+async fn delete_entry(State(state): State<AppState>, Path(id): Path<i64>) -> ApiResponse<()> {
+    respond(state.backend.delete_entry(id).await)
+}
+
+#[derive(Debug, Deserialize)]
+struct ValidateEntryBody {
+    meal_option_id: i64,
+    #[serde(default)]
+    extra_option_ids: Vec<i64>, // Other options of a composite meal
+    slot: SlotType,
+    date: NaiveDate,
+}
+
+async fn validate_entry(
+    State(state): State<AppState>,
+    Json(body): Json<ValidateEntryBody>,
+) -> ApiResponse<Vec<ValidationWarning>> {
+    let option_ids: Vec<i64> = std::iter::once(body.meal_option_id)
+        .chain(body.extra_option_ids)
+        .collect();
+
+    respond(
+        ValidationService::validate_meal_selection(
+            &state.backend,
+            &option_ids,
+            &body.slot,
+            body.date,
+        )
+        .await,
+    )
+}
+
+#[derive(Debug, Deserialize)]
+struct WeekFilter {
+    week: String, // Format: "YYYY-WW" - ISO week format
+}
+
+async fn option_usage(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Query(filter): Query<WeekFilter>,
+) -> ApiResponse<Option<WeeklyUsage>> {
+    respond(state.backend.get_weekly_usage(id, &filter.week).await)
+}
+
+async fn template_usage(
+    State(state): State<AppState>,
+    Query(filter): Query<WeekFilter>,
+) -> ApiResponse<Vec<WeeklyTemplateUsage>> {
+    respond(
+        state
+            .backend
+            .get_all_weekly_template_usage(&filter.week)
+            .await,
+    )
+}
+
+async fn tag_usage(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Query(filter): Query<WeekFilter>,
+) -> ApiResponse<Option<WeeklyTagUsage>> {
+    respond(state.backend.get_weekly_tag_usage(id, &filter.week).await)
+}
+
+async fn tag_usage_breakdown(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Query(filter): Query<WeekFilter>,
+) -> ApiResponse<Vec<WeeklyTagUsageNode>> {
+    respond(
+        state
+            .backend
+            .get_weekly_tag_usage_breakdown(id, &filter.week)
+            .await,
+    )
+}
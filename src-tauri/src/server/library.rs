@@ -0,0 +1,444 @@
+// Library endpoints
+// Meal slots, tags, meal templates and meal options, mirroring their Tauri commands
+
+use super::{found, respond, ApiResponse, AppState, Json, Path, Query};
+use crate::error::ApiError;
+use crate::models::{
+    CreateMealOption, CreateMealSlot, CreateMealTemplate, CreateTag, LocationType, MealOption,
+    MealSlot, MealTemplate, NutrientProfile, OptionGroup, SetNutrientProfile, SlotType, Tag,
+    TagCategory, UpdateMealOption, UpdateMealSlot, UpdateMealTemplate, UpdateTag,
+};
+use crate::repository::{MealOptionStore, MealSlotStore, MealTemplateStore, TagStore};
+use axum::extract::State;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post, put};
+use axum::Router;
+use serde::Deserialize;
+
+pub(super) fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/slots", get(list_slots).post(create_slot))
+        .route(
+            "/slots/{id}",
+            get(get_slot).put(update_slot).delete(delete_slot),
+        )
+        .route("/tags", get(list_tags).post(create_tag))
+        .route("/tags/archived", get(archived_tags))
+        .route("/tags/by-name/{name}", get(get_tag_by_name))
+        .route(
+            "/tags/{id}",
+            get(get_tag).put(update_tag).delete(delete_tag),
+        )
+        .route("/tags/{id}/children", get(tag_children))
+        .route("/tags/{id}/archive", post(archive_tag))
+        .route("/tags/{id}/unarchive", post(unarchive_tag))
+        .route("/templates", get(list_templates).post(create_template))
+        .route("/templates/archived", get(archived_templates))
+        .route(
+            "/templates/{id}",
+            get(get_template)
+                .put(update_template)
+                .delete(delete_template),
+        )
+        .route(
+            "/templates/{id}/option-groups",
+            get(get_option_groups).put(set_option_groups),
+        )
+        .route("/templates/{id}/options", get(template_options))
+        .route("/templates/{id}/archive", post(archive_template))
+        .route("/templates/{id}/unarchive", post(unarchive_template))
+        .route("/options", get(list_options).post(create_option))
+        .route("/options/archived", get(archived_options))
+        .route(
+            "/options/{id}",
+            get(get_option).put(update_option).delete(delete_option),
+        )
+        .route("/options/{id}/group", put(set_option_group))
+        .route("/options/{id}/archive", post(archive_option))
+        .route("/options/{id}/unarchive", post(unarchive_option))
+        .route(
+            "/options/{id}/tags",
+            post(add_option_tags).put(set_option_tags),
+        )
+        .route("/options/{id}/tags/remove", post(remove_option_tags))
+        .route(
+            "/options/{id}/nutrients",
+            get(get_option_nutrients)
+                .put(set_option_nutrients)
+                .delete(delete_option_nutrients),
+        )
+}
+
+// ============================================================================
+// Meal slots
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+struct SlotFilter {
+    #[serde(default)]
+    active: bool, // Only active slots
+}
+
+async fn list_slots(
+    State(state): State<AppState>,
+    Query(filter): Query<SlotFilter>,
+) -> ApiResponse<Vec<MealSlot>> {
+    if filter.active {
+        respond(state.backend.get_active_slots().await)
+    } else {
+        respond(state.backend.get_slots().await)
+    }
+}
+
+async fn get_slot(State(state): State<AppState>, Path(id): Path<i64>) -> ApiResponse<MealSlot> {
+    found(state.backend.get_slot(id).await?, "Meal slot", id)
+}
+
+async fn create_slot(
+    State(state): State<AppState>,
+    Json(slot): Json<CreateMealSlot>,
+) -> ApiResponse<MealSlot> {
+    respond(state.backend.create_slot(slot).await)
+}
+
+async fn update_slot(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(updates): Json<UpdateMealSlot>,
+) -> ApiResponse<MealSlot> {
+    respond(state.backend.update_slot(id, updates).await)
+}
+
+async fn delete_slot(State(state): State<AppState>, Path(id): Path<i64>) -> ApiResponse<bool> {
+    respond(state.backend.delete_slot(id).await)
+}
+
+// ============================================================================
+// Tags
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+struct TagFilter {
+    category: Option<TagCategory>,
+}
+
+async fn list_tags(
+    State(state): State<AppState>,
+    Query(filter): Query<TagFilter>,
+) -> ApiResponse<Vec<Tag>> {
+    match filter.category {
+        Some(category) => respond(state.backend.get_tags_by_category(category).await),
+        None => respond(state.backend.get_tags().await),
+    }
+}
+
+async fn archived_tags(State(state): State<AppState>) -> ApiResponse<Vec<Tag>> {
+    respond(state.backend.get_archived_tags().await)
+}
+
+async fn get_tag(State(state): State<AppState>, Path(id): Path<i64>) -> ApiResponse<Tag> {
+    found(state.backend.get_tag(id).await?, "Tag", id)
+}
+
+async fn get_tag_by_name(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResponse<Tag> {
+    found(state.backend.get_tag_by_name(&name).await?, "Tag", &name)
+}
+
+async fn tag_children(State(state): State<AppState>, Path(id): Path<i64>) -> ApiResponse<Vec<Tag>> {
+    respond(state.backend.get_child_tags(id).await)
+}
+
+async fn create_tag(State(state): State<AppState>, Json(tag): Json<CreateTag>) -> ApiResponse<Tag> {
+    respond(state.backend.create_tag(tag).await)
+}
+
+async fn update_tag(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(updates): Json<UpdateTag>,
+) -> ApiResponse<Tag> {
+    respond(state.backend.update_tag(id, updates).await)
+}
+
+async fn archive_tag(State(state): State<AppState>, Path(id): Path<i64>) -> ApiResponse<Tag> {
+    respond(state.backend.set_tag_archived(id, true).await)
+}
+
+async fn unarchive_tag(State(state): State<AppState>, Path(id): Path<i64>) -> ApiResponse<Tag> {
+    respond(state.backend.set_tag_archived(id, false).await)
+}
+
+async fn delete_tag(State(state): State<AppState>, Path(id): Path<i64>) -> ApiResponse<bool> {
+    if let Some(blocked) = state.backend.get_tag_delete_blockers(id).await? {
+        return Err(ApiError::DeleteBlocked(blocked));
+    }
+
+    respond(state.backend.delete_tag(id).await)
+}
+
+// ============================================================================
+// Meal templates
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+struct TemplateFilter {
+    search: Option<String>, // Name search, optionally with `slot`
+    location: Option<LocationType>,
+    slot: Option<SlotType>,
+}
+
+async fn list_templates(
+    State(state): State<AppState>,
+    Query(filter): Query<TemplateFilter>,
+) -> ApiResponse<Vec<MealTemplate>> {
+    let backend = &state.backend;
+    let templates = match (filter.search, filter.location, filter.slot) {
+        (Some(query), None, slot) => backend.search_templates(&query, slot.as_ref()).await,
+        (Some(_), Some(_), _) => {
+            return Err(ApiError::ValidationError(
+                "search can only be combined with slot".to_string(),
+            ))
+        }
+        (None, Some(location), Some(slot)) => {
+            backend
+                .get_templates_by_slot_and_location(&slot, location)
+                .await
+        }
+        (None, Some(location), None) => backend.get_templates_by_location(location).await,
+        (None, None, Some(slot)) => backend.get_templates_by_slot(&slot).await,
+        (None, None, None) => backend.get_templates().await,
+    };
+
+    respond(templates)
+}
+
+async fn archived_templates(State(state): State<AppState>) -> ApiResponse<Vec<MealTemplate>> {
+    respond(state.backend.get_archived_templates().await)
+}
+
+async fn get_template(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> ApiResponse<MealTemplate> {
+    found(state.backend.get_template(id).await?, "Meal template", id)
+}
+
+async fn create_template(
+    State(state): State<AppState>,
+    Json(template): Json<CreateMealTemplate>,
+) -> ApiResponse<MealTemplate> {
+    respond(state.backend.create_template(template).await)
+}
+
+async fn update_template(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(updates): Json<UpdateMealTemplate>,
+) -> ApiResponse<MealTemplate> {
+    respond(state.backend.update_template(id, updates).await)
+}
+
+async fn get_option_groups(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> ApiResponse<Vec<OptionGroup>> {
+    respond(state.backend.get_option_groups(id).await)
+}
+
+async fn set_option_groups(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(groups): Json<Vec<OptionGroup>>,
+) -> ApiResponse<Vec<OptionGroup>> {
+    respond(state.backend.set_option_groups(id, groups).await)
+}
+
+#[derive(Debug, Deserialize)]
+struct WithTagsFilter {
+    #[serde(default)]
+    with_tags: bool,
+}
+
+/// The options of a template, with their tags when `with_tags` is set
+async fn template_options(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Query(filter): Query<WithTagsFilter>,
+) -> Result<Response, ApiError> {
+    if filter.with_tags {
+        let options = state.backend.get_options_by_template_with_tags(id).await?;
+        Ok(Json(options).into_response())
+    } else {
+        let options = state.backend.get_options_by_template(id).await?;
+        Ok(Json(options).into_response())
+    }
+}
+
+async fn archive_template(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> ApiResponse<MealTemplate> {
+    respond(state.backend.set_template_archived(id, true).await)
+}
+
+async fn unarchive_template(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> ApiResponse<MealTemplate> {
+    respond(state.backend.set_template_archived(id, false).await)
+}
+
+async fn delete_template(State(state): State<AppState>, Path(id): Path<i64>) -> ApiResponse<bool> {
+    if let Some(blocked) = state.backend.get_template_delete_blockers(id).await? {
+        return Err(ApiError::DeleteBlocked(blocked));
+    }
+
+    respond(state.backend.delete_template(id).await)
+}
+
+// ============================================================================
+// Meal options
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+struct OptionFilter {
+    search: Option<String>,
+}
+
+async fn list_options(
+    State(state): State<AppState>,
+    Query(filter): Query<OptionFilter>,
+) -> ApiResponse<Vec<MealOption>> {
+    match filter.search {
+        Some(query) => respond(state.backend.search_options(&query).await),
+        None => respond(state.backend.get_options().await),
+    }
+}
+
+async fn archived_options(State(state): State<AppState>) -> ApiResponse<Vec<MealOption>> {
+    respond(state.backend.get_archived_options().await)
+}
+
+/// A meal option, with its tags when `with_tags` is set
+async fn get_option(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Query(filter): Query<WithTagsFilter>,
+) -> Result<Response, ApiError> {
+    if filter.with_tags {
+        let option = state.backend.get_option_with_tags(id).await?;
+        Ok(found(option, "Meal option", id)?.into_response())
+    } else {
+        let option = state.backend.get_option(id).await?;
+        Ok(found(option, "Meal option", id)?.into_response())
+    }
+}
+
+async fn create_option(
+    State(state): State<AppState>,
+    Json(option): Json<CreateMealOption>,
+) -> ApiResponse<MealOption> {
+    respond(state.backend.create_option(option).await)
+}
+
+async fn update_option(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(updates): Json<UpdateMealOption>,
+) -> ApiResponse<MealOption> {
+    respond(state.backend.update_option(id, updates).await)
+}
+
+#[derive(Debug, Deserialize)]
+struct OptionGroupBody {
+    option_group: Option<String>, // None = ungrouped
+}
+
+async fn set_option_group(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(body): Json<OptionGroupBody>,
+) -> ApiResponse<MealOption> {
+    respond(state.backend.set_option_group(id, body.option_group).await)
+}
+
+async fn archive_option(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> ApiResponse<MealOption> {
+    respond(state.backend.set_option_archived(id, true).await)
+}
+
+async fn unarchive_option(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> ApiResponse<MealOption> {
+    respond(state.backend.set_option_archived(id, false).await)
+}
+
+async fn delete_option(State(state): State<AppState>, Path(id): Path<i64>) -> ApiResponse<()> {
+    if let Some(blocked) = state.backend.get_option_delete_blockers(id).await? {
+        return Err(ApiError::DeleteBlocked(blocked));
+    }
+
+    respond(state.backend.delete_option(id).await)
+}
+
+#[derive(Debug, Deserialize)]
+struct TagIdsBody {
+    tag_ids: Vec<i64>,
+}
+
+async fn add_option_tags(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(body): Json<TagIdsBody>,
+) -> ApiResponse<()> {
+    respond(state.backend.add_option_tags(id, body.tag_ids).await)
+}
+
+async fn remove_option_tags(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(body): Json<TagIdsBody>,
+) -> ApiResponse<()> {
+    respond(state.backend.remove_option_tags(id, body.tag_ids).await)
+}
+
+/// Replace all tags of a meal option
+async fn set_option_tags(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(body): Json<TagIdsBody>,
+) -> ApiResponse<()> {
+    respond(state.backend.set_option_tags(id, body.tag_ids).await)
+}
+
+async fn get_option_nutrients(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> ApiResponse<NutrientProfile> {
+    found(
+        state.backend.get_option_nutrients(id).await?,
+        "Nutrient profile of meal option",
+        id,
+    )
+}
+
+async fn set_option_nutrients(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(nutrients): Json<SetNutrientProfile>,
+) -> ApiResponse<NutrientProfile> {
+    respond(state.backend.set_option_nutrients(id, nutrients).await)
+}
+
+async fn delete_option_nutrients(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> ApiResponse<bool> {
+    respond(state.backend.delete_option_nutrients(id).await)
+}
@@ -0,0 +1,266 @@
+// HTTP/JSON API server
+// REST endpoints over the same repositories and services as the Tauri commands,
+// for clients that cannot use Tauri IPC (companion apps, NAS deployments)
+
+mod data;
+mod entries;
+mod library;
+mod planning;
+pub mod sync;
+
+use crate::db::BackupConfig;
+use crate::error::ApiError;
+use crate::repository::Backend;
+use axum::extract::rejection::{JsonRejection, PathRejection, QueryRejection};
+use axum::extract::{FromRequest, FromRequestParts, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use chrono::NaiveDate;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+/// Environment variable holding the bearer token; the server only starts when it is set
+pub const TOKEN_ENV: &str = "NUTRITION_API_TOKEN";
+/// Environment variable overriding the listen address
+pub const ADDR_ENV: &str = "NUTRITION_API_ADDR";
+
+/// Where the server listens and the token clients must send
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerConfig {
+    pub addr: SocketAddr, // Localhost by default
+    pub token: String,    // Expected in "Authorization: Bearer <token>"
+}
+
+impl ServerConfig {
+    pub const DEFAULT_PORT: u16 = 7878;
+
+    /// Listen on localhost with the default port
+    pub fn new(token: String) -> Self {
+        Self {
+            addr: SocketAddr::from((Ipv4Addr::LOCALHOST, Self::DEFAULT_PORT)),
+            token,
+        }
+    }
+
+    /// Read the token and optional address from the environment
+    /// Returns None when no token is set
+    pub fn from_env() -> Result<Option<Self>, ApiError> {
+        let Ok(token) = std::env::var(TOKEN_ENV) else {
+            return Ok(None);
+        };
+
+        let mut config = Self::new(token);
+        if let Ok(addr) = std::env::var(ADDR_ENV) {
+            config.addr = addr.parse().map_err(|e| {
+                ApiError::ValidationError(format!("Invalid {} '{}': {}", ADDR_ENV, addr, e))
+            })?;
+        }
+        config.validate()?;
+
+        Ok(Some(config))
+    }
+
+    /// A short token is too easy to guess for a server on the network
+    pub fn validate(&self) -> Result<(), ApiError> {
+        if self.token.trim().len() < 16 {
+            return Err(ApiError::ValidationError(
+                "API token must be at least 16 characters".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Shared by every handler
+#[derive(Clone)]
+struct AppState {
+    backend: Backend,
+    backup_config: BackupConfig,
+    token: Arc<str>,
+}
+
+/// Build the API router; every route lives under `/api` and requires the bearer token
+pub fn router(backend: Backend, backup_config: BackupConfig, token: &str) -> Router {
+    let state = AppState {
+        backend,
+        backup_config,
+        token: token.into(),
+    };
+
+    let api = Router::new()
+        .merge(library::routes())
+        .merge(entries::routes())
+        .merge(planning::routes())
+        .merge(data::routes())
+        .fallback(|| async { ApiError::NotFound("No such endpoint".to_string()) })
+        .route_layer(middleware::from_fn_with_state(
+            state.token.clone(),
+            require_token,
+        ))
+        .with_state(state);
+
+    Router::new().nest("/api", api)
+}
+
+/// Serve the API on an already bound listener until the task is dropped
+pub async fn serve(listener: TcpListener, router: Router) -> std::io::Result<()> {
+    axum::serve(listener, router).await
+}
+
+/// Bind `config.addr` and serve the API over `backend`
+pub async fn run_server(
+    backend: Backend,
+    backup_config: BackupConfig,
+    config: &ServerConfig,
+) -> Result<(), ApiError> {
+    config.validate()?;
+
+    let listener = TcpListener::bind(config.addr)
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Cannot listen on {}: {}", config.addr, e)))?;
+
+    serve(listener, router(backend, backup_config, &config.token))
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Server stopped: {}", e)))
+}
+
+/// Reject requests without the configured bearer token
+async fn require_token(
+    State(token): State<Arc<str>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match provided {
+        Some(provided) if constant_time_eq(provided.as_bytes(), token.as_bytes()) => {
+            Ok(next.run(request).await)
+        }
+        Some(_) => Err(ApiError::Unauthorized("Invalid bearer token".to_string())),
+        None => Err(ApiError::Unauthorized("Missing bearer token".to_string())),
+    }
+}
+
+/// Compare without stopping at the first difference, so timing does not leak the token
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Errors are sent with the same JSON as over IPC, with a matching status code
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::ValidationError(_)
+            | ApiError::BusinessValidationError(_)
+            | ApiError::ForeignKeyViolation(_) => StatusCode::BAD_REQUEST,
+            ApiError::Conflict(_) | ApiError::DeleteBlocked(_) => StatusCode::CONFLICT,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::DatabaseError(_) | ApiError::InternalError(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        };
+
+        (status, axum::Json(self)).into_response()
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        ApiError::ValidationError(rejection.body_text())
+    }
+}
+
+impl From<PathRejection> for ApiError {
+    fn from(rejection: PathRejection) -> Self {
+        ApiError::ValidationError(rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(rejection: QueryRejection) -> Self {
+        ApiError::ValidationError(rejection.body_text())
+    }
+}
+
+/// JSON body or response; a malformed body is reported as an ApiError
+#[derive(FromRequest)]
+#[from_request(via(axum::Json), rejection(ApiError))]
+struct Json<T>(T);
+
+impl<T: serde::Serialize> IntoResponse for Json<T> {
+    fn into_response(self) -> Response {
+        axum::Json(self.0).into_response()
+    }
+}
+
+/// Path parameters; a malformed one is reported as an ApiError
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Path), rejection(ApiError))]
+struct Path<T>(T);
+
+/// Query string; a malformed one is reported as an ApiError
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Query), rejection(ApiError))]
+struct Query<T>(T);
+
+type ApiResponse<T> = Result<Json<T>, ApiError>;
+
+/// Wrap a command-style result as a JSON response
+fn respond<T, E: Into<ApiError>>(result: Result<T, E>) -> ApiResponse<T> {
+    result.map(Json).map_err(Into::into)
+}
+
+/// Turn a missing row into a 404, where the Tauri command would return null
+fn found<T>(value: Option<T>, what: &str, id: impl std::fmt::Display) -> ApiResponse<T> {
+    value
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("{} {} not found", what, id)))
+}
+
+/// Same date range rule as the Tauri commands
+fn check_range(start: NaiveDate, end: NaiveDate) -> Result<(), ApiError> {
+    if start > end {
+        return Err(ApiError::ValidationError(
+            "Start date must not be after end date".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Parse a "YYYY-WW" ISO week into its Monday
+fn parse_week(week: &str) -> Result<NaiveDate, ApiError> {
+    crate::services::ValidationService::parse_week_string(week)
+        .ok_or_else(|| ApiError::ValidationError(format!("Invalid week: {}", week)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_validation() {
+        let config = ServerConfig::new("short".to_string());
+        assert_eq!(config.addr.ip(), Ipv4Addr::LOCALHOST);
+        assert!(config.validate().is_err());
+        assert!(ServerConfig::new("0123456789abcdef".to_string())
+            .validate()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"secret-token", b"secret-token"));
+        assert!(!constant_time_eq(b"secret-token", b"secret-tokem"));
+        assert!(!constant_time_eq(b"secret", b"secret-token"));
+    }
+}
@@ -0,0 +1,168 @@
+// Planning and report endpoints
+// Copying, generation and suggestions, plus week summary, adherence and nutrient totals
+
+use super::{check_range, parse_week, respond, ApiResponse, AppState, Json, Path, Query};
+use crate::models::{
+    AdherenceReport, CopyConflictStrategy, CopyPlanResult, DailyNutrientTotals,
+    GeneratePlanRequest, GeneratedPlan, LocationType, NutrientSummary, OptionSuggestion, SlotType,
+    WeekSummary,
+};
+use crate::services::{
+    AdherenceService, NutritionService, PlanningService, SuggestionService, WeekSummaryService,
+};
+use axum::extract::State;
+use axum::routing::{get, post};
+use axum::Router;
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+pub(super) fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/plan/copy-day", post(copy_day))
+        .route("/plan/copy-week", post(copy_week))
+        .route("/plan/generate", post(generate_week_plan))
+        .route("/suggestions", get(suggest_options))
+        .route("/weeks/{week}/summary", get(week_summary))
+        .route("/adherence", get(adherence))
+        .route("/nutrition", get(nutrient_totals))
+        .route("/nutrition/daily", get(daily_nutrient_totals))
+}
+
+#[derive(Debug, Deserialize)]
+struct CopyDayBody {
+    source_date: NaiveDate,
+    target_date: NaiveDate,
+    on_conflict: CopyConflictStrategy,
+}
+
+async fn copy_day(
+    State(state): State<AppState>,
+    Json(body): Json<CopyDayBody>,
+) -> ApiResponse<CopyPlanResult> {
+    respond(
+        PlanningService::copy_day(
+            state.backend.sqlite()?,
+            body.source_date,
+            body.target_date,
+            body.on_conflict,
+        )
+        .await,
+    )
+}
+
+#[derive(Debug, Deserialize)]
+struct CopyWeekBody {
+    source_week: String, // Format: "YYYY-WW" - ISO week format
+    target_week: String, // Format: "YYYY-WW" - ISO week format
+    on_conflict: CopyConflictStrategy,
+}
+
+async fn copy_week(
+    State(state): State<AppState>,
+    Json(body): Json<CopyWeekBody>,
+) -> ApiResponse<CopyPlanResult> {
+    let source = parse_week(&body.source_week)?;
+    let target = parse_week(&body.target_week)?;
+
+    respond(
+        PlanningService::copy_week(state.backend.sqlite()?, source, target, body.on_conflict).await,
+    )
+}
+
+async fn generate_week_plan(
+    State(state): State<AppState>,
+    Json(request): Json<GeneratePlanRequest>,
+) -> ApiResponse<GeneratedPlan> {
+    respond(PlanningService::generate_week(state.backend.sqlite()?, request).await)
+}
+
+#[derive(Debug, Deserialize)]
+struct SuggestionFilter {
+    date: NaiveDate,
+    slot: SlotType,
+    location: LocationType,
+}
+
+async fn suggest_options(
+    State(state): State<AppState>,
+    Query(filter): Query<SuggestionFilter>,
+) -> ApiResponse<Vec<OptionSuggestion>> {
+    respond(
+        SuggestionService::suggest_options(
+            state.backend.sqlite()?,
+            filter.date,
+            filter.slot,
+            filter.location,
+        )
+        .await,
+    )
+}
+
+async fn week_summary(
+    State(state): State<AppState>,
+    Path(week): Path<String>,
+) -> ApiResponse<WeekSummary> {
+    let week_start = parse_week(&week)?;
+
+    respond(WeekSummaryService::get_week_summary(state.backend.sqlite()?, week_start).await)
+}
+
+#[derive(Debug, Deserialize)]
+struct RangeFilter {
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    #[serde(default)]
+    completed_only: bool, // Nutrient totals only
+}
+
+async fn adherence(
+    State(state): State<AppState>,
+    Query(filter): Query<RangeFilter>,
+) -> ApiResponse<AdherenceReport> {
+    respond(
+        AdherenceService::get_adherence(
+            state.backend.sqlite()?,
+            filter.start_date,
+            filter.end_date,
+        )
+        .await,
+    )
+}
+
+async fn nutrient_totals(
+    State(state): State<AppState>,
+    Query(filter): Query<RangeFilter>,
+) -> ApiResponse<NutrientSummary> {
+    check_range(filter.start_date, filter.end_date)?;
+
+    respond(
+        NutritionService::get_totals_for_range(
+            state.backend.sqlite()?,
+            filter.start_date,
+            filter.end_date,
+            filter.completed_only,
+        )
+        .await,
+    )
+}
+
+#[derive(Debug, Deserialize)]
+struct DayFilter {
+    date: NaiveDate,
+    #[serde(default)]
+    completed_only: bool,
+}
+
+async fn daily_nutrient_totals(
+    State(state): State<AppState>,
+    Query(filter): Query<DayFilter>,
+) -> ApiResponse<DailyNutrientTotals> {
+    respond(
+        NutritionService::get_daily_totals(
+            state.backend.sqlite()?,
+            filter.date,
+            filter.completed_only,
+        )
+        .await,
+    )
+}
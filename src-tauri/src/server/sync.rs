@@ -0,0 +1,192 @@
+// Sync relay
+// Reference sync server: stores the latest version of each entity pushed by any
+// device and hands out what changed since a device's cursor. Runs on its own
+// database (`migrations_sync_server`), e.g. on a NAS.
+
+use super::{require_token, respond, ApiResponse, Json, Query, ServerConfig};
+use crate::error::ApiError;
+use crate::models::{SyncChange, SyncEntity, SyncPull, SyncPush, SyncPushResult};
+use axum::extract::State;
+use axum::middleware;
+use axum::routing::{get, post};
+use axum::Router;
+use serde::Deserialize;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{Row, SqlitePool};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+/// Listen on localhost only: the server speaks plain HTTP, so devices reach it
+/// through a TLS reverse proxy rather than directly
+pub const SYNC_DEFAULT_ADDR: SocketAddr =
+    SocketAddr::new(std::net::IpAddr::V4(Ipv4Addr::LOCALHOST), 7879);
+
+/// Open (creating if needed) and migrate the relay database
+pub async fn open_sync_store(path: &Path) -> Result<SqlitePool, sqlx::Error> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(sqlx::Error::Io)?;
+    }
+
+    let options = SqliteConnectOptions::new()
+        .filename(path)
+        .create_if_missing(true);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(5)
+        .connect_with(options)
+        .await?;
+    sqlx::migrate!("./migrations_sync_server")
+        .run(&pool)
+        .await?;
+
+    Ok(pool)
+}
+
+/// Keep each pushed change that is newer than the stored version of its entity
+pub async fn push_changes(pool: &SqlitePool, push: SyncPush) -> Result<SyncPushResult, ApiError> {
+    if push.device_id.trim().is_empty() {
+        return Err(ApiError::ValidationError(
+            "Device ID is required".to_string(),
+        ));
+    }
+    if let Some(change) = push
+        .changes
+        .iter()
+        .find(|c| c.device_id != push.device_id || c.key.is_empty())
+    {
+        return Err(ApiError::ValidationError(format!(
+            "Change to {} '{}' does not belong to device {}",
+            change.entity.to_db_string(),
+            change.key,
+            push.device_id
+        )));
+    }
+
+    let mut tx = pool.begin().await?;
+    let mut result = SyncPushResult::default();
+    for change in push.changes {
+        let stored: Option<(i64, String)> = sqlx::query_as(
+            "SELECT clock, device_id FROM sync_entities WHERE entity = ?1 AND entity_key = ?2",
+        )
+        .bind(change.entity.to_db_string())
+        .bind(&change.key)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if let Some((clock, device_id)) = stored {
+            if !change.supersedes(clock, &device_id) {
+                result.stale += 1;
+                continue;
+            }
+        }
+
+        // REPLACE deletes the old version, so the new one gets the next seq
+        sqlx::query(
+            "INSERT OR REPLACE INTO sync_entities (entity, entity_key, clock, device_id, data)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )
+        .bind(change.entity.to_db_string())
+        .bind(&change.key)
+        .bind(change.clock)
+        .bind(&change.device_id)
+        .bind(change.data.map(|data| data.to_string()))
+        .execute(&mut *tx)
+        .await?;
+        result.accepted += 1;
+    }
+    tx.commit().await?;
+
+    Ok(result)
+}
+
+/// Everything received after `since` that another device pushed
+pub async fn pull_changes(
+    pool: &SqlitePool,
+    since: i64,
+    device_id: &str,
+) -> Result<SyncPull, ApiError> {
+    let mut tx = pool.begin().await?;
+    let cursor: i64 = sqlx::query_scalar("SELECT COALESCE(MAX(seq), 0) FROM sync_entities")
+        .fetch_one(&mut *tx)
+        .await?;
+
+    let rows = sqlx::query(
+        "SELECT entity, entity_key, clock, device_id, data FROM sync_entities
+         WHERE seq > ?1 AND seq <= ?2 AND device_id != ?3
+         ORDER BY seq",
+    )
+    .bind(since)
+    .bind(cursor)
+    .bind(device_id)
+    .fetch_all(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    let changes = rows
+        .iter()
+        .map(|row| {
+            let entity: String = row.try_get("entity")?;
+            let data: Option<String> = row.try_get("data")?;
+            Ok(SyncChange {
+                entity: SyncEntity::from_db_string(&entity).map_err(ApiError::DatabaseError)?,
+                key: row.try_get("entity_key")?,
+                clock: row.try_get("clock")?,
+                device_id: row.try_get("device_id")?,
+                data: data
+                    .map(|data| serde_json::from_str(&data))
+                    .transpose()
+                    .map_err(|e| ApiError::DatabaseError(e.to_string()))?,
+            })
+        })
+        .collect::<Result<Vec<_>, ApiError>>()?;
+
+    Ok(SyncPull { changes, cursor })
+}
+
+/// Build the sync router: `/sync/push` and `/sync/pull`, behind the bearer token
+pub fn sync_router(pool: SqlitePool, token: &str) -> Router {
+    Router::new()
+        .route("/sync/push", post(push))
+        .route("/sync/pull", get(pull))
+        .fallback(|| async { ApiError::NotFound("No such endpoint".to_string()) })
+        .route_layer(middleware::from_fn_with_state(
+            Arc::<str>::from(token),
+            require_token,
+        ))
+        .with_state(pool)
+}
+
+/// Bind `config.addr` and relay changes stored in `pool`
+pub async fn run_sync_server(pool: SqlitePool, config: &ServerConfig) -> Result<(), ApiError> {
+    config.validate()?;
+
+    let listener = TcpListener::bind(config.addr)
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Cannot listen on {}: {}", config.addr, e)))?;
+
+    super::serve(listener, sync_router(pool, &config.token))
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Server stopped: {}", e)))
+}
+
+async fn push(
+    State(pool): State<SqlitePool>,
+    Json(push): Json<SyncPush>,
+) -> ApiResponse<SyncPushResult> {
+    respond(push_changes(&pool, push).await)
+}
+
+#[derive(Debug, Deserialize)]
+struct PullQuery {
+    #[serde(default)]
+    since: i64,
+    device_id: String,
+}
+
+async fn pull(
+    State(pool): State<SqlitePool>,
+    Query(query): Query<PullQuery>,
+) -> ApiResponse<SyncPull> {
+    respond(pull_changes(&pool, query.since, &query.device_id).await)
+}
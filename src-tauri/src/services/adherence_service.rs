@@ -0,0 +1,244 @@
+// Adherence Service
+// Compares what was eaten with what was planned, per day and per ISO week
+
+use crate::models::{
+    AdherenceCounts, AdherenceReport, DayAdherence, EntryStatus, MealEntry, WeekAdherence,
+};
+use crate::repository::MealEntryRepository;
+use crate::services::ValidationService;
+use chrono::{Duration, NaiveDate};
+use sqlx::SqlitePool;
+
+pub struct AdherenceService;
+
+impl AdherenceService {
+    /// Get plan adherence for a date range (inclusive)
+    /// An entry counts as eaten as planned when it was eaten with exactly the options
+    /// it was planned with; servings are not compared
+    pub async fn get_adherence(
+        pool: &SqlitePool,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> sqlx::Result<AdherenceReport> {
+        if start_date > end_date {
+            return Err(sqlx::Error::Protocol(
+                "Start date must not be after end date".to_string(),
+            ));
+        }
+
+        let entries = MealEntryRepository::get_by_date_range(pool, start_date, end_date).await?;
+
+        let mut days = Vec::new();
+        let mut weeks: Vec<WeekAdherence> = Vec::new();
+        let mut total = AdherenceCounts::default();
+        let mut date = start_date;
+        while date <= end_date {
+            let mut counts = AdherenceCounts::default();
+            for entry in entries.iter().filter(|e| e.date == date) {
+                Self::count(&mut counts, entry);
+            }
+
+            let week = ValidationService::get_week_string(date);
+            match weeks.last_mut() {
+                Some(current) if current.week == week => current.counts.add(&counts),
+                _ => weeks.push(WeekAdherence {
+                    week,
+                    start_date: ValidationService::get_week_start(date),
+                    counts: counts.clone(),
+                }),
+            }
+            total.add(&counts);
+
+            counts.update_percent();
+            days.push(DayAdherence { date, counts });
+            date += Duration::days(1);
+        }
+
+        for week in &mut weeks {
+            week.counts.update_percent();
+        }
+        total.update_percent();
+
+        Ok(AdherenceReport {
+            start_date,
+            end_date,
+            days,
+            weeks,
+            total,
+        })
+    }
+
+    fn count(counts: &mut AdherenceCounts, entry: &MealEntry) {
+        if entry.is_off_plan() {
+            counts.off_plan += 1;
+            return;
+        }
+
+        counts.planned_slots += 1;
+        match entry.status {
+            EntryStatus::Planned => counts.pending += 1,
+            EntryStatus::Skipped => counts.skipped += 1,
+            EntryStatus::Eaten if entry.is_swap() => counts.swapped += 1,
+            EntryStatus::Eaten => counts.eaten_as_planned += 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        CreateMealEntry, CreateMealOption, CreateMealTemplate, LocationType, LogActualMeal,
+        SlotType, UpdateMealEntry,
+    };
+    use crate::repository::{MealOptionRepository, MealTemplateRepository};
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect(":memory:")
+            .await
+            .expect("Failed to create test pool");
+
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
+
+        pool
+    }
+
+    async fn create_options(pool: &SqlitePool, names: &[&str]) -> Vec<i64> {
+        let template = MealTemplateRepository::create(
+            pool,
+            CreateMealTemplate {
+                name: "Pranzo".to_string(),
+                description: None,
+                compatible_slots: vec![SlotType::LUNCH, SlotType::DINNER],
+                location_type: LocationType::Any,
+                weekly_limit: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let mut ids = Vec::new();
+        for name in names {
+            let option = MealOptionRepository::create(
+                pool,
+                CreateMealOption {
+                    template_id: template.id,
+                    name: name.to_string(),
+                    description: None,
+                    nutritional_notes: None,
+                },
+            )
+            .await
+            .unwrap();
+            ids.push(option.id);
+        }
+        ids
+    }
+
+    async fn plan(pool: &SqlitePool, option_id: i64, date: NaiveDate, slot: SlotType) -> i64 {
+        MealEntryRepository::create(
+            pool,
+            CreateMealEntry {
+                meal_option_id: option_id,
+                date,
+                slot_type: slot,
+                location: LocationType::Home,
+                servings: None,
+                notes: None,
+                status: None,
+                extra_options: vec![],
+            },
+        )
+        .await
+        .unwrap()
+        .id
+    }
+
+    async fn set_status(pool: &SqlitePool, entry_id: i64, status: EntryStatus) {
+        MealEntryRepository::update(
+            pool,
+            entry_id,
+            UpdateMealEntry {
+                location: None,
+                servings: None,
+                notes: None,
+                status: Some(status),
+            },
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_adherence_per_day_and_week() {
+        let pool = setup_test_pool().await;
+        let options = create_options(&pool, &["Pasta", "Riso"]).await;
+        let sunday = NaiveDate::from_ymd_opt(2024, 11, 10).unwrap();
+        let monday = sunday + Duration::days(1);
+
+        // Sunday: lunch eaten as planned, dinner swapped for rice
+        let lunch = plan(&pool, options[0], sunday, SlotType::LUNCH).await;
+        set_status(&pool, lunch, EntryStatus::Eaten).await;
+        let dinner = plan(&pool, options[0], sunday, SlotType::DINNER).await;
+        MealEntryRepository::log_actual(
+            &pool,
+            dinner,
+            LogActualMeal {
+                meal_option_id: options[1],
+                servings: Some(1.5),
+                extra_options: vec![],
+                location: Some(LocationType::Restaurant),
+                notes: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        // Monday: lunch skipped, dinner not logged yet
+        let lunch = plan(&pool, options[1], monday, SlotType::LUNCH).await;
+        set_status(&pool, lunch, EntryStatus::Skipped).await;
+        plan(&pool, options[1], monday, SlotType::DINNER).await;
+
+        let report = AdherenceService::get_adherence(&pool, sunday, monday + Duration::days(1))
+            .await
+            .unwrap();
+
+        assert_eq!(report.days.len(), 3);
+        let sunday_counts = &report.days[0].counts;
+        assert_eq!(sunday_counts.eaten_as_planned, 1);
+        assert_eq!(sunday_counts.swapped, 1);
+        assert_eq!(sunday_counts.adherence_percent, 50.0);
+        let monday_counts = &report.days[1].counts;
+        assert_eq!(monday_counts.skipped, 1);
+        assert_eq!(monday_counts.pending, 1);
+        assert_eq!(monday_counts.adherence_percent, 0.0);
+        assert_eq!(report.days[2].counts, AdherenceCounts::default());
+
+        // Sunday and Monday fall in different ISO weeks
+        let weeks: Vec<(&str, i64)> = report
+            .weeks
+            .iter()
+            .map(|w| (w.week.as_str(), w.counts.planned_slots))
+            .collect();
+        assert_eq!(weeks, vec![("2024-45", 2), ("2024-46", 2)]);
+        assert_eq!(report.weeks[1].start_date, monday);
+
+        assert_eq!(report.total.planned_slots, 4);
+        assert!((report.total.adherence_percent - 100.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_adherence_rejects_inverted_range() {
+        let pool = setup_test_pool().await;
+        let date = NaiveDate::from_ymd_opt(2024, 11, 4).unwrap();
+
+        let result = AdherenceService::get_adherence(&pool, date, date - Duration::days(1)).await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,1558 @@
+// Export Service
+// Full-database JSON export/import with ID remapping, and CSV export of meal entries
+
+use crate::error::{ApiError, ApiResult};
+use crate::models::{
+    CreateMealEntry, CreateMealTemplate, CreateOffPlanEntry, CreateTag, DatabaseExport,
+    EntryStatus, ExportedEntry, ExportedEntryOption, ExportedEntrySnapshot, ExportedOption,
+    ExportedOptionTag, ExportedSlot, ExportedTag, ExportedTemplate, ImportSummary, SelectedOption,
+    SetNutrientProfile, SyncEntity, EXPORT_FORMAT_VERSION,
+};
+use crate::repository::{
+    AuditScope, MealEntryRepository, MealOptionRepository, MealSlotRepository,
+    MealTemplateRepository, TagRepository,
+};
+use crate::services::ValidationService;
+use chrono::{NaiveDate, Utc};
+use futures_util::TryStreamExt;
+use sqlx::{Row, SqliteConnection, SqlitePool};
+use std::collections::{HashMap, HashSet};
+
+/// Column headers of the meal entries CSV export
+pub const ENTRIES_CSV_HEADERS: [&str; 11] = [
+    "date",
+    "slot",
+    "location",
+    "template",
+    "option",
+    "planned_option",
+    "servings",
+    "status",
+    "off_plan",
+    "notes",
+    "tags",
+];
+
+pub struct ExportService;
+
+impl ExportService {
+    /// Serialize every slot, tag, template, option, option-tag link and entry
+    /// Archived tags, templates and options are included with their flag
+    pub async fn export_database(pool: &SqlitePool) -> ApiResult<DatabaseExport> {
+        let slots = MealSlotRepository::get_all(pool)
+            .await?
+            .into_iter()
+            .map(|s| ExportedSlot {
+                name: s.name,
+                display_name: s.display_name,
+                sort_order: s.sort_order,
+                active: s.active,
+            })
+            .collect();
+
+        let tags = TagRepository::get_all_including_archived(pool)
+            .await?
+            .into_iter()
+            .map(|t| ExportedTag {
+                id: t.id,
+                name: t.name,
+                display_name: t.display_name,
+                category: t.category,
+                weekly_suggestion: t.weekly_suggestion,
+                parent_tag_id: t.parent_tag_id,
+                archived: t.archived,
+            })
+            .collect();
+
+        let mut templates = Vec::new();
+        for t in MealTemplateRepository::get_all_including_archived(pool).await? {
+            templates.push(ExportedTemplate {
+                option_groups: MealTemplateRepository::get_option_groups(pool, t.id).await?,
+                id: t.id,
+                name: t.name,
+                description: t.description,
+                compatible_slots: t.compatible_slots,
+                location_type: t.location_type,
+                weekly_limit: t.weekly_limit,
+                archived: t.archived,
+            });
+        }
+
+        let mut nutrients: HashMap<i64, _> = MealOptionRepository::get_all_nutrients(pool)
+            .await?
+            .into_iter()
+            .map(|n| {
+                (
+                    n.meal_option_id,
+                    SetNutrientProfile {
+                        kcal: n.kcal,
+                        protein_g: n.protein_g,
+                        carbs_g: n.carbs_g,
+                        fat_g: n.fat_g,
+                        fiber_g: n.fiber_g,
+                    },
+                )
+            })
+            .collect();
+
+        let options = MealOptionRepository::get_all_including_archived(pool)
+            .await?
+            .into_iter()
+            .map(|o| ExportedOption {
+                nutrients: nutrients.remove(&o.id),
+                id: o.id,
+                template_id: o.template_id,
+                name: o.name,
+                description: o.description,
+                nutritional_notes: o.nutritional_notes,
+                archived: o.archived,
+                option_group: o.option_group,
+            })
+            .collect();
+
+        let option_tags = MealOptionRepository::get_all_tag_links(pool)
+            .await?
+            .into_iter()
+            .map(|(meal_option_id, tag_id)| ExportedOptionTag {
+                meal_option_id,
+                tag_id,
+            })
+            .collect();
+
+        let entries = MealEntryRepository::get_all(pool)
+            .await?
+            .into_iter()
+            .map(|e| ExportedEntry {
+                extra_options: e
+                    .options
+                    .into_iter()
+                    .skip(1)
+                    .map(|o| ExportedEntryOption {
+                        meal_option_id: o.meal_option_id,
+                        servings: o.servings,
+                        option_name: o.option_name,
+                    })
+                    .collect(),
+                planned_options: e
+                    .planned_options
+                    .into_iter()
+                    .map(|o| ExportedEntryOption {
+                        meal_option_id: o.meal_option_id,
+                        servings: o.servings,
+                        option_name: o.option_name,
+                    })
+                    .collect(),
+                meal_option_id: e.meal_option_id,
+                estimated_nutrients: e.estimated_nutrients,
+                date: e.date,
+                slot_type: e.slot_type,
+                location: e.location,
+                servings: e.servings,
+                notes: e.notes,
+                status: e.status,
+                snapshot: Some(ExportedEntrySnapshot {
+                    option_name: e.option_name,
+                    template_name: e.template_name,
+                    template_location_type: e.template_location_type,
+                    tag_names: e.tag_names,
+                }),
+            })
+            .collect();
+
+        Ok(DatabaseExport {
+            format_version: EXPORT_FORMAT_VERSION,
+            exported_at: Utc::now(),
+            slots,
+            tags,
+            templates,
+            options,
+            option_tags,
+            entries,
+        })
+    }
+
+    /// Check the format version and deserialize an export document
+    pub fn parse_document(mut document: serde_json::Value) -> ApiResult<DatabaseExport> {
+        let version = document
+            .get("format_version")
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| {
+                ApiError::ValidationError("Export document has no format_version".to_string())
+            })?;
+
+        if version == 0 || version > EXPORT_FORMAT_VERSION as u64 {
+            return Err(ApiError::ValidationError(format!(
+                "Unsupported export format version {} (supported: 1-{})",
+                version, EXPORT_FORMAT_VERSION
+            )));
+        }
+
+        // Version 5 and older flag eaten entries with `completed`
+        if version < 6 {
+            let entries = document
+                .get_mut("entries")
+                .and_then(|e| e.as_array_mut())
+                .into_iter()
+                .flatten();
+            for entry in entries.filter_map(|e| e.as_object_mut()) {
+                let Some(completed) = entry.remove("completed") else {
+                    continue;
+                };
+                let status = if completed.as_bool() == Some(true) {
+                    EntryStatus::Eaten
+                } else {
+                    EntryStatus::Planned
+                };
+                entry.insert("status".to_string(), status.to_db_string().into());
+            }
+        }
+
+        serde_json::from_value(document)
+            .map_err(|e| ApiError::ValidationError(format!("Invalid export document: {}", e)))
+    }
+
+    /// Import an export document into the database in a single transaction
+    /// Every row gets a new ID; slots and tags whose name already exists are reused.
+    /// Entries are restored as-is, without weekly limit validation.
+    /// Everything created is one operation in the audit log.
+    pub async fn import_database(
+        pool: &SqlitePool,
+        document: serde_json::Value,
+    ) -> ApiResult<ImportSummary> {
+        let export = Self::parse_document(document)?;
+        Self::check_tag_hierarchy(&export.tags)?;
+        let mut summary = ImportSummary::default();
+        let mut tx = pool.begin().await?;
+
+        let mut audit = AuditScope::new("import_database");
+        for entity in SyncEntity::ALL {
+            audit.track_new(&mut tx, entity).await?;
+        }
+
+        // Slots: matched by name, since entries and templates reference them by name
+        for slot in &export.slots {
+            let exists: bool =
+                sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM meal_slots WHERE name = ?)")
+                    .bind(slot.name.to_db_string())
+                    .fetch_one(&mut *tx)
+                    .await?;
+
+            if exists {
+                summary.slots_merged += 1;
+            } else {
+                sqlx::query(
+                    "INSERT INTO meal_slots (name, display_name, sort_order, active)
+                     VALUES (?, ?, ?, ?)",
+                )
+                .bind(slot.name.to_db_string())
+                .bind(&slot.display_name)
+                .bind(slot.sort_order)
+                .bind(slot.active)
+                .execute(&mut *tx)
+                .await?;
+                summary.slots_created += 1;
+            }
+        }
+
+        // Tags: insert without parents first, then link parents once every ID is known
+        let mut tag_ids: HashMap<i64, i64> = HashMap::new();
+        let mut created_tags: Vec<&ExportedTag> = Vec::new();
+        for tag in &export.tags {
+            CreateTag {
+                name: tag.name.clone(),
+                display_name: tag.display_name.clone(),
+                category: tag.category,
+                weekly_suggestion: tag.weekly_suggestion,
+                parent_tag_id: None,
+            }
+            .validate()
+            .map_err(ApiError::ValidationError)?;
+
+            let existing: Option<i64> = sqlx::query_scalar("SELECT id FROM tags WHERE name = ?")
+                .bind(&tag.name)
+                .fetch_optional(&mut *tx)
+                .await?;
+
+            let new_id = match existing {
+                Some(id) => {
+                    summary.tags_merged += 1;
+                    id
+                }
+                None => {
+                    let id: i64 = sqlx::query_scalar(
+                        "INSERT INTO tags (name, display_name, category, weekly_suggestion, archived)
+                         VALUES (?, ?, ?, ?, ?)
+                         RETURNING id",
+                    )
+                    .bind(&tag.name)
+                    .bind(&tag.display_name)
+                    .bind(tag.category.to_db_string())
+                    .bind(tag.weekly_suggestion)
+                    .bind(tag.archived)
+                    .fetch_one(&mut *tx)
+                    .await?;
+                    created_tags.push(tag);
+                    summary.tags_created += 1;
+                    id
+                }
+            };
+
+            if tag_ids.insert(tag.id, new_id).is_some() {
+                return Err(Self::duplicate_id("tag", tag.id));
+            }
+        }
+
+        for tag in created_tags {
+            if let Some(parent_id) = tag.parent_tag_id {
+                let new_parent = Self::remap(&tag_ids, parent_id, "tag", &tag.name)?;
+                sqlx::query("UPDATE tags SET parent_tag_id = ? WHERE id = ?")
+                    .bind(new_parent)
+                    .bind(tag_ids[&tag.id])
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+
+        // Templates
+        let mut template_ids: HashMap<i64, i64> = HashMap::new();
+        for template in &export.templates {
+            CreateMealTemplate {
+                name: template.name.clone(),
+                description: template.description.clone(),
+                compatible_slots: template.compatible_slots.clone(),
+                location_type: template.location_type,
+                weekly_limit: template.weekly_limit,
+            }
+            .validate()
+            .map_err(ApiError::ValidationError)?;
+
+            let id: i64 = sqlx::query_scalar(
+                "INSERT INTO meal_templates (name, description, location_type, weekly_limit, archived)
+                 VALUES (?, ?, ?, ?, ?)
+                 RETURNING id",
+            )
+            .bind(&template.name)
+            .bind(&template.description)
+            .bind(template.location_type.to_db_string())
+            .bind(template.weekly_limit)
+            .bind(template.archived)
+            .fetch_one(&mut *tx)
+            .await?;
+            MealTemplateRepository::set_slots(&mut tx, id, &template.compatible_slots).await?;
+
+            for group in &template.option_groups {
+                group.validate().map_err(ApiError::ValidationError)?;
+            }
+            MealTemplateRepository::replace_option_groups(&mut tx, id, &template.option_groups)
+                .await?;
+
+            if template_ids.insert(template.id, id).is_some() {
+                return Err(Self::duplicate_id("template", template.id));
+            }
+            summary.templates_created += 1;
+        }
+
+        // Options (with their nutrient profiles)
+        let mut option_ids: HashMap<i64, i64> = HashMap::new();
+        for option in &export.options {
+            if option.name.trim().is_empty() {
+                return Err(ApiError::ValidationError(
+                    "Option name cannot be empty".to_string(),
+                ));
+            }
+            let template_id =
+                Self::remap(&template_ids, option.template_id, "template", &option.name)?;
+
+            if let Some(group) = &option.option_group {
+                let declared: bool = sqlx::query_scalar(
+                    "SELECT EXISTS(SELECT 1 FROM meal_template_option_groups
+                                   WHERE template_id = ? AND name = ?)",
+                )
+                .bind(template_id)
+                .bind(group)
+                .fetch_one(&mut *tx)
+                .await?;
+                if !declared {
+                    return Err(ApiError::ValidationError(format!(
+                        "Option '{}' is in group '{}', which its template does not declare",
+                        option.name, group
+                    )));
+                }
+            }
+
+            let id: i64 = sqlx::query_scalar(
+                "INSERT INTO meal_options (template_id, name, description, nutritional_notes, archived, option_group)
+                 VALUES (?, ?, ?, ?, ?, ?)
+                 RETURNING id",
+            )
+            .bind(template_id)
+            .bind(&option.name)
+            .bind(&option.description)
+            .bind(&option.nutritional_notes)
+            .bind(option.archived)
+            .bind(&option.option_group)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            if let Some(nutrients) = &option.nutrients {
+                nutrients.validate().map_err(ApiError::ValidationError)?;
+                sqlx::query(
+                    "INSERT INTO meal_option_nutrients (meal_option_id, kcal, protein_g, carbs_g, fat_g, fiber_g)
+                     VALUES (?, ?, ?, ?, ?, ?)",
+                )
+                .bind(id)
+                .bind(nutrients.kcal)
+                .bind(nutrients.protein_g)
+                .bind(nutrients.carbs_g)
+                .bind(nutrients.fat_g)
+                .bind(nutrients.fiber_g)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            if option_ids.insert(option.id, id).is_some() {
+                return Err(Self::duplicate_id("option", option.id));
+            }
+            summary.options_created += 1;
+        }
+
+        // Option-tag links
+        for link in &export.option_tags {
+            let context = format!("option-tag link {}/{}", link.meal_option_id, link.tag_id);
+            let option_id = Self::remap(&option_ids, link.meal_option_id, "option", &context)?;
+            let tag_id = Self::remap(&tag_ids, link.tag_id, "tag", &context)?;
+
+            let result = sqlx::query(
+                "INSERT OR IGNORE INTO meal_option_tags (meal_option_id, tag_id) VALUES (?, ?)",
+            )
+            .bind(option_id)
+            .bind(tag_id)
+            .execute(&mut *tx)
+            .await?;
+            summary.option_tags_created += result.rows_affected() as usize;
+        }
+
+        // Entries
+        for entry in &export.entries {
+            let context = format!("entry on {} ({:?})", entry.date, entry.slot_type);
+            let Some(meal_option_id) = entry.meal_option_id else {
+                Self::import_off_plan_entry(&mut tx, entry, &context).await?;
+                summary.entries_created += 1;
+                continue;
+            };
+            let option_id = Self::remap(&option_ids, meal_option_id, "option", &context)?;
+            let extra_options = entry
+                .extra_options
+                .iter()
+                .map(|o| {
+                    Ok(SelectedOption {
+                        meal_option_id: Self::remap(
+                            &option_ids,
+                            o.meal_option_id,
+                            "option",
+                            &context,
+                        )?,
+                        servings: Some(o.servings),
+                    })
+                })
+                .collect::<ApiResult<Vec<_>>>()?;
+
+            CreateMealEntry {
+                meal_option_id: option_id,
+                date: entry.date,
+                slot_type: entry.slot_type.clone(),
+                location: entry.location,
+                servings: Some(entry.servings),
+                notes: entry.notes.clone(),
+                status: Some(entry.status),
+                extra_options: extra_options.clone(),
+            }
+            .validate()
+            .map_err(ApiError::ValidationError)?;
+
+            let id: i64 = sqlx::query_scalar(
+                "INSERT INTO meal_entries (meal_option_id, date, iso_week, slot_type, location, servings, notes, status)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                 RETURNING id",
+            )
+            .bind(option_id)
+            .bind(entry.date)
+            .bind(ValidationService::get_week_string(entry.date))
+            .bind(entry.slot_type.to_db_string())
+            .bind(entry.location.to_db_string())
+            .bind(entry.servings)
+            .bind(&entry.notes)
+            .bind(entry.status.to_db_string())
+            .fetch_one(&mut *tx)
+            .await?;
+
+            // Start from the imported library, then restore what the entry was logged with
+            MealEntryRepository::insert_options(&mut tx, id, &extra_options).await?;
+            MealEntryRepository::snapshot(&mut tx, id).await?;
+            if let Some(snapshot) = &entry.snapshot {
+                Self::restore_snapshot(&mut tx, id, snapshot, &entry.extra_options).await?;
+            }
+            MealEntryRepository::record_plan(&mut tx, id).await?;
+            if !entry.planned_options.is_empty() {
+                Self::restore_plan(&mut tx, id, &entry.planned_options, &option_ids, &context)
+                    .await?;
+            }
+            summary.entries_created += 1;
+        }
+
+        audit.record(&mut tx).await?;
+        tx.commit().await?;
+
+        Ok(summary)
+    }
+
+    /// Insert an off-plan entry; its description and estimated tags come from the snapshot
+    async fn import_off_plan_entry(
+        conn: &mut SqliteConnection,
+        entry: &ExportedEntry,
+        context: &str,
+    ) -> ApiResult<()> {
+        let snapshot = entry.snapshot.as_ref().ok_or_else(|| {
+            ApiError::ValidationError(format!("Off-plan {} has no description", context))
+        })?;
+
+        CreateOffPlanEntry {
+            date: entry.date,
+            slot_type: entry.slot_type.clone(),
+            location: entry.location,
+            description: snapshot.option_name.clone(),
+            servings: Some(entry.servings),
+            notes: entry.notes.clone(),
+            status: Some(entry.status),
+            tag_ids: vec![],
+            estimated_nutrients: entry.estimated_nutrients.clone(),
+        }
+        .validate()
+        .map_err(|e| ApiError::ValidationError(format!("Off-plan {}: {}", context, e)))?;
+
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO meal_entries (meal_option_id, date, iso_week, slot_type, location, servings, notes, status, option_name)
+             VALUES (NULL, ?, ?, ?, ?, ?, ?, ?, ?)
+             RETURNING id",
+        )
+        .bind(entry.date)
+        .bind(ValidationService::get_week_string(entry.date))
+        .bind(entry.slot_type.to_db_string())
+        .bind(entry.location.to_db_string())
+        .bind(entry.servings)
+        .bind(&entry.notes)
+        .bind(entry.status.to_db_string())
+        .bind(snapshot.option_name.trim())
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Self::restore_snapshot(conn, id, snapshot, &[]).await?;
+        if let Some(nutrients) = &entry.estimated_nutrients {
+            MealEntryRepository::set_estimated_nutrients(conn, id, nutrients).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Replace an imported entry's plan with the exported planned options
+    async fn restore_plan(
+        conn: &mut SqliteConnection,
+        entry_id: i64,
+        planned_options: &[ExportedEntryOption],
+        option_ids: &HashMap<i64, i64>,
+        context: &str,
+    ) -> ApiResult<()> {
+        sqlx::query("DELETE FROM meal_entry_planned_options WHERE meal_entry_id = ?")
+            .bind(entry_id)
+            .execute(&mut *conn)
+            .await?;
+
+        for (position, planned) in planned_options.iter().enumerate() {
+            let option_id = Self::remap(option_ids, planned.meal_option_id, "option", context)?;
+            sqlx::query(
+                "INSERT INTO meal_entry_planned_options (meal_entry_id, meal_option_id, position, servings, option_name)
+                 VALUES (?, ?, ?, ?, ?)",
+            )
+            .bind(entry_id)
+            .bind(option_id)
+            .bind(position as i64)
+            .bind(planned.servings)
+            .bind(&planned.option_name)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Overwrite an imported entry's snapshot with the exported one
+    /// Tag IDs are resolved by name; tags missing from this database keep a NULL ID
+    async fn restore_snapshot(
+        conn: &mut SqliteConnection,
+        entry_id: i64,
+        snapshot: &ExportedEntrySnapshot,
+        extra_options: &[ExportedEntryOption],
+    ) -> ApiResult<()> {
+        sqlx::query(
+            "UPDATE meal_entries
+             SET option_name = ?, template_name = ?, template_location_type = ?
+             WHERE id = ?",
+        )
+        .bind(&snapshot.option_name)
+        .bind(&snapshot.template_name)
+        .bind(snapshot.template_location_type.to_db_string())
+        .bind(entry_id)
+        .execute(&mut *conn)
+        .await?;
+
+        let names = std::iter::once(&snapshot.option_name)
+            .chain(extra_options.iter().map(|o| &o.option_name));
+        for (position, name) in names.enumerate() {
+            sqlx::query(
+                "UPDATE meal_entry_options SET option_name = ?
+                 WHERE meal_entry_id = ? AND position = ?",
+            )
+            .bind(name)
+            .bind(entry_id)
+            .bind(position as i64)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        sqlx::query("DELETE FROM meal_entry_tags WHERE meal_entry_id = ?")
+            .bind(entry_id)
+            .execute(&mut *conn)
+            .await?;
+
+        for tag_name in &snapshot.tag_names {
+            sqlx::query(
+                "INSERT OR IGNORE INTO meal_entry_tags (meal_entry_id, tag_id, tag_name)
+                 VALUES (?1, (SELECT id FROM tags WHERE name = ?2), ?2)",
+            )
+            .bind(entry_id)
+            .bind(tag_name)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Stream meal entries in a date range (inclusive) to CSV
+    /// Rows are read from a database cursor and written one at a time.
+    /// Names and tags are the entry's snapshot; the options of a composite meal
+    /// are joined with ' + ' and off-plan entries give their description. Tags
+    /// are the names recorded on the entry, separated by ';'.
+    /// Returns the number of data rows written.
+    pub async fn write_entries_csv<W: std::io::Write>(
+        pool: &SqlitePool,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        writer: W,
+    ) -> ApiResult<usize> {
+        if start_date > end_date {
+            return Err(ApiError::ValidationError(
+                "Start date must not be after end date".to_string(),
+            ));
+        }
+
+        let mut csv_writer = csv::Writer::from_writer(writer);
+        csv_writer
+            .write_record(ENTRIES_CSV_HEADERS)
+            .map_err(Self::csv_error)?;
+
+        let mut rows = sqlx::query(
+            "SELECT me.date, me.slot_type, me.location, me.template_name,
+                    COALESCE((SELECT GROUP_CONCAT(eo.option_name, ' + ' ORDER BY eo.position)
+                              FROM meal_entry_options eo
+                              WHERE eo.meal_entry_id = me.id), me.option_name) AS option_name,
+                    (SELECT GROUP_CONCAT(po.option_name, ' + ' ORDER BY po.position)
+                     FROM meal_entry_planned_options po
+                     WHERE po.meal_entry_id = me.id) AS planned_option_name,
+                    me.servings, me.status, me.meal_option_id IS NULL AS off_plan, me.notes,
+                    (SELECT GROUP_CONCAT(met.tag_name, ';' ORDER BY met.tag_name)
+                     FROM meal_entry_tags met
+                     WHERE met.meal_entry_id = me.id) AS tags
+             FROM meal_entries me
+             WHERE me.date BETWEEN ? AND ?
+             ORDER BY me.date,
+                      (SELECT sort_order FROM meal_slots WHERE name = me.slot_type),
+                      me.id",
+        )
+        .bind(start_date)
+        .bind(end_date)
+        .fetch(pool);
+
+        let mut count = 0;
+        while let Some(row) = rows.try_next().await? {
+            let date: NaiveDate = row.try_get("date")?;
+            let servings: f64 = row.try_get("servings")?;
+            let planned: Option<String> = row.try_get("planned_option_name")?;
+            let status: String = row.try_get("status")?;
+            let off_plan: bool = row.try_get("off_plan")?;
+            let notes: Option<String> = row.try_get("notes")?;
+            let tags: Option<String> = row.try_get("tags")?;
+
+            csv_writer
+                .write_record([
+                    date.format("%Y-%m-%d").to_string(),
+                    row.try_get("slot_type")?,
+                    row.try_get("location")?,
+                    row.try_get("template_name")?,
+                    row.try_get("option_name")?,
+                    planned.unwrap_or_default(),
+                    servings.to_string(),
+                    status,
+                    off_plan.to_string(),
+                    notes.unwrap_or_default(),
+                    tags.unwrap_or_default(),
+                ])
+                .map_err(Self::csv_error)?;
+            count += 1;
+        }
+
+        csv_writer
+            .flush()
+            .map_err(|e| ApiError::InternalError(e.to_string()))?;
+
+        Ok(count)
+    }
+
+    fn csv_error(err: csv::Error) -> ApiError {
+        ApiError::InternalError(format!("Failed to write CSV: {}", err))
+    }
+
+    /// Map an ID from the export document to the newly inserted row
+    fn remap(ids: &HashMap<i64, i64>, old_id: i64, kind: &str, context: &str) -> ApiResult<i64> {
+        ids.get(&old_id).copied().ok_or_else(|| {
+            ApiError::ValidationError(format!(
+                "{} references unknown {} id {}",
+                context, kind, old_id
+            ))
+        })
+    }
+
+    /// Reject a document whose tag parents form a cycle
+    /// Usage roll-ups walk parent_tag_id, so the hierarchy must stay acyclic.
+    fn check_tag_hierarchy(tags: &[ExportedTag]) -> ApiResult<()> {
+        let parents: HashMap<i64, Option<i64>> =
+            tags.iter().map(|t| (t.id, t.parent_tag_id)).collect();
+
+        for tag in tags {
+            let mut seen = HashSet::new();
+            let mut current = tag.parent_tag_id;
+            while let Some(id) = current {
+                if id == tag.id {
+                    return Err(ApiError::ValidationError(format!(
+                        "Tag '{}' would be nested under itself",
+                        tag.name
+                    )));
+                }
+                if !seen.insert(id) {
+                    break;
+                }
+                current = parents.get(&id).copied().flatten();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn duplicate_id(kind: &str, id: i64) -> ApiError {
+        ApiError::ValidationError(format!("Duplicate {} id {} in export document", kind, id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        CreateMealOption, CreateMealSlot, CreateOffPlanEntry, LocationType, LogActualMeal,
+        OptionGroup, SlotType, TagCategory, UpdateMealOption, UpdateTag,
+    };
+    use crate::repository::AuditRepository;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect(":memory:")
+            .await
+            .expect("Failed to create test pool");
+
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
+
+        pool
+    }
+
+    async fn create_tag(pool: &SqlitePool, name: &str, parent_tag_id: Option<i64>) -> i64 {
+        TagRepository::create(
+            pool,
+            CreateTag {
+                name: name.to_string(),
+                display_name: name.to_string(),
+                category: TagCategory::Ingredient,
+                weekly_suggestion: Some(2),
+                parent_tag_id,
+            },
+        )
+        .await
+        .expect("Failed to create tag")
+        .id
+    }
+
+    /// Populate a database with a small plan: pasta -> pasta_integrale, one template, one option, two entries
+    async fn populate(pool: &SqlitePool) {
+        let pasta = create_tag(pool, "pasta", None).await;
+        let integrale = create_tag(pool, "pasta_integrale", Some(pasta)).await;
+
+        let template = MealTemplateRepository::create(
+            pool,
+            CreateMealTemplate {
+                name: "Pasta al pomodoro".to_string(),
+                description: Some("Classica".to_string()),
+                compatible_slots: vec![SlotType::LUNCH, SlotType::DINNER],
+                location_type: LocationType::Home,
+                weekly_limit: Some(3),
+            },
+        )
+        .await
+        .expect("Failed to create template");
+
+        let option = MealOptionRepository::create(
+            pool,
+            CreateMealOption {
+                template_id: template.id,
+                name: "Pasta integrale".to_string(),
+                description: None,
+                nutritional_notes: Some("80g".to_string()),
+            },
+        )
+        .await
+        .expect("Failed to create option");
+
+        MealOptionRepository::set_tags(pool, option.id, vec![pasta, integrale])
+            .await
+            .expect("Failed to set tags");
+        MealOptionRepository::set_nutrients(
+            pool,
+            option.id,
+            SetNutrientProfile {
+                kcal: 280.0,
+                protein_g: 10.0,
+                carbs_g: 55.0,
+                fat_g: 2.0,
+                fiber_g: 6.0,
+            },
+        )
+        .await
+        .expect("Failed to set nutrients");
+
+        for (day, status) in [(4, EntryStatus::Eaten), (5, EntryStatus::Planned)] {
+            MealEntryRepository::create(
+                pool,
+                CreateMealEntry {
+                    meal_option_id: option.id,
+                    date: chrono::NaiveDate::from_ymd_opt(2024, 11, day).unwrap(),
+                    slot_type: SlotType::LUNCH,
+                    location: LocationType::Home,
+                    servings: Some(1.5),
+                    notes: Some("con basilico".to_string()),
+                    status: Some(status),
+                    extra_options: vec![],
+                },
+            )
+            .await
+            .expect("Failed to create entry");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_contents() {
+        let pool = setup_test_pool().await;
+        populate(&pool).await;
+
+        let export = ExportService::export_database(&pool).await.unwrap();
+
+        assert_eq!(export.format_version, EXPORT_FORMAT_VERSION);
+        assert_eq!(export.tags.len(), 2);
+        assert_eq!(export.templates.len(), 1);
+        assert_eq!(export.options.len(), 1);
+        assert_eq!(export.option_tags.len(), 2);
+        assert_eq!(export.entries.len(), 2);
+        assert!(export.options[0].nutrients.is_some());
+
+        let child = export
+            .tags
+            .iter()
+            .find(|t| t.name == "pasta_integrale")
+            .unwrap();
+        let parent = export.tags.iter().find(|t| t.name == "pasta").unwrap();
+        assert_eq!(child.parent_tag_id, Some(parent.id));
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_remaps_ids() {
+        let source = setup_test_pool().await;
+        populate(&source).await;
+        let export = ExportService::export_database(&source).await.unwrap();
+        let json = serde_json::to_value(&export).unwrap();
+
+        // Target already has data so every ID is shifted, and one tag name overlaps
+        let target = setup_test_pool().await;
+        create_tag(&target, "verdure", None).await;
+        create_tag(&target, "pasta", None).await;
+        let filler = MealTemplateRepository::create(
+            &target,
+            CreateMealTemplate {
+                name: "Filler".to_string(),
+                description: None,
+                compatible_slots: vec![SlotType::BREAKFAST],
+                location_type: LocationType::Any,
+                weekly_limit: None,
+            },
+        )
+        .await
+        .unwrap();
+        MealOptionRepository::create(
+            &target,
+            CreateMealOption {
+                template_id: filler.id,
+                name: "Filler option".to_string(),
+                description: None,
+                nutritional_notes: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let summary = ExportService::import_database(&target, json).await.unwrap();
+
+        assert_eq!(summary.tags_created, 1);
+        assert_eq!(summary.tags_merged, 1);
+        assert_eq!(summary.templates_created, 1);
+        assert_eq!(summary.options_created, 1);
+        assert_eq!(summary.option_tags_created, 2);
+        assert_eq!(summary.entries_created, 2);
+
+        // Hierarchy points at the pre-existing "pasta" tag
+        let pasta = TagRepository::get_by_name(&target, "pasta")
+            .await
+            .unwrap()
+            .unwrap();
+        let integrale = TagRepository::get_by_name(&target, "pasta_integrale")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(integrale.parent_tag_id, Some(pasta.id));
+
+        // Entries point at the imported option, which kept its tags and nutrients
+        let options = MealOptionRepository::search(&target, "Pasta integrale")
+            .await
+            .unwrap();
+        assert_eq!(options.len(), 1);
+        let option_id = options[0].id;
+
+        let entries = MealEntryRepository::get_by_meal_option(&target, option_id)
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|e| e.servings == 1.5));
+
+        let with_tags = MealOptionRepository::get_with_tags(&target, option_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(with_tags.tags.contains(&pasta.id));
+        assert!(with_tags.tags.contains(&integrale.id));
+
+        let nutrients = MealOptionRepository::get_nutrients(&target, option_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(nutrients.kcal, 280.0);
+
+        // The import is undone as one operation, leaving the existing rows alone
+        let operation = AuditRepository::undo_last(&target).await.unwrap().unwrap();
+        assert_eq!(operation.command, "import_database");
+        assert_eq!(operation.records.len(), 5);
+        let tags: Vec<String> = TagRepository::get_all(&target)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(tags.len(), 2);
+        assert!(tags.contains(&"pasta".to_string()) && tags.contains(&"verdure".to_string()));
+        assert_eq!(
+            MealTemplateRepository::get_all(&target)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(MealEntryRepository::get_all(&target)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_custom_slots() {
+        let source = setup_test_pool().await;
+        let slot = MealSlotRepository::create(
+            &source,
+            CreateMealSlot {
+                name: "pre_workout".to_string(),
+                display_name: "Pre-workout".to_string(),
+                sort_order: 35,
+                active: None,
+            },
+        )
+        .await
+        .unwrap();
+        let template = MealTemplateRepository::create(
+            &source,
+            CreateMealTemplate {
+                name: "Banana".to_string(),
+                description: None,
+                compatible_slots: vec![slot.name.clone()],
+                location_type: LocationType::Any,
+                weekly_limit: None,
+            },
+        )
+        .await
+        .unwrap();
+        let option = MealOptionRepository::create(
+            &source,
+            CreateMealOption {
+                template_id: template.id,
+                name: "Banana".to_string(),
+                description: None,
+                nutritional_notes: None,
+            },
+        )
+        .await
+        .unwrap();
+        MealEntryRepository::create(
+            &source,
+            CreateMealEntry {
+                meal_option_id: option.id,
+                date: chrono::NaiveDate::from_ymd_opt(2024, 11, 4).unwrap(),
+                slot_type: slot.name.clone(),
+                location: LocationType::Home,
+                servings: None,
+                notes: None,
+                status: Some(EntryStatus::Eaten),
+                extra_options: vec![],
+            },
+        )
+        .await
+        .unwrap();
+        let json =
+            serde_json::to_value(ExportService::export_database(&source).await.unwrap()).unwrap();
+
+        let target = setup_test_pool().await;
+        let summary = ExportService::import_database(&target, json).await.unwrap();
+
+        assert_eq!(summary.slots_created, 1);
+        assert_eq!(summary.slots_merged, 5);
+        let imported = MealSlotRepository::get_by_name(&target, "pre_workout")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(imported.display_name, "Pre-workout");
+        assert_eq!(imported.sort_order, 35);
+        let entries = MealEntryRepository::get_all(&target).await.unwrap();
+        assert_eq!(entries[0].slot_type, slot.name);
+    }
+
+    #[tokio::test]
+    async fn test_import_version_1_document() {
+        let source = setup_test_pool().await;
+        populate(&source).await;
+        let mut json =
+            serde_json::to_value(ExportService::export_database(&source).await.unwrap()).unwrap();
+
+        // Version 1 predates configurable slots
+        json["format_version"] = serde_json::json!(1);
+        json.as_object_mut().unwrap().remove("slots");
+
+        let target = setup_test_pool().await;
+        let summary = ExportService::import_database(&target, json).await.unwrap();
+        assert_eq!(summary.slots_created, 0);
+        assert_eq!(summary.entries_created, 2);
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_entry_snapshot() {
+        let source = setup_test_pool().await;
+        populate(&source).await;
+
+        // The library changes after the entries were logged
+        let option = MealOptionRepository::get_all(&source).await.unwrap()[0].clone();
+        MealOptionRepository::update(
+            &source,
+            option.id,
+            UpdateMealOption {
+                name: Some("Pasta corta".to_string()),
+                description: None,
+                nutritional_notes: None,
+            },
+        )
+        .await
+        .unwrap();
+        MealOptionRepository::set_tags(&source, option.id, vec![])
+            .await
+            .unwrap();
+        let json =
+            serde_json::to_value(ExportService::export_database(&source).await.unwrap()).unwrap();
+
+        let target = setup_test_pool().await;
+        ExportService::import_database(&target, json).await.unwrap();
+
+        let entries = MealEntryRepository::get_all(&target).await.unwrap();
+        assert_eq!(entries[0].option_name, "Pasta integrale");
+        assert_eq!(entries[0].template_name, "Pasta al pomodoro");
+        assert_eq!(entries[0].tag_names, vec!["pasta", "pasta_integrale"]);
+
+        // Snapshot tags resolve to the target's tags and count toward usage
+        let pasta = TagRepository::get_by_name(&target, "pasta")
+            .await
+            .unwrap()
+            .unwrap();
+        let usage = MealEntryRepository::get_weekly_tag_usage(&target, pasta.id, "2024-45")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(usage.usage_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_swapped_entry() {
+        let source = setup_test_pool().await;
+        populate(&source).await;
+        let planned = MealEntryRepository::get_all(&source).await.unwrap()[1].clone();
+        let rice = MealOptionRepository::create(
+            &source,
+            CreateMealOption {
+                template_id: planned.template_id.unwrap(),
+                name: "Riso".to_string(),
+                description: None,
+                nutritional_notes: None,
+            },
+        )
+        .await
+        .unwrap();
+        MealEntryRepository::log_actual(
+            &source,
+            planned.id,
+            LogActualMeal {
+                meal_option_id: rice.id,
+                servings: None,
+                extra_options: vec![],
+                location: None,
+                notes: None,
+            },
+        )
+        .await
+        .unwrap();
+        let json =
+            serde_json::to_value(ExportService::export_database(&source).await.unwrap()).unwrap();
+
+        let target = setup_test_pool().await;
+        ExportService::import_database(&target, json).await.unwrap();
+
+        let swapped = MealEntryRepository::get_all(&target).await.unwrap()[1].clone();
+        assert_eq!(swapped.status, EntryStatus::Eaten);
+        assert!(swapped.is_swap());
+        assert_eq!(swapped.option_name, "Riso");
+        assert_eq!(swapped.planned_options[0].option_name, "Pasta integrale");
+        assert_eq!(swapped.planned_options[0].servings, 1.5);
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_option_groups() {
+        let source = setup_test_pool().await;
+        let template = MealTemplateRepository::create(
+            &source,
+            CreateMealTemplate {
+                name: "Pane e spalmabile".to_string(),
+                description: None,
+                compatible_slots: vec![SlotType::BREAKFAST],
+                location_type: LocationType::Any,
+                weekly_limit: None,
+            },
+        )
+        .await
+        .unwrap();
+        let groups = vec![
+            OptionGroup {
+                name: "pane".to_string(),
+                min_choices: 1,
+                max_choices: Some(1),
+            },
+            OptionGroup {
+                name: "spalmabile".to_string(),
+                min_choices: 0,
+                max_choices: None,
+            },
+        ];
+        MealTemplateRepository::set_option_groups(&source, template.id, groups.clone())
+            .await
+            .unwrap();
+
+        let mut option_ids = Vec::new();
+        for (name, group) in [("Pane", "pane"), ("Marmellata", "spalmabile")] {
+            let option = MealOptionRepository::create(
+                &source,
+                CreateMealOption {
+                    template_id: template.id,
+                    name: name.to_string(),
+                    description: None,
+                    nutritional_notes: None,
+                },
+            )
+            .await
+            .unwrap();
+            MealOptionRepository::set_group(&source, option.id, Some(group.to_string()))
+                .await
+                .unwrap();
+            option_ids.push(option.id);
+        }
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 11, 4).unwrap();
+        MealEntryRepository::create(
+            &source,
+            CreateMealEntry {
+                meal_option_id: option_ids[0],
+                date,
+                slot_type: SlotType::BREAKFAST,
+                location: LocationType::Home,
+                servings: None,
+                notes: None,
+                status: Some(EntryStatus::Eaten),
+                extra_options: vec![SelectedOption {
+                    meal_option_id: option_ids[1],
+                    servings: Some(0.5),
+                }],
+            },
+        )
+        .await
+        .unwrap();
+
+        // The spread is renamed after it was logged
+        MealOptionRepository::update(
+            &source,
+            option_ids[1],
+            UpdateMealOption {
+                name: Some("Miele".to_string()),
+                description: None,
+                nutritional_notes: None,
+            },
+        )
+        .await
+        .unwrap();
+        let json =
+            serde_json::to_value(ExportService::export_database(&source).await.unwrap()).unwrap();
+
+        let target = setup_test_pool().await;
+        ExportService::import_database(&target, json).await.unwrap();
+
+        let imported = MealTemplateRepository::get_all(&target).await.unwrap();
+        assert_eq!(
+            MealTemplateRepository::get_option_groups(&target, imported[0].id)
+                .await
+                .unwrap(),
+            groups
+        );
+        let options = MealOptionRepository::get_by_template_id(&target, imported[0].id)
+            .await
+            .unwrap();
+        let option_groups: Vec<_> = options
+            .iter()
+            .map(|o| (o.name.as_str(), o.option_group.as_deref()))
+            .collect();
+        assert_eq!(
+            option_groups,
+            vec![("Miele", Some("spalmabile")), ("Pane", Some("pane"))]
+        );
+
+        let entries = MealEntryRepository::get_all(&target).await.unwrap();
+        let names: Vec<_> = entries[0]
+            .options
+            .iter()
+            .map(|o| (o.option_name.as_str(), o.servings))
+            .collect();
+        assert_eq!(names, vec![("Pane", 1.0), ("Marmellata", 0.5)]);
+
+        // The CSV lists every option of the meal
+        let mut out = Vec::new();
+        ExportService::write_entries_csv(&target, date, date, &mut out)
+            .await
+            .unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        assert!(csv.contains("Pane + Marmellata"));
+    }
+
+    #[tokio::test]
+    async fn test_import_version_2_document() {
+        let source = setup_test_pool().await;
+        populate(&source).await;
+        let mut json =
+            serde_json::to_value(ExportService::export_database(&source).await.unwrap()).unwrap();
+
+        // Version 2 predates entry snapshots
+        json["format_version"] = serde_json::json!(2);
+        for entry in json["entries"].as_array_mut().unwrap() {
+            entry.as_object_mut().unwrap().remove("snapshot");
+        }
+
+        let target = setup_test_pool().await;
+        ExportService::import_database(&target, json).await.unwrap();
+
+        // The snapshot is taken from the imported library
+        let entries = MealEntryRepository::get_all(&target).await.unwrap();
+        assert_eq!(entries[0].option_name, "Pasta integrale");
+        assert_eq!(entries[0].tag_names, vec!["pasta", "pasta_integrale"]);
+    }
+
+    #[tokio::test]
+    async fn test_import_version_5_document() {
+        let source = setup_test_pool().await;
+        populate(&source).await;
+        let mut json =
+            serde_json::to_value(ExportService::export_database(&source).await.unwrap()).unwrap();
+
+        // Version 5 flags eaten entries and has no plans
+        json["format_version"] = serde_json::json!(5);
+        for entry in json["entries"].as_array_mut().unwrap() {
+            let entry = entry.as_object_mut().unwrap();
+            let eaten = entry.remove("status").unwrap() == "eaten";
+            entry.remove("planned_options");
+            entry.insert("completed".to_string(), eaten.into());
+        }
+
+        let target = setup_test_pool().await;
+        ExportService::import_database(&target, json).await.unwrap();
+
+        let entries = MealEntryRepository::get_all(&target).await.unwrap();
+        assert_eq!(entries[0].status, EntryStatus::Eaten);
+        assert_eq!(entries[1].status, EntryStatus::Planned);
+        assert!(entries.iter().all(|e| e.planned_options == e.options));
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_unsupported_version() {
+        let pool = setup_test_pool().await;
+        populate(&pool).await;
+        let mut json =
+            serde_json::to_value(ExportService::export_database(&pool).await.unwrap()).unwrap();
+        json["format_version"] = serde_json::json!(EXPORT_FORMAT_VERSION + 1);
+
+        let result = ExportService::import_database(&pool, json).await;
+        assert!(matches!(result, Err(ApiError::ValidationError(_))));
+
+        let result = ExportService::import_database(&pool, serde_json::json!({})).await;
+        assert!(matches!(result, Err(ApiError::ValidationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_import_is_atomic() {
+        let source = setup_test_pool().await;
+        populate(&source).await;
+        let mut export = ExportService::export_database(&source).await.unwrap();
+
+        // Break the last section so the failure happens after earlier inserts
+        export.entries[1].meal_option_id = Some(99999);
+        let json = serde_json::to_value(&export).unwrap();
+
+        let target = setup_test_pool().await;
+        let result = ExportService::import_database(&target, json).await;
+        assert!(matches!(result, Err(ApiError::ValidationError(_))));
+
+        // Nothing from the document was written
+        assert!(TagRepository::get_all(&target).await.unwrap().is_empty());
+        assert!(MealTemplateRepository::get_all(&target)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(MealEntryRepository::get_all(&target)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_tag_cycle() {
+        let source = setup_test_pool().await;
+        populate(&source).await;
+        let mut export = ExportService::export_database(&source).await.unwrap();
+
+        // pasta -> pasta_integrale -> pasta
+        let integrale = export
+            .tags
+            .iter()
+            .find(|t| t.name == "pasta_integrale")
+            .unwrap()
+            .id;
+        let pasta = export.tags.iter_mut().find(|t| t.name == "pasta").unwrap();
+        pasta.parent_tag_id = Some(integrale);
+        let json = serde_json::to_value(&export).unwrap();
+
+        let target = setup_test_pool().await;
+        let result = ExportService::import_database(&target, json).await;
+        assert!(matches!(result, Err(ApiError::ValidationError(_))));
+        assert!(TagRepository::get_all(&target).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_entries_csv() {
+        let pool = setup_test_pool().await;
+        populate(&pool).await;
+
+        // Second option without tags, with a note that needs quoting
+        let template = MealTemplateRepository::get_all(&pool).await.unwrap()[0].clone();
+        let option = MealOptionRepository::create(
+            &pool,
+            CreateMealOption {
+                template_id: template.id,
+                name: "Riso".to_string(),
+                description: None,
+                nutritional_notes: None,
+            },
+        )
+        .await
+        .unwrap();
+        MealEntryRepository::create(
+            &pool,
+            CreateMealEntry {
+                meal_option_id: option.id,
+                date: NaiveDate::from_ymd_opt(2024, 11, 4).unwrap(),
+                slot_type: SlotType::DINNER,
+                location: LocationType::Restaurant,
+                servings: None,
+                notes: Some("cena fuori, \"porzione\" grande".to_string()),
+                status: Some(EntryStatus::Eaten),
+                extra_options: vec![],
+            },
+        )
+        .await
+        .unwrap();
+
+        let mut buffer = Vec::new();
+        let count = ExportService::write_entries_csv(
+            &pool,
+            NaiveDate::from_ymd_opt(2024, 11, 4).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 11, 4).unwrap(),
+            &mut buffer,
+        )
+        .await
+        .unwrap();
+        assert_eq!(count, 2);
+
+        let csv_text = String::from_utf8(buffer).unwrap();
+        let lines: Vec<&str> = csv_text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            "date,slot,location,template,option,planned_option,servings,status,off_plan,notes,tags"
+        );
+        assert_eq!(
+            lines[1],
+            "2024-11-04,lunch,home,Pasta al pomodoro,Pasta integrale,Pasta integrale,1.5,eaten,false,con basilico,pasta;pasta_integrale"
+        );
+        assert_eq!(
+            lines[2],
+            "2024-11-04,dinner,restaurant,Pasta al pomodoro,Riso,Riso,1,eaten,false,\"cena fuori, \"\"porzione\"\" grande\","
+        );
+    }
+
+    #[tokio::test]
+    async fn test_entries_csv_keeps_tag_snapshot_after_rename() {
+        let pool = setup_test_pool().await;
+        populate(&pool).await;
+
+        let pasta = TagRepository::get_by_name(&pool, "pasta")
+            .await
+            .unwrap()
+            .unwrap();
+        TagRepository::update(
+            &pool,
+            pasta.id,
+            UpdateTag {
+                display_name: Some("Pasta di semola".to_string()),
+                category: None,
+                weekly_suggestion: None,
+                parent_tag_id: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2024, 11, 4).unwrap();
+        let mut buffer = Vec::new();
+        ExportService::write_entries_csv(&pool, date, date, &mut buffer)
+            .await
+            .unwrap();
+
+        let csv_text = String::from_utf8(buffer).unwrap();
+        assert!(csv_text
+            .lines()
+            .nth(1)
+            .unwrap()
+            .ends_with(",pasta;pasta_integrale"));
+    }
+
+    #[tokio::test]
+    async fn test_entries_csv_off_plan() {
+        let pool = setup_test_pool().await;
+        let date = NaiveDate::from_ymd_opt(2024, 11, 5).unwrap();
+        MealEntryRepository::create_off_plan(
+            &pool,
+            CreateOffPlanEntry {
+                date,
+                slot_type: SlotType::DINNER,
+                location: LocationType::Restaurant,
+                description: "Pizza margherita".to_string(),
+                servings: None,
+                notes: None,
+                status: None,
+                tag_ids: vec![],
+                estimated_nutrients: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let mut buffer = Vec::new();
+        ExportService::write_entries_csv(&pool, date, date, &mut buffer)
+            .await
+            .unwrap();
+
+        let csv_text = String::from_utf8(buffer).unwrap();
+        assert_eq!(
+            csv_text.lines().nth(1).unwrap(),
+            "2024-11-05,dinner,restaurant,,Pizza margherita,,1,eaten,true,,"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_entries_csv_empty_and_invalid_range() {
+        let pool = setup_test_pool().await;
+        let start = NaiveDate::from_ymd_opt(2024, 11, 4).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 11, 10).unwrap();
+
+        let mut buffer = Vec::new();
+        let count = ExportService::write_entries_csv(&pool, start, end, &mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+        assert_eq!(String::from_utf8(buffer).unwrap().lines().count(), 1);
+
+        let result = ExportService::write_entries_csv(&pool, end, start, Vec::new()).await;
+        assert!(matches!(result, Err(ApiError::ValidationError(_))));
+    }
+}
@@ -0,0 +1,1178 @@
+// Library Pack Service
+// Exports the template library without meal history and merges library packs by name
+
+use crate::error::{ApiError, ApiResult};
+use crate::models::{
+    CreateMealTemplate, CreateTag, ExportedSlot, LibraryItemKind, LibraryPack, LibraryPackImport,
+    MealOption, MealTemplate, OptionGroup, PackConflict, PackConflictStrategy, PackOption, PackTag,
+    PackTemplate, SetNutrientProfile, SyncEntity, Tag, LIBRARY_PACK_FORMAT_VERSION,
+};
+use crate::repository::{
+    AuditScope, MealOptionRepository, MealSlotRepository, MealTemplateRepository, TagRepository,
+};
+use chrono::Utc;
+use sqlx::{SqliteConnection, SqlitePool};
+use std::collections::{HashMap, HashSet};
+
+/// A library option with everything a pack option is compared against
+struct LibraryOption {
+    option: MealOption,
+    tag_ids: Vec<i64>,
+    nutrients: Option<SetNutrientProfile>,
+}
+
+/// A library template matched by a pack template, with its groups and active options
+struct LibraryTemplate {
+    template: MealTemplate,
+    option_groups: Vec<OptionGroup>,
+    options: Vec<LibraryOption>,
+}
+
+pub struct LibraryPackService;
+
+impl LibraryPackService {
+    /// Build a library pack from the active templates and options
+    /// Active tags are included with their hierarchy; archived tags only when an
+    /// exported option or tag still references them. Slots are included when a
+    /// template is compatible with them.
+    pub async fn export_pack(pool: &SqlitePool) -> ApiResult<LibraryPack> {
+        let all_tags = TagRepository::get_all_including_archived(pool).await?;
+        let tag_names: HashMap<i64, &str> =
+            all_tags.iter().map(|t| (t.id, t.name.as_str())).collect();
+        let mut included_tags: HashSet<i64> = all_tags
+            .iter()
+            .filter(|t| !t.archived)
+            .map(|t| t.id)
+            .collect();
+
+        let mut nutrients: HashMap<i64, SetNutrientProfile> =
+            MealOptionRepository::get_all_nutrients(pool)
+                .await?
+                .into_iter()
+                .map(|n| {
+                    (
+                        n.meal_option_id,
+                        SetNutrientProfile {
+                            kcal: n.kcal,
+                            protein_g: n.protein_g,
+                            carbs_g: n.carbs_g,
+                            fat_g: n.fat_g,
+                            fiber_g: n.fiber_g,
+                        },
+                    )
+                })
+                .collect();
+
+        let mut slot_names = Vec::new();
+        let mut templates = Vec::new();
+        for template in MealTemplateRepository::get_all(pool).await? {
+            let mut options = Vec::new();
+            for o in MealOptionRepository::get_by_template_with_tags(pool, template.id).await? {
+                included_tags.extend(o.tags.iter().copied());
+                options.push(PackOption {
+                    nutrients: nutrients.remove(&o.option.id),
+                    tags: o
+                        .tags
+                        .iter()
+                        .filter_map(|id| tag_names.get(id))
+                        .map(|name| name.to_string())
+                        .collect(),
+                    name: o.option.name,
+                    description: o.option.description,
+                    nutritional_notes: o.option.nutritional_notes,
+                    option_group: o.option.option_group,
+                });
+            }
+
+            for slot in &template.compatible_slots {
+                if !slot_names.contains(slot) {
+                    slot_names.push(slot.clone());
+                }
+            }
+
+            templates.push(PackTemplate {
+                option_groups: MealTemplateRepository::get_option_groups(pool, template.id).await?,
+                options,
+                name: template.name,
+                description: template.description,
+                compatible_slots: template.compatible_slots,
+                location_type: template.location_type,
+                weekly_limit: template.weekly_limit,
+            });
+        }
+
+        // Parents of included tags, up to the roots
+        loop {
+            let parents: Vec<i64> = all_tags
+                .iter()
+                .filter(|t| included_tags.contains(&t.id))
+                .filter_map(|t| t.parent_tag_id)
+                .filter(|p| !included_tags.contains(p))
+                .collect();
+            if parents.is_empty() {
+                break;
+            }
+            included_tags.extend(parents);
+        }
+
+        let tags = all_tags
+            .iter()
+            .filter(|t| included_tags.contains(&t.id))
+            .map(|t| PackTag {
+                name: t.name.clone(),
+                display_name: t.display_name.clone(),
+                category: t.category,
+                weekly_suggestion: t.weekly_suggestion,
+                parent: t
+                    .parent_tag_id
+                    .and_then(|p| tag_names.get(&p))
+                    .map(|name| name.to_string()),
+            })
+            .collect();
+
+        let slots = MealSlotRepository::get_all(pool)
+            .await?
+            .into_iter()
+            .filter(|s| slot_names.contains(&s.name))
+            .map(|s| ExportedSlot {
+                name: s.name,
+                display_name: s.display_name,
+                sort_order: s.sort_order,
+                active: s.active,
+            })
+            .collect();
+
+        Ok(LibraryPack {
+            format_version: LIBRARY_PACK_FORMAT_VERSION,
+            exported_at: Utc::now(),
+            slots,
+            tags,
+            templates,
+        })
+    }
+
+    /// Check the format version and deserialize a library pack
+    pub fn parse_pack(document: serde_json::Value) -> ApiResult<LibraryPack> {
+        let version = document
+            .get("format_version")
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| {
+                ApiError::ValidationError("Library pack has no format_version".to_string())
+            })?;
+
+        if version == 0 || version > LIBRARY_PACK_FORMAT_VERSION as u64 {
+            return Err(ApiError::ValidationError(format!(
+                "Unsupported library pack version {} (supported: 1-{})",
+                version, LIBRARY_PACK_FORMAT_VERSION
+            )));
+        }
+
+        serde_json::from_value(document)
+            .map_err(|e| ApiError::ValidationError(format!("Invalid library pack: {}", e)))
+    }
+
+    /// Merge a library pack into the library in a single transaction
+    /// Tags are matched on `name`, templates by name among active templates and options
+    /// by name within their template (case-insensitive). Identical items are merged;
+    /// items that differ are reported as conflicts and resolved with `on_conflict`.
+    /// With `preview` set the import is rolled back, so the result shows what would happen.
+    pub async fn import_pack(
+        pool: &SqlitePool,
+        document: serde_json::Value,
+        on_conflict: PackConflictStrategy,
+        preview: bool,
+    ) -> ApiResult<LibraryPackImport> {
+        let pack = Self::parse_pack(document)?;
+        let library_tags = TagRepository::get_all_including_archived(pool).await?;
+        Self::validate_pack(&pack, &library_tags)?;
+
+        // Everything the pack is compared against is read before the transaction
+        let active_templates = MealTemplateRepository::get_all(pool).await?;
+        let mut matched: HashMap<usize, LibraryTemplate> = HashMap::new();
+        for (index, template) in pack.templates.iter().enumerate() {
+            let Some(existing) = active_templates
+                .iter()
+                .find(|t| Self::same_name(&t.name, &template.name))
+            else {
+                continue;
+            };
+
+            let mut options = Vec::new();
+            for o in MealOptionRepository::get_by_template_with_tags(pool, existing.id).await? {
+                let nutrients = MealOptionRepository::get_nutrients(pool, o.option.id)
+                    .await?
+                    .map(|n| SetNutrientProfile {
+                        kcal: n.kcal,
+                        protein_g: n.protein_g,
+                        carbs_g: n.carbs_g,
+                        fat_g: n.fat_g,
+                        fiber_g: n.fiber_g,
+                    });
+                options.push(LibraryOption {
+                    option: o.option,
+                    tag_ids: o.tags,
+                    nutrients,
+                });
+            }
+
+            matched.insert(
+                index,
+                LibraryTemplate {
+                    template: existing.clone(),
+                    option_groups: MealTemplateRepository::get_option_groups(pool, existing.id)
+                        .await?,
+                    options,
+                },
+            );
+        }
+
+        let mut result = LibraryPackImport {
+            preview,
+            ..Default::default()
+        };
+        let mut tx = pool.begin().await?;
+
+        // Any library row may be overwritten; entries are never touched
+        let mut audit = AuditScope::new("import_library_pack");
+        if !preview {
+            for entity in [
+                SyncEntity::Slot,
+                SyncEntity::Tag,
+                SyncEntity::Template,
+                SyncEntity::Option,
+            ] {
+                audit.track_all(&mut tx, entity).await?;
+            }
+        }
+
+        for slot in &pack.slots {
+            let created = sqlx::query(
+                "INSERT OR IGNORE INTO meal_slots (name, display_name, sort_order, active)
+                 VALUES (?, ?, ?, ?)",
+            )
+            .bind(slot.name.to_db_string())
+            .bind(&slot.display_name)
+            .bind(slot.sort_order)
+            .bind(slot.active)
+            .execute(&mut *tx)
+            .await?;
+            result.slots_created += created.rows_affected() as usize;
+        }
+
+        let tag_ids =
+            Self::import_tags(&mut tx, &pack, &library_tags, on_conflict, &mut result).await?;
+
+        let mut taken_template_names: Vec<String> = active_templates
+            .iter()
+            .map(|t| t.name.clone())
+            .chain(pack.templates.iter().map(|t| t.name.clone()))
+            .collect();
+
+        for (index, template) in pack.templates.iter().enumerate() {
+            let Some(library) = matched.get(&index) else {
+                Self::create_template(&mut tx, template, &template.name, &tag_ids).await?;
+                result.templates_created += 1;
+                result.options_created += template.options.len();
+                continue;
+            };
+
+            let fields = Self::template_differences(library, template);
+            let mut declared_groups = &library.option_groups;
+            if !fields.is_empty() {
+                let mut renamed_to = None;
+                match on_conflict {
+                    PackConflictStrategy::Skip => {}
+                    PackConflictStrategy::Overwrite => {
+                        Self::overwrite_template(&mut tx, library.template.id, template).await?;
+                        declared_groups = &template.option_groups;
+                    }
+                    PackConflictStrategy::Rename => {
+                        let name = Self::unique_name(&template.name, &taken_template_names);
+                        Self::create_template(&mut tx, template, &name, &tag_ids).await?;
+                        taken_template_names.push(name.clone());
+                        renamed_to = Some(name);
+                    }
+                }
+                result.conflicts.push(PackConflict {
+                    kind: LibraryItemKind::Template,
+                    name: template.name.clone(),
+                    template_name: None,
+                    fields,
+                    resolution: on_conflict,
+                    renamed_to: renamed_to.clone(),
+                });
+
+                if renamed_to.is_some() {
+                    result.templates_created += 1;
+                    result.options_created += template.options.len();
+                    continue;
+                }
+            }
+            result.templates_merged += 1;
+
+            Self::merge_options(
+                &mut tx,
+                library,
+                template,
+                declared_groups,
+                &tag_ids,
+                on_conflict,
+                &mut result,
+            )
+            .await?;
+        }
+
+        if preview {
+            tx.rollback().await?;
+        } else {
+            audit.record(&mut tx).await?;
+            tx.commit().await?;
+        }
+
+        Ok(result)
+    }
+
+    /// Check names, references and values of a pack before anything is written
+    fn validate_pack(pack: &LibraryPack, library_tags: &[Tag]) -> ApiResult<()> {
+        let known_tag = |name: &str| {
+            pack.tags.iter().any(|t| t.name == name) || library_tags.iter().any(|t| t.name == name)
+        };
+
+        for (i, tag) in pack.tags.iter().enumerate() {
+            CreateTag {
+                name: tag.name.clone(),
+                display_name: tag.display_name.clone(),
+                category: tag.category,
+                weekly_suggestion: tag.weekly_suggestion,
+                parent_tag_id: None,
+            }
+            .validate()
+            .map_err(ApiError::ValidationError)?;
+
+            if pack.tags[..i].iter().any(|t| t.name == tag.name) {
+                return Err(Self::duplicate("tag", &tag.name));
+            }
+            if let Some(parent) = &tag.parent {
+                if !known_tag(parent) {
+                    return Err(ApiError::ValidationError(format!(
+                        "Tag '{}' has unknown parent '{}'",
+                        tag.name, parent
+                    )));
+                }
+            }
+        }
+
+        for (i, template) in pack.templates.iter().enumerate() {
+            CreateMealTemplate {
+                name: template.name.clone(),
+                description: template.description.clone(),
+                compatible_slots: template.compatible_slots.clone(),
+                location_type: template.location_type,
+                weekly_limit: template.weekly_limit,
+            }
+            .validate()
+            .map_err(ApiError::ValidationError)?;
+
+            if pack.templates[..i]
+                .iter()
+                .any(|t| Self::same_name(&t.name, &template.name))
+            {
+                return Err(Self::duplicate("template", &template.name));
+            }
+            for group in &template.option_groups {
+                group.validate().map_err(ApiError::ValidationError)?;
+            }
+
+            for (j, option) in template.options.iter().enumerate() {
+                if option.name.trim().is_empty() {
+                    return Err(ApiError::ValidationError(
+                        "Option name cannot be empty".to_string(),
+                    ));
+                }
+                if template.options[..j]
+                    .iter()
+                    .any(|o| Self::same_name(&o.name, &option.name))
+                {
+                    return Err(Self::duplicate("option", &option.name));
+                }
+                if let Some(group) = &option.option_group {
+                    if !template.option_groups.iter().any(|g| &g.name == group) {
+                        return Err(ApiError::ValidationError(format!(
+                            "Option '{}' is in group '{}', which its template does not declare",
+                            option.name, group
+                        )));
+                    }
+                }
+                if let Some(nutrients) = &option.nutrients {
+                    nutrients.validate().map_err(ApiError::ValidationError)?;
+                }
+                if let Some(tag) = option.tags.iter().find(|t| !known_tag(t)) {
+                    return Err(ApiError::ValidationError(format!(
+                        "Option '{}' has unknown tag '{}'",
+                        option.name, tag
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Resolve every pack tag to a tag of this database, then link parents
+    /// Returns the tag ID of each pack tag name
+    async fn import_tags(
+        conn: &mut SqliteConnection,
+        pack: &LibraryPack,
+        library_tags: &[Tag],
+        on_conflict: PackConflictStrategy,
+        result: &mut LibraryPackImport,
+    ) -> ApiResult<HashMap<String, i64>> {
+        let mut tag_ids: HashMap<String, i64> = library_tags
+            .iter()
+            .map(|t| (t.name.clone(), t.id))
+            .collect();
+        let mut relink: Vec<(&PackTag, i64)> = Vec::new();
+
+        for tag in &pack.tags {
+            let Some(existing) = library_tags.iter().find(|t| t.name == tag.name) else {
+                let id = Self::insert_tag(conn, tag, &tag.name, &tag.display_name).await?;
+                tag_ids.insert(tag.name.clone(), id);
+                relink.push((tag, id));
+                result.tags_created += 1;
+                continue;
+            };
+
+            let fields = Self::tag_differences(existing, tag, library_tags);
+            if fields.is_empty() {
+                result.tags_merged += 1;
+                continue;
+            }
+
+            let mut renamed_to = None;
+            match on_conflict {
+                PackConflictStrategy::Skip => result.tags_merged += 1,
+                PackConflictStrategy::Overwrite => {
+                    sqlx::query(
+                        "UPDATE tags SET display_name = ?, category = ?, weekly_suggestion = ?
+                         WHERE id = ?",
+                    )
+                    .bind(&tag.display_name)
+                    .bind(tag.category.to_db_string())
+                    .bind(tag.weekly_suggestion)
+                    .bind(existing.id)
+                    .execute(&mut *conn)
+                    .await?;
+                    relink.push((tag, existing.id));
+                    result.tags_merged += 1;
+                }
+                PackConflictStrategy::Rename => {
+                    let taken: Vec<&str> = library_tags
+                        .iter()
+                        .map(|t| t.name.as_str())
+                        .chain(pack.tags.iter().map(|t| t.name.as_str()))
+                        .chain(tag_ids.keys().map(String::as_str))
+                        .collect();
+                    let name = Self::unique_tag_name(&tag.name, &taken);
+                    let display_name = format!("{} (imported)", tag.display_name);
+                    let id = Self::insert_tag(conn, tag, &name, &display_name).await?;
+                    tag_ids.insert(tag.name.clone(), id);
+                    tag_ids.insert(name.clone(), id);
+                    relink.push((tag, id));
+                    result.tags_created += 1;
+                    renamed_to = Some(name);
+                }
+            }
+
+            result.conflicts.push(PackConflict {
+                kind: LibraryItemKind::Tag,
+                name: tag.name.clone(),
+                template_name: None,
+                fields,
+                resolution: on_conflict,
+                renamed_to,
+            });
+        }
+
+        for (tag, id) in relink {
+            let parent_id = tag.parent.as_ref().map(|name| tag_ids[name]);
+            sqlx::query("UPDATE tags SET parent_tag_id = ? WHERE id = ?")
+                .bind(parent_id)
+                .bind(id)
+                .execute(&mut *conn)
+                .await?;
+
+            // Usage roll-ups walk parent_tag_id, so the hierarchy must stay acyclic
+            let creates_cycle: bool = sqlx::query_scalar(
+                r#"
+                WITH RECURSIVE ancestors(id) AS (
+                    SELECT parent_tag_id FROM tags WHERE id = ?1
+                    UNION
+                    SELECT t.parent_tag_id FROM tags t JOIN ancestors a ON t.id = a.id
+                )
+                SELECT EXISTS(SELECT 1 FROM ancestors WHERE id = ?1)
+                "#,
+            )
+            .bind(id)
+            .fetch_one(&mut *conn)
+            .await?;
+
+            if creates_cycle {
+                return Err(ApiError::ValidationError(format!(
+                    "Tag '{}' would be nested under itself",
+                    tag.name
+                )));
+            }
+        }
+
+        Ok(tag_ids)
+    }
+
+    async fn insert_tag(
+        conn: &mut SqliteConnection,
+        tag: &PackTag,
+        name: &str,
+        display_name: &str,
+    ) -> ApiResult<i64> {
+        let id = sqlx::query_scalar(
+            "INSERT INTO tags (name, display_name, category, weekly_suggestion)
+             VALUES (?, ?, ?, ?)
+             RETURNING id",
+        )
+        .bind(name)
+        .bind(display_name)
+        .bind(tag.category.to_db_string())
+        .bind(tag.weekly_suggestion)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(id)
+    }
+
+    /// Insert a pack template under `name` with all of its options
+    async fn create_template(
+        conn: &mut SqliteConnection,
+        template: &PackTemplate,
+        name: &str,
+        tag_ids: &HashMap<String, i64>,
+    ) -> ApiResult<()> {
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO meal_templates (name, description, location_type, weekly_limit)
+             VALUES (?, ?, ?, ?)
+             RETURNING id",
+        )
+        .bind(name)
+        .bind(&template.description)
+        .bind(template.location_type.to_db_string())
+        .bind(template.weekly_limit)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        MealTemplateRepository::set_slots(conn, id, &template.compatible_slots).await?;
+        MealTemplateRepository::replace_option_groups(conn, id, &template.option_groups).await?;
+        for option in &template.options {
+            Self::insert_option(
+                conn,
+                id,
+                option,
+                &option.name,
+                option.option_group.as_ref(),
+                tag_ids,
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn overwrite_template(
+        conn: &mut SqliteConnection,
+        template_id: i64,
+        template: &PackTemplate,
+    ) -> ApiResult<()> {
+        sqlx::query(
+            "UPDATE meal_templates SET description = ?, location_type = ?, weekly_limit = ?
+             WHERE id = ?",
+        )
+        .bind(&template.description)
+        .bind(template.location_type.to_db_string())
+        .bind(template.weekly_limit)
+        .bind(template_id)
+        .execute(&mut *conn)
+        .await?;
+
+        MealTemplateRepository::set_slots(conn, template_id, &template.compatible_slots).await?;
+        MealTemplateRepository::replace_option_groups(conn, template_id, &template.option_groups)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Add or reconcile the options of a pack template with a matched library template
+    /// `declared_groups` are the template's groups after the import; options in
+    /// other groups are added ungrouped
+    async fn merge_options(
+        conn: &mut SqliteConnection,
+        library: &LibraryTemplate,
+        template: &PackTemplate,
+        declared_groups: &[OptionGroup],
+        tag_ids: &HashMap<String, i64>,
+        on_conflict: PackConflictStrategy,
+        result: &mut LibraryPackImport,
+    ) -> ApiResult<()> {
+        let mut taken_names: Vec<String> = library
+            .options
+            .iter()
+            .map(|o| o.option.name.clone())
+            .chain(template.options.iter().map(|o| o.name.clone()))
+            .collect();
+
+        for option in &template.options {
+            let group = option
+                .option_group
+                .as_ref()
+                .filter(|g| declared_groups.iter().any(|d| &d.name == *g));
+
+            let Some(existing) = library
+                .options
+                .iter()
+                .find(|o| Self::same_name(&o.option.name, &option.name))
+            else {
+                Self::insert_option(
+                    conn,
+                    library.template.id,
+                    option,
+                    &option.name,
+                    group,
+                    tag_ids,
+                )
+                .await?;
+                result.options_created += 1;
+                continue;
+            };
+
+            let fields = Self::option_differences(existing, option, group, tag_ids);
+            if fields.is_empty() {
+                result.options_merged += 1;
+                continue;
+            }
+
+            let mut renamed_to = None;
+            match on_conflict {
+                PackConflictStrategy::Skip => result.options_merged += 1,
+                PackConflictStrategy::Overwrite => {
+                    Self::overwrite_option(conn, existing.option.id, option, group, tag_ids)
+                        .await?;
+                    result.options_merged += 1;
+                }
+                PackConflictStrategy::Rename => {
+                    let name = Self::unique_name(&option.name, &taken_names);
+                    Self::insert_option(conn, library.template.id, option, &name, group, tag_ids)
+                        .await?;
+                    taken_names.push(name.clone());
+                    result.options_created += 1;
+                    renamed_to = Some(name);
+                }
+            }
+
+            result.conflicts.push(PackConflict {
+                kind: LibraryItemKind::Option,
+                name: option.name.clone(),
+                template_name: Some(library.template.name.clone()),
+                fields,
+                resolution: on_conflict,
+                renamed_to,
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn insert_option(
+        conn: &mut SqliteConnection,
+        template_id: i64,
+        option: &PackOption,
+        name: &str,
+        group: Option<&String>,
+        tag_ids: &HashMap<String, i64>,
+    ) -> ApiResult<()> {
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO meal_options (template_id, name, description, nutritional_notes, option_group)
+             VALUES (?, ?, ?, ?, ?)
+             RETURNING id",
+        )
+        .bind(template_id)
+        .bind(name)
+        .bind(&option.description)
+        .bind(&option.nutritional_notes)
+        .bind(group)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Self::write_option_details(conn, id, option, tag_ids).await
+    }
+
+    async fn overwrite_option(
+        conn: &mut SqliteConnection,
+        option_id: i64,
+        option: &PackOption,
+        group: Option<&String>,
+        tag_ids: &HashMap<String, i64>,
+    ) -> ApiResult<()> {
+        sqlx::query(
+            "UPDATE meal_options SET description = ?, nutritional_notes = ?, option_group = ?
+             WHERE id = ?",
+        )
+        .bind(&option.description)
+        .bind(&option.nutritional_notes)
+        .bind(group)
+        .bind(option_id)
+        .execute(&mut *conn)
+        .await?;
+
+        sqlx::query("DELETE FROM meal_option_tags WHERE meal_option_id = ?")
+            .bind(option_id)
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query("DELETE FROM meal_option_nutrients WHERE meal_option_id = ?")
+            .bind(option_id)
+            .execute(&mut *conn)
+            .await?;
+
+        Self::write_option_details(conn, option_id, option, tag_ids).await
+    }
+
+    /// Insert the tags and nutrient profile of a pack option
+    async fn write_option_details(
+        conn: &mut SqliteConnection,
+        option_id: i64,
+        option: &PackOption,
+        tag_ids: &HashMap<String, i64>,
+    ) -> ApiResult<()> {
+        for tag in &option.tags {
+            sqlx::query(
+                "INSERT OR IGNORE INTO meal_option_tags (meal_option_id, tag_id) VALUES (?, ?)",
+            )
+            .bind(option_id)
+            .bind(tag_ids[tag])
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        if let Some(nutrients) = &option.nutrients {
+            sqlx::query(
+                "INSERT INTO meal_option_nutrients (meal_option_id, kcal, protein_g, carbs_g, fat_g, fiber_g)
+                 VALUES (?, ?, ?, ?, ?, ?)",
+            )
+            .bind(option_id)
+            .bind(nutrients.kcal)
+            .bind(nutrients.protein_g)
+            .bind(nutrients.carbs_g)
+            .bind(nutrients.fat_g)
+            .bind(nutrients.fiber_g)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    fn tag_differences(existing: &Tag, tag: &PackTag, library_tags: &[Tag]) -> Vec<String> {
+        let parent = existing
+            .parent_tag_id
+            .and_then(|id| library_tags.iter().find(|t| t.id == id))
+            .map(|t| t.name.as_str());
+
+        let mut fields = Vec::new();
+        if existing.display_name != tag.display_name {
+            fields.push("display_name".to_string());
+        }
+        if existing.category != tag.category {
+            fields.push("category".to_string());
+        }
+        if existing.weekly_suggestion != tag.weekly_suggestion {
+            fields.push("weekly_suggestion".to_string());
+        }
+        if parent != tag.parent.as_deref() {
+            fields.push("parent".to_string());
+        }
+        fields
+    }
+
+    fn template_differences(library: &LibraryTemplate, template: &PackTemplate) -> Vec<String> {
+        let existing = &library.template;
+        let same_slots = existing.compatible_slots.len() == template.compatible_slots.len()
+            && existing
+                .compatible_slots
+                .iter()
+                .all(|s| template.compatible_slots.contains(s));
+
+        let mut fields = Vec::new();
+        if existing.description != template.description {
+            fields.push("description".to_string());
+        }
+        if !same_slots {
+            fields.push("compatible_slots".to_string());
+        }
+        if existing.location_type != template.location_type {
+            fields.push("location_type".to_string());
+        }
+        if existing.weekly_limit != template.weekly_limit {
+            fields.push("weekly_limit".to_string());
+        }
+        if library.option_groups != template.option_groups {
+            fields.push("option_groups".to_string());
+        }
+        fields
+    }
+
+    fn option_differences(
+        existing: &LibraryOption,
+        option: &PackOption,
+        group: Option<&String>,
+        tag_ids: &HashMap<String, i64>,
+    ) -> Vec<String> {
+        let pack_tags: HashSet<i64> = option.tags.iter().map(|t| tag_ids[t]).collect();
+        let library_tags: HashSet<i64> = existing.tag_ids.iter().copied().collect();
+
+        let mut fields = Vec::new();
+        if existing.option.description != option.description {
+            fields.push("description".to_string());
+        }
+        if existing.option.nutritional_notes != option.nutritional_notes {
+            fields.push("nutritional_notes".to_string());
+        }
+        if existing.nutrients != option.nutrients {
+            fields.push("nutrients".to_string());
+        }
+        if existing.option.option_group.as_ref() != group {
+            fields.push("option_group".to_string());
+        }
+        if library_tags != pack_tags {
+            fields.push("tags".to_string());
+        }
+        fields
+    }
+
+    /// "Name" -> "Name (2)", or the first free number after it
+    fn unique_name(name: &str, taken: &[String]) -> String {
+        (2..)
+            .map(|n| format!("{} ({})", name, n))
+            .find(|candidate| !taken.iter().any(|t| Self::same_name(t, candidate)))
+            .unwrap_or_else(|| name.to_string())
+    }
+
+    /// Tag names only allow lowercase letters and underscores:
+    /// "pasta" -> "pasta_imported", then "pasta_imported_b", "pasta_imported_c", ...
+    fn unique_tag_name(name: &str, taken: &[&str]) -> String {
+        std::iter::once(format!("{}_imported", name))
+            .chain(('b'..='z').map(|c| format!("{}_imported_{}", name, c)))
+            .find(|candidate| !taken.contains(&candidate.as_str()))
+            .unwrap_or_else(|| format!("{}_imported", name))
+    }
+
+    fn same_name(a: &str, b: &str) -> bool {
+        a.trim().to_lowercase() == b.trim().to_lowercase()
+    }
+
+    fn duplicate(kind: &str, name: &str) -> ApiError {
+        ApiError::ValidationError(format!("Duplicate {} '{}' in library pack", kind, name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CreateMealOption, LocationType, SlotType, TagCategory};
+    use crate::repository::AuditRepository;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect(":memory:")
+            .await
+            .expect("Failed to create test pool");
+
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
+
+        pool
+    }
+
+    /// A lunch template with a grouped, tagged option and a nested tag
+    async fn seed_library(pool: &SqlitePool) {
+        let cereals = TagRepository::create(
+            pool,
+            CreateTag {
+                name: "cereals".to_string(),
+                display_name: "Cereals".to_string(),
+                category: TagCategory::Ingredient,
+                weekly_suggestion: None,
+                parent_tag_id: None,
+            },
+        )
+        .await
+        .unwrap();
+        let pasta = TagRepository::create(
+            pool,
+            CreateTag {
+                name: "pasta".to_string(),
+                display_name: "Pasta".to_string(),
+                category: TagCategory::Ingredient,
+                weekly_suggestion: Some(3),
+                parent_tag_id: Some(cereals.id),
+            },
+        )
+        .await
+        .unwrap();
+
+        let template = MealTemplateRepository::create(
+            pool,
+            CreateMealTemplate {
+                name: "Pasta con verdure".to_string(),
+                description: Some("Primo piatto".to_string()),
+                compatible_slots: vec![SlotType::LUNCH, SlotType::DINNER],
+                location_type: LocationType::Any,
+                weekly_limit: Some(4),
+            },
+        )
+        .await
+        .unwrap();
+        MealTemplateRepository::set_option_groups(
+            pool,
+            template.id,
+            vec![OptionGroup {
+                name: "Base".to_string(),
+                min_choices: 1,
+                max_choices: Some(1),
+            }],
+        )
+        .await
+        .unwrap();
+
+        let option = MealOptionRepository::create(
+            pool,
+            CreateMealOption {
+                template_id: template.id,
+                name: "Pasta integrale".to_string(),
+                description: None,
+                nutritional_notes: Some("80 g".to_string()),
+            },
+        )
+        .await
+        .unwrap();
+        MealOptionRepository::set_group(pool, option.id, Some("Base".to_string()))
+            .await
+            .unwrap();
+        MealOptionRepository::add_tags(pool, option.id, vec![pasta.id])
+            .await
+            .unwrap();
+        MealOptionRepository::set_nutrients(
+            pool,
+            option.id,
+            SetNutrientProfile {
+                kcal: 280.0,
+                protein_g: 10.0,
+                carbs_g: 54.0,
+                fat_g: 2.0,
+                fiber_g: 6.0,
+            },
+        )
+        .await
+        .unwrap();
+    }
+
+    fn to_document(pack: &LibraryPack) -> serde_json::Value {
+        serde_json::to_value(pack).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_into_empty_library() {
+        let source = setup_test_pool().await;
+        seed_library(&source).await;
+        let pack = LibraryPackService::export_pack(&source).await.unwrap();
+        assert_eq!(pack.tags.len(), 2);
+        assert_eq!(pack.tags[1].parent.as_deref(), Some("cereals"));
+        assert_eq!(pack.templates[0].options[0].tags, vec!["pasta"]);
+
+        let target = setup_test_pool().await;
+        let result = LibraryPackService::import_pack(
+            &target,
+            to_document(&pack),
+            PackConflictStrategy::Skip,
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.tags_created, 2);
+        assert_eq!(result.templates_created, 1);
+        assert_eq!(result.options_created, 1);
+        assert!(result.conflicts.is_empty());
+
+        let copy = LibraryPackService::export_pack(&target).await.unwrap();
+        assert_eq!(copy.tags, pack.tags);
+        assert_eq!(copy.templates, pack.templates);
+    }
+
+    #[tokio::test]
+    async fn test_reimport_merges_identical_items() {
+        let pool = setup_test_pool().await;
+        seed_library(&pool).await;
+        let pack = LibraryPackService::export_pack(&pool).await.unwrap();
+
+        let result = LibraryPackService::import_pack(
+            &pool,
+            to_document(&pack),
+            PackConflictStrategy::Rename,
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.slots_created, 0);
+        assert_eq!(result.tags_merged, 2);
+        assert_eq!(result.templates_merged, 1);
+        assert_eq!(result.options_merged, 1);
+        assert_eq!(result.tags_created + result.templates_created, 0);
+        assert!(result.conflicts.is_empty());
+        assert_eq!(
+            LibraryPackService::export_pack(&pool)
+                .await
+                .unwrap()
+                .templates,
+            pack.templates
+        );
+    }
+
+    #[tokio::test]
+    async fn test_conflict_strategies() {
+        let source = setup_test_pool().await;
+        seed_library(&source).await;
+        let mut pack = LibraryPackService::export_pack(&source).await.unwrap();
+        pack.tags[1].display_name = "Whole pasta".to_string();
+        pack.templates[0].options[0].nutritional_notes = Some("100 g".to_string());
+
+        for strategy in [
+            PackConflictStrategy::Skip,
+            PackConflictStrategy::Overwrite,
+            PackConflictStrategy::Rename,
+        ] {
+            let pool = setup_test_pool().await;
+            seed_library(&pool).await;
+            let seeded = LibraryPackService::export_pack(&pool).await.unwrap();
+            let result =
+                LibraryPackService::import_pack(&pool, to_document(&pack), strategy, false)
+                    .await
+                    .unwrap();
+
+            // A renamed tag is what the pack's option now refers to
+            let mut option_fields = vec!["nutritional_notes".to_string()];
+            if strategy == PackConflictStrategy::Rename {
+                option_fields.push("tags".to_string());
+            }
+            let kinds: Vec<_> = result
+                .conflicts
+                .iter()
+                .map(|c| (c.kind, c.fields.clone()))
+                .collect();
+            assert_eq!(
+                kinds,
+                vec![
+                    (LibraryItemKind::Tag, vec!["display_name".to_string()]),
+                    (LibraryItemKind::Option, option_fields),
+                ]
+            );
+
+            let library = LibraryPackService::export_pack(&pool).await.unwrap();
+            let options = &library.templates[0].options;
+            match strategy {
+                PackConflictStrategy::Skip => {
+                    assert_eq!(library.tags[1].display_name, "Pasta");
+                    assert_eq!(options.len(), 1);
+                    assert_eq!(options[0].nutritional_notes.as_deref(), Some("80 g"));
+                }
+                PackConflictStrategy::Overwrite => {
+                    assert_eq!(library.tags[1].display_name, "Whole pasta");
+                    assert_eq!(options.len(), 1);
+                    assert_eq!(options[0].nutritional_notes.as_deref(), Some("100 g"));
+                }
+                PackConflictStrategy::Rename => {
+                    assert_eq!(
+                        result.conflicts[0].renamed_to.as_deref(),
+                        Some("pasta_imported")
+                    );
+                    assert_eq!(
+                        result.conflicts[1].renamed_to.as_deref(),
+                        Some("Pasta integrale (2)")
+                    );
+                    let renamed = library
+                        .tags
+                        .iter()
+                        .find(|t| t.name == "pasta_imported")
+                        .unwrap();
+                    assert_eq!(renamed.display_name, "Whole pasta (imported)");
+                    assert_eq!(renamed.parent.as_deref(), Some("cereals"));
+
+                    // The renamed option uses the renamed tag
+                    let option = options
+                        .iter()
+                        .find(|o| o.name == "Pasta integrale (2)")
+                        .unwrap();
+                    assert_eq!(option.tags, vec!["pasta_imported"]);
+                    assert_eq!(option.option_group.as_deref(), Some("Base"));
+                }
+            }
+
+            // Undo puts the library back as it was before the import
+            if strategy != PackConflictStrategy::Skip {
+                let operation = AuditRepository::undo_last(&pool).await.unwrap().unwrap();
+                assert_eq!(operation.command, "import_library_pack");
+                let restored = LibraryPackService::export_pack(&pool).await.unwrap();
+                assert_eq!(restored.tags, seeded.tags);
+                assert_eq!(restored.templates, seeded.templates);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_preview_and_invalid_packs() {
+        let source = setup_test_pool().await;
+        seed_library(&source).await;
+        let pack = LibraryPackService::export_pack(&source).await.unwrap();
+
+        let target = setup_test_pool().await;
+        let result = LibraryPackService::import_pack(
+            &target,
+            to_document(&pack),
+            PackConflictStrategy::Skip,
+            true,
+        )
+        .await
+        .unwrap();
+        assert!(result.preview);
+        assert_eq!(result.templates_created, 1);
+        let library = LibraryPackService::export_pack(&target).await.unwrap();
+        assert!(library.tags.is_empty());
+        assert!(library.templates.is_empty());
+
+        let mut unknown_tag = pack.clone();
+        unknown_tag.templates[0].options[0].tags = vec!["legumes".to_string()];
+        let mut duplicate = pack.clone();
+        duplicate.templates.push(pack.templates[0].clone());
+        let mut future = to_document(&pack);
+        future["format_version"] = serde_json::json!(LIBRARY_PACK_FORMAT_VERSION + 1);
+
+        for document in [to_document(&unknown_tag), to_document(&duplicate), future] {
+            let err = LibraryPackService::import_pack(
+                &target,
+                document,
+                PackConflictStrategy::Skip,
+                false,
+            )
+            .await
+            .unwrap_err();
+            assert!(matches!(err, ApiError::ValidationError(_)), "{:?}", err);
+        }
+    }
+}
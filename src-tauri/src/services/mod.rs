@@ -1,7 +1,27 @@
 // Services module
 // Business logic layer
 
+pub mod adherence_service;
+pub mod export_service;
+pub mod library_pack_service;
+pub mod nutrition_service;
+pub mod plan_import_service;
+pub mod planning_service;
+pub mod suggestion_service;
+pub mod sync_service;
 pub mod validation_service;
+pub mod week_summary_service;
 
 // Re-export for convenient access
+pub use adherence_service::AdherenceService;
+pub use export_service::ExportService;
+pub use library_pack_service::LibraryPackService;
+pub use nutrition_service::NutritionService;
+pub use plan_import_service::PlanImportService;
+pub use planning_service::PlanningService;
+pub use suggestion_service::SuggestionService;
+#[cfg(feature = "sync")]
+pub use sync_service::SyncClient;
+pub use sync_service::{SyncService, SYNC_TOKEN_ENV};
 pub use validation_service::{ValidationError, ValidationService, ValidationWarning, WarningType};
+pub use week_summary_service::WeekSummaryService;
@@ -0,0 +1,375 @@
+// Nutrition Service
+// Aggregates per-serving nutrient profiles into daily and date-range totals
+
+use crate::models::{DailyNutrientTotals, NutrientSummary, NutrientTotals};
+use chrono::NaiveDate;
+use sqlx::{Row, SqlitePool};
+
+pub struct NutritionService;
+
+impl NutritionService {
+    /// Get nutrient totals for a single day
+    /// Days without entries return zeroed totals
+    pub async fn get_daily_totals(
+        pool: &SqlitePool,
+        date: NaiveDate,
+        completed_only: bool,
+    ) -> sqlx::Result<DailyNutrientTotals> {
+        let days = Self::query_daily_totals(pool, date, date, completed_only).await?;
+
+        Ok(days
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| DailyNutrientTotals {
+                date,
+                totals: NutrientTotals::default(),
+                entry_count: 0,
+                entries_without_profile: 0,
+                off_plan_entries: 0,
+            }))
+    }
+
+    /// Get nutrient totals for a date range (inclusive), with a per-day breakdown
+    /// Each entry contributes the profile of every selected option multiplied by its servings;
+    /// off-plan entries contribute their estimated profile and skipped entries nothing
+    pub async fn get_totals_for_range(
+        pool: &SqlitePool,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        completed_only: bool,
+    ) -> sqlx::Result<NutrientSummary> {
+        if start_date > end_date {
+            return Err(sqlx::Error::Protocol(
+                "Start date must not be after end date".to_string(),
+            ));
+        }
+
+        let days = Self::query_daily_totals(pool, start_date, end_date, completed_only).await?;
+
+        let mut totals = NutrientTotals::default();
+        let mut entry_count = 0;
+        let mut entries_without_profile = 0;
+        let mut off_plan_entries = 0;
+        for day in &days {
+            totals.add(&day.totals);
+            entry_count += day.entry_count;
+            entries_without_profile += day.entries_without_profile;
+            off_plan_entries += day.off_plan_entries;
+        }
+
+        Ok(NutrientSummary {
+            start_date,
+            end_date,
+            days,
+            totals,
+            entry_count,
+            entries_without_profile,
+            off_plan_entries,
+        })
+    }
+
+    /// Sum nutrients per day in a single query
+    async fn query_daily_totals(
+        pool: &SqlitePool,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        completed_only: bool,
+    ) -> sqlx::Result<Vec<DailyNutrientTotals>> {
+        // One row per selected option, plus one per off-plan entry with its estimate
+        let rows = sqlx::query(
+            "WITH contributions(entry_id, date, has_profile, off_plan,
+                                kcal, protein_g, carbs_g, fat_g, fiber_g) AS (
+                 SELECT me.id, me.date, n.meal_option_id IS NOT NULL, 0,
+                        n.kcal * eo.servings, n.protein_g * eo.servings, n.carbs_g * eo.servings,
+                        n.fat_g * eo.servings, n.fiber_g * eo.servings
+                 FROM meal_entries me
+                 JOIN meal_entry_options eo ON eo.meal_entry_id = me.id
+                 LEFT JOIN meal_option_nutrients n ON n.meal_option_id = eo.meal_option_id
+                 WHERE me.date BETWEEN ?1 AND ?2
+                   AND me.status != 'skipped'
+                   AND (?3 = 0 OR me.status = 'eaten')
+                 UNION ALL
+                 SELECT me.id, me.date, n.meal_entry_id IS NOT NULL, 1,
+                        n.kcal * me.servings, n.protein_g * me.servings, n.carbs_g * me.servings,
+                        n.fat_g * me.servings, n.fiber_g * me.servings
+                 FROM meal_entries me
+                 LEFT JOIN meal_entry_nutrients n ON n.meal_entry_id = me.id
+                 WHERE me.meal_option_id IS NULL
+                   AND me.date BETWEEN ?1 AND ?2
+                   AND me.status != 'skipped'
+                   AND (?3 = 0 OR me.status = 'eaten')
+             )
+             SELECT date,
+                    COUNT(DISTINCT entry_id) AS entry_count,
+                    COUNT(DISTINCT CASE WHEN NOT has_profile THEN entry_id END) AS entries_without_profile,
+                    COUNT(DISTINCT CASE WHEN off_plan THEN entry_id END) AS off_plan_entries,
+                    COALESCE(SUM(kcal), 0.0) AS kcal,
+                    COALESCE(SUM(protein_g), 0.0) AS protein_g,
+                    COALESCE(SUM(carbs_g), 0.0) AS carbs_g,
+                    COALESCE(SUM(fat_g), 0.0) AS fat_g,
+                    COALESCE(SUM(fiber_g), 0.0) AS fiber_g
+             FROM contributions
+             GROUP BY date
+             ORDER BY date",
+        )
+        .bind(start_date)
+        .bind(end_date)
+        .bind(completed_only)
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(DailyNutrientTotals {
+                    date: row.try_get("date")?,
+                    totals: NutrientTotals {
+                        kcal: row.try_get("kcal")?,
+                        protein_g: row.try_get("protein_g")?,
+                        carbs_g: row.try_get("carbs_g")?,
+                        fat_g: row.try_get("fat_g")?,
+                        fiber_g: row.try_get("fiber_g")?,
+                    },
+                    entry_count: row.try_get("entry_count")?,
+                    entries_without_profile: row.try_get("entries_without_profile")?,
+                    off_plan_entries: row.try_get("off_plan_entries")?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        CreateMealEntry, CreateMealOption, CreateMealTemplate, CreateOffPlanEntry, EntryStatus,
+        LocationType, SetNutrientProfile, SlotType,
+    };
+    use crate::repository::{MealEntryRepository, MealOptionRepository, MealTemplateRepository};
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect(":memory:")
+            .await
+            .expect("Failed to create test pool");
+
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
+
+        pool
+    }
+
+    async fn create_test_option(pool: &SqlitePool, name: &str) -> i64 {
+        let template = CreateMealTemplate {
+            name: format!("{} Template", name),
+            description: None,
+            location_type: LocationType::Home,
+            compatible_slots: vec![SlotType::BREAKFAST, SlotType::LUNCH],
+            weekly_limit: None,
+        };
+        let template_id = MealTemplateRepository::create(pool, template)
+            .await
+            .expect("Failed to create template")
+            .id;
+
+        let option = CreateMealOption {
+            template_id,
+            name: name.to_string(),
+            description: None,
+            nutritional_notes: None,
+        };
+        MealOptionRepository::create(pool, option)
+            .await
+            .expect("Failed to create option")
+            .id
+    }
+
+    async fn create_entry(
+        pool: &SqlitePool,
+        option_id: i64,
+        date: NaiveDate,
+        slot: SlotType,
+        servings: f64,
+        eaten: bool,
+    ) {
+        let entry = CreateMealEntry {
+            meal_option_id: option_id,
+            date,
+            slot_type: slot,
+            location: LocationType::Home,
+            servings: Some(servings),
+            notes: None,
+            status: Some(if eaten {
+                EntryStatus::Eaten
+            } else {
+                EntryStatus::Planned
+            }),
+            extra_options: vec![],
+        };
+        MealEntryRepository::create(pool, entry)
+            .await
+            .expect("Failed to create entry");
+    }
+
+    fn profile(kcal: f64, protein_g: f64) -> SetNutrientProfile {
+        SetNutrientProfile {
+            kcal,
+            protein_g,
+            carbs_g: 10.0,
+            fat_g: 5.0,
+            fiber_g: 1.0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_daily_totals_apply_servings() {
+        let pool = setup_test_pool().await;
+        let yogurt = create_test_option(&pool, "Yogurt").await;
+        let pasta = create_test_option(&pool, "Pasta").await;
+        MealOptionRepository::set_nutrients(&pool, yogurt, profile(100.0, 8.0))
+            .await
+            .unwrap();
+        MealOptionRepository::set_nutrients(&pool, pasta, profile(350.0, 12.0))
+            .await
+            .unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2024, 11, 4).unwrap();
+        create_entry(&pool, yogurt, date, SlotType::BREAKFAST, 1.5, true).await;
+        create_entry(&pool, pasta, date, SlotType::LUNCH, 1.0, true).await;
+
+        let day = NutritionService::get_daily_totals(&pool, date, false)
+            .await
+            .unwrap();
+
+        assert_eq!(day.entry_count, 2);
+        assert_eq!(day.entries_without_profile, 0);
+        assert_eq!(day.totals.kcal, 500.0);
+        assert_eq!(day.totals.protein_g, 24.0);
+        assert_eq!(day.totals.carbs_g, 25.0);
+    }
+
+    #[tokio::test]
+    async fn test_daily_totals_include_off_plan_estimates() {
+        let pool = setup_test_pool().await;
+        let yogurt = create_test_option(&pool, "Yogurt").await;
+        MealOptionRepository::set_nutrients(&pool, yogurt, profile(100.0, 8.0))
+            .await
+            .unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2024, 11, 4).unwrap();
+        create_entry(&pool, yogurt, date, SlotType::BREAKFAST, 1.0, true).await;
+        for (slot, estimate) in [
+            (SlotType::LUNCH, Some(profile(800.0, 40.0))),
+            (SlotType::DINNER, None),
+        ] {
+            MealEntryRepository::create_off_plan(
+                &pool,
+                CreateOffPlanEntry {
+                    date,
+                    slot_type: slot,
+                    location: LocationType::Restaurant,
+                    description: "Pranzo di lavoro".to_string(),
+                    servings: Some(0.5),
+                    notes: None,
+                    status: None,
+                    tag_ids: vec![],
+                    estimated_nutrients: estimate,
+                },
+            )
+            .await
+            .unwrap();
+        }
+
+        let day = NutritionService::get_daily_totals(&pool, date, true)
+            .await
+            .unwrap();
+
+        assert_eq!(day.entry_count, 3);
+        assert_eq!(day.off_plan_entries, 2);
+        assert_eq!(day.entries_without_profile, 1);
+        assert_eq!(day.totals.kcal, 500.0);
+        assert_eq!(day.totals.protein_g, 28.0);
+    }
+
+    #[tokio::test]
+    async fn test_daily_totals_without_entries() {
+        let pool = setup_test_pool().await;
+        let date = NaiveDate::from_ymd_opt(2024, 11, 4).unwrap();
+
+        let day = NutritionService::get_daily_totals(&pool, date, false)
+            .await
+            .unwrap();
+
+        assert_eq!(day.date, date);
+        assert_eq!(day.entry_count, 0);
+        assert_eq!(day.totals, NutrientTotals::default());
+    }
+
+    #[tokio::test]
+    async fn test_range_totals_and_missing_profiles() {
+        let pool = setup_test_pool().await;
+        let yogurt = create_test_option(&pool, "Yogurt").await;
+        let unknown = create_test_option(&pool, "Unknown").await;
+        MealOptionRepository::set_nutrients(&pool, yogurt, profile(100.0, 8.0))
+            .await
+            .unwrap();
+
+        let monday = NaiveDate::from_ymd_opt(2024, 11, 4).unwrap();
+        let tuesday = NaiveDate::from_ymd_opt(2024, 11, 5).unwrap();
+        let next_monday = NaiveDate::from_ymd_opt(2024, 11, 11).unwrap();
+        create_entry(&pool, yogurt, monday, SlotType::BREAKFAST, 1.0, true).await;
+        create_entry(&pool, unknown, monday, SlotType::LUNCH, 1.0, true).await;
+        create_entry(&pool, yogurt, tuesday, SlotType::BREAKFAST, 2.0, true).await;
+        create_entry(&pool, yogurt, next_monday, SlotType::BREAKFAST, 1.0, true).await;
+
+        let sunday = NaiveDate::from_ymd_opt(2024, 11, 10).unwrap();
+        let summary = NutritionService::get_totals_for_range(&pool, monday, sunday, false)
+            .await
+            .unwrap();
+
+        assert_eq!(summary.days.len(), 2);
+        assert_eq!(summary.entry_count, 3);
+        assert_eq!(summary.entries_without_profile, 1);
+        assert_eq!(summary.totals.kcal, 300.0);
+        assert_eq!(summary.days[0].totals.kcal, 100.0);
+        assert_eq!(summary.days[1].totals.kcal, 200.0);
+    }
+
+    #[tokio::test]
+    async fn test_range_totals_completed_only() {
+        let pool = setup_test_pool().await;
+        let yogurt = create_test_option(&pool, "Yogurt").await;
+        MealOptionRepository::set_nutrients(&pool, yogurt, profile(100.0, 8.0))
+            .await
+            .unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2024, 11, 4).unwrap();
+        create_entry(&pool, yogurt, date, SlotType::BREAKFAST, 1.0, true).await;
+        create_entry(&pool, yogurt, date, SlotType::LUNCH, 1.0, false).await;
+
+        let planned_and_eaten = NutritionService::get_totals_for_range(&pool, date, date, false)
+            .await
+            .unwrap();
+        assert_eq!(planned_and_eaten.totals.kcal, 200.0);
+
+        let eaten = NutritionService::get_totals_for_range(&pool, date, date, true)
+            .await
+            .unwrap();
+        assert_eq!(eaten.totals.kcal, 100.0);
+        assert_eq!(eaten.entry_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_range_totals_invalid_range() {
+        let pool = setup_test_pool().await;
+        let start = NaiveDate::from_ymd_opt(2024, 11, 10).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 11, 4).unwrap();
+
+        let result = NutritionService::get_totals_for_range(&pool, start, end, false).await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,873 @@
+// Plan Import Service
+// Parses a dietitian's plain-text meal plan into templates and options, compares it
+// with the library and imports it in one transaction
+
+use crate::error::{ApiError, ApiResult};
+use crate::models::{
+    CreateMealTemplate, LocationType, MealSlot, OptionGroup, ParsedOption, ParsedPlan,
+    ParsedTemplate, PlanImport, PlanTemplatePreview, PlanTemplateStatus, SlotType, SyncEntity,
+};
+use crate::repository::{
+    AuditScope, MealOptionRepository, MealSlotRepository, MealTemplateRepository,
+};
+use sqlx::{SqliteConnection, SqlitePool};
+
+/// Italian headings of the default slots, besides their names and display names
+const SLOT_ALIASES: &[(&str, SlotType)] = &[
+    ("colazione", SlotType::BREAKFAST),
+    ("prima colazione", SlotType::BREAKFAST),
+    ("spuntino", SlotType::MORNING_SNACK),
+    ("spuntino mattutino", SlotType::MORNING_SNACK),
+    ("spuntino di metà mattina", SlotType::MORNING_SNACK),
+    ("metà mattina", SlotType::MORNING_SNACK),
+    ("pranzo", SlotType::LUNCH),
+    ("merenda", SlotType::AFTERNOON_SNACK),
+    ("spuntino pomeridiano", SlotType::AFTERNOON_SNACK),
+    ("spuntino di metà pomeriggio", SlotType::AFTERNOON_SNACK),
+    ("metà pomeriggio", SlotType::AFTERNOON_SNACK),
+    ("cena", SlotType::DINNER),
+];
+
+/// Library state a changed template is brought to on import
+struct PendingChange {
+    compatible_slots: Vec<SlotType>,
+    option_groups: Option<Vec<OptionGroup>>, // None = leave the groups alone
+    regrouped_options: Vec<(i64, Option<String>)>, // Existing options moved to another group
+}
+
+pub struct PlanImportService;
+
+impl PlanImportService {
+    /// Parse a plan's text into templates and options
+    /// The text is split into blocks by slot headings: a line holding only a slot's
+    /// name, display name or Italian heading ("Colazione", "Pranzo", ...), optionally
+    /// followed by ':'. Within a block, templates are separated by "Oppure".
+    /// A template reads "Name: a + b o c + d e/o e": every '+' part is an option group,
+    /// "o" separates alternatives (pick one) and "e/o" options that can be combined
+    /// (pick at least one). Without ':' the whole text also names the template.
+    /// Text in parentheses after an option becomes its nutritional notes.
+    pub fn parse(text: &str, slots: &[MealSlot]) -> ParsedPlan {
+        let mut plan = ParsedPlan::default();
+        let mut slot: Option<SlotType> = None;
+        let mut block: Vec<&str> = Vec::new();
+
+        for (number, raw) in text.lines().enumerate() {
+            let line = raw.trim().trim_start_matches(['-', '*', '•']).trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(heading) = Self::slot_heading(line, slots) {
+                Self::flush(&mut plan, slot.as_ref(), &mut block);
+                slot = Some(heading);
+                continue;
+            }
+
+            if slot.is_none() {
+                plan.warnings.push(format!(
+                    "Line {}: '{}' is not under a slot heading",
+                    number + 1,
+                    line
+                ));
+                continue;
+            }
+
+            match Self::strip_oppure(line) {
+                Some(rest) => {
+                    Self::flush(&mut plan, slot.as_ref(), &mut block);
+                    if !rest.is_empty() {
+                        block.push(rest);
+                    }
+                }
+                None => block.push(line),
+            }
+        }
+        Self::flush(&mut plan, slot.as_ref(), &mut block);
+
+        plan
+    }
+
+    /// Parse a plan's text and compare it with the library
+    /// Templates are matched by name (case-insensitive) among active templates. Matched
+    /// templates only gain slots, options and option groups; nothing is removed.
+    /// New templates can be prepared anywhere (location "any") and have no weekly limit.
+    /// Unless `preview` is set, the changes are written in one transaction and undone as one.
+    pub async fn import_text(
+        pool: &SqlitePool,
+        text: &str,
+        preview: bool,
+    ) -> ApiResult<PlanImport> {
+        let slots = MealSlotRepository::get_all(pool).await?;
+        let plan = Self::parse(text, &slots);
+
+        if plan.templates.is_empty() {
+            return Err(ApiError::ValidationError(
+                "No meal templates found in the plan text".to_string(),
+            ));
+        }
+
+        for parsed in &plan.templates {
+            parsed
+                .template
+                .validate()
+                .map_err(ApiError::ValidationError)?;
+            for group in &parsed.option_groups {
+                group.validate().map_err(ApiError::ValidationError)?;
+            }
+        }
+
+        let library = MealTemplateRepository::get_all(pool).await?;
+        let mut templates = Vec::with_capacity(plan.templates.len());
+        let mut changes = Vec::with_capacity(plan.templates.len());
+
+        for parsed in plan.templates {
+            let Some(existing) = library
+                .iter()
+                .find(|t| Self::same_name(&t.name, &parsed.template.name))
+            else {
+                templates.push(PlanTemplatePreview {
+                    status: PlanTemplateStatus::New,
+                    template_id: None,
+                    added_slots: parsed.template.compatible_slots.clone(),
+                    added_options: parsed.options.iter().map(|o| o.name.clone()).collect(),
+                    unlisted_options: Vec::new(),
+                    option_groups_changed: !parsed.option_groups.is_empty(),
+                    parsed,
+                });
+                changes.push(None);
+                continue;
+            };
+
+            let options = MealOptionRepository::get_by_template_id(pool, existing.id).await?;
+            let groups = MealTemplateRepository::get_option_groups(pool, existing.id).await?;
+
+            let added_slots: Vec<SlotType> = parsed
+                .template
+                .compatible_slots
+                .iter()
+                .filter(|s| !existing.compatible_slots.contains(s))
+                .cloned()
+                .collect();
+            let added_options: Vec<String> = parsed
+                .options
+                .iter()
+                .filter(|p| !options.iter().any(|o| Self::same_name(&o.name, &p.name)))
+                .map(|p| p.name.clone())
+                .collect();
+            let unlisted_options: Vec<String> = options
+                .iter()
+                .filter(|o| {
+                    !parsed
+                        .options
+                        .iter()
+                        .any(|p| Self::same_name(&o.name, &p.name))
+                })
+                .map(|o| o.name.clone())
+                .collect();
+
+            // Groups are only touched when the plan declares some
+            let mut option_groups = None;
+            let mut regrouped_options = Vec::new();
+            if !parsed.option_groups.is_empty() {
+                let merged = Self::merge_groups(&groups, &parsed.option_groups);
+                if merged != groups {
+                    option_groups = Some(merged);
+                }
+                for option in &options {
+                    let listed = parsed
+                        .options
+                        .iter()
+                        .find(|p| Self::same_name(&option.name, &p.name));
+                    if let Some(listed) = listed {
+                        if listed.option_group != option.option_group {
+                            regrouped_options.push((option.id, listed.option_group.clone()));
+                        }
+                    }
+                }
+            }
+
+            let option_groups_changed = option_groups.is_some() || !regrouped_options.is_empty();
+            let status =
+                if added_slots.is_empty() && added_options.is_empty() && !option_groups_changed {
+                    PlanTemplateStatus::Unchanged
+                } else {
+                    PlanTemplateStatus::Changed
+                };
+
+            let mut compatible_slots = existing.compatible_slots.clone();
+            compatible_slots.extend(added_slots.iter().cloned());
+            changes.push(Some(PendingChange {
+                compatible_slots,
+                option_groups,
+                regrouped_options,
+            }));
+
+            templates.push(PlanTemplatePreview {
+                status,
+                template_id: Some(existing.id),
+                parsed,
+                added_slots,
+                added_options,
+                unlisted_options,
+                option_groups_changed,
+            });
+        }
+
+        if !preview {
+            let mut tx = pool.begin().await?;
+
+            // Matched templates and their options may change; new ones are picked up too
+            let mut audit = AuditScope::new("import_plan_text");
+            for item in &templates {
+                if let (PlanTemplateStatus::Changed, Some(id)) = (item.status, item.template_id) {
+                    audit.track(&mut tx, SyncEntity::Template, [id]).await?;
+                    audit
+                        .track_query(
+                            &mut tx,
+                            SyncEntity::Option,
+                            "SELECT id FROM meal_options WHERE template_id = ?1",
+                            id,
+                        )
+                        .await?;
+                }
+            }
+            audit.track_new(&mut tx, SyncEntity::Template).await?;
+            audit.track_new(&mut tx, SyncEntity::Option).await?;
+
+            for (item, change) in templates.iter_mut().zip(&changes) {
+                match (item.status, change) {
+                    (PlanTemplateStatus::New, _) => {
+                        item.template_id =
+                            Some(Self::create_template(&mut tx, &item.parsed).await?);
+                    }
+                    (PlanTemplateStatus::Changed, Some(change)) => {
+                        if let Some(id) = item.template_id {
+                            Self::apply_change(&mut tx, id, item, change).await?;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            audit.record(&mut tx).await?;
+            tx.commit().await?;
+        }
+
+        Ok(PlanImport {
+            preview,
+            templates,
+            warnings: plan.warnings,
+        })
+    }
+
+    /// Insert a parsed template with its groups and options
+    async fn create_template(
+        conn: &mut SqliteConnection,
+        parsed: &ParsedTemplate,
+    ) -> ApiResult<i64> {
+        let template = &parsed.template;
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO meal_templates (name, description, location_type, weekly_limit)
+             VALUES (?, ?, ?, ?)
+             RETURNING id",
+        )
+        .bind(&template.name)
+        .bind(&template.description)
+        .bind(template.location_type.to_db_string())
+        .bind(template.weekly_limit)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        MealTemplateRepository::set_slots(conn, id, &template.compatible_slots).await?;
+        MealTemplateRepository::replace_option_groups(conn, id, &parsed.option_groups).await?;
+        for option in &parsed.options {
+            Self::insert_option(conn, id, option).await?;
+        }
+
+        Ok(id)
+    }
+
+    /// Add the plan's slots, groups and options to a matched template
+    async fn apply_change(
+        conn: &mut SqliteConnection,
+        template_id: i64,
+        item: &PlanTemplatePreview,
+        change: &PendingChange,
+    ) -> ApiResult<()> {
+        if !item.added_slots.is_empty() {
+            MealTemplateRepository::set_slots(conn, template_id, &change.compatible_slots).await?;
+        }
+
+        if let Some(groups) = &change.option_groups {
+            MealTemplateRepository::replace_option_groups(conn, template_id, groups).await?;
+        }
+
+        for (option_id, option_group) in &change.regrouped_options {
+            sqlx::query("UPDATE meal_options SET option_group = ? WHERE id = ?")
+                .bind(option_group)
+                .bind(option_id)
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        for option in &item.parsed.options {
+            if item
+                .added_options
+                .iter()
+                .any(|name| Self::same_name(name, &option.name))
+            {
+                Self::insert_option(conn, template_id, option).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn insert_option(
+        conn: &mut SqliteConnection,
+        template_id: i64,
+        option: &ParsedOption,
+    ) -> ApiResult<()> {
+        let create = option.to_create(template_id);
+        create.validate().map_err(ApiError::ValidationError)?;
+
+        sqlx::query(
+            "INSERT INTO meal_options (template_id, name, description, nutritional_notes, option_group)
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(create.template_id)
+        .bind(&create.name)
+        .bind(&create.description)
+        .bind(&create.nutritional_notes)
+        .bind(&option.option_group)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Existing groups with the plan's definitions applied, then the plan's new groups
+    fn merge_groups(existing: &[OptionGroup], parsed: &[OptionGroup]) -> Vec<OptionGroup> {
+        let mut merged: Vec<OptionGroup> = existing
+            .iter()
+            .map(|group| {
+                parsed
+                    .iter()
+                    .find(|p| p.name == group.name)
+                    .unwrap_or(group)
+                    .clone()
+            })
+            .collect();
+        for group in parsed {
+            if !merged.iter().any(|g| g.name == group.name) {
+                merged.push(group.clone());
+            }
+        }
+        merged
+    }
+
+    /// Parse the pending template text of a slot block and empty the block
+    fn flush(plan: &mut ParsedPlan, slot: Option<&SlotType>, block: &mut Vec<&str>) {
+        if block.is_empty() {
+            return;
+        }
+        let text = block.join(" ");
+        block.clear();
+
+        let Some(slot) = slot else {
+            return;
+        };
+        let Some(parsed) = Self::parse_template(&text, slot) else {
+            plan.warnings
+                .push(format!("'{}' has no options and was skipped", text));
+            return;
+        };
+
+        // The same template listed under another slot adds that slot
+        let existing = plan
+            .templates
+            .iter_mut()
+            .find(|t| Self::same_name(&t.template.name, &parsed.template.name));
+        let Some(existing) = existing else {
+            plan.templates.push(parsed);
+            return;
+        };
+
+        if !existing.template.compatible_slots.contains(slot) {
+            existing.template.compatible_slots.push(slot.clone());
+        }
+        for group in parsed.option_groups {
+            if !existing.option_groups.iter().any(|g| g.name == group.name) {
+                existing.option_groups.push(group);
+            }
+        }
+        for option in parsed.options {
+            if !existing
+                .options
+                .iter()
+                .any(|o| Self::same_name(&o.name, &option.name))
+            {
+                existing.options.push(option);
+            }
+        }
+    }
+
+    /// Parse "Name: a + b o c" into a template for one slot
+    fn parse_template(text: &str, slot: &SlotType) -> Option<ParsedTemplate> {
+        let text = text.trim();
+        let (name, body) = match text.split_once(':') {
+            Some((name, body)) if !name.trim().is_empty() && !body.trim().is_empty() => {
+                (name.trim(), body.trim())
+            }
+            _ => (
+                text.trim_end_matches(':').trim(),
+                text.trim_end_matches(':'),
+            ),
+        };
+
+        let components: Vec<(String, Vec<String>, bool)> = body
+            .split('+')
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .map(|c| {
+                let (alternatives, and_or) = Self::split_alternatives(c);
+                (c.to_string(), alternatives, and_or)
+            })
+            .collect();
+
+        // A single "o" list needs no groups: one option is picked
+        let grouped = components.len() > 1 || components.iter().any(|(_, _, and_or)| *and_or);
+
+        let mut option_groups: Vec<OptionGroup> = Vec::new();
+        let mut options: Vec<ParsedOption> = Vec::new();
+        for (component, alternatives, and_or) in components {
+            let group_name = grouped.then(|| {
+                let mut group_name = component.clone();
+                let mut n = 2;
+                while option_groups.iter().any(|g| g.name == group_name) {
+                    group_name = format!("{} ({})", component, n);
+                    n += 1;
+                }
+                option_groups.push(OptionGroup {
+                    name: group_name.clone(),
+                    min_choices: 1,
+                    max_choices: if and_or { None } else { Some(1) },
+                });
+                group_name
+            });
+
+            for alternative in alternatives {
+                let mut option = Self::parse_option(&alternative);
+                if options
+                    .iter()
+                    .any(|o| Self::same_name(&o.name, &option.name))
+                {
+                    continue;
+                }
+                option.option_group = group_name.clone();
+                options.push(option);
+            }
+        }
+
+        if options.is_empty() {
+            return None;
+        }
+
+        Some(ParsedTemplate {
+            template: CreateMealTemplate {
+                name: name.to_string(),
+                description: None,
+                compatible_slots: vec![slot.clone()],
+                location_type: LocationType::Any,
+                weekly_limit: None,
+            },
+            option_groups,
+            options,
+        })
+    }
+
+    /// Split one '+' part on "o" and "e/o" (and on commas listing alternatives)
+    /// Returns the alternatives and whether they can be combined ("e/o")
+    fn split_alternatives(component: &str) -> (Vec<String>, bool) {
+        let words: Vec<&str> = component.split_whitespace().collect();
+        let is_separator = |w: &str| w.eq_ignore_ascii_case("o") || w.eq_ignore_ascii_case("e/o");
+        let listed = words.iter().any(|w| is_separator(w));
+        let and_or = words.iter().any(|w| w.eq_ignore_ascii_case("e/o"));
+
+        let mut alternatives = Vec::new();
+        let mut current: Vec<&str> = Vec::new();
+        let mut depth = 0i32; // Separators inside parentheses belong to the notes
+        for word in words {
+            if depth == 0 && is_separator(word) {
+                Self::push_alternative(&mut alternatives, &mut current);
+                continue;
+            }
+
+            depth += word.matches('(').count() as i32 - word.matches(')').count() as i32;
+            if depth == 0 && listed && word.ends_with(',') {
+                current.push(word.trim_end_matches(','));
+                Self::push_alternative(&mut alternatives, &mut current);
+            } else {
+                current.push(word);
+            }
+        }
+        Self::push_alternative(&mut alternatives, &mut current);
+
+        (alternatives, and_or)
+    }
+
+    fn push_alternative(alternatives: &mut Vec<String>, current: &mut Vec<&str>) {
+        if !current.is_empty() {
+            alternatives.push(current.join(" "));
+            current.clear();
+        }
+    }
+
+    /// "ricotta (100 g)" -> option "ricotta" with notes "100 g"
+    fn parse_option(text: &str) -> ParsedOption {
+        let text = text.trim();
+        if let Some(open) = text.rfind('(') {
+            if text.ends_with(')') && open > 0 {
+                let name = text[..open].trim();
+                let notes = text[open + 1..text.len() - 1].trim();
+                if !name.is_empty() {
+                    return ParsedOption {
+                        name: name.to_string(),
+                        nutritional_notes: (!notes.is_empty()).then(|| notes.to_string()),
+                        option_group: None,
+                    };
+                }
+            }
+        }
+
+        ParsedOption {
+            name: text.to_string(),
+            nutritional_notes: None,
+            option_group: None,
+        }
+    }
+
+    /// The slot a heading line opens, if it is one
+    fn slot_heading(line: &str, slots: &[MealSlot]) -> Option<SlotType> {
+        let heading = Self::normalize(line.trim_start_matches('#').trim_end_matches(':'));
+
+        let by_name = slots.iter().find(|s| {
+            Self::normalize(s.name.to_db_string()) == heading
+                || Self::normalize(&s.display_name) == heading
+        });
+        if let Some(slot) = by_name {
+            return Some(slot.name.clone());
+        }
+
+        SLOT_ALIASES
+            .iter()
+            .find(|(alias, name)| *alias == heading && slots.iter().any(|s| s.name == *name))
+            .map(|(_, name)| name.clone())
+    }
+
+    /// Text after a leading "Oppure", if the line starts a new template
+    fn strip_oppure(line: &str) -> Option<&str> {
+        let prefix = line.get(..6)?;
+        let rest = &line[6..];
+        if !prefix.eq_ignore_ascii_case("oppure") || rest.starts_with(char::is_alphanumeric) {
+            return None;
+        }
+        Some(rest.trim_start_matches([':', ',', '.', ' ']).trim())
+    }
+
+    fn normalize(text: &str) -> String {
+        text.to_lowercase()
+            .replace('_', " ")
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn same_name(a: &str, b: &str) -> bool {
+        a.trim().to_lowercase() == b.trim().to_lowercase()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CreateMealOption, MealTemplate};
+    use crate::repository::AuditRepository;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    const PLAN: &str = "\
+COLAZIONE
+Pane con marmellata e formaggio spalmabile: pane integrale (60 g) + marmellata o miele + philadelphia, ricotta o crema spalmabile 100% frutta secca
+Oppure
+Yogurt greco e/o kefir
+
+Spuntino:
+Frutta fresca o frutta secca (30 g)
+Oppure Yogurt greco e/o kefir
+
+PRANZO
+Pasta con verdure: pasta integrale (80 g) + zucchine e/o melanzane e/o peperoni
+";
+
+    async fn setup_test_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect(":memory:")
+            .await
+            .expect("Failed to create test pool");
+
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
+
+        pool
+    }
+
+    fn option_names(template: &ParsedTemplate) -> Vec<&str> {
+        template.options.iter().map(|o| o.name.as_str()).collect()
+    }
+
+    async fn find_template(pool: &SqlitePool, name: &str) -> MealTemplate {
+        MealTemplateRepository::get_all(pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|t| t.name == name)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_parse_plan_text() {
+        let pool = setup_test_pool().await;
+        let slots = MealSlotRepository::get_all(&pool).await.unwrap();
+
+        let plan = PlanImportService::parse(PLAN, &slots);
+        assert!(plan.warnings.is_empty(), "{:?}", plan.warnings);
+        assert_eq!(plan.templates.len(), 4);
+
+        // "+" parts become groups; "o" picks one, "e/o" one or more
+        let bread = &plan.templates[0];
+        assert_eq!(
+            bread.template.name,
+            "Pane con marmellata e formaggio spalmabile"
+        );
+        assert_eq!(bread.template.compatible_slots, vec![SlotType::BREAKFAST]);
+        assert_eq!(bread.template.location_type, LocationType::Any);
+        assert_eq!(
+            option_names(bread),
+            vec![
+                "pane integrale",
+                "marmellata",
+                "miele",
+                "philadelphia",
+                "ricotta",
+                "crema spalmabile 100% frutta secca"
+            ]
+        );
+        assert_eq!(bread.options[0].nutritional_notes.as_deref(), Some("60 g"));
+        assert_eq!(bread.option_groups.len(), 3);
+        assert!(bread
+            .option_groups
+            .iter()
+            .all(|g| g.min_choices == 1 && g.max_choices == Some(1)));
+        assert_eq!(
+            bread.options[2].option_group.as_deref(),
+            Some("marmellata o miele")
+        );
+
+        // Listed under two slots: one template compatible with both
+        let yogurt = &plan.templates[1];
+        assert_eq!(yogurt.template.name, "Yogurt greco e/o kefir");
+        assert_eq!(
+            yogurt.template.compatible_slots,
+            vec![SlotType::BREAKFAST, SlotType::MORNING_SNACK]
+        );
+        assert_eq!(option_names(yogurt), vec!["Yogurt greco", "kefir"]);
+        assert_eq!(yogurt.option_groups[0].max_choices, None);
+
+        // A plain "o" list needs no groups; parentheses are not split
+        let fruit = &plan.templates[2];
+        assert_eq!(option_names(fruit), vec!["Frutta fresca", "frutta secca"]);
+        assert_eq!(fruit.options[1].nutritional_notes.as_deref(), Some("30 g"));
+        assert!(fruit.option_groups.is_empty());
+        assert!(fruit.options.iter().all(|o| o.option_group.is_none()));
+
+        let pasta = &plan.templates[3];
+        assert_eq!(pasta.template.compatible_slots, vec![SlotType::LUNCH]);
+        assert_eq!(pasta.options.len(), 4);
+        assert_eq!(pasta.option_groups[1].max_choices, None);
+    }
+
+    #[tokio::test]
+    async fn test_parse_reports_skipped_lines() {
+        let pool = setup_test_pool().await;
+        let slots = MealSlotRepository::get_all(&pool).await.unwrap();
+
+        let plan = PlanImportService::parse(
+            "Piano alimentare settimanale\nCena\nPesce o carne bianca\noppure\n",
+            &slots,
+        );
+        assert_eq!(plan.warnings.len(), 1);
+        assert!(plan.warnings[0].contains("Line 1"));
+        assert_eq!(plan.templates.len(), 1);
+        assert_eq!(
+            plan.templates[0].template.compatible_slots,
+            vec![SlotType::DINNER]
+        );
+
+        // "Oppurea" is not a separator
+        assert!(PlanImportService::strip_oppure("Oppure: pesce").is_some());
+        assert!(PlanImportService::strip_oppure("Oppurea").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_import_preview_writes_nothing() {
+        let pool = setup_test_pool().await;
+
+        let import = PlanImportService::import_text(&pool, PLAN, true)
+            .await
+            .unwrap();
+        assert!(import.preview);
+        assert_eq!(import.templates.len(), 4);
+        assert!(import
+            .templates
+            .iter()
+            .all(|t| t.status == PlanTemplateStatus::New && t.template_id.is_none()));
+        assert!(MealTemplateRepository::get_all(&pool)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_import_creates_and_merges_by_name() {
+        let pool = setup_test_pool().await;
+
+        // The library already has the yogurt template, for breakfast only, with one option
+        let yogurt = MealTemplateRepository::create(
+            &pool,
+            CreateMealTemplate {
+                name: "yogurt greco e/o kefir".to_string(),
+                description: None,
+                compatible_slots: vec![SlotType::BREAKFAST],
+                location_type: LocationType::Home,
+                weekly_limit: Some(3),
+            },
+        )
+        .await
+        .unwrap();
+        for name in ["Yogurt greco", "Skyr"] {
+            MealOptionRepository::create(
+                &pool,
+                CreateMealOption {
+                    template_id: yogurt.id,
+                    name: name.to_string(),
+                    description: None,
+                    nutritional_notes: None,
+                },
+            )
+            .await
+            .unwrap();
+        }
+
+        let preview = PlanImportService::import_text(&pool, PLAN, true)
+            .await
+            .unwrap();
+        let matched = preview
+            .templates
+            .iter()
+            .find(|t| t.template_id == Some(yogurt.id))
+            .unwrap();
+        assert_eq!(matched.status, PlanTemplateStatus::Changed);
+        assert_eq!(matched.added_slots, vec![SlotType::MORNING_SNACK]);
+        assert_eq!(matched.added_options, vec!["kefir".to_string()]);
+        assert_eq!(matched.unlisted_options, vec!["Skyr".to_string()]);
+        assert!(matched.option_groups_changed);
+
+        let import = PlanImportService::import_text(&pool, PLAN, false)
+            .await
+            .unwrap();
+        assert!(!import.preview);
+        assert!(import.templates.iter().all(|t| t.template_id.is_some()));
+        assert_eq!(
+            MealTemplateRepository::get_all(&pool).await.unwrap().len(),
+            4
+        );
+
+        // The matched template kept its own settings and options
+        let merged = MealTemplateRepository::get_by_id(&pool, yogurt.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(merged.location_type, LocationType::Home);
+        assert_eq!(merged.weekly_limit, Some(3));
+        assert_eq!(
+            merged.compatible_slots,
+            vec![SlotType::BREAKFAST, SlotType::MORNING_SNACK]
+        );
+        let options = MealOptionRepository::get_by_template_id(&pool, yogurt.id)
+            .await
+            .unwrap();
+        let names: Vec<_> = options
+            .iter()
+            .map(|o| (o.name.as_str(), o.option_group.as_deref()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("Skyr", None),
+                ("Yogurt greco", Some("Yogurt greco e/o kefir")),
+                ("kefir", Some("Yogurt greco e/o kefir")),
+            ]
+        );
+
+        // New templates come with their groups and notes
+        let bread = find_template(&pool, "Pane con marmellata e formaggio spalmabile").await;
+        assert_eq!(
+            MealTemplateRepository::get_option_groups(&pool, bread.id)
+                .await
+                .unwrap()
+                .len(),
+            3
+        );
+        let options = MealOptionRepository::get_by_template_id(&pool, bread.id)
+            .await
+            .unwrap();
+        assert_eq!(options.len(), 6);
+        let pane = options.iter().find(|o| o.name == "pane integrale").unwrap();
+        assert_eq!(pane.nutritional_notes.as_deref(), Some("60 g"));
+
+        // Importing the same plan again changes nothing
+        let again = PlanImportService::import_text(&pool, PLAN, true)
+            .await
+            .unwrap();
+        assert!(again
+            .templates
+            .iter()
+            .all(|t| t.status == PlanTemplateStatus::Unchanged));
+
+        // Undo removes the new templates and puts the matched one back as it was
+        let operation = AuditRepository::undo_last(&pool).await.unwrap().unwrap();
+        assert_eq!(operation.command, "import_plan_text");
+        let templates = MealTemplateRepository::get_all(&pool).await.unwrap();
+        assert_eq!(templates.len(), 1);
+        assert_eq!(templates[0].compatible_slots, vec![SlotType::BREAKFAST]);
+        let options = MealOptionRepository::get_by_template_id(&pool, yogurt.id)
+            .await
+            .unwrap();
+        assert!(options.iter().all(|o| o.option_group.is_none()));
+        assert_eq!(options.len(), 2);
+        assert!(MealTemplateRepository::get_option_groups(&pool, yogurt.id)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_empty_plan() {
+        let pool = setup_test_pool().await;
+
+        let result = PlanImportService::import_text(&pool, "Nothing to see here", false).await;
+        assert!(matches!(result, Err(ApiError::ValidationError(_))));
+    }
+}
@@ -0,0 +1,278 @@
+// Suggestion Service
+// Ranks the options that can fill a slot right now
+
+use crate::error::ApiResult;
+use crate::models::{LocationType, OptionSuggestion, SlotType};
+use crate::repository::{
+    MealEntryRepository, MealOptionRepository, MealTemplateRepository, TagRepository,
+};
+use crate::services::{ValidationError, ValidationService};
+use chrono::NaiveDate;
+use sqlx::SqlitePool;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+pub struct SuggestionService;
+
+impl SuggestionService {
+    /// Suggest options for a slot on a date at a location
+    /// Only templates compatible with the slot and location are considered, and options
+    /// failing `validate_meal_entry` (e.g. weekly limit reached) are dropped; database
+    /// errors during validation are returned.
+    /// The rest are ranked by remaining weekly uses of their template (unlimited first),
+    /// then by tag suggestion headroom (no suggestions first), then by how long ago they
+    /// were last eaten (never eaten first), then by name.
+    pub async fn suggest_options(
+        pool: &SqlitePool,
+        date: NaiveDate,
+        slot: SlotType,
+        location: LocationType,
+    ) -> ApiResult<Vec<OptionSuggestion>> {
+        let week = ValidationService::get_week_string(date);
+
+        let template_usage: HashMap<i64, i64> =
+            MealEntryRepository::get_all_weekly_template_usage(pool, &week)
+                .await?
+                .into_iter()
+                .map(|u| (u.template_id, u.usage_count))
+                .collect();
+        let last_eaten: HashMap<i64, NaiveDate> =
+            MealEntryRepository::get_last_eaten_dates(pool, date)
+                .await?
+                .into_iter()
+                .collect();
+        let mut tag_usage: HashMap<i64, i64> = HashMap::new();
+
+        let mut suggestions = Vec::new();
+        for template in
+            MealTemplateRepository::get_by_slot_and_location(pool, &slot, location).await?
+        {
+            let remaining_weekly_uses = template
+                .weekly_limit
+                .map(|limit| limit as i64 - template_usage.get(&template.id).copied().unwrap_or(0));
+
+            for option in MealOptionRepository::get_by_template_id(pool, template.id).await? {
+                let warnings = match ValidationService::validate_meal_entry(
+                    pool, option.id, &slot, date,
+                )
+                .await
+                {
+                    Ok(warnings) => warnings,
+                    Err(error @ ValidationError::Database { .. }) => return Err(error.into()),
+                    Err(_) => continue,
+                };
+
+                let mut tag_headroom: Option<i64> = None;
+                for tag in TagRepository::get_option_tags_with_ancestors(pool, option.id).await? {
+                    let Some(suggestion) = tag.weekly_suggestion else {
+                        continue;
+                    };
+
+                    let usage = match tag_usage.get(&tag.id) {
+                        Some(usage) => *usage,
+                        None => {
+                            let usage =
+                                MealEntryRepository::get_weekly_tag_usage(pool, tag.id, &week)
+                                    .await?
+                                    .map(|u| u.usage_count)
+                                    .unwrap_or(0);
+                            tag_usage.insert(tag.id, usage);
+                            usage
+                        }
+                    };
+
+                    let headroom = suggestion as i64 - usage;
+                    tag_headroom = Some(tag_headroom.map_or(headroom, |h| h.min(headroom)));
+                }
+
+                suggestions.push(OptionSuggestion {
+                    meal_option_id: option.id,
+                    last_eaten: last_eaten.get(&option.id).copied(),
+                    option_name: option.name,
+                    template_id: template.id,
+                    template_name: template.name.clone(),
+                    remaining_weekly_uses,
+                    tag_headroom,
+                    warnings,
+                });
+            }
+        }
+
+        // None sorts before Some, so wrap "unlimited"/"never" as the best values
+        suggestions.sort_by(|a, b| {
+            let key = |s: &OptionSuggestion| {
+                (
+                    s.remaining_weekly_uses.map(Reverse),
+                    s.tag_headroom.map(Reverse),
+                    s.last_eaten,
+                )
+            };
+            key(a)
+                .cmp(&key(b))
+                .then_with(|| a.option_name.cmp(&b.option_name))
+        });
+
+        Ok(suggestions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        CreateMealEntry, CreateMealOption, CreateMealTemplate, CreateTag, EntryStatus, TagCategory,
+    };
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect(":memory:")
+            .await
+            .expect("Failed to create test pool");
+
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
+
+        pool
+    }
+
+    async fn create_option(
+        pool: &SqlitePool,
+        name: &str,
+        slots: Vec<SlotType>,
+        location_type: LocationType,
+        weekly_limit: Option<i32>,
+    ) -> i64 {
+        let template = MealTemplateRepository::create(
+            pool,
+            CreateMealTemplate {
+                name: name.to_string(),
+                description: None,
+                compatible_slots: slots,
+                location_type,
+                weekly_limit,
+            },
+        )
+        .await
+        .unwrap();
+
+        MealOptionRepository::create(
+            pool,
+            CreateMealOption {
+                template_id: template.id,
+                name: name.to_string(),
+                description: None,
+                nutritional_notes: None,
+            },
+        )
+        .await
+        .unwrap()
+        .id
+    }
+
+    async fn eat(pool: &SqlitePool, option_id: i64, date: NaiveDate) {
+        MealEntryRepository::create(
+            pool,
+            CreateMealEntry {
+                meal_option_id: option_id,
+                date,
+                slot_type: SlotType::LUNCH,
+                location: LocationType::Home,
+                servings: None,
+                notes: None,
+                status: Some(EntryStatus::Eaten),
+                extra_options: vec![],
+            },
+        )
+        .await
+        .unwrap();
+    }
+
+    fn names(suggestions: &[OptionSuggestion]) -> Vec<&str> {
+        suggestions.iter().map(|s| s.option_name.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_suggest_options_filters() {
+        let pool = setup_test_pool().await;
+        let lunch = vec![SlotType::LUNCH];
+        let date = NaiveDate::from_ymd_opt(2024, 11, 6).unwrap();
+
+        create_option(&pool, "Insalata", lunch.clone(), LocationType::Any, None).await;
+        create_option(&pool, "Pasta", lunch.clone(), LocationType::Home, None).await;
+        create_option(
+            &pool,
+            "Yogurt",
+            vec![SlotType::BREAKFAST],
+            LocationType::Any,
+            None,
+        )
+        .await;
+        let pizza = create_option(&pool, "Pizza", lunch, LocationType::Any, Some(1)).await;
+        eat(&pool, pizza, date).await;
+
+        let suggestions =
+            SuggestionService::suggest_options(&pool, date, SlotType::LUNCH, LocationType::Office)
+                .await
+                .unwrap();
+
+        // Wrong slot, wrong location and limit reached are all dropped
+        assert_eq!(names(&suggestions), vec!["Insalata"]);
+    }
+
+    #[tokio::test]
+    async fn test_suggest_options_ranking() {
+        let pool = setup_test_pool().await;
+        let dinner = vec![SlotType::DINNER];
+        let date = NaiveDate::from_ymd_opt(2024, 11, 8).unwrap();
+        let monday = NaiveDate::from_ymd_opt(2024, 11, 4).unwrap();
+
+        let pasta = create_option(&pool, "Pasta", dinner.clone(), LocationType::Any, None).await;
+        let riso = create_option(&pool, "Riso", dinner.clone(), LocationType::Any, None).await;
+        let _zuppa = create_option(&pool, "Zuppa", dinner.clone(), LocationType::Any, None).await;
+        let pesce = create_option(&pool, "Pesce", dinner.clone(), LocationType::Any, None).await;
+        let pizza = create_option(&pool, "Pizza", dinner, LocationType::Any, Some(3)).await;
+
+        let tag = TagRepository::create(
+            &pool,
+            CreateTag {
+                name: "pesce".to_string(),
+                display_name: "Pesce".to_string(),
+                category: TagCategory::Ingredient,
+                weekly_suggestion: Some(2),
+                parent_tag_id: None,
+            },
+        )
+        .await
+        .unwrap();
+        MealOptionRepository::add_tags(&pool, pesce, vec![tag.id])
+            .await
+            .unwrap();
+
+        eat(&pool, pasta, monday).await;
+        eat(&pool, riso, monday - chrono::Duration::days(10)).await;
+        eat(&pool, pesce, monday).await;
+        eat(&pool, pizza, monday).await;
+
+        let suggestions =
+            SuggestionService::suggest_options(&pool, date, SlotType::DINNER, LocationType::Home)
+                .await
+                .unwrap();
+
+        // Unlimited without suggestions first, by recency; then tag headroom; then limited
+        assert_eq!(
+            names(&suggestions),
+            vec!["Zuppa", "Riso", "Pasta", "Pesce", "Pizza"]
+        );
+
+        let pesce = &suggestions[3];
+        assert_eq!(pesce.tag_headroom, Some(1));
+        assert_eq!(pesce.last_eaten, Some(monday));
+        let pizza = &suggestions[4];
+        assert_eq!(pizza.remaining_weekly_uses, Some(2));
+        assert!(suggestions[0].last_eaten.is_none());
+    }
+}
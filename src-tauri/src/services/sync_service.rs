@@ -0,0 +1,141 @@
+// Sync Service
+// Exchanges change batches with a sync server (see src/server/sync.rs)
+// The HTTP client is only built with the `sync` feature
+
+use crate::error::{ApiError, ApiResult};
+use crate::models::SyncReport;
+#[cfg(feature = "sync")]
+use crate::models::{SyncPull, SyncPush, SyncPushResult};
+#[cfg(feature = "sync")]
+use crate::repository::SyncRepository;
+#[cfg(feature = "sync")]
+use serde::de::DeserializeOwned;
+use sqlx::SqlitePool;
+
+/// Environment variable holding the sync server's bearer token, for the CLI and
+/// the server binary
+pub const SYNC_TOKEN_ENV: &str = "NUTRITION_SYNC_TOKEN";
+
+/// HTTP client for one sync server
+#[cfg(feature = "sync")]
+#[derive(Clone)]
+pub struct SyncClient {
+    base_url: String,
+    token: String,
+    http: reqwest::Client,
+}
+
+#[cfg(feature = "sync")]
+impl SyncClient {
+    pub fn new(base_url: &str, token: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Client for the server configured in the database, if any
+    pub async fn from_settings(pool: &SqlitePool) -> ApiResult<Option<Self>> {
+        Ok(SyncRepository::get_server(pool)
+            .await?
+            .map(|(url, token)| Self::new(&url, &token)))
+    }
+
+    pub async fn push(&self, push: &SyncPush) -> ApiResult<SyncPushResult> {
+        let response = self
+            .http
+            .post(format!("{}/sync/push", self.base_url))
+            .bearer_auth(&self.token)
+            .json(push)
+            .send()
+            .await;
+
+        Self::read(response).await
+    }
+
+    /// Changes made by other devices after `since`
+    pub async fn pull(&self, since: i64, device_id: &str) -> ApiResult<SyncPull> {
+        let response = self
+            .http
+            .get(format!("{}/sync/pull", self.base_url))
+            .bearer_auth(&self.token)
+            .query(&[
+                ("since", since.to_string()),
+                ("device_id", device_id.to_string()),
+            ])
+            .send()
+            .await;
+
+        Self::read(response).await
+    }
+
+    /// Server errors come back as ApiError JSON; anything else is reported as is
+    async fn read<T: DeserializeOwned>(
+        response: reqwest::Result<reqwest::Response>,
+    ) -> ApiResult<T> {
+        let response = response
+            .map_err(|e| ApiError::InternalError(format!("Sync server unreachable: {}", e)))?;
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| ApiError::InternalError(format!("Sync server response: {}", e)))?;
+
+        if !status.is_success() {
+            return Err(serde_json::from_str(&body).unwrap_or_else(|_| {
+                ApiError::InternalError(format!("Sync server returned {}: {}", status, body))
+            }));
+        }
+
+        serde_json::from_str(&body)
+            .map_err(|e| ApiError::InternalError(format!("Invalid sync server response: {}", e)))
+    }
+}
+
+pub struct SyncService;
+
+impl SyncService {
+    /// Pull and apply what other devices changed, then push what changed here
+    /// Pulling first means local edits that lost a conflict are not pushed
+    #[cfg(feature = "sync")]
+    pub async fn sync(pool: &SqlitePool, client: &SyncClient) -> ApiResult<SyncReport> {
+        let status = SyncRepository::get_status(pool).await?;
+        let cursor = SyncRepository::get_cursor(pool).await?;
+
+        let pull = client.pull(cursor, &status.device_id).await?;
+        let pulled = SyncRepository::apply_pull(pool, pull).await?;
+
+        let (changes, seq) = SyncRepository::get_pending_changes(pool).await?;
+        let pushed = if changes.is_empty() {
+            SyncPushResult::default()
+        } else {
+            client
+                .push(&SyncPush {
+                    device_id: status.device_id,
+                    changes,
+                })
+                .await?
+        };
+        SyncRepository::mark_pushed(pool, seq).await?;
+
+        Ok(SyncReport { pulled, pushed })
+    }
+
+    /// Sync with the server configured in the database
+    #[cfg(feature = "sync")]
+    pub async fn sync_configured(pool: &SqlitePool) -> ApiResult<SyncReport> {
+        let client = SyncClient::from_settings(pool)
+            .await?
+            .ok_or_else(|| ApiError::ValidationError("No sync server configured".to_string()))?;
+
+        Self::sync(pool, &client).await
+    }
+    /// Without the `sync` feature there is no HTTP client to reach the server with
+    #[cfg(not(feature = "sync"))]
+    pub async fn sync_configured(_pool: &SqlitePool) -> ApiResult<SyncReport> {
+        Err(ApiError::InternalError(
+            "Syncing requires a build with the `sync` feature".to_string(),
+        ))
+    }
+}
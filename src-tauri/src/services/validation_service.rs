@@ -1,11 +1,10 @@
 // Validation Service
 // Business logic for validating meal entries and enforcing business rules
 
-use crate::models::{MealTemplate, SlotType};
-use crate::repository::{MealEntryRepository, MealOptionRepository, TagRepository};
-use chrono::{Datelike, IsoWeek, NaiveDate};
+use crate::models::{MealEntry, MealTemplate, SlotType};
+use crate::repository::Store;
+use chrono::{Datelike, NaiveDate};
 use serde::{Deserialize, Serialize};
-use sqlx::SqlitePool;
 
 /// Result type for validation operations
 pub type ValidationResult<T> = Result<T, ValidationError>;
@@ -32,6 +31,15 @@ pub enum ValidationError {
         suggestion: i32,
         current_usage: i64,
     },
+    /// Slot is unknown or deactivated and takes no new entries
+    InactiveSlot { slot: SlotType },
+    /// Selected options break the template's option group rules
+    InvalidCombination {
+        template_name: String,
+        reason: String,
+    },
+    /// The rules could not be checked because the database failed
+    Database { message: String },
 }
 
 impl std::fmt::Display for ValidationError {
@@ -52,8 +60,14 @@ impl std::fmt::Display for ValidationError {
                 compatible_slots,
             } => write!(
                 f,
-                "'{}' is not compatible with {:?}. Compatible slots: {:?}",
-                option_name, slot, compatible_slots
+                "'{}' is not compatible with {}. Compatible slots: {}",
+                option_name,
+                slot,
+                compatible_slots
+                    .iter()
+                    .map(|s| s.to_db_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
             ),
             ValidationError::TagSuggestionExceeded {
                 tag_name,
@@ -64,6 +78,22 @@ impl std::fmt::Display for ValidationError {
                 "Tag '{}' suggestion exceeded: {}/{} uses this week",
                 tag_name, current_usage, suggestion
             ),
+            ValidationError::InactiveSlot { slot } => {
+                write!(f, "Slot '{}' is not an active meal slot", slot)
+            }
+            ValidationError::InvalidCombination {
+                template_name,
+                reason,
+            } => write!(f, "Invalid selection for '{}': {}", template_name, reason),
+            ValidationError::Database { message } => write!(f, "Database error: {}", message),
+        }
+    }
+}
+
+impl From<sqlx::Error> for ValidationError {
+    fn from(err: sqlx::Error) -> Self {
+        ValidationError::Database {
+            message: err.to_string(),
         }
     }
 }
@@ -85,10 +115,18 @@ pub struct ValidationService;
 
 impl ValidationService {
     /// Get the ISO week string for a given date (format: "YYYY-WW")
-    /// Weeks start on Monday as per ISO 8601
+    /// See `MealEntry::week_key`
     pub fn get_week_string(date: NaiveDate) -> String {
-        let iso_week: IsoWeek = date.iso_week();
-        format!("{}-{:02}", iso_week.year(), iso_week.week())
+        MealEntry::week_key(date)
+    }
+
+    /// Parse an ISO week string ("YYYY-WW") into the Monday of that week
+    /// Returns None for malformed strings or weeks that don't exist in that year
+    pub fn parse_week_string(week: &str) -> Option<NaiveDate> {
+        let (year, week) = week.split_once('-')?;
+        let year: i32 = year.parse().ok()?;
+        let week: u32 = week.parse().ok()?;
+        NaiveDate::from_isoywd_opt(year, week, chrono::Weekday::Mon)
     }
 
     /// Get the Monday of the week for a given date
@@ -100,28 +138,30 @@ impl ValidationService {
     /// Validate that a meal option is compatible with a specific slot
     pub fn validate_slot_compatibility(
         template: &MealTemplate,
-        slot: SlotType,
+        slot: &SlotType,
     ) -> ValidationResult<()> {
-        if template.compatible_slots.contains(&slot) {
+        if template.compatible_slots.contains(slot) {
             Ok(())
         } else {
             Err(ValidationError::IncompatibleSlot {
                 option_name: template.name.clone(),
-                slot,
+                slot: slot.clone(),
                 compatible_slots: template.compatible_slots.clone(),
             })
         }
     }
 
-    /// Check if adding a meal entry would exceed weekly limits
+    /// Check if adding a meal entry would exceed the weekly limit of its template
+    /// Usage is counted across all options of the template
     /// Returns Ok(()) if within limits, Err with details if exceeded
     pub async fn check_weekly_limit(
-        pool: &SqlitePool,
+        store: &impl Store,
         meal_option_id: i64,
         date: NaiveDate,
     ) -> ValidationResult<()> {
         // Get the meal option to check if it has a weekly limit
-        let option = MealOptionRepository::get_by_id(pool, meal_option_id)
+        let option = store
+            .get_option(meal_option_id)
             .await
             .map_err(|_| ValidationError::WeeklyLimitExceeded {
                 item_name: "Unknown".to_string(),
@@ -135,29 +175,31 @@ impl ValidationService {
             })?;
 
         // Get the template to check for weekly limit
-        let template =
-            crate::repository::MealTemplateRepository::get_by_id(pool, option.template_id)
-                .await
-                .map_err(|_| ValidationError::WeeklyLimitExceeded {
-                    item_name: option.name.clone(),
-                    limit: 0,
-                    current_usage: 0,
-                })?
-                .ok_or_else(|| ValidationError::WeeklyLimitExceeded {
-                    item_name: option.name.clone(),
-                    limit: 0,
-                    current_usage: 0,
-                })?;
+        let template = store
+            .get_template(option.template_id)
+            .await
+            .map_err(|_| ValidationError::WeeklyLimitExceeded {
+                item_name: option.name.clone(),
+                limit: 0,
+                current_usage: 0,
+            })?
+            .ok_or_else(|| ValidationError::WeeklyLimitExceeded {
+                item_name: option.name.clone(),
+                limit: 0,
+                current_usage: 0,
+            })?;
 
         // Check if template has a weekly limit
+        // The limit applies to the template as a whole, across all of its options
         if let Some(weekly_limit) = template.weekly_limit {
             let week_str = Self::get_week_string(date);
 
             // Get current usage for this week
-            let usage = MealEntryRepository::get_weekly_usage(pool, meal_option_id, &week_str)
+            let usage = store
+                .get_weekly_template_usage(template.id, &week_str)
                 .await
                 .map_err(|_| ValidationError::WeeklyLimitExceeded {
-                    item_name: option.name.clone(),
+                    item_name: template.name.clone(),
                     limit: weekly_limit,
                     current_usage: 0,
                 })?;
@@ -167,7 +209,7 @@ impl ValidationService {
             // Check if adding one more would exceed the limit
             if current_count >= weekly_limit as i64 {
                 return Err(ValidationError::WeeklyLimitExceeded {
-                    item_name: option.name,
+                    item_name: template.name,
                     limit: weekly_limit,
                     current_usage: current_count,
                 });
@@ -180,14 +222,15 @@ impl ValidationService {
     /// Check tag weekly suggestions (returns warnings, not errors)
     /// Tag suggestions are soft limits that generate warnings but don't block
     pub async fn check_tag_suggestions(
-        pool: &SqlitePool,
+        store: &impl Store,
         meal_option_id: i64,
         date: NaiveDate,
     ) -> ValidationResult<Vec<ValidationWarning>> {
         let mut warnings = Vec::new();
 
-        // Get option with tags
-        let option_with_tags = MealOptionRepository::get_with_tags(pool, meal_option_id)
+        // Make sure the option exists
+        store
+            .get_option(meal_option_id)
             .await
             .map_err(|_| ValidationError::WeeklyLimitExceeded {
                 item_name: "Unknown".to_string(),
@@ -202,23 +245,22 @@ impl ValidationService {
 
         let week_str = Self::get_week_string(date);
 
-        // Check each tag for weekly suggestions
-        for tag_id in option_with_tags.tags {
-            let tag = TagRepository::get_by_id(pool, tag_id)
-                .await
-                .map_err(|_| ValidationError::WeeklyLimitExceeded {
-                    item_name: "Unknown".to_string(),
-                    limit: 0,
-                    current_usage: 0,
-                })?
-                .ok_or_else(|| ValidationError::WeeklyLimitExceeded {
-                    item_name: "Unknown".to_string(),
-                    limit: 0,
-                    current_usage: 0,
-                })?;
+        // Check the option's tags and all of their ancestors: eating "pasta_integrale"
+        // also counts against the "pasta" suggestion
+        let tags = store
+            .get_option_tags_with_ancestors(meal_option_id)
+            .await
+            .map_err(|_| ValidationError::WeeklyLimitExceeded {
+                item_name: "Unknown".to_string(),
+                limit: 0,
+                current_usage: 0,
+            })?;
 
+        for tag in tags {
             if let Some(suggestion) = tag.weekly_suggestion {
-                let usage = MealEntryRepository::get_weekly_tag_usage(pool, tag_id, &week_str)
+                // Usage is rolled up over the tag's descendants
+                let usage = store
+                    .get_weekly_tag_usage(tag.id, &week_str)
                     .await
                     .map_err(|_| ValidationError::WeeklyLimitExceeded {
                         item_name: tag.name.clone(),
@@ -247,13 +289,14 @@ impl ValidationService {
     /// Comprehensive validation before creating a meal entry
     /// Returns Ok(warnings) if valid, Err if validation fails
     pub async fn validate_meal_entry(
-        pool: &SqlitePool,
+        store: &impl Store,
         meal_option_id: i64,
-        slot: SlotType,
+        slot: &SlotType,
         date: NaiveDate,
     ) -> ValidationResult<Vec<ValidationWarning>> {
         // Get meal option and template
-        let option = MealOptionRepository::get_by_id(pool, meal_option_id)
+        let option = store
+            .get_option(meal_option_id)
             .await
             .map_err(|_| ValidationError::WeeklyLimitExceeded {
                 item_name: "Unknown".to_string(),
@@ -266,28 +309,168 @@ impl ValidationService {
                 current_usage: 0,
             })?;
 
-        let template =
-            crate::repository::MealTemplateRepository::get_by_id(pool, option.template_id)
-                .await
-                .map_err(|_| ValidationError::WeeklyLimitExceeded {
-                    item_name: option.name.clone(),
-                    limit: 0,
-                    current_usage: 0,
-                })?
-                .ok_or_else(|| ValidationError::WeeklyLimitExceeded {
-                    item_name: option.name.clone(),
-                    limit: 0,
-                    current_usage: 0,
-                })?;
+        let template = store
+            .get_template(option.template_id)
+            .await
+            .map_err(|_| ValidationError::WeeklyLimitExceeded {
+                item_name: option.name.clone(),
+                limit: 0,
+                current_usage: 0,
+            })?
+            .ok_or_else(|| ValidationError::WeeklyLimitExceeded {
+                item_name: option.name.clone(),
+                limit: 0,
+                current_usage: 0,
+            })?;
 
-        // 1. Check slot compatibility (hard requirement)
+        // 1. Check the slot takes new entries and fits the template (hard requirements)
+        let slot_active = store
+            .get_slot_by_name(slot.to_db_string())
+            .await?
+            .is_some_and(|s| s.active);
+        if !slot_active {
+            return Err(ValidationError::InactiveSlot { slot: slot.clone() });
+        }
         Self::validate_slot_compatibility(&template, slot)?;
 
         // 2. Check weekly limits (hard requirement)
-        Self::check_weekly_limit(pool, meal_option_id, date).await?;
+        Self::check_weekly_limit(store, meal_option_id, date).await?;
 
         // 3. Check tag suggestions (soft warnings)
-        let warnings = Self::check_tag_suggestions(pool, meal_option_id, date).await?;
+        let warnings = Self::check_tag_suggestions(store, meal_option_id, date).await?;
+
+        Ok(warnings)
+    }
+
+    /// Check that a set of options can make up one entry
+    /// All options come from the template of the first one. A template without
+    /// option groups takes a single option; otherwise every option must be in a
+    /// declared group and each group's min/max choices must be met.
+    pub async fn check_option_combination(
+        store: &impl Store,
+        option_ids: &[i64],
+    ) -> ValidationResult<()> {
+        let unknown = || ValidationError::InvalidCombination {
+            template_name: "Unknown".to_string(),
+            reason: "Meal option not found".to_string(),
+        };
+
+        let mut options = Vec::with_capacity(option_ids.len());
+        for &id in option_ids {
+            let option = store
+                .get_option(id)
+                .await
+                .map_err(|_| unknown())?
+                .ok_or_else(unknown)?;
+            options.push(option);
+        }
+
+        let Some(main) = options.first() else {
+            return Err(ValidationError::InvalidCombination {
+                template_name: "Unknown".to_string(),
+                reason: "No option selected".to_string(),
+            });
+        };
+
+        let template = store
+            .get_template(main.template_id)
+            .await
+            .map_err(|_| unknown())?
+            .ok_or_else(unknown)?;
+        let invalid = |reason: String| ValidationError::InvalidCombination {
+            template_name: template.name.clone(),
+            reason,
+        };
+
+        for (i, option) in options.iter().enumerate() {
+            if option.template_id != template.id {
+                return Err(invalid(format!(
+                    "'{}' is not an option of this template",
+                    option.name
+                )));
+            }
+            if options[..i].iter().any(|o| o.id == option.id) {
+                return Err(invalid(format!(
+                    "'{}' is selected more than once",
+                    option.name
+                )));
+            }
+        }
+
+        let groups = store
+            .get_option_groups(template.id)
+            .await
+            .map_err(|_| unknown())?;
+
+        if groups.is_empty() {
+            if options.len() > 1 {
+                return Err(invalid("only one option can be selected".to_string()));
+            }
+            return Ok(());
+        }
+
+        for option in &options {
+            let declared = option
+                .option_group
+                .as_ref()
+                .is_some_and(|g| groups.iter().any(|group| &group.name == g));
+            if !declared {
+                return Err(invalid(format!(
+                    "'{}' is not in any option group",
+                    option.name
+                )));
+            }
+        }
+
+        for group in &groups {
+            let picked = options
+                .iter()
+                .filter(|o| o.option_group.as_deref() == Some(group.name.as_str()))
+                .count() as i32;
+
+            if picked < group.min_choices {
+                return Err(invalid(format!(
+                    "pick at least {} from '{}'",
+                    group.min_choices, group.name
+                )));
+            }
+            if let Some(max) = group.max_choices {
+                if picked > max {
+                    return Err(invalid(format!(
+                        "pick at most {} from '{}'",
+                        max, group.name
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Comprehensive validation before creating an entry with one or more options
+    /// The first option is the main one: slot, template limit and its tags are
+    /// checked as in `validate_meal_entry`, then the combination rules, then the
+    /// tag suggestions of the other options (each warning once)
+    pub async fn validate_meal_selection(
+        store: &impl Store,
+        option_ids: &[i64],
+        slot: &SlotType,
+        date: NaiveDate,
+    ) -> ValidationResult<Vec<ValidationWarning>> {
+        Self::check_option_combination(store, option_ids).await?;
+
+        let Some((&main, extra)) = option_ids.split_first() else {
+            return Ok(Vec::new());
+        };
+        let mut warnings = Self::validate_meal_entry(store, main, slot, date).await?;
+
+        for &option_id in extra {
+            for warning in Self::check_tag_suggestions(store, option_id, date).await? {
+                if !warnings.contains(&warning) {
+                    warnings.push(warning);
+                }
+            }
+        }
 
         Ok(warnings)
     }
@@ -297,10 +480,16 @@ impl ValidationService {
 mod tests {
     use super::*;
     use crate::models::{
-        CreateMealEntry, CreateMealOption, CreateMealTemplate, CreateTag, LocationType, TagCategory,
+        CreateMealEntry, CreateMealOption, CreateMealTemplate, CreateTag, EntryStatus,
+        LocationType, OptionGroup, TagCategory, UpdateMealSlot,
+    };
+    use crate::repository::{
+        MealEntryRepository, MealOptionRepository, MealSlotRepository, MealTemplateRepository,
+        TagRepository,
     };
-    use crate::repository::MealTemplateRepository;
     use sqlx::sqlite::SqlitePoolOptions;
+    use sqlx::SqlitePool;
+    use std::collections::HashMap;
 
     async fn setup_test_pool() -> SqlitePool {
         let pool = SqlitePoolOptions::new()
@@ -322,7 +511,7 @@ mod tests {
             name: "Test Template".to_string(),
             description: None,
             location_type: LocationType::Home,
-            compatible_slots: vec![SlotType::Breakfast, SlotType::Lunch],
+            compatible_slots: vec![SlotType::BREAKFAST, SlotType::LUNCH],
             weekly_limit,
         };
 
@@ -376,6 +565,57 @@ mod tests {
         assert_eq!(ValidationService::get_week_string(date), "2024-46");
     }
 
+    #[test]
+    fn test_get_week_string_year_boundaries() {
+        let week =
+            |y, m, d| ValidationService::get_week_string(NaiveDate::from_ymd_opt(y, m, d).unwrap());
+
+        // Late December can belong to week 1 of the next year...
+        assert_eq!(week(2024, 12, 29), "2024-52");
+        assert_eq!(week(2024, 12, 30), "2025-01");
+        assert_eq!(week(2025, 1, 5), "2025-01");
+
+        // ...and early January to the last week of the previous one
+        assert_eq!(week(2023, 1, 1), "2022-52");
+        assert_eq!(week(2023, 1, 2), "2023-01");
+
+        // 53-week years (2020, 2026) and the week 53 spilling into January
+        assert_eq!(week(2020, 12, 28), "2020-53");
+        assert_eq!(week(2021, 1, 3), "2020-53");
+        assert_eq!(week(2021, 1, 4), "2021-01");
+        assert_eq!(week(2026, 12, 31), "2026-53");
+        assert_eq!(week(2027, 1, 3), "2026-53");
+        assert_eq!(
+            ValidationService::parse_week_string("2026-53"),
+            NaiveDate::from_ymd_opt(2026, 12, 28)
+        );
+    }
+
+    #[test]
+    fn test_parse_week_string() {
+        assert_eq!(
+            ValidationService::parse_week_string("2024-45"),
+            NaiveDate::from_ymd_opt(2024, 11, 4)
+        );
+        // ISO week 1 of 2025 starts in December 2024
+        assert_eq!(
+            ValidationService::parse_week_string("2025-01"),
+            NaiveDate::from_ymd_opt(2024, 12, 30)
+        );
+
+        // Round-trips with get_week_string
+        let date = NaiveDate::from_ymd_opt(2024, 11, 6).unwrap();
+        let week = ValidationService::get_week_string(date);
+        assert_eq!(
+            ValidationService::parse_week_string(&week),
+            NaiveDate::from_ymd_opt(2024, 11, 4)
+        );
+
+        assert_eq!(ValidationService::parse_week_string("2024-54"), None);
+        assert_eq!(ValidationService::parse_week_string("2024-00"), None);
+        assert_eq!(ValidationService::parse_week_string("2024W45"), None);
+    }
+
     #[test]
     fn test_get_week_start() {
         // Any day in week should return Monday
@@ -406,12 +646,14 @@ mod tests {
 
         // Compatible slots should pass
         assert!(
-            ValidationService::validate_slot_compatibility(&template, SlotType::Breakfast).is_ok()
+            ValidationService::validate_slot_compatibility(&template, &SlotType::BREAKFAST).is_ok()
+        );
+        assert!(
+            ValidationService::validate_slot_compatibility(&template, &SlotType::LUNCH).is_ok()
         );
-        assert!(ValidationService::validate_slot_compatibility(&template, SlotType::Lunch).is_ok());
 
         // Incompatible slot should fail
-        let result = ValidationService::validate_slot_compatibility(&template, SlotType::Dinner);
+        let result = ValidationService::validate_slot_compatibility(&template, &SlotType::DINNER);
         assert!(result.is_err());
 
         if let Err(ValidationError::IncompatibleSlot {
@@ -419,7 +661,7 @@ mod tests {
         }) = result
         {
             assert_eq!(option_name, "Test Template");
-            assert_eq!(slot, SlotType::Dinner);
+            assert_eq!(slot, SlotType::DINNER);
         }
     }
 
@@ -435,11 +677,12 @@ mod tests {
         let entry = CreateMealEntry {
             meal_option_id: option_id,
             date,
-            slot_type: SlotType::Breakfast,
+            slot_type: SlotType::BREAKFAST,
             location: LocationType::Home,
             servings: None,
             notes: None,
-            completed: Some(true),
+            status: Some(EntryStatus::Eaten),
+            extra_options: vec![],
         };
         MealEntryRepository::create(&pool, entry).await.unwrap();
 
@@ -463,11 +706,12 @@ mod tests {
             let entry = CreateMealEntry {
                 meal_option_id: option_id,
                 date: *date,
-                slot_type: SlotType::Breakfast,
+                slot_type: SlotType::BREAKFAST,
                 location: LocationType::Home,
                 servings: None,
                 notes: None,
-                completed: Some(true),
+                status: Some(EntryStatus::Eaten),
+                extra_options: vec![],
             };
             MealEntryRepository::create(&pool, entry).await.unwrap();
         }
@@ -482,12 +726,137 @@ mod tests {
             current_usage,
         }) = result
         {
-            assert_eq!(item_name, "Test Option");
+            assert_eq!(item_name, "Test Template");
             assert_eq!(limit, 2);
             assert_eq!(current_usage, 2);
         }
     }
 
+    #[tokio::test]
+    async fn test_weekly_limit_across_new_year() {
+        let pool = setup_test_pool().await;
+        let template_id = create_test_template_with_limit(&pool, Some(1)).await;
+        let option_id = create_test_option(&pool, template_id).await;
+
+        // Monday 2024-12-30 starts ISO week 2025-01
+        let entry = CreateMealEntry {
+            meal_option_id: option_id,
+            date: NaiveDate::from_ymd_opt(2024, 12, 30).unwrap(),
+            slot_type: SlotType::BREAKFAST,
+            location: LocationType::Home,
+            servings: None,
+            notes: None,
+            status: Some(EntryStatus::Eaten),
+            extra_options: vec![],
+        };
+        MealEntryRepository::create(&pool, entry).await.unwrap();
+
+        // Same ISO week, different calendar year
+        let friday = NaiveDate::from_ymd_opt(2025, 1, 3).unwrap();
+        let result = ValidationService::check_weekly_limit(&pool, option_id, friday).await;
+        assert!(matches!(
+            result,
+            Err(ValidationError::WeeklyLimitExceeded {
+                current_usage: 1,
+                ..
+            })
+        ));
+
+        // The Sunday before is still ISO week 2024-52
+        let sunday = NaiveDate::from_ymd_opt(2024, 12, 29).unwrap();
+        assert!(
+            ValidationService::check_weekly_limit(&pool, option_id, sunday)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_weekly_limit_in_week_53() {
+        let pool = setup_test_pool().await;
+        let template_id = create_test_template_with_limit(&pool, Some(2)).await;
+        let option_id = create_test_option(&pool, template_id).await;
+
+        // ISO week 2026-53 runs from Monday 2026-12-28 to Sunday 2027-01-03
+        for date in [
+            NaiveDate::from_ymd_opt(2026, 12, 28).unwrap(),
+            NaiveDate::from_ymd_opt(2027, 1, 2).unwrap(),
+        ] {
+            let entry = CreateMealEntry {
+                meal_option_id: option_id,
+                date,
+                slot_type: SlotType::BREAKFAST,
+                location: LocationType::Home,
+                servings: None,
+                notes: None,
+                status: Some(EntryStatus::Eaten),
+                extra_options: vec![],
+            };
+            MealEntryRepository::create(&pool, entry).await.unwrap();
+        }
+
+        let usage = MealEntryRepository::get_weekly_template_usage(&pool, template_id, "2026-53")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(usage.usage_count, 2);
+
+        let sunday = NaiveDate::from_ymd_opt(2027, 1, 3).unwrap();
+        assert!(
+            ValidationService::check_weekly_limit(&pool, option_id, sunday)
+                .await
+                .is_err()
+        );
+        let next_monday = NaiveDate::from_ymd_opt(2027, 1, 4).unwrap();
+        assert!(
+            ValidationService::check_weekly_limit(&pool, option_id, next_monday)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_weekly_limit_shared_across_options() {
+        let pool = setup_test_pool().await;
+        let template_id = create_test_template_with_limit(&pool, Some(2)).await;
+        let first_option = create_test_option(&pool, template_id).await;
+        let second_option = create_test_option(&pool, template_id).await;
+        let third_option = create_test_option(&pool, template_id).await;
+
+        let monday = NaiveDate::from_ymd_opt(2024, 11, 4).unwrap();
+
+        // One entry each for two different options of the same template
+        for (day, option_id) in [first_option, second_option].into_iter().enumerate() {
+            let entry = CreateMealEntry {
+                meal_option_id: option_id,
+                date: monday + chrono::Duration::days(day as i64),
+                slot_type: SlotType::BREAKFAST,
+                location: LocationType::Home,
+                servings: None,
+                notes: None,
+                status: Some(EntryStatus::Eaten),
+                extra_options: vec![],
+            };
+            MealEntryRepository::create(&pool, entry).await.unwrap();
+        }
+
+        // A third option of the template has no uses itself, but the template is at its limit
+        let result = ValidationService::check_weekly_limit(&pool, third_option, monday).await;
+        assert!(matches!(
+            result,
+            Err(ValidationError::WeeklyLimitExceeded {
+                limit: 2,
+                current_usage: 2,
+                ..
+            })
+        ));
+
+        // Next week is unaffected
+        let next_monday = monday + chrono::Duration::days(7);
+        let result = ValidationService::check_weekly_limit(&pool, third_option, next_monday).await;
+        assert!(result.is_ok());
+    }
+
     #[tokio::test]
     async fn test_no_weekly_limit() {
         let pool = setup_test_pool().await;
@@ -502,11 +871,12 @@ mod tests {
             let entry = CreateMealEntry {
                 meal_option_id: option_id,
                 date: entry_date,
-                slot_type: SlotType::Breakfast,
+                slot_type: SlotType::BREAKFAST,
                 location: LocationType::Home,
                 servings: None,
                 notes: None,
-                completed: Some(true),
+                status: Some(EntryStatus::Eaten),
+                extra_options: vec![],
             };
             MealEntryRepository::create(&pool, entry).await.unwrap();
         }
@@ -537,11 +907,12 @@ mod tests {
             let entry = CreateMealEntry {
                 meal_option_id: option_id,
                 date: *date,
-                slot_type: SlotType::Breakfast,
+                slot_type: SlotType::BREAKFAST,
                 location: LocationType::Home,
                 servings: None,
                 notes: None,
-                completed: Some(true),
+                status: Some(EntryStatus::Eaten),
+                extra_options: vec![],
             };
             MealEntryRepository::create(&pool, entry).await.unwrap();
         }
@@ -556,6 +927,55 @@ mod tests {
         assert_eq!(warnings[0].warning_type, WarningType::TagSuggestion);
     }
 
+    #[tokio::test]
+    async fn test_tag_suggestions_include_child_tags() {
+        let pool = setup_test_pool().await;
+        let template_id = create_test_template_with_limit(&pool, None).await;
+        let option_id = create_test_option(&pool, template_id).await;
+        let parent_id = create_test_tag(&pool, "pasta", Some(2)).await;
+        let child_id = TagRepository::create(
+            &pool,
+            CreateTag {
+                name: "pasta_integrale".to_string(),
+                display_name: "Pasta integrale".to_string(),
+                category: TagCategory::Ingredient,
+                parent_tag_id: Some(parent_id),
+                weekly_suggestion: None,
+            },
+        )
+        .await
+        .unwrap()
+        .id;
+
+        // Option is only tagged with the child
+        MealOptionRepository::add_tags(&pool, option_id, vec![child_id])
+            .await
+            .unwrap();
+
+        let monday = NaiveDate::from_ymd_opt(2024, 11, 4).unwrap();
+        for day in 0..2 {
+            let entry = CreateMealEntry {
+                meal_option_id: option_id,
+                date: monday + chrono::Duration::days(day),
+                slot_type: SlotType::LUNCH,
+                location: LocationType::Home,
+                servings: None,
+                notes: None,
+                status: Some(EntryStatus::Eaten),
+                extra_options: vec![],
+            };
+            MealEntryRepository::create(&pool, entry).await.unwrap();
+        }
+
+        // The parent's suggestion is reached through the child
+        let warnings = ValidationService::check_tag_suggestions(&pool, option_id, monday)
+            .await
+            .unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].message.contains("pasta"));
+        assert!(warnings[0].message.contains("2/2"));
+    }
+
     #[tokio::test]
     async fn test_comprehensive_validation() {
         let pool = setup_test_pool().await;
@@ -566,13 +986,13 @@ mod tests {
 
         // Valid: Compatible slot, within limit
         let result =
-            ValidationService::validate_meal_entry(&pool, option_id, SlotType::Breakfast, date)
+            ValidationService::validate_meal_entry(&pool, option_id, &SlotType::BREAKFAST, date)
                 .await;
         assert!(result.is_ok());
 
         // Invalid: Incompatible slot
         let result =
-            ValidationService::validate_meal_entry(&pool, option_id, SlotType::Dinner, date).await;
+            ValidationService::validate_meal_entry(&pool, option_id, &SlotType::DINNER, date).await;
         assert!(result.is_err());
         assert!(matches!(
             result,
@@ -584,18 +1004,19 @@ mod tests {
             let entry = CreateMealEntry {
                 meal_option_id: option_id,
                 date,
-                slot_type: SlotType::Breakfast,
+                slot_type: SlotType::BREAKFAST,
                 location: LocationType::Home,
                 servings: None,
                 notes: None,
-                completed: Some(true),
+                status: Some(EntryStatus::Eaten),
+                extra_options: vec![],
             };
             MealEntryRepository::create(&pool, entry).await.unwrap();
         }
 
         // Invalid: Weekly limit exceeded
         let result =
-            ValidationService::validate_meal_entry(&pool, option_id, SlotType::Breakfast, date)
+            ValidationService::validate_meal_entry(&pool, option_id, &SlotType::BREAKFAST, date)
                 .await;
         assert!(result.is_err());
         assert!(matches!(
@@ -604,6 +1025,173 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    async fn test_inactive_slot() {
+        let pool = setup_test_pool().await;
+        let template_id = create_test_template_with_limit(&pool, None).await;
+        let option_id = create_test_option(&pool, template_id).await;
+        let date = NaiveDate::from_ymd_opt(2024, 11, 4).unwrap();
+
+        let breakfast = MealSlotRepository::get_by_name(&pool, "breakfast")
+            .await
+            .unwrap()
+            .unwrap();
+        MealSlotRepository::update(
+            &pool,
+            breakfast.id,
+            UpdateMealSlot {
+                display_name: None,
+                sort_order: None,
+                active: Some(false),
+            },
+        )
+        .await
+        .unwrap();
+
+        // Deactivated slots take no new entries, even for compatible templates
+        let result =
+            ValidationService::validate_meal_entry(&pool, option_id, &SlotType::BREAKFAST, date)
+                .await;
+        assert_eq!(
+            result,
+            Err(ValidationError::InactiveSlot {
+                slot: SlotType::BREAKFAST
+            })
+        );
+        assert!(
+            ValidationService::validate_meal_entry(&pool, option_id, &SlotType::LUNCH, date)
+                .await
+                .is_ok()
+        );
+
+        // Unknown slots are rejected the same way
+        let unknown = SlotType::from_db_string("pre_workout").unwrap();
+        let result = ValidationService::validate_meal_entry(&pool, option_id, &unknown, date).await;
+        assert!(matches!(result, Err(ValidationError::InactiveSlot { .. })));
+
+        // A failed lookup is a database error, not an inactive slot
+        sqlx::query("ALTER TABLE meal_slots RENAME COLUMN active TO active_moved")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let result =
+            ValidationService::validate_meal_entry(&pool, option_id, &SlotType::LUNCH, date).await;
+        assert!(matches!(result, Err(ValidationError::Database { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_option_combination() {
+        let pool = setup_test_pool().await;
+        let template_id = create_test_template_with_limit(&pool, None).await;
+        let single = create_test_option(&pool, template_id).await;
+        let other = create_test_option(&pool, template_id).await;
+
+        // Without option groups only one option can be picked
+        assert!(
+            ValidationService::check_option_combination(&pool, &[single])
+                .await
+                .is_ok()
+        );
+        let result = ValidationService::check_option_combination(&pool, &[single, other]).await;
+        assert!(matches!(
+            result,
+            Err(ValidationError::InvalidCombination { .. })
+        ));
+
+        // "Pane" exactly one, "spalmabile" one or two
+        MealTemplateRepository::set_option_groups(
+            &pool,
+            template_id,
+            vec![
+                OptionGroup {
+                    name: "pane".to_string(),
+                    min_choices: 1,
+                    max_choices: Some(1),
+                },
+                OptionGroup {
+                    name: "spalmabile".to_string(),
+                    min_choices: 1,
+                    max_choices: Some(2),
+                },
+            ],
+        )
+        .await
+        .unwrap();
+
+        let mut ids = HashMap::new();
+        for (name, group) in [
+            ("bread", "pane"),
+            ("rusks", "pane"),
+            ("jam", "spalmabile"),
+            ("cheese", "spalmabile"),
+        ] {
+            let id = create_test_option(&pool, template_id).await;
+            MealOptionRepository::set_group(&pool, id, Some(group.to_string()))
+                .await
+                .unwrap();
+            ids.insert(name, id);
+        }
+
+        let valid = [
+            vec![ids["bread"], ids["jam"]],
+            vec![ids["rusks"], ids["jam"], ids["cheese"]],
+        ];
+        for selection in valid {
+            assert!(
+                ValidationService::check_option_combination(&pool, &selection)
+                    .await
+                    .is_ok(),
+                "{:?} should be valid",
+                selection
+            );
+        }
+
+        let invalid = [
+            vec![ids["bread"]],                           // Missing spread
+            vec![ids["bread"], ids["rusks"], ids["jam"]], // Two breads
+            vec![ids["bread"], ids["jam"], ids["jam"]],   // Duplicate
+            vec![ids["bread"], ids["jam"], single],       // Ungrouped option
+        ];
+        for selection in invalid {
+            let result = ValidationService::check_option_combination(&pool, &selection).await;
+            assert!(
+                matches!(result, Err(ValidationError::InvalidCombination { .. })),
+                "{:?} should be invalid",
+                selection
+            );
+        }
+
+        // Options of another template cannot be combined
+        let other_template = create_test_template_with_limit(&pool, None).await;
+        let foreign = create_test_option(&pool, other_template).await;
+        let result =
+            ValidationService::check_option_combination(&pool, &[ids["bread"], foreign]).await;
+        assert!(matches!(
+            result,
+            Err(ValidationError::InvalidCombination { .. })
+        ));
+
+        // The full selection check runs the combination rules first
+        let date = NaiveDate::from_ymd_opt(2024, 11, 4).unwrap();
+        let result = ValidationService::validate_meal_selection(
+            &pool,
+            &[ids["bread"]],
+            &SlotType::BREAKFAST,
+            date,
+        )
+        .await;
+        assert!(result.is_err());
+        let warnings = ValidationService::validate_meal_selection(
+            &pool,
+            &[ids["bread"], ids["jam"]],
+            &SlotType::BREAKFAST,
+            date,
+        )
+        .await
+        .unwrap();
+        assert!(warnings.is_empty());
+    }
+
     #[test]
     fn test_validation_error_display() {
         // Test Display implementation for WeeklyLimitExceeded
@@ -620,12 +1208,13 @@ mod tests {
         // Test Display implementation for IncompatibleSlot
         let error = ValidationError::IncompatibleSlot {
             option_name: "Pizza".to_string(),
-            slot: SlotType::Breakfast,
-            compatible_slots: vec![SlotType::Lunch, SlotType::Dinner],
+            slot: SlotType::BREAKFAST,
+            compatible_slots: vec![SlotType::LUNCH, SlotType::DINNER],
         };
         let display_str = format!("{}", error);
         assert!(display_str.contains("Pizza"));
         assert!(display_str.contains("not compatible"));
+        assert!(display_str.contains("lunch, dinner"));
 
         // Test Display implementation for TagSuggestionExceeded
         let error = ValidationError::TagSuggestionExceeded {
@@ -637,6 +1226,15 @@ mod tests {
         assert!(display_str.contains("bread"));
         assert!(display_str.contains("suggestion exceeded"));
         assert!(display_str.contains("4/3"));
+
+        // Test Display implementation for InvalidCombination
+        let error = ValidationError::InvalidCombination {
+            template_name: "Pane e spalmabile".to_string(),
+            reason: "'Pane' is selected more than once".to_string(),
+        };
+        let display_str = format!("{}", error);
+        assert!(display_str.contains("Invalid selection"));
+        assert!(display_str.contains("Pane e spalmabile"));
     }
 
     #[tokio::test]
@@ -649,7 +1247,7 @@ mod tests {
         let result = ValidationService::validate_meal_entry(
             &pool,
             invalid_option_id,
-            SlotType::Breakfast,
+            &SlotType::BREAKFAST,
             date,
         )
         .await;
@@ -0,0 +1,589 @@
+// Week Summary Service
+// Builds the weekly overview (slot grid, limit and suggestion usage) in a few queries
+
+use crate::models::{
+    EntryStatus, LocationType, MealSlot, SlotType, WeekSummary, WeekSummaryDay, WeekSummaryEntry,
+    WeekSummarySlot, WeeklyTagSuggestionUsage, WeeklyTemplateUsage,
+};
+use crate::repository::MealSlotRepository;
+use crate::services::ValidationService;
+use chrono::{Duration, NaiveDate};
+use sqlx::{Row, SqlitePool};
+
+pub struct WeekSummaryService;
+
+impl WeekSummaryService {
+    /// Get the summary of the ISO week containing `date`
+    /// Usage counts only eaten entries, like the weekly limit checks
+    /// Off-plan entries are flagged and count toward tag usage through their estimated tags
+    pub async fn get_week_summary(pool: &SqlitePool, date: NaiveDate) -> sqlx::Result<WeekSummary> {
+        let week = ValidationService::get_week_string(date);
+        let start_date = ValidationService::get_week_start(date);
+        let end_date = start_date + Duration::days(6);
+
+        let entries = Self::query_entries(pool, start_date, end_date).await?;
+        let template_usage = Self::query_template_usage(pool, &week, start_date, end_date).await?;
+        let tag_usage = Self::query_tag_usage(pool, start_date, end_date).await?;
+
+        let total_entries = entries.len() as i64;
+        let completed_entries = entries
+            .iter()
+            .filter(|(_, _, e)| e.status == EntryStatus::Eaten)
+            .count() as i64;
+        let off_plan_entries = entries.iter().filter(|(_, _, e)| e.off_plan).count() as i64;
+        let completion_ratio = if total_entries > 0 {
+            completed_entries as f64 / total_entries as f64
+        } else {
+            0.0
+        };
+
+        // Deactivated slots only show up in weeks that still have entries in them
+        let slots: Vec<MealSlot> = MealSlotRepository::get_all(pool)
+            .await?
+            .into_iter()
+            .filter(|s| s.active || entries.iter().any(|(_, slot_type, _)| *slot_type == s.name))
+            .collect();
+
+        // Lay entries out on the 7 x slots grid
+        let mut days: Vec<WeekSummaryDay> = (0..7)
+            .map(|offset| WeekSummaryDay {
+                date: start_date + Duration::days(offset),
+                slots: slots
+                    .iter()
+                    .map(|slot| WeekSummarySlot {
+                        slot_type: slot.name.clone(),
+                        display_name: slot.display_name.clone(),
+                        entries: Vec::new(),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        for (date, slot_type, entry) in entries {
+            let day = (date - start_date).num_days() as usize;
+            if let Some(slot) = days[day]
+                .slots
+                .iter_mut()
+                .find(|s| s.slot_type == slot_type)
+            {
+                slot.entries.push(entry);
+            }
+        }
+
+        Ok(WeekSummary {
+            week,
+            start_date,
+            end_date,
+            days,
+            template_usage,
+            tag_usage,
+            total_entries,
+            completed_entries,
+            off_plan_entries,
+            completion_ratio,
+        })
+    }
+
+    /// All entries of the week with the option and template names they were logged with
+    async fn query_entries(
+        pool: &SqlitePool,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> sqlx::Result<Vec<(NaiveDate, SlotType, WeekSummaryEntry)>> {
+        let rows = sqlx::query(
+            "SELECT me.id, me.meal_option_id, me.date, me.slot_type, me.location, me.servings,
+                    me.notes, me.status, me.option_name, me.template_id, me.template_name
+             FROM meal_entries me
+             WHERE me.date BETWEEN ? AND ?
+             ORDER BY me.date, me.id",
+        )
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let slot_type_str: String = row.try_get("slot_type")?;
+                let slot_type =
+                    SlotType::from_db_string(&slot_type_str).map_err(sqlx::Error::Protocol)?;
+                let location_str: String = row.try_get("location")?;
+                let location =
+                    LocationType::from_db_string(&location_str).map_err(sqlx::Error::Protocol)?;
+                let status_str: String = row.try_get("status")?;
+                let status =
+                    EntryStatus::from_db_string(&status_str).map_err(sqlx::Error::Protocol)?;
+                let meal_option_id: Option<i64> = row.try_get("meal_option_id")?;
+
+                Ok((
+                    row.try_get("date")?,
+                    slot_type,
+                    WeekSummaryEntry {
+                        entry_id: row.try_get("id")?,
+                        meal_option_id,
+                        option_name: row.try_get("option_name")?,
+                        template_id: row.try_get("template_id")?,
+                        template_name: row.try_get("template_name")?,
+                        location,
+                        servings: row.try_get("servings")?,
+                        notes: row.try_get("notes")?,
+                        status,
+                        off_plan: meal_option_id.is_none(),
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    /// Eaten uses per template, for templates with a limit or any use this week
+    async fn query_template_usage(
+        pool: &SqlitePool,
+        week: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> sqlx::Result<Vec<WeeklyTemplateUsage>> {
+        sqlx::query_as::<_, WeeklyTemplateUsage>(
+            "SELECT mt.id AS template_id, mt.name AS template_name, ? AS week,
+                    COUNT(me.id) AS usage_count, mt.weekly_limit
+             FROM meal_templates mt
+             LEFT JOIN meal_entries me ON me.template_id = mt.id
+                  AND me.status = 'eaten' AND me.date BETWEEN ? AND ?
+             GROUP BY mt.id, mt.name, mt.weekly_limit
+             HAVING mt.weekly_limit IS NOT NULL OR COUNT(me.id) > 0
+             ORDER BY mt.name",
+        )
+        .bind(week)
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Eaten uses per tag (rolled up over child tags), for tags with a
+    /// suggestion (including zero) or any use this week
+    async fn query_tag_usage(
+        pool: &SqlitePool,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> sqlx::Result<Vec<WeeklyTagSuggestionUsage>> {
+        let rows = sqlx::query(
+            "WITH RECURSIVE
+             closure(ancestor_id, tag_id) AS (
+                 SELECT id, id FROM tags
+                 UNION
+                 SELECT c.ancestor_id, t.id
+                 FROM tags t JOIN closure c ON t.parent_tag_id = c.tag_id
+             ),
+             week_entries(entry_id, tag_id) AS (
+                 SELECT me.id, met.tag_id
+                 FROM meal_entries me
+                 JOIN meal_entry_tags met ON met.meal_entry_id = me.id
+                 WHERE me.status = 'eaten' AND me.date BETWEEN ? AND ?
+             )
+             SELECT t.id, t.name, t.display_name, t.weekly_suggestion,
+                    COUNT(DISTINCT we.entry_id) AS usage_count
+             FROM tags t
+             JOIN closure c ON c.ancestor_id = t.id
+             LEFT JOIN week_entries we ON we.tag_id = c.tag_id
+             GROUP BY t.id, t.name, t.display_name, t.weekly_suggestion
+             HAVING t.weekly_suggestion IS NOT NULL OR COUNT(DISTINCT we.entry_id) > 0
+             ORDER BY t.name",
+        )
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let weekly_suggestion: Option<i32> = row.try_get("weekly_suggestion")?;
+                let usage_count: i64 = row.try_get("usage_count")?;
+
+                Ok(WeeklyTagSuggestionUsage {
+                    tag_id: row.try_get("id")?,
+                    tag_name: row.try_get("name")?,
+                    display_name: row.try_get("display_name")?,
+                    weekly_suggestion,
+                    usage_count,
+                    avoid: weekly_suggestion == Some(0),
+                    exceeded: weekly_suggestion.is_some_and(|s| usage_count > s as i64),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        CreateMealEntry, CreateMealOption, CreateMealSlot, CreateMealTemplate, CreateOffPlanEntry,
+        CreateTag, TagCategory, UpdateMealSlot,
+    };
+    use crate::repository::{
+        MealEntryRepository, MealOptionRepository, MealTemplateRepository, TagRepository,
+    };
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect(":memory:")
+            .await
+            .expect("Failed to create test pool");
+
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
+
+        pool
+    }
+
+    async fn create_option(
+        pool: &SqlitePool,
+        template_name: &str,
+        option_name: &str,
+        weekly_limit: Option<i32>,
+    ) -> i64 {
+        let template = MealTemplateRepository::create(
+            pool,
+            CreateMealTemplate {
+                name: template_name.to_string(),
+                description: None,
+                compatible_slots: SlotType::defaults().to_vec(),
+                location_type: LocationType::Any,
+                weekly_limit,
+            },
+        )
+        .await
+        .unwrap();
+
+        MealOptionRepository::create(
+            pool,
+            CreateMealOption {
+                template_id: template.id,
+                name: option_name.to_string(),
+                description: None,
+                nutritional_notes: None,
+            },
+        )
+        .await
+        .unwrap()
+        .id
+    }
+
+    async fn create_tag(
+        pool: &SqlitePool,
+        name: &str,
+        suggestion: Option<i32>,
+        parent_tag_id: Option<i64>,
+    ) -> i64 {
+        TagRepository::create(
+            pool,
+            CreateTag {
+                name: name.to_string(),
+                display_name: name.to_string(),
+                category: TagCategory::Ingredient,
+                weekly_suggestion: suggestion,
+                parent_tag_id,
+            },
+        )
+        .await
+        .unwrap()
+        .id
+    }
+
+    async fn add_entry(
+        pool: &SqlitePool,
+        option_id: i64,
+        date: NaiveDate,
+        slot_type: SlotType,
+        status: EntryStatus,
+    ) {
+        MealEntryRepository::create(
+            pool,
+            CreateMealEntry {
+                meal_option_id: option_id,
+                date,
+                slot_type,
+                location: LocationType::Home,
+                servings: None,
+                notes: None,
+                status: Some(status),
+                extra_options: vec![],
+            },
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_empty_week() {
+        let pool = setup_test_pool().await;
+        let monday = NaiveDate::from_ymd_opt(2024, 11, 4).unwrap();
+
+        let summary = WeekSummaryService::get_week_summary(&pool, monday)
+            .await
+            .unwrap();
+
+        assert_eq!(summary.week, "2024-45");
+        assert_eq!(summary.days.len(), 7);
+        assert!(summary.days.iter().all(|d| d.slots.len() == 5));
+        assert_eq!(
+            summary.end_date,
+            NaiveDate::from_ymd_opt(2024, 11, 10).unwrap()
+        );
+        assert_eq!(summary.total_entries, 0);
+        assert_eq!(summary.completion_ratio, 0.0);
+        assert!(summary.template_usage.is_empty());
+        assert!(summary.tag_usage.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_week_summary() {
+        let pool = setup_test_pool().await;
+        let monday = NaiveDate::from_ymd_opt(2024, 11, 4).unwrap();
+
+        let pasta = create_option(&pool, "Pasta", "Pasta al pomodoro", Some(2)).await;
+        let yogurt = create_option(&pool, "Yogurt", "Yogurt greco", None).await;
+        let _unused = create_option(&pool, "Pesce", "Salmone", Some(3)).await;
+
+        let pasta_tag = create_tag(&pool, "pasta", Some(1), None).await;
+        let wholegrain_tag = create_tag(&pool, "pasta_integrale", None, Some(pasta_tag)).await;
+        let _fried_tag = create_tag(&pool, "fritto", Some(0), None).await;
+        MealOptionRepository::add_tags(&pool, pasta, vec![wholegrain_tag])
+            .await
+            .unwrap();
+
+        add_entry(
+            &pool,
+            yogurt,
+            monday,
+            SlotType::BREAKFAST,
+            EntryStatus::Eaten,
+        )
+        .await;
+        add_entry(&pool, pasta, monday, SlotType::LUNCH, EntryStatus::Eaten).await;
+        add_entry(
+            &pool,
+            pasta,
+            monday + Duration::days(2),
+            SlotType::DINNER,
+            EntryStatus::Eaten,
+        )
+        .await;
+        add_entry(
+            &pool,
+            yogurt,
+            monday + Duration::days(6),
+            SlotType::BREAKFAST,
+            EntryStatus::Planned,
+        )
+        .await;
+        // Next week, not part of the summary
+        add_entry(
+            &pool,
+            pasta,
+            monday + Duration::days(7),
+            SlotType::LUNCH,
+            EntryStatus::Eaten,
+        )
+        .await;
+
+        // Any day of the week works
+        let summary = WeekSummaryService::get_week_summary(&pool, monday + Duration::days(3))
+            .await
+            .unwrap();
+
+        assert_eq!(summary.start_date, monday);
+        assert_eq!(summary.total_entries, 4);
+        assert_eq!(summary.completed_entries, 3);
+        assert_eq!(summary.completion_ratio, 0.75);
+
+        // Grid
+        let monday_lunch = &summary.days[0].slots[2];
+        assert_eq!(monday_lunch.slot_type, SlotType::LUNCH);
+        assert_eq!(monday_lunch.entries.len(), 1);
+        assert_eq!(monday_lunch.entries[0].option_name, "Pasta al pomodoro");
+        assert_eq!(monday_lunch.entries[0].template_name, "Pasta");
+        let sunday_breakfast = &summary.days[6].slots[0];
+        assert_eq!(sunday_breakfast.entries[0].status, EntryStatus::Planned);
+        assert!(summary.days[1].slots.iter().all(|s| s.entries.is_empty()));
+
+        // Template usage: limited templates (used or not) and used ones
+        let usage: Vec<(&str, i64, Option<i32>)> = summary
+            .template_usage
+            .iter()
+            .map(|u| (u.template_name.as_str(), u.usage_count, u.weekly_limit))
+            .collect();
+        assert_eq!(
+            usage,
+            vec![
+                ("Pasta", 2, Some(2)),
+                ("Pesce", 0, Some(3)),
+                ("Yogurt", 1, None),
+            ]
+        );
+
+        // Tag usage: child tag rolls up into pasta, avoid tag listed with no use
+        let pasta_usage = summary
+            .tag_usage
+            .iter()
+            .find(|t| t.tag_name == "pasta")
+            .unwrap();
+        assert_eq!(pasta_usage.usage_count, 2);
+        assert!(pasta_usage.exceeded);
+
+        let fried = summary
+            .tag_usage
+            .iter()
+            .find(|t| t.tag_name == "fritto")
+            .unwrap();
+        assert!(fried.avoid);
+        assert!(!fried.exceeded);
+        assert_eq!(fried.usage_count, 0);
+
+        assert_eq!(summary.tag_usage.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_off_plan_entries_flagged() {
+        let pool = setup_test_pool().await;
+        let monday = NaiveDate::from_ymd_opt(2024, 11, 4).unwrap();
+        let pasta = create_option(&pool, "Pasta", "Pasta al pomodoro", Some(2)).await;
+        let fried_tag = create_tag(&pool, "fritto", Some(0), None).await;
+
+        add_entry(&pool, pasta, monday, SlotType::LUNCH, EntryStatus::Eaten).await;
+        MealEntryRepository::create_off_plan(
+            &pool,
+            CreateOffPlanEntry {
+                date: monday,
+                slot_type: SlotType::DINNER,
+                location: LocationType::Restaurant,
+                description: "Fritto misto con i colleghi".to_string(),
+                servings: None,
+                notes: None,
+                status: None,
+                tag_ids: vec![fried_tag],
+                estimated_nutrients: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let summary = WeekSummaryService::get_week_summary(&pool, monday)
+            .await
+            .unwrap();
+
+        assert_eq!(summary.total_entries, 2);
+        assert_eq!(summary.completed_entries, 2);
+        assert_eq!(summary.off_plan_entries, 1);
+
+        let dinner = &summary.days[0].slots[4].entries[0];
+        assert!(dinner.off_plan);
+        assert_eq!(dinner.meal_option_id, None);
+        assert_eq!(dinner.option_name, "Fritto misto con i colleghi");
+        assert!(!summary.days[0].slots[2].entries[0].off_plan);
+
+        // Estimated tags count; templates only see the planned lunch
+        let fried = summary
+            .tag_usage
+            .iter()
+            .find(|t| t.tag_name == "fritto")
+            .unwrap();
+        assert_eq!(fried.usage_count, 1);
+        assert!(fried.exceeded);
+        let usage: Vec<(&str, i64)> = summary
+            .template_usage
+            .iter()
+            .map(|u| (u.template_name.as_str(), u.usage_count))
+            .collect();
+        assert_eq!(usage, vec![("Pasta", 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_configured_slots() {
+        let pool = setup_test_pool().await;
+        let monday = NaiveDate::from_ymd_opt(2024, 11, 4).unwrap();
+        let option = create_option(&pool, "Yogurt", "Yogurt bianco", None).await;
+
+        MealSlotRepository::create(
+            &pool,
+            CreateMealSlot {
+                name: "pre_workout".to_string(),
+                display_name: "Pre-workout".to_string(),
+                sort_order: 45,
+                active: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        // Used this week, then deactivated
+        add_entry(
+            &pool,
+            option,
+            monday,
+            SlotType::MORNING_SNACK,
+            EntryStatus::Eaten,
+        )
+        .await;
+        let snack = MealSlotRepository::get_by_name(&pool, "morning_snack")
+            .await
+            .unwrap()
+            .unwrap();
+        MealSlotRepository::update(
+            &pool,
+            snack.id,
+            UpdateMealSlot {
+                display_name: None,
+                sort_order: None,
+                active: Some(false),
+            },
+        )
+        .await
+        .unwrap();
+
+        let slot_names = |summary: &WeekSummary| -> Vec<String> {
+            summary.days[0]
+                .slots
+                .iter()
+                .map(|s| s.slot_type.to_string())
+                .collect()
+        };
+
+        // The inactive slot still shows where it holds entries
+        let summary = WeekSummaryService::get_week_summary(&pool, monday)
+            .await
+            .unwrap();
+        assert_eq!(
+            slot_names(&summary),
+            vec![
+                "breakfast",
+                "morning_snack",
+                "lunch",
+                "afternoon_snack",
+                "pre_workout",
+                "dinner"
+            ]
+        );
+        assert_eq!(summary.days[0].slots[1].entries.len(), 1);
+        assert_eq!(summary.days[0].slots[4].display_name, "Pre-workout");
+
+        // ...and is gone from weeks without any
+        let next_week = WeekSummaryService::get_week_summary(&pool, monday + Duration::days(7))
+            .await
+            .unwrap();
+        assert_eq!(
+            slot_names(&next_week),
+            vec![
+                "breakfast",
+                "lunch",
+                "afternoon_snack",
+                "pre_workout",
+                "dinner"
+            ]
+        );
+    }
+}
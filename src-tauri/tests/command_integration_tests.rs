@@ -45,7 +45,7 @@ fn test_enum_serialization() {
     // Test that enum types can be serialized/deserialized for IPC
 
     // SlotType
-    let slot = SlotType::Breakfast;
+    let slot = SlotType::BREAKFAST;
     let json = serde_json::to_string(&slot).unwrap();
     let deserialized: SlotType = serde_json::from_str(&json).unwrap();
     assert_eq!(slot, deserialized);
@@ -83,7 +83,7 @@ fn test_create_types_serialization() {
     let create_template = CreateMealTemplate {
         name: "Test Template".to_string(),
         description: Some("Test".to_string()),
-        compatible_slots: vec![SlotType::Breakfast],
+        compatible_slots: vec![SlotType::BREAKFAST],
         location_type: LocationType::Home,
         weekly_limit: Some(3),
     };
@@ -132,6 +132,7 @@ fn test_model_serialization() {
         category: TagCategory::Ingredient,
         parent_tag_id: None,
         weekly_suggestion: Some(3),
+        archived: false,
         created_at: chrono::Utc::now(),
     };
     let json = serde_json::to_string(&tag).unwrap();
@@ -144,9 +145,10 @@ fn test_model_serialization() {
         id: 1,
         name: "Test".to_string(),
         description: None,
-        compatible_slots: vec![SlotType::Breakfast],
+        compatible_slots: vec![SlotType::BREAKFAST],
         location_type: LocationType::Home,
         weekly_limit: Some(3),
+        archived: false,
         created_at: chrono::Utc::now(),
         updated_at: chrono::Utc::now(),
     };
@@ -188,14 +190,14 @@ fn test_weekly_tag_usage_serialization() {
 #[test]
 fn test_enum_database_conversion() {
     // Test enum conversion functions
-    assert_eq!(SlotType::Breakfast.to_db_string(), "breakfast");
+    assert_eq!(SlotType::BREAKFAST.to_db_string(), "breakfast");
     assert_eq!(LocationType::Home.to_db_string(), "home");
     assert_eq!(TagCategory::Ingredient.to_db_string(), "ingredient");
 
     // Test from_db_string
     assert_eq!(
         SlotType::from_db_string("breakfast").ok(),
-        Some(SlotType::Breakfast)
+        Some(SlotType::BREAKFAST)
     );
     assert_eq!(
         LocationType::from_db_string("home").ok(),
@@ -210,13 +212,13 @@ fn test_enum_database_conversion() {
 #[test]
 fn test_slot_type_all() {
     // Test that all slot types are included
-    let all_slots = SlotType::all();
+    let all_slots = SlotType::defaults();
     assert_eq!(all_slots.len(), 5);
-    assert!(all_slots.contains(&SlotType::Breakfast));
-    assert!(all_slots.contains(&SlotType::MorningSnack));
-    assert!(all_slots.contains(&SlotType::Lunch));
-    assert!(all_slots.contains(&SlotType::AfternoonSnack));
-    assert!(all_slots.contains(&SlotType::Dinner));
+    assert!(all_slots.contains(&SlotType::BREAKFAST));
+    assert!(all_slots.contains(&SlotType::MORNING_SNACK));
+    assert!(all_slots.contains(&SlotType::LUNCH));
+    assert!(all_slots.contains(&SlotType::AFTERNOON_SNACK));
+    assert!(all_slots.contains(&SlotType::DINNER));
 }
 
 #[test]
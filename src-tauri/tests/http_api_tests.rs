@@ -0,0 +1,303 @@
+// Integration tests for the HTTP/JSON API server
+// Each test serves the API on an ephemeral localhost port and calls it over HTTP
+#![cfg(feature = "http-server")]
+
+use nutrition_helper::db::{self, BackupConfig};
+use nutrition_helper::models::{EntryStatus, MealEntry, MealOption, MealTemplate};
+use nutrition_helper::repository::Backend;
+use nutrition_helper::server;
+use reqwest::{Client, StatusCode};
+use serde_json::{json, Value};
+use tempfile::TempDir;
+use tokio::net::TcpListener;
+
+const TOKEN: &str = "test-token-0123456789";
+
+struct TestServer {
+    base: String, // "http://127.0.0.1:<port>/api"
+    client: Client,
+    _temp_dir: TempDir,
+}
+
+impl TestServer {
+    async fn start() -> Self {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("api.db");
+        let pool = db::initialize_database(db_path.clone()).await.unwrap();
+        let router = server::router(
+            Backend::Sqlite(pool),
+            BackupConfig::for_database(&db_path),
+            TOKEN,
+        );
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}/api", listener.local_addr().unwrap());
+        tokio::spawn(server::serve(listener, router));
+
+        Self {
+            base,
+            client: Client::new(),
+            _temp_dir: temp_dir,
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base, path)
+    }
+
+    async fn get(&self, path: &str) -> (StatusCode, Value) {
+        let response = self
+            .client
+            .get(self.url(path))
+            .bearer_auth(TOKEN)
+            .send()
+            .await
+            .unwrap();
+        (response.status(), response.json().await.unwrap())
+    }
+
+    async fn send(&self, method: reqwest::Method, path: &str, body: Value) -> (StatusCode, Value) {
+        let response = self
+            .client
+            .request(method, self.url(path))
+            .bearer_auth(TOKEN)
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+        (response.status(), response.json().await.unwrap())
+    }
+
+    async fn post(&self, path: &str, body: Value) -> (StatusCode, Value) {
+        self.send(reqwest::Method::POST, path, body).await
+    }
+
+    /// A lunch template with a weekly limit of one and a single option
+    async fn create_pasta(&self) -> (MealTemplate, MealOption) {
+        let (status, template) = self
+            .post(
+                "/templates",
+                json!({
+                    "name": "Pasta",
+                    "description": null,
+                    "compatible_slots": ["lunch"],
+                    "location_type": "any",
+                    "weekly_limit": 1
+                }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        let template: MealTemplate = serde_json::from_value(template).unwrap();
+
+        let (status, option) = self
+            .post(
+                "/options",
+                json!({
+                    "template_id": template.id,
+                    "name": "Pasta al pomodoro",
+                    "description": null,
+                    "nutritional_notes": null
+                }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+
+        (template, serde_json::from_value(option).unwrap())
+    }
+}
+
+#[tokio::test]
+async fn test_requires_bearer_token() {
+    let server = TestServer::start().await;
+
+    let response = server.client.get(server.url("/tags")).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["type"], "Unauthorized");
+
+    let response = server
+        .client
+        .get(server.url("/tags"))
+        .bearer_auth("wrong-token-0123456789")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let (status, tags) = server.get("/tags").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(tags, json!([]));
+
+    let (status, body) = server.get("/no-such-endpoint").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["type"], "NotFound");
+}
+
+#[tokio::test]
+async fn test_log_entries_like_the_commands() {
+    let server = TestServer::start().await;
+    let (template, option) = server.create_pasta().await;
+
+    let (status, templates) = server.get("/templates?slot=lunch").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(templates[0]["id"], template.id);
+
+    // Validated and answered as [entry, warnings], like create_entry
+    let (status, created) = server
+        .post(
+            "/entries",
+            json!({
+                "meal_option_id": option.id,
+                "date": "2024-11-04",
+                "slot_type": "lunch",
+                "location": "home",
+                "servings": null,
+                "notes": null,
+                "status": "eaten"
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let entry: MealEntry = serde_json::from_value(created[0].clone()).unwrap();
+    assert_eq!(entry.status, EntryStatus::Eaten);
+    assert_eq!(created[1], json!([]));
+
+    // The weekly limit is a business validation error with the IPC serialization
+    let (status, error) = server
+        .post(
+            "/entries/validate",
+            json!({ "meal_option_id": option.id, "slot": "lunch", "date": "2024-11-06" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(error["type"], "BusinessValidationError");
+
+    let (status, entries) = server.get("/entries?date=2024-11-04&slot=lunch").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(entries.as_array().unwrap().len(), 1);
+
+    let (status, summary) = server.get("/weeks/2024-45/summary").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(summary["completed_entries"], 1);
+
+    let (status, error) = server.get(&format!("/entries/{}", entry.id + 1)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(error["type"], "NotFound");
+
+    // Logged options cannot be deleted
+    let response = server
+        .client
+        .delete(server.url(&format!("/options/{}", option.id)))
+        .bearer_auth(TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let error: Value = response.json().await.unwrap();
+    assert_eq!(error["type"], "DeleteBlocked");
+    assert_eq!(error["message"]["id"], option.id);
+}
+
+#[tokio::test]
+async fn test_undo_and_history() {
+    let server = TestServer::start().await;
+    let (template, _) = server.create_pasta().await;
+
+    let (status, _) = server
+        .send(
+            reqwest::Method::PUT,
+            &format!("/templates/{}", template.id),
+            json!({ "name": "Pasta bake" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, undone) = server.post("/undo", json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(undone["command"], "update_template");
+    let (_, restored) = server.get(&format!("/templates/{}", template.id)).await;
+    assert_eq!(restored["name"], "Pasta");
+
+    let (status, redone) = server.post("/redo", json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(redone["command"], "update_template");
+
+    let (status, history) = server
+        .get(&format!("/history/template/{}", template.id))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let commands: Vec<&str> = history
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|record| record["command"].as_str().unwrap())
+        .collect();
+    assert_eq!(commands, ["create_template", "update_template"]);
+    assert_eq!(history[1]["after"]["name"], "Pasta bake");
+}
+
+#[tokio::test]
+async fn test_malformed_requests() {
+    let server = TestServer::start().await;
+
+    let (status, error) = server.post("/tags", json!({ "name": "pasta" })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(error["type"], "ValidationError");
+
+    let (status, error) = server.get("/entries?date=04-11-2024").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(error["type"], "ValidationError");
+
+    let (status, error) = server.get("/entries?status=eaten&meal_option_id=1").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(error["type"], "ValidationError");
+}
+
+#[tokio::test]
+async fn test_csv_export_in_response_body() {
+    let server = TestServer::start().await;
+    let (_, option) = server.create_pasta().await;
+    server
+        .post(
+            "/entries/off-plan",
+            json!({
+                "date": "2024-11-05",
+                "slot_type": "dinner",
+                "location": "restaurant",
+                "description": "Pizza margherita"
+            }),
+        )
+        .await;
+    server
+        .post(
+            "/entries",
+            json!({
+                "meal_option_id": option.id,
+                "date": "2024-11-04",
+                "slot_type": "lunch",
+                "location": "home",
+                "servings": null,
+                "notes": null,
+                "status": "planned"
+            }),
+        )
+        .await;
+
+    let response = server
+        .client
+        .get(server.url("/export/entries.csv?start_date=2024-11-04&end_date=2024-11-10"))
+        .bearer_auth(TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["content-type"],
+        "text/csv; charset=utf-8"
+    );
+    let csv = response.text().await.unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[1].starts_with("2024-11-04,lunch,home,Pasta,Pasta al pomodoro"));
+    assert!(lines[2].contains("Pizza margherita"));
+}
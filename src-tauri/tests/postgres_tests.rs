@@ -0,0 +1,608 @@
+// Integration tests for the PostgreSQL backend
+// Each test runs in its own schema of the database named by NUTRITION_TEST_POSTGRES_URL
+// and is skipped when the variable is not set; the same scenarios run against SQLite
+#![cfg(feature = "postgres")]
+
+use chrono::NaiveDate;
+use nutrition_helper::db::{self, postgres};
+use nutrition_helper::models::*;
+use nutrition_helper::repository::{
+    Backend, MealEntryStore, MealOptionStore, MealTemplateStore, Store, SyncRepository, TagStore,
+    SQLITE_ONLY_COMMANDS,
+};
+use nutrition_helper::services::{ValidationError, ValidationService};
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::{PgPool, SqlitePool};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tempfile::TempDir;
+
+const URL_ENV: &str = "NUTRITION_TEST_POSTGRES_URL";
+
+/// A migrated PostgreSQL schema, dropped when the test is done
+struct TestSchema {
+    pool: PgPool,
+    admin: PgPool,
+    name: String,
+}
+
+impl TestSchema {
+    async fn create() -> Option<Self> {
+        let Ok(url) = std::env::var(URL_ENV) else {
+            eprintln!("{} not set, skipping PostgreSQL test", URL_ENV);
+            return None;
+        };
+
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let name = format!(
+            "nutrition_test_{}_{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst)
+        );
+
+        let admin = PgPool::connect(&url).await.unwrap();
+        sqlx::query(&format!("DROP SCHEMA IF EXISTS {} CASCADE", name))
+            .execute(&admin)
+            .await
+            .unwrap();
+        sqlx::query(&format!("CREATE SCHEMA {}", name))
+            .execute(&admin)
+            .await
+            .unwrap();
+
+        let options = PgConnectOptions::from_str(&url)
+            .unwrap()
+            .options([("search_path", name.as_str())]);
+        let pool = PgPoolOptions::new()
+            .max_connections(2)
+            .connect_with(options)
+            .await
+            .unwrap();
+        postgres::migrate(&pool).await.unwrap();
+
+        Some(Self { pool, admin, name })
+    }
+
+    async fn drop(self) {
+        self.pool.close().await;
+        sqlx::query(&format!("DROP SCHEMA {} CASCADE", self.name))
+            .execute(&self.admin)
+            .await
+            .unwrap();
+    }
+}
+
+async fn sqlite_pool() -> (SqlitePool, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let pool = db::initialize_database(temp_dir.path().join("test.db"))
+        .await
+        .unwrap();
+    (pool, temp_dir)
+}
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 11, day).unwrap()
+}
+
+/// Build a small library and log a week of meals through the traits only
+/// Returns the ids of the tags, template and options it created
+async fn log_week<S>(store: &S) -> (i64, i64, i64, i64, i64)
+where
+    S: TagStore + MealTemplateStore + MealOptionStore + MealEntryStore,
+{
+    let pasta = store
+        .create_tag(CreateTag {
+            name: "pasta".to_string(),
+            display_name: "Pasta".to_string(),
+            category: TagCategory::Ingredient,
+            weekly_suggestion: Some(3),
+            parent_tag_id: None,
+        })
+        .await
+        .unwrap();
+    let wholegrain = store
+        .create_tag(CreateTag {
+            name: "pasta_integrale".to_string(),
+            display_name: "Pasta Integrale".to_string(),
+            category: TagCategory::Ingredient,
+            weekly_suggestion: None,
+            parent_tag_id: Some(pasta.id),
+        })
+        .await
+        .unwrap();
+
+    let template = store
+        .create_template(CreateMealTemplate {
+            name: "Primo".to_string(),
+            description: Some("Pasta dish".to_string()),
+            compatible_slots: vec![SlotType::DINNER, SlotType::LUNCH],
+            location_type: LocationType::Any,
+            weekly_limit: Some(2),
+        })
+        .await
+        .unwrap();
+
+    let pomodoro = store
+        .create_option(CreateMealOption {
+            template_id: template.id,
+            name: "Pasta al pomodoro".to_string(),
+            description: None,
+            nutritional_notes: None,
+        })
+        .await
+        .unwrap();
+    let integrale = store
+        .create_option(CreateMealOption {
+            template_id: template.id,
+            name: "Pasta integrale".to_string(),
+            description: None,
+            nutritional_notes: Some("80g".to_string()),
+        })
+        .await
+        .unwrap();
+    store
+        .add_option_tags(pomodoro.id, vec![pasta.id])
+        .await
+        .unwrap();
+    store
+        .add_option_tags(integrale.id, vec![wholegrain.id, wholegrain.id])
+        .await
+        .unwrap();
+
+    for (day, option_id, status) in [
+        (4, pomodoro.id, EntryStatus::Eaten),
+        (5, integrale.id, EntryStatus::Eaten),
+        (6, pomodoro.id, EntryStatus::Planned),
+    ] {
+        store
+            .create_entry(CreateMealEntry {
+                meal_option_id: option_id,
+                date: date(day),
+                slot_type: SlotType::LUNCH,
+                location: LocationType::Home,
+                servings: None,
+                notes: None,
+                status: Some(status),
+                extra_options: vec![],
+            })
+            .await
+            .unwrap();
+    }
+
+    store
+        .create_off_plan_entry(CreateOffPlanEntry {
+            date: date(5),
+            slot_type: SlotType::DINNER,
+            location: LocationType::Restaurant,
+            description: "Pizza margherita".to_string(),
+            servings: None,
+            notes: None,
+            status: None,
+            tag_ids: vec![],
+            estimated_nutrients: Some(SetNutrientProfile {
+                kcal: 850.0,
+                protein_g: 30.0,
+                carbs_g: 110.0,
+                fat_g: 28.0,
+                fiber_g: 5.0,
+            }),
+        })
+        .await
+        .unwrap();
+
+    (
+        pasta.id,
+        wholegrain.id,
+        template.id,
+        pomodoro.id,
+        integrale.id,
+    )
+}
+
+/// The behavior every backend shares
+async fn check_backend<S: Store>(store: &S) {
+    let (pasta, wholegrain, template, pomodoro, integrale) = log_week(store).await;
+
+    // Tags
+    let children = store.get_child_tags(pasta).await.unwrap();
+    assert_eq!(children.len(), 1);
+    assert_eq!(children[0].id, wholegrain);
+    let lineage = store
+        .get_option_tags_with_ancestors(integrale)
+        .await
+        .unwrap();
+    assert_eq!(lineage.len(), 2);
+    let cycle = store
+        .update_tag(
+            pasta,
+            UpdateTag {
+                display_name: None,
+                category: None,
+                weekly_suggestion: None,
+                parent_tag_id: Some(Some(wholegrain)),
+            },
+        )
+        .await;
+    assert!(matches!(cycle, Err(sqlx::Error::Protocol(_))));
+    assert!(store
+        .get_tag_delete_blockers(pasta)
+        .await
+        .unwrap()
+        .is_some());
+
+    // Templates and options
+    let primo = store.get_template(template).await.unwrap().unwrap();
+    assert_eq!(
+        primo.compatible_slots,
+        vec![SlotType::LUNCH, SlotType::DINNER]
+    );
+    assert_eq!(
+        store.search_templates("PRIMO", None).await.unwrap().len(),
+        1
+    );
+    assert_eq!(
+        store
+            .get_templates_by_slot_and_location(&SlotType::BREAKFAST, LocationType::Home)
+            .await
+            .unwrap(),
+        vec![]
+    );
+    assert_eq!(store.search_options("integrale").await.unwrap().len(), 1);
+    let with_tags = store
+        .get_option_with_tags(integrale)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(with_tags.tags, vec![wholegrain]);
+
+    let renamed = store
+        .update_option(
+            integrale,
+            UpdateMealOption {
+                name: Some("Pasta integrale al pesto".to_string()),
+                description: None,
+                nutritional_notes: Some(None),
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(renamed.nutritional_notes, None);
+    let nutrients = store
+        .set_option_nutrients(
+            pomodoro,
+            SetNutrientProfile {
+                kcal: 520.0,
+                protein_g: 16.0,
+                carbs_g: 90.0,
+                fat_g: 10.0,
+                fiber_g: 6.0,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(nutrients.kcal, 520.0);
+
+    let groups = store
+        .set_option_groups(
+            template,
+            vec![OptionGroup {
+                name: "base".to_string(),
+                min_choices: 1,
+                max_choices: Some(1),
+            }],
+        )
+        .await
+        .unwrap();
+    assert_eq!(groups.len(), 1);
+    let grouped = store
+        .set_option_group(pomodoro, Some("base".to_string()))
+        .await
+        .unwrap();
+    assert_eq!(grouped.option_group.as_deref(), Some("base"));
+
+    // Entries keep the snapshot taken when they were logged
+    let week = store
+        .get_entries_by_date_range(date(4), date(10))
+        .await
+        .unwrap();
+    assert_eq!(week.len(), 4);
+    assert_eq!(week[0].option_name, "Pasta al pomodoro");
+    assert_eq!(week[0].tag_names, vec!["pasta".to_string()]);
+    assert_eq!(week[1].option_name, "Pasta integrale");
+    let off_plan = week.iter().find(|e| e.is_off_plan()).unwrap();
+    assert_eq!(off_plan.estimated_nutrients.as_ref().unwrap().kcal, 850.0);
+
+    // Weekly usage rolls tags up to their parent
+    let usage = store
+        .get_weekly_template_usage(template, "2024-45")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(usage.usage_count, 2);
+    assert_eq!(usage.weekly_limit, Some(2));
+
+    // The validation rules read through the store
+    let full =
+        ValidationService::validate_meal_entry(store, pomodoro, &SlotType::LUNCH, date(7)).await;
+    assert!(matches!(
+        full,
+        Err(ValidationError::WeeklyLimitExceeded { limit: 2, .. })
+    ));
+    let wrong_slot =
+        ValidationService::validate_meal_entry(store, pomodoro, &SlotType::BREAKFAST, date(11))
+            .await;
+    assert!(matches!(
+        wrong_slot,
+        Err(ValidationError::IncompatibleSlot { .. })
+    ));
+    ValidationService::validate_meal_entry(store, pomodoro, &SlotType::LUNCH, date(11))
+        .await
+        .unwrap();
+    let pasta_usage = store
+        .get_weekly_tag_usage(pasta, "2024-45")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(pasta_usage.usage_count, 2);
+    let breakdown = store
+        .get_weekly_tag_usage_breakdown(wholegrain, "2024-45")
+        .await
+        .unwrap();
+    assert_eq!(breakdown.len(), 2);
+    assert_eq!(breakdown[0].tag_id, pasta);
+    assert_eq!(
+        (breakdown[0].direct_count, breakdown[0].total_count),
+        (1, 2)
+    );
+    assert_eq!(
+        store
+            .get_all_weekly_template_usage("2024-46")
+            .await
+            .unwrap()[0]
+            .usage_count,
+        0
+    );
+
+    // Logging a swap keeps the plan
+    let planned = &store
+        .get_entries_by_status(EntryStatus::Planned)
+        .await
+        .unwrap()[0];
+    let logged = store
+        .log_actual(
+            planned.id,
+            LogActualMeal {
+                meal_option_id: integrale,
+                servings: Some(1.5),
+                extra_options: vec![],
+                location: None,
+                notes: Some(Some("Swapped".to_string())),
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(logged.status, EntryStatus::Eaten);
+    assert_eq!(logged.option_name, "Pasta integrale al pesto");
+    assert_eq!(logged.planned_options[0].meal_option_id, pomodoro);
+    assert_eq!(logged.options[0].servings, 1.5);
+    assert_eq!(
+        store.get_entries_by_option(integrale).await.unwrap().len(),
+        2
+    );
+
+    let updated = store
+        .update_entry(
+            logged.id,
+            UpdateMealEntry {
+                location: Some(LocationType::Office),
+                servings: None,
+                notes: Some(None),
+                status: None,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(updated.location, LocationType::Office);
+    assert_eq!(updated.notes, None);
+
+    assert!(store
+        .get_option_delete_blockers(pomodoro)
+        .await
+        .unwrap()
+        .is_some());
+    store.delete_entry(updated.id).await.unwrap();
+    assert!(matches!(
+        store.delete_entry(updated.id).await,
+        Err(sqlx::Error::RowNotFound)
+    ));
+    assert_eq!(store.get_recent_entries(10).await.unwrap().len(), 2);
+
+    // Slots
+    let slot = store
+        .create_slot(CreateMealSlot {
+            name: "pre_workout".to_string(),
+            display_name: "Pre-workout".to_string(),
+            sort_order: 35,
+            active: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(store.get_slots().await.unwrap()[3].id, slot.id);
+    store
+        .update_slot(
+            slot.id,
+            UpdateMealSlot {
+                display_name: None,
+                sort_order: None,
+                active: Some(false),
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(store.get_active_slots().await.unwrap().len(), 5);
+    assert!(
+        !store
+            .get_slot_by_name("pre_workout")
+            .await
+            .unwrap()
+            .unwrap()
+            .active
+    );
+    assert!(store.delete_slot(slot.id).await.unwrap());
+    assert!(!store.delete_slot(slot.id).await.unwrap());
+    let lunch = store.get_slot_by_name("lunch").await.unwrap().unwrap();
+    assert!(matches!(
+        store.delete_slot(lunch.id).await,
+        Err(sqlx::Error::Protocol(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_sqlite_backend() {
+    let (pool, _temp_dir) = sqlite_pool().await;
+    let backend = Backend::Sqlite(pool);
+    check_backend(&backend).await;
+    assert!(backend.sqlite().is_ok());
+    assert_eq!(backend.kind(), "sqlite");
+    assert!(backend.unsupported_commands().is_empty());
+}
+
+#[tokio::test]
+async fn test_postgres_backend() {
+    let Some(schema) = TestSchema::create().await else {
+        return;
+    };
+    let backend = Backend::Postgres(schema.pool.clone());
+    check_backend(&backend).await;
+
+    // Sync, undo, backups and the other SQLite-only features are refused
+    assert!(matches!(
+        backend.sqlite(),
+        Err(nutrition_helper::ApiError::ValidationError(_))
+    ));
+    assert_eq!(backend.kind(), "postgres");
+    assert_eq!(backend.unsupported_commands(), SQLITE_ONLY_COMMANDS);
+    schema.drop().await;
+}
+
+#[tokio::test]
+async fn test_migrate_sqlite_to_postgres() {
+    let Some(schema) = TestSchema::create().await else {
+        return;
+    };
+    let (sqlite, _temp_dir) = sqlite_pool().await;
+    let (pasta, wholegrain, template, pomodoro, _) = log_week(&sqlite).await;
+    sqlite
+        .set_option_groups(
+            template,
+            vec![OptionGroup {
+                name: "base".to_string(),
+                min_choices: 1,
+                max_choices: None,
+            }],
+        )
+        .await
+        .unwrap();
+
+    let copied = postgres::migrate_sqlite_to_postgres(&sqlite, &schema.pool)
+        .await
+        .unwrap();
+    let rows = |table: &str| copied.iter().find(|c| c.table == table).unwrap().rows;
+    assert_eq!(rows("meal_slots"), 5);
+    assert_eq!(rows("meal_entries"), 4);
+    assert_eq!(rows("meal_entry_nutrients"), 1);
+
+    // Same ids, snapshots and timestamps on both sides
+    let pg = &schema.pool;
+    assert_eq!(
+        pg.get_entries_by_date_range(date(1), date(30))
+            .await
+            .unwrap(),
+        sqlite
+            .get_entries_by_date_range(date(1), date(30))
+            .await
+            .unwrap()
+    );
+    assert_eq!(
+        pg.get_template(template).await.unwrap(),
+        sqlite.get_template(template).await.unwrap()
+    );
+    assert_eq!(
+        pg.get_option_groups(template).await.unwrap(),
+        sqlite.get_option_groups(template).await.unwrap()
+    );
+    assert_eq!(
+        pg.get_tag(wholegrain).await.unwrap().unwrap().parent_tag_id,
+        Some(pasta)
+    );
+    assert_eq!(
+        pg.get_weekly_tag_usage(pasta, "2024-45")
+            .await
+            .unwrap()
+            .unwrap()
+            .usage_count,
+        2
+    );
+
+    // New rows get fresh ids after the copied ones
+    let option = pg
+        .create_option(CreateMealOption {
+            template_id: template,
+            name: "Pasta al ragù".to_string(),
+            description: None,
+            nutritional_notes: None,
+        })
+        .await
+        .unwrap();
+    assert!(option.id > pomodoro);
+
+    // A database with data is never overwritten
+    let again = postgres::migrate_sqlite_to_postgres(&sqlite, pg).await;
+    assert!(matches!(again, Err(sqlx::Error::Protocol(_))));
+
+    schema.drop().await;
+}
+
+#[tokio::test]
+async fn test_migrate_refuses_a_syncing_database() {
+    let Some(schema) = TestSchema::create().await else {
+        return;
+    };
+    let (sqlite, _temp_dir) = sqlite_pool().await;
+    log_week(&sqlite).await;
+    SyncRepository::set_settings(
+        &sqlite,
+        SyncSettings {
+            server_url: Some("http://127.0.0.1:7879".to_string()),
+            token: Some("sync-token-0123456789".to_string()),
+        },
+    )
+    .await
+    .unwrap();
+
+    let refused = postgres::migrate_sqlite_to_postgres(&sqlite, &schema.pool).await;
+    assert!(matches!(refused, Err(sqlx::Error::Protocol(_))));
+    assert!(schema.pool.get_tags().await.unwrap().is_empty());
+
+    schema.drop().await;
+}
+
+#[tokio::test]
+async fn test_migrate_refuses_uncopied_columns() {
+    let Some(schema) = TestSchema::create().await else {
+        return;
+    };
+    let (sqlite, _temp_dir) = sqlite_pool().await;
+    sqlx::query("ALTER TABLE meal_options ADD COLUMN rating INTEGER")
+        .execute(&sqlite)
+        .await
+        .unwrap();
+
+    let refused = postgres::migrate_sqlite_to_postgres(&sqlite, &schema.pool).await;
+    let Err(sqlx::Error::Protocol(message)) = refused else {
+        panic!("expected the migration to be refused");
+    };
+    assert!(message.contains("meal_options.rating"));
+
+    schema.drop().await;
+}
@@ -0,0 +1,351 @@
+// End-to-end tests for multi-device sync
+// Each test runs the reference sync server on an ephemeral localhost port with two
+// device databases syncing through it
+#![cfg(all(feature = "http-server", feature = "sync"))]
+
+use chrono::NaiveDate;
+use nutrition_helper::db;
+use nutrition_helper::models::{
+    CreateMealEntry, CreateMealOption, CreateMealTemplate, CreateTag, EntryStatus, LocationType,
+    SlotType, SyncChange, SyncEntity, SyncPush, TagCategory, UpdateMealEntry, UpdateMealTemplate,
+};
+use nutrition_helper::repository::{
+    MealEntryRepository, MealOptionRepository, MealTemplateRepository, SyncRepository,
+    TagRepository,
+};
+use nutrition_helper::server::serve;
+use nutrition_helper::server::sync::{open_sync_store, sync_router};
+use nutrition_helper::services::{SyncClient, SyncService};
+use nutrition_helper::ApiError;
+use sqlx::SqlitePool;
+use tempfile::TempDir;
+use tokio::net::TcpListener;
+
+const TOKEN: &str = "sync-token-0123456789";
+
+struct TestSync {
+    url: String, // "http://127.0.0.1:<port>"
+    a: SqlitePool,
+    b: SqlitePool,
+    _temp_dir: TempDir,
+}
+
+impl TestSync {
+    async fn start() -> Self {
+        let temp_dir = TempDir::new().unwrap();
+        let store = open_sync_store(&temp_dir.path().join("relay.db"))
+            .await
+            .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(serve(listener, sync_router(store, TOKEN)));
+
+        let a = db::initialize_database(temp_dir.path().join("a.db"))
+            .await
+            .unwrap();
+        let b = db::initialize_database(temp_dir.path().join("b.db"))
+            .await
+            .unwrap();
+
+        Self {
+            url,
+            a,
+            b,
+            _temp_dir: temp_dir,
+        }
+    }
+
+    fn client(&self) -> SyncClient {
+        SyncClient::new(&self.url, TOKEN)
+    }
+
+    async fn sync(&self, pool: &SqlitePool) {
+        let report = SyncService::sync(pool, &self.client()).await.unwrap();
+        assert!(
+            report.pulled.skipped.is_empty(),
+            "{:?}",
+            report.pulled.skipped
+        );
+    }
+
+    /// Sync A, then B, then A again: both end up with every change
+    async fn sync_all(&self) {
+        self.sync(&self.a).await;
+        self.sync(&self.b).await;
+        self.sync(&self.a).await;
+    }
+}
+
+async fn uuid_of(pool: &SqlitePool, table: &str, id: i64) -> String {
+    sqlx::query_scalar(&format!("SELECT uuid FROM {} WHERE id = ?1", table))
+        .bind(id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+async fn id_of(pool: &SqlitePool, table: &str, uuid: &str) -> Option<i64> {
+    sqlx::query_scalar(&format!("SELECT id FROM {} WHERE uuid = ?1", table))
+        .bind(uuid)
+        .fetch_optional(pool)
+        .await
+        .unwrap()
+}
+
+/// A breakfast template with one tagged option, and a meal logged with it
+async fn create_breakfast(pool: &SqlitePool) -> (i64, i64, i64) {
+    let template = MealTemplateRepository::create(
+        pool,
+        CreateMealTemplate {
+            name: "Yogurt".to_string(),
+            description: None,
+            compatible_slots: vec![SlotType::BREAKFAST],
+            location_type: LocationType::Any,
+            weekly_limit: Some(5),
+        },
+    )
+    .await
+    .unwrap();
+    let option = MealOptionRepository::create(
+        pool,
+        CreateMealOption {
+            template_id: template.id,
+            name: "Greek yogurt".to_string(),
+            description: None,
+            nutritional_notes: None,
+        },
+    )
+    .await
+    .unwrap();
+    let tag = TagRepository::create(
+        pool,
+        CreateTag {
+            name: "yogurt".to_string(),
+            display_name: "Yogurt".to_string(),
+            category: TagCategory::Ingredient,
+            weekly_suggestion: Some(4),
+            parent_tag_id: None,
+        },
+    )
+    .await
+    .unwrap();
+    MealOptionRepository::set_tags(pool, option.id, vec![tag.id])
+        .await
+        .unwrap();
+    let entry = MealEntryRepository::create(
+        pool,
+        CreateMealEntry {
+            meal_option_id: option.id,
+            date: NaiveDate::from_ymd_opt(2025, 12, 2).unwrap(),
+            slot_type: SlotType::BREAKFAST,
+            location: LocationType::Home,
+            servings: None,
+            notes: None,
+            status: None,
+            extra_options: vec![],
+        },
+    )
+    .await
+    .unwrap();
+
+    (template.id, option.id, entry.id)
+}
+
+fn rename(name: &str) -> UpdateMealTemplate {
+    UpdateMealTemplate {
+        name: Some(name.to_string()),
+        description: None,
+        compatible_slots: None,
+        location_type: None,
+        weekly_limit: None,
+    }
+}
+
+#[tokio::test]
+async fn test_changes_reach_the_other_device() {
+    let sync = TestSync::start().await;
+    let (template_id, option_id, entry_id) = create_breakfast(&sync.a).await;
+    sync.sync_all().await;
+
+    let option_uuid = uuid_of(&sync.a, "meal_options", option_id).await;
+    let b_option_id = id_of(&sync.b, "meal_options", &option_uuid).await.unwrap();
+    let b_option = MealOptionRepository::get_with_tags(&sync.b, b_option_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(b_option.option.name, "Greek yogurt");
+    assert_eq!(b_option.tags.len(), 1);
+
+    let entry_uuid = uuid_of(&sync.a, "meal_entries", entry_id).await;
+    let b_entry_id = id_of(&sync.b, "meal_entries", &entry_uuid).await.unwrap();
+    let b_entry = MealEntryRepository::get_by_id(&sync.b, b_entry_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(b_entry.meal_option_id, Some(b_option_id));
+    assert_eq!(b_entry.tag_names, vec!["yogurt".to_string()]);
+
+    // B logs the meal as eaten; A gets the update
+    MealEntryRepository::update(
+        &sync.b,
+        b_entry_id,
+        UpdateMealEntry {
+            location: None,
+            servings: Some(2.0),
+            notes: None,
+            status: Some(EntryStatus::Eaten),
+        },
+    )
+    .await
+    .unwrap();
+    sync.sync_all().await;
+
+    let a_entry = MealEntryRepository::get_by_id(&sync.a, entry_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(a_entry.status, EntryStatus::Eaten);
+    assert_eq!(a_entry.servings, 2.0);
+
+    // Weekly template usage counts the synced entry like a local one
+    let week = nutrition_helper::services::ValidationService::get_week_string(a_entry.date);
+    let a_usage = MealEntryRepository::get_weekly_template_usage(&sync.a, template_id, &week)
+        .await
+        .unwrap()
+        .unwrap();
+    let b_template_id = id_of(
+        &sync.b,
+        "meal_templates",
+        &uuid_of(&sync.a, "meal_templates", template_id).await,
+    )
+    .await
+    .unwrap();
+    let b_usage = MealEntryRepository::get_weekly_template_usage(&sync.b, b_template_id, &week)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(a_usage.usage_count, b_usage.usage_count);
+
+    for pool in [&sync.a, &sync.b] {
+        assert_eq!(
+            SyncRepository::get_status(pool)
+                .await
+                .unwrap()
+                .pending_changes,
+            0
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_deletes_propagate() {
+    let sync = TestSync::start().await;
+    let (_, _, entry_id) = create_breakfast(&sync.a).await;
+    sync.sync_all().await;
+
+    let entry_uuid = uuid_of(&sync.a, "meal_entries", entry_id).await;
+    assert!(id_of(&sync.b, "meal_entries", &entry_uuid).await.is_some());
+
+    MealEntryRepository::delete(&sync.a, entry_id)
+        .await
+        .unwrap();
+    sync.sync_all().await;
+    assert!(id_of(&sync.b, "meal_entries", &entry_uuid).await.is_none());
+}
+
+#[tokio::test]
+async fn test_concurrent_edits_converge() {
+    let sync = TestSync::start().await;
+    let (template_id, _, _) = create_breakfast(&sync.a).await;
+    sync.sync_all().await;
+
+    let uuid = uuid_of(&sync.a, "meal_templates", template_id).await;
+    let b_template_id = id_of(&sync.b, "meal_templates", &uuid).await.unwrap();
+
+    // Both edit offline, then sync in turn
+    MealTemplateRepository::update(&sync.a, template_id, rename("Yogurt bowl"))
+        .await
+        .unwrap();
+    MealTemplateRepository::update(&sync.b, b_template_id, rename("Yogurt cup"))
+        .await
+        .unwrap();
+    sync.sync_all().await;
+    sync.sync(&sync.b).await;
+
+    let a_name = MealTemplateRepository::get_by_id(&sync.a, template_id)
+        .await
+        .unwrap()
+        .unwrap()
+        .name;
+    let b_name = MealTemplateRepository::get_by_id(&sync.b, b_template_id)
+        .await
+        .unwrap()
+        .unwrap()
+        .name;
+    assert_eq!(a_name, b_name);
+    assert!(a_name == "Yogurt bowl" || a_name == "Yogurt cup");
+}
+
+#[tokio::test]
+async fn test_same_tag_on_both_devices() {
+    let sync = TestSync::start().await;
+    for pool in [&sync.a, &sync.b] {
+        TagRepository::create(
+            pool,
+            CreateTag {
+                name: "oats".to_string(),
+                display_name: "Oats".to_string(),
+                category: TagCategory::Ingredient,
+                weekly_suggestion: None,
+                parent_tag_id: None,
+            },
+        )
+        .await
+        .unwrap();
+    }
+    sync.sync_all().await;
+
+    let tags = |pool: SqlitePool| async move {
+        let tags: Vec<(String, String)> =
+            sqlx::query_as("SELECT uuid, name FROM tags WHERE display_name = 'Oats' ORDER BY uuid")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        tags
+    };
+    let a_tags = tags(sync.a.clone()).await;
+    assert_eq!(a_tags.len(), 2);
+    assert_eq!(a_tags, tags(sync.b.clone()).await);
+    assert_eq!(a_tags[0].1, "oats");
+    assert!(a_tags[1].1.starts_with("oats_"));
+}
+
+#[tokio::test]
+async fn test_server_requires_token() {
+    let sync = TestSync::start().await;
+    let client = SyncClient::new(&sync.url, "wrong-token-0123456789");
+
+    let result = SyncService::sync(&sync.a, &client).await;
+    assert!(matches!(result, Err(ApiError::Unauthorized(_))));
+}
+
+#[tokio::test]
+async fn test_server_rejects_changes_of_other_devices() {
+    let sync = TestSync::start().await;
+
+    let result = sync
+        .client()
+        .push(&SyncPush {
+            device_id: "device-a".to_string(),
+            changes: vec![SyncChange {
+                entity: SyncEntity::Tag,
+                key: "0b5c8a3e-0000-4000-8000-000000000000".to_string(),
+                clock: 1,
+                device_id: "device-b".to_string(),
+                data: None,
+            }],
+        })
+        .await;
+    assert!(matches!(result, Err(ApiError::ValidationError(_))));
+}
@@ -1,4 +1,6 @@
-import { useState } from "react";
+import { useEffect, useState } from "react";
+import { getStorageBackend } from "./lib/api";
+import { StorageBackend } from "./lib/types";
 import { DailyView } from "./views/DailyView";
 import { TemplatesView } from "./views/TemplatesView";
 
@@ -6,6 +8,13 @@ type View = "daily" | "templates";
 
 function App() {
   const [currentView, setCurrentView] = useState<View>("daily");
+  const [backend, setBackend] = useState<StorageBackend | null>(null);
+
+  useEffect(() => {
+    getStorageBackend()
+      .then(setBackend)
+      .catch((err) => console.error("Failed to load storage backend:", err));
+  }, []);
 
   return (
     <div>
@@ -35,6 +44,18 @@ function App() {
         </div>
       </nav>
 
+      {/* Features the PostgreSQL backend refuses */}
+      {backend && backend.unsupported_commands.length > 0 && (
+        <div className="bg-yellow-50 border-b border-yellow-200 text-yellow-800 text-sm p-3">
+          <div className="max-w-7xl mx-auto">
+            Connected to a shared {backend.kind} database. Sync, undo, backups,
+            export/import, planning and summaries need the local SQLite
+            database and are not available. Unsupported commands:{" "}
+            {backend.unsupported_commands.join(", ")}
+          </div>
+        </div>
+      )}
+
       {/* View Content */}
       {currentView === "daily" && <DailyView />}
       {currentView === "templates" && <TemplatesView />}
@@ -1,4 +1,10 @@
-import { LocationType, MealEntry, MealOption, MealTemplate } from "../../lib/types";
+import {
+  EntryStatus,
+  LocationType,
+  MealEntry,
+  MealOption,
+  MealTemplate,
+} from "../../lib/types";
 
 interface MealCardProps {
   entry: MealEntry;
@@ -30,7 +36,8 @@ export function MealCard({ entry, option, template, onClick }: MealCardProps) {
         bg-white rounded-md border-2 border-gray-200 p-4
         transition-all hover:shadow-md
         ${onClick ? "cursor-pointer" : ""}
-        ${entry.completed ? "bg-green-50 border-green-300" : ""}
+        ${entry.status === EntryStatus.Eaten ? "bg-green-50 border-green-300" : ""}
+        ${entry.status === EntryStatus.Skipped ? "opacity-60" : ""}
       `}
       onClick={onClick}
     >
@@ -56,11 +63,19 @@ export function MealCard({ entry, option, template, onClick }: MealCardProps) {
           <span>{entry.servings} serving{entry.servings !== 1 ? "s" : ""}</span>
         </div>
 
-        {/* Completed Badge */}
-        {entry.completed && (
+        {/* Eaten Badge */}
+        {entry.status === EntryStatus.Eaten && (
           <div className="flex items-center gap-1 px-2 py-0.5 bg-green-100 text-green-800 rounded-full text-xs font-medium">
             <span>✓</span>
-            <span>Completed</span>
+            <span>Eaten</span>
+          </div>
+        )}
+
+        {/* Skipped Badge */}
+        {entry.status === EntryStatus.Skipped && (
+          <div className="flex items-center gap-1 px-2 py-0.5 bg-gray-100 text-gray-700 rounded-full text-xs font-medium">
+            <span>✗</span>
+            <span>Skipped</span>
           </div>
         )}
       </div>
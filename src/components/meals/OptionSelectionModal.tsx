@@ -3,6 +3,7 @@ import { useEffect, useState } from "react";
 import { createEntry, getWeeklyUsage } from "../../lib/api";
 import {
     CreateMealEntry,
+    EntryStatus,
     LocationType,
     MealOptionWithTags,
     MealTemplate,
@@ -145,7 +146,7 @@ export function OptionSelectionModal({
         location,
         servings,
         notes: notes.trim() || null,
-        completed: isPastDate, // Auto-complete if past date
+        status: isPastDate ? EntryStatus.Eaten : EntryStatus.Planned, // Past dates are logged as eaten
       };
 
       await createEntry(entry);
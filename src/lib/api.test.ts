@@ -2,6 +2,7 @@ import { invoke } from "@tauri-apps/api/core";
 import { beforeEach, describe, expect, it, vi } from "vitest";
 import * as api from "./api";
 import {
+    EntryStatus,
     LocationType,
     SlotType,
     TagCategory,
@@ -28,6 +29,7 @@ describe("Tag API", () => {
         category: TagCategory.Ingredient,
         weekly_suggestion: 3,
         parent_tag_id: null,
+        archived: false,
         created_at: "2024-01-01T00:00:00Z",
       },
     ];
@@ -48,6 +50,7 @@ describe("Tag API", () => {
       category: TagCategory.Ingredient,
       weekly_suggestion: 3,
       parent_tag_id: null,
+      archived: false,
       created_at: "2024-01-01T00:00:00Z",
     };
 
@@ -71,6 +74,7 @@ describe("Tag API", () => {
       id: 1,
       ...newTag,
       parent_tag_id: null,
+      archived: false,
       created_at: "2024-01-01T00:00:00Z",
     };
 
@@ -104,6 +108,7 @@ describe("Template API", () => {
         compatible_slots: [SlotType.Breakfast],
         location_type: LocationType.Home,
         weekly_limit: null,
+        archived: false,
         created_at: "2024-01-01T00:00:00Z",
         updated_at: "2024-01-01T00:00:00Z",
       },
@@ -126,6 +131,7 @@ describe("Template API", () => {
         compatible_slots: [SlotType.Lunch, SlotType.Dinner],
         location_type: LocationType.Any,
         weekly_limit: 2,
+        archived: false,
         created_at: "2024-01-01T00:00:00Z",
         updated_at: "2024-01-01T00:00:00Z",
       },
@@ -177,6 +183,8 @@ describe("Option API", () => {
         name: "Ricotta",
         description: "Fresh ricotta cheese",
         nutritional_notes: null,
+        option_group: null,
+        archived: false,
         created_at: "2024-01-01T00:00:00Z",
         updated_at: "2024-01-01T00:00:00Z",
       },
@@ -203,6 +211,8 @@ describe("Option API", () => {
     const createdOption: MealOption = {
       id: 1,
       ...newOption,
+      option_group: null,
+      archived: false,
       created_at: "2024-01-01T00:00:00Z",
       updated_at: "2024-01-01T00:00:00Z",
     };
@@ -253,7 +263,17 @@ describe("Entry API", () => {
         location: LocationType.Home,
         servings: 1.0,
         notes: null,
-        completed: true,
+        status: EntryStatus.Eaten,
+        option_name: "Oatmeal",
+        template_id: 1,
+        template_name: "Breakfast Bowl",
+        template_location_type: LocationType.Home,
+        tag_names: [],
+        options: [{ meal_option_id: 10, option_name: "Oatmeal", servings: 1.0 }],
+        planned_options: [
+          { meal_option_id: 10, option_name: "Oatmeal", servings: 1.0 },
+        ],
+        estimated_nutrients: null,
         created_at: "2024-01-15T08:00:00Z",
         updated_at: "2024-01-15T08:00:00Z",
       },
@@ -290,12 +310,22 @@ describe("Entry API", () => {
       location: LocationType.Office,
       servings: 1.5,
       notes: "Extra serving",
-      completed: false,
+      status: EntryStatus.Planned,
     };
 
     const createdEntry: MealEntry = {
       id: 1,
       ...newEntry,
+      option_name: "Salad",
+      template_id: 2,
+      template_name: "Lunch Salad",
+      template_location_type: LocationType.Office,
+      tag_names: [],
+      options: [{ meal_option_id: 10, option_name: "Salad", servings: 1.5 }],
+      planned_options: [
+        { meal_option_id: 10, option_name: "Salad", servings: 1.5 },
+      ],
+      estimated_nutrients: null,
       created_at: "2024-01-15T12:00:00Z",
       updated_at: "2024-01-15T12:00:00Z",
     };
@@ -345,3 +375,23 @@ describe("Entry API", () => {
     expect(result).toEqual(mockUsage);
   });
 });
+
+describe("Storage backend API", () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  it("should get the storage backend", async () => {
+    const mockBackend = {
+      kind: "postgres",
+      unsupported_commands: ["sync_now", "get_week_summary"],
+    };
+
+    vi.mocked(invoke).mockResolvedValue(mockBackend);
+
+    const result = await api.getStorageBackend();
+
+    expect(invoke).toHaveBeenCalledWith("get_storage_backend");
+    expect(result).toEqual(mockBackend);
+  });
+});
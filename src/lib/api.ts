@@ -6,13 +6,17 @@ import type {
     CreateMealEntry,
     CreateMealOption,
     CreateMealTemplate,
+    CreateOffPlanEntry,
     CreateTag,
+    EntryStatus,
     LocationType,
+    LogActualMeal,
     MealEntry,
     MealOption,
     MealOptionWithTags,
     MealTemplate,
     SlotType,
+    StorageBackend,
     Tag,
     TagCategory,
     UpdateMealEntry,
@@ -456,13 +460,13 @@ export async function getEntryByDateAndSlot(
 }
 
 /**
- * Get entries by completion status
+ * Get entries by status (planned, eaten or skipped)
  */
-export async function getEntriesByCompleted(
-  completed: boolean
+export async function getEntriesByStatus(
+  status: EntryStatus
 ): Promise<MealEntry[]> {
-  const result = await invoke<MealEntry[]>("get_entries_by_completed", {
-    completed,
+  const result = await invoke<MealEntry[]>("get_entries_by_status", {
+    status,
   });
   if (isApiError(result)) {
     throw new Error(result.message);
@@ -539,6 +543,19 @@ export async function createEntry(entry: CreateMealEntry): Promise<MealEntry> {
   return result;
 }
 
+/**
+ * Log a restaurant or ad-hoc meal that has no meal option
+ */
+export async function createOffPlanEntry(
+  entry: CreateOffPlanEntry
+): Promise<MealEntry> {
+  const result = await invoke<MealEntry>("create_off_plan_entry", { entry });
+  if (isApiError(result)) {
+    throw new Error(result.message);
+  }
+  return result;
+}
+
 /**
  * Update an existing meal entry
  */
@@ -553,6 +570,24 @@ export async function updateEntry(
   return result;
 }
 
+/**
+ * Log what was actually eaten for a planned entry
+ * The planned selection is kept, so swaps stay visible
+ */
+export async function logActual(
+  entryId: number,
+  actual: LogActualMeal
+): Promise<MealEntry> {
+  const result = await invoke<[MealEntry, unknown[]]>("log_actual", {
+    entryId,
+    actual,
+  });
+  if (isApiError(result)) {
+    throw new Error(result.message);
+  }
+  return result[0];
+}
+
 /**
  * Delete a meal entry
  */
@@ -577,4 +612,17 @@ export async function validateEntry(
   return result;
 }
 
+// ============================================================================
+// STORAGE BACKEND API
+// ============================================================================
 
+/**
+ * Get the database the app runs on and the commands it does not support
+ */
+export async function getStorageBackend(): Promise<StorageBackend> {
+  const result = await invoke<StorageBackend>("get_storage_backend");
+  if (isApiError(result)) {
+    throw new Error(result.message);
+  }
+  return result;
+}
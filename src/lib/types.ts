@@ -6,16 +6,21 @@
 // ============================================================================
 
 /**
- * The five fixed meal slots per day
+ * Name of a meal slot; slots live in the meal_slots table and users can add their own
  * Matches Rust: SlotType
  */
-export enum SlotType {
-  Breakfast = "breakfast",
-  MorningSnack = "morning_snack",
-  Lunch = "lunch",
-  AfternoonSnack = "afternoon_snack",
-  Dinner = "dinner",
-}
+export type SlotType = string;
+
+/**
+ * Names of the five seeded default slots
+ */
+export const SlotType = {
+  Breakfast: "breakfast",
+  MorningSnack: "morning_snack",
+  Lunch: "lunch",
+  AfternoonSnack: "afternoon_snack",
+  Dinner: "dinner",
+} as const;
 
 /**
  * Where a meal can be prepared/consumed
@@ -39,6 +44,16 @@ export enum TagCategory {
   Other = "other",
 }
 
+/**
+ * Whether a planned meal was eaten, skipped or is still pending
+ * Matches Rust: EntryStatus
+ */
+export enum EntryStatus {
+  Planned = "planned",
+  Eaten = "eaten",
+  Skipped = "skipped",
+}
+
 // ============================================================================
 // DOMAIN MODELS
 // ============================================================================
@@ -54,6 +69,7 @@ export interface Tag {
   category: TagCategory;
   weekly_suggestion: number | null; // Soft limit (e.g., 3 for "max 3x/week")
   parent_tag_id: number | null; // For hierarchies: pasta_integrale -> pasta
+  archived: boolean; // Hidden from listings; still counts in usage roll-ups
   created_at: string; // ISO 8601 datetime string
 }
 
@@ -69,6 +85,7 @@ export interface MealTemplate {
   compatible_slots: SlotType[]; // Which slots can this template fill
   location_type: LocationType; // Where this meal can be prepared
   weekly_limit: number | null; // Hard limit: max times per week (null = unlimited)
+  archived: boolean; // Hidden (with its options) from listings and suggestions
   created_at: string; // ISO 8601 datetime string
   updated_at: string; // ISO 8601 datetime string
 }
@@ -84,6 +101,8 @@ export interface MealOption {
   name: string;
   description: string | null;
   nutritional_notes: string | null;
+  option_group: string | null; // Option group of the template (null = ungrouped)
+  archived: boolean; // Hidden from listings and suggestions, kept for history
   created_at: string; // ISO 8601 datetime string
   updated_at: string; // ISO 8601 datetime string
 }
@@ -105,22 +124,51 @@ export interface MealOptionWithTags {
 
 /**
  * Meal Entry - Actual meal logging and planning
- * Tracks both planned meals (future) and logged meals (past/completed)
+ * Tracks both planned meals (future) and logged meals (past/eaten)
  * Matches Rust: MealEntry
  */
 export interface MealEntry {
   id: number;
-  meal_option_id: number;
+  meal_option_id: number | null; // null for off-plan entries
   date: string; // ISO 8601 date string (YYYY-MM-DD)
   slot_type: SlotType;
   location: LocationType;
   servings: number; // Default 1.0, nutrition plan uses strict serving sizes
   notes: string | null;
-  completed: boolean; // false = planned, true = consumed
+  status: EntryStatus;
+  // Snapshot of the option/template when the entry was logged
+  option_name: string; // Free-text description of off-plan entries
+  template_id: number | null; // null for off-plan entries
+  template_name: string;
+  template_location_type: LocationType;
+  tag_names: string[];
+  options: MealEntryOption[]; // Every selected option, main option first
+  planned_options: MealEntryOption[]; // Selection the entry was planned with
+  estimated_nutrients: NutrientProfile | null; // Per serving, off-plan entries only
   created_at: string; // ISO 8601 datetime string
   updated_at: string; // ISO 8601 datetime string
 }
 
+/**
+ * One option selected in a meal entry
+ * Matches Rust: MealEntryOption
+ */
+export interface MealEntryOption {
+  meal_option_id: number;
+  option_name: string; // Snapshot
+  servings: number;
+}
+
+/**
+ * Combination rule of a template: how many options to pick from one group
+ * Matches Rust: OptionGroup
+ */
+export interface OptionGroup {
+  name: string;
+  min_choices: number;
+  max_choices: number | null; // null = no upper bound
+}
+
 // ============================================================================
 // CREATE/UPDATE TYPES
 // ============================================================================
@@ -208,7 +256,17 @@ export interface CreateMealEntry {
   location: LocationType;
   servings?: number; // Defaults to 1.0 if not provided
   notes?: string | null;
-  completed?: boolean; // Defaults to false (planned)
+  status?: EntryStatus; // Defaults to planned
+  extra_options?: SelectedOption[]; // Other options of a composite meal
+}
+
+/**
+ * An option selected alongside the main one when creating an entry
+ * Matches Rust: SelectedOption
+ */
+export interface SelectedOption {
+  meal_option_id: number;
+  servings?: number; // Defaults to 1.0 if not provided
 }
 
 /**
@@ -219,7 +277,47 @@ export interface UpdateMealEntry {
   location?: LocationType;
   servings?: number;
   notes?: string | null;
-  completed?: boolean;
+  status?: EntryStatus;
+}
+
+/**
+ * What was actually eaten for a planned entry
+ * Matches Rust: LogActualMeal
+ */
+export interface LogActualMeal {
+  meal_option_id: number;
+  servings?: number; // Defaults to 1.0 if not provided
+  extra_options?: SelectedOption[];
+  location?: LocationType; // Keeps the planned location if not provided
+  notes?: string | null;
+}
+
+/**
+ * A restaurant or ad-hoc meal logged without a meal option
+ * Matches Rust: CreateOffPlanEntry
+ */
+export interface CreateOffPlanEntry {
+  date: string; // ISO 8601 date string (YYYY-MM-DD)
+  slot_type: SlotType;
+  location: LocationType;
+  description: string;
+  servings?: number; // Defaults to 1.0 if not provided
+  notes?: string | null;
+  status?: EntryStatus; // Defaults to Eaten if not provided
+  tag_ids?: number[]; // Estimated tags, counted toward weekly suggestions
+  estimated_nutrients?: NutrientProfile | null;
+}
+
+/**
+ * Nutrients of one serving
+ * Matches Rust: SetNutrientProfile
+ */
+export interface NutrientProfile {
+  kcal: number;
+  protein_g: number;
+  carbs_g: number;
+  fat_g: number;
+  fiber_g: number;
 }
 
 // ============================================================================
@@ -247,6 +345,20 @@ export interface WeeklyTagUsage {
   usage_count: number;
 }
 
+// ============================================================================
+// STORAGE BACKEND TYPES
+// ============================================================================
+
+/**
+ * Database the app runs on
+ * On PostgreSQL, the commands in `unsupported_commands` are refused
+ * (sync, undo, backups, export/import, planning and summaries)
+ */
+export interface StorageBackend {
+  kind: "sqlite" | "postgres";
+  unsupported_commands: string[];
+}
+
 // ============================================================================
 // API ERROR TYPES
 // ============================================================================